@@ -0,0 +1,247 @@
+use bevy::prelude::*;
+use bevy_asset_loader::prelude::*;
+use bevy_kira_audio::{AudioApp, AudioChannel, AudioControl, AudioSource};
+
+use crate::{
+    damage::{Health, KillEvent},
+    enemies::{BossPhaseChanged, Enemy},
+    level::LevelFinished,
+    player::{Player, PlayerId, PLAYER_HEALTH},
+    GameSettings, GlobalState,
+};
+
+pub const ANNOUNCER_INITIAL_VOLUME: f32 = 0.5;
+
+// Kills that are close enough in time
+// count as one multikill
+const ANNOUNCER_MULTIKILL_KILLS: u32 = 3;
+const ANNOUNCER_MULTIKILL_WINDOW_SECONDS: f32 = 1.5;
+const ANNOUNCER_LOW_HEALTH: i32 = PLAYER_HEALTH / 4;
+// Lines that wait longer than this are not relevant anymore
+const ANNOUNCER_LINE_LIFETIME_SECONDS: f32 = 3.0;
+
+/// Voice lines for game events. Lines are played
+/// one at a time, the most important first.
+pub struct AnnouncerPlugin;
+
+impl Plugin for AnnouncerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_collection_to_loading_state::<_, AnnouncerAssets>(GlobalState::AssetLoading);
+
+        app.add_audio_channel::<AnnouncerChannel>();
+        app.add_event::<Announcement>();
+        app.init_resource::<AnnouncerQueue>();
+
+        app.add_systems(
+            Update,
+            (
+                announce_level_cleared,
+                announce_multikill,
+                announce_low_health,
+                announce_boss_phase,
+                announcer_queue,
+                announcer_play,
+            )
+                .chain()
+                .run_if(in_state(GlobalState::InGame)),
+        );
+        app.add_systems(OnExit(GlobalState::InGame), announcer_clear);
+        app.add_systems(
+            Update,
+            announcer_update_volume.run_if(resource_changed::<GameSettings>()),
+        );
+    }
+}
+
+#[derive(AssetCollection, Resource)]
+pub struct AnnouncerAssets {
+    #[asset(path = "announcer/level_cleared.wav")]
+    pub level_cleared: Handle<AudioSource>,
+    #[asset(path = "announcer/multikill.wav")]
+    pub multikill: Handle<AudioSource>,
+    #[asset(path = "announcer/low_health.wav")]
+    pub low_health: Handle<AudioSource>,
+    #[asset(path = "announcer/boss_phase.wav")]
+    pub boss_phase: Handle<AudioSource>,
+}
+
+// Announcer has its own channel and volume,
+// so it can be turned down separately
+#[derive(Resource)]
+pub struct AnnouncerChannel;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnnouncerLine {
+    LevelCleared,
+    Multikill,
+    LowHealth,
+    BossPhase,
+}
+
+impl AnnouncerLine {
+    // Higher goes first
+    fn priority(&self) -> u32 {
+        match self {
+            AnnouncerLine::Multikill => 0,
+            AnnouncerLine::LevelCleared => 1,
+            AnnouncerLine::LowHealth => 2,
+            AnnouncerLine::BossPhase => 3,
+        }
+    }
+
+    fn sound(&self, assets: &AnnouncerAssets) -> Handle<AudioSource> {
+        match self {
+            AnnouncerLine::LevelCleared => assets.level_cleared.clone(),
+            AnnouncerLine::Multikill => assets.multikill.clone(),
+            AnnouncerLine::LowHealth => assets.low_health.clone(),
+            AnnouncerLine::BossPhase => assets.boss_phase.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Event)]
+pub struct Announcement {
+    pub line: AnnouncerLine,
+}
+
+// Lines waiting for the current one to finish
+// with the time they were announced
+#[derive(Default, Resource)]
+struct AnnouncerQueue {
+    lines: Vec<(AnnouncerLine, f32)>,
+}
+
+fn announce_level_cleared(
+    mut level_finished_events: EventReader<LevelFinished>,
+    mut announcements: EventWriter<Announcement>,
+) {
+    for _ in level_finished_events.read() {
+        announcements.send(Announcement {
+            line: AnnouncerLine::LevelCleared,
+        });
+    }
+}
+
+fn announce_multikill(
+    time: Res<Time>,
+    enemies: Query<(), With<Enemy>>,
+    mut kill_events: EventReader<KillEvent>,
+    mut announcements: EventWriter<Announcement>,
+    mut streak: Local<(u32, f32)>,
+) {
+    for kill_event in kill_events.read() {
+        if !enemies.contains(kill_event.entity) {
+            continue;
+        }
+        let (kills, last_kill) = &mut *streak;
+        if time.elapsed_seconds() - *last_kill < ANNOUNCER_MULTIKILL_WINDOW_SECONDS {
+            *kills += 1;
+        } else {
+            *kills = 1;
+        }
+        *last_kill = time.elapsed_seconds();
+
+        if *kills == ANNOUNCER_MULTIKILL_KILLS {
+            announcements.send(Announcement {
+                line: AnnouncerLine::Multikill,
+            });
+        }
+    }
+}
+
+fn announce_low_health(
+    players: Query<(&PlayerId, &Health), (With<Player>, Changed<Health>)>,
+    mut announcements: EventWriter<Announcement>,
+    mut was_low: Local<bool>,
+) {
+    let Some((_, health)) = players.iter().find(|(id, _)| id.is_local()) else {
+        return;
+    };
+
+    // announced once until the player heals
+    let is_low = health.health <= ANNOUNCER_LOW_HEALTH;
+    if is_low && !*was_low {
+        announcements.send(Announcement {
+            line: AnnouncerLine::LowHealth,
+        });
+    }
+    *was_low = is_low;
+}
+
+fn announce_boss_phase(
+    mut boss_phase_events: EventReader<BossPhaseChanged>,
+    mut announcements: EventWriter<Announcement>,
+) {
+    for _ in boss_phase_events.read() {
+        announcements.send(Announcement {
+            line: AnnouncerLine::BossPhase,
+        });
+    }
+}
+
+fn announcer_queue(
+    time: Res<Time<Real>>,
+    game_settings: Res<GameSettings>,
+    mut announcer_queue: ResMut<AnnouncerQueue>,
+    mut announcements: EventReader<Announcement>,
+) {
+    for announcement in announcements.read() {
+        if !game_settings.announcer {
+            continue;
+        }
+        // same line waiting twice is only played once
+        if announcer_queue
+            .lines
+            .iter()
+            .any(|(line, _)| *line == announcement.line)
+        {
+            continue;
+        }
+        announcer_queue
+            .lines
+            .push((announcement.line, time.elapsed_seconds()));
+    }
+
+    let now = time.elapsed_seconds();
+    announcer_queue
+        .lines
+        .retain(|(_, announced)| now - announced < ANNOUNCER_LINE_LIFETIME_SECONDS);
+}
+
+fn announcer_play(
+    announcer_assets: Res<AnnouncerAssets>,
+    announcer_channel: Res<AudioChannel<AnnouncerChannel>>,
+    mut announcer_queue: ResMut<AnnouncerQueue>,
+) {
+    if announcer_queue.lines.is_empty() || announcer_channel.is_playing_sound() {
+        return;
+    }
+
+    let Some(next) = announcer_queue
+        .lines
+        .iter()
+        .enumerate()
+        .max_by_key(|(_, (line, _))| line.priority())
+        .map(|(i, _)| i)
+    else {
+        return;
+    };
+    let (line, _) = announcer_queue.lines.remove(next);
+    announcer_channel.play(line.sound(announcer_assets.as_ref()));
+}
+
+fn announcer_clear(
+    announcer_channel: Res<AudioChannel<AnnouncerChannel>>,
+    mut announcer_queue: ResMut<AnnouncerQueue>,
+) {
+    announcer_queue.lines.clear();
+    announcer_channel.stop();
+}
+
+fn announcer_update_volume(
+    game_settings: Res<GameSettings>,
+    announcer_channel: Res<AudioChannel<AnnouncerChannel>>,
+) {
+    // announcer is quieter together with the rest of the game
+    announcer_channel.set_volume((game_settings.volume * game_settings.announcer_volume) as f64);
+}
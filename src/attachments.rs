@@ -0,0 +1,322 @@
+use bevy::{pbr::NotShadowCaster, prelude::*};
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    new_game_plus::NewGamePlus,
+    player::{Player, PlayerCamera, PlayerId, PlayerWeapon},
+    weapons::Ammo,
+    GlobalState,
+};
+
+const LASER_KEY: KeyCode = KeyCode::L;
+const LASER_MAX_DISTANCE: f32 = 300.0;
+const LASER_DOT_SIZE: f32 = 0.1;
+const LASER_DOT_COLOR: Color = Color::RED;
+
+const FLASHLIGHT_KEY: KeyCode = KeyCode::T;
+const FLASHLIGHT_INTENSITY: f32 = 20000.0;
+const FLASHLIGHT_RANGE: f32 = 80.0;
+const FLASHLIGHT_OUTER_ANGLE: f32 = std::f32::consts::FRAC_PI_6;
+const FLASHLIGHT_INNER_ANGLE: f32 = std::f32::consts::FRAC_PI_8;
+// Full battery lasts for 60 seconds and
+// recharges in 20 seconds
+const FLASHLIGHT_BATTERY_DRAIN: f32 = 1.0 / 60.0;
+const FLASHLIGHT_BATTERY_RECHARGE: f32 = 1.0 / 20.0;
+// Light starts to flicker when battery is low
+const FLASHLIGHT_BATTERY_LOW: f32 = 0.2;
+const FLASHLIGHT_FLICKER_SPEED: f32 = 30.0;
+
+// Extended mag adds this part of the ammo
+// to each weapon the player picks up
+const EXTENDED_MAG_AMMO_BONUS: f32 = 0.5;
+
+pub struct AttachmentsPlugin;
+
+impl Plugin for AttachmentsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Attachments>();
+
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::AssetLoading,
+                to: GlobalState::MainMenu,
+            },
+            spawn_laser_dot,
+        );
+        app.add_systems(OnExit(GlobalState::InGame), hide_laser_dot);
+
+        // crafted attachments last for one run
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::MainMenu,
+                to: GlobalState::InGame,
+            },
+            reset_crafted_attachments,
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::GameOver,
+                to: GlobalState::InGame,
+            },
+            reset_crafted_attachments,
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::GameWon,
+                to: GlobalState::InGame,
+            },
+            reset_crafted_attachments,
+        );
+
+        app.add_systems(
+            Update,
+            (
+                attachments_toggle,
+                spawn_flashlight,
+                update_laser_dot,
+                update_flashlight,
+                apply_extended_mag,
+            )
+                .run_if(in_state(GlobalState::InGame)),
+        );
+    }
+}
+
+/// Attachments crafted at a crafting bench
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WeaponAttachment {
+    ExtendedMag,
+    LaserSight,
+    // Loads incendiary rounds into the current weapon
+    IncendiaryConverter,
+}
+
+impl WeaponAttachment {
+    pub fn name(&self) -> &'static str {
+        match self {
+            WeaponAttachment::ExtendedMag => "extended mag",
+            WeaponAttachment::LaserSight => "laser sight",
+            WeaponAttachment::IncendiaryConverter => "incendiary converter",
+        }
+    }
+}
+
+#[derive(Debug, Resource)]
+pub struct Attachments {
+    pub laser: bool,
+    pub flashlight: bool,
+    pub battery: f32,
+    // Crafted during the run
+    pub laser_sight: bool,
+    pub extended_mag: bool,
+}
+
+impl Default for Attachments {
+    fn default() -> Self {
+        Self {
+            laser: false,
+            flashlight: false,
+            battery: 1.0,
+            laser_sight: false,
+            extended_mag: false,
+        }
+    }
+}
+
+impl Attachments {
+    /// Attachments that stay until the end of the run
+    /// can not be crafted twice
+    pub fn installed(&self, attachment: WeaponAttachment) -> bool {
+        match attachment {
+            WeaponAttachment::ExtendedMag => self.extended_mag,
+            WeaponAttachment::LaserSight => self.laser_sight,
+            WeaponAttachment::IncendiaryConverter => false,
+        }
+    }
+}
+
+// Weapon already got the extended mag ammo
+#[derive(Component)]
+struct ExtendedMag;
+
+#[derive(Component)]
+struct LaserDot;
+
+#[derive(Component)]
+struct Flashlight;
+
+fn spawn_laser_dot(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.spawn((
+        PbrBundle {
+            mesh: meshes.add(
+                shape::UVSphere {
+                    radius: LASER_DOT_SIZE,
+                    ..default()
+                }
+                .into(),
+            ),
+            material: materials.add(StandardMaterial {
+                base_color: LASER_DOT_COLOR,
+                emissive: LASER_DOT_COLOR,
+                unlit: true,
+                ..default()
+            }),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        NotShadowCaster,
+        LaserDot,
+    ));
+}
+
+fn hide_laser_dot(mut laser_dot: Query<&mut Visibility, With<LaserDot>>) {
+    let Ok(mut visibility) = laser_dot.get_single_mut() else {
+        return;
+    };
+
+    *visibility = Visibility::Hidden;
+}
+
+// Flashlight is attached to the camera, so it
+// is spawned again every time player is spawned
+fn spawn_flashlight(player_camera: Query<Entity, Added<PlayerCamera>>, mut commands: Commands) {
+    for camera in player_camera.iter() {
+        commands.entity(camera).with_children(|builder| {
+            builder.spawn((
+                SpotLightBundle {
+                    spot_light: SpotLight {
+                        intensity: FLASHLIGHT_INTENSITY,
+                        range: FLASHLIGHT_RANGE,
+                        outer_angle: FLASHLIGHT_OUTER_ANGLE,
+                        inner_angle: FLASHLIGHT_INNER_ANGLE,
+                        shadows_enabled: true,
+                        ..default()
+                    },
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                Flashlight,
+            ));
+        });
+    }
+}
+
+// New Game+ keeps attachments of the carried weapon
+fn reset_crafted_attachments(
+    new_game_plus: Res<NewGamePlus>,
+    mut attachments: ResMut<Attachments>,
+) {
+    let carried = |attachment| new_game_plus.attachments.contains(&attachment);
+    attachments.laser = false;
+    attachments.laser_sight = carried(WeaponAttachment::LaserSight);
+    attachments.extended_mag = carried(WeaponAttachment::ExtendedMag);
+}
+
+fn attachments_toggle(keys: Res<Input<KeyCode>>, mut attachments: ResMut<Attachments>) {
+    if keys.just_pressed(LASER_KEY) && attachments.laser_sight {
+        attachments.laser = !attachments.laser;
+    }
+    if keys.just_pressed(FLASHLIGHT_KEY) {
+        attachments.flashlight = !attachments.flashlight;
+    }
+}
+
+fn update_laser_dot(
+    attachments: Res<Attachments>,
+    rapier_context: Res<RapierContext>,
+    players: Query<(Entity, &PlayerId), With<Player>>,
+    player_weapon: Query<(), With<PlayerWeapon>>,
+    player_cameras: Query<(&PlayerId, &GlobalTransform), With<PlayerCamera>>,
+    mut laser_dot: Query<(&mut Transform, &mut Visibility), With<LaserDot>>,
+) {
+    let Ok((mut dot_transform, mut dot_visibility)) = laser_dot.get_single_mut() else {
+        return;
+    };
+
+    // laser is attached to the weapon
+    if !attachments.laser || player_weapon.is_empty() {
+        *dot_visibility = Visibility::Hidden;
+        return;
+    }
+
+    // laser dot is only shown on the local screen
+    let Some((player, _)) = players.iter().find(|(_, id)| id.is_local()) else {
+        return;
+    };
+    let Some((_, camera_global_transform)) = player_cameras.iter().find(|(id, _)| id.is_local())
+    else {
+        return;
+    };
+
+    let ray_origin = camera_global_transform.translation();
+    let ray_dir = camera_global_transform.forward();
+    let solid = true;
+    let filter = QueryFilter {
+        flags: QueryFilterFlags::EXCLUDE_SENSORS,
+        exclude_collider: Some(player),
+        ..default()
+    };
+    match rapier_context.cast_ray(ray_origin, ray_dir, LASER_MAX_DISTANCE, solid, filter) {
+        Some((_, toi)) => {
+            dot_transform.translation = ray_origin + ray_dir * toi;
+            *dot_visibility = Visibility::Visible;
+        }
+        None => *dot_visibility = Visibility::Hidden,
+    }
+}
+
+fn update_flashlight(
+    time: Res<Time>,
+    mut attachments: ResMut<Attachments>,
+    mut flashlights: Query<(&mut SpotLight, &mut Visibility), With<Flashlight>>,
+) {
+    if flashlights.is_empty() {
+        return;
+    }
+
+    if attachments.flashlight {
+        attachments.battery -= FLASHLIGHT_BATTERY_DRAIN * time.delta_seconds();
+        if attachments.battery <= 0.0 {
+            attachments.battery = 0.0;
+            attachments.flashlight = false;
+        }
+    } else {
+        attachments.battery =
+            (attachments.battery + FLASHLIGHT_BATTERY_RECHARGE * time.delta_seconds()).min(1.0);
+    }
+
+    // every player camera carries a flashlight
+    for (mut spot_light, mut visibility) in flashlights.iter_mut() {
+        if !attachments.flashlight {
+            *visibility = Visibility::Hidden;
+            continue;
+        }
+
+        *visibility = Visibility::Visible;
+        spot_light.intensity = if attachments.battery < FLASHLIGHT_BATTERY_LOW {
+            let flicker = (time.elapsed_seconds() * FLASHLIGHT_FLICKER_SPEED).sin();
+            FLASHLIGHT_INTENSITY * (0.6 + 0.4 * flicker)
+        } else {
+            FLASHLIGHT_INTENSITY
+        };
+    }
+}
+
+fn apply_extended_mag(
+    attachments: Res<Attachments>,
+    mut player_weapons: Query<(Entity, &mut Ammo), (With<PlayerWeapon>, Without<ExtendedMag>)>,
+    mut commands: Commands,
+) {
+    if !attachments.extended_mag {
+        return;
+    }
+    for (weapon, mut ammo) in player_weapons.iter_mut() {
+        ammo.ammo += (ammo.ammo as f32 * EXTENDED_MAG_AMMO_BONUS) as u32;
+        commands.entity(weapon).insert(ExtendedMag);
+    }
+}
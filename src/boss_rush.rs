@@ -0,0 +1,140 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    level::grade::RunBonus,
+    profile::{load_ron, save_ron, Profile},
+    run_modifiers::RunModifiers,
+    scrap::Scrap,
+    GlobalState,
+};
+
+const BOSS_RUSH_LEADERBOARD_PATH: &str = "boss_rush.ron";
+const BOSS_RUSH_LEADERBOARD_ENTRIES: usize = 10;
+
+const BOSS_RUSH_SCORE_PER_ARENA: u32 = 500;
+
+/// Chain of boss arenas with shop breaks between
+/// them. Unlocked after the game is won once.
+pub struct BossRushPlugin;
+
+impl Plugin for BossRushPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BossRush>();
+        app.insert_resource(load_ron::<BossRushLeaderboard>(BOSS_RUSH_LEADERBOARD_PATH));
+
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::InGame,
+                to: GlobalState::GameOver,
+            },
+            record_boss_rush_result.run_if(boss_rush_active),
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::GameOver,
+                to: GlobalState::InGame,
+            },
+            restart_boss_rush,
+        );
+
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::Paused,
+                to: GlobalState::MainMenu,
+            },
+            reset_boss_rush,
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::GameOver,
+                to: GlobalState::MainMenu,
+            },
+            reset_boss_rush,
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::Hub,
+                to: GlobalState::MainMenu,
+            },
+            reset_boss_rush,
+        );
+    }
+}
+
+// Run never ends with a win, only
+// with the death of the player
+#[derive(Debug, Default, Resource)]
+pub struct BossRush {
+    pub active: bool,
+    pub arenas_cleared: u32,
+    pub last_score: u32,
+}
+
+impl BossRush {
+    pub fn start(&mut self) {
+        *self = Self {
+            active: true,
+            ..default()
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct BossRushEntry {
+    pub score: u32,
+    pub arenas: u32,
+}
+
+#[derive(Debug, Default, Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BossRushLeaderboard {
+    pub entries: Vec<BossRushEntry>,
+}
+
+impl BossRushLeaderboard {
+    pub fn best(&self) -> Option<u32> {
+        self.entries.first().map(|entry| entry.score)
+    }
+
+    fn add(&mut self, entry: BossRushEntry) {
+        self.entries.push(entry);
+        self.entries.sort_by(|a, b| b.score.cmp(&a.score));
+        self.entries.truncate(BOSS_RUSH_LEADERBOARD_ENTRIES);
+    }
+}
+
+pub fn boss_rush_unlocked(profile: &Profile) -> bool {
+    0 < profile.boss_kills
+}
+
+pub fn boss_rush_active(boss_rush: Res<BossRush>) -> bool {
+    boss_rush.active
+}
+
+fn reset_boss_rush(mut boss_rush: ResMut<BossRush>) {
+    *boss_rush = BossRush::default();
+}
+
+fn restart_boss_rush(mut boss_rush: ResMut<BossRush>) {
+    boss_rush.arenas_cleared = 0;
+}
+
+fn record_boss_rush_result(
+    scrap: Res<Scrap>,
+    run_bonus: Res<RunBonus>,
+    run_modifiers: Res<RunModifiers>,
+    mut boss_rush: ResMut<BossRush>,
+    mut leaderboard: ResMut<BossRushLeaderboard>,
+) {
+    let score =
+        boss_rush.arenas_cleared * BOSS_RUSH_SCORE_PER_ARENA + scrap.amount + run_bonus.score;
+    let score = run_modifiers.apply_score_multiplier(score);
+
+    boss_rush.last_score = score;
+    leaderboard.add(BossRushEntry {
+        score,
+        arenas: boss_rush.arenas_cleared,
+    });
+    save_ron(BOSS_RUSH_LEADERBOARD_PATH, leaderboard.as_ref());
+}
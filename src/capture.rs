@@ -0,0 +1,188 @@
+use std::{
+    collections::VecDeque,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::{
+    input::mouse::MouseMotion, prelude::*, render::view::screenshot::ScreenshotManager,
+    window::PrimaryWindow,
+};
+use serde::Serialize;
+
+use crate::{profile::save_ron, rng::GameRng, GlobalState};
+
+const CAPTURE_SCREENSHOT_KEY: KeyCode = KeyCode::F12;
+const CAPTURE_SCREENSHOTS_DIR: &str = "screenshots";
+
+const CAPTURE_CLIP_KEY: KeyCode = KeyCode::F9;
+const CAPTURE_CLIPS_DIR: &str = "clips";
+const CAPTURE_CLIP_SECONDS: f32 = 10.0;
+
+/// Screenshots and short clips for sharing. Clip
+/// is the input of the last seconds of the run
+/// together with the run seed. It has no simulation
+/// state, so it can not be replayed, only read.
+pub struct CapturePlugin;
+
+impl Plugin for CapturePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CaptureSaved>();
+
+        app.init_resource::<CaptureClip>();
+
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::MainMenu,
+                to: GlobalState::InGame,
+            },
+            clear_capture_clip,
+        );
+
+        // Captures are saved to the file system
+        // which is not available on web
+        if cfg!(target_arch = "wasm32") {
+            return;
+        }
+        app.add_systems(
+            Update,
+            capture_record_clip.run_if(in_state(GlobalState::InGame)),
+        );
+        app.add_systems(Update, (capture_screenshot, capture_export_clip));
+    }
+}
+
+/// Screenshot or clip is saved, text
+/// is shown to the player
+#[derive(Event)]
+pub struct CaptureSaved {
+    pub text: String,
+}
+
+// Input of one frame, keys and buttons are
+// stored by name to stay readable
+#[derive(Debug, Serialize)]
+struct CaptureFrame {
+    // Seconds since the game start
+    time: f32,
+    keys: Vec<String>,
+    mouse_buttons: Vec<String>,
+    mouse_motion: (f32, f32),
+}
+
+#[derive(Debug, Default, Resource)]
+struct CaptureClip {
+    frames: VecDeque<CaptureFrame>,
+}
+
+#[derive(Debug, Serialize)]
+struct CaptureClipFile<'a> {
+    seed: u64,
+    frames: &'a VecDeque<CaptureFrame>,
+}
+
+fn capture_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn clear_capture_clip(mut capture_clip: ResMut<CaptureClip>) {
+    capture_clip.frames.clear();
+}
+
+fn capture_screenshot(
+    keys: Res<Input<KeyCode>>,
+    primary_window: Query<Entity, With<PrimaryWindow>>,
+    mut screenshot_manager: ResMut<ScreenshotManager>,
+    mut capture_saved_events: EventWriter<CaptureSaved>,
+) {
+    if !keys.just_pressed(CAPTURE_SCREENSHOT_KEY) {
+        return;
+    }
+    let Ok(window) = primary_window.get_single() else {
+        return;
+    };
+
+    if let Err(e) = std::fs::create_dir_all(CAPTURE_SCREENSHOTS_DIR) {
+        warn!("Could not create {CAPTURE_SCREENSHOTS_DIR}: {e}");
+        return;
+    }
+    let path = format!(
+        "{CAPTURE_SCREENSHOTS_DIR}/screenshot_{}.png",
+        capture_timestamp()
+    );
+    match screenshot_manager.save_screenshot_to_disk(window, &path) {
+        Ok(_) => {
+            info!("Screenshot saved to {path}");
+            capture_saved_events.send(CaptureSaved {
+                text: "SCREENSHOT SAVED".to_string(),
+            });
+        }
+        Err(e) => warn!("Could not take screenshot {path}: {e}"),
+    }
+}
+
+fn capture_record_clip(
+    time: Res<Time>,
+    keys: Res<Input<KeyCode>>,
+    mouse: Res<Input<MouseButton>>,
+    mut capture_clip: ResMut<CaptureClip>,
+    mut mouse_motion_events: EventReader<MouseMotion>,
+) {
+    let now = time.elapsed_seconds();
+    while capture_clip
+        .frames
+        .front()
+        .is_some_and(|frame| frame.time < now - CAPTURE_CLIP_SECONDS)
+    {
+        capture_clip.frames.pop_front();
+    }
+
+    let mouse_motion = mouse_motion_events
+        .read()
+        .fold(Vec2::ZERO, |motion, event| motion + event.delta);
+    capture_clip.frames.push_back(CaptureFrame {
+        time: now,
+        keys: keys.get_pressed().map(|key| format!("{key:?}")).collect(),
+        mouse_buttons: mouse
+            .get_pressed()
+            .map(|button| format!("{button:?}"))
+            .collect(),
+        mouse_motion: (mouse_motion.x, mouse_motion.y),
+    });
+}
+
+fn capture_export_clip(
+    keys: Res<Input<KeyCode>>,
+    game_rng: Res<GameRng>,
+    capture_clip: Res<CaptureClip>,
+    mut capture_saved_events: EventWriter<CaptureSaved>,
+) {
+    if !keys.just_pressed(CAPTURE_CLIP_KEY) {
+        return;
+    }
+    if capture_clip.frames.is_empty() {
+        return;
+    }
+
+    if let Err(e) = std::fs::create_dir_all(CAPTURE_CLIPS_DIR) {
+        warn!("Could not create {CAPTURE_CLIPS_DIR}: {e}");
+        return;
+    }
+    let path = format!("{CAPTURE_CLIPS_DIR}/clip_{}.ron", capture_timestamp());
+    let saved = save_ron(
+        &path,
+        &CaptureClipFile {
+            seed: game_rng.seed(),
+            frames: &capture_clip.frames,
+        },
+    );
+    if !saved {
+        return;
+    }
+    info!("Input clip saved to {path}");
+    capture_saved_events.send(CaptureSaved {
+        text: "INPUT CLIP SAVED".to_string(),
+    });
+}
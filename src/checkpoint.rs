@@ -0,0 +1,188 @@
+use bevy::prelude::*;
+
+use crate::{
+    damage::{apply_damage, Health},
+    level::{
+        ladder::{PlayerClimb, PlayerOnLoft},
+        LevelInfo, LevelStarted,
+    },
+    player::{player_kills_reading, Player, PlayerId, PlayerVelocity, PlayerWeapon},
+    weapons::Ammo,
+    GlobalState,
+};
+
+const CHECKPOINT_SPARE_FUSES: u32 = 1;
+const CHECKPOINT_BOSS_LEVEL_PROGRESS: i32 = 100;
+
+pub struct CheckpointPlugin;
+
+impl Plugin for CheckpointPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Checkpoint>();
+
+        app.add_event::<CheckpointReached>();
+        app.add_event::<CheckpointRespawn>();
+
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::MainMenu,
+                to: GlobalState::InGame,
+            },
+            reset_checkpoint,
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::GameOver,
+                to: GlobalState::InGame,
+            },
+            reset_checkpoint,
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::GameWon,
+                to: GlobalState::InGame,
+            },
+            reset_checkpoint,
+        );
+
+        // `Health` removed by `apply_damage` on the player death has
+        // to be applied before the respawn inserts it back
+        app.add_systems(
+            Update,
+            (
+                checkpoint_trigger,
+                (apply_deferred, checkpoint_respawn)
+                    .chain()
+                    .after(apply_damage)
+                    .after(player_kills_reading),
+            )
+                .run_if(in_state(GlobalState::InGame)),
+        );
+    }
+}
+
+#[derive(Event)]
+pub struct CheckpointReached;
+
+// Sent instead of game over if the dead player
+// has a checkpoint and there is a spare fuse
+#[derive(Event)]
+pub struct CheckpointRespawn(pub PlayerId);
+
+#[derive(Debug, Clone, Copy)]
+pub struct PlayerSnapshot {
+    pub player_id: PlayerId,
+    pub transform: Transform,
+    pub health: i32,
+    pub ammo: Option<u32>,
+}
+
+// Only the boss level has a checkpoint. Each player
+// has a snapshot, and each respawn at the checkpoint
+// consumes a spare fuse shared by all players.
+#[derive(Debug, Resource)]
+pub struct Checkpoint {
+    pub spare_fuses: u32,
+    pub snapshots: Vec<PlayerSnapshot>,
+}
+
+impl Default for Checkpoint {
+    fn default() -> Self {
+        Self {
+            spare_fuses: CHECKPOINT_SPARE_FUSES,
+            snapshots: Vec::new(),
+        }
+    }
+}
+
+impl Checkpoint {
+    pub fn snapshot(&self, player_id: PlayerId) -> Option<PlayerSnapshot> {
+        self.snapshots
+            .iter()
+            .find(|snapshot| snapshot.player_id == player_id)
+            .copied()
+    }
+
+    pub fn can_respawn(&self, player_id: PlayerId) -> bool {
+        self.snapshot(player_id).is_some() && 0 < self.spare_fuses
+    }
+}
+
+fn reset_checkpoint(mut checkpoint: ResMut<Checkpoint>) {
+    *checkpoint = Checkpoint::default();
+}
+
+fn checkpoint_trigger(
+    level_info: Res<LevelInfo>,
+    players: Query<(&PlayerId, &Transform, &Health), With<Player>>,
+    player_ammos: Query<(&PlayerId, &Ammo), With<PlayerWeapon>>,
+    mut checkpoint: ResMut<Checkpoint>,
+    mut level_started_events: EventReader<LevelStarted>,
+    mut checkpoint_reached_events: EventWriter<CheckpointReached>,
+) {
+    for _ in level_started_events.read() {
+        if level_info.game_progress != CHECKPOINT_BOSS_LEVEL_PROGRESS {
+            continue;
+        }
+        checkpoint.snapshots = players
+            .iter()
+            .map(|(player_id, transform, health)| PlayerSnapshot {
+                player_id: *player_id,
+                transform: *transform,
+                health: health.health,
+                ammo: player_ammos
+                    .iter()
+                    .find(|(id, _)| *id == player_id)
+                    .map(|(_, ammo)| ammo.ammo),
+            })
+            .collect();
+        if !checkpoint.snapshots.is_empty() {
+            checkpoint_reached_events.send(CheckpointReached);
+        }
+    }
+}
+
+fn checkpoint_respawn(
+    mut players: Query<(Entity, &PlayerId, &mut Transform, &mut PlayerVelocity), With<Player>>,
+    mut player_ammos: Query<(&PlayerId, &mut Ammo), With<PlayerWeapon>>,
+    mut checkpoint: ResMut<Checkpoint>,
+    mut commands: Commands,
+    mut checkpoint_respawn_events: EventReader<CheckpointRespawn>,
+) {
+    // several deaths of one player in one frame only use one fuse
+    let mut respawned = Vec::new();
+    for CheckpointRespawn(player_id) in checkpoint_respawn_events.read() {
+        if respawned.contains(player_id) || !checkpoint.can_respawn(*player_id) {
+            continue;
+        }
+        let Some(snapshot) = checkpoint.snapshot(*player_id) else {
+            continue;
+        };
+        let Some((player, _, mut transform, mut velocity)) =
+            players.iter_mut().find(|(_, id, _, _)| *id == player_id)
+        else {
+            continue;
+        };
+        respawned.push(*player_id);
+
+        checkpoint.spare_fuses -= 1;
+
+        *transform = snapshot.transform;
+        velocity.velocity = Vec3::ZERO;
+        velocity.external = Vec3::ZERO;
+        // `Health` is removed from the player on death
+        // and the player is taken off the ladder or the loft
+        commands
+            .entity(player)
+            .insert(Health {
+                health: snapshot.health,
+            })
+            .remove::<(PlayerClimb, PlayerOnLoft)>();
+        if let (Some(ammo), Some((_, mut player_ammo))) = (
+            snapshot.ammo,
+            player_ammos.iter_mut().find(|(id, _)| *id == player_id),
+        ) {
+            player_ammo.ammo = ammo;
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use bevy_rapier3d::prelude::*;
+
+pub const COLLISION_GROUP_LEVEL: Group = Group::GROUP_1;
+pub const COLLISION_GROUP_PLAYER: Group = Group::GROUP_2;
+pub const COLLISION_GROUP_ENEMY: Group = Group::GROUP_3;
+pub const COLLISION_GROUP_PROJECTILES: Group = Group::GROUP_4;
+pub const COLLISION_GROUP_PICKUP: Group = Group::GROUP_5;
+pub const COLLISION_GROUP_ENEMY_PROJECTILES: Group = Group::GROUP_6;
+pub const COLLISION_GROUP_DEBRIS: Group = Group::GROUP_7;
+pub const COLLISION_GROUP_ENEMY_AURA: Group = Group::GROUP_8;
+
+/// What the collider is. Every collider in the game uses
+/// one of these, so all pair rules live in one place.
+///
+/// Two colliders interact only if each one is in the
+/// filter of the other, so every rule is in both filters:
+/// - level blocks everything except pickups
+/// - enemies push each other apart
+/// - player and enemy projectiles hit the level,
+///   enemies and each other
+/// - enemy projectiles also hit the player
+/// - pickups only sense the player
+/// - shells, scrap and other debris only land on the level
+///   and on moving platforms
+/// - enemy shields only block player projectiles
+/// - kill volumes sense the player and enemies
+/// - enemy auras only sense the player
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollisionLayer {
+    Level,
+    // Triggers in the level, e.g. door sensors
+    LevelSensor,
+    Player,
+    Enemy,
+    EnemyShield,
+    Projectile,
+    EnemyProjectile,
+    Pickup,
+    Debris,
+    // Moving platforms, everything except debris
+    // is carried by them without touching them
+    MovingFloor,
+    KillVolume,
+    EnemyAura,
+}
+
+impl CollisionLayer {
+    pub fn groups(self) -> CollisionGroups {
+        match self {
+            CollisionLayer::Level => CollisionGroups::new(
+                COLLISION_GROUP_LEVEL,
+                COLLISION_GROUP_PLAYER
+                    | COLLISION_GROUP_ENEMY
+                    | COLLISION_GROUP_PROJECTILES
+                    | COLLISION_GROUP_ENEMY_PROJECTILES
+                    | COLLISION_GROUP_DEBRIS,
+            ),
+            CollisionLayer::LevelSensor => {
+                CollisionGroups::new(COLLISION_GROUP_LEVEL, COLLISION_GROUP_PLAYER)
+            }
+            CollisionLayer::Player => CollisionGroups::new(
+                COLLISION_GROUP_PLAYER,
+                COLLISION_GROUP_LEVEL
+                    | COLLISION_GROUP_ENEMY_PROJECTILES
+                    | COLLISION_GROUP_PICKUP
+                    | COLLISION_GROUP_ENEMY_AURA,
+            ),
+            CollisionLayer::Enemy => CollisionGroups::new(
+                COLLISION_GROUP_ENEMY,
+                COLLISION_GROUP_LEVEL
+                    | COLLISION_GROUP_ENEMY
+                    | COLLISION_GROUP_PROJECTILES
+                    | COLLISION_GROUP_ENEMY_PROJECTILES,
+            ),
+            CollisionLayer::EnemyShield => {
+                CollisionGroups::new(COLLISION_GROUP_ENEMY, COLLISION_GROUP_PROJECTILES)
+            }
+            CollisionLayer::Projectile => CollisionGroups::new(
+                COLLISION_GROUP_PROJECTILES,
+                COLLISION_GROUP_LEVEL | COLLISION_GROUP_ENEMY | COLLISION_GROUP_ENEMY_PROJECTILES,
+            ),
+            CollisionLayer::EnemyProjectile => CollisionGroups::new(
+                COLLISION_GROUP_ENEMY_PROJECTILES,
+                COLLISION_GROUP_LEVEL
+                    | COLLISION_GROUP_PLAYER
+                    | COLLISION_GROUP_ENEMY
+                    | COLLISION_GROUP_PROJECTILES,
+            ),
+            CollisionLayer::Pickup => {
+                CollisionGroups::new(COLLISION_GROUP_PICKUP, COLLISION_GROUP_PLAYER)
+            }
+            CollisionLayer::Debris => {
+                CollisionGroups::new(COLLISION_GROUP_DEBRIS, COLLISION_GROUP_LEVEL)
+            }
+            CollisionLayer::MovingFloor => {
+                CollisionGroups::new(COLLISION_GROUP_LEVEL, COLLISION_GROUP_DEBRIS)
+            }
+            CollisionLayer::KillVolume => CollisionGroups::new(
+                COLLISION_GROUP_LEVEL,
+                COLLISION_GROUP_PLAYER | COLLISION_GROUP_ENEMY,
+            ),
+            // own group, so level queries do not see
+            // the space around enemies as blocked
+            CollisionLayer::EnemyAura => {
+                CollisionGroups::new(COLLISION_GROUP_ENEMY_AURA, COLLISION_GROUP_PLAYER)
+            }
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use std::{
+    backtrace::Backtrace,
+    collections::VecDeque,
+    fmt::Write,
+    panic::PanicInfo,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::prelude::*;
+
+use crate::{display::DisplaySettings, level::LevelInfo, GameSettings, GlobalState};
+
+const CRASH_LOG_DIR: &str = "crashes";
+const CRASH_MAX_TRANSITIONS: usize = 10;
+
+// Panic hook can not access the world, so systems
+// copy everything useful for a report here
+static CRASH_CONTEXT: Mutex<Option<CrashContext>> = Mutex::new(None);
+
+pub struct CrashPlugin;
+
+impl Plugin for CrashPlugin {
+    fn build(&self, app: &mut App) {
+        let default_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            write_crash_log(info);
+            default_hook(info);
+        }));
+
+        app.add_systems(
+            Update,
+            (
+                crash_record_state.run_if(state_changed::<GlobalState>()),
+                crash_record_settings,
+                crash_record_level,
+            ),
+        );
+    }
+}
+
+#[derive(Debug, Default)]
+struct CrashContext {
+    transitions: VecDeque<String>,
+    settings: String,
+    level: String,
+}
+
+fn with_crash_context(f: impl FnOnce(&mut CrashContext)) {
+    // panic in another thread while holding the lock
+    // should not prevent writing the report
+    let mut context = CRASH_CONTEXT
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    f(context.get_or_insert_with(CrashContext::default));
+}
+
+fn crash_record_state(time: Res<Time>, global_state: Res<State<GlobalState>>) {
+    let transition = format!("{:.2}s {:?}", time.elapsed_seconds(), global_state.get());
+    with_crash_context(|context| {
+        if context.transitions.len() == CRASH_MAX_TRANSITIONS {
+            context.transitions.pop_front();
+        }
+        context.transitions.push_back(transition);
+    });
+}
+
+fn crash_record_settings(game_settings: Res<GameSettings>, display_settings: Res<DisplaySettings>) {
+    if !game_settings.is_changed() && !display_settings.is_changed() {
+        return;
+    }
+    let settings = format!(
+        "{:?}\n{:?}",
+        game_settings.as_ref(),
+        display_settings.as_ref()
+    );
+    with_crash_context(|context| context.settings = settings);
+}
+
+fn crash_record_level(level_info: Option<Res<LevelInfo>>) {
+    let Some(level_info) = level_info else {
+        return;
+    };
+    if !level_info.is_changed() {
+        return;
+    }
+    let level = format!(
+        "seed: {} progress: {} type: {:?}",
+        level_info.seed, level_info.game_progress, level_info.level_type
+    );
+    with_crash_context(|context| context.level = level);
+}
+
+fn write_crash_log(info: &PanicInfo) {
+    // system time and file system are not available on web
+    if cfg!(target_arch = "wasm32") {
+        return;
+    }
+    let backtrace = Backtrace::force_capture();
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    let mut log = String::new();
+    _ = writeln!(log, "{info}\n");
+    with_crash_context(|context| {
+        _ = writeln!(log, "Level:\n{}\n", context.level);
+        _ = writeln!(log, "Settings:\n{}\n", context.settings);
+        _ = writeln!(log, "Last state transitions:");
+        for transition in context.transitions.iter() {
+            _ = writeln!(log, "{transition}");
+        }
+    });
+    _ = writeln!(log, "\nBacktrace:\n{backtrace}");
+
+    // window is gone at this point, so the
+    // message can only go to the console
+    let path = format!("{CRASH_LOG_DIR}/crash_{timestamp}.log");
+    match std::fs::create_dir_all(CRASH_LOG_DIR).and_then(|_| std::fs::write(&path, log)) {
+        Ok(_) => eprintln!("The game crashed, log saved to {path}"),
+        Err(e) => eprintln!("The game crashed, could not save log to {path}: {e}"),
+    }
+}
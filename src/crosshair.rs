@@ -0,0 +1,259 @@
+use std::f32::consts::{FRAC_PI_2, FRAC_PI_4};
+
+use bevy::{prelude::*, render::view::RenderLayers};
+use serde::{Deserialize, Serialize};
+
+use crate::profile::{load_ron, save_ron};
+
+const CROSSHAIR_SETTINGS_PATH: &str = "crosshair.ron";
+
+pub const CROSSHAIR_MIN_SIZE: f32 = 2.0;
+pub const CROSSHAIR_MAX_SIZE: f32 = 40.0;
+pub const CROSSHAIR_MIN_GAP: f32 = 0.0;
+pub const CROSSHAIR_MAX_GAP: f32 = 40.0;
+pub const CROSSHAIR_MIN_THICKNESS: f32 = 1.0;
+pub const CROSSHAIR_MAX_THICKNESS: f32 = 10.0;
+
+const CROSSHAIR_OUTLINE_THICKNESS: f32 = 1.0;
+const CROSSHAIR_OUTLINE_COLOR: Color = Color::BLACK;
+const CROSSHAIR_HIT_MARKER_COLOR: Color = Color::RED;
+
+pub struct CrosshairPlugin;
+
+impl Plugin for CrosshairPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(load_ron::<CrosshairSettings>(CROSSHAIR_SETTINGS_PATH));
+
+        app.add_systems(
+            Update,
+            save_crosshair_settings.run_if(resource_changed::<CrosshairSettings>()),
+        );
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CrosshairStyle {
+    #[default]
+    Diagonal,
+    Cross,
+    Dot,
+    CrossDot,
+}
+
+impl CrosshairStyle {
+    pub fn next(self) -> Self {
+        match self {
+            CrosshairStyle::Diagonal => CrosshairStyle::Cross,
+            CrosshairStyle::Cross => CrosshairStyle::Dot,
+            CrosshairStyle::Dot => CrosshairStyle::CrossDot,
+            CrosshairStyle::CrossDot => CrosshairStyle::Diagonal,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CrosshairColor {
+    #[default]
+    White,
+    Red,
+    Green,
+    Cyan,
+    Yellow,
+}
+
+impl CrosshairColor {
+    pub fn next(self) -> Self {
+        match self {
+            CrosshairColor::White => CrosshairColor::Red,
+            CrosshairColor::Red => CrosshairColor::Green,
+            CrosshairColor::Green => CrosshairColor::Cyan,
+            CrosshairColor::Cyan => CrosshairColor::Yellow,
+            CrosshairColor::Yellow => CrosshairColor::White,
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            CrosshairColor::White => Color::WHITE,
+            CrosshairColor::Red => Color::RED,
+            CrosshairColor::Green => Color::LIME_GREEN,
+            CrosshairColor::Cyan => Color::CYAN,
+            CrosshairColor::Yellow => Color::YELLOW,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HitMarkerStyle {
+    Off,
+    // Small diagonal lines around the crosshair
+    #[default]
+    Cross,
+    // Crosshair itself flashes red
+    Flash,
+}
+
+impl HitMarkerStyle {
+    pub fn next(self) -> Self {
+        match self {
+            HitMarkerStyle::Off => HitMarkerStyle::Cross,
+            HitMarkerStyle::Cross => HitMarkerStyle::Flash,
+            HitMarkerStyle::Flash => HitMarkerStyle::Off,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CrosshairSettings {
+    pub style: CrosshairStyle,
+    // Length of a single line
+    pub size: f32,
+    // Distance from the center to the start of a line
+    pub gap: f32,
+    pub thickness: f32,
+    pub color: CrosshairColor,
+    pub outline: bool,
+    pub hit_marker: HitMarkerStyle,
+}
+
+impl Default for CrosshairSettings {
+    fn default() -> Self {
+        Self {
+            style: CrosshairStyle::Diagonal,
+            size: 10.0,
+            gap: 9.0,
+            thickness: 2.0,
+            color: CrosshairColor::White,
+            outline: false,
+            hit_marker: HitMarkerStyle::Cross,
+        }
+    }
+}
+
+// Single sprite of the crosshair
+#[derive(Debug, Clone, Copy)]
+struct CrosshairPiece {
+    transform: Transform,
+    size: Vec2,
+}
+
+fn crosshair_lines(angles: &[f32], size: f32, gap: f32, thickness: f32) -> Vec<CrosshairPiece> {
+    angles
+        .iter()
+        .map(|angle| {
+            let rotation = Quat::from_rotation_z(*angle);
+            CrosshairPiece {
+                transform: Transform::from_translation(rotation * Vec3::X * (gap + size / 2.0))
+                    .with_rotation(rotation),
+                size: Vec2::new(size, thickness),
+            }
+        })
+        .collect()
+}
+
+fn crosshair_pieces(settings: &CrosshairSettings) -> Vec<CrosshairPiece> {
+    let cross = [0.0, FRAC_PI_2, 2.0 * FRAC_PI_2, 3.0 * FRAC_PI_2];
+    let diagonal = cross.map(|angle| angle + FRAC_PI_4);
+    let dot = CrosshairPiece {
+        transform: Transform::default(),
+        size: Vec2::splat(settings.thickness * 2.0),
+    };
+
+    match settings.style {
+        CrosshairStyle::Diagonal => {
+            crosshair_lines(&diagonal, settings.size, settings.gap, settings.thickness)
+        }
+        CrosshairStyle::Cross => {
+            crosshair_lines(&cross, settings.size, settings.gap, settings.thickness)
+        }
+        CrosshairStyle::Dot => vec![dot],
+        CrosshairStyle::CrossDot => {
+            let mut pieces =
+                crosshair_lines(&cross, settings.size, settings.gap, settings.thickness);
+            pieces.push(dot);
+            pieces
+        }
+    }
+}
+
+/// Spawns crosshair sprites as children. Used by the
+/// HUD and by the preview in the crosshair options.
+pub fn spawn_crosshair(
+    builder: &mut ChildBuilder,
+    settings: &CrosshairSettings,
+    color: Color,
+    render_layers: RenderLayers,
+) {
+    for piece in crosshair_pieces(settings) {
+        if settings.outline {
+            builder.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: CROSSHAIR_OUTLINE_COLOR,
+                        custom_size: Some(
+                            piece.size + Vec2::splat(CROSSHAIR_OUTLINE_THICKNESS * 2.0),
+                        ),
+                        ..default()
+                    },
+                    transform: piece.transform,
+                    ..default()
+                },
+                render_layers,
+            ));
+        }
+        builder.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(piece.size),
+                    ..default()
+                },
+                // draw on top of the outline
+                transform: piece
+                    .transform
+                    .with_translation(piece.transform.translation + Vec3::Z),
+                ..default()
+            },
+            render_layers,
+        ));
+    }
+}
+
+/// Spawns hit marker sprites as children
+pub fn spawn_hit_marker(builder: &mut ChildBuilder, settings: &CrosshairSettings) {
+    match settings.hit_marker {
+        HitMarkerStyle::Off => {}
+        HitMarkerStyle::Cross => {
+            let angles = [1.0, 3.0, 5.0, 7.0].map(|i| i * FRAC_PI_4);
+            let gap = settings.gap + settings.size;
+            for piece in crosshair_lines(&angles, settings.size, gap, settings.thickness) {
+                builder.spawn(SpriteBundle {
+                    sprite: Sprite {
+                        color: CROSSHAIR_HIT_MARKER_COLOR,
+                        custom_size: Some(piece.size),
+                        ..default()
+                    },
+                    transform: piece.transform,
+                    ..default()
+                });
+            }
+        }
+        HitMarkerStyle::Flash => {
+            let settings = CrosshairSettings {
+                outline: false,
+                ..*settings
+            };
+            spawn_crosshair(
+                builder,
+                &settings,
+                CROSSHAIR_HIT_MARKER_COLOR,
+                RenderLayers::default(),
+            );
+        }
+    }
+}
+
+fn save_crosshair_settings(settings: Res<CrosshairSettings>) {
+    save_ron(CROSSHAIR_SETTINGS_PATH, settings.as_ref());
+}
@@ -0,0 +1,252 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    level::{grade::RunBonus, LevelInfo},
+    perks::PerkModifiers,
+    profile::{load_ron, save_ron},
+    scrap::Scrap,
+    GlobalState,
+};
+
+const DAILY_LEADERBOARD_PATH: &str = "daily.ron";
+// Only best results of each day are kept
+const DAILY_LEADERBOARD_ENTRIES: usize = 10;
+const DAILY_MODIFIERS: usize = 2;
+
+const DAILY_SCORE_PER_LEVEL: u32 = 100;
+const DAILY_SCORE_WIN: u32 = 1000;
+
+const DAILY_GLASS_CANNON_DAMAGE_MODIFIER: f32 = 1.5;
+const DAILY_GLASS_CANNON_DAMAGE_TAKEN_MODIFIER: f32 = 1.5;
+const DAILY_HEAVY_BOOTS_MOVEMENT_SPEED_MODIFIER: f32 = 0.8;
+const DAILY_VAMPIRE_LIFESTEAL: i32 = 15;
+const DAILY_VAMPIRE_DAMAGE_TAKEN_MODIFIER: f32 = 1.25;
+const DAILY_ADRENALINE_FIRE_RATE_MODIFIER: f32 = 1.5;
+const DAILY_ADRENALINE_DAMAGE_MODIFIER: f32 = 0.8;
+const DAILY_PITCHER_THROW_DAMAGE_MODIFIER: f32 = 3.0;
+const DAILY_PITCHER_DAMAGE_MODIFIER: f32 = 0.75;
+
+pub struct DailyPlugin;
+
+impl Plugin for DailyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DailyRun>();
+        app.insert_resource(load_ron::<DailyLeaderboard>(DAILY_LEADERBOARD_PATH));
+
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::InGame,
+                to: GlobalState::GameOver,
+            },
+            record_daily_result.run_if(daily_run_active),
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::InGame,
+                to: GlobalState::GameWon,
+            },
+            record_daily_result.run_if(daily_run_active),
+        );
+
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::Paused,
+                to: GlobalState::MainMenu,
+            },
+            reset_daily_run,
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::GameOver,
+                to: GlobalState::MainMenu,
+            },
+            reset_daily_run,
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::GameWon,
+                to: GlobalState::MainMenu,
+            },
+            reset_daily_run,
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::Hub,
+                to: GlobalState::MainMenu,
+            },
+            reset_daily_run,
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DailyModifier {
+    GlassCannon,
+    HeavyBoots,
+    Vampire,
+    Adrenaline,
+    Pitcher,
+}
+
+impl DailyModifier {
+    pub const ALL: [DailyModifier; 5] = [
+        DailyModifier::GlassCannon,
+        DailyModifier::HeavyBoots,
+        DailyModifier::Vampire,
+        DailyModifier::Adrenaline,
+        DailyModifier::Pitcher,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DailyModifier::GlassCannon => "Glass cannon",
+            DailyModifier::HeavyBoots => "Heavy boots",
+            DailyModifier::Vampire => "Vampire",
+            DailyModifier::Adrenaline => "Adrenaline",
+            DailyModifier::Pitcher => "Pitcher",
+        }
+    }
+
+    pub fn apply(&self, modifiers: &mut PerkModifiers) {
+        match self {
+            DailyModifier::GlassCannon => {
+                modifiers.damage *= DAILY_GLASS_CANNON_DAMAGE_MODIFIER;
+                modifiers.damage_taken *= DAILY_GLASS_CANNON_DAMAGE_TAKEN_MODIFIER;
+            }
+            DailyModifier::HeavyBoots => {
+                modifiers.movement_speed *= DAILY_HEAVY_BOOTS_MOVEMENT_SPEED_MODIFIER;
+            }
+            DailyModifier::Vampire => {
+                modifiers.lifesteal += DAILY_VAMPIRE_LIFESTEAL;
+                modifiers.damage_taken *= DAILY_VAMPIRE_DAMAGE_TAKEN_MODIFIER;
+            }
+            DailyModifier::Adrenaline => {
+                modifiers.fire_rate *= DAILY_ADRENALINE_FIRE_RATE_MODIFIER;
+                modifiers.damage *= DAILY_ADRENALINE_DAMAGE_MODIFIER;
+            }
+            DailyModifier::Pitcher => {
+                modifiers.throw_damage *= DAILY_PITCHER_THROW_DAMAGE_MODIFIER;
+                modifiers.damage *= DAILY_PITCHER_DAMAGE_MODIFIER;
+            }
+        }
+    }
+}
+
+// Every player gets the same levels and
+// modifiers during the same day.
+// Director does not adapt to the player
+// during daily run.
+#[derive(Debug, Default, Resource)]
+pub struct DailyRun {
+    pub active: bool,
+    pub day: u64,
+    pub seed: u64,
+    pub modifiers: Vec<DailyModifier>,
+    pub last_score: u32,
+}
+
+impl DailyRun {
+    // System time is not available on web
+    pub fn today() -> Option<u64> {
+        if cfg!(target_arch = "wasm32") {
+            return None;
+        }
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        Some(secs / (60 * 60 * 24))
+    }
+
+    pub fn start(&mut self) {
+        let Some(day) = Self::today() else {
+            return;
+        };
+        let seed = day.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        self.active = true;
+        self.day = day;
+        self.seed = seed;
+        self.modifiers = DailyModifier::ALL
+            .choose_multiple(&mut rng, DAILY_MODIFIERS)
+            .copied()
+            .collect();
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct DailyEntry {
+    pub day: u64,
+    pub score: u32,
+    pub won: bool,
+}
+
+#[derive(Debug, Default, Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DailyLeaderboard {
+    pub entries: Vec<DailyEntry>,
+}
+
+impl DailyLeaderboard {
+    pub fn best(&self, day: u64) -> Option<u32> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.day == day)
+            .map(|entry| entry.score)
+            .max()
+    }
+
+    fn add(&mut self, entry: DailyEntry) {
+        self.entries.push(entry);
+        self.entries
+            .sort_by(|a, b| b.day.cmp(&a.day).then(b.score.cmp(&a.score)));
+
+        let mut day = None;
+        let mut day_entries = 0;
+        self.entries.retain(|entry| {
+            if day != Some(entry.day) {
+                day = Some(entry.day);
+                day_entries = 0;
+            }
+            day_entries += 1;
+            day_entries <= DAILY_LEADERBOARD_ENTRIES
+        });
+    }
+}
+
+pub fn daily_run_active(daily_run: Res<DailyRun>) -> bool {
+    daily_run.active
+}
+
+fn reset_daily_run(mut daily_run: ResMut<DailyRun>) {
+    *daily_run = DailyRun::default();
+}
+
+fn record_daily_result(
+    scrap: Res<Scrap>,
+    run_bonus: Res<RunBonus>,
+    level_info: Res<LevelInfo>,
+    mut daily_run: ResMut<DailyRun>,
+    mut leaderboard: ResMut<DailyLeaderboard>,
+) {
+    // game progress goes past 100 only after boss is killed
+    let won = 100 < level_info.game_progress;
+    let levels = (level_info.game_progress / 10).max(0) as u32;
+    let score = levels * DAILY_SCORE_PER_LEVEL
+        + scrap.amount
+        + run_bonus.score
+        + if won { DAILY_SCORE_WIN } else { 0 };
+
+    daily_run.last_score = score;
+    leaderboard.add(DailyEntry {
+        day: daily_run.day,
+        score,
+        won,
+    });
+    save_ron(DAILY_LEADERBOARD_PATH, leaderboard.as_ref());
+}
@@ -1,7 +1,14 @@
 use bevy::prelude::*;
 use bevy_rapier3d::{prelude::*, rapier::geometry::CollisionEventFlags};
 
-use crate::{weapons::Projectile, GlobalState};
+use crate::{
+    perks::Perks,
+    player::Player,
+    powerups::{ActivePowerups, PowerupType},
+    run_modifiers::{RunModifier, RunModifiers},
+    weapons::{ammo::AmmoType, Projectile, WeaponType},
+    GlobalState,
+};
 
 pub struct DamagePlugin;
 
@@ -9,6 +16,7 @@ impl Plugin for DamagePlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<DamageEvent>();
         app.add_event::<KillEvent>();
+        app.add_event::<ContactDamageEvent>();
 
         app.add_systems(Update, apply_damage.run_if(in_state(GlobalState::InGame)));
     }
@@ -18,11 +26,44 @@ impl Plugin for DamagePlugin {
 pub struct DamageEvent {
     pub entity: Entity,
     pub direction: Vec3,
+    pub ammo_type: AmmoType,
 }
 
 #[derive(Clone, Copy, Event)]
 pub struct KillEvent {
     pub entity: Entity,
+    pub cause: KillCause,
+    // Only set for kills by projectiles
+    pub shot: Option<ShotSource>,
+}
+
+/// Projectile that landed the killing hit
+#[derive(Debug, Clone, Copy)]
+pub struct ShotSource {
+    pub origin: Vec3,
+    pub weapon_type: WeaponType,
+    pub player_weapon: bool,
+    pub pellet_group: Option<u32>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum KillCause {
+    // Projectile of a weapon
+    #[default]
+    Shot,
+    // Thrown weapon or contact with an enemy
+    Impact,
+    // Fire, pits and other hazards
+    Environment,
+}
+
+// Damage without a damage object, e.g. enemy
+// touching the player
+#[derive(Clone, Copy, Event)]
+pub struct ContactDamageEvent {
+    pub entity: Entity,
+    pub damage: i32,
+    pub direction: Vec3,
 }
 
 #[derive(Default, Component)]
@@ -35,67 +76,185 @@ pub struct Health {
     pub health: i32,
 }
 
-fn apply_damage(
-    projectiles: Query<&Projectile>,
-    damage_objects: Query<(Entity, &Damage)>,
+// Hits weaker than `min_damage` do nothing
+#[derive(Default, Component)]
+pub struct DamageThreshold {
+    pub min_damage: i32,
+}
+
+// Hit of one damage object, or of all pellets of
+// one shot, against one entity in this frame
+struct Hit {
+    entity: Entity,
+    damage: i32,
+    direction: Option<Vec3>,
+    ammo_type: AmmoType,
+    cause: KillCause,
+    shot: Option<ShotSource>,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn apply_damage(
+    perks: Res<Perks>,
+    active_powerups: Res<ActivePowerups>,
+    run_modifiers: Res<RunModifiers>,
+    mut projectiles: Query<&mut Projectile>,
+    thresholds: Query<&DamageThreshold>,
+    damage_objects: Query<&Damage>,
     mut commands: Commands,
     mut kill_events: EventWriter<KillEvent>,
     mut damage_events: EventWriter<DamageEvent>,
     mut collision_events: EventReader<CollisionEvent>,
-    mut entities: Query<(Entity, &mut Health)>,
+    mut contact_damage_events: EventReader<ContactDamageEvent>,
+    mut entities: Query<(&mut Health, Has<Player>)>,
 ) {
+    let mut hits: Vec<Hit> = vec![];
+    // pellet group and target of each pellet hit, so
+    // the whole shot counts as a single hit
+    let mut pellet_hits: Vec<(u32, Entity, usize)> = vec![];
+
     for collision_event in collision_events.read() {
-        let (collider_1, collider_2, flags) = match collision_event {
-            CollisionEvent::Started(c1, c2, f) => (c1, c2, f),
-            CollisionEvent::Stopped(c1, c2, f) => (c1, c2, f),
+        // piercing projectiles also stop touching
+        // their targets, which is not a new hit
+        let CollisionEvent::Started(collider_1, collider_2, flags) = collision_event else {
+            continue;
         };
+        // sensors, e.g. enemy auras, do not deal damage
         if flags.contains(CollisionEventFlags::SENSOR) {
-            return;
+            continue;
         }
 
-        let ((damage_entity, damage), (entity, mut entity_health)) =
-            if let Ok(p) = damage_objects.get(*collider_1) {
-                let e = if let Ok(e) = entities.get_mut(*collider_2) {
-                    e
-                } else {
-                    continue;
-                };
-                (p, e)
-            } else if let Ok(p) = damage_objects.get(*collider_2) {
-                let e = if let Ok(e) = entities.get_mut(*collider_1) {
-                    e
-                } else {
-                    continue;
-                };
-                (p, e)
+        // both colliders can have health, e.g. player
+        // projectile hitting enemy projectile, so
+        // orientation is checked before borrowing
+        let (damage_entity, entity) =
+            if damage_objects.contains(*collider_1) && entities.contains(*collider_2) {
+                (*collider_1, *collider_2)
+            } else if damage_objects.contains(*collider_2) && entities.contains(*collider_1) {
+                (*collider_2, *collider_1)
             } else {
                 continue;
             };
+        let Ok(damage) = damage_objects.get(damage_entity) else {
+            continue;
+        };
+        let Ok((entity_health, is_player)) = entities.get(entity) else {
+            continue;
+        };
 
         // skip enemies that were killed by prevous iterations
         if entity_health.health <= 0 {
             continue;
         }
-        entity_health.health -= damage.damage;
-
+        // thresholds are checked for each pellet, so
+        // shotgun can not break everything at once
+        if let Ok(threshold) = thresholds.get(entity) {
+            if damage.damage < threshold.min_damage {
+                continue;
+            }
+        }
+        // shield power-up absorbs all hits
+        let absorbed = is_player && active_powerups.is_active(PowerupType::Shield);
         let Some(mut e) = commands.get_entity(damage_entity) else {
             continue;
         };
-        e.remove::<Damage>();
+        match projectiles.get_mut(damage_entity) {
+            Ok(mut projectile) if 0 < projectile.pierce => projectile.pierce -= 1,
+            Ok(_) => e.despawn_recursive(),
+            Err(_) => {
+                e.remove::<Damage>();
+            }
+        }
+        if absorbed {
+            continue;
+        }
 
-        if entity_health.health <= 0 {
-            let Some(mut e) = commands.get_entity(entity) else {
+        let projectile = projectiles.get(damage_entity).ok();
+        let direction = projectile.map(|projectile| projectile.direction);
+        let pellet_group = projectile.and_then(|projectile| projectile.pellet_group);
+        let ammo_type = projectile
+            .map(|projectile| projectile.ammo_type)
+            .unwrap_or_default();
+        let cause = if projectile.is_some() {
+            KillCause::Shot
+        } else {
+            KillCause::Impact
+        };
+        let shot = projectile.map(|projectile| ShotSource {
+            origin: projectile.origin,
+            weapon_type: projectile.weapon_type,
+            player_weapon: projectile.player_weapon,
+            pellet_group,
+        });
+        if let Some(pellet_group) = pellet_group {
+            if let Some((_, _, index)) = pellet_hits
+                .iter()
+                .find(|(group, target, _)| *group == pellet_group && *target == entity)
+            {
+                hits[*index].damage += damage.damage;
                 continue;
-            };
-            e.remove::<Health>();
-            kill_events.send(KillEvent { entity });
+            }
+            pellet_hits.push((pellet_group, entity, hits.len()));
+        }
+        hits.push(Hit {
+            entity,
+            damage: damage.damage,
+            direction,
+            ammo_type,
+            cause,
+            shot,
+        });
+    }
+
+    for contact in contact_damage_events.read() {
+        let Ok((_, is_player)) = entities.get(contact.entity) else {
+            continue;
+        };
+        if is_player && active_powerups.is_active(PowerupType::Shield) {
+            continue;
+        }
+        hits.push(Hit {
+            entity: contact.entity,
+            damage: contact.damage,
+            direction: Some(contact.direction),
+            ammo_type: AmmoType::Standard,
+            cause: KillCause::Impact,
+            shot: None,
+        });
+    }
+
+    for hit in hits {
+        let Ok((mut entity_health, is_player)) = entities.get_mut(hit.entity) else {
+            continue;
+        };
+
+        // killed by earlier hits in this frame
+        if entity_health.health <= 0 {
+            continue;
+        }
+        if is_player && run_modifiers.has(RunModifier::OneHitDeath) {
+            entity_health.health = 0;
+        } else if is_player {
+            entity_health.health -= (hit.damage as f32 * perks.modifiers.damage_taken) as i32;
         } else {
-            let Ok(projectile) = projectiles.get(damage_entity) else {
+            entity_health.health -= hit.damage;
+        }
+
+        if entity_health.health <= 0 {
+            let Some(mut e) = commands.get_entity(hit.entity) else {
                 continue;
             };
+            e.remove::<Health>();
+            kill_events.send(KillEvent {
+                entity: hit.entity,
+                cause: hit.cause,
+                shot: hit.shot,
+            });
+        } else if let Some(direction) = hit.direction {
             damage_events.send(DamageEvent {
-                entity,
-                direction: projectile.direction,
+                entity: hit.entity,
+                direction,
+                ammo_type: hit.ammo_type,
             });
         }
     }
@@ -0,0 +1,252 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    daily::daily_run_active,
+    damage::{DamageEvent, Health, KillEvent},
+    new_game_plus::NewGamePlus,
+    player::{Player, PlayerWeapon, PLAYER_HEALTH},
+    run_modifiers::{RunModifier, RunModifiers, RUN_MODIFIER_DOUBLE_ENEMIES_MODIFIER},
+    weapons::Ammo,
+    GlobalState,
+};
+
+const DIRECTOR_INITIAL_INTENSITY: f32 = 0.3;
+const DIRECTOR_MIN_INTENSITY: f32 = 0.0;
+const DIRECTOR_MAX_INTENSITY: f32 = 1.0;
+// How fast intensity follows the target intensity (per second)
+const DIRECTOR_INTENSITY_SPEED: f32 = 0.05;
+// How fast recent damage counters decay (per second)
+const DIRECTOR_RECENT_DECAY: f32 = 0.1;
+
+const DIRECTOR_LOW_AMMO: u32 = 5;
+const DIRECTOR_HITS_FOR_FULL_OUTPUT: f32 = 20.0;
+const DIRECTOR_HITS_FOR_FULL_PRESSURE: f32 = 5.0;
+
+// Default designer bounds for what the director can tweak
+const DIRECTOR_MIN_ENEMIES: u32 = 3;
+const DIRECTOR_MAX_ENEMIES: u32 = 8;
+const DIRECTOR_MIN_SMALL_ENEMIES_PERCENT: f64 = 0.3;
+const DIRECTOR_MAX_SMALL_ENEMIES_PERCENT: f64 = 0.7;
+const DIRECTOR_MIN_DROP_CHANCE: f64 = 0.5;
+const DIRECTOR_MAX_DROP_CHANCE: f64 = 1.0;
+const DIRECTOR_MIN_ELITE_CHANCE: f64 = 0.0;
+const DIRECTOR_MAX_ELITE_CHANCE: f64 = 0.3;
+
+// Each New Game+ makes levels harder
+// independently of how player is doing
+const DIRECTOR_PRESTIGE_BASELINE: f32 = 0.25;
+
+pub struct DirectorPlugin;
+
+impl Plugin for DirectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Director>();
+        app.init_resource::<DifficultyTuning>();
+
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::MainMenu,
+                to: GlobalState::InGame,
+            },
+            reset_director,
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::GameOver,
+                to: GlobalState::InGame,
+            },
+            reset_director,
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::GameWon,
+                to: GlobalState::InGame,
+            },
+            reset_director,
+        );
+
+        app.add_systems(
+            Update,
+            director_apply_tuning.run_if(resource_changed::<DifficultyTuning>()),
+        );
+        app.add_systems(
+            Update,
+            (director_track_damage, director_update)
+                .run_if(in_state(GlobalState::InGame).and_then(not(daily_run_active))),
+        );
+    }
+}
+
+/// Difficulty curve. Intensity of the director
+/// moves values between min and max.
+#[derive(Debug, Clone, Copy, PartialEq, Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DifficultyTuning {
+    pub min_enemies: u32,
+    pub max_enemies: u32,
+    pub min_small_enemies_percent: f64,
+    pub max_small_enemies_percent: f64,
+    pub min_drop_chance: f64,
+    pub max_drop_chance: f64,
+    pub min_elite_chance: f64,
+    pub max_elite_chance: f64,
+    pub prestige_baseline: f32,
+}
+
+impl Default for DifficultyTuning {
+    fn default() -> Self {
+        Self {
+            min_enemies: DIRECTOR_MIN_ENEMIES,
+            max_enemies: DIRECTOR_MAX_ENEMIES,
+            min_small_enemies_percent: DIRECTOR_MIN_SMALL_ENEMIES_PERCENT,
+            max_small_enemies_percent: DIRECTOR_MAX_SMALL_ENEMIES_PERCENT,
+            min_drop_chance: DIRECTOR_MIN_DROP_CHANCE,
+            max_drop_chance: DIRECTOR_MAX_DROP_CHANCE,
+            min_elite_chance: DIRECTOR_MIN_ELITE_CHANCE,
+            max_elite_chance: DIRECTOR_MAX_ELITE_CHANCE,
+            prestige_baseline: DIRECTOR_PRESTIGE_BASELINE,
+        }
+    }
+}
+
+// Watches how the player is doing and
+// decides how hard next levels should be.
+// 0.0 intensity - player is struggling
+// 1.0 intensity - player is doing great
+#[derive(Resource)]
+pub struct Director {
+    pub intensity: f32,
+    pub prestige: u32,
+    pub enemies_multiplier: u32,
+    recent_damage_dealt: f32,
+    recent_damage_taken: f32,
+    tuning: DifficultyTuning,
+}
+
+impl Default for Director {
+    fn default() -> Self {
+        Self {
+            intensity: DIRECTOR_INITIAL_INTENSITY,
+            prestige: 0,
+            enemies_multiplier: 1,
+            recent_damage_dealt: 0.0,
+            recent_damage_taken: 0.0,
+            tuning: DifficultyTuning::default(),
+        }
+    }
+}
+
+impl Director {
+    // Intensity with the New Game+ baseline on top.
+    // Can go above 1.0, so values can go past the
+    // designer bounds in New Game+.
+    pub fn difficulty(&self) -> f32 {
+        self.intensity + self.prestige as f32 * self.tuning.prestige_baseline
+    }
+
+    pub fn enemies(&self) -> u32 {
+        let tuning = &self.tuning;
+        (tuning.min_enemies
+            + (tuning.max_enemies.saturating_sub(tuning.min_enemies) as f32 * self.difficulty())
+                .round() as u32)
+            * self.enemies_multiplier
+    }
+
+    // more intensity - less small enemies
+    pub fn small_enemies_percent(&self) -> f64 {
+        let tuning = &self.tuning;
+        (tuning.max_small_enemies_percent
+            - (tuning.max_small_enemies_percent - tuning.min_small_enemies_percent)
+                * self.difficulty() as f64)
+            .clamp(0.0, 1.0)
+    }
+
+    // more intensity - less drops
+    pub fn drop_chance(&self) -> f64 {
+        let tuning = &self.tuning;
+        (tuning.max_drop_chance
+            - (tuning.max_drop_chance - tuning.min_drop_chance) * self.difficulty() as f64)
+            .clamp(0.0, 1.0)
+    }
+
+    pub fn elite_chance(&self) -> f64 {
+        let tuning = &self.tuning;
+        (tuning.min_elite_chance
+            + (tuning.max_elite_chance - tuning.min_elite_chance) * self.difficulty() as f64)
+            .clamp(0.0, 1.0)
+    }
+}
+
+fn reset_director(
+    new_game_plus: Res<NewGamePlus>,
+    run_modifiers: Res<RunModifiers>,
+    mut director: ResMut<Director>,
+) {
+    let enemies_multiplier = if run_modifiers.has(RunModifier::DoubleEnemies) {
+        RUN_MODIFIER_DOUBLE_ENEMIES_MODIFIER
+    } else {
+        1
+    };
+    *director = Director {
+        prestige: new_game_plus.prestige,
+        enemies_multiplier,
+        tuning: director.tuning,
+        ..default()
+    };
+}
+
+fn director_apply_tuning(difficulty_tuning: Res<DifficultyTuning>, mut director: ResMut<Director>) {
+    director.tuning = *difficulty_tuning;
+}
+
+fn director_track_damage(
+    player: Query<Entity, With<Player>>,
+    mut director: ResMut<Director>,
+    mut damage_events: EventReader<DamageEvent>,
+    mut kill_events: EventReader<KillEvent>,
+) {
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+
+    let damaged = damage_events.read().map(|e| e.entity);
+    let killed = kill_events.read().map(|e| e.entity);
+    for entity in damaged.chain(killed) {
+        if entity == player {
+            director.recent_damage_taken += 1.0;
+        } else {
+            director.recent_damage_dealt += 1.0;
+        }
+    }
+}
+
+fn director_update(
+    time: Res<Time>,
+    player: Query<&Health, With<Player>>,
+    player_ammo: Query<&Ammo, With<PlayerWeapon>>,
+    mut director: ResMut<Director>,
+) {
+    let Ok(health) = player.get_single() else {
+        return;
+    };
+
+    let decay = (1.0 - DIRECTOR_RECENT_DECAY * time.delta_seconds()).max(0.0);
+    director.recent_damage_dealt *= decay;
+    director.recent_damage_taken *= decay;
+
+    let health_score = (health.health as f32 / PLAYER_HEALTH as f32).clamp(0.0, 1.0);
+    let ammo_score = match player_ammo.get_single() {
+        Ok(ammo) if DIRECTOR_LOW_AMMO <= ammo.ammo => 1.0,
+        Ok(ammo) => ammo.ammo as f32 / DIRECTOR_LOW_AMMO as f32,
+        Err(_) => 0.0,
+    };
+    let output_score = (director.recent_damage_dealt / DIRECTOR_HITS_FOR_FULL_OUTPUT).min(1.0);
+    let pressure = (director.recent_damage_taken / DIRECTOR_HITS_FOR_FULL_PRESSURE).min(1.0);
+
+    let target = ((health_score + ammo_score + output_score) / 3.0 - pressure * 0.5)
+        .clamp(DIRECTOR_MIN_INTENSITY, DIRECTOR_MAX_INTENSITY);
+
+    let step = DIRECTOR_INTENSITY_SPEED * time.delta_seconds();
+    director.intensity += (target - director.intensity).clamp(-step, step);
+}
@@ -0,0 +1,418 @@
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
+
+#[cfg(not(target_arch = "wasm32"))]
+use bevy::utils::Instant;
+use bevy::{
+    pbr::{DirectionalLightShadowMap, PointLightShadowMap},
+    prelude::*,
+    window::{MonitorSelection, PresentMode, PrimaryWindow, WindowMode, WindowPosition},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    profile::{load_ron, save_ron},
+    GlobalState,
+};
+
+const DISPLAY_SETTINGS_PATH: &str = "display.ron";
+
+pub const DISPLAY_RESOLUTIONS: [(u32, u32); 4] =
+    [(1280, 720), (1600, 900), (1920, 1080), (2560, 1440)];
+// Bevy does not expose the list of connected
+// monitors, so selection just cycles indices
+pub const DISPLAY_MAX_MONITORS: usize = 4;
+
+// Changed display settings are reverted unless
+// confirmed in this time
+pub const DISPLAY_REVERT_SECONDS: f32 = 10.0;
+
+const DISPLAY_TOGGLE_KEY: KeyCode = KeyCode::Return;
+
+pub const DISPLAY_MIN_CUSTOM_FPS: u32 = 30;
+pub const DISPLAY_MAX_CUSTOM_FPS: u32 = 360;
+pub const DISPLAY_CUSTOM_FPS_STEP: u32 = 10;
+
+// Automatic quality steps down if the average frame
+// rate stays below this fraction of the target one
+const QUALITY_TARGET_FPS_FRACTION: f32 = 0.9;
+// Target for the uncapped frame rate
+const QUALITY_UNCAPPED_TARGET_FPS: u32 = 60;
+const QUALITY_SAMPLE_SECONDS: f32 = 3.0;
+
+pub struct DisplayPlugin;
+
+impl Plugin for DisplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(load_ron::<DisplaySettings>(DISPLAY_SETTINGS_PATH));
+        app.init_resource::<DisplayConfirmation>();
+        app.init_resource::<Quality>();
+
+        app.add_systems(
+            Update,
+            (
+                display_toggle_fullscreen,
+                display_confirmation_tick,
+                apply_display_settings.run_if(resource_changed::<DisplaySettings>()),
+                save_display_settings.run_if(resource_changed::<DisplaySettings>()),
+                quality_reset.run_if(resource_changed::<DisplaySettings>()),
+                // loading and menus are not representative
+                quality_auto_scale.run_if(in_state(GlobalState::InGame)),
+                apply_quality.run_if(resource_changed::<Quality>()),
+            )
+                .chain(),
+        );
+        // Browser paces the frames on web and
+        // the thread can not sleep there
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_systems(Last, display_limit_fps);
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DisplayMode {
+    Windowed,
+    #[default]
+    Borderless,
+    Fullscreen,
+}
+
+impl DisplayMode {
+    pub fn next(self) -> Self {
+        match self {
+            DisplayMode::Windowed => DisplayMode::Borderless,
+            DisplayMode::Borderless => DisplayMode::Fullscreen,
+            DisplayMode::Fullscreen => DisplayMode::Windowed,
+        }
+    }
+}
+
+impl From<DisplayMode> for WindowMode {
+    fn from(value: DisplayMode) -> Self {
+        match value {
+            DisplayMode::Windowed => WindowMode::Windowed,
+            DisplayMode::Borderless => WindowMode::BorderlessFullscreen,
+            DisplayMode::Fullscreen => WindowMode::Fullscreen,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FpsLimit {
+    Uncapped,
+    #[default]
+    Fps60,
+    Fps120,
+    Fps144,
+    // Uses `DisplaySettings::custom_fps`
+    Custom,
+}
+
+impl FpsLimit {
+    pub fn next(self) -> Self {
+        match self {
+            FpsLimit::Uncapped => FpsLimit::Fps60,
+            FpsLimit::Fps60 => FpsLimit::Fps120,
+            FpsLimit::Fps120 => FpsLimit::Fps144,
+            FpsLimit::Fps144 => FpsLimit::Custom,
+            FpsLimit::Custom => FpsLimit::Uncapped,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QualityPreset {
+    Low,
+    Medium,
+    #[default]
+    High,
+}
+
+impl QualityPreset {
+    pub fn next(self) -> Self {
+        match self {
+            QualityPreset::Low => QualityPreset::Medium,
+            QualityPreset::Medium => QualityPreset::High,
+            QualityPreset::High => QualityPreset::Low,
+        }
+    }
+
+    fn lower(self) -> Option<Self> {
+        match self {
+            QualityPreset::Low => None,
+            QualityPreset::Medium => Some(QualityPreset::Low),
+            QualityPreset::High => Some(QualityPreset::Medium),
+        }
+    }
+
+    fn shadow_map_size(&self) -> usize {
+        match self {
+            QualityPreset::Low => 512,
+            QualityPreset::Medium => 1024,
+            QualityPreset::High => 2048,
+        }
+    }
+
+    /// Only every n-th mounted light of the level is on
+    pub fn light_stride(&self) -> u32 {
+        match self {
+            QualityPreset::Low => 3,
+            QualityPreset::Medium => 2,
+            QualityPreset::High => 1,
+        }
+    }
+
+    /// Only every n-th debris piece is spawned
+    pub fn particle_stride(&self) -> u32 {
+        match self {
+            QualityPreset::Low => 3,
+            QualityPreset::Medium => 2,
+            QualityPreset::High => 1,
+        }
+    }
+}
+
+/// Quality in use. Starts at the preset from the settings
+/// and can be lowered by the automatic scaling.
+#[derive(Debug, Default, Resource)]
+pub struct Quality {
+    pub preset: QualityPreset,
+    frame_time_sum: f32,
+    frames: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DisplaySettings {
+    pub mode: DisplayMode,
+    // Size of the window in the windowed mode
+    // and the video mode in the fullscreen mode
+    pub resolution: (u32, u32),
+    pub monitor: usize,
+    pub vsync: bool,
+    pub fps_limit: FpsLimit,
+    pub custom_fps: u32,
+    pub quality: QualityPreset,
+    pub auto_quality: bool,
+    // Security cameras render the level one more time
+    pub security_cameras: bool,
+    pub projectile_trails: bool,
+}
+
+impl Default for DisplaySettings {
+    fn default() -> Self {
+        Self {
+            mode: DisplayMode::Borderless,
+            resolution: DISPLAY_RESOLUTIONS[0],
+            monitor: 0,
+            vsync: true,
+            fps_limit: FpsLimit::Fps60,
+            custom_fps: 90,
+            quality: QualityPreset::High,
+            auto_quality: true,
+            security_cameras: true,
+            projectile_trails: true,
+        }
+    }
+}
+
+impl DisplaySettings {
+    pub fn next_resolution(&self) -> (u32, u32) {
+        let current = DISPLAY_RESOLUTIONS
+            .iter()
+            .position(|resolution| *resolution == self.resolution)
+            .unwrap_or(0);
+        DISPLAY_RESOLUTIONS[(current + 1) % DISPLAY_RESOLUTIONS.len()]
+    }
+
+    pub fn next_monitor(&self) -> usize {
+        (self.monitor + 1) % DISPLAY_MAX_MONITORS
+    }
+
+    pub fn max_fps(&self) -> Option<u32> {
+        match self.fps_limit {
+            FpsLimit::Uncapped => None,
+            FpsLimit::Fps60 => Some(60),
+            FpsLimit::Fps120 => Some(120),
+            FpsLimit::Fps144 => Some(144),
+            FpsLimit::Custom => Some(self.custom_fps),
+        }
+    }
+}
+
+// Last confirmed settings while new ones
+// are waiting for the confirmation
+#[derive(Debug, Default, Resource)]
+pub struct DisplayConfirmation {
+    previous: Option<DisplaySettings>,
+    remaining: f32,
+}
+
+impl DisplayConfirmation {
+    pub fn remaining(&self) -> Option<f32> {
+        self.previous.map(|_| self.remaining)
+    }
+
+    /// Starts the countdown. Several changes in a row
+    /// are reverted to the settings before the first one.
+    pub fn start(&mut self, previous: DisplaySettings) {
+        if self.previous.is_none() {
+            self.previous = Some(previous);
+        }
+        self.remaining = DISPLAY_REVERT_SECONDS;
+    }
+
+    pub fn keep(&mut self) {
+        self.previous = None;
+    }
+
+    pub fn revert(&mut self) -> Option<DisplaySettings> {
+        self.previous.take()
+    }
+}
+
+// Works in menus and in game. Resolution is kept in the
+// settings while in fullscreen, so switching back
+// restores the previous window size.
+fn display_toggle_fullscreen(
+    keys: Res<Input<KeyCode>>,
+    mut display_settings: ResMut<DisplaySettings>,
+    mut display_confirmation: ResMut<DisplayConfirmation>,
+) {
+    let alt = keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]);
+    if !alt || !keys.just_pressed(DISPLAY_TOGGLE_KEY) {
+        return;
+    }
+
+    // explicit toggle does not need a confirmation
+    display_confirmation.keep();
+    display_settings.mode = match display_settings.mode {
+        DisplayMode::Windowed => DisplayMode::Borderless,
+        DisplayMode::Borderless | DisplayMode::Fullscreen => DisplayMode::Windowed,
+    };
+}
+
+fn display_confirmation_tick(
+    time: Res<Time>,
+    mut display_settings: ResMut<DisplaySettings>,
+    mut display_confirmation: ResMut<DisplayConfirmation>,
+) {
+    if display_confirmation.previous.is_none() {
+        return;
+    }
+
+    display_confirmation.remaining -= time.delta_seconds();
+    if 0.0 < display_confirmation.remaining {
+        return;
+    }
+    if let Some(previous) = display_confirmation.revert() {
+        *display_settings = previous;
+    }
+}
+
+fn apply_display_settings(
+    display_settings: Res<DisplaySettings>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let Ok(mut window) = windows.get_single_mut() else {
+        return;
+    };
+
+    let (width, height) = display_settings.resolution;
+    // window is moved first, so fullscreen mode
+    // is applied on the selected monitor
+    window.position = WindowPosition::Centered(MonitorSelection::Index(display_settings.monitor));
+    window.resolution.set(width as f32, height as f32);
+    window.mode = display_settings.mode.into();
+    window.present_mode = if display_settings.vsync {
+        PresentMode::AutoVsync
+    } else {
+        PresentMode::AutoNoVsync
+    };
+}
+
+fn quality_reset(display_settings: Res<DisplaySettings>, mut quality: ResMut<Quality>) {
+    if quality.preset != display_settings.quality {
+        *quality = Quality {
+            preset: display_settings.quality,
+            ..default()
+        };
+    }
+}
+
+// Quality only goes down, so it does not jump
+// back and forth. Changing the settings resets it.
+fn quality_auto_scale(
+    time: Res<Time<Real>>,
+    display_settings: Res<DisplaySettings>,
+    mut quality: ResMut<Quality>,
+) {
+    if !display_settings.auto_quality {
+        return;
+    }
+
+    // collecting samples does not count as a change
+    let samples = quality.bypass_change_detection();
+    samples.frame_time_sum += time.delta_seconds();
+    samples.frames += 1;
+    if samples.frame_time_sum < QUALITY_SAMPLE_SECONDS {
+        return;
+    }
+
+    let fps = samples.frames as f32 / samples.frame_time_sum;
+    samples.frame_time_sum = 0.0;
+    samples.frames = 0;
+
+    let target_fps = display_settings
+        .max_fps()
+        .unwrap_or(QUALITY_UNCAPPED_TARGET_FPS) as f32;
+    if fps < target_fps * QUALITY_TARGET_FPS_FRACTION {
+        if let Some(lower) = quality.preset.lower() {
+            info!(
+                "Average frame rate {fps:.0} is below {target_fps}, lowering quality to {lower:?}"
+            );
+            quality.preset = lower;
+        }
+    }
+}
+
+fn apply_quality(quality: Res<Quality>, mut commands: Commands) {
+    let size = quality.preset.shadow_map_size();
+    commands.insert_resource(DirectionalLightShadowMap { size });
+    commands.insert_resource(PointLightShadowMap { size });
+}
+
+// Sleeps at the end of the frame, so the frame
+// does not finish faster than the limit allows
+#[cfg(not(target_arch = "wasm32"))]
+fn display_limit_fps(
+    display_settings: Res<DisplaySettings>,
+    mut last_frame_end: Local<Option<Instant>>,
+) {
+    let now = Instant::now();
+    let Some(max_fps) = display_settings.max_fps() else {
+        *last_frame_end = Some(now);
+        return;
+    };
+
+    if let Some(last_frame_end) = *last_frame_end {
+        let frame_time = Duration::from_secs_f64(1.0 / max_fps.max(1) as f64);
+        let elapsed = now - last_frame_end;
+        if elapsed < frame_time {
+            std::thread::sleep(frame_time - elapsed);
+        }
+    }
+    *last_frame_end = Some(Instant::now());
+}
+
+// Unconfirmed settings are not saved, so the game
+// does not start with a broken display mode
+fn save_display_settings(
+    display_settings: Res<DisplaySettings>,
+    display_confirmation: Res<DisplayConfirmation>,
+) {
+    if display_confirmation.remaining().is_some() {
+        return;
+    }
+    save_ron(DISPLAY_SETTINGS_PATH, display_settings.as_ref());
+}
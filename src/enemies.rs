@@ -1,16 +1,33 @@
-use bevy::prelude::*;
+use bevy::{pbr::NotShadowCaster, prelude::*, utils::HashMap};
 use bevy_asset_loader::prelude::*;
+use bevy_kira_audio::{
+    AudioApp, AudioChannel, AudioControl, AudioInstance, AudioSource, AudioTween,
+};
 use bevy_rapier3d::prelude::*;
+use rand::{
+    distributions::{Distribution, Standard},
+    Rng,
+};
 
 use crate::{
-    damage::{Health, KillEvent},
-    level::{LevelObject, LevelStarted},
-    player::Player,
+    animation::Animation,
+    collision::{CollisionLayer, COLLISION_GROUP_LEVEL},
+    damage::{ContactDamageEvent, DamageEvent, Health, KillEvent},
+    director::Director,
+    display::Quality,
+    impacts::SurfaceMaterial,
+    level::{blackout::Blackout, LevelInfo, LevelObject, LevelStarted},
+    player::{
+        Player, PlayerCamera, PlayerId, PlayerVelocity, PlayerWeapon, PLAYER_COLLIDER_RADIUS,
+    },
+    rng::{GameRng, RngStream},
+    sequence::sequence_playing,
+    sound::{SoundLimit, SoundPool},
     weapons::{
-        attach_weapon, floating::FloatingObjectBundle, ShootEvent, WeaponAssets, WeaponAttackTimer,
-        WeaponBundle, WeaponModel,
+        ammo::AmmoType, attach_weapon, floating::FloatingObjectBundle, ShootEvent, Weapon,
+        WeaponAssets, WeaponAttackTimer, WeaponBundle, WeaponModel,
     },
-    GlobalState, COLLISION_GROUP_ENEMY, COLLISION_GROUP_LEVEL, COLLISION_GROUP_PROJECTILES,
+    GameSettings, GlobalState, SfxChannel,
 };
 
 // Small enemy
@@ -97,12 +114,134 @@ const ENEMY_BIG_ROTATION_SPEED: f32 = 0.5;
 const ENEMY_BIG_MIN_DISTANCE: f32 = 200.0;
 const ENEMY_BIG_WEAPON_OFFSET: Vec3 = Vec3::new(2.0, 2.2, 0.5);
 
+// Elite enemy
+const ENEMY_ELITE_HEALTH_MODIFIER: i32 = 2;
+const ENEMY_ELITE_SCALE_MODIFIER: f32 = 1.2;
+const ENEMY_ELITE_ARMORED_HEALTH_MODIFIER: i32 = 2;
+const ENEMY_ELITE_SWIFT_SPEED_MODIFIER: f32 = 1.5;
+const ENEMY_ELITE_RELENTLESS_MIN_DISTANCE_MODIFIER: f32 = 0.25;
+
+// Shield
+const ENEMY_SHIELD_THICKNESS: f32 = 0.2;
+const ENEMY_SHIELD_COLOR: Color = Color::SILVER;
+const ENEMY_SMALL_SHIELD_HEALTH: i32 = 30;
+const ENEMY_MID_SHIELD_HEALTH: i32 = 60;
+const ENEMY_BIG_SHIELD_HEALTH: i32 = 200;
+// Shield opens before every attack and closes right after,
+// so enemy only shoots while the door is opened enough
+const ENEMY_SHIELD_ANIMATION_SPEED: f32 = 2.0;
+const ENEMY_SHIELD_ATTACK_PROGRESS: f32 = 0.5;
+
+// Patrol
+const ENEMY_PATROL_SPEED_MODIFIER: f32 = 0.5;
+const ENEMY_PATROL_WAYPOINT_DISTANCE: f32 = 1.0;
+// Enemy gives up on a waypoint it can't reach
+const ENEMY_PATROL_WAYPOINT_TIMEOUT: f32 = 5.0;
+const ENEMY_PATROL_SCAN_TIME: f32 = 2.0;
+const ENEMY_PATROL_SCAN_ANGLE: f32 = std::f32::consts::FRAC_PI_4;
+const ENEMY_PATROL_SCAN_SPEED: f32 = 3.0;
+// Player this close is noticed even if not seen
+const ENEMY_PATROL_NOTICE_DISTANCE: f32 = 10.0;
+// Player hidden by the blackout is noticed only up close
+const ENEMY_PATROL_DARK_NOTICE_DISTANCE: f32 = 4.0;
+// Every column or door between the shot and
+// the enemy reduces distance the shot is heard at
+const ENEMY_HEARING_OCCLUSION: f32 = 0.4;
+
+// Separation
+// Chasing enemies steer away from each other,
+// so groups surround the player instead of stacking
+const ENEMY_SEPARATION_RADIUS: f32 = 6.0;
+const ENEMY_SEPARATION_WEIGHT: f32 = 1.5;
+
+// Spatial grid
+// Size of the grid cells on the XY plane. Queries
+// check all cells overlapped by the query radius.
+const ENEMY_GRID_CELL_SIZE: f32 = 10.0;
+
+// Stun
+// Meter is filled by damage and drains over time,
+// so only rapid damage can stagger the enemy
+const ENEMY_STUN_PER_DAMAGE: f32 = 0.02;
+const ENEMY_STUN_DECAY: f32 = 0.25;
+const ENEMY_STUN_STAGGER_TIME: f32 = 2.0;
+// After the stagger meter fills slower for some time
+const ENEMY_STUN_RESISTANCE: f32 = 0.5;
+const ENEMY_STUN_RESISTANCE_TIME: f32 = 5.0;
+
+// Health bar and stun pips above the enemy
+const ENEMY_BAR_MARGIN: f32 = 1.0;
+const ENEMY_BAR_WIDTH: f32 = 2.0;
+const ENEMY_BAR_HEIGHT: f32 = 0.2;
+const ENEMY_BAR_PIPS: u32 = 5;
+const ENEMY_BAR_PIP_WIDTH: f32 = 0.3;
+const ENEMY_BAR_PIP_HEIGHT: f32 = 0.15;
+const ENEMY_BAR_PIP_GAP: f32 = 0.1;
+const ENEMY_BAR_BACKGROUND_COLOR: Color = Color::BLACK;
+const ENEMY_BAR_HEALTH_COLOR: Color = Color::RED;
+const ENEMY_BAR_PIP_EMPTY_COLOR: Color = Color::DARK_GRAY;
+const ENEMY_BAR_PIP_FULL_COLOR: Color = Color::YELLOW;
+
+// Spawn telegraph
+const ENEMY_SPAWN_TELEGRAPH_TIME: f32 = 1.5;
+const ENEMY_SPAWN_SWIRL_RADIUS: f32 = 2.0;
+const ENEMY_SPAWN_SWIRL_RING_RADIUS: f32 = 0.1;
+const ENEMY_SPAWN_SWIRL_COLOR: Color = Color::rgba(0.6, 0.9, 1.0, 0.6);
+const ENEMY_SPAWN_SWIRL_TILT: f32 = 0.3;
+const ENEMY_SPAWN_SWIRL_ROTATION_SPEED: f32 = 8.0;
+const ENEMY_SPAWN_SOUND_MAX_DISTANCE: f32 = 200.0;
+
+// Hum
+const ENEMY_HUM_MAX_DISTANCE: f32 = 80.0;
+// Hum behind the level geometry is quieter
+const ENEMY_HUM_OCCLUSION_VOLUME: f32 = 0.25;
+const ENEMY_HUM_TWEEN_MILLIS: u64 = 100;
+
+// Footsteps
+// Bigger fridges take longer steps
+const ENEMY_SMALL_STEP_LENGTH: f32 = 3.0;
+const ENEMY_MID_STEP_LENGTH: f32 = 4.0;
+const ENEMY_BIG_STEP_LENGTH: f32 = 6.0;
+const ENEMY_FOOTSTEP_MAX_DISTANCE: f32 = 60.0;
+const ENEMY_FOOTSTEP_VOLUME: f32 = 0.5;
+const ENEMY_FOOTSTEP_SOUND_LIMIT: SoundLimit = SoundLimit {
+    max_voices: 4,
+    cooldown: 0.08,
+    pitch_variation: 0.15,
+};
+
+// Contact
+// Player touching the enemy is hurt and pushed away.
+// Each enemy can hit again only after its cooldown.
+const ENEMY_SMALL_CONTACT_DAMAGE: i32 = 10;
+const ENEMY_MID_CONTACT_DAMAGE: i32 = 20;
+const ENEMY_BIG_CONTACT_DAMAGE: i32 = 40;
+const ENEMY_SMALL_CONTACT_COOLDOWN: f32 = 0.5;
+const ENEMY_MID_CONTACT_COOLDOWN: f32 = 0.8;
+const ENEMY_BIG_CONTACT_COOLDOWN: f32 = 1.5;
+const ENEMY_SMALL_CONTACT_KNOCKBACK: f32 = 15.0;
+const ENEMY_MID_CONTACT_KNOCKBACK: f32 = 25.0;
+const ENEMY_BIG_CONTACT_KNOCKBACK: f32 = 40.0;
+// Extra damage per unit of director difficulty
+const ENEMY_CONTACT_DIFFICULTY_DAMAGE: f32 = 0.5;
+// Character controllers stop before touching,
+// so contact is checked with a small margin
+const ENEMY_CONTACT_MARGIN: f32 = 0.2;
+
+// Boss enters the second phase at this fraction of health
+const ENEMY_BOSS_PHASE_HEALTH: f32 = 0.5;
+
 pub struct EnemiesPlugin;
 
 impl Plugin for EnemiesPlugin {
     fn build(&self, app: &mut App) {
         app.add_collection_to_loading_state::<_, EnemyAssets>(GlobalState::AssetLoading);
 
+        app.add_event::<NoiseEvent>();
+        app.add_event::<LastEnemyKilled>();
+        app.add_event::<BossPhaseChanged>();
+        app.init_resource::<EnemyGrid>();
+
         app.add_systems(
             OnTransition {
                 from: GlobalState::AssetLoading,
@@ -113,9 +252,93 @@ impl Plugin for EnemiesPlugin {
 
         app.add_systems(
             Update,
-            (enemy_enable, enemy_move, enemy_shoot, enemy_die)
+            (
+                enemy_grid_update,
+                enemy_enable,
+                enemy_spawn_telegraph,
+                enemy_spawn_telegraph_sound,
+                enemy_spawn_shield,
+                enemy_spawn_bar,
+                enemy_stun,
+                enemy_bar_update,
+                enemy_alert,
+                enemy_hear,
+                enemy_patrol,
+                enemy_move,
+                enemy_shoot,
+                enemy_contact,
+                enemy_shield_destroyed,
+                enemy_die,
+            )
+                .run_if(in_state(GlobalState::InGame).and_then(not(sequence_playing))),
+        );
+
+        app.add_audio_channel::<EnemyHumChannel>();
+        // hums are started again for all active
+        // enemies when game is resumed
+        app.add_systems(OnExit(GlobalState::InGame), enemy_hum_stop_all);
+        app.add_systems(
+            Update,
+            (enemy_hum_start, enemy_hum_update, enemy_hum_stop)
                 .run_if(in_state(GlobalState::InGame)),
         );
+        app.add_systems(
+            Update,
+            enemy_footsteps.run_if(in_state(GlobalState::InGame).and_then(not(sequence_playing))),
+        );
+        app.add_systems(
+            Update,
+            enemy_boss_phase.run_if(in_state(GlobalState::InGame)),
+        );
+    }
+}
+
+// Looped enemy hums are played in their own
+// channel, so all of them can be stopped at once
+#[derive(Resource)]
+pub struct EnemyHumChannel;
+
+// Loud sound patrolling enemies can hear. Player
+// shots are turned into noises automatically.
+#[derive(Clone, Copy, Event)]
+pub struct NoiseEvent {
+    pub translation: Vec3,
+    pub loudness: f32,
+}
+
+/// Active enemies bucketed by their position on the XY plane.
+/// Rebuilt every frame, so it lets systems look up enemies
+/// near a point without going through all of them.
+#[derive(Debug, Default, Resource)]
+pub struct EnemyGrid {
+    cells: HashMap<(i32, i32), Vec<(Entity, Vec3)>>,
+}
+
+impl EnemyGrid {
+    fn cell(translation: Vec3) -> (i32, i32) {
+        (
+            (translation.x / ENEMY_GRID_CELL_SIZE).floor() as i32,
+            (translation.y / ENEMY_GRID_CELL_SIZE).floor() as i32,
+        )
+    }
+
+    /// Enemies closer than `radius` to the `translation`
+    /// on the XY plane with their translations.
+    pub fn enemies_within_radius(
+        &self,
+        translation: Vec3,
+        radius: f32,
+    ) -> impl Iterator<Item = (Entity, Vec3)> + '_ {
+        let (min_x, min_y) = Self::cell(translation - Vec3::new(radius, radius, 0.0));
+        let (max_x, max_y) = Self::cell(translation + Vec3::new(radius, radius, 0.0));
+        (min_y..=max_y)
+            .flat_map(move |y| (min_x..=max_x).map(move |x| (x, y)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+            .filter(move |(_, enemy_translation)| {
+                enemy_translation.xy().distance_squared(translation.xy()) < radius * radius
+            })
     }
 }
 
@@ -127,6 +350,12 @@ pub struct EnemyAssets {
     pub mid_enemy_scene: Handle<Scene>,
     #[asset(path = "enemies/big_fridge.glb#Scene0")]
     pub big_enemy_scene: Handle<Scene>,
+    #[asset(path = "enemies/spawn_hum.wav")]
+    pub spawn_hum: Handle<AudioSource>,
+    #[asset(path = "enemies/fridge_hum.wav")]
+    pub fridge_hum: Handle<AudioSource>,
+    #[asset(path = "enemies/fridge_step.wav")]
+    pub fridge_step: Handle<AudioSource>,
 }
 
 #[derive(Resource)]
@@ -137,9 +366,18 @@ pub struct EnemyResources {
     mid_part_material: Handle<StandardMaterial>,
     big_part_mesh: Handle<Mesh>,
     big_part_material: Handle<StandardMaterial>,
+    shield_mesh: Handle<Mesh>,
+    shield_material: Handle<StandardMaterial>,
+    swirl_mesh: Handle<Mesh>,
+    swirl_material: Handle<StandardMaterial>,
+    bar_mesh: Handle<Mesh>,
+    bar_background_material: Handle<StandardMaterial>,
+    bar_health_material: Handle<StandardMaterial>,
+    bar_pip_empty_material: Handle<StandardMaterial>,
+    bar_pip_full_material: Handle<StandardMaterial>,
 }
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum EnemyType {
     Small,
     #[default]
@@ -147,21 +385,265 @@ pub enum EnemyType {
     Big,
 }
 
+impl EnemyType {
+    fn step_length(&self) -> f32 {
+        match self {
+            EnemyType::Small => ENEMY_SMALL_STEP_LENGTH,
+            EnemyType::Mid => ENEMY_MID_STEP_LENGTH,
+            EnemyType::Big => ENEMY_BIG_STEP_LENGTH,
+        }
+    }
+
+    fn contact_damage(&self) -> i32 {
+        match self {
+            EnemyType::Small => ENEMY_SMALL_CONTACT_DAMAGE,
+            EnemyType::Mid => ENEMY_MID_CONTACT_DAMAGE,
+            EnemyType::Big => ENEMY_BIG_CONTACT_DAMAGE,
+        }
+    }
+
+    fn contact_cooldown(&self) -> f32 {
+        match self {
+            EnemyType::Small => ENEMY_SMALL_CONTACT_COOLDOWN,
+            EnemyType::Mid => ENEMY_MID_CONTACT_COOLDOWN,
+            EnemyType::Big => ENEMY_BIG_CONTACT_COOLDOWN,
+        }
+    }
+
+    fn contact_knockback(&self) -> f32 {
+        match self {
+            EnemyType::Small => ENEMY_SMALL_CONTACT_KNOCKBACK,
+            EnemyType::Mid => ENEMY_MID_CONTACT_KNOCKBACK,
+            EnemyType::Big => ENEMY_BIG_CONTACT_KNOCKBACK,
+        }
+    }
+
+    fn collider_half_size(&self) -> Vec3 {
+        match self {
+            EnemyType::Small => Vec3::new(
+                ENEMY_SMALL_COLLIDER_DIMENTION_X,
+                ENEMY_SMALL_COLLIDER_DIMENTION_Y,
+                ENEMY_SMALL_COLLIDER_DIMENTION_Z,
+            ),
+            EnemyType::Mid => Vec3::new(
+                ENEMY_MID_COLLIDER_DIMENTION_X,
+                ENEMY_MID_COLLIDER_DIMENTION_Y,
+                ENEMY_MID_COLLIDER_DIMENTION_Z,
+            ),
+            EnemyType::Big => Vec3::new(
+                ENEMY_BIG_COLLIDER_DIMENTION_X,
+                ENEMY_BIG_COLLIDER_DIMENTION_Y,
+                ENEMY_BIG_COLLIDER_DIMENTION_Z,
+            ),
+        }
+    }
+}
+
 #[derive(Default, Component)]
 pub struct Enemy {
-    enemy_type: EnemyType,
+    pub enemy_type: EnemyType,
+    max_health: i32,
     speed: f32,
     rotation_speed: f32,
     min_distance: f32,
     attached_weapon: Option<Entity>,
+    shield: Option<Entity>,
+}
+
+impl Enemy {
+    pub fn max_health(&self) -> i32 {
+        self.max_health
+    }
+}
+
+// Decisions of the enemy behavior script. Only
+// enemies with a script have this component.
+#[derive(Debug, Clone, Copy, Component)]
+pub struct EnemyScripted {
+    // Enemy moves to this point relative to the player
+    pub target_offset: Vec2,
+    // Enemy does not shoot even if it sees the player
+    pub hold_fire: bool,
+    pub speed_modifier: f32,
+}
+
+impl Default for EnemyScripted {
+    fn default() -> Self {
+        Self {
+            target_offset: Vec2::ZERO,
+            hold_fire: false,
+            speed_modifier: 1.0,
+        }
+    }
+}
+
+// Elite modifiers only appear in the New Game+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EliteModifier {
+    Armored,
+    Swift,
+    Relentless,
+    // Pulls the weapon out of the player hands
+    Magnetic,
+}
+
+impl Distribution<EliteModifier> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> EliteModifier {
+        match rng.gen_range(0..4) {
+            0 => EliteModifier::Armored,
+            1 => EliteModifier::Swift,
+            2 => EliteModifier::Relentless,
+            3 => EliteModifier::Magnetic,
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Elite {
+    pub modifier: Option<EliteModifier>,
 }
 
 #[derive(Component)]
 pub struct EnemyWeapon;
 
+// Frontal door of the fridge. Blocks player projectiles
+// until destroyed or opened for an attack.
+#[derive(Component)]
+pub struct EnemyShield {
+    closed_transform: Transform,
+    opened_transform: Transform,
+}
+
+impl EnemyShield {
+    fn is_opened(animation: &Animation) -> bool {
+        if animation.animate_forward {
+            ENEMY_SHIELD_ATTACK_PROGRESS <= animation.progress
+        } else {
+            animation.progress <= 1.0 - ENEMY_SHIELD_ATTACK_PROGRESS
+        }
+    }
+}
+
 #[derive(Component)]
 pub struct DisabledEnemy;
 
+// Enemy spawned in the middle of the level. It
+// appears without waiting for the level start.
+#[derive(Component)]
+pub struct EnemyReinforcement;
+
+// Disabled enemies are hidden and appear only
+// after the telegraph at their position is finished
+#[derive(Component)]
+pub struct EnemySpawnTelegraph {
+    timer: Timer,
+    swirl: Entity,
+}
+
+#[derive(Component)]
+struct EnemySpawnSwirl;
+
+// Un-alerted enemies walk in a loop and scan around at each
+// waypoint. Patrol is removed once enemy notices the player.
+#[derive(Component)]
+pub struct EnemyPatrol {
+    waypoints: Vec<Vec3>,
+    current: usize,
+    walk_timer: Timer,
+    scan_timer: Timer,
+    scan_rotation: Option<Quat>,
+}
+
+// Enemy is staggered when the meter is full
+#[derive(Component)]
+pub struct EnemyStun {
+    meter: f32,
+    last_health: i32,
+    resistance_timer: Timer,
+}
+
+impl EnemyStun {
+    fn new(health: i32) -> Self {
+        // resistance is not active at the start
+        let mut resistance_timer = Timer::from_seconds(ENEMY_STUN_RESISTANCE_TIME, TimerMode::Once);
+        resistance_timer.tick(resistance_timer.duration());
+        Self {
+            meter: 0.0,
+            last_health: health,
+            resistance_timer,
+        }
+    }
+
+    // Fills the meter without damage, e.g. shock ammo
+    pub fn add(&mut self, amount: f32) {
+        self.meter = (self.meter + amount).min(1.0);
+    }
+}
+
+// Staggered enemy does not move or shoot
+// and its shield is opened
+#[derive(Component)]
+pub struct EnemyStaggered {
+    timer: Timer,
+}
+
+#[derive(Component)]
+struct EnemyHum {
+    instance: Handle<AudioInstance>,
+}
+
+// Distance walked since the last step
+#[derive(Component)]
+struct EnemyFootsteps {
+    last_translation: Vec3,
+    distance: f32,
+}
+
+/// Sent when the last enemy of the level dies
+#[derive(Debug, Clone, Copy, Event)]
+pub struct LastEnemyKilled {
+    pub translation: Vec3,
+}
+
+/// Sent once when the boss health drops
+/// below the second phase threshold
+#[derive(Debug, Clone, Copy, Event)]
+pub struct BossPhaseChanged {
+    pub entity: Entity,
+}
+
+/// Piece of a dead enemy
+#[derive(Component)]
+pub struct EnemyPart;
+
+// Time until the enemy can hit the player by touching again
+#[derive(Component)]
+struct EnemyContact {
+    cooldown: Timer,
+}
+
+// Bar is not a child of the enemy, so
+// it is not affected by enemy rotation
+#[derive(Component)]
+struct EnemyBar {
+    enemy: Entity,
+    health: Entity,
+    pips: Vec<Entity>,
+}
+
+impl EnemyPatrol {
+    fn new(waypoints: Vec<Vec3>) -> Self {
+        Self {
+            waypoints,
+            current: 0,
+            walk_timer: Timer::from_seconds(ENEMY_PATROL_WAYPOINT_TIMEOUT, TimerMode::Once),
+            scan_timer: Timer::from_seconds(ENEMY_PATROL_SCAN_TIME, TimerMode::Once),
+            scan_rotation: None,
+        }
+    }
+}
+
 #[derive(Bundle)]
 pub struct EnemyBundle {
     rigid_body: RigidBody,
@@ -173,7 +655,9 @@ pub struct EnemyBundle {
 
     scene_bundle: SceneBundle,
     health: Health,
+    surface_material: SurfaceMaterial,
     disabled: DisabledEnemy,
+    disabled_body: RigidBodyDisabled,
 
     level_object: LevelObject,
 }
@@ -183,14 +667,12 @@ impl Default for EnemyBundle {
         Self {
             rigid_body: RigidBody::KinematicPositionBased,
             collider: Collider::default(),
-            collision_groups: CollisionGroups::new(
-                COLLISION_GROUP_ENEMY,
-                COLLISION_GROUP_LEVEL | COLLISION_GROUP_PROJECTILES,
-            ),
+            collision_groups: CollisionLayer::Enemy.groups(),
             controller: KinematicCharacterController {
                 up: Vec3::Z,
                 offset: CharacterLength::Relative(0.1),
                 filter_flags: QueryFilterFlags::EXCLUDE_SENSORS | QueryFilterFlags::EXCLUDE_DYNAMIC,
+                filter_groups: Some(CollisionLayer::Enemy.groups()),
                 ..default()
             },
             locked_axis: LockedAxes::TRANSLATION_LOCKED_Z,
@@ -198,7 +680,9 @@ impl Default for EnemyBundle {
 
             scene_bundle: SceneBundle::default(),
             health: Health::default(),
+            surface_material: SurfaceMaterial::Fridge,
             disabled: DisabledEnemy,
+            disabled_body: RigidBodyDisabled,
 
             level_object: LevelObject,
         }
@@ -240,6 +724,37 @@ fn init_resources(
     );
     let big_part_material = materials.add(Color::RED.into());
 
+    // shields are scaled to the size of the enemy
+    let shield_mesh = meshes.add(shape::Cube::new(1.0).into());
+    let shield_material = materials.add(ENEMY_SHIELD_COLOR.into());
+
+    let swirl_mesh = meshes.add(
+        shape::Torus {
+            radius: ENEMY_SPAWN_SWIRL_RADIUS,
+            ring_radius: ENEMY_SPAWN_SWIRL_RING_RADIUS,
+            ..default()
+        }
+        .into(),
+    );
+    let swirl_material = materials.add(StandardMaterial {
+        base_color: ENEMY_SPAWN_SWIRL_COLOR,
+        emissive: ENEMY_SPAWN_SWIRL_COLOR,
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        ..default()
+    });
+
+    let bar_mesh = meshes.add(shape::Quad::new(Vec2::ONE).into());
+    let bar_material = |color: Color| StandardMaterial {
+        base_color: color,
+        unlit: true,
+        ..default()
+    };
+    let bar_background_material = materials.add(bar_material(ENEMY_BAR_BACKGROUND_COLOR));
+    let bar_health_material = materials.add(bar_material(ENEMY_BAR_HEALTH_COLOR));
+    let bar_pip_empty_material = materials.add(bar_material(ENEMY_BAR_PIP_EMPTY_COLOR));
+    let bar_pip_full_material = materials.add(bar_material(ENEMY_BAR_PIP_FULL_COLOR));
+
     commands.insert_resource(EnemyResources {
         small_part_mesh,
         small_part_material,
@@ -247,6 +762,15 @@ fn init_resources(
         mid_part_material,
         big_part_mesh,
         big_part_material,
+        shield_mesh,
+        shield_material,
+        swirl_mesh,
+        swirl_material,
+        bar_mesh,
+        bar_background_material,
+        bar_health_material,
+        bar_pip_empty_material,
+        bar_pip_full_material,
     });
 }
 
@@ -254,10 +778,12 @@ pub fn spawn_enemy(
     enemy_assets: &EnemyAssets,
     weapons_assets: &WeaponAssets,
     enemy_type: EnemyType,
+    elite: Option<Elite>,
+    patrol_route: Vec<Vec3>,
     commands: &mut Commands,
     transform: Transform,
-) {
-    let (weapon_offset, health, collider, mut enemy, scene) = match enemy_type {
+) -> Entity {
+    let (weapon_offset, mut health, collider, mut enemy, scene) = match enemy_type {
         EnemyType::Small => (
             ENEMY_SMALL_WEAPON_OFFSET,
             ENEMY_SMALL_HEALTH,
@@ -268,10 +794,12 @@ pub fn spawn_enemy(
             ),
             Enemy {
                 enemy_type,
+                max_health: 0,
                 speed: ENEMY_SMALL_SPEED,
                 rotation_speed: ENEMY_SMALL_ROTATION_SPEED,
                 min_distance: ENEMY_SMALL_MIN_DISTANCE,
                 attached_weapon: None,
+                shield: None,
             },
             enemy_assets.small_enemy_scene.clone(),
         ),
@@ -285,10 +813,12 @@ pub fn spawn_enemy(
             ),
             Enemy {
                 enemy_type,
+                max_health: 0,
                 speed: ENEMY_MID_SPEED,
                 rotation_speed: ENEMY_MID_ROTATION_SPEED,
                 min_distance: ENEMY_MID_MIN_DISTANCE,
                 attached_weapon: None,
+                shield: None,
             },
             enemy_assets.mid_enemy_scene.clone(),
         ),
@@ -302,10 +832,12 @@ pub fn spawn_enemy(
             ),
             Enemy {
                 enemy_type,
+                max_health: 0,
                 speed: ENEMY_BIG_SPEED,
                 rotation_speed: ENEMY_BIG_ROTATION_SPEED,
                 min_distance: ENEMY_BIG_MIN_DISTANCE,
                 attached_weapon: None,
+                shield: None,
             },
             enemy_assets.big_enemy_scene.clone(),
         ),
@@ -342,33 +874,434 @@ pub fn spawn_enemy(
         .id(),
     };
 
+    let mut scale = Vec3::new(1.5, 1.5, 1.5);
+    if let Some(elite) = &elite {
+        health *= ENEMY_ELITE_HEALTH_MODIFIER;
+        scale *= ENEMY_ELITE_SCALE_MODIFIER;
+        match elite.modifier {
+            Some(EliteModifier::Armored) => health *= ENEMY_ELITE_ARMORED_HEALTH_MODIFIER,
+            Some(EliteModifier::Swift) => {
+                enemy.speed *= ENEMY_ELITE_SWIFT_SPEED_MODIFIER;
+                enemy.rotation_speed *= ENEMY_ELITE_SWIFT_SPEED_MODIFIER;
+            }
+            Some(EliteModifier::Relentless) => {
+                enemy.min_distance *= ENEMY_ELITE_RELENTLESS_MIN_DISTANCE_MODIFIER
+            }
+            // magnet is attached by the `MagnetPlugin`
+            Some(EliteModifier::Magnetic) | None => {}
+        }
+    }
+
+    enemy.max_health = health;
     enemy.attached_weapon = Some(weapon);
-    commands
-        .spawn(EnemyBundle {
-            scene_bundle: SceneBundle {
-                scene,
-                transform: transform.with_scale(Vec3::new(1.5, 1.5, 1.5)),
-                ..default()
-            },
-            enemy,
-            health: Health { health },
-            collider,
+    let mut enemy_commands = commands.spawn(EnemyBundle {
+        scene_bundle: SceneBundle {
+            scene,
+            transform: transform.with_scale(scale),
+            visibility: Visibility::Hidden,
             ..default()
-        })
-        .add_child(weapon);
+        },
+        enemy,
+        health: Health { health },
+        collider,
+        ..default()
+    });
+    enemy_commands.add_child(weapon);
+    if let Some(elite) = elite {
+        enemy_commands.insert(elite);
+    }
+    enemy_commands.insert(EnemyStun::new(health));
+    enemy_commands.insert(EnemyFootsteps {
+        last_translation: transform.translation,
+        distance: 0.0,
+    });
+    let mut cooldown = Timer::from_seconds(enemy_type.contact_cooldown(), TimerMode::Once);
+    cooldown.tick(cooldown.duration());
+    enemy_commands.insert(EnemyContact { cooldown });
+    if !patrol_route.is_empty() {
+        enemy_commands.insert(EnemyPatrol::new(patrol_route));
+    }
+    enemy_commands.id()
 }
 
+fn enemy_grid_update(
+    enemies: Query<(Entity, &Transform), (With<Enemy>, Without<DisabledEnemy>)>,
+    mut enemy_grid: ResMut<EnemyGrid>,
+) {
+    // cells are kept to reuse their allocations
+    for cell in enemy_grid.cells.values_mut() {
+        cell.clear();
+    }
+    for (entity, transform) in enemies.iter() {
+        enemy_grid
+            .cells
+            .entry(EnemyGrid::cell(transform.translation))
+            .or_default()
+            .push((entity, transform.translation));
+    }
+}
+
+#[allow(clippy::complexity)]
 fn enemy_enable(
-    enemies: Query<Entity, With<DisabledEnemy>>,
+    level_info: Res<LevelInfo>,
+    enemy_resources: Res<EnemyResources>,
+    enemies: Query<
+        (Entity, &Transform, Has<EnemyReinforcement>),
+        (With<DisabledEnemy>, Without<EnemySpawnTelegraph>),
+    >,
     mut commands: Commands,
     mut level_started_events: EventReader<LevelStarted>,
 ) {
-    for _ in level_started_events.read() {
-        for enemy in enemies.iter() {
+    let level_started = level_started_events.read().count() != 0;
+    for (enemy, transform, reinforcement) in enemies.iter() {
+        if level_started || level_info.enemies_alerted || reinforcement {
+            let swirl = commands
+                .spawn((
+                    PbrBundle {
+                        mesh: enemy_resources.swirl_mesh.clone(),
+                        material: enemy_resources.swirl_material.clone(),
+                        transform: Transform::from_translation(transform.translation)
+                            .with_scale(Vec3::ZERO),
+                        ..default()
+                    },
+                    NotShadowCaster,
+                    EnemySpawnSwirl,
+                    LevelObject,
+                ))
+                .id();
             commands
                 .get_entity(enemy)
                 .unwrap()
-                .remove::<DisabledEnemy>();
+                .insert(EnemySpawnTelegraph {
+                    timer: Timer::from_seconds(ENEMY_SPAWN_TELEGRAPH_TIME, TimerMode::Once),
+                    swirl,
+                });
+        }
+    }
+}
+
+fn enemy_spawn_telegraph(
+    time: Res<Time>,
+    mut enemies: Query<(Entity, &mut EnemySpawnTelegraph, &mut Visibility)>,
+    mut swirls: Query<&mut Transform, With<EnemySpawnSwirl>>,
+    mut commands: Commands,
+) {
+    for (enemy, mut telegraph, mut visibility) in enemies.iter_mut() {
+        telegraph.timer.tick(time.delta());
+
+        if let Ok(mut swirl_transform) = swirls.get_mut(telegraph.swirl) {
+            swirl_transform.scale = Vec3::splat(telegraph.timer.percent());
+            // torus is in the XZ plane by default
+            swirl_transform.rotation =
+                Quat::from_rotation_z(time.elapsed_seconds() * ENEMY_SPAWN_SWIRL_ROTATION_SPEED)
+                    * Quat::from_rotation_x(std::f32::consts::FRAC_PI_2 + ENEMY_SPAWN_SWIRL_TILT);
+        }
+
+        if telegraph.timer.finished() {
+            *visibility = Visibility::Inherited;
+            commands.get_entity(enemy).unwrap().remove::<(
+                EnemySpawnTelegraph,
+                DisabledEnemy,
+                RigidBodyDisabled,
+            )>();
+            if let Some(e) = commands.get_entity(telegraph.swirl) {
+                e.despawn_recursive();
+            }
+        }
+    }
+}
+
+fn enemy_spawn_telegraph_sound(
+    sfx: Res<AudioChannel<SfxChannel>>,
+    enemy_assets: Res<EnemyAssets>,
+    game_settings: Res<GameSettings>,
+    player_cameras: Query<(&PlayerId, &GlobalTransform), With<PlayerCamera>>,
+    enemies: Query<&GlobalTransform, Added<EnemySpawnTelegraph>>,
+) {
+    let Some((_, camera_global_transform)) = player_cameras.iter().find(|(id, _)| id.is_local())
+    else {
+        return;
+    };
+
+    for enemy_global_transform in enemies.iter() {
+        let v = enemy_global_transform.translation() - camera_global_transform.translation();
+        let attenuation = (1.0 - v.length() / ENEMY_SPAWN_SOUND_MAX_DISTANCE).clamp(0.0, 1.0);
+        let panning = 0.5 + 0.5 * camera_global_transform.right().dot(v.normalize_or_zero());
+
+        sfx.play(enemy_assets.spawn_hum.clone())
+            .with_volume((game_settings.volume * attenuation) as f64)
+            .with_panning(panning as f64);
+    }
+}
+
+fn enemy_spawn_shield(
+    enemy_resources: Res<EnemyResources>,
+    mut enemies: Query<(Entity, &mut Enemy), Added<Enemy>>,
+    mut commands: Commands,
+) {
+    for (enemy_entity, mut enemy) in enemies.iter_mut() {
+        let half_size = enemy.enemy_type.collider_half_size();
+        let health = match enemy.enemy_type {
+            EnemyType::Small => ENEMY_SMALL_SHIELD_HEALTH,
+            EnemyType::Mid => ENEMY_MID_SHIELD_HEALTH,
+            EnemyType::Big => ENEMY_BIG_SHIELD_HEALTH,
+        };
+
+        let scale = Vec3::new(half_size.x * 2.0, ENEMY_SHIELD_THICKNESS, half_size.z * 2.0);
+        let closed_transform =
+            Transform::from_xyz(0.0, half_size.y + ENEMY_SHIELD_THICKNESS / 2.0, 0.0)
+                .with_scale(scale);
+        // door swings around the hinge on the left side,
+        // away from the attached weapon
+        let opened_transform = Transform::from_xyz(
+            -half_size.x - ENEMY_SHIELD_THICKNESS / 2.0,
+            half_size.y + half_size.x,
+            0.0,
+        )
+        .with_rotation(Quat::from_rotation_z(-std::f32::consts::FRAC_PI_2))
+        .with_scale(scale);
+
+        let shield = commands
+            .spawn((
+                PbrBundle {
+                    mesh: enemy_resources.shield_mesh.clone(),
+                    material: enemy_resources.shield_material.clone(),
+                    transform: closed_transform,
+                    ..default()
+                },
+                // collider is scaled with the transform
+                Collider::cuboid(0.5, 0.5, 0.5),
+                CollisionLayer::EnemyShield.groups(),
+                Health { health },
+                EnemyShield {
+                    closed_transform,
+                    opened_transform,
+                },
+            ))
+            .id();
+        commands.entity(enemy_entity).add_child(shield);
+        enemy.shield = Some(shield);
+    }
+}
+
+fn enemy_spawn_bar(
+    enemy_resources: Res<EnemyResources>,
+    enemies: Query<Entity, Added<Enemy>>,
+    mut commands: Commands,
+) {
+    for enemy in enemies.iter() {
+        let mut health = Entity::PLACEHOLDER;
+        let mut pips = vec![];
+        commands
+            .spawn((
+                SpatialBundle {
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                LevelObject,
+            ))
+            .with_children(|builder| {
+                builder.spawn((
+                    PbrBundle {
+                        mesh: enemy_resources.bar_mesh.clone(),
+                        material: enemy_resources.bar_background_material.clone(),
+                        transform: Transform::from_scale(Vec3::new(
+                            ENEMY_BAR_WIDTH,
+                            ENEMY_BAR_HEIGHT,
+                            1.0,
+                        )),
+                        ..default()
+                    },
+                    NotShadowCaster,
+                ));
+                health =
+                    builder
+                        .spawn((
+                            PbrBundle {
+                                mesh: enemy_resources.bar_mesh.clone(),
+                                material: enemy_resources.bar_health_material.clone(),
+                                // slightly in front of the background
+                                transform: Transform::from_xyz(0.0, 0.0, 0.01)
+                                    .with_scale(Vec3::new(ENEMY_BAR_WIDTH, ENEMY_BAR_HEIGHT, 1.0)),
+                                ..default()
+                            },
+                            NotShadowCaster,
+                        ))
+                        .id();
+
+                let pips_width = ENEMY_BAR_PIPS as f32 * (ENEMY_BAR_PIP_WIDTH + ENEMY_BAR_PIP_GAP)
+                    - ENEMY_BAR_PIP_GAP;
+                for i in 0..ENEMY_BAR_PIPS {
+                    let x = -pips_width / 2.0
+                        + ENEMY_BAR_PIP_WIDTH / 2.0
+                        + i as f32 * (ENEMY_BAR_PIP_WIDTH + ENEMY_BAR_PIP_GAP);
+                    let y = -(ENEMY_BAR_HEIGHT + ENEMY_BAR_PIP_HEIGHT) / 2.0 - ENEMY_BAR_PIP_GAP;
+                    let pip =
+                        builder
+                            .spawn((
+                                PbrBundle {
+                                    mesh: enemy_resources.bar_mesh.clone(),
+                                    material: enemy_resources.bar_pip_empty_material.clone(),
+                                    transform: Transform::from_xyz(x, y, 0.0).with_scale(
+                                        Vec3::new(ENEMY_BAR_PIP_WIDTH, ENEMY_BAR_PIP_HEIGHT, 1.0),
+                                    ),
+                                    ..default()
+                                },
+                                NotShadowCaster,
+                            ))
+                            .id();
+                    pips.push(pip);
+                }
+            })
+            .insert(EnemyBar {
+                enemy,
+                health,
+                pips,
+            });
+    }
+}
+
+fn animate_shield(
+    shield_entity: Entity,
+    initial_transform: Transform,
+    target_transform: Transform,
+    commands: &mut Commands,
+) {
+    let Some(mut e) = commands.get_entity(shield_entity) else {
+        return;
+    };
+    e.insert(Animation {
+        animate_forward: true,
+        animate_backward: false,
+        animation_speed: ENEMY_SHIELD_ANIMATION_SPEED,
+        progress: 0.0,
+        initial_transform,
+        target_transform,
+    });
+}
+
+fn enemy_stun(
+    time: Res<Time>,
+    shields: Query<(&EnemyShield, &Transform)>,
+    mut enemies: Query<(
+        Entity,
+        &Enemy,
+        &Health,
+        &mut EnemyStun,
+        Option<&mut EnemyStaggered>,
+    )>,
+    mut commands: Commands,
+) {
+    for (enemy_entity, enemy, health, mut stun, staggered) in enemies.iter_mut() {
+        let damage = (stun.last_health - health.health).max(0);
+        stun.last_health = health.health;
+        stun.resistance_timer.tick(time.delta());
+
+        let shield = enemy.shield.and_then(|s| {
+            shields
+                .get(s)
+                .ok()
+                .map(|(shield, transform)| (s, shield, transform))
+        });
+
+        if let Some(mut staggered) = staggered {
+            staggered.timer.tick(time.delta());
+            if staggered.timer.finished() {
+                stun.meter = 0.0;
+                stun.resistance_timer.reset();
+                commands.entity(enemy_entity).remove::<EnemyStaggered>();
+                if let Some((shield_entity, shield, shield_transform)) = shield {
+                    animate_shield(
+                        shield_entity,
+                        *shield_transform,
+                        shield.closed_transform,
+                        &mut commands,
+                    );
+                }
+            }
+            continue;
+        }
+
+        let resistance = if stun.resistance_timer.finished() {
+            1.0
+        } else {
+            ENEMY_STUN_RESISTANCE
+        };
+        stun.meter += damage as f32 * ENEMY_STUN_PER_DAMAGE * resistance;
+        stun.meter = (stun.meter - ENEMY_STUN_DECAY * time.delta_seconds()).max(0.0);
+
+        if 1.0 <= stun.meter {
+            stun.meter = 1.0;
+            commands.entity(enemy_entity).insert(EnemyStaggered {
+                timer: Timer::from_seconds(ENEMY_STUN_STAGGER_TIME, TimerMode::Once),
+            });
+            // door flaps open exposing the fridge
+            if let Some((shield_entity, shield, shield_transform)) = shield {
+                animate_shield(
+                    shield_entity,
+                    *shield_transform,
+                    shield.opened_transform,
+                    &mut commands,
+                );
+            }
+        }
+    }
+}
+
+#[allow(clippy::complexity)]
+fn enemy_bar_update(
+    enemy_resources: Res<EnemyResources>,
+    player_cameras: Query<(&PlayerId, &GlobalTransform), With<PlayerCamera>>,
+    enemies: Query<(&Transform, &Enemy, Option<&Health>, &EnemyStun)>,
+    mut bars: Query<(Entity, &EnemyBar, &mut Transform, &mut Visibility), Without<Enemy>>,
+    mut bar_parts: Query<
+        (&mut Transform, &mut Handle<StandardMaterial>),
+        (Without<EnemyBar>, Without<Enemy>),
+    >,
+    mut commands: Commands,
+) {
+    let Some((_, camera_global_transform)) = player_cameras.iter().find(|(id, _)| id.is_local())
+    else {
+        return;
+    };
+
+    for (bar_entity, bar, mut bar_transform, mut bar_visibility) in bars.iter_mut() {
+        let Ok((enemy_transform, enemy, health, stun)) = enemies.get(bar.enemy) else {
+            commands.entity(bar_entity).despawn_recursive();
+            continue;
+        };
+
+        let health = health.map(|h| h.health).unwrap_or_default().max(0);
+        // bar is only shown for damaged enemies
+        if enemy.max_health <= health && stun.meter == 0.0 {
+            *bar_visibility = Visibility::Hidden;
+            continue;
+        }
+        *bar_visibility = Visibility::Visible;
+
+        let offset =
+            enemy.enemy_type.collider_half_size().z * enemy_transform.scale.z + ENEMY_BAR_MARGIN;
+        bar_transform.translation = enemy_transform.translation + Vec3::Z * offset;
+        // quads are facing +Z, so bar looks away from the camera
+        let target = bar_transform.translation * 2.0 - camera_global_transform.translation();
+        bar_transform.look_at(target, Vec3::Z);
+
+        if let Ok((mut health_transform, _)) = bar_parts.get_mut(bar.health) {
+            let fraction = health as f32 / enemy.max_health as f32;
+            health_transform.scale.x = ENEMY_BAR_WIDTH * fraction;
+            health_transform.translation.x = -ENEMY_BAR_WIDTH * (1.0 - fraction) / 2.0;
+        }
+
+        for (i, pip) in bar.pips.iter().enumerate() {
+            let Ok((_, mut pip_material)) = bar_parts.get_mut(*pip) else {
+                continue;
+            };
+            let full = (i + 1) as f32 / ENEMY_BAR_PIPS as f32 <= stun.meter;
+            *pip_material = if full {
+                enemy_resources.bar_pip_full_material.clone()
+            } else {
+                enemy_resources.bar_pip_empty_material.clone()
+            };
         }
     }
 }
@@ -376,75 +1309,523 @@ fn enemy_enable(
 #[allow(clippy::complexity)]
 fn enemy_move(
     time: Res<Time>,
+    enemy_grid: Res<EnemyGrid>,
     player: Query<&Transform, (With<Player>, Without<Enemy>)>,
     mut enemies: Query<
-        (&Enemy, &mut Transform, &mut KinematicCharacterController),
-        (Without<DisabledEnemy>, Without<Player>),
+        (
+            Entity,
+            &Enemy,
+            Option<&EnemyScripted>,
+            &mut Transform,
+            &mut KinematicCharacterController,
+        ),
+        (
+            Without<DisabledEnemy>,
+            Without<EnemyStaggered>,
+            Without<EnemyPatrol>,
+            Without<Player>,
+        ),
     >,
 ) {
     let Ok(player_transfomr) = player.get_single() else {
         return;
     };
 
-    for (enemy, mut enemy_transform, mut enemy_controller) in enemies.iter_mut() {
-        let v = player_transfomr.translation.xy() - enemy_transform.translation.xy();
+    for (entity, enemy, scripted, mut enemy_transform, mut enemy_controller) in enemies.iter_mut() {
+        let (target_offset, speed_modifier) = scripted
+            .map(|scripted| (scripted.target_offset, scripted.speed_modifier))
+            .unwrap_or((Vec2::ZERO, 1.0));
+        let v =
+            player_transfomr.translation.xy() + target_offset - enemy_transform.translation.xy();
         let direction = v.normalize();
+
+        let mut steering = enemy_separation(entity, enemy_transform.translation, &enemy_grid)
+            * ENEMY_SEPARATION_WEIGHT;
         if enemy.min_distance < v.length_squared() {
-            let movement = direction * enemy.speed * time.delta_seconds();
+            steering += direction;
+        }
+        if steering != Vec2::ZERO {
+            let movement = steering.clamp_length_max(1.0)
+                * enemy.speed
+                * speed_modifier
+                * time.delta_seconds();
             enemy_controller.translation = Some(movement.extend(0.0));
         }
 
-        let direction = direction.extend(0.0);
-        let enemy_forward = enemy_transform.rotation * Vec3::Y;
-        let mut angle = direction.angle_between(enemy_forward);
-        let cross = direction.cross(enemy_forward);
-        if 0.0 <= cross.z {
-            angle *= -1.0;
+        enemy_rotate(
+            &mut enemy_transform,
+            direction,
+            enemy.rotation_speed * time.delta_seconds(),
+        );
+    }
+}
+
+// Pushes away from neighbours, stronger
+// the closer they are
+fn enemy_separation(entity: Entity, translation: Vec3, enemy_grid: &EnemyGrid) -> Vec2 {
+    enemy_grid
+        .enemies_within_radius(translation, ENEMY_SEPARATION_RADIUS)
+        .filter(|(other, _)| *other != entity)
+        .map(|(_, other_translation)| {
+            let away = translation.xy() - other_translation.xy();
+            let distance = away.length();
+            // enemies at the same spot push in
+            // an arbitrary direction
+            let away = away.try_normalize().unwrap_or(Vec2::X);
+            away * (1.0 - distance / ENEMY_SEPARATION_RADIUS)
+        })
+        .sum()
+}
+
+fn enemy_rotate(enemy_transform: &mut Transform, direction: Vec2, step: f32) {
+    let direction = direction.extend(0.0);
+    let enemy_forward = enemy_transform.rotation * Vec3::Y;
+    let mut angle = direction.angle_between(enemy_forward);
+    let cross = direction.cross(enemy_forward);
+    if 0.0 <= cross.z {
+        angle *= -1.0;
+    }
+    let target_rotation = enemy_transform.rotation * Quat::from_rotation_z(angle);
+    enemy_transform.rotation = enemy_transform.rotation.lerp(target_rotation, step);
+}
+
+#[allow(clippy::complexity)]
+fn enemy_patrol(
+    time: Res<Time>,
+    mut enemies: Query<
+        (
+            &Enemy,
+            &mut EnemyPatrol,
+            &mut Transform,
+            &mut KinematicCharacterController,
+        ),
+        (Without<DisabledEnemy>, Without<EnemyStaggered>),
+    >,
+) {
+    for (enemy, mut patrol, mut enemy_transform, mut enemy_controller) in enemies.iter_mut() {
+        // pause and look around at the waypoint
+        if let Some(scan_rotation) = patrol.scan_rotation {
+            patrol.scan_timer.tick(time.delta());
+            let angle = (patrol.scan_timer.elapsed_secs() * ENEMY_PATROL_SCAN_SPEED).sin()
+                * ENEMY_PATROL_SCAN_ANGLE;
+            enemy_transform.rotation = scan_rotation * Quat::from_rotation_z(angle);
+
+            if patrol.scan_timer.finished() {
+                patrol.scan_rotation = None;
+                patrol.current = (patrol.current + 1) % patrol.waypoints.len();
+                patrol.walk_timer.reset();
+            }
+            continue;
+        }
+
+        patrol.walk_timer.tick(time.delta());
+        let v = patrol.waypoints[patrol.current].xy() - enemy_transform.translation.xy();
+        if v.length() < ENEMY_PATROL_WAYPOINT_DISTANCE || patrol.walk_timer.finished() {
+            patrol.scan_timer.reset();
+            patrol.scan_rotation = Some(enemy_transform.rotation);
+            continue;
+        }
+
+        let direction = v.normalize();
+        let movement = direction * enemy.speed * ENEMY_PATROL_SPEED_MODIFIER * time.delta_seconds();
+        enemy_controller.translation = Some(movement.extend(0.0));
+        enemy_rotate(
+            &mut enemy_transform,
+            direction,
+            enemy.rotation_speed * time.delta_seconds(),
+        );
+    }
+}
+
+// Enemy is alerted when it is hit, when player comes too close
+// or when player breaks into the level. Enemies also notice the
+// player when they see him in `enemy_shoot` or hear his shots
+// in `enemy_hear`.
+#[allow(clippy::complexity)]
+#[allow(clippy::too_many_arguments)]
+fn enemy_alert(
+    level_info: Res<LevelInfo>,
+    blackout: Res<Blackout>,
+    player: Query<&Transform, With<Player>>,
+    enemies: Query<
+        (Entity, &Transform),
+        (With<EnemyPatrol>, Without<DisabledEnemy>, Without<Player>),
+    >,
+    shields: Query<&Parent, With<EnemyShield>>,
+    mut commands: Commands,
+    mut damage_events: EventReader<DamageEvent>,
+    mut kill_events: EventReader<KillEvent>,
+) {
+    let Ok(player_transform) = player.get_single() else {
+        return;
+    };
+
+    let damaged = damage_events.read().map(|e| e.entity);
+    let killed = kill_events.read().map(|e| e.entity);
+    // hits to the shield alert its enemy
+    let hit = damaged
+        .chain(killed)
+        .map(|entity| shields.get(entity).map(|p| p.get()).unwrap_or(entity))
+        .collect::<Vec<_>>();
+
+    let notice_distance = if blackout.player_hidden {
+        ENEMY_PATROL_DARK_NOTICE_DISTANCE
+    } else {
+        ENEMY_PATROL_NOTICE_DISTANCE
+    };
+    for (enemy, enemy_transform) in enemies.iter() {
+        let distance = enemy_transform
+            .translation
+            .distance(player_transform.translation);
+        if level_info.enemies_alerted || distance < notice_distance || hit.contains(&enemy) {
+            commands.entity(enemy).remove::<EnemyPatrol>();
+        }
+    }
+}
+
+#[allow(clippy::complexity)]
+fn enemy_hear(
+    rapier_context: Res<RapierContext>,
+    enemy_grid: Res<EnemyGrid>,
+    player_weapons: Query<&Weapon, With<PlayerWeapon>>,
+    enemies: Query<(), (With<EnemyPatrol>, Without<DisabledEnemy>)>,
+    mut commands: Commands,
+    mut shoot_events: EventReader<ShootEvent>,
+    mut noise_events: EventReader<NoiseEvent>,
+) {
+    let shots = shoot_events.read().filter_map(|shoot_event| {
+        player_weapons
+            .get(shoot_event.weapon_entity)
+            .ok()
+            .map(|weapon| NoiseEvent {
+                translation: shoot_event.weapon_translation,
+                loudness: weapon.loudness(),
+            })
+    });
+    for noise in shots.chain(noise_events.read().copied()) {
+        for (enemy, enemy_translation) in
+            enemy_grid.enemies_within_radius(noise.translation, noise.loudness)
+        {
+            if !enemies.contains(enemy) {
+                continue;
+            }
+            let v = enemy_translation - noise.translation;
+            let distance = v.length();
+            if noise.loudness < distance {
+                continue;
+            }
+
+            let mut occluders = 0;
+            let filter = QueryFilter {
+                flags: QueryFilterFlags::EXCLUDE_SENSORS,
+                groups: Some(CollisionGroups::new(Group::ALL, COLLISION_GROUP_LEVEL)),
+                ..default()
+            };
+            rapier_context.intersections_with_ray(
+                noise.translation,
+                v / distance,
+                distance,
+                true,
+                filter,
+                |_, _| {
+                    occluders += 1;
+                    true
+                },
+            );
+
+            if distance < noise.loudness * ENEMY_HEARING_OCCLUSION.powi(occluders) {
+                commands.entity(enemy).remove::<EnemyPatrol>();
+            }
         }
-        let target_rotation = enemy_transform.rotation * Quat::from_rotation_z(angle);
-        enemy_transform.rotation = enemy_transform
-            .rotation
-            .lerp(target_rotation, enemy.rotation_speed * time.delta_seconds());
     }
 }
 
+#[allow(clippy::complexity)]
 fn enemy_shoot(
     rapier_context: Res<RapierContext>,
     player: Query<Entity, With<Player>>,
-    mut enemy_weapons: Query<(Entity, &GlobalTransform, &mut WeaponAttackTimer), With<EnemyWeapon>>,
+    enemies: Query<
+        (&Enemy, Has<EnemyPatrol>, Option<&EnemyScripted>),
+        (Without<DisabledEnemy>, Without<EnemyStaggered>),
+    >,
+    shields: Query<(&EnemyShield, Option<&Animation>)>,
+    mut enemy_weapons: Query<
+        (Entity, &Parent, &GlobalTransform, &mut WeaponAttackTimer),
+        With<EnemyWeapon>,
+    >,
+    mut commands: Commands,
     mut shoot_event: EventWriter<ShootEvent>,
 ) {
     let Ok(player) = player.get_single() else {
         return;
     };
 
-    for (weapon_entity, weapon_global_transform, mut weapon_attack_timer) in
+    // enemy should not see its own shield
+    let not_shield = |entity| !shields.contains(entity);
+    for (weapon_entity, weapon_parent, weapon_global_transform, mut weapon_attack_timer) in
         enemy_weapons.iter_mut()
     {
+        let enemy_entity = weapon_parent.get();
+        let Ok((enemy, patrolling, scripted)) = enemies.get(enemy_entity) else {
+            continue;
+        };
+        let hold_fire = scripted.map(|scripted| scripted.hold_fire).unwrap_or(false);
+
         let ray_dir = weapon_global_transform.up();
         let ray_origin = weapon_global_transform.translation();
         let max_toi = 300.0;
         let solid = true;
         let filter = QueryFilter {
             flags: QueryFilterFlags::EXCLUDE_SENSORS,
+            predicate: Some(&not_shield),
             ..default()
         };
         if let Some((entity, _)) =
             rapier_context.cast_ray(ray_origin, ray_dir, max_toi, solid, filter)
         {
-            if entity == player && weapon_attack_timer.ready {
+            // patrolling enemy notices the player it sees
+            if entity == player && patrolling {
+                commands.entity(enemy_entity).remove::<EnemyPatrol>();
+            }
+            if entity == player && weapon_attack_timer.ready && !hold_fire {
+                if let Some(shield_entity) = enemy.shield {
+                    let Ok((shield, animation)) = shields.get(shield_entity) else {
+                        continue;
+                    };
+                    // door is opened before the attack
+                    match animation {
+                        None => {
+                            commands.entity(shield_entity).insert(Animation {
+                                animate_forward: true,
+                                animate_backward: true,
+                                animation_speed: ENEMY_SHIELD_ANIMATION_SPEED,
+                                progress: 0.0,
+                                initial_transform: shield.closed_transform,
+                                target_transform: shield.opened_transform,
+                            });
+                            continue;
+                        }
+                        Some(animation) if !EnemyShield::is_opened(animation) => continue,
+                        Some(_) => {}
+                    }
+                }
+
                 weapon_attack_timer.attack_timer.reset();
                 weapon_attack_timer.ready = false;
                 shoot_event.send(ShootEvent {
                     weapon_entity,
                     weapon_translation: weapon_global_transform.translation(),
                     direction: weapon_global_transform.up(),
+                    spread: 0.0,
+                    ammo_type: AmmoType::Standard,
                 });
             }
         }
     }
 }
 
+fn enemy_contact(
+    time: Res<Time>,
+    director: Res<Director>,
+    mut players: Query<(Entity, &Transform, &mut PlayerVelocity), With<Player>>,
+    mut enemies: Query<
+        (&Enemy, &Transform, &mut EnemyContact),
+        (Without<DisabledEnemy>, Without<Player>),
+    >,
+    mut contact_damage_events: EventWriter<ContactDamageEvent>,
+) {
+    let damage_modifier = 1.0 + director.difficulty() * ENEMY_CONTACT_DIFFICULTY_DAMAGE;
+    for (enemy, enemy_transform, mut contact) in enemies.iter_mut() {
+        contact.cooldown.tick(time.delta());
+        if !contact.cooldown.finished() {
+            continue;
+        }
+
+        let half_size = enemy.enemy_type.collider_half_size() * enemy_transform.scale;
+        for (player_entity, player_transform, mut player_velocity) in players.iter_mut() {
+            // closest point of the enemy box to the player on the XY plane
+            let local = enemy_transform.rotation.inverse()
+                * (player_transform.translation - enemy_transform.translation);
+            let closest = local.xy().clamp(-half_size.xy(), half_size.xy());
+            if PLAYER_COLLIDER_RADIUS + ENEMY_CONTACT_MARGIN < local.xy().distance(closest) {
+                continue;
+            }
+
+            let direction = (player_transform.translation.xy() - enemy_transform.translation.xy())
+                .normalize_or_zero()
+                .extend(0.0);
+            player_velocity.push(direction * enemy.enemy_type.contact_knockback());
+            contact_damage_events.send(ContactDamageEvent {
+                entity: player_entity,
+                damage: (enemy.enemy_type.contact_damage() as f32 * damage_modifier) as i32,
+                direction,
+            });
+            contact.cooldown.reset();
+        }
+    }
+}
+
+fn enemy_shield_destroyed(
+    shields: Query<&Parent, With<EnemyShield>>,
+    mut enemies: Query<&mut Enemy>,
+    mut commands: Commands,
+    mut kill_events: EventReader<KillEvent>,
+) {
+    for kill_event in kill_events.read() {
+        let Ok(shield_parent) = shields.get(kill_event.entity) else {
+            continue;
+        };
+        if let Ok(mut enemy) = enemies.get_mut(shield_parent.get()) {
+            enemy.shield = None;
+        }
+        let Some(e) = commands.get_entity(kill_event.entity) else {
+            continue;
+        };
+        e.despawn_recursive();
+    }
+}
+
+#[allow(clippy::complexity)]
+fn enemy_hum_start(
+    hum_channel: Res<AudioChannel<EnemyHumChannel>>,
+    enemy_assets: Res<EnemyAssets>,
+    enemies: Query<Entity, (With<Enemy>, Without<DisabledEnemy>, Without<EnemyHum>)>,
+    mut commands: Commands,
+) {
+    for enemy in enemies.iter() {
+        // volume is set in `enemy_hum_update`
+        let instance = hum_channel
+            .play(enemy_assets.fridge_hum.clone())
+            .looped()
+            .with_volume(0.0)
+            .handle();
+        commands.entity(enemy).insert(EnemyHum { instance });
+    }
+}
+
+fn enemy_hum_update(
+    rapier_context: Res<RapierContext>,
+    game_settings: Res<GameSettings>,
+    player_cameras: Query<(&PlayerId, &GlobalTransform), With<PlayerCamera>>,
+    enemies: Query<(&GlobalTransform, &EnemyHum)>,
+    mut audio_instances: ResMut<Assets<AudioInstance>>,
+) {
+    let Some((_, camera_global_transform)) = player_cameras.iter().find(|(id, _)| id.is_local())
+    else {
+        return;
+    };
+
+    let tween = AudioTween::linear(std::time::Duration::from_millis(ENEMY_HUM_TWEEN_MILLIS));
+    for (enemy_global_transform, enemy_hum) in enemies.iter() {
+        let Some(instance) = audio_instances.get_mut(&enemy_hum.instance) else {
+            continue;
+        };
+
+        let ray_origin = camera_global_transform.translation();
+        let v = enemy_global_transform.translation() - ray_origin;
+        let distance = v.length();
+        let mut volume = (1.0 - distance / ENEMY_HUM_MAX_DISTANCE).clamp(0.0, 1.0);
+        if 0.0 < volume {
+            let filter = QueryFilter {
+                flags: QueryFilterFlags::EXCLUDE_SENSORS,
+                groups: Some(CollisionGroups::new(Group::ALL, COLLISION_GROUP_LEVEL)),
+                ..default()
+            };
+            if rapier_context
+                .cast_ray(ray_origin, v / distance, distance, true, filter)
+                .is_some()
+            {
+                volume *= ENEMY_HUM_OCCLUSION_VOLUME;
+            }
+        }
+        let panning = 0.5 + 0.5 * camera_global_transform.right().dot(v.normalize_or_zero());
+
+        instance.set_volume((game_settings.volume * volume) as f64, tween.clone());
+        instance.set_panning(panning as f64, tween.clone());
+    }
+}
+
+// Hums are stopped for every despawned enemy, be it killed
+// or removed together with the old level.
+fn enemy_hum_stop(
+    enemies: Query<(Entity, &EnemyHum), Added<EnemyHum>>,
+    mut audio_instances: ResMut<Assets<AudioInstance>>,
+    mut removed_hums: RemovedComponents<EnemyHum>,
+    mut hums: Local<HashMap<Entity, Handle<AudioInstance>>>,
+) {
+    for enemy in removed_hums.read() {
+        let Some(handle) = hums.remove(&enemy) else {
+            continue;
+        };
+        if let Some(instance) = audio_instances.get_mut(&handle) {
+            instance.stop(AudioTween::default());
+        }
+    }
+    for (enemy, enemy_hum) in enemies.iter() {
+        hums.insert(enemy, enemy_hum.instance.clone());
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn enemy_footsteps(
+    time: Res<Time>,
+    sfx: Res<AudioChannel<SfxChannel>>,
+    enemy_assets: Res<EnemyAssets>,
+    game_settings: Res<GameSettings>,
+    player_cameras: Query<(&PlayerId, &GlobalTransform), With<PlayerCamera>>,
+    mut enemies: Query<(&Enemy, &Transform, &mut EnemyFootsteps), Without<DisabledEnemy>>,
+    mut sound_pool: ResMut<SoundPool>,
+    mut audio_instances: ResMut<Assets<AudioInstance>>,
+    mut game_rng: ResMut<GameRng>,
+) {
+    let Some((_, camera_global_transform)) = player_cameras.iter().find(|(id, _)| id.is_local())
+    else {
+        return;
+    };
+
+    for (enemy, transform, mut footsteps) in enemies.iter_mut() {
+        footsteps.distance += transform
+            .translation
+            .xy()
+            .distance(footsteps.last_translation.xy());
+        footsteps.last_translation = transform.translation;
+
+        let step_length = enemy.enemy_type.step_length();
+        if footsteps.distance < step_length {
+            continue;
+        }
+        footsteps.distance %= step_length;
+
+        let v = transform.translation - camera_global_transform.translation();
+        let attenuation = (1.0 - v.length() / ENEMY_FOOTSTEP_MAX_DISTANCE).clamp(0.0, 1.0);
+        if attenuation == 0.0 {
+            continue;
+        }
+        let panning = 0.5 + 0.5 * camera_global_transform.right().dot(v.normalize_or_zero());
+        sound_pool.play(
+            sfx.as_ref(),
+            audio_instances.as_mut(),
+            &enemy_assets.fridge_step,
+            ENEMY_FOOTSTEP_SOUND_LIMIT,
+            (game_settings.volume * ENEMY_FOOTSTEP_VOLUME * attenuation) as f64,
+            panning as f64,
+            time.elapsed_seconds(),
+            game_rng.stream(RngStream::Effects),
+        );
+    }
+}
+
+fn enemy_hum_stop_all(
+    hum_channel: Res<AudioChannel<EnemyHumChannel>>,
+    enemies: Query<Entity, With<EnemyHum>>,
+    mut commands: Commands,
+) {
+    hum_channel.stop();
+    for enemy in enemies.iter() {
+        commands.entity(enemy).remove::<EnemyHum>();
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 fn spawn_parts(
     parts_x: u32,
@@ -463,6 +1844,7 @@ fn spawn_parts(
     gap_delta_y: f32,
     gap_delta_z: f32,
     pulse_strength: f32,
+    part_stride: u32,
     part_mesh: Handle<Mesh>,
     part_material: Handle<StandardMaterial>,
     enemy_transform: Transform,
@@ -471,6 +1853,10 @@ fn spawn_parts(
     for x in 0..parts_x {
         for y in 0..parts_y {
             for z in 0..parts_z {
+                // lower quality skips some of the parts
+                if (x + y + z) % part_stride != 0 {
+                    continue;
+                }
                 let x_pos =
                     -(dimention_x + gap_x) / 2.0 + (part_dimention_x + gap_delta_x) * x as f32;
                 let y_pos =
@@ -497,11 +1883,13 @@ fn spawn_parts(
                         part_dimention_y / 2.0,
                         part_dimention_z / 2.0,
                     ),
+                    CollisionLayer::Debris.groups(),
                     RigidBody::Dynamic,
                     Velocity {
                         linvel,
                         ..default()
                     },
+                    EnemyPart,
                     LevelObject,
                 ));
             }
@@ -509,14 +1897,23 @@ fn spawn_parts(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn enemy_die(
+    director: Res<Director>,
+    quality: Res<Quality>,
     enemy_resources: Res<EnemyResources>,
     enemies: Query<(Entity, &Transform, &Enemy), Without<EnemyWeapon>>,
+    mut game_rng: ResMut<GameRng>,
     mut commands: Commands,
     mut kill_events: EventReader<KillEvent>,
+    mut last_enemy_killed_events: EventWriter<LastEnemyKilled>,
 ) {
+    let mut killed = 0;
+    let mut last_translation = Vec3::ZERO;
     for kill_event in kill_events.read() {
         if let Ok((enemy_entity, enemy_transform, enemy)) = enemies.get(kill_event.entity) {
+            killed += 1;
+            last_translation = enemy_transform.translation;
             match enemy.enemy_type {
                 EnemyType::Small => spawn_parts(
                     ENEMY_SMALL_PARTS_X,
@@ -535,6 +1932,7 @@ fn enemy_die(
                     ENEMY_SMALL_DEATH_GAP_DELTA_Y,
                     ENEMY_SMALL_DEATH_GAP_DELTA_Z,
                     ENEMY_SMALL_DEATH_PULSE_STENGTH,
+                    quality.preset.particle_stride(),
                     enemy_resources.small_part_mesh.clone(),
                     enemy_resources.small_part_material.clone(),
                     *enemy_transform,
@@ -557,6 +1955,7 @@ fn enemy_die(
                     ENEMY_MID_DEATH_GAP_DELTA_Y,
                     ENEMY_MID_DEATH_GAP_DELTA_Z,
                     ENEMY_MID_DEATH_PULSE_STENGTH,
+                    quality.preset.particle_stride(),
                     enemy_resources.mid_part_mesh.clone(),
                     enemy_resources.mid_part_material.clone(),
                     *enemy_transform,
@@ -579,6 +1978,7 @@ fn enemy_die(
                     ENEMY_BIG_DEATH_GAP_DELTA_Y,
                     ENEMY_BIG_DEATH_GAP_DELTA_Z,
                     ENEMY_BIG_DEATH_PULSE_STENGTH,
+                    quality.preset.particle_stride(),
                     enemy_resources.big_part_mesh.clone(),
                     enemy_resources.big_part_material.clone(),
                     *enemy_transform,
@@ -587,7 +1987,10 @@ fn enemy_die(
             }
 
             // drop weapon
-            if let Some(attached_weapon) = enemy.attached_weapon {
+            let drop_weapon = game_rng
+                .stream(RngStream::Drops)
+                .gen_bool(director.drop_chance());
+            if let Some(attached_weapon) = enemy.attached_weapon.filter(|_| drop_weapon) {
                 commands
                     .get_entity(enemy_entity)
                     .unwrap()
@@ -609,4 +2012,28 @@ fn enemy_die(
                 .despawn_recursive();
         }
     }
+
+    // killed enemies are despawned only after this system
+    if 0 < killed && killed == enemies.iter().count() {
+        last_enemy_killed_events.send(LastEnemyKilled {
+            translation: last_translation,
+        });
+    }
+}
+
+fn enemy_boss_phase(
+    bosses: Query<(Entity, &Enemy, &Health), Changed<Health>>,
+    mut boss_phase_events: EventWriter<BossPhaseChanged>,
+    mut changed: Local<Option<Entity>>,
+) {
+    for (entity, enemy, health) in bosses.iter() {
+        if enemy.enemy_type != EnemyType::Big || *changed == Some(entity) {
+            continue;
+        }
+        let fraction = health.health as f32 / enemy.max_health() as f32;
+        if fraction <= ENEMY_BOSS_PHASE_HEALTH {
+            *changed = Some(entity);
+            boss_phase_events.send(BossPhaseChanged { entity });
+        }
+    }
 }
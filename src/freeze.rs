@@ -0,0 +1,124 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{collision::CollisionLayer, enemies::Enemy, player::Player, GlobalState};
+
+// Bigger fridges get bigger auras, the
+// sensor is scaled together with the enemy
+const FREEZE_AURA_RADIUS: f32 = 5.0;
+// Each aura the player stands in adds this
+// much chill per second, so auras stack
+const FREEZE_CHILL_RATE: f32 = 0.4;
+const FREEZE_CHILL_DECAY: f32 = 0.6;
+// Movement speed at full chill is reduced by this part
+const FREEZE_MAX_SLOW: f32 = 0.6;
+
+pub struct FreezePlugin;
+
+impl Plugin for FreezePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                freeze_aura_attach,
+                freeze_chill_attach,
+                freeze_aura_contacts,
+                freeze_chill_update,
+            )
+                .chain()
+                .run_if(in_state(GlobalState::InGame)),
+        );
+    }
+}
+
+// Sensor around the enemy chilling the player
+#[derive(Component)]
+struct FreezeAura;
+
+/// How frozen the player is
+#[derive(Default, Component)]
+pub struct Chill {
+    // Auras the player is standing in
+    auras: Vec<Entity>,
+    // 0 is not chilled, 1 is fully chilled
+    pub amount: f32,
+}
+
+impl Chill {
+    pub fn movement_modifier(&self) -> f32 {
+        1.0 - FREEZE_MAX_SLOW * self.amount
+    }
+}
+
+fn freeze_aura_attach(enemies: Query<Entity, Added<Enemy>>, mut commands: Commands) {
+    for enemy in enemies.iter() {
+        let aura = commands
+            .spawn((
+                TransformBundle::default(),
+                Collider::ball(FREEZE_AURA_RADIUS),
+                CollisionLayer::EnemyAura.groups(),
+                ActiveCollisionTypes::default() | ActiveCollisionTypes::KINEMATIC_KINEMATIC,
+                Sensor,
+                ActiveEvents::COLLISION_EVENTS,
+                FreezeAura,
+            ))
+            .id();
+        commands.entity(enemy).add_child(aura);
+    }
+}
+
+fn freeze_chill_attach(players: Query<Entity, Added<Player>>, mut commands: Commands) {
+    for player in players.iter() {
+        commands.entity(player).insert(Chill::default());
+    }
+}
+
+fn freeze_aura_contacts(
+    auras: Query<(), With<FreezeAura>>,
+    mut players: Query<&mut Chill>,
+    mut collision_events: EventReader<CollisionEvent>,
+) {
+    for collision_event in collision_events.read() {
+        let (collider_1, collider_2, entered) = match collision_event {
+            CollisionEvent::Started(c1, c2, _) => (c1, c2, true),
+            CollisionEvent::Stopped(c1, c2, _) => (c1, c2, false),
+        };
+        let (aura, player) = if auras.contains(*collider_1) {
+            (*collider_1, *collider_2)
+        } else if auras.contains(*collider_2) {
+            (*collider_2, *collider_1)
+        } else {
+            continue;
+        };
+        let Ok(mut chill) = players.get_mut(player) else {
+            continue;
+        };
+
+        if entered {
+            if !chill.auras.contains(&aura) {
+                chill.auras.push(aura);
+            }
+        } else {
+            chill.auras.retain(|a| *a != aura);
+        }
+    }
+}
+
+fn freeze_chill_update(
+    time: Res<Time>,
+    auras: Query<(), With<FreezeAura>>,
+    mut players: Query<&mut Chill>,
+) {
+    for mut chill in players.iter_mut() {
+        // auras of killed enemies are gone without
+        // any collision events
+        chill.auras.retain(|aura| auras.contains(*aura));
+
+        let delta = time.delta_seconds();
+        chill.amount = if chill.auras.is_empty() {
+            (chill.amount - FREEZE_CHILL_DECAY * delta).max(0.0)
+        } else {
+            (chill.amount + FREEZE_CHILL_RATE * chill.auras.len() as f32 * delta).min(1.0)
+        };
+    }
+}
@@ -0,0 +1,243 @@
+use std::{
+    collections::VecDeque,
+    fmt::Write,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::prelude::*;
+
+use crate::{
+    damage::{DamageEvent, KillEvent},
+    enemies::Enemy,
+    level::{LevelInfo, LevelStarted},
+    player::{Player, PlayerWeapon},
+    powerups::PowerupCollected,
+    scrap::Scrap,
+    weapons::{ShootEvent, Weapon},
+    GlobalState,
+};
+
+const GAME_LOG_CAPACITY: usize = 512;
+const GAME_LOG_DIR: &str = "logs";
+const GAME_LOG_DUMP_KEY: KeyCode = KeyCode::F8;
+
+pub struct GameLogPlugin;
+
+impl Plugin for GameLogPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameLog>();
+
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::MainMenu,
+                to: GlobalState::InGame,
+            },
+            clear_game_log,
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::GameOver,
+                to: GlobalState::InGame,
+            },
+            clear_game_log,
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::GameWon,
+                to: GlobalState::InGame,
+            },
+            clear_game_log,
+        );
+        app.add_systems(OnEnter(GlobalState::GameOver), dump_game_log);
+
+        app.add_systems(
+            Update,
+            (
+                game_log_shots,
+                game_log_damage,
+                game_log_levels,
+                game_log_pickups,
+            )
+                .run_if(in_state(GlobalState::InGame)),
+        );
+        app.add_systems(Update, game_log_dump_key);
+    }
+}
+
+#[derive(Debug)]
+pub struct GameLogEntry {
+    // Seconds since the game start
+    pub time: f32,
+    pub text: String,
+}
+
+/// Last high level events of the run. Dumped to
+/// a file on death or with the dump key.
+#[derive(Debug, Default, Resource)]
+pub struct GameLog {
+    entries: VecDeque<GameLogEntry>,
+}
+
+impl GameLog {
+    pub fn push(&mut self, time: f32, text: String) {
+        if self.entries.len() == GAME_LOG_CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(GameLogEntry { time, text });
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &GameLogEntry> {
+        self.entries.iter()
+    }
+}
+
+fn format_vec(v: Vec3) -> String {
+    format!("({:.1}, {:.1}, {:.1})", v.x, v.y, v.z)
+}
+
+fn clear_game_log(mut game_log: ResMut<GameLog>) {
+    game_log.entries.clear();
+}
+
+fn game_log_shots(
+    time: Res<Time>,
+    weapons: Query<(&Weapon, Has<PlayerWeapon>)>,
+    mut game_log: ResMut<GameLog>,
+    mut shoot_events: EventReader<ShootEvent>,
+) {
+    for event in shoot_events.read() {
+        let Ok((weapon, player_weapon)) = weapons.get(event.weapon_entity) else {
+            continue;
+        };
+        let shooter = if player_weapon { "Player" } else { "Enemy" };
+        game_log.push(
+            time.elapsed_seconds(),
+            format!(
+                "{shooter} shot {} from {} to {}",
+                weapon.weapon_type().name(),
+                format_vec(event.weapon_translation),
+                format_vec(event.direction),
+            ),
+        );
+    }
+}
+
+fn entity_name(entity: Entity, targets: &Query<(Has<Player>, Option<&Enemy>)>) -> String {
+    match targets.get(entity) {
+        Ok((true, _)) => "Player".to_string(),
+        Ok((_, Some(enemy))) => format!("{:?} enemy {entity:?}", enemy.enemy_type),
+        _ => format!("Object {entity:?}"),
+    }
+}
+
+fn game_log_damage(
+    time: Res<Time>,
+    targets: Query<(Has<Player>, Option<&Enemy>)>,
+    transforms: Query<&GlobalTransform>,
+    mut game_log: ResMut<GameLog>,
+    mut damage_events: EventReader<DamageEvent>,
+    mut kill_events: EventReader<KillEvent>,
+) {
+    for event in damage_events.read() {
+        let position = transforms
+            .get(event.entity)
+            .map(|transform| format_vec(transform.translation()))
+            .unwrap_or_default();
+        game_log.push(
+            time.elapsed_seconds(),
+            format!(
+                "{} damaged at {position} from {}",
+                entity_name(event.entity, &targets),
+                format_vec(event.direction),
+            ),
+        );
+    }
+    for event in kill_events.read() {
+        let position = transforms
+            .get(event.entity)
+            .map(|transform| format_vec(transform.translation()))
+            .unwrap_or_default();
+        game_log.push(
+            time.elapsed_seconds(),
+            format!(
+                "{} killed at {position}",
+                entity_name(event.entity, &targets)
+            ),
+        );
+    }
+}
+
+fn game_log_levels(
+    time: Res<Time>,
+    level_info: Res<LevelInfo>,
+    mut game_log: ResMut<GameLog>,
+    mut level_started_events: EventReader<LevelStarted>,
+) {
+    for _ in level_started_events.read() {
+        game_log.push(
+            time.elapsed_seconds(),
+            format!(
+                "Level started, progress: {} type: {:?}",
+                level_info.game_progress, level_info.level_type
+            ),
+        );
+    }
+}
+
+fn game_log_pickups(
+    time: Res<Time>,
+    scrap: Res<Scrap>,
+    new_weapons: Query<&Weapon, Added<PlayerWeapon>>,
+    mut game_log: ResMut<GameLog>,
+    mut last_scrap: Local<u32>,
+    mut powerup_collected_events: EventReader<PowerupCollected>,
+) {
+    for weapon in new_weapons.iter() {
+        game_log.push(
+            time.elapsed_seconds(),
+            format!("Player picked up {}", weapon.weapon_type().name()),
+        );
+    }
+    for event in powerup_collected_events.read() {
+        game_log.push(
+            time.elapsed_seconds(),
+            format!("Player picked up {:?} power-up", event.powerup_type),
+        );
+    }
+    // scrap is reset on the run start
+    if *last_scrap < scrap.amount {
+        game_log.push(
+            time.elapsed_seconds(),
+            format!("Player picked up {} scrap", scrap.amount - *last_scrap),
+        );
+    }
+    *last_scrap = scrap.amount;
+}
+
+fn game_log_dump_key(keys: Res<Input<KeyCode>>, game_log: Res<GameLog>) {
+    if keys.just_pressed(GAME_LOG_DUMP_KEY) {
+        dump_game_log(game_log);
+    }
+}
+
+fn dump_game_log(game_log: Res<GameLog>) {
+    // system time and file system are not available on web
+    if cfg!(target_arch = "wasm32") {
+        return;
+    }
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    let mut log = String::new();
+    for entry in game_log.entries() {
+        _ = writeln!(log, "{:.2}s {}", entry.time, entry.text);
+    }
+
+    let path = format!("{GAME_LOG_DIR}/game_log_{timestamp}.log");
+    match std::fs::create_dir_all(GAME_LOG_DIR).and_then(|_| std::fs::write(&path, log)) {
+        Ok(_) => info!("Game log saved to {path}"),
+        Err(e) => warn!("Could not save game log to {path}: {e}"),
+    }
+}
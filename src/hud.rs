@@ -1,16 +1,45 @@
-use bevy::{core_pipeline::clear_color::ClearColorConfig, prelude::*};
+use std::collections::VecDeque;
+
+use bevy::{
+    core_pipeline::clear_color::ClearColorConfig,
+    diagnostic::{DiagnosticsStore, EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin},
+    prelude::*,
+    render::view::RenderLayers,
+    sprite::Anchor,
+};
+use bevy_rapier3d::{prelude::RigidBody, render::DebugRenderContext};
 
 use crate::{
-    damage::DamageEvent,
-    level::{LevelInfo, LevelStarted},
-    player::{Player, PlayerCamera},
+    capture::CaptureSaved,
+    checkpoint::{CheckpointReached, CheckpointRespawn},
+    crosshair::{spawn_crosshair, spawn_hit_marker, CrosshairSettings},
+    damage::{DamageEvent, Health, KillEvent},
+    enemies::{Enemy, EnemySpawnTelegraph, NoiseEvent},
+    freeze::Chill,
+    input_device::ActiveInputDevice,
+    level::{
+        blackout::LightSwitchPrompt,
+        crafting::CraftingPrompt,
+        defense::DefendedDoor,
+        door::{DoorAnimationStarted, DoorLockedInteraction, DoorPrompt},
+        ladder::LadderPrompt,
+        range::{RangePrompt, ShootingRange},
+        terminal::{TerminalPrompt, LORE_ENTRIES},
+        vending::{VendingItem, VendingPrompt},
+        LevelInfo, LevelStarted,
+    },
+    player::{Player, PlayerCamera, PlayerId, PlayerWeapon},
+    powerups::{ActivePowerups, PowerupType},
+    run_modifiers::{RunModifier, RunModifiers},
+    style::StyleMeter,
+    time_attack::{Medal, TimeAttack, TimeAttackSplit},
     ui::UiAssets,
-    GlobalState,
+    unlocks::WeaponUnlocked,
+    weapons::{floating::WeaponPrompt, DryFireEvent, ShootEvent},
+    GameSettings, GlobalState,
 };
 
-const CROSSHAIR_COLOR: Color = Color::WHITE;
-const CROSSHAIR_SIZE: Vec2 = Vec2::new(10.0, 2.0);
-const CROSSHAIR_ROTATION: f32 = std::f32::consts::FRAC_PI_4;
+const HIT_MARKER_DISPAWN_TIME_SECONDS: f32 = 0.15;
 
 const DAMAGE_COLOR: Color = Color::CRIMSON;
 const DAMAGE_SIZE: Vec2 = Vec2::new(3.0, 3.0);
@@ -20,15 +49,118 @@ const DAMAGE_DISTANCE: f32 = 2.0;
 const DAMAGE_DISPAWN_TIME_SECONDS: f32 = 1.0;
 
 const TUTORIAL_TEXT: &str =
-    "WASD - Move\nSPACE - Shoot\nF - throw a weapon\n(Throwing weapons also deal damage)";
+    "WASD - Move\nSPACE - Shoot\nRMB - Aim\nF - tap to throw a weapon, hold to drop it\nI - inspect a weapon\nL - laser sight (crafted at a bench)\nT - flashlight\nE - open a door, use a vending machine or a crafting bench, read a terminal, flip a light switch or reset the range\nENTER - skip a cutscene\n(Throwing weapons also deal damage)";
 const TUTORIAL_TEXT_DISPAWN_TIME_SECONDS: f32 = 5.0;
 const BOSS_TEXT: &str = "THE RED DRAGON LAIR";
 const BOSS_TEXT_DISPAWN_TIME_SECONDS: f32 = 2.0;
+const FLOOR_TEXT_TRANSLATION: Vec3 = Vec3::new(0.0, 120.0, 0.0);
+const FLOOR_TEXT_DISPAWN_TIME_SECONDS: f32 = 2.0;
+// Screen goes black for a moment when the
+// player enters a level and fades back
+const LEVEL_FADE_SIZE: Vec2 = Vec2::new(1280.0, 720.0);
+const LEVEL_FADE_DISPAWN_TIME_SECONDS: f32 = 0.6;
+
+// Frost creeps in from the screen edges
+// while the player is chilled
+const FROST_VIGNETTE_SCREEN: Vec2 = Vec2::new(1280.0, 720.0);
+const FROST_VIGNETTE_THICKNESS: f32 = 80.0;
+const FROST_VIGNETTE_COLOR: Color = Color::rgba(0.7, 0.9, 1.0, 0.0);
+const FROST_VIGNETTE_MAX_ALPHA: f32 = 0.6;
+
+// Rank letter with the multiplier and a bar
+// filling up to the next rank
+const STYLE_RANK_TRANSLATION: Vec3 = Vec3::new(540.0, 180.0, 0.0);
+const STYLE_RANK_SIZE: f32 = 60.0;
+const STYLE_BAR_TRANSLATION: Vec3 = Vec3::new(540.0, 110.0, 0.0);
+const STYLE_BAR_SIZE: Vec2 = Vec2::new(120.0, 8.0);
+
+const TIME_ATTACK_TIMER_TRANSLATION: Vec3 = Vec3::new(0.0, 320.0, 0.0);
+const TIME_ATTACK_SPLIT_TRANSLATION: Vec3 = Vec3::new(0.0, 200.0, 0.0);
+const TIME_ATTACK_SPLIT_DISPAWN_TIME_SECONDS: f32 = 3.0;
+
+const CHECKPOINT_TEXT_TRANSLATION: Vec3 = Vec3::new(0.0, 200.0, 0.0);
+const CHECKPOINT_TEXT_DISPAWN_TIME_SECONDS: f32 = 2.0;
+
+const UNLOCK_TEXT_TRANSLATION: Vec3 = Vec3::new(0.0, 260.0, 0.0);
+const UNLOCK_TEXT_DISPAWN_TIME_SECONDS: f32 = 3.0;
+
+const NO_AMMO_TEXT: &str = "NO AMMO";
+const NO_AMMO_TEXT_TRANSLATION: Vec3 = Vec3::new(0.0, -60.0, 0.0);
+const NO_AMMO_TEXT_DISPAWN_TIME_SECONDS: f32 = 0.5;
+
+const CAPTURE_TOAST_TRANSLATION: Vec3 = Vec3::new(500.0, 320.0, 0.0);
+const CAPTURE_TOAST_DISPAWN_TIME_SECONDS: f32 = 2.0;
+
+const DOOR_PROMPT_TRANSLATION: Vec3 = Vec3::new(0.0, -100.0, 0.0);
+const VENDING_PROMPT_TRANSLATION: Vec3 = Vec3::new(0.0, -100.0, 0.0);
+const CRAFTING_PROMPT_TRANSLATION: Vec3 = Vec3::new(0.0, -100.0, 0.0);
+const LIGHT_SWITCH_PROMPT_TRANSLATION: Vec3 = Vec3::new(0.0, -100.0, 0.0);
+const LADDER_PROMPT_TRANSLATION: Vec3 = Vec3::new(0.0, -100.0, 0.0);
+const WEAPON_PROMPT_TRANSLATION: Vec3 = Vec3::new(0.0, -140.0, 0.0);
+const RANGE_PROMPT_TRANSLATION: Vec3 = Vec3::new(0.0, -100.0, 0.0);
+const RANGE_SCORE_TRANSLATION: Vec3 = Vec3::new(0.0, 280.0, 0.0);
+const DEFENSE_TEXT_TRANSLATION: Vec3 = Vec3::new(0.0, 280.0, 0.0);
+const DEFENSE_TEXT_COLOR: Color = Color::ORANGE_RED;
+const TERMINAL_PROMPT_TRANSLATION: Vec3 = Vec3::new(0.0, -100.0, 0.0);
+const TERMINAL_PANEL_TRANSLATION: Vec3 = Vec3::new(0.0, 100.0, 0.0);
+const TERMINAL_PANEL_SIZE: Vec2 = Vec2::new(1000.0, 240.0);
+const TERMINAL_PANEL_COLOR: Color = Color::rgba(0.0, 0.1, 0.05, 0.8);
+const TERMINAL_TEXT_SIZE: f32 = 30.0;
+const TERMINAL_TEXT_COLOR: Color = Color::SEA_GREEN;
+
+// Accessibility indicators for important sounds
+const GUNSHOT_PIP_COLOR: Color = Color::ORANGE;
+const GUNSHOT_PIP_SIZE: Vec2 = Vec2::new(8.0, 8.0);
+const GUNSHOT_PIP_DISTANCE: f32 = 150.0;
+const GUNSHOT_PIP_DISPAWN_TIME_SECONDS: f32 = 0.5;
+const CAPTIONS_TRANSLATION: Vec3 = Vec3::new(0.0, -200.0, 0.0);
+const CAPTIONS_MAX_LINES: usize = 3;
+const CAPTIONS_DISPAWN_TIME_SECONDS: f32 = 3.0;
+
+const POWERUP_ICONS_TRANSLATION: Vec3 = Vec3::new(-560.0, -300.0, 0.0);
+const POWERUP_ICON_SPACING: f32 = 160.0;
+const POWERUP_ICON_SIZE: Vec2 = Vec2::new(20.0, 20.0);
+const POWERUP_TEXT_OFFSET: Vec3 = Vec3::new(0.0, -30.0, 0.0);
+// Icon blinks when power-up is about to run out
+const POWERUP_BLINK_TIME: f32 = 3.0;
+const POWERUP_BLINK_SPEED: f32 = 10.0;
+
+// Debug overlay, enabled by default only in debug builds
+const DIAGNOSTICS_KEY: KeyCode = KeyCode::F3;
+const DIAGNOSTICS_TRANSLATION: Vec3 = Vec3::new(-620.0, 340.0, 0.0);
+const DIAGNOSTICS_GRAPH_OFFSET: Vec3 = Vec3::new(0.0, -160.0, 0.0);
+const DIAGNOSTICS_GRAPH_BARS: usize = 100;
+const DIAGNOSTICS_GRAPH_BAR_WIDTH: f32 = 2.0;
+// Pixels per millisecond of the frame time
+const DIAGNOSTICS_GRAPH_SCALE: f32 = 2.0;
+const DIAGNOSTICS_GRAPH_COLOR: Color = Color::LIME_GREEN;
+// Frames slower than 60 FPS are highlighted
+const DIAGNOSTICS_GRAPH_SLOW_FRAME_MS: f32 = 1000.0 / 60.0;
+const DIAGNOSTICS_GRAPH_SLOW_COLOR: Color = Color::RED;
+
+// Collider shapes and sensors drawn by rapier,
+// always disabled on start
+const PHYSICS_DEBUG_KEY: KeyCode = KeyCode::F4;
+
+// HUD scale and the margin on each side of the
+// screen, the margin is a part of the screen size
+pub const HUD_SCALE_MIN: f32 = 0.75;
+pub const HUD_SCALE_MAX: f32 = 1.5;
+pub const HUD_SCALE_STEP: f32 = 0.05;
+pub const HUD_SAFE_AREA_MAX: f32 = 0.1;
+pub const HUD_SAFE_AREA_STEP: f32 = 0.01;
+// Only the crosshair is on this layer, minimal
+// HUD camera renders nothing else
+const HUD_MINIMAL_LAYER: u8 = 3;
 
 pub struct HudPlugin;
 
 impl Plugin for HudPlugin {
     fn build(&self, app: &mut App) {
+        app.insert_resource(HudDiagnostics {
+            enabled: cfg!(debug_assertions),
+        });
+
         app.add_systems(
             OnTransition {
                 from: GlobalState::AssetLoading,
@@ -61,6 +193,21 @@ impl Plugin for HudPlugin {
             enable_hud,
         );
 
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::InGame,
+                to: GlobalState::PerkSelection,
+            },
+            disable_hud,
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::PerkSelection,
+                to: GlobalState::InGame,
+            },
+            enable_hud,
+        );
+
         app.add_systems(
             OnTransition {
                 from: GlobalState::InGame,
@@ -84,39 +231,169 @@ impl Plugin for HudPlugin {
             },
             (disable_hud, despawn_all_timed_elements),
         );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::GameWon,
+                to: GlobalState::InGame,
+            },
+            (enable_hud, despawn_all_timed_elements),
+        );
 
         app.add_systems(
             Update,
             (
+                update_crosshair,
+                show_hit_marker,
                 display_incomming_damage,
                 progress_timed_elements,
                 show_boss_text,
+                show_floor_text,
+                update_level_fade,
+                update_time_attack_timer,
+                show_time_attack_split,
+                update_door_prompt,
+                update_vending_prompt,
+                update_weapon_prompt,
+                update_terminal_prompt,
+                update_powerup_icons,
+                show_checkpoint_text,
+                show_weapon_unlocked_text,
+                show_no_ammo_text,
+                show_gunshot_pips,
+                show_sound_captions,
+                update_diagnostics,
+            )
+                .run_if(in_state(GlobalState::InGame)),
+        );
+        app.add_systems(
+            Update,
+            (
+                update_range_prompt,
+                show_capture_toast,
+                update_crafting_prompt,
+                update_light_switch_prompt,
+                update_ladder_prompt,
+                update_range_score,
+                update_defense_text,
+                update_frost_vignette,
+                update_style_meter,
             )
                 .run_if(in_state(GlobalState::InGame)),
         );
+        app.add_systems(
+            Update,
+            (toggle_diagnostics, toggle_physics_debug, apply_hud_settings),
+        );
     }
 }
 
+#[derive(Resource)]
+struct HudDiagnostics {
+    enabled: bool,
+}
+
+#[derive(Component)]
+struct HudDiagnosticsOverlay;
+
+#[derive(Component)]
+struct HudDiagnosticsText;
+
+#[derive(Component)]
+struct HudDiagnosticsBar {
+    index: usize,
+}
+
 #[derive(Component)]
 struct HudCamera;
 
+#[derive(Component)]
+struct HudCrosshair;
+
+#[derive(Component)]
+struct HudCaptions;
+
 #[derive(Component)]
 struct HudTimedElement {
     spawn_time: f32,
     lifespawn: f32,
 }
 
+#[derive(Component)]
+struct HudTimeAttackTimer;
+
+#[derive(Component)]
+struct HudFrostVignette;
+
+#[derive(Component)]
+struct HudStyleRank;
+
+#[derive(Component)]
+struct HudStyleBar;
+
+#[derive(Component)]
+struct HudRangePrompt;
+
+#[derive(Component)]
+struct HudRangeScore;
+
+#[derive(Component)]
+struct HudDefenseText;
+
+#[derive(Component)]
+struct HudNoAmmo;
+
+#[derive(Component)]
+struct HudLevelFade;
+
+#[derive(Component)]
+struct HudDoorPrompt;
+
+#[derive(Component)]
+struct HudVendingPrompt;
+
+#[derive(Component)]
+struct HudCraftingPrompt;
+
+#[derive(Component)]
+struct HudLightSwitchPrompt;
+
+#[derive(Component)]
+struct HudLadderPrompt;
+
+#[derive(Component)]
+struct HudWeaponPrompt;
+
+#[derive(Component)]
+struct HudTerminalPrompt;
+
+#[derive(Component)]
+struct HudTerminalPanel;
+
+#[derive(Component)]
+struct HudTerminalText;
+
+#[derive(Component)]
+struct HudPowerupIcon {
+    powerup_type: PowerupType,
+}
+
 #[derive(Resource)]
 struct HudResources {
     text_style: TextStyle,
     boss_text_style: TextStyle,
+    timer_text_style: TextStyle,
 }
 
-fn init_hud(ui_assets: Res<UiAssets>, mut commands: Commands) {
+fn init_hud(
+    ui_assets: Res<UiAssets>,
+    crosshair_settings: Res<CrosshairSettings>,
+    mut commands: Commands,
+) {
     commands.spawn((
         Camera2dBundle {
             camera: Camera {
-                order: 1,
+                // after the world and viewmodel cameras
+                order: 2,
                 is_active: false,
                 ..default()
             },
@@ -126,57 +403,355 @@ fn init_hud(ui_assets: Res<UiAssets>, mut commands: Commands) {
             ..default()
         },
         UiCameraConfig { show_ui: false },
+        RenderLayers::default(),
         HudCamera,
     ));
 
     // Crosshair
-    // Top right
-    commands.spawn(SpriteBundle {
-        sprite: Sprite {
-            color: CROSSHAIR_COLOR,
-            custom_size: Some(CROSSHAIR_SIZE),
+    // Rebuilt every time settings change
+    commands
+        .spawn((SpatialBundle::default(), HudCrosshair))
+        .with_children(|builder| {
+            spawn_crosshair(
+                builder,
+                crosshair_settings.as_ref(),
+                crosshair_settings.color.color(),
+                RenderLayers::default().with(HUD_MINIMAL_LAYER),
+            );
+        });
+
+    // Frost vignette
+    // One transparent strip along each screen edge
+    let half_screen = FROST_VIGNETTE_SCREEN / 2.0;
+    let half_thickness = FROST_VIGNETTE_THICKNESS / 2.0;
+    for (size, translation) in [
+        (
+            Vec2::new(FROST_VIGNETTE_SCREEN.x, FROST_VIGNETTE_THICKNESS),
+            Vec3::new(0.0, half_screen.y - half_thickness, 0.0),
+        ),
+        (
+            Vec2::new(FROST_VIGNETTE_SCREEN.x, FROST_VIGNETTE_THICKNESS),
+            Vec3::new(0.0, -half_screen.y + half_thickness, 0.0),
+        ),
+        (
+            Vec2::new(FROST_VIGNETTE_THICKNESS, FROST_VIGNETTE_SCREEN.y),
+            Vec3::new(-half_screen.x + half_thickness, 0.0, 0.0),
+        ),
+        (
+            Vec2::new(FROST_VIGNETTE_THICKNESS, FROST_VIGNETTE_SCREEN.y),
+            Vec3::new(half_screen.x - half_thickness, 0.0, 0.0),
+        ),
+    ] {
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: FROST_VIGNETTE_COLOR,
+                    custom_size: Some(size),
+                    ..default()
+                },
+                transform: Transform::from_translation(translation),
+                ..default()
+            },
+            HudFrostVignette,
+        ));
+    }
+
+    let timer_text_style = TextStyle {
+        font: ui_assets.font.clone(),
+        font_size: 40.0,
+        color: Color::WHITE,
+    };
+
+    // Style rank and bar
+    // Stay hidden while the meter is empty
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: ui_assets.font.clone(),
+                    font_size: STYLE_RANK_SIZE,
+                    color: Color::WHITE,
+                },
+            )
+            .with_alignment(TextAlignment::Center),
+            transform: Transform::from_translation(STYLE_RANK_TRANSLATION),
+            ..default()
+        },
+        HudStyleRank,
+    ));
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                custom_size: Some(STYLE_BAR_SIZE),
+                anchor: Anchor::CenterLeft,
+                ..default()
+            },
+            transform: Transform::from_translation(
+                STYLE_BAR_TRANSLATION - Vec3::X * STYLE_BAR_SIZE.x / 2.0,
+            ),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        HudStyleBar,
+    ));
+
+    // Time attack timer
+    // Stays empty outside of time attack
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section("", timer_text_style.clone())
+                .with_alignment(TextAlignment::Center),
+            transform: Transform::from_translation(TIME_ATTACK_TIMER_TRANSLATION),
+            ..default()
+        },
+        HudTimeAttackTimer,
+    ));
+
+    // Door prompt
+    // Stays empty unless player aims at a door
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section("", timer_text_style.clone())
+                .with_alignment(TextAlignment::Center),
+            transform: Transform::from_translation(DOOR_PROMPT_TRANSLATION),
+            ..default()
+        },
+        HudDoorPrompt,
+    ));
+
+    // Vending machine prompt
+    // Stays empty unless player aims at a vending machine
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section("", timer_text_style.clone())
+                .with_alignment(TextAlignment::Center),
+            transform: Transform::from_translation(VENDING_PROMPT_TRANSLATION),
+            ..default()
+        },
+        HudVendingPrompt,
+    ));
+
+    // Crafting bench prompt
+    // Stays empty unless player aims at a crafting bench
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section("", timer_text_style.clone())
+                .with_alignment(TextAlignment::Center),
+            transform: Transform::from_translation(CRAFTING_PROMPT_TRANSLATION),
+            ..default()
+        },
+        HudCraftingPrompt,
+    ));
+
+    // Light switch prompt
+    // Stays empty unless player aims at a light switch
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section("", timer_text_style.clone())
+                .with_alignment(TextAlignment::Center),
+            transform: Transform::from_translation(LIGHT_SWITCH_PROMPT_TRANSLATION),
+            ..default()
+        },
+        HudLightSwitchPrompt,
+    ));
+
+    // Ladder prompt
+    // Stays empty unless player aims at a ladder
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section("", timer_text_style.clone())
+                .with_alignment(TextAlignment::Center),
+            transform: Transform::from_translation(LADDER_PROMPT_TRANSLATION),
             ..default()
         },
-        transform: Transform::from_translation(Vec3::new(10.0, 10.0, 0.0))
-            .with_rotation(Quat::from_rotation_z(CROSSHAIR_ROTATION)),
-        ..default()
-    });
-
-    // Bottom right
-    commands.spawn(SpriteBundle {
-        sprite: Sprite {
-            color: CROSSHAIR_COLOR,
-            custom_size: Some(CROSSHAIR_SIZE),
+        HudLadderPrompt,
+    ));
+
+    // Shooting range reset prompt and score
+    // Stay empty outside of the tutorial range
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section("", timer_text_style.clone())
+                .with_alignment(TextAlignment::Center),
+            transform: Transform::from_translation(RANGE_PROMPT_TRANSLATION),
+            ..default()
+        },
+        HudRangePrompt,
+    ));
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section("", timer_text_style.clone())
+                .with_alignment(TextAlignment::Center),
+            transform: Transform::from_translation(RANGE_SCORE_TRANSLATION),
+            ..default()
+        },
+        HudRangeScore,
+    ));
+
+    // Door defense timer
+    // Stays empty unless a door is defended
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    color: DEFENSE_TEXT_COLOR,
+                    ..timer_text_style.clone()
+                },
+            )
+            .with_alignment(TextAlignment::Center),
+            transform: Transform::from_translation(DEFENSE_TEXT_TRANSLATION),
+            ..default()
+        },
+        HudDefenseText,
+    ));
+
+    // Weapon prompt
+    // Stays empty unless player looks at a floating weapon
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section("", timer_text_style.clone())
+                .with_alignment(TextAlignment::Center),
+            transform: Transform::from_translation(WEAPON_PROMPT_TRANSLATION),
             ..default()
         },
-        transform: Transform::from_translation(Vec3::new(10.0, -10.0, 0.0))
-            .with_rotation(Quat::from_rotation_z(-CROSSHAIR_ROTATION)),
-        ..default()
-    });
-
-    // Top left
-    commands.spawn(SpriteBundle {
-        sprite: Sprite {
-            color: CROSSHAIR_COLOR,
-            custom_size: Some(CROSSHAIR_SIZE),
+        HudWeaponPrompt,
+    ));
+
+    // Terminal prompt and panel with the lore entry
+    // Stay hidden unless player aims at a terminal
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section("", timer_text_style.clone())
+                .with_alignment(TextAlignment::Center),
+            transform: Transform::from_translation(TERMINAL_PROMPT_TRANSLATION),
             ..default()
         },
-        transform: Transform::from_translation(Vec3::new(-10.0, 10.0, 0.0))
-            .with_rotation(Quat::from_rotation_z(-CROSSHAIR_ROTATION)),
-        ..default()
-    });
-
-    // Bottom left
-    commands.spawn(SpriteBundle {
-        sprite: Sprite {
-            color: CROSSHAIR_COLOR,
-            custom_size: Some(CROSSHAIR_SIZE),
+        HudTerminalPrompt,
+    ));
+    commands
+        .spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: TERMINAL_PANEL_COLOR,
+                    custom_size: Some(TERMINAL_PANEL_SIZE),
+                    ..default()
+                },
+                transform: Transform::from_translation(TERMINAL_PANEL_TRANSLATION),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            HudTerminalPanel,
+        ))
+        .with_children(|builder| {
+            builder.spawn((
+                Text2dBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: ui_assets.font.clone(),
+                            font_size: TERMINAL_TEXT_SIZE,
+                            color: TERMINAL_TEXT_COLOR,
+                        },
+                    )
+                    .with_alignment(TextAlignment::Center),
+                    // text is drawn above the panel
+                    transform: Transform::from_translation(Vec3::Z),
+                    ..default()
+                },
+                HudTerminalText,
+            ));
+        });
+
+    // Sound captions
+    // Stays empty unless captions are enabled
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section("", timer_text_style.clone())
+                .with_alignment(TextAlignment::Center),
+            transform: Transform::from_translation(CAPTIONS_TRANSLATION),
             ..default()
         },
-        transform: Transform::from_translation(Vec3::new(-10.0, -10.0, 0.0))
-            .with_rotation(Quat::from_rotation_z(CROSSHAIR_ROTATION)),
-        ..default()
-    });
+        HudCaptions,
+    ));
+
+    // Power-up countdown icons
+    // Hidden unless power-up is active
+    let powerup_text_style = TextStyle {
+        font_size: 24.0,
+        ..timer_text_style.clone()
+    };
+    for (i, powerup_type) in PowerupType::ALL.into_iter().enumerate() {
+        commands
+            .spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: powerup_type.color(),
+                        custom_size: Some(POWERUP_ICON_SIZE),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(
+                        POWERUP_ICONS_TRANSLATION + Vec3::X * POWERUP_ICON_SPACING * i as f32,
+                    ),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                HudPowerupIcon { powerup_type },
+            ))
+            .with_children(|builder| {
+                builder.spawn(Text2dBundle {
+                    text: Text::from_section("", powerup_text_style.clone())
+                        .with_alignment(TextAlignment::Center),
+                    transform: Transform::from_translation(POWERUP_TEXT_OFFSET),
+                    ..default()
+                });
+            });
+    }
+
+    // Diagnostics overlay
+    // Text with the frame time graph below it
+    let diagnostics_text_style = TextStyle {
+        font_size: 20.0,
+        ..timer_text_style.clone()
+    };
+    commands
+        .spawn((
+            SpatialBundle {
+                transform: Transform::from_translation(DIAGNOSTICS_TRANSLATION),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            HudDiagnosticsOverlay,
+        ))
+        .with_children(|builder| {
+            builder.spawn((
+                Text2dBundle {
+                    text: Text::from_section("", diagnostics_text_style),
+                    text_anchor: Anchor::TopLeft,
+                    ..default()
+                },
+                HudDiagnosticsText,
+            ));
+            for index in 0..DIAGNOSTICS_GRAPH_BARS {
+                builder.spawn((
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: DIAGNOSTICS_GRAPH_COLOR,
+                            custom_size: Some(Vec2::new(DIAGNOSTICS_GRAPH_BAR_WIDTH, 0.0)),
+                            anchor: Anchor::BottomLeft,
+                            ..default()
+                        },
+                        transform: Transform::from_translation(
+                            DIAGNOSTICS_GRAPH_OFFSET
+                                + Vec3::X * DIAGNOSTICS_GRAPH_BAR_WIDTH * index as f32,
+                        ),
+                        ..default()
+                    },
+                    HudDiagnosticsBar { index },
+                ));
+            }
+        });
 
     commands.insert_resource(HudResources {
         text_style: TextStyle {
@@ -189,9 +764,36 @@ fn init_hud(ui_assets: Res<UiAssets>, mut commands: Commands) {
             font_size: 80.0,
             color: Color::ORANGE_RED,
         },
+        timer_text_style,
     })
 }
 
+// Whole layout is scaled around the center of the
+// screen, so the safe area shrinks it to fit the margins
+fn apply_hud_settings(
+    game_settings: Res<GameSettings>,
+    mut hud_camera: Query<(
+        Ref<HudCamera>,
+        &mut OrthographicProjection,
+        &mut RenderLayers,
+    )>,
+) {
+    let Ok((hud_camera, mut projection, mut layers)) = hud_camera.get_single_mut() else {
+        return;
+    };
+    if !game_settings.is_changed() && !hud_camera.is_added() {
+        return;
+    }
+
+    let scale = game_settings.hud_scale * (1.0 - 2.0 * game_settings.hud_safe_area);
+    projection.scale = 1.0 / scale;
+    *layers = if game_settings.minimal_hud {
+        RenderLayers::layer(HUD_MINIMAL_LAYER)
+    } else {
+        RenderLayers::default().with(HUD_MINIMAL_LAYER)
+    };
+}
+
 fn disable_hud(mut hud_camera: Query<&mut Camera, With<HudCamera>>) {
     let Ok(mut camera) = hud_camera.get_single_mut() else {
         return;
@@ -200,7 +802,14 @@ fn disable_hud(mut hud_camera: Query<&mut Camera, With<HudCamera>>) {
     camera.is_active = false;
 }
 
-fn enable_hud(mut hud_camera: Query<&mut Camera, With<HudCamera>>) {
+fn enable_hud(
+    run_modifiers: Res<RunModifiers>,
+    mut hud_camera: Query<&mut Camera, With<HudCamera>>,
+) {
+    if run_modifiers.has(RunModifier::NoHud) {
+        return;
+    }
+
     let Ok(mut camera) = hud_camera.get_single_mut() else {
         return;
     };
@@ -246,22 +855,282 @@ fn show_boss_text(
     }
 }
 
-fn display_incomming_damage(
+fn show_floor_text(
     time: Res<Time>,
-    player: Query<Entity, With<Player>>,
-    player_camera: Query<&Transform, With<PlayerCamera>>,
+    hud_resources: Res<HudResources>,
+    level_info: Res<LevelInfo>,
     mut commands: Commands,
-    mut damage_events: EventReader<DamageEvent>,
+    mut level_started_events: EventReader<LevelStarted>,
 ) {
-    let Ok(player) = player.get_single() else {
-        return;
-    };
-    let Ok(player_camera_transform) = player_camera.get_single() else {
-        return;
-    };
-
-    for event in damage_events.read() {
-        if event.entity != player {
+    for _ in level_started_events.read() {
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::BLACK,
+                    custom_size: Some(LEVEL_FADE_SIZE),
+                    ..default()
+                },
+                ..default()
+            },
+            HudTimedElement {
+                spawn_time: time.elapsed_seconds(),
+                lifespawn: LEVEL_FADE_DISPAWN_TIME_SECONDS,
+            },
+            HudLevelFade,
+        ));
+
+        // boss level has its own title
+        if level_info.game_progress == 100 {
+            continue;
+        }
+        // progress is 0 on the first level after the tutorial
+        let floor = level_info.game_progress / 10 + 1;
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section(
+                    format!("FLOOR {floor}"),
+                    hud_resources.text_style.clone(),
+                )
+                .with_alignment(TextAlignment::Center),
+                // above the fade
+                transform: Transform::from_translation(FLOOR_TEXT_TRANSLATION + Vec3::Z),
+                ..default()
+            },
+            HudTimedElement {
+                spawn_time: time.elapsed_seconds(),
+                lifespawn: FLOOR_TEXT_DISPAWN_TIME_SECONDS,
+            },
+        ));
+    }
+}
+
+fn update_level_fade(
+    time: Res<Time>,
+    mut fades: Query<(&HudTimedElement, &mut Sprite), With<HudLevelFade>>,
+) {
+    for (element, mut sprite) in fades.iter_mut() {
+        let progress = (time.elapsed_seconds() - element.spawn_time) / element.lifespawn;
+        sprite.color.set_a((1.0 - progress).clamp(0.0, 1.0));
+    }
+}
+
+fn update_crosshair(
+    crosshair_settings: Res<CrosshairSettings>,
+    crosshair: Query<Entity, With<HudCrosshair>>,
+    mut commands: Commands,
+) {
+    if !crosshair_settings.is_changed() {
+        return;
+    }
+
+    let Ok(crosshair) = crosshair.get_single() else {
+        return;
+    };
+
+    commands
+        .entity(crosshair)
+        .despawn_descendants()
+        .with_children(|builder| {
+            spawn_crosshair(
+                builder,
+                crosshair_settings.as_ref(),
+                crosshair_settings.color.color(),
+                RenderLayers::default().with(HUD_MINIMAL_LAYER),
+            );
+        });
+}
+
+fn show_hit_marker(
+    time: Res<Time>,
+    crosshair_settings: Res<CrosshairSettings>,
+    enemies: Query<(), With<Enemy>>,
+    mut commands: Commands,
+    mut damage_events: EventReader<DamageEvent>,
+    mut kill_events: EventReader<KillEvent>,
+) {
+    let damaged = damage_events.read().map(|event| event.entity);
+    let killed = kill_events.read().map(|event| event.entity);
+    let hits = damaged
+        .chain(killed)
+        .filter(|entity| enemies.contains(*entity))
+        .count();
+
+    // one marker per frame is enough
+    if hits == 0 {
+        return;
+    }
+
+    commands
+        .spawn((
+            // drawn on top of the crosshair
+            SpatialBundle::from_transform(Transform::from_xyz(0.0, 0.0, 2.0)),
+            HudTimedElement {
+                spawn_time: time.elapsed_seconds(),
+                lifespawn: HIT_MARKER_DISPAWN_TIME_SECONDS,
+            },
+        ))
+        .with_children(|builder| {
+            spawn_hit_marker(builder, crosshair_settings.as_ref());
+        });
+}
+
+// Where the sound comes from relative to the player
+fn sound_direction(camera_global_transform: &GlobalTransform, translation: Vec3) -> &'static str {
+    let v = translation - camera_global_transform.translation();
+    let forward = camera_global_transform.forward().dot(v);
+    let right = camera_global_transform.right().dot(v);
+    if right.abs() <= forward.abs() {
+        if 0.0 < forward {
+            "ahead"
+        } else {
+            "behind you"
+        }
+    } else if 0.0 < right {
+        "to the right"
+    } else {
+        "to the left"
+    }
+}
+
+fn show_gunshot_pips(
+    time: Res<Time>,
+    game_settings: Res<GameSettings>,
+    player_weapon: Query<(), With<PlayerWeapon>>,
+    player_cameras: Query<(&PlayerId, &GlobalTransform), With<PlayerCamera>>,
+    mut commands: Commands,
+    mut shoot_events: EventReader<ShootEvent>,
+) {
+    if !game_settings.sound_captions {
+        shoot_events.clear();
+        return;
+    }
+
+    let Some((_, camera_global_transform)) = player_cameras.iter().find(|(id, _)| id.is_local())
+    else {
+        return;
+    };
+
+    for event in shoot_events.read() {
+        if player_weapon.contains(event.weapon_entity) {
+            continue;
+        }
+
+        let v = event.weapon_translation - camera_global_transform.translation();
+        let direction = Vec2::new(
+            camera_global_transform.right().dot(v),
+            camera_global_transform.forward().dot(v),
+        )
+        .normalize_or_zero();
+
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: GUNSHOT_PIP_COLOR,
+                    custom_size: Some(GUNSHOT_PIP_SIZE),
+                    ..default()
+                },
+                transform: Transform::from_translation(
+                    (direction * GUNSHOT_PIP_DISTANCE).extend(0.0),
+                ),
+                ..default()
+            },
+            HudTimedElement {
+                spawn_time: time.elapsed_seconds(),
+                lifespawn: GUNSHOT_PIP_DISPAWN_TIME_SECONDS,
+            },
+        ));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn show_sound_captions(
+    time: Res<Time>,
+    game_settings: Res<GameSettings>,
+    player_cameras: Query<(&PlayerId, &GlobalTransform), With<PlayerCamera>>,
+    spawning_enemies: Query<&GlobalTransform, Added<EnemySpawnTelegraph>>,
+    doors: Query<&GlobalTransform>,
+    mut captions_text: Query<&mut Text, With<HudCaptions>>,
+    mut captions: Local<Vec<(f32, String)>>,
+    mut door_started_events: EventReader<DoorAnimationStarted>,
+    mut door_locked_events: EventReader<DoorLockedInteraction>,
+    mut noise_events: EventReader<NoiseEvent>,
+) {
+    let Ok(mut text) = captions_text.get_single_mut() else {
+        return;
+    };
+
+    if !game_settings.sound_captions {
+        door_started_events.clear();
+        door_locked_events.clear();
+        noise_events.clear();
+        captions.clear();
+        if !text.sections[0].value.is_empty() {
+            text.sections[0].value.clear();
+        }
+        return;
+    }
+
+    let Some((_, camera_global_transform)) = player_cameras.iter().find(|(id, _)| id.is_local())
+    else {
+        return;
+    };
+
+    let now = time.elapsed_seconds();
+    let mut add_caption = |caption: String| captions.push((now, caption));
+
+    for enemy_global_transform in spawning_enemies.iter() {
+        let direction = sound_direction(
+            camera_global_transform,
+            enemy_global_transform.translation(),
+        );
+        add_caption(format!("[fridge powering up {direction}]"));
+    }
+    for event in door_started_events.read().filter(|e| e.opening) {
+        let Ok(door_global_transform) = doors.get(event.door) else {
+            continue;
+        };
+        let direction =
+            sound_direction(camera_global_transform, door_global_transform.translation());
+        add_caption(format!("[door opens {direction}]"));
+    }
+    for _ in door_locked_events.read() {
+        add_caption("[door is locked]".to_string());
+    }
+    for event in noise_events.read() {
+        let direction = sound_direction(camera_global_transform, event.translation);
+        add_caption(format!("[loud crash {direction}]"));
+    }
+
+    captions.retain(|(spawn_time, _)| now < spawn_time + CAPTIONS_DISPAWN_TIME_SECONDS);
+    let skip = captions.len().saturating_sub(CAPTIONS_MAX_LINES);
+    let value = captions
+        .iter()
+        .skip(skip)
+        .map(|(_, caption)| caption.as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+    if text.sections[0].value != value {
+        text.sections[0].value = value;
+    }
+}
+
+fn display_incomming_damage(
+    time: Res<Time>,
+    players: Query<(Entity, &PlayerId), With<Player>>,
+    player_cameras: Query<(&PlayerId, &Transform), With<PlayerCamera>>,
+    mut commands: Commands,
+    mut damage_events: EventReader<DamageEvent>,
+) {
+    let Some((player, _)) = players.iter().find(|(_, id)| id.is_local()) else {
+        return;
+    };
+    let Some((_, player_camera_transform)) = player_cameras.iter().find(|(id, _)| id.is_local())
+    else {
+        return;
+    };
+
+    for event in damage_events.read() {
+        if event.entity != player {
             continue;
         }
 
@@ -297,6 +1166,503 @@ fn display_incomming_damage(
     }
 }
 
+fn update_time_attack_timer(
+    time_attack: Res<TimeAttack>,
+    mut timer: Query<&mut Text, With<HudTimeAttackTimer>>,
+) {
+    let Ok(mut text) = timer.get_single_mut() else {
+        return;
+    };
+
+    text.sections[0].value = if time_attack.active {
+        format!(
+            "LEVEL {:.2}  TOTAL {:.2}",
+            time_attack.level_time,
+            time_attack.total_time()
+        )
+    } else {
+        String::new()
+    };
+}
+
+fn update_door_prompt(
+    active_input_device: Res<ActiveInputDevice>,
+    door_prompt: Res<DoorPrompt>,
+    mut prompt: Query<&mut Text, With<HudDoorPrompt>>,
+) {
+    if !door_prompt.is_changed() && !active_input_device.is_changed() {
+        return;
+    }
+    let key = active_input_device.interact_glyph();
+
+    let Ok(mut text) = prompt.get_single_mut() else {
+        return;
+    };
+
+    text.sections[0].value = match *door_prompt {
+        DoorPrompt::None => String::new(),
+        DoorPrompt::Open => format!("Press {key} to open"),
+        DoorPrompt::Sealed => "Sealed".to_string(),
+        DoorPrompt::Locked { enemies: 0 } => "Locked".to_string(),
+        // level is cleared, doors unlock after the grade card
+        DoorPrompt::Locked { enemies: 0 } => "Unlocking...".to_string(),
+        DoorPrompt::Locked { enemies: 1 } => "Locked: 1 enemy remaining".to_string(),
+        DoorPrompt::Locked { enemies } => format!("Locked: {enemies} enemies remaining"),
+    };
+}
+
+fn update_vending_prompt(
+    active_input_device: Res<ActiveInputDevice>,
+    vending_prompt: Res<VendingPrompt>,
+    mut prompt: Query<&mut Text, With<HudVendingPrompt>>,
+) {
+    if !vending_prompt.is_changed() && !active_input_device.is_changed() {
+        return;
+    }
+    let key = active_input_device.interact_glyph();
+
+    let Ok(mut text) = prompt.get_single_mut() else {
+        return;
+    };
+
+    text.sections[0].value = match *vending_prompt {
+        VendingPrompt::None => String::new(),
+        VendingPrompt::Buy { item, price, stock } => {
+            let item = match item {
+                VendingItem::Ammo => "ammo",
+                VendingItem::Health => "health",
+            };
+            if price == 0 {
+                format!("Press {key} to take {item} ({stock} left)")
+            } else {
+                format!("Press {key} to buy {item} for {price} scrap ({stock} left)")
+            }
+        }
+        VendingPrompt::SoldOut => "Sold out".to_string(),
+        VendingPrompt::Jammed => "Jammed".to_string(),
+    };
+}
+
+fn update_crafting_prompt(
+    active_input_device: Res<ActiveInputDevice>,
+    crafting_prompt: Res<CraftingPrompt>,
+    mut prompt: Query<&mut Text, With<HudCraftingPrompt>>,
+) {
+    if !crafting_prompt.is_changed() && !active_input_device.is_changed() {
+        return;
+    }
+    let key = active_input_device.interact_glyph();
+
+    let Ok(mut text) = prompt.get_single_mut() else {
+        return;
+    };
+
+    text.sections[0].value = match *crafting_prompt {
+        CraftingPrompt::None => String::new(),
+        CraftingPrompt::Craft {
+            recipe,
+            index,
+            count,
+        } => format!(
+            "Press {key} to craft {} for {} scrap and {} coolant cells\nTAB - next recipe ({}/{count})",
+            recipe.attachment.name(),
+            recipe.scrap,
+            recipe.coolant_cells,
+            index + 1,
+        ),
+        CraftingPrompt::Installed {
+            attachment,
+            index,
+            count,
+        } => format!(
+            "{} is installed\nTAB - next recipe ({}/{count})",
+            attachment.name(),
+            index + 1,
+        ),
+    };
+}
+
+fn update_light_switch_prompt(
+    active_input_device: Res<ActiveInputDevice>,
+    light_switch_prompt: Res<LightSwitchPrompt>,
+    mut prompt: Query<&mut Text, With<HudLightSwitchPrompt>>,
+) {
+    if !light_switch_prompt.is_changed() && !active_input_device.is_changed() {
+        return;
+    }
+    let key = active_input_device.interact_glyph();
+
+    let Ok(mut text) = prompt.get_single_mut() else {
+        return;
+    };
+
+    text.sections[0].value = match *light_switch_prompt {
+        LightSwitchPrompt::None => String::new(),
+        LightSwitchPrompt::TurnOn => format!("Press {key} to turn the lights on"),
+        LightSwitchPrompt::TurnOff => format!("Press {key} to turn the lights off"),
+    };
+}
+
+fn update_ladder_prompt(
+    active_input_device: Res<ActiveInputDevice>,
+    ladder_prompt: Res<LadderPrompt>,
+    mut prompt: Query<&mut Text, With<HudLadderPrompt>>,
+) {
+    if !ladder_prompt.is_changed() && !active_input_device.is_changed() {
+        return;
+    }
+    let key = active_input_device.interact_glyph();
+
+    let Ok(mut text) = prompt.get_single_mut() else {
+        return;
+    };
+
+    text.sections[0].value = match *ladder_prompt {
+        LadderPrompt::None => String::new(),
+        LadderPrompt::ClimbUp => format!("Press {key} to climb up"),
+        LadderPrompt::ClimbDown => format!("Press {key} to climb down"),
+    };
+}
+
+fn update_frost_vignette(
+    players: Query<(&PlayerId, &Chill)>,
+    mut vignette: Query<&mut Sprite, With<HudFrostVignette>>,
+) {
+    let amount = players
+        .iter()
+        .find(|(id, _)| id.is_local())
+        .map(|(_, chill)| chill.amount)
+        .unwrap_or_default();
+    for mut sprite in vignette.iter_mut() {
+        sprite.color.set_a(amount * FROST_VIGNETTE_MAX_ALPHA);
+    }
+}
+
+fn update_style_meter(
+    style_meter: Res<StyleMeter>,
+    mut rank_text: Query<&mut Text, With<HudStyleRank>>,
+    mut bar: Query<(&mut Sprite, &mut Transform, &mut Visibility), With<HudStyleBar>>,
+) {
+    if !style_meter.is_changed() {
+        return;
+    }
+
+    let Ok(mut text) = rank_text.get_single_mut() else {
+        return;
+    };
+    let Ok((mut sprite, mut transform, mut visibility)) = bar.get_single_mut() else {
+        return;
+    };
+
+    if style_meter.points <= 0.0 {
+        text.sections[0].value = String::new();
+        *visibility = Visibility::Hidden;
+        return;
+    }
+
+    let rank = style_meter.rank();
+    text.sections[0].value = format!("{rank:?}\nx{:.2}", rank.multiplier());
+    text.sections[0].style.color = rank.color();
+    sprite.color = rank.color();
+    transform.scale.x = style_meter.rank_progress();
+    *visibility = Visibility::Visible;
+}
+
+fn update_range_prompt(
+    active_input_device: Res<ActiveInputDevice>,
+    range_prompt: Res<RangePrompt>,
+    mut prompt: Query<&mut Text, With<HudRangePrompt>>,
+) {
+    if !range_prompt.is_changed() && !active_input_device.is_changed() {
+        return;
+    }
+    let key = active_input_device.interact_glyph();
+
+    let Ok(mut text) = prompt.get_single_mut() else {
+        return;
+    };
+
+    text.sections[0].value = match *range_prompt {
+        RangePrompt::None => String::new(),
+        RangePrompt::Reset => format!("Press {key} to reset the range"),
+    };
+}
+
+fn update_range_score(
+    ranges: Query<&ShootingRange>,
+    mut score: Query<&mut Text, With<HudRangeScore>>,
+) {
+    let Ok(mut text) = score.get_single_mut() else {
+        return;
+    };
+
+    text.sections[0].value = match ranges.get_single() {
+        Ok(range) => format!(
+            "HITS {}  SHOTS {}  ACCURACY {:.0}%",
+            range.hits,
+            range.shots,
+            range.accuracy() * 100.0
+        ),
+        Err(_) => String::new(),
+    };
+}
+
+fn update_defense_text(
+    doors: Query<(&DefendedDoor, &Health)>,
+    mut defense_text: Query<&mut Text, With<HudDefenseText>>,
+) {
+    let Ok(mut text) = defense_text.get_single_mut() else {
+        return;
+    };
+
+    text.sections[0].value = match doors
+        .iter()
+        .find(|(defended_door, _)| defended_door.started())
+    {
+        Some((defended_door, health)) => format!(
+            "DEFEND THE DOOR {:.0}s\nDOOR {}  WAVE {}",
+            defended_door.seconds_left(),
+            health.health,
+            defended_door.waves(),
+        ),
+        None => String::new(),
+    };
+}
+
+fn update_terminal_prompt(
+    active_input_device: Res<ActiveInputDevice>,
+    terminal_prompt: Res<TerminalPrompt>,
+    mut prompt: Query<&mut Text, (With<HudTerminalPrompt>, Without<HudTerminalText>)>,
+    mut panel: Query<&mut Visibility, With<HudTerminalPanel>>,
+    mut panel_text: Query<&mut Text, (With<HudTerminalText>, Without<HudTerminalPrompt>)>,
+) {
+    if !terminal_prompt.is_changed() && !active_input_device.is_changed() {
+        return;
+    }
+    let key = active_input_device.interact_glyph();
+
+    let Ok(mut prompt) = prompt.get_single_mut() else {
+        return;
+    };
+    let Ok(mut panel) = panel.get_single_mut() else {
+        return;
+    };
+    let Ok(mut panel_text) = panel_text.get_single_mut() else {
+        return;
+    };
+
+    match *terminal_prompt {
+        TerminalPrompt::None => {
+            prompt.sections[0].value = String::new();
+            *panel = Visibility::Hidden;
+        }
+        TerminalPrompt::Read => {
+            prompt.sections[0].value = format!("Press {key} to read the terminal");
+            *panel = Visibility::Hidden;
+        }
+        TerminalPrompt::Reading { entry } => {
+            let entry = &LORE_ENTRIES[entry];
+            prompt.sections[0].value = format!("Press {key} to close");
+            panel_text.sections[0].value = format!("{}\n\n{}", entry.title, entry.text);
+            *panel = Visibility::Visible;
+        }
+    }
+}
+
+fn update_weapon_prompt(
+    weapon_prompt: Res<WeaponPrompt>,
+    mut prompt: Query<&mut Text, With<HudWeaponPrompt>>,
+) {
+    if !weapon_prompt.is_changed() {
+        return;
+    }
+
+    let Ok(mut text) = prompt.get_single_mut() else {
+        return;
+    };
+
+    match *weapon_prompt {
+        WeaponPrompt::None => text.sections[0].value = String::new(),
+        WeaponPrompt::Weapon { weapon_type, roll } => {
+            text.sections[0].value = format!(
+                "{} {}\n{}",
+                roll.rarity.name(),
+                weapon_type.name(),
+                roll.bonus_text()
+            );
+            text.sections[0].style.color = roll.rarity.color();
+        }
+    }
+}
+
+fn update_powerup_icons(
+    time: Res<Time>,
+    active_powerups: Res<ActivePowerups>,
+    mut icons: Query<(&HudPowerupIcon, &Children, &mut Sprite, &mut Visibility)>,
+    mut texts: Query<&mut Text>,
+) {
+    for (icon, icon_children, mut sprite, mut visibility) in icons.iter_mut() {
+        let remaining = active_powerups.remaining(icon.powerup_type);
+        if remaining <= 0.0 {
+            *visibility = Visibility::Hidden;
+            continue;
+        }
+        *visibility = Visibility::Inherited;
+
+        let blink = remaining < POWERUP_BLINK_TIME
+            && (time.elapsed_seconds() * POWERUP_BLINK_SPEED).sin() < 0.0;
+        sprite.color.set_a(if blink { 0.2 } else { 1.0 });
+
+        let Ok(mut text) = texts.get_mut(icon_children[0]) else {
+            continue;
+        };
+        text.sections[0].value = format!(
+            "{} {}",
+            icon.powerup_type.short_name(),
+            remaining.ceil() as u32
+        );
+    }
+}
+
+fn show_checkpoint_text(
+    time: Res<Time>,
+    hud_resources: Res<HudResources>,
+    mut commands: Commands,
+    mut checkpoint_reached_events: EventReader<CheckpointReached>,
+    mut checkpoint_respawn_events: EventReader<CheckpointRespawn>,
+) {
+    let reached = checkpoint_reached_events.read().map(|_| "CHECKPOINT");
+    let respawned = checkpoint_respawn_events
+        .read()
+        .filter(|CheckpointRespawn(player_id)| player_id.is_local())
+        .map(|_| "SPARE FUSE USED");
+
+    for checkpoint_text in reached.chain(respawned) {
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section(checkpoint_text, hud_resources.timer_text_style.clone())
+                    .with_alignment(TextAlignment::Center),
+                transform: Transform::from_translation(CHECKPOINT_TEXT_TRANSLATION),
+                ..default()
+            },
+            HudTimedElement {
+                spawn_time: time.elapsed_seconds(),
+                lifespawn: CHECKPOINT_TEXT_DISPAWN_TIME_SECONDS,
+            },
+        ));
+    }
+}
+
+fn show_weapon_unlocked_text(
+    time: Res<Time>,
+    hud_resources: Res<HudResources>,
+    mut commands: Commands,
+    mut weapon_unlocked_events: EventReader<WeaponUnlocked>,
+) {
+    for event in weapon_unlocked_events.read() {
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section(
+                    format!("{} UNLOCKED", event.weapon_type.name()),
+                    hud_resources.timer_text_style.clone(),
+                )
+                .with_alignment(TextAlignment::Center),
+                transform: Transform::from_translation(UNLOCK_TEXT_TRANSLATION),
+                ..default()
+            },
+            HudTimedElement {
+                spawn_time: time.elapsed_seconds(),
+                lifespawn: UNLOCK_TEXT_DISPAWN_TIME_SECONDS,
+            },
+        ));
+    }
+}
+
+// New flash replaces the old one, so
+// fast clicking does not stack the text
+fn show_no_ammo_text(
+    time: Res<Time>,
+    hud_resources: Res<HudResources>,
+    no_ammo_texts: Query<Entity, With<HudNoAmmo>>,
+    mut commands: Commands,
+    mut dry_fire_events: EventReader<DryFireEvent>,
+) {
+    if dry_fire_events.read().count() == 0 {
+        return;
+    }
+    for entity in no_ammo_texts.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(NO_AMMO_TEXT, hud_resources.timer_text_style.clone())
+                .with_alignment(TextAlignment::Center),
+            transform: Transform::from_translation(NO_AMMO_TEXT_TRANSLATION),
+            ..default()
+        },
+        HudTimedElement {
+            spawn_time: time.elapsed_seconds(),
+            lifespawn: NO_AMMO_TEXT_DISPAWN_TIME_SECONDS,
+        },
+        HudNoAmmo,
+    ));
+}
+
+fn show_capture_toast(
+    time: Res<Time>,
+    hud_resources: Res<HudResources>,
+    mut commands: Commands,
+    mut capture_saved_events: EventReader<CaptureSaved>,
+) {
+    for event in capture_saved_events.read() {
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section(event.text.clone(), hud_resources.text_style.clone())
+                    .with_alignment(TextAlignment::Center),
+                transform: Transform::from_translation(CAPTURE_TOAST_TRANSLATION),
+                ..default()
+            },
+            HudTimedElement {
+                spawn_time: time.elapsed_seconds(),
+                lifespawn: CAPTURE_TOAST_DISPAWN_TIME_SECONDS,
+            },
+        ));
+    }
+}
+
+fn show_time_attack_split(
+    time: Res<Time>,
+    hud_resources: Res<HudResources>,
+    mut commands: Commands,
+    mut split_events: EventReader<TimeAttackSplit>,
+) {
+    for split in split_events.read() {
+        let mut split_text = format!("LEVEL {} {:.2}", split.level + 1, split.time);
+        if let Some(best) = split.best {
+            split_text += &format!("\nBEST {:.2} ({:+.2})", best, split.time - best);
+        }
+        match split.medal {
+            Some(Medal::Gold) => split_text += "\nGOLD",
+            Some(Medal::Silver) => split_text += "\nSILVER",
+            Some(Medal::Bronze) => split_text += "\nBRONZE",
+            None => {}
+        }
+
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section(split_text, hud_resources.timer_text_style.clone())
+                    .with_alignment(TextAlignment::Center),
+                transform: Transform::from_translation(TIME_ATTACK_SPLIT_TRANSLATION),
+                ..default()
+            },
+            HudTimedElement {
+                spawn_time: time.elapsed_seconds(),
+                lifespawn: TIME_ATTACK_SPLIT_DISPAWN_TIME_SECONDS,
+            },
+        ));
+    }
+}
+
 fn progress_timed_elements(
     time: Res<Time>,
     points: Query<(Entity, &HudTimedElement)>,
@@ -323,3 +1689,83 @@ fn despawn_all_timed_elements(
         e.despawn_recursive();
     }
 }
+
+fn toggle_diagnostics(keys: Res<Input<KeyCode>>, mut hud_diagnostics: ResMut<HudDiagnostics>) {
+    if keys.just_pressed(DIAGNOSTICS_KEY) {
+        hud_diagnostics.enabled = !hud_diagnostics.enabled;
+    }
+}
+
+fn toggle_physics_debug(
+    keys: Res<Input<KeyCode>>,
+    mut debug_render_context: ResMut<DebugRenderContext>,
+) {
+    if keys.just_pressed(PHYSICS_DEBUG_KEY) {
+        debug_render_context.enabled = !debug_render_context.enabled;
+    }
+}
+
+// Bevy does not report draw calls, so the number
+// of visible meshes is shown instead
+#[allow(clippy::too_many_arguments)]
+fn update_diagnostics(
+    hud_diagnostics: Res<HudDiagnostics>,
+    diagnostics: Res<DiagnosticsStore>,
+    rigid_bodies: Query<(), With<RigidBody>>,
+    meshes: Query<&ViewVisibility, With<Handle<Mesh>>>,
+    mut overlay: Query<&mut Visibility, With<HudDiagnosticsOverlay>>,
+    mut text: Query<&mut Text, With<HudDiagnosticsText>>,
+    mut bars: Query<(&HudDiagnosticsBar, &mut Sprite)>,
+    mut frame_times: Local<VecDeque<f32>>,
+) {
+    let Ok(mut overlay_visibility) = overlay.get_single_mut() else {
+        return;
+    };
+    if !hud_diagnostics.enabled {
+        overlay_visibility.set_if_neq(Visibility::Hidden);
+        frame_times.clear();
+        return;
+    }
+    overlay_visibility.set_if_neq(Visibility::Visible);
+
+    let value = |path| {
+        diagnostics
+            .get(path)
+            .and_then(|diagnostic| diagnostic.smoothed())
+            .unwrap_or_default()
+    };
+    let fps = value(FrameTimeDiagnosticsPlugin::FPS);
+    let frame_time = value(FrameTimeDiagnosticsPlugin::FRAME_TIME);
+    let entities = value(EntityCountDiagnosticsPlugin::ENTITY_COUNT);
+    let visible_meshes = meshes.iter().filter(|v| v.get()).count();
+
+    if let Ok(mut text) = text.get_single_mut() {
+        let bodies = rigid_bodies.iter().count();
+        text.sections[0].value = format!(
+            "FPS {fps:.0}\nFrame {frame_time:.2} ms\nEntities {entities:.0}\n\
+             Bodies {bodies}\nVisible meshes {visible_meshes}"
+        );
+    }
+
+    if let Some(last_frame_time) = diagnostics
+        .get(FrameTimeDiagnosticsPlugin::FRAME_TIME)
+        .and_then(|diagnostic| diagnostic.value())
+    {
+        if frame_times.len() == DIAGNOSTICS_GRAPH_BARS {
+            frame_times.pop_front();
+        }
+        frame_times.push_back(last_frame_time as f32);
+    }
+    for (bar, mut sprite) in bars.iter_mut() {
+        let frame_time = frame_times.get(bar.index).copied().unwrap_or_default();
+        sprite.custom_size = Some(Vec2::new(
+            DIAGNOSTICS_GRAPH_BAR_WIDTH,
+            frame_time * DIAGNOSTICS_GRAPH_SCALE,
+        ));
+        sprite.color = if DIAGNOSTICS_GRAPH_SLOW_FRAME_MS < frame_time {
+            DIAGNOSTICS_GRAPH_SLOW_COLOR
+        } else {
+            DIAGNOSTICS_GRAPH_COLOR
+        };
+    }
+}
@@ -0,0 +1,409 @@
+use std::collections::VecDeque;
+
+use bevy::{pbr::NotShadowCaster, prelude::*};
+use bevy_kira_audio::{AudioChannel, AudioInstance, AudioSource};
+use bevy_rapier3d::prelude::*;
+use rand::Rng;
+
+use crate::{
+    display::Quality,
+    enemies::EnemyAssets,
+    level::{LevelAssets, LevelObject},
+    player::{PlayerCamera, PlayerId},
+    rng::{GameRng, RngStream},
+    sound::{SoundLimit, SoundPool},
+    weapons::Projectile,
+    GameSettings, GlobalState, SfxChannel,
+};
+
+const IMPACT_PARTICLE_SIZE: f32 = 0.08;
+const IMPACT_SPARKS: u32 = 8;
+const IMPACT_SPARK_SPEED: f32 = 12.0;
+const IMPACT_SPARK_LIFESPAN: f32 = 0.25;
+const IMPACT_DUST_PUFFS: u32 = 5;
+const IMPACT_DUST_SPEED: f32 = 2.0;
+const IMPACT_DUST_LIFESPAN: f32 = 0.6;
+const IMPACT_DUST_SCALE: f32 = 3.0;
+const IMPACT_FROST_PUFFS: u32 = 6;
+const IMPACT_FROST_SPEED: f32 = 5.0;
+const IMPACT_FROST_LIFESPAN: f32 = 0.4;
+const IMPACT_FROST_SCALE: f32 = 2.0;
+// Particles fly out in a cone around the
+// surface normal, not straight back
+const IMPACT_SPREAD: f32 = 0.6;
+const IMPACT_PARTICLE_DRAG: f32 = 6.0;
+
+const IMPACT_DECAL_SIZE: f32 = 0.25;
+// Offset from the surface to avoid z-fighting
+const IMPACT_DECAL_OFFSET: f32 = 0.01;
+const IMPACT_MAX_DECALS: usize = 64;
+// Distance in front of and behind the projectile
+// probed for the exact point of the hit
+const IMPACT_PROBE_DISTANCE: f32 = 1.0;
+
+const IMPACT_SOUND_MAX_DISTANCE: f32 = 40.0;
+const IMPACT_SOUND_VOLUME: f32 = 0.4;
+const IMPACT_SOUND_LIMIT: SoundLimit = SoundLimit {
+    max_voices: 4,
+    cooldown: 0.05,
+    pitch_variation: 0.3,
+};
+
+/// Sparks, dust and frost on projectile hits
+/// depending on the surface being hit
+pub struct ImpactPlugin;
+
+impl Plugin for ImpactPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ImpactEvent>();
+        app.init_resource::<ImpactDecals>();
+
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::AssetLoading,
+                to: GlobalState::MainMenu,
+            },
+            init_resources,
+        );
+        app.add_systems(OnEnter(GlobalState::MainMenu), reset_decals);
+
+        app.add_systems(
+            Update,
+            (
+                projectile_impact,
+                (impact_effects, impact_sounds),
+                impact_particle_update,
+            )
+                .chain()
+                .run_if(in_state(GlobalState::InGame)),
+        );
+    }
+}
+
+/// What the surface hit by a projectile is made of
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+pub enum SurfaceMaterial {
+    Metal,
+    Concrete,
+    Fridge,
+}
+
+#[derive(Debug, Clone, Copy, Event)]
+struct ImpactEvent {
+    translation: Vec3,
+    normal: Vec3,
+    surface: SurfaceMaterial,
+    // Decals only stay on the static level, moving
+    // targets would leave them hanging in the air
+    decal: bool,
+}
+
+#[derive(Resource)]
+struct ImpactResources {
+    particle_mesh: Handle<Mesh>,
+    spark_material: Handle<StandardMaterial>,
+    dust_material: Handle<StandardMaterial>,
+    frost_material: Handle<StandardMaterial>,
+    decal_mesh: Handle<Mesh>,
+    decal_material: Handle<StandardMaterial>,
+}
+
+// Oldest decals are removed when there are too many
+#[derive(Default, Resource)]
+struct ImpactDecals {
+    decals: VecDeque<Entity>,
+}
+
+#[derive(Component)]
+struct ImpactParticle {
+    velocity: Vec3,
+    spawn_time: f32,
+    lifespan: f32,
+    // Puffs grow and fade, sparks only shrink
+    puff_scale: Option<f32>,
+}
+
+impl SurfaceMaterial {
+    // (count, speed, lifespan, puff scale)
+    fn particles(self) -> (u32, f32, f32, Option<f32>) {
+        match self {
+            SurfaceMaterial::Metal => (
+                IMPACT_SPARKS,
+                IMPACT_SPARK_SPEED,
+                IMPACT_SPARK_LIFESPAN,
+                None,
+            ),
+            SurfaceMaterial::Concrete => (
+                IMPACT_DUST_PUFFS,
+                IMPACT_DUST_SPEED,
+                IMPACT_DUST_LIFESPAN,
+                Some(IMPACT_DUST_SCALE),
+            ),
+            SurfaceMaterial::Fridge => (
+                IMPACT_FROST_PUFFS,
+                IMPACT_FROST_SPEED,
+                IMPACT_FROST_LIFESPAN,
+                Some(IMPACT_FROST_SCALE),
+            ),
+        }
+    }
+}
+
+fn init_resources(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let particle_mesh = meshes.add(
+        shape::UVSphere {
+            radius: IMPACT_PARTICLE_SIZE,
+            ..default()
+        }
+        .into(),
+    );
+    let spark_material = materials.add(StandardMaterial {
+        base_color: Color::ORANGE,
+        emissive: Color::ORANGE,
+        unlit: true,
+        ..default()
+    });
+    let dust_material = materials.add(StandardMaterial {
+        base_color: Color::rgba(0.5, 0.48, 0.45, 0.5),
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        ..default()
+    });
+    let frost_material = materials.add(StandardMaterial {
+        base_color: Color::rgba(0.8, 0.95, 1.0, 0.6),
+        emissive: Color::rgb(0.2, 0.3, 0.4),
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        ..default()
+    });
+
+    let decal_mesh = meshes.add(shape::Quad::new(Vec2::splat(IMPACT_DECAL_SIZE)).into());
+    let decal_material = materials.add(StandardMaterial {
+        base_color: Color::rgba(0.05, 0.05, 0.05, 0.8),
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        ..default()
+    });
+
+    commands.insert_resource(ImpactResources {
+        particle_mesh,
+        spark_material,
+        dust_material,
+        frost_material,
+        decal_mesh,
+        decal_material,
+    });
+}
+
+fn reset_decals(mut impact_decals: ResMut<ImpactDecals>) {
+    impact_decals.decals.clear();
+}
+
+fn projectile_impact(
+    rapier_context: Res<RapierContext>,
+    projectiles: Query<(&Transform, &Projectile)>,
+    surfaces: Query<(&SurfaceMaterial, Option<&RigidBody>)>,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut impact_events: EventWriter<ImpactEvent>,
+) {
+    for collision_event in collision_events.read() {
+        let CollisionEvent::Started(collider_1, collider_2, _) = collision_event else {
+            continue;
+        };
+
+        let (projectile_entity, surface_entity) =
+            if projectiles.contains(*collider_1) && surfaces.contains(*collider_2) {
+                (*collider_1, *collider_2)
+            } else if projectiles.contains(*collider_2) && surfaces.contains(*collider_1) {
+                (*collider_2, *collider_1)
+            } else {
+                continue;
+            };
+        let Ok((projectile_transform, projectile)) = projectiles.get(projectile_entity) else {
+            continue;
+        };
+        let Ok((surface, rigid_body)) = surfaces.get(surface_entity) else {
+            continue;
+        };
+
+        // projectile is already inside of the surface,
+        // so the exact point is found by a ray along its path
+        let direction = projectile.direction.normalize_or_zero();
+        let ray_origin = projectile_transform.translation - direction * IMPACT_PROBE_DISTANCE;
+        let predicate = |entity| entity == surface_entity;
+        let filter = QueryFilter::default().predicate(&predicate);
+        let (translation, normal) = rapier_context
+            .cast_ray_and_get_normal(
+                ray_origin,
+                direction,
+                IMPACT_PROBE_DISTANCE * 2.0,
+                true,
+                filter,
+            )
+            .map_or(
+                (projectile_transform.translation, -direction),
+                |(_, intersection)| (intersection.point, intersection.normal),
+            );
+
+        impact_events.send(ImpactEvent {
+            translation,
+            normal,
+            surface: *surface,
+            decal: rigid_body == Some(&RigidBody::Fixed),
+        });
+    }
+}
+
+fn impact_effects(
+    time: Res<Time>,
+    quality: Res<Quality>,
+    impact_resources: Res<ImpactResources>,
+    mut game_rng: ResMut<GameRng>,
+    mut impact_decals: ResMut<ImpactDecals>,
+    mut commands: Commands,
+    mut impact_events: EventReader<ImpactEvent>,
+) {
+    let rng = game_rng.stream(RngStream::Effects);
+    for impact in impact_events.read() {
+        let (count, speed, lifespan, puff_scale) = impact.surface.particles();
+        let material = match impact.surface {
+            SurfaceMaterial::Metal => impact_resources.spark_material.clone(),
+            SurfaceMaterial::Concrete => impact_resources.dust_material.clone(),
+            SurfaceMaterial::Fridge => impact_resources.frost_material.clone(),
+        };
+
+        for _ in 0..count / quality.preset.particle_stride() {
+            let spread = Vec3::new(
+                rng.gen_range(-IMPACT_SPREAD..IMPACT_SPREAD),
+                rng.gen_range(-IMPACT_SPREAD..IMPACT_SPREAD),
+                rng.gen_range(-IMPACT_SPREAD..IMPACT_SPREAD),
+            );
+            let velocity =
+                (impact.normal + spread).normalize_or_zero() * speed * rng.gen_range(0.5..1.0);
+            commands.spawn((
+                PbrBundle {
+                    mesh: impact_resources.particle_mesh.clone(),
+                    material: material.clone(),
+                    transform: Transform::from_translation(impact.translation),
+                    ..default()
+                },
+                NotShadowCaster,
+                ImpactParticle {
+                    velocity,
+                    spawn_time: time.elapsed_seconds(),
+                    lifespan,
+                    puff_scale,
+                },
+                LevelObject,
+            ));
+        }
+
+        if !impact.decal {
+            continue;
+        }
+
+        if IMPACT_MAX_DECALS <= impact_decals.decals.len() {
+            if let Some(e) = impact_decals
+                .decals
+                .pop_front()
+                .and_then(|decal| commands.get_entity(decal))
+            {
+                e.despawn_recursive();
+            }
+        }
+        // quad faces +Z
+        let rotation = Quat::from_rotation_arc(Vec3::Z, impact.normal)
+            * Quat::from_rotation_z(rng.gen_range(0.0..std::f32::consts::TAU));
+        let decal = commands
+            .spawn((
+                PbrBundle {
+                    mesh: impact_resources.decal_mesh.clone(),
+                    material: impact_resources.decal_material.clone(),
+                    transform: Transform::from_translation(
+                        impact.translation + impact.normal * IMPACT_DECAL_OFFSET,
+                    )
+                    .with_rotation(rotation),
+                    ..default()
+                },
+                NotShadowCaster,
+                LevelObject,
+            ))
+            .id();
+        impact_decals.decals.push_back(decal);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn impact_sounds(
+    time: Res<Time>,
+    sfx: Res<AudioChannel<SfxChannel>>,
+    level_assets: Res<LevelAssets>,
+    enemy_assets: Res<EnemyAssets>,
+    game_settings: Res<GameSettings>,
+    player_cameras: Query<(&PlayerId, &GlobalTransform), With<PlayerCamera>>,
+    mut sound_pool: ResMut<SoundPool>,
+    mut audio_instances: ResMut<Assets<AudioInstance>>,
+    mut game_rng: ResMut<GameRng>,
+    mut impact_events: EventReader<ImpactEvent>,
+) {
+    let Some((_, camera_global_transform)) = player_cameras.iter().find(|(id, _)| id.is_local())
+    else {
+        return;
+    };
+
+    for impact in impact_events.read() {
+        let v = impact.translation - camera_global_transform.translation();
+        let attenuation = (1.0 - v.length() / IMPACT_SOUND_MAX_DISTANCE).clamp(0.0, 1.0);
+        if attenuation == 0.0 {
+            continue;
+        }
+        let panning = 0.5 + 0.5 * camera_global_transform.right().dot(v.normalize_or_zero());
+
+        // no dedicated impact sounds, closest existing ones are used
+        let sound: &Handle<AudioSource> = match impact.surface {
+            SurfaceMaterial::Metal => &level_assets.door_locked,
+            SurfaceMaterial::Concrete => &level_assets.footstep_indoor,
+            SurfaceMaterial::Fridge => &enemy_assets.fridge_step,
+        };
+        sound_pool.play(
+            sfx.as_ref(),
+            audio_instances.as_mut(),
+            sound,
+            IMPACT_SOUND_LIMIT,
+            (game_settings.volume * IMPACT_SOUND_VOLUME * attenuation) as f64,
+            panning as f64,
+            time.elapsed_seconds(),
+            game_rng.stream(RngStream::Effects),
+        );
+    }
+}
+
+fn impact_particle_update(
+    time: Res<Time>,
+    mut particles: Query<(Entity, &mut ImpactParticle, &mut Transform)>,
+    mut commands: Commands,
+) {
+    for (particle_entity, mut particle, mut particle_transform) in particles.iter_mut() {
+        let progress = (time.elapsed_seconds() - particle.spawn_time) / particle.lifespan;
+        if 1.0 <= progress {
+            let Some(e) = commands.get_entity(particle_entity) else {
+                continue;
+            };
+            e.despawn_recursive();
+            continue;
+        }
+
+        particle_transform.translation += particle.velocity * time.delta_seconds();
+        let drag = (1.0 - IMPACT_PARTICLE_DRAG * time.delta_seconds()).max(0.0);
+        particle.velocity *= drag;
+
+        particle_transform.scale = Vec3::splat(match particle.puff_scale {
+            Some(scale) => (progress * std::f32::consts::PI).sin() * scale,
+            None => 1.0 - progress,
+        });
+    }
+}
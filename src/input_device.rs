@@ -0,0 +1,103 @@
+use bevy::{ecs::system::SystemParam, input::gamepad::GamepadConnection, prelude::*};
+
+use crate::GlobalState;
+
+// Same button as the E key on the keyboard
+const INPUT_GAMEPAD_INTERACT_BUTTON: GamepadButtonType = GamepadButtonType::West;
+
+/// Keeps track of the device the player uses, so
+/// prompts show the right buttons. Game pauses if
+/// the gamepad in use disconnects during a level.
+pub struct InputDevicePlugin;
+
+impl Plugin for InputDevicePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActiveInputDevice>();
+
+        app.add_systems(
+            Update,
+            (input_device_connections, input_device_switch).chain(),
+        );
+    }
+}
+
+/// Device that was used last
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Resource)]
+pub enum ActiveInputDevice {
+    #[default]
+    Keyboard,
+    Gamepad(Gamepad),
+}
+
+impl ActiveInputDevice {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ActiveInputDevice::Keyboard => "Keyboard",
+            ActiveInputDevice::Gamepad(_) => "Gamepad",
+        }
+    }
+
+    /// Label of the interaction button for prompts
+    pub fn interact_glyph(&self) -> &'static str {
+        match self {
+            ActiveInputDevice::Keyboard => "E",
+            ActiveInputDevice::Gamepad(_) => "(X)",
+        }
+    }
+}
+
+/// Interaction button on the keyboard or on any gamepad
+#[derive(SystemParam)]
+pub struct InteractInput<'w> {
+    keys: Res<'w, Input<KeyCode>>,
+    gamepads: Res<'w, Gamepads>,
+    gamepad_buttons: Res<'w, Input<GamepadButton>>,
+}
+
+impl InteractInput<'_> {
+    pub fn just_pressed(&self, key: KeyCode) -> bool {
+        self.keys.just_pressed(key)
+            || self.gamepads.iter().any(|gamepad| {
+                self.gamepad_buttons
+                    .just_pressed(GamepadButton::new(gamepad, INPUT_GAMEPAD_INTERACT_BUTTON))
+            })
+    }
+}
+
+fn input_device_connections(
+    global_state: Res<State<GlobalState>>,
+    mut active_input_device: ResMut<ActiveInputDevice>,
+    mut next_global_state: ResMut<NextState<GlobalState>>,
+    mut connection_events: EventReader<GamepadConnectionEvent>,
+) {
+    for connection_event in connection_events.read() {
+        match connection_event.connection {
+            GamepadConnection::Connected(_) => {
+                *active_input_device = ActiveInputDevice::Gamepad(connection_event.gamepad);
+            }
+            GamepadConnection::Disconnected => {
+                if *active_input_device != ActiveInputDevice::Gamepad(connection_event.gamepad) {
+                    continue;
+                }
+                *active_input_device = ActiveInputDevice::Keyboard;
+                if *global_state.get() == GlobalState::InGame {
+                    next_global_state.set(GlobalState::Paused);
+                }
+            }
+        }
+    }
+}
+
+fn input_device_switch(
+    keys: Res<Input<KeyCode>>,
+    mouse: Res<Input<MouseButton>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    mut active_input_device: ResMut<ActiveInputDevice>,
+) {
+    if let Some(button) = gamepad_buttons.get_just_pressed().next() {
+        active_input_device.set_if_neq(ActiveInputDevice::Gamepad(button.gamepad));
+    } else if keys.get_just_pressed().next().is_some() || mouse.get_just_pressed().next().is_some()
+    {
+        active_input_device.set_if_neq(ActiveInputDevice::Keyboard);
+    }
+}
@@ -0,0 +1,189 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    enemies::{EnemyPart, LastEnemyKilled},
+    player::{Player, PlayerCamera, PlayerId},
+    ui::tablet::TabletCamera,
+    GlobalState,
+};
+
+const KILL_CAM_SKIP_KEY: KeyCode = KeyCode::Return;
+// Kill cam runs in real time, the game is slowed down
+const KILL_CAM_SECONDS: f32 = 1.5;
+const KILL_CAM_TIME_SCALE: f32 = 0.25;
+const KILL_CAM_DISTANCE: f32 = 8.0;
+const KILL_CAM_HEIGHT: f32 = 3.0;
+const KILL_CAM_ORBIT_SPEED: f32 = 0.6;
+const KILL_CAM_FOLLOW_SPEED: f32 = 8.0;
+// Parts of the last enemy get an extra push up
+const KILL_CAM_LAUNCH_SPEED: f32 = 6.0;
+const KILL_CAM_TRACK_RADIUS: f32 = 6.0;
+
+/// Slow motion camera following the carcass of the
+/// last enemy of a level. Player cameras are turned
+/// off while it plays.
+pub struct KillCamPlugin;
+
+impl Plugin for KillCamPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<KillCam>();
+
+        app.add_systems(OnExit(GlobalState::InGame), kill_cam_stop);
+        app.add_systems(
+            Update,
+            (kill_cam_start, kill_cam_launch, kill_cam_update)
+                .chain()
+                .run_if(in_state(GlobalState::InGame)),
+        );
+    }
+}
+
+#[derive(Default, Resource)]
+pub struct KillCam {
+    camera: Option<Entity>,
+    // Point the camera looks at
+    target: Vec3,
+    time: f32,
+}
+
+pub fn kill_cam_playing(kill_cam: Res<KillCam>) -> bool {
+    kill_cam.camera.is_some()
+}
+
+fn kill_cam_start(
+    players: Query<(&PlayerId, &GlobalTransform), With<Player>>,
+    mut player_cameras: Query<&mut Camera, Or<(With<PlayerCamera>, With<TabletCamera>)>>,
+    mut time: ResMut<Time<Virtual>>,
+    mut kill_cam: ResMut<KillCam>,
+    mut commands: Commands,
+    mut last_enemy_killed_events: EventReader<LastEnemyKilled>,
+) {
+    let Some(event) = last_enemy_killed_events.read().last() else {
+        return;
+    };
+    if kill_cam.camera.is_some() {
+        return;
+    }
+    // kill cam is shown from the side of the local player
+    let Some((_, player_transform)) = players.iter().find(|(id, _)| id.is_local()) else {
+        return;
+    };
+
+    let target = event.translation;
+    let direction = (target - player_transform.translation())
+        .truncate()
+        .normalize_or_zero()
+        .extend(0.0);
+    let translation = target - direction * KILL_CAM_DISTANCE + Vec3::Z * KILL_CAM_HEIGHT;
+    let camera = commands
+        .spawn((
+            Camera3dBundle {
+                transform: Transform::from_translation(translation).looking_at(target, Vec3::Z),
+                ..default()
+            },
+            UiCameraConfig { show_ui: false },
+        ))
+        .id();
+
+    for mut camera in player_cameras.iter_mut() {
+        camera.is_active = false;
+    }
+    time.set_relative_speed(KILL_CAM_TIME_SCALE);
+    *kill_cam = KillCam {
+        camera: Some(camera),
+        target,
+        time: 0.0,
+    };
+}
+
+fn kill_cam_launch(
+    kill_cam: Res<KillCam>,
+    mut parts: Query<(&Transform, &mut Velocity), Added<EnemyPart>>,
+) {
+    if kill_cam.camera.is_none() {
+        return;
+    }
+    for (transform, mut velocity) in parts.iter_mut() {
+        if transform.translation.distance(kill_cam.target) < KILL_CAM_TRACK_RADIUS {
+            velocity.linvel += Vec3::Z * KILL_CAM_LAUNCH_SPEED;
+        }
+    }
+}
+
+fn kill_cam_update(
+    time: Res<Time<Real>>,
+    keys: Res<Input<KeyCode>>,
+    parts: Query<&Transform, (With<EnemyPart>, Without<Camera>)>,
+    mut cameras: Query<&mut Transform, With<Camera>>,
+    mut player_cameras: Query<&mut Camera, Or<(With<PlayerCamera>, With<TabletCamera>)>>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    mut kill_cam: ResMut<KillCam>,
+    mut commands: Commands,
+) {
+    let Some(camera) = kill_cam.camera else {
+        return;
+    };
+
+    kill_cam.time += time.delta_seconds();
+    if KILL_CAM_SECONDS <= kill_cam.time || keys.just_pressed(KILL_CAM_SKIP_KEY) {
+        stop(
+            &mut kill_cam,
+            &mut virtual_time,
+            &mut player_cameras,
+            &mut commands,
+        );
+        return;
+    }
+
+    // carcass is followed by the middle of its parts
+    let (sum, count) = parts
+        .iter()
+        .filter(|transform| transform.translation.distance(kill_cam.target) < KILL_CAM_TRACK_RADIUS)
+        .fold((Vec3::ZERO, 0), |(sum, count), transform| {
+            (sum + transform.translation, count + 1)
+        });
+    if 0 < count {
+        let center = sum / count as f32;
+        let t = (KILL_CAM_FOLLOW_SPEED * time.delta_seconds()).min(1.0);
+        kill_cam.target = kill_cam.target.lerp(center, t);
+    }
+
+    let Ok(mut transform) = cameras.get_mut(camera) else {
+        return;
+    };
+    let target = kill_cam.target;
+    transform.rotate_around(
+        target,
+        Quat::from_rotation_z(KILL_CAM_ORBIT_SPEED * time.delta_seconds()),
+    );
+    transform.look_at(target, Vec3::Z);
+}
+
+fn kill_cam_stop(
+    mut player_cameras: Query<&mut Camera, Or<(With<PlayerCamera>, With<TabletCamera>)>>,
+    mut time: ResMut<Time<Virtual>>,
+    mut kill_cam: ResMut<KillCam>,
+    mut commands: Commands,
+) {
+    if kill_cam.camera.is_some() {
+        stop(&mut kill_cam, &mut time, &mut player_cameras, &mut commands);
+    }
+}
+
+fn stop(
+    kill_cam: &mut KillCam,
+    time: &mut Time<Virtual>,
+    player_cameras: &mut Query<&mut Camera, Or<(With<PlayerCamera>, With<TabletCamera>)>>,
+    commands: &mut Commands,
+) {
+    if let Some(camera) = kill_cam.camera.take() {
+        if let Some(e) = commands.get_entity(camera) {
+            e.despawn_recursive();
+        }
+    }
+    for mut camera in player_cameras.iter_mut() {
+        camera.is_active = true;
+    }
+    time.set_relative_speed(1.0);
+}
@@ -0,0 +1,124 @@
+use bevy::{prelude::*, render::view::ColorGrading};
+
+use crate::player::PlayerCamera;
+
+use super::{LevelInfo, LevelType};
+
+// Early floors are bright, deep floors are dark
+// and washed out. Boss level is the darkest.
+const ATMOSPHERE_SURFACE_EXPOSURE: f32 = 0.2;
+const ATMOSPHERE_DEEP_EXPOSURE: f32 = -1.2;
+const ATMOSPHERE_SURFACE_SATURATION: f32 = 1.1;
+const ATMOSPHERE_DEEP_SATURATION: f32 = 0.6;
+const ATMOSPHERE_DEEP_PROGRESS: f32 = 100.0;
+// How fast the grading moves to the values of the
+// current floor, so changes happen between floors
+const ATMOSPHERE_BLEND_SPEED: f32 = 0.5;
+
+// Covered floors this deep and the boss
+// level are parts of the freezer
+const ATMOSPHERE_FREEZER_PROGRESS: i32 = 70;
+
+const FOG_COVERED_COLOR: Color = Color::rgb(0.05, 0.05, 0.06);
+const FOG_COVERED_DENSITY: f32 = 0.006;
+// Open floors get a thin haze of their own color
+// glowing around the sun
+const FOG_OPEN_COLOR_MODIFIER: f32 = 0.3;
+const FOG_OPEN_DENSITY: f32 = 0.004;
+const FOG_OPEN_SUN_ALPHA: f32 = 0.5;
+const FOG_FREEZER_COLOR: Color = Color::rgb(0.6, 0.72, 0.8);
+const FOG_FREEZER_DENSITY: f32 = 0.025;
+const FOG_FREEZER_SUN_COLOR: Color = Color::rgba(0.8, 0.9, 1.0, 0.4);
+// Higher exponent makes the glow around the sun smaller
+const FOG_SUN_EXPONENT: f32 = 30.0;
+
+pub struct AtmospherePlugin;
+
+impl Plugin for AtmospherePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            atmosphere_update.run_if(resource_exists::<LevelInfo>()),
+        );
+    }
+}
+
+// Fog of the floor theme
+struct Fog {
+    color: Color,
+    density: f32,
+    sun_color: Color,
+}
+
+fn atmosphere_target(level_info: &LevelInfo) -> (f32, f32) {
+    let depth = (level_info.game_progress as f32 / ATMOSPHERE_DEEP_PROGRESS).clamp(0.0, 1.0);
+    let exposure = ATMOSPHERE_SURFACE_EXPOSURE
+        + (ATMOSPHERE_DEEP_EXPOSURE - ATMOSPHERE_SURFACE_EXPOSURE) * depth;
+    let saturation = ATMOSPHERE_SURFACE_SATURATION
+        + (ATMOSPHERE_DEEP_SATURATION - ATMOSPHERE_SURFACE_SATURATION) * depth;
+    (exposure, saturation)
+}
+
+fn atmosphere_fog(level_info: &LevelInfo) -> Fog {
+    let boss_level = level_info.game_progress == 100;
+    match level_info.level_type {
+        _ if boss_level => Fog {
+            color: FOG_FREEZER_COLOR,
+            density: FOG_FREEZER_DENSITY,
+            sun_color: FOG_FREEZER_SUN_COLOR,
+        },
+        LevelType::Covered if ATMOSPHERE_FREEZER_PROGRESS <= level_info.game_progress => Fog {
+            color: FOG_FREEZER_COLOR,
+            density: FOG_FREEZER_DENSITY,
+            sun_color: Color::NONE,
+        },
+        LevelType::Covered => Fog {
+            color: FOG_COVERED_COLOR,
+            density: FOG_COVERED_DENSITY,
+            sun_color: Color::NONE,
+        },
+        LevelType::Open(level_color) => {
+            let color = Color::from(level_color);
+            Fog {
+                color: blend_color(Color::BLACK, color, FOG_OPEN_COLOR_MODIFIER),
+                density: FOG_OPEN_DENSITY,
+                sun_color: color.with_a(FOG_OPEN_SUN_ALPHA),
+            }
+        }
+    }
+}
+
+fn blend_color(from: Color, to: Color, t: f32) -> Color {
+    let from = Vec4::from(from.as_rgba_f32());
+    let to = Vec4::from(to.as_rgba_f32());
+    Color::from(from.lerp(to, t))
+}
+
+fn atmosphere_update(
+    time: Res<Time>,
+    level_info: Res<LevelInfo>,
+    mut camera: Query<(&mut ColorGrading, &mut FogSettings, Ref<PlayerCamera>)>,
+) {
+    let Ok((mut color_grading, mut fog_settings, player_camera)) = camera.get_single_mut() else {
+        return;
+    };
+
+    let (exposure, saturation) = atmosphere_target(level_info.as_ref());
+    // new camera starts with the values of the current floor
+    let t = if player_camera.is_added() {
+        1.0
+    } else {
+        (ATMOSPHERE_BLEND_SPEED * time.delta_seconds()).min(1.0)
+    };
+    color_grading.exposure += (exposure - color_grading.exposure) * t;
+    color_grading.post_saturation += (saturation - color_grading.post_saturation) * t;
+
+    let fog = atmosphere_fog(level_info.as_ref());
+    fog_settings.color = blend_color(fog_settings.color, fog.color, t);
+    fog_settings.directional_light_color =
+        blend_color(fog_settings.directional_light_color, fog.sun_color, t);
+    fog_settings.directional_light_exponent = FOG_SUN_EXPONENT;
+    if let FogFalloff::Exponential { density } = &mut fog_settings.falloff {
+        *density += (fog.density - *density) * t;
+    }
+}
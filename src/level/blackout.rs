@@ -0,0 +1,271 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::Rng;
+
+use crate::{
+    attachments::Attachments,
+    input_device::InteractInput,
+    player::{Player, PlayerId},
+    rng::{GameRng, RngStream},
+    GlobalState, AMBIENT_BRIGHTNESS,
+};
+
+use super::{
+    interaction::InteractionTarget,
+    modifiers::{LevelFloor, LevelModifier, LevelModifiers},
+    LevelColliderBundle, LevelInfo, LevelResources, LevelType, MountedLight, COLUMN_SIZE,
+};
+
+pub const LIGHT_SWITCH_WIDTH: f32 = 1.0;
+pub const LIGHT_SWITCH_DEPTH: f32 = 0.5;
+pub const LIGHT_SWITCH_HIGHT: f32 = 2.5;
+
+const LIGHT_SWITCH_INTERACTION_DISTANCE: f32 = COLUMN_SIZE * 3.0;
+const LIGHT_SWITCH_INTERACTION_KEY: KeyCode = KeyCode::E;
+
+// Level is split into 2x2 sections with
+// one switch for the lamps of each of them
+const BLACKOUT_SECTIONS: usize = 4;
+// Few lamps still work
+const BLACKOUT_LAMP_ON_CHANCE: f64 = 0.1;
+const BLACKOUT_AMBIENT_BRIGHTNESS: f32 = 0.01;
+
+/// Blackout level modifier. Lamps of the level
+/// are off until their section is switched on.
+pub struct BlackoutPlugin;
+
+impl Plugin for BlackoutPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Blackout>();
+        app.init_resource::<LightSwitchPrompt>();
+
+        app.add_systems(OnEnter(GlobalState::MainMenu), reset_blackout);
+        app.add_systems(
+            Update,
+            (
+                blackout_setup,
+                light_switch_interact,
+                blackout_player_hidden,
+            )
+                .chain()
+                .run_if(in_state(GlobalState::InGame)),
+        );
+        app.add_systems(
+            Update,
+            blackout_ambient.run_if(resource_changed::<Blackout>()),
+        );
+    }
+}
+
+// Blackout of the current level. Only covered
+// levels have lamps, so open levels are not affected.
+#[derive(Debug, Default, Resource)]
+pub struct Blackout {
+    pub active: bool,
+    // Player is in a dark section without the flashlight
+    pub player_hidden: bool,
+    center: Vec3,
+    lit: [bool; BLACKOUT_SECTIONS],
+}
+
+impl Blackout {
+    fn section(&self, translation: Vec3) -> usize {
+        (self.center.x <= translation.x) as usize + 2 * (self.center.y <= translation.y) as usize
+    }
+
+    pub fn dark_at(&self, translation: Vec3) -> bool {
+        self.active && !self.lit[self.section(translation)]
+    }
+}
+
+// What the player sees when aiming at a light switch
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Resource)]
+pub enum LightSwitchPrompt {
+    #[default]
+    None,
+    TurnOn,
+    TurnOff,
+}
+
+#[derive(Component)]
+struct LightSwitch {
+    section: usize,
+}
+
+// Lamp turned off by the blackout
+#[derive(Component)]
+struct SwitchedLight {
+    section: usize,
+    intensity: f32,
+}
+
+fn spawn_light_switch(
+    level_resources: &LevelResources,
+    commands: &mut Commands,
+    mut translation: Vec3,
+    section: usize,
+) {
+    // switch stands on the floor under the lamp
+    translation.z = LIGHT_SWITCH_HIGHT / 2.0;
+
+    commands.spawn((
+        LevelColliderBundle::new(
+            level_resources.light_switch_mesh.clone(),
+            level_resources.door_closed_light_material.clone(),
+            Transform::from_translation(translation),
+            Collider::cuboid(
+                LIGHT_SWITCH_WIDTH / 2.0,
+                LIGHT_SWITCH_DEPTH / 2.0,
+                LIGHT_SWITCH_HIGHT / 2.0,
+            ),
+        ),
+        LightSwitch { section },
+    ));
+}
+
+fn reset_blackout(mut blackout: ResMut<Blackout>) {
+    *blackout = Blackout::default();
+}
+
+// Lamps spawned for the next level are turned off
+// if the blackout is selected for it
+#[allow(clippy::too_many_arguments)]
+fn blackout_setup(
+    level_info: Res<LevelInfo>,
+    level_modifiers: Res<LevelModifiers>,
+    level_resources: Res<LevelResources>,
+    floors: Query<(), Added<LevelFloor>>,
+    mut lights: Query<(Entity, &Parent, &mut PointLight), Added<MountedLight>>,
+    mut lamps: Query<(&Transform, &mut Handle<StandardMaterial>)>,
+    mut blackout: ResMut<Blackout>,
+    mut game_rng: ResMut<GameRng>,
+    mut commands: Commands,
+) {
+    if floors.is_empty() {
+        return;
+    }
+
+    *blackout = Blackout {
+        active: level_modifiers.modifier == Some(LevelModifier::Blackout)
+            && level_info.level_type == LevelType::Covered,
+        center: level_info.translation,
+        ..default()
+    };
+    if !blackout.active {
+        return;
+    }
+
+    let rng = game_rng.stream(RngStream::LevelModifiers);
+    let mut switch_spots: [Vec<Vec3>; BLACKOUT_SECTIONS] = default();
+    for (light, parent, mut point_light) in lights.iter_mut() {
+        let Ok((lamp_transform, mut lamp_material)) = lamps.get_mut(parent.get()) else {
+            continue;
+        };
+        let section = blackout.section(lamp_transform.translation);
+        switch_spots[section].push(lamp_transform.translation);
+
+        if rng.gen_bool(BLACKOUT_LAMP_ON_CHANCE) {
+            continue;
+        }
+        commands.entity(light).insert(SwitchedLight {
+            section,
+            intensity: point_light.intensity,
+        });
+        point_light.intensity = 0.0;
+        *lamp_material = level_resources.light_off_material.clone();
+    }
+
+    for (section, spots) in switch_spots.iter().enumerate() {
+        if spots.is_empty() {
+            continue;
+        }
+        let spot = spots[rng.gen_range(0..spots.len())];
+        spawn_light_switch(&level_resources, &mut commands, spot, section);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn light_switch_interact(
+    interact_input: InteractInput,
+    level_resources: Res<LevelResources>,
+    players: Query<(&PlayerId, &InteractionTarget), With<Player>>,
+    mut switches: Query<(&LightSwitch, &mut Handle<StandardMaterial>)>,
+    mut lights: Query<(&Parent, &SwitchedLight, &mut PointLight)>,
+    mut lamps: Query<&mut Handle<StandardMaterial>, Without<LightSwitch>>,
+    mut blackout: ResMut<Blackout>,
+    mut light_switch_prompt: ResMut<LightSwitchPrompt>,
+) {
+    for (player_id, interaction_target) in players.iter() {
+        let switch = interaction_target
+            .get(LIGHT_SWITCH_INTERACTION_DISTANCE)
+            .and_then(|entity| switches.get_mut(entity).ok());
+
+        let Some((switch, mut switch_material)) = switch else {
+            if player_id.is_local() {
+                light_switch_prompt.set_if_neq(LightSwitchPrompt::None);
+            }
+            continue;
+        };
+
+        let lit = blackout.lit[switch.section];
+        // prompt is only shown on the local screen
+        if player_id.is_local() {
+            light_switch_prompt.set_if_neq(if lit {
+                LightSwitchPrompt::TurnOff
+            } else {
+                LightSwitchPrompt::TurnOn
+            });
+        }
+
+        if !interact_input.just_pressed(LIGHT_SWITCH_INTERACTION_KEY) {
+            continue;
+        }
+
+        let lit = !lit;
+        blackout.lit[switch.section] = lit;
+        *switch_material = if lit {
+            level_resources.door_open_light_material.clone()
+        } else {
+            level_resources.door_closed_light_material.clone()
+        };
+        for (parent, switched_light, mut point_light) in lights.iter_mut() {
+            if switched_light.section != switch.section {
+                continue;
+            }
+            let Ok(mut lamp_material) = lamps.get_mut(parent.get()) else {
+                continue;
+            };
+            if lit {
+                point_light.intensity = switched_light.intensity;
+                *lamp_material = level_resources.light_material.clone();
+            } else {
+                point_light.intensity = 0.0;
+                *lamp_material = level_resources.light_off_material.clone();
+            }
+        }
+    }
+}
+
+fn blackout_player_hidden(
+    attachments: Res<Attachments>,
+    players: Query<(&PlayerId, &Transform), With<Player>>,
+    mut blackout: ResMut<Blackout>,
+) {
+    let Some((_, player_transform)) = players.iter().find(|(id, _)| id.is_local()) else {
+        return;
+    };
+
+    // flashlight gives the player away
+    let hidden = blackout.dark_at(player_transform.translation) && !attachments.flashlight;
+    if blackout.player_hidden != hidden {
+        blackout.player_hidden = hidden;
+    }
+}
+
+fn blackout_ambient(blackout: Res<Blackout>, mut ambient_light: ResMut<AmbientLight>) {
+    ambient_light.brightness = if blackout.active {
+        BLACKOUT_AMBIENT_BRIGHTNESS
+    } else {
+        AMBIENT_BRIGHTNESS
+    };
+}
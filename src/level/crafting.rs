@@ -0,0 +1,222 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    animation::Animation,
+    attachments::{Attachments, WeaponAttachment},
+    input_device::InteractInput,
+    player::{Player, PlayerId, PlayerWeapon},
+    scrap::Scrap,
+    weapons::ammo::{AmmoType, AmmoTypes},
+    GlobalState,
+};
+
+use super::{interaction::InteractionTarget, LevelColliderBundle, LevelResources, COLUMN_SIZE};
+
+pub const CRAFTING_BENCH_WIDTH: f32 = COLUMN_SIZE * 0.8;
+pub const CRAFTING_BENCH_DEPTH: f32 = COLUMN_SIZE * 0.5;
+pub const CRAFTING_BENCH_HIGHT: f32 = 3.0;
+
+const CRAFTING_INTERACTION_DISTANCE: f32 = COLUMN_SIZE * 3.0;
+const CRAFTING_INTERACTION_KEY: KeyCode = KeyCode::E;
+const CRAFTING_NEXT_RECIPE_KEY: KeyCode = KeyCode::Tab;
+
+const CRAFTING_CONVERTER_ROUNDS: u32 = 20;
+
+const CRAFTING_ANIMATION_SPEED: f32 = 8.0;
+const CRAFTING_ANIMATION_OFFSET: Vec3 = Vec3::new(0.0, 0.0, 0.2);
+
+pub struct CraftingPlugin;
+
+impl Plugin for CraftingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CraftingPrompt>();
+
+        app.add_systems(
+            Update,
+            crafting_interact.run_if(in_state(GlobalState::InGame)),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CraftingRecipe {
+    pub attachment: WeaponAttachment,
+    pub scrap: u32,
+    pub coolant_cells: u32,
+}
+
+/// Recipes of the crafting bench, loaded
+/// from `assets/config/crafting.ron`
+#[derive(Debug, Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CraftingRecipes {
+    pub recipes: Vec<CraftingRecipe>,
+}
+
+impl Default for CraftingRecipes {
+    fn default() -> Self {
+        Self {
+            recipes: vec![
+                CraftingRecipe {
+                    attachment: WeaponAttachment::ExtendedMag,
+                    scrap: 12,
+                    coolant_cells: 1,
+                },
+                CraftingRecipe {
+                    attachment: WeaponAttachment::LaserSight,
+                    scrap: 8,
+                    coolant_cells: 1,
+                },
+                CraftingRecipe {
+                    attachment: WeaponAttachment::IncendiaryConverter,
+                    scrap: 6,
+                    coolant_cells: 2,
+                },
+            ],
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct CraftingBench {
+    // Recipe shown to the player
+    selected: usize,
+}
+
+// What the player sees when aiming at a crafting bench
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Resource)]
+pub enum CraftingPrompt {
+    #[default]
+    None,
+    Craft {
+        recipe: CraftingRecipe,
+        index: usize,
+        count: usize,
+    },
+    Installed {
+        attachment: WeaponAttachment,
+        index: usize,
+        count: usize,
+    },
+}
+
+pub fn spawn_crafting_bench(
+    level_resources: &LevelResources,
+    commands: &mut Commands,
+    mut transform: Transform,
+) {
+    // bench stands on the floor
+    transform.translation.z = CRAFTING_BENCH_HIGHT / 2.0;
+
+    commands.spawn((
+        LevelColliderBundle::new(
+            level_resources.crafting_bench_mesh.clone(),
+            level_resources.crafting_bench_material.clone(),
+            transform,
+            Collider::cuboid(
+                CRAFTING_BENCH_WIDTH / 2.0,
+                CRAFTING_BENCH_DEPTH / 2.0,
+                CRAFTING_BENCH_HIGHT / 2.0,
+            ),
+        ),
+        CraftingBench { selected: 0 },
+    ));
+}
+
+#[allow(clippy::too_many_arguments)]
+fn crafting_interact(
+    keys: Res<Input<KeyCode>>,
+    interact_input: InteractInput,
+    crafting_recipes: Res<CraftingRecipes>,
+    players: Query<(&PlayerId, &InteractionTarget), With<Player>>,
+    mut player_ammo_types: Query<(&PlayerId, &mut AmmoTypes), With<PlayerWeapon>>,
+    mut benches: Query<(Entity, &Transform, &mut CraftingBench, Has<Animation>)>,
+    mut attachments: ResMut<Attachments>,
+    mut scrap: ResMut<Scrap>,
+    mut crafting_prompt: ResMut<CraftingPrompt>,
+    mut commands: Commands,
+) {
+    for (player_id, interaction_target) in players.iter() {
+        let bench = interaction_target
+            .get(CRAFTING_INTERACTION_DISTANCE)
+            .and_then(|entity| benches.get_mut(entity).ok());
+
+        let count = crafting_recipes.recipes.len();
+        let Some((bench_entity, bench_transform, mut bench, crafting)) =
+            bench.filter(|_| count != 0)
+        else {
+            if player_id.is_local() {
+                crafting_prompt.set_if_neq(CraftingPrompt::None);
+            }
+            continue;
+        };
+
+        if keys.just_pressed(CRAFTING_NEXT_RECIPE_KEY) {
+            bench.selected += 1;
+        }
+        let index = bench.selected % count;
+        let recipe = crafting_recipes.recipes[index];
+
+        let installed = attachments.installed(recipe.attachment);
+        // prompt is only shown on the local screen
+        if player_id.is_local() {
+            crafting_prompt.set_if_neq(if installed {
+                CraftingPrompt::Installed {
+                    attachment: recipe.attachment,
+                    index,
+                    count,
+                }
+            } else {
+                CraftingPrompt::Craft {
+                    recipe,
+                    index,
+                    count,
+                }
+            });
+        }
+
+        if installed
+            || !interact_input.just_pressed(CRAFTING_INTERACTION_KEY)
+            || crafting
+            || scrap.amount < recipe.scrap
+            || scrap.coolant_cells < recipe.coolant_cells
+        {
+            continue;
+        }
+
+        match recipe.attachment {
+            WeaponAttachment::ExtendedMag => attachments.extended_mag = true,
+            WeaponAttachment::LaserSight => {
+                attachments.laser_sight = true;
+                attachments.laser = true;
+            }
+            WeaponAttachment::IncendiaryConverter => {
+                // rounds are loaded into the weapon in hands
+                let Some((_, mut ammo_types)) = player_ammo_types
+                    .iter_mut()
+                    .find(|(id, _)| *id == player_id)
+                else {
+                    continue;
+                };
+                ammo_types.add(AmmoType::Incendiary, CRAFTING_CONVERTER_ROUNDS);
+            }
+        }
+        scrap.amount -= recipe.scrap;
+        scrap.coolant_cells -= recipe.coolant_cells;
+
+        // bench shakes while crafting
+        let initial_transform = *bench_transform;
+        let mut target_transform = initial_transform;
+        target_transform.translation += CRAFTING_ANIMATION_OFFSET;
+        commands.entity(bench_entity).insert(Animation {
+            animate_forward: true,
+            animate_backward: true,
+            animation_speed: CRAFTING_ANIMATION_SPEED,
+            progress: 0.0,
+            initial_transform,
+            target_transform,
+        });
+    }
+}
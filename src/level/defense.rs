@@ -0,0 +1,353 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    damage::{ContactDamageEvent, DamageThreshold, Health, KillEvent},
+    enemies::{spawn_enemy, Elite, EnemyAssets, EnemyReinforcement, EnemyScripted, EnemyType},
+    player::{Player, PlayerId},
+    rng::{GameRng, RngStream},
+    weapons::WeaponAssets,
+    GlobalState,
+};
+
+use super::{
+    door::{Door, DoorLight, DoorLightMesh, DoorState, DoorType},
+    LevelResources, LevelStarted, COLUMN_SIZE,
+};
+
+const DEFENSE_SECONDS: f32 = 90.0;
+const DEFENSE_DOOR_HEALTH: i32 = 400;
+
+const DEFENSE_WAVE_SECONDS: f32 = 15.0;
+const DEFENSE_WAVE: [EnemyType; 3] = [EnemyType::Small, EnemyType::Small, EnemyType::Mid];
+// Waves appear in a row in front of the door
+const DEFENSE_WAVE_DISTANCE: f32 = COLUMN_SIZE * 12.0;
+const DEFENSE_SPAWN_SPACING: f32 = COLUMN_SIZE * 1.5;
+
+// Attackers close to the door damage it
+const DEFENSE_ATTACK_DISTANCE: f32 = COLUMN_SIZE * 5.0;
+const DEFENSE_ATTACK_SECONDS: f32 = 1.0;
+const DEFENSE_ATTACK_DAMAGE: i32 = 10;
+
+// Elites guarding another exit after the door is lost
+const DEFENSE_ROUTE_SQUAD: [EnemyType; 3] = [EnemyType::Mid, EnemyType::Big, EnemyType::Mid];
+const DEFENSE_ROUTE_DISTANCE: f32 = COLUMN_SIZE * 4.0;
+
+/// Malfunctioning exits which have to be held
+/// against waves of enemies until they are repaired
+pub struct DefensePlugin;
+
+impl Plugin for DefensePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DoorDefenseStarted>();
+        app.add_event::<DoorDefenseFinished>();
+
+        app.add_systems(
+            Update,
+            (
+                defense_start,
+                defense_waves,
+                defense_attack,
+                defense_won,
+                defense_lost,
+            )
+                .chain()
+                .run_if(in_state(GlobalState::InGame)),
+        );
+    }
+}
+
+#[derive(Event)]
+pub struct DoorDefenseStarted;
+
+#[derive(Event)]
+pub struct DoorDefenseFinished {
+    pub defended: bool,
+}
+
+// Defense starts together with the level
+#[derive(Component)]
+pub struct DefendedDoor {
+    started: bool,
+    waves: u32,
+    timer: Timer,
+    wave_timer: Timer,
+    attack_timer: Timer,
+}
+
+impl DefendedDoor {
+    pub fn started(&self) -> bool {
+        self.started
+    }
+
+    pub fn waves(&self) -> u32 {
+        self.waves
+    }
+
+    pub fn seconds_left(&self) -> f32 {
+        self.timer.remaining_secs()
+    }
+}
+
+#[derive(Component)]
+struct DefenseAttacker {
+    door: Entity,
+}
+
+pub fn defend_door(commands: &mut Commands, door_entity: Entity) {
+    commands.entity(door_entity).insert((
+        DefendedDoor {
+            started: false,
+            waves: 0,
+            timer: Timer::from_seconds(DEFENSE_SECONDS, TimerMode::Once),
+            wave_timer: Timer::from_seconds(DEFENSE_WAVE_SECONDS, TimerMode::Repeating),
+            attack_timer: Timer::from_seconds(DEFENSE_ATTACK_SECONDS, TimerMode::Repeating),
+        },
+        Health {
+            health: DEFENSE_DOOR_HEALTH,
+        },
+        // only attackers can damage the door
+        DamageThreshold {
+            min_damage: i32::MAX,
+        },
+    ));
+}
+
+// Direction from the door into its level
+fn door_inward(door_type: DoorType) -> Vec3 {
+    match door_type {
+        DoorType::Top => Vec3::NEG_Y,
+        DoorType::Bottom => Vec3::Y,
+        DoorType::Left => Vec3::X,
+        DoorType::Right => Vec3::NEG_X,
+    }
+}
+
+// Enemies are placed in a row along `across`
+// with the `center` in the middle
+fn spawn_squad(
+    enemy_assets: &EnemyAssets,
+    weapon_assets: &WeaponAssets,
+    commands: &mut Commands,
+    center: Vec3,
+    across: Vec3,
+    squad: &[EnemyType],
+    mut elite: impl FnMut() -> Option<Elite>,
+) -> Vec<Entity> {
+    let middle = (squad.len() as f32 - 1.0) / 2.0;
+    squad
+        .iter()
+        .enumerate()
+        .map(|(i, enemy_type)| {
+            let translation = center + across * (i as f32 - middle) * DEFENSE_SPAWN_SPACING;
+            let enemy = spawn_enemy(
+                enemy_assets,
+                weapon_assets,
+                *enemy_type,
+                elite(),
+                vec![],
+                commands,
+                Transform::from_translation(translation),
+            );
+            commands.entity(enemy).insert(EnemyReinforcement);
+            enemy
+        })
+        .collect()
+}
+
+// Attackers go back to chasing the player
+fn release_attackers(
+    door: Entity,
+    attackers: &Query<(Entity, &DefenseAttacker)>,
+    commands: &mut Commands,
+) {
+    for (attacker, _) in attackers.iter().filter(|(_, a)| a.door == door) {
+        let Some(mut e) = commands.get_entity(attacker) else {
+            continue;
+        };
+        e.remove::<(DefenseAttacker, EnemyScripted)>();
+    }
+}
+
+fn defense_start(
+    mut doors: Query<&mut DefendedDoor>,
+    mut level_started_events: EventReader<LevelStarted>,
+    mut defense_started_events: EventWriter<DoorDefenseStarted>,
+) {
+    for _ in level_started_events.read() {
+        for mut defended_door in doors.iter_mut() {
+            if defended_door.started {
+                continue;
+            }
+            defended_door.started = true;
+            defense_started_events.send(DoorDefenseStarted);
+        }
+    }
+}
+
+fn defense_waves(
+    time: Res<Time>,
+    enemy_assets: Res<EnemyAssets>,
+    weapon_assets: Res<WeaponAssets>,
+    mut doors: Query<(Entity, &GlobalTransform, &Door, &mut DefendedDoor)>,
+    mut commands: Commands,
+) {
+    for (door_entity, door_global_transform, door, mut defended_door) in doors.iter_mut() {
+        if !defended_door.started {
+            continue;
+        }
+        defended_door.timer.tick(time.delta());
+        defended_door.wave_timer.tick(time.delta());
+        defended_door.attack_timer.tick(time.delta());
+
+        // first wave comes right away
+        let wave = defended_door.waves == 0 || defended_door.wave_timer.just_finished();
+        if !wave || defended_door.timer.finished() {
+            continue;
+        }
+        defended_door.waves += 1;
+
+        let inward = door_inward(door.door_type);
+        let attackers = spawn_squad(
+            enemy_assets.as_ref(),
+            weapon_assets.as_ref(),
+            &mut commands,
+            door_global_transform.translation() + inward * DEFENSE_WAVE_DISTANCE,
+            inward.cross(Vec3::Z),
+            &DEFENSE_WAVE,
+            || None,
+        );
+        for attacker in attackers {
+            commands.entity(attacker).insert((
+                DefenseAttacker { door: door_entity },
+                EnemyScripted::default(),
+            ));
+        }
+    }
+}
+
+fn defense_attack(
+    players: Query<(&PlayerId, &Transform), With<Player>>,
+    doors: Query<(&GlobalTransform, &DefendedDoor)>,
+    mut attackers: Query<(&Transform, &DefenseAttacker, &mut EnemyScripted), Without<Player>>,
+    mut contact_damage_events: EventWriter<ContactDamageEvent>,
+) {
+    let Some((_, player_transform)) = players.iter().find(|(id, _)| id.is_local()) else {
+        return;
+    };
+
+    for (attacker_transform, attacker, mut scripted) in attackers.iter_mut() {
+        let Ok((door_global_transform, defended_door)) = doors.get(attacker.door) else {
+            continue;
+        };
+        let door_translation = door_global_transform.translation();
+
+        // enemies move relative to the player, so
+        // the door is given as an offset from the player
+        scripted.target_offset = door_translation.xy() - player_transform.translation.xy();
+
+        if !defended_door.attack_timer.just_finished()
+            || DEFENSE_ATTACK_DISTANCE
+                < attacker_transform
+                    .translation
+                    .xy()
+                    .distance(door_translation.xy())
+        {
+            continue;
+        }
+        contact_damage_events.send(ContactDamageEvent {
+            entity: attacker.door,
+            damage: DEFENSE_ATTACK_DAMAGE,
+            direction: (door_translation - attacker_transform.translation).normalize_or_zero(),
+        });
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn defense_won(
+    level_resources: Res<LevelResources>,
+    attackers: Query<(Entity, &DefenseAttacker)>,
+    mut doors: Query<(Entity, &mut Door, &DefendedDoor, &Children)>,
+    mut door_light_meshes: Query<(&mut Handle<StandardMaterial>, &Children), With<DoorLightMesh>>,
+    mut door_lights: Query<&mut PointLight, With<DoorLight>>,
+    mut commands: Commands,
+    mut defense_finished_events: EventWriter<DoorDefenseFinished>,
+) {
+    for (door_entity, mut door, defended_door, children) in doors.iter_mut() {
+        if !defended_door.timer.finished() {
+            continue;
+        }
+
+        // repaired door can be used right away
+        if door.door_state == DoorState::Locked {
+            door.door_state = DoorState::Unlocked;
+        }
+        for child in children.iter() {
+            let Ok((mut light_material, light_children)) = door_light_meshes.get_mut(*child) else {
+                continue;
+            };
+            *light_material = level_resources.door_open_light_material.clone();
+            for light_child in light_children.iter() {
+                if let Ok(mut light) = door_lights.get_mut(*light_child) {
+                    light.color = Color::GREEN;
+                }
+            }
+        }
+
+        release_attackers(door_entity, &attackers, &mut commands);
+        commands.entity(door_entity).remove::<DefendedDoor>();
+        defense_finished_events.send(DoorDefenseFinished { defended: true });
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn defense_lost(
+    enemy_assets: Res<EnemyAssets>,
+    weapon_assets: Res<WeaponAssets>,
+    attackers: Query<(Entity, &DefenseAttacker)>,
+    other_doors: Query<(&GlobalTransform, &Door), Without<DefendedDoor>>,
+    mut doors: Query<&mut Door, With<DefendedDoor>>,
+    mut game_rng: ResMut<GameRng>,
+    mut commands: Commands,
+    mut kill_events: EventReader<KillEvent>,
+    mut defense_finished_events: EventWriter<DoorDefenseFinished>,
+) {
+    for kill_event in kill_events.read() {
+        let Ok(mut door) = doors.get_mut(kill_event.entity) else {
+            continue;
+        };
+
+        door.door_state = DoorState::Sealed;
+        release_attackers(kill_event.entity, &attackers, &mut commands);
+        commands.entity(kill_event.entity).remove::<DefendedDoor>();
+        defense_finished_events.send(DoorDefenseFinished { defended: false });
+
+        // another exit becomes the way out,
+        // but it is guarded by elites
+        let exits = other_doors
+            .iter()
+            .filter(|(_, door)| matches!(door.door_state, DoorState::Locked | DoorState::Unlocked))
+            .collect::<Vec<_>>();
+        if exits.is_empty() {
+            continue;
+        }
+        let (exit_global_transform, exit) =
+            exits[game_rng.stream(RngStream::Level).gen_range(0..exits.len())];
+
+        let inward = door_inward(exit.door_type);
+        let rng = game_rng.stream(RngStream::Elites);
+        spawn_squad(
+            enemy_assets.as_ref(),
+            weapon_assets.as_ref(),
+            &mut commands,
+            exit_global_transform.translation() + inward * DEFENSE_ROUTE_DISTANCE,
+            inward.cross(Vec3::Z),
+            &DEFENSE_ROUTE_SQUAD,
+            || {
+                Some(Elite {
+                    modifier: Some(rng.gen()),
+                })
+            },
+        );
+    }
+}
@@ -1,28 +1,71 @@
 use bevy::prelude::*;
 use bevy_rapier3d::{prelude::*, rapier::geometry::CollisionEventFlags};
 
+use bevy_kira_audio::{AudioChannel, AudioControl};
+use rand::Rng;
+
 use crate::{
-    animation::Animation, player::Player, GlobalState, COLLISION_GROUP_ENEMY,
-    COLLISION_GROUP_LEVEL, COLLISION_GROUP_PLAYER, COLLISION_GROUP_PROJECTILES,
+    animation::Animation,
+    collision::CollisionLayer,
+    damage::{DamageThreshold, Health, KillEvent},
+    display::Quality,
+    enemies::Enemy,
+    impacts::SurfaceMaterial,
+    input_device::InteractInput,
+    player::{Player, PlayerCamera, PlayerId},
+    rng::{GameRng, RngStream},
+    GameSettings, GlobalState, SfxChannel,
 };
 
 use super::{
-    LevelCollider, LevelFinished, LevelObject, LevelResources, LevelStarted, LevelSwitch,
-    COLUMN_HIGHT, COLUMN_SIZE, DOOR_THICKNESS,
+    defense::DefendedDoor, grade::LevelExitUnlocked, interaction::InteractionTarget, LevelAssets,
+    LevelCollider, LevelInfo, LevelObject, LevelResources, LevelStarted, LevelSwitch, COLUMN_HIGHT,
+    COLUMN_SIZE, DOOR_THICKNESS,
 };
 
 const DOOR_ANIMATION_DISTANCE: f32 = COLUMN_SIZE - 0.2;
 const DOOR_ANIMATION_SPEED: f32 = 2.0;
+// Door behind the player entering a level slams shut
+const DOOR_SLAM_ANIMATION_SPEED: f32 = 6.0;
+const DOOR_SLAM_PLAYBACK_RATE: f64 = 0.6;
 
 const DOOR_LIGHT_OFFSET: Vec3 = Vec3::new(0.0, 0.0, 3.0);
 
+const DOOR_INTERACTION_DISTANCE: f32 = COLUMN_SIZE * 3.0;
+const DOOR_INTERACTION_KEY: KeyCode = KeyCode::E;
+
+// Only heavy hits like thrown weapons can break a door
+const DOOR_HEALTH: i32 = 100;
+const DOOR_MIN_DAMAGE: i32 = 50;
+pub const DOOR_DEBRIS_SIZE: f32 = 1.0;
+const DOOR_DEBRIS_PIECES: u32 = 10;
+const DOOR_DEBRIS_VELOCITY: f32 = 20.0;
+
+// Door sounds fade out linearly up to this distance
+const DOOR_SOUND_MAX_DISTANCE: f32 = COLUMN_SIZE * 20.0;
+const DOOR_SOUND_PITCH_VARIATION: f64 = 0.1;
+
 pub struct DoorPlugin;
 
 impl Plugin for DoorPlugin {
     fn build(&self, app: &mut App) {
+        app.init_resource::<DoorPrompt>();
+
+        app.add_event::<DoorAnimationStarted>();
+        app.add_event::<DoorAnimationFinished>();
+        app.add_event::<DoorLockedInteraction>();
+
         app.add_systems(
             Update,
-            (level_finished, door_use).run_if(in_state(GlobalState::InGame)),
+            (
+                level_finished,
+                door_interact,
+                door_use,
+                door_broken,
+                door_animation_finished,
+                door_sounds,
+            )
+                .run_if(in_state(GlobalState::InGame)),
         );
     }
 }
@@ -41,6 +84,46 @@ pub enum DoorState {
     Unlocked,
     Used,
     TemporaryOpen,
+    Broken,
+    // Exit lost during a door defense, never unlocks
+    Sealed,
+}
+
+#[derive(Event)]
+pub struct DoorAnimationStarted {
+    pub door: Entity,
+    pub opening: bool,
+}
+
+#[derive(Event)]
+pub struct DoorAnimationFinished {
+    pub door: Entity,
+    pub opening: bool,
+    pub slam: bool,
+}
+
+// Player tried to open a locked door
+#[derive(Event)]
+pub struct DoorLockedInteraction {
+    pub door: Entity,
+}
+
+#[derive(Component)]
+struct DoorAnimation {
+    opening: bool,
+    slam: bool,
+}
+
+// What the player sees when aiming at a door
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Resource)]
+pub enum DoorPrompt {
+    #[default]
+    None,
+    Open,
+    Locked {
+        enemies: usize,
+    },
+    Sealed,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
@@ -65,6 +148,7 @@ pub struct DoorBundle {
     pub active_events: ActiveEvents,
     pub door: Door,
     pub level_collider: LevelCollider,
+    pub surface_material: SurfaceMaterial,
 
     pub level_object: LevelObject,
 }
@@ -74,10 +158,7 @@ impl Default for DoorBundle {
         Self {
             pbr_bundle: PbrBundle::default(),
             collider: Collider::default(),
-            collision_groups: CollisionGroups::new(
-                COLLISION_GROUP_LEVEL,
-                COLLISION_GROUP_ENEMY | COLLISION_GROUP_PLAYER | COLLISION_GROUP_PROJECTILES,
-            ),
+            collision_groups: CollisionLayer::Level.groups(),
             rigid_body: RigidBody::Fixed,
             active_events: ActiveEvents::COLLISION_EVENTS,
             door: Door {
@@ -86,6 +167,7 @@ impl Default for DoorBundle {
                 grid_pos: 0,
             },
             level_collider: LevelCollider,
+            surface_material: SurfaceMaterial::Metal,
 
             level_object: LevelObject,
         }
@@ -136,7 +218,7 @@ impl Default for DoorSensorBundle {
         Self {
             transform_bundle: TransformBundle::default(),
             collider: Collider::default(),
-            collision_groups: CollisionGroups::new(COLLISION_GROUP_LEVEL, COLLISION_GROUP_PLAYER),
+            collision_groups: CollisionLayer::LevelSensor.groups(),
             sensor: Sensor,
             active_events: ActiveEvents::COLLISION_EVENTS,
             door_sensor: DoorSensor {
@@ -153,7 +235,7 @@ pub fn spawn_door(
     commands: &mut Commands,
     transform: Transform,
     door: Door,
-) {
+) -> Entity {
     let transform = match door.door_type {
         DoorType::Top | DoorType::Bottom => transform,
         DoorType::Left | DoorType::Right => {
@@ -262,6 +344,18 @@ pub fn spawn_door(
             .id()
     };
 
+    // Only exit doors can be broken
+    if door.door_state == DoorState::Locked {
+        commands.entity(door_entity).insert((
+            Health {
+                health: DOOR_HEALTH,
+            },
+            DamageThreshold {
+                min_damage: DOOR_MIN_DAMAGE,
+            },
+        ));
+    }
+
     let sensor_collider =
         Collider::cuboid(COLUMN_SIZE / 2.0, COLUMN_SIZE / 2.0, COLUMN_HIGHT / 2.0);
     commands
@@ -274,19 +368,23 @@ pub fn spawn_door(
             ..default()
         })
         .add_child(door_entity);
+
+    door_entity
 }
 
 fn level_finished(
     level_resources: Res<LevelResources>,
-    mut level_finished_events: EventReader<LevelFinished>,
+    mut exit_unlocked_events: EventReader<LevelExitUnlocked>,
     mut doors: Query<&mut Door, With<Door>>,
     mut door_lights: Query<&mut PointLight, With<DoorLight>>,
     mut door_light_meshes: Query<&mut Handle<StandardMaterial>, With<DoorLightMesh>>,
 ) {
-    if !level_finished_events.is_empty() {
-        level_finished_events.clear();
+    if !exit_unlocked_events.is_empty() {
+        exit_unlocked_events.clear();
         for mut door in doors.iter_mut() {
-            door.door_state = DoorState::Unlocked;
+            if !matches!(door.door_state, DoorState::Broken | DoorState::Sealed) {
+                door.door_state = DoorState::Unlocked;
+            }
         }
         for mut light in door_lights.iter_mut() {
             light.color = Color::GREEN;
@@ -297,19 +395,133 @@ fn level_finished(
     }
 }
 
+fn open_door(
+    door_entity: Entity,
+    door_transform: &Transform,
+    door: &mut Door,
+    commands: &mut Commands,
+    level_switch_events: &mut EventWriter<LevelSwitch>,
+    animation_started_events: &mut EventWriter<DoorAnimationStarted>,
+) {
+    door.door_state = DoorState::Used;
+
+    level_switch_events.send(LevelSwitch { exit_door: *door });
+
+    animate_door(
+        door_entity,
+        door_transform,
+        true,
+        false,
+        commands,
+        animation_started_events,
+    );
+}
+
+fn animate_door(
+    door_entity: Entity,
+    door_transform: &Transform,
+    opening: bool,
+    slam: bool,
+    commands: &mut Commands,
+    animation_started_events: &mut EventWriter<DoorAnimationStarted>,
+) {
+    let initial_transform = *door_transform;
+    let mut target_transform = initial_transform;
+    if opening {
+        target_transform.translation += Vec3::X * DOOR_ANIMATION_DISTANCE;
+    } else {
+        target_transform.translation -= Vec3::X * DOOR_ANIMATION_DISTANCE;
+    }
+    let Some(mut e) = commands.get_entity(door_entity) else {
+        return;
+    };
+    e.insert((
+        Animation {
+            animate_forward: true,
+            animate_backward: false,
+            animation_speed: if slam {
+                DOOR_SLAM_ANIMATION_SPEED
+            } else {
+                DOOR_ANIMATION_SPEED
+            },
+            progress: 0.0,
+            initial_transform,
+            target_transform,
+        },
+        DoorAnimation { opening, slam },
+    ));
+
+    animation_started_events.send(DoorAnimationStarted {
+        door: door_entity,
+        opening,
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn door_interact(
+    interact_input: InteractInput,
+    players: Query<(&PlayerId, &InteractionTarget), With<Player>>,
+    enemies: Query<(), With<Enemy>>,
+    mut commands: Commands,
+    mut doors: Query<(Entity, &Transform, &mut Door)>,
+    mut door_prompt: ResMut<DoorPrompt>,
+    mut level_switch_events: EventWriter<LevelSwitch>,
+    mut animation_started_events: EventWriter<DoorAnimationStarted>,
+    mut locked_interaction_events: EventWriter<DoorLockedInteraction>,
+) {
+    let pressed = interact_input.just_pressed(DOOR_INTERACTION_KEY);
+    for (player_id, interaction_target) in players.iter() {
+        let door = interaction_target
+            .get(DOOR_INTERACTION_DISTANCE)
+            .and_then(|entity| doors.get_mut(entity).ok());
+
+        let prompt = match door {
+            Some((door_entity, door_transform, mut door)) => match door.door_state {
+                DoorState::Unlocked => {
+                    if pressed {
+                        open_door(
+                            door_entity,
+                            door_transform,
+                            door.as_mut(),
+                            &mut commands,
+                            &mut level_switch_events,
+                            &mut animation_started_events,
+                        );
+                        DoorPrompt::None
+                    } else {
+                        DoorPrompt::Open
+                    }
+                }
+                DoorState::Locked => {
+                    if pressed {
+                        locked_interaction_events.send(DoorLockedInteraction { door: door_entity });
+                    }
+                    DoorPrompt::Locked {
+                        enemies: enemies.iter().count(),
+                    }
+                }
+                DoorState::Sealed => DoorPrompt::Sealed,
+                DoorState::Used | DoorState::TemporaryOpen | DoorState::Broken => DoorPrompt::None,
+            },
+            None => DoorPrompt::None,
+        };
+
+        // prompt is only shown on the local screen
+        if player_id.is_local() {
+            door_prompt.set_if_neq(prompt);
+        }
+    }
+}
+
 fn door_use(
-    player: Query<(Entity, &Transform), With<Player>>,
+    players: Query<&Transform, With<Player>>,
     door_sensors: Query<(&DoorSensor, &Transform), Without<Player>>,
     mut commands: Commands,
     mut doors: Query<(Entity, &Transform, &mut Door), Without<Player>>,
     mut level_start_events: EventWriter<LevelStarted>,
-    mut level_switch_events: EventWriter<LevelSwitch>,
+    mut animation_started_events: EventWriter<DoorAnimationStarted>,
     mut collision_events: EventReader<CollisionEvent>,
 ) {
-    let Ok((player, player_transform)) = player.get_single() else {
-        return;
-    };
-
     for collision_event in collision_events.read() {
         let (collider_1, collider_2, flags) = match collision_event {
             CollisionEvent::Started(c1, c2, f) => (c1, c2, f),
@@ -319,20 +531,15 @@ fn door_use(
         if flags.contains(CollisionEventFlags::REMOVED)
             || !flags.contains(CollisionEventFlags::SENSOR)
         {
-            return;
+            continue;
         }
-        let (door_sensor, door_sensor_transform) = if collider_1 == &player {
-            if let Ok(p) = door_sensors.get(*collider_2) {
-                p
-            } else {
-                continue;
-            }
-        } else if collider_2 == &player {
-            if let Ok(p) = door_sensors.get(*collider_1) {
-                p
-            } else {
-                continue;
-            }
+        // any player walking through the door uses it
+        let (player_transform, (door_sensor, door_sensor_transform)) = if let (Ok(t), Ok(s)) =
+            (players.get(*collider_1), door_sensors.get(*collider_2))
+        {
+            (t, s)
+        } else if let (Ok(t), Ok(s)) = (players.get(*collider_2), door_sensors.get(*collider_1)) {
+            (t, s)
         } else {
             continue;
         };
@@ -365,38 +572,18 @@ fn door_use(
 
                             door.door_state = DoorState::Locked;
 
-                            let initial_transform = *door_transform;
-                            let mut target_transform = initial_transform;
-                            target_transform.translation -= Vec3::X * DOOR_ANIMATION_DISTANCE;
-                            commands.get_entity(door_entity).unwrap().insert(Animation {
-                                animate_forward: true,
-                                animate_backward: false,
-                                animation_speed: DOOR_ANIMATION_SPEED,
-                                progress: 0.0,
-                                initial_transform,
-                                target_transform,
-                            });
+                            animate_door(
+                                door_entity,
+                                door_transform,
+                                false,
+                                true,
+                                &mut commands,
+                                &mut animation_started_events,
+                            );
                         }
                     }
                 };
             }
-            DoorState::Unlocked => {
-                door.door_state = DoorState::Used;
-
-                level_switch_events.send(LevelSwitch { exit_door: *door });
-
-                let initial_transform = *door_transform;
-                let mut target_transform = initial_transform;
-                target_transform.translation += Vec3::X * DOOR_ANIMATION_DISTANCE;
-                commands.get_entity(door_entity).unwrap().insert(Animation {
-                    animate_forward: true,
-                    animate_backward: false,
-                    animation_speed: DOOR_ANIMATION_SPEED,
-                    progress: 0.0,
-                    initial_transform,
-                    target_transform,
-                });
-            }
             DoorState::Used => {
                 match collision_event {
                     CollisionEvent::Started(_, _, _) => return,
@@ -417,22 +604,165 @@ fn door_use(
                             }
                         };
                         if player_went_though {
-                            let initial_transform = *door_transform;
-                            let mut target_transform = initial_transform;
-                            target_transform.translation -= Vec3::X * DOOR_ANIMATION_DISTANCE;
-                            commands.get_entity(door_entity).unwrap().insert(Animation {
-                                animate_forward: true,
-                                animate_backward: false,
-                                animation_speed: DOOR_ANIMATION_SPEED,
-                                progress: 0.0,
-                                initial_transform,
-                                target_transform,
-                            });
+                            animate_door(
+                                door_entity,
+                                door_transform,
+                                false,
+                                false,
+                                &mut commands,
+                                &mut animation_started_events,
+                            );
                         }
                     }
                 }
             }
-            DoorState::Locked => {}
+            // Opened manually in `door_interact`
+            DoorState::Unlocked | DoorState::Locked | DoorState::Broken | DoorState::Sealed => {}
         }
     }
 }
+
+// Broken door switches level the same way as opened one,
+// but enemies of the next level are alerted immediately.
+// Defended doors are lost instead of broken.
+#[allow(clippy::too_many_arguments)]
+fn door_broken(
+    quality: Res<Quality>,
+    level_resources: Res<LevelResources>,
+    mut level_info: ResMut<LevelInfo>,
+    mut doors: Query<(Entity, &GlobalTransform, &mut Door), Without<DefendedDoor>>,
+    mut game_rng: ResMut<GameRng>,
+    mut commands: Commands,
+    mut kill_events: EventReader<KillEvent>,
+    mut level_switch_events: EventWriter<LevelSwitch>,
+) {
+    let rng = game_rng.stream(RngStream::Effects);
+    for kill_event in kill_events.read() {
+        let Ok((door_entity, door_global_transform, mut door)) = doors.get_mut(kill_event.entity)
+        else {
+            continue;
+        };
+        if !matches!(door.door_state, DoorState::Locked | DoorState::Unlocked) {
+            continue;
+        }
+
+        door.door_state = DoorState::Broken;
+        level_info.enemies_alerted = true;
+        level_switch_events.send(LevelSwitch { exit_door: *door });
+
+        let Some(mut e) = commands.get_entity(door_entity) else {
+            continue;
+        };
+        e.remove::<Collider>().insert(Visibility::Hidden);
+
+        let door_translation = door_global_transform.translation();
+        for _ in 0..DOOR_DEBRIS_PIECES / quality.preset.particle_stride() {
+            let offset = Vec3::new(
+                rng.gen_range(-COLUMN_SIZE / 2.0..COLUMN_SIZE / 2.0),
+                rng.gen_range(-DOOR_THICKNESS / 2.0..DOOR_THICKNESS / 2.0),
+                rng.gen_range(-COLUMN_HIGHT / 2.0..COLUMN_HIGHT / 2.0),
+            );
+            let direction =
+                Vec3::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), 1.0).normalize();
+            commands.spawn((
+                PbrBundle {
+                    mesh: level_resources.door_debris_mesh.clone(),
+                    material: level_resources.door_material.clone(),
+                    transform: Transform::from_translation(door_translation + offset),
+                    ..default()
+                },
+                RigidBody::Dynamic,
+                Collider::cuboid(
+                    DOOR_DEBRIS_SIZE / 2.0,
+                    DOOR_DEBRIS_SIZE / 2.0,
+                    DOOR_DEBRIS_SIZE / 2.0,
+                ),
+                CollisionLayer::Debris.groups(),
+                Velocity {
+                    linvel: direction * DOOR_DEBRIS_VELOCITY,
+                    ..default()
+                },
+                LevelObject,
+            ));
+        }
+    }
+}
+
+fn door_animation_finished(
+    doors: Query<&DoorAnimation>,
+    mut commands: Commands,
+    mut removed_animations: RemovedComponents<Animation>,
+    mut animation_finished_events: EventWriter<DoorAnimationFinished>,
+) {
+    for entity in removed_animations.read() {
+        let Ok(door_animation) = doors.get(entity) else {
+            continue;
+        };
+        animation_finished_events.send(DoorAnimationFinished {
+            door: entity,
+            opening: door_animation.opening,
+            slam: door_animation.slam,
+        });
+
+        let Some(mut e) = commands.get_entity(entity) else {
+            continue;
+        };
+        e.remove::<DoorAnimation>();
+    }
+}
+
+// Doors creak when start opening and slam when
+// finish closing. The door behind the player entering
+// a level slams with a lower, heavier sound. Volume and panning depend on
+// the door position relative to the player camera.
+#[allow(clippy::too_many_arguments)]
+fn door_sounds(
+    sfx: Res<AudioChannel<SfxChannel>>,
+    level_assets: Res<LevelAssets>,
+    game_settings: Res<GameSettings>,
+    player_cameras: Query<(&PlayerId, &GlobalTransform), With<PlayerCamera>>,
+    doors: Query<&GlobalTransform, With<Door>>,
+    mut game_rng: ResMut<GameRng>,
+    mut animation_started_events: EventReader<DoorAnimationStarted>,
+    mut animation_finished_events: EventReader<DoorAnimationFinished>,
+    mut locked_interaction_events: EventReader<DoorLockedInteraction>,
+) {
+    // sounds are heard from the local player camera
+    let Some((_, camera_global_transform)) = player_cameras.iter().find(|(id, _)| id.is_local())
+    else {
+        return;
+    };
+
+    let started = animation_started_events
+        .read()
+        .filter(|e| e.opening)
+        .map(|e| (e.door, &level_assets.door_open, false));
+    let finished = animation_finished_events
+        .read()
+        .filter(|e| !e.opening)
+        .map(|e| (e.door, &level_assets.door_close, e.slam));
+    let locked = locked_interaction_events
+        .read()
+        .map(|e| (e.door, &level_assets.door_locked, false));
+
+    let rng = game_rng.stream(RngStream::Effects);
+    for (door, sound, slam) in started.chain(finished).chain(locked) {
+        let Ok(door_global_transform) = doors.get(door) else {
+            continue;
+        };
+
+        let v = door_global_transform.translation() - camera_global_transform.translation();
+        let attenuation = (1.0 - v.length() / DOOR_SOUND_MAX_DISTANCE).clamp(0.0, 1.0);
+        let panning = 0.5 + 0.5 * camera_global_transform.right().dot(v.normalize_or_zero());
+        let playback_rate = if slam {
+            DOOR_SLAM_PLAYBACK_RATE
+        } else {
+            1.0 + rng.gen_range(-DOOR_SOUND_PITCH_VARIATION..DOOR_SOUND_PITCH_VARIATION)
+        };
+
+        sfx.play(sound.clone())
+            .with_volume((game_settings.volume * attenuation) as f64)
+            .with_panning(panning as f64)
+            .with_playback_rate(playback_rate);
+    }
+}
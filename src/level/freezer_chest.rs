@@ -0,0 +1,351 @@
+use bevy::{pbr::NotShadowCaster, prelude::*};
+use bevy_rapier3d::prelude::*;
+use rand::Rng;
+
+use crate::{
+    collision::CollisionLayer,
+    damage::{Health, KillEvent},
+    display::Quality,
+    enemies::{spawn_enemy, Enemy, EnemyAssets, EnemyReinforcement, EnemyType},
+    impacts::SurfaceMaterial,
+    player::{Player, PlayerCamera, PlayerId},
+    rng::{GameRng, RngStream},
+    weapons::WeaponAssets,
+    GlobalState,
+};
+
+use super::{
+    grade::RunBonus, props::PROP_DEBRIS_SIZE, LevelColliderBundle, LevelInfo, LevelObject,
+    LevelResources, COLUMN_HIGHT, COLUMN_SIZE,
+};
+
+pub const FREEZER_CHEST_WIDTH: f32 = 3.0;
+pub const FREEZER_CHEST_DEPTH: f32 = 1.8;
+pub const FREEZER_CHEST_HIGHT: f32 = 1.4;
+pub const FREEZER_CHEST_LID_THICKNESS: f32 = 0.2;
+const FREEZER_CHEST_HEALTH: i32 = 150;
+
+const FREEZER_CHEST_SPAWN_SECONDS: f32 = 10.0;
+// Lid opens this long before the spawn
+const FREEZER_CHEST_TELEGRAPH_SECONDS: f32 = 1.5;
+const FREEZER_CHEST_LID_OPEN_ANGLE: f32 = 1.2;
+// Radians per second
+const FREEZER_CHEST_LID_SPEED: f32 = 3.0;
+// Chest waits with the next spawn while
+// this many of its fridges are alive
+const FREEZER_CHEST_MAX_MINIONS: usize = 4;
+const FREEZER_CHEST_SPAWN_DISTANCE: f32 = COLUMN_SIZE;
+
+const FREEZER_CHEST_SCORE: u32 = 50;
+const FREEZER_CHEST_DEBRIS_PIECES: u32 = 8;
+const FREEZER_CHEST_DEBRIS_VELOCITY: f32 = 15.0;
+
+pub const HEALTH_BAR_WIDTH: f32 = 3.0;
+pub const HEALTH_BAR_HIGHT: f32 = 0.25;
+pub const HEALTH_BAR_DEPTH: f32 = 0.05;
+const HEALTH_BAR_OFFSET: f32 = 1.0;
+const HEALTH_BAR_TARGET_DISTANCE: f32 = COLUMN_SIZE * 15.0;
+
+/// Haunted freezer chests spawning small fridges
+/// until they are destroyed
+pub struct FreezerChestPlugin;
+
+impl Plugin for FreezerChestPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                freezer_chest_spawn,
+                freezer_chest_lid,
+                freezer_chest_health_bar,
+                freezer_chest_destroyed,
+            )
+                .chain()
+                .run_if(in_state(GlobalState::InGame)),
+        );
+    }
+}
+
+#[derive(Component)]
+pub struct FreezerChest {
+    spawn_timer: Timer,
+    minions: Vec<Entity>,
+    lid: Entity,
+    health_bar: Entity,
+    health_bar_fill: Entity,
+}
+
+impl FreezerChest {
+    fn telegraphing(&self) -> bool {
+        self.spawn_timer.remaining_secs() < FREEZER_CHEST_TELEGRAPH_SECONDS
+    }
+}
+
+// Pivot of the lid at the back edge of the chest
+#[derive(Component)]
+struct FreezerChestLid {
+    angle: f32,
+}
+
+pub fn spawn_freezer_chest(
+    level_resources: &LevelResources,
+    commands: &mut Commands,
+    mut transform: Transform,
+    rotation: f32,
+) {
+    // chest stands on the floor
+    transform.translation.z += -COLUMN_HIGHT / 2.0 + FREEZER_CHEST_HIGHT / 2.0;
+    transform.rotation = Quat::from_rotation_z(rotation);
+
+    let lid = commands
+        .spawn(SpatialBundle::from_transform(Transform::from_xyz(
+            0.0,
+            FREEZER_CHEST_DEPTH / 2.0,
+            FREEZER_CHEST_HIGHT / 2.0,
+        )))
+        .with_children(|builder| {
+            builder.spawn(PbrBundle {
+                mesh: level_resources.freezer_chest_lid_mesh.clone(),
+                material: level_resources.freezer_chest_material.clone(),
+                transform: Transform::from_xyz(
+                    0.0,
+                    -FREEZER_CHEST_DEPTH / 2.0,
+                    FREEZER_CHEST_LID_THICKNESS / 2.0,
+                ),
+                ..default()
+            });
+        })
+        .id();
+
+    let health_bar_fill = commands
+        .spawn((
+            PbrBundle {
+                mesh: level_resources.health_bar_fill_mesh.clone(),
+                material: level_resources.health_bar_material.clone(),
+                ..default()
+            },
+            NotShadowCaster,
+        ))
+        .id();
+    let health_bar = commands
+        .spawn((
+            PbrBundle {
+                mesh: level_resources.health_bar_mesh.clone(),
+                material: level_resources.health_bar_background_material.clone(),
+                transform: Transform::from_xyz(
+                    0.0,
+                    0.0,
+                    FREEZER_CHEST_HIGHT / 2.0 + HEALTH_BAR_OFFSET,
+                ),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            NotShadowCaster,
+        ))
+        .add_child(health_bar_fill)
+        .id();
+
+    commands
+        .spawn((
+            LevelColliderBundle::new(
+                level_resources.freezer_chest_mesh.clone(),
+                level_resources.freezer_chest_material.clone(),
+                transform,
+                Collider::cuboid(
+                    FREEZER_CHEST_WIDTH / 2.0,
+                    FREEZER_CHEST_DEPTH / 2.0,
+                    FREEZER_CHEST_HIGHT / 2.0,
+                ),
+            ),
+            Health {
+                health: FREEZER_CHEST_HEALTH,
+            },
+            SurfaceMaterial::Metal,
+            FreezerChest {
+                spawn_timer: Timer::from_seconds(FREEZER_CHEST_SPAWN_SECONDS, TimerMode::Repeating),
+                minions: vec![],
+                lid,
+                health_bar,
+                health_bar_fill,
+            },
+        ))
+        .add_child(lid)
+        .add_child(health_bar);
+    commands.entity(lid).insert(FreezerChestLid { angle: 0.0 });
+}
+
+fn freezer_chest_spawn(
+    time: Res<Time>,
+    level_info: Res<LevelInfo>,
+    enemy_assets: Res<EnemyAssets>,
+    weapon_assets: Res<WeaponAssets>,
+    enemies: Query<(), With<Enemy>>,
+    mut chests: Query<(&Transform, &mut FreezerChest)>,
+    mut commands: Commands,
+) {
+    // chests go quiet once the level is cleared
+    if level_info.finished {
+        return;
+    }
+
+    for (transform, mut chest) in chests.iter_mut() {
+        chest.minions.retain(|minion| enemies.contains(*minion));
+        if FREEZER_CHEST_MAX_MINIONS <= chest.minions.len() {
+            continue;
+        }
+
+        chest.spawn_timer.tick(time.delta());
+        if !chest.spawn_timer.just_finished() {
+            continue;
+        }
+
+        // fridge climbs out in front of the chest
+        let mut translation =
+            transform.translation + transform.rotation * Vec3::NEG_Y * FREEZER_CHEST_SPAWN_DISTANCE;
+        translation.z += -FREEZER_CHEST_HIGHT / 2.0 + COLUMN_HIGHT / 2.0;
+        let minion = spawn_enemy(
+            enemy_assets.as_ref(),
+            weapon_assets.as_ref(),
+            EnemyType::Small,
+            None,
+            vec![],
+            &mut commands,
+            Transform::from_translation(translation),
+        );
+        commands.entity(minion).insert(EnemyReinforcement);
+        chest.minions.push(minion);
+    }
+}
+
+fn freezer_chest_lid(
+    time: Res<Time>,
+    chests: Query<&FreezerChest>,
+    mut lids: Query<(&mut FreezerChestLid, &mut Transform)>,
+) {
+    for chest in chests.iter() {
+        let Ok((mut lid, mut lid_transform)) = lids.get_mut(chest.lid) else {
+            continue;
+        };
+
+        let target = if chest.telegraphing() {
+            FREEZER_CHEST_LID_OPEN_ANGLE
+        } else {
+            0.0
+        };
+        let step = FREEZER_CHEST_LID_SPEED * time.delta_seconds();
+        lid.angle += (target - lid.angle).clamp(-step, step);
+        // front edge of the lid goes up
+        lid_transform.rotation = Quat::from_rotation_x(-lid.angle);
+    }
+}
+
+// Health bar is shown while the local player aims at the chest
+fn freezer_chest_health_bar(
+    rapier_context: Res<RapierContext>,
+    players: Query<(Entity, &PlayerId), With<Player>>,
+    player_cameras: Query<(&PlayerId, &GlobalTransform), With<PlayerCamera>>,
+    chests: Query<(Entity, &Transform, &Health, &FreezerChest)>,
+    mut health_bars: Query<(&mut Transform, &mut Visibility), Without<FreezerChest>>,
+) {
+    let Some((player, _)) = players.iter().find(|(_, id)| id.is_local()) else {
+        return;
+    };
+    let Some((_, camera_global_transform)) = player_cameras.iter().find(|(id, _)| id.is_local())
+    else {
+        return;
+    };
+
+    let filter = QueryFilter {
+        flags: QueryFilterFlags::EXCLUDE_SENSORS,
+        exclude_collider: Some(player),
+        ..default()
+    };
+    let target = rapier_context
+        .cast_ray(
+            camera_global_transform.translation(),
+            camera_global_transform.forward(),
+            HEALTH_BAR_TARGET_DISTANCE,
+            true,
+            filter,
+        )
+        .map(|(entity, _)| entity);
+
+    for (chest_entity, chest_transform, health, chest) in chests.iter() {
+        let Ok((mut bar_transform, mut bar_visibility)) = health_bars.get_mut(chest.health_bar)
+        else {
+            continue;
+        };
+        if target != Some(chest_entity) {
+            *bar_visibility = Visibility::Hidden;
+            continue;
+        }
+        *bar_visibility = Visibility::Inherited;
+
+        // bar is turned to the camera around the vertical axis
+        let to_camera = camera_global_transform.translation() - chest_transform.translation;
+        let angle = (-to_camera.x).atan2(to_camera.y);
+        bar_transform.rotation = chest_transform.rotation.inverse() * Quat::from_rotation_z(angle);
+
+        let Ok((mut fill_transform, _)) = health_bars.get_mut(chest.health_bar_fill) else {
+            continue;
+        };
+        let fraction = (health.health as f32 / FREEZER_CHEST_HEALTH as f32).clamp(0.0, 1.0);
+        // fill shrinks towards the left edge
+        fill_transform.scale.x = fraction;
+        fill_transform.translation.x = -(1.0 - fraction) * HEALTH_BAR_WIDTH / 2.0;
+    }
+}
+
+fn freezer_chest_destroyed(
+    quality: Res<Quality>,
+    level_resources: Res<LevelResources>,
+    chests: Query<&GlobalTransform, With<FreezerChest>>,
+    mut run_bonus: ResMut<RunBonus>,
+    mut game_rng: ResMut<GameRng>,
+    mut commands: Commands,
+    mut kill_events: EventReader<KillEvent>,
+) {
+    let rng = game_rng.stream(RngStream::Effects);
+    for kill_event in kill_events.read() {
+        let Ok(chest_global_transform) = chests.get(kill_event.entity) else {
+            continue;
+        };
+        let Some(e) = commands.get_entity(kill_event.entity) else {
+            continue;
+        };
+        e.despawn_recursive();
+
+        run_bonus.score += FREEZER_CHEST_SCORE;
+
+        let chest_translation = chest_global_transform.translation();
+        for _ in 0..FREEZER_CHEST_DEBRIS_PIECES / quality.preset.particle_stride() {
+            let offset = Vec3::new(
+                rng.gen_range(-FREEZER_CHEST_WIDTH / 2.0..FREEZER_CHEST_WIDTH / 2.0),
+                rng.gen_range(-FREEZER_CHEST_DEPTH / 2.0..FREEZER_CHEST_DEPTH / 2.0),
+                rng.gen_range(-FREEZER_CHEST_HIGHT / 2.0..FREEZER_CHEST_HIGHT / 2.0),
+            );
+            let direction =
+                Vec3::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), 1.0).normalize();
+            commands.spawn((
+                PbrBundle {
+                    mesh: level_resources.prop_debris_mesh.clone(),
+                    material: level_resources.freezer_chest_material.clone(),
+                    transform: Transform::from_translation(chest_translation + offset),
+                    ..default()
+                },
+                RigidBody::Dynamic,
+                Collider::cuboid(
+                    PROP_DEBRIS_SIZE / 2.0,
+                    PROP_DEBRIS_SIZE / 2.0,
+                    PROP_DEBRIS_SIZE / 2.0,
+                ),
+                CollisionLayer::Debris.groups(),
+                Velocity {
+                    linvel: direction * FREEZER_CHEST_DEBRIS_VELOCITY,
+                    ..default()
+                },
+                LevelObject,
+            ));
+        }
+    }
+}
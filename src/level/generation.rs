@@ -1,38 +1,100 @@
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
-use rand::Rng;
+use rand::{rngs::StdRng, Rng};
 
 use crate::{
-    enemies::{spawn_enemy, EnemyAssets, EnemyType},
-    player::{spawn_player, PlayerResources},
+    director::Director,
+    enemies::{spawn_enemy, Elite, EnemyAssets, EnemyType},
+    impacts::SurfaceMaterial,
+    player::{spawn_player, PlayerResources, PLAYER_LOCAL_INDEX},
+    profile::Profile,
+    rng::{GameRng, RngStream},
     ui::UiResources,
-    weapons::{spawn_weapon, WeaponAssets, WeaponType},
+    unlocks::weapon_unlocked,
+    weapons::{spawn_weapon, WeaponAssets, WeaponRoll, WeaponSpawnTable, WeaponType},
 };
 
 use super::{
+    crafting::spawn_crafting_bench,
+    defense::defend_door,
     door::{spawn_door, Door, DoorState, DoorType},
-    spawn_light, LevelAssets, LevelColliderBundle, LevelObject, LevelResources, LevelType,
-    COLUMN_HIGHT, COLUMN_SIZE, FILL_AMOUNT, FLOOR_THICKNESS, GRID_SIZE, LEVEL_ENEMIES,
-    LEVEL_LIGHTS_COVERAGE, LEVEL_SIZE, LEVEL_SMALL_ENEMIES_PERCENT,
-    LEVEL_WEAPON_PISTOL_SPAWN_THRESHOLD, LEVEL_WEAPON_SHOTGUN_SPAWN_THRESHOLD, LEVEL_WEAPON_SPAWNS,
-    STRIP_LENGTH,
+    freezer_chest::spawn_freezer_chest,
+    ladder::spawn_ladder,
+    modifiers::LevelFloor,
+    moving_floor::{spawn_conveyor, spawn_platform, FloorDirection},
+    pit::spawn_pit,
+    props::spawn_prop,
+    range::{spawn_range_button, spawn_range_target, spawn_shooting_range, RANGE_LENGTH},
+    security::{spawn_security_monitor, SECURITY_CAMERA_HIGHT},
+    spawn_columns, spawn_light,
+    terminal::{random_unread_entry, spawn_terminal},
+    vending::{spawn_vending_machine, VendingItem},
+    LevelAssets, LevelColliderBundle, LevelObject, LevelResources, LevelType, COLUMN_HIGHT,
+    COLUMN_SIZE, FILL_AMOUNT, FLOOR_THICKNESS, GRID_SIZE, LEVEL_CONVEYORS,
+    LEVEL_CONVEYORS_MIN_DEPTH, LEVEL_CONVEYOR_LENGTH, LEVEL_CRAFTING_BENCH_CHANCE,
+    LEVEL_DEFENSE_CHANCE, LEVEL_DEFENSE_MIN_DEPTH, LEVEL_FREEZER_CHEST_CHANCE,
+    LEVEL_FREEZER_CHEST_MIN_DEPTH, LEVEL_LADDER_CHANCE, LEVEL_LIGHTS_COVERAGE, LEVEL_PATROL_RADIUS,
+    LEVEL_PITS, LEVEL_PITS_MIN_DEPTH, LEVEL_PLATFORM_CHANCE, LEVEL_PLATFORM_PITS, LEVEL_PROPS,
+    LEVEL_SECURITY_CAMERA_CHANCE, LEVEL_SIZE, LEVEL_TERMINAL_CHANCE, LEVEL_VENDING_MACHINE_CHANCE,
+    LEVEL_VENDING_MACHINE_JAMMED_CHANCE, LEVEL_WEAPON_SPAWNS, STRIP_LENGTH,
 };
 
+/// What the level is generated for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LevelLayout {
+    Normal,
+    Boss,
+    // Boss level of the boss rush, it
+    // has exits to the next level
+    BossArena,
+    // Level without enemies between boss
+    // rush arenas
+    Shop,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum CellType {
     Empty,
     Door(Door),
+    // Malfunctioning exit attacked by waves
+    DefendedDoor(Door),
     Column,
     Light,
     Weapon(WeaponType),
+    // Weapon carried over from the New Game+
+    // keeps its roll
+    TutorialWeapon,
     Enemy(EnemyType),
+    VendingMachine(VendingItem, bool),
+    Terminal(usize),
+    CraftingBench,
+    FreezerChest,
+    // Loft with a ladder on the side facing the direction
+    Ladder(FloorDirection),
+    // Kept free in front of the ladder
+    LadderFoot,
+    // Cell of the camera watching the level
+    SecurityMonitor((usize, usize)),
+    Pit,
+    Conveyor(FloorDirection),
+    // Start of the moving platform with its end cell
+    Platform((usize, usize)),
+    // Cell where the moving platform turns around
+    PlatformStop,
+    Prop,
+    RangeRack(WeaponType),
+    RangeTarget,
+    RangeButton,
     Player,
 }
 
 // ^ y
 // |
 // -->x
-fn generate_boss_level(previus_door: Option<Door>) -> [[CellType; GRID_SIZE]; GRID_SIZE] {
+fn generate_boss_level(
+    previus_door: Option<Door>,
+    exits: bool,
+) -> [[CellType; GRID_SIZE]; GRID_SIZE] {
     // row order
     let mut grid = [[CellType::Empty; GRID_SIZE]; GRID_SIZE];
 
@@ -51,6 +113,40 @@ fn generate_boss_level(previus_door: Option<Door>) -> [[CellType; GRID_SIZE]; GR
     });
 
     let door = previus_door.unwrap();
+
+    // exits are on every side except the entrance one
+    if exits {
+        let middle = GRID_SIZE / 2;
+        if door.door_type != DoorType::Bottom {
+            grid[0][middle] = CellType::Door(Door {
+                door_type: DoorType::Top,
+                door_state: DoorState::Locked,
+                grid_pos: middle,
+            });
+        }
+        if door.door_type != DoorType::Top {
+            grid[GRID_SIZE - 1][middle] = CellType::Door(Door {
+                door_type: DoorType::Bottom,
+                door_state: DoorState::Locked,
+                grid_pos: middle,
+            });
+        }
+        if door.door_type != DoorType::Right {
+            grid[middle][0] = CellType::Door(Door {
+                door_type: DoorType::Left,
+                door_state: DoorState::Locked,
+                grid_pos: middle,
+            });
+        }
+        if door.door_type != DoorType::Left {
+            grid[middle][GRID_SIZE - 1] = CellType::Door(Door {
+                door_type: DoorType::Right,
+                door_state: DoorState::Locked,
+                grid_pos: middle,
+            });
+        }
+    }
+
     match door.door_type {
         DoorType::Top => {
             grid[GRID_SIZE - 1][door.grid_pos] = CellType::Door(Door {
@@ -164,9 +260,14 @@ fn generate_boss_level(previus_door: Option<Door>) -> [[CellType; GRID_SIZE]; GR
 // ^ y
 // |
 // -->x
-fn generate_normal_level(previus_door: Option<Door>) -> [[CellType; GRID_SIZE]; GRID_SIZE] {
-    let mut rng = rand::thread_rng();
-
+fn generate_normal_level(
+    previus_door: Option<Door>,
+    director: &Director,
+    profile: &Profile,
+    weapon_spawn_table: &WeaponSpawnTable,
+    depth: i32,
+    rng: &mut StdRng,
+) -> [[CellType; GRID_SIZE]; GRID_SIZE] {
     // row order
     let mut grid = [[CellType::Empty; GRID_SIZE]; GRID_SIZE];
 
@@ -246,6 +347,27 @@ fn generate_normal_level(previus_door: Option<Door>) -> [[CellType; GRID_SIZE];
         grid_pos: door_right_pos,
     });
 
+    // one of the exits may have to be defended
+    if LEVEL_DEFENSE_MIN_DEPTH <= depth && rng.gen_bool(LEVEL_DEFENSE_CHANCE) {
+        let exits = [
+            (0, door_top_pos),
+            (GRID_SIZE - 1, door_bottom_pos),
+            (door_left_pos, 0),
+            (door_right_pos, GRID_SIZE - 1),
+        ]
+        .into_iter()
+        .filter(|(y, x)| {
+            matches!(grid[*y][*x], CellType::Door(door) if door.door_state == DoorState::Locked)
+        })
+        .collect::<Vec<_>>();
+        if !exits.is_empty() {
+            let (y, x) = exits[rng.gen_range(0..exits.len())];
+            if let CellType::Door(door) = grid[y][x] {
+                grid[y][x] = CellType::DefendedDoor(door);
+            }
+        }
+    }
+
     // generate walls
     let fill_cells = (GRID_SIZE as f32 * GRID_SIZE as f32 * FILL_AMOUNT) as u32;
     let num_strips = fill_cells / STRIP_LENGTH;
@@ -309,19 +431,16 @@ fn generate_normal_level(previus_door: Option<Door>) -> [[CellType; GRID_SIZE];
             random_cell_y = rng.gen_range(2..GRID_SIZE - 2);
         }
 
-        let random = rng.gen_range(0.0..1.0);
-        if random < LEVEL_WEAPON_PISTOL_SPAWN_THRESHOLD {
-            grid[random_cell_y][random_cell_x] = CellType::Weapon(WeaponType::Pistol);
-        }
-        if random < LEVEL_WEAPON_SHOTGUN_SPAWN_THRESHOLD {
-            grid[random_cell_y][random_cell_x] = CellType::Weapon(WeaponType::Shotgun);
-        } else {
-            grid[random_cell_y][random_cell_x] = CellType::Weapon(WeaponType::Minigun);
+        let mut weapon_type = weapon_spawn_table.pick(depth, director.difficulty(), rng);
+        // locked weapons are replaced with a pistol
+        if !weapon_unlocked(profile, weapon_type) {
+            weapon_type = WeaponType::Pistol;
         }
+        grid[random_cell_y][random_cell_x] = CellType::Weapon(weapon_type);
     }
 
     // generate enemies
-    for _ in 0..LEVEL_ENEMIES {
+    for _ in 0..director.enemies() {
         let mut random_cell_x = rng.gen_range(2..GRID_SIZE - 2);
         let mut random_cell_y = rng.gen_range(2..GRID_SIZE - 2);
 
@@ -330,13 +449,159 @@ fn generate_normal_level(previus_door: Option<Door>) -> [[CellType; GRID_SIZE];
             random_cell_y = rng.gen_range(2..GRID_SIZE - 2);
         }
 
-        if rng.gen_bool(LEVEL_SMALL_ENEMIES_PERCENT) {
+        if rng.gen_bool(director.small_enemies_percent()) {
             grid[random_cell_y][random_cell_x] = CellType::Enemy(EnemyType::Small);
         } else {
             grid[random_cell_y][random_cell_x] = CellType::Enemy(EnemyType::Mid);
         }
     }
 
+    // generate vending machine
+    if rng.gen_bool(LEVEL_VENDING_MACHINE_CHANCE) {
+        let mut random_cell_x = rng.gen_range(2..GRID_SIZE - 2);
+        let mut random_cell_y = rng.gen_range(2..GRID_SIZE - 2);
+
+        while grid[random_cell_y][random_cell_x] != CellType::Empty {
+            random_cell_x = rng.gen_range(2..GRID_SIZE - 2);
+            random_cell_y = rng.gen_range(2..GRID_SIZE - 2);
+        }
+
+        let item = rng.gen();
+        let jammed = rng.gen_bool(LEVEL_VENDING_MACHINE_JAMMED_CHANCE);
+        grid[random_cell_y][random_cell_x] = CellType::VendingMachine(item, jammed);
+    }
+
+    // generate crafting bench
+    if rng.gen_bool(LEVEL_CRAFTING_BENCH_CHANCE) {
+        let mut random_cell_x = rng.gen_range(2..GRID_SIZE - 2);
+        let mut random_cell_y = rng.gen_range(2..GRID_SIZE - 2);
+
+        while grid[random_cell_y][random_cell_x] != CellType::Empty {
+            random_cell_x = rng.gen_range(2..GRID_SIZE - 2);
+            random_cell_y = rng.gen_range(2..GRID_SIZE - 2);
+        }
+
+        grid[random_cell_y][random_cell_x] = CellType::CraftingBench;
+    }
+
+    // generate loft with a ladder
+    if rng.gen_bool(LEVEL_LADDER_CHANCE) {
+        let direction = rng.gen();
+        let cells = random_empty_line(&grid, direction, 2, rng);
+        let ((loft_y, loft_x), (foot_y, foot_x)) = (cells[0], cells[1]);
+        grid[loft_y][loft_x] = CellType::Ladder(direction);
+        grid[foot_y][foot_x] = CellType::LadderFoot;
+    }
+
+    // generate freezer chest
+    if LEVEL_FREEZER_CHEST_MIN_DEPTH <= depth && rng.gen_bool(LEVEL_FREEZER_CHEST_CHANCE) {
+        let mut random_cell_x = rng.gen_range(2..GRID_SIZE - 2);
+        let mut random_cell_y = rng.gen_range(2..GRID_SIZE - 2);
+
+        while grid[random_cell_y][random_cell_x] != CellType::Empty {
+            random_cell_x = rng.gen_range(2..GRID_SIZE - 2);
+            random_cell_y = rng.gen_range(2..GRID_SIZE - 2);
+        }
+
+        grid[random_cell_y][random_cell_x] = CellType::FreezerChest;
+    }
+
+    // generate lore terminal
+    // only entries the player has not read yet are placed
+    if rng.gen_bool(LEVEL_TERMINAL_CHANCE) {
+        if let Some(entry) = random_unread_entry(profile, rng) {
+            let mut random_cell_x = rng.gen_range(2..GRID_SIZE - 2);
+            let mut random_cell_y = rng.gen_range(2..GRID_SIZE - 2);
+
+            while grid[random_cell_y][random_cell_x] != CellType::Empty {
+                random_cell_x = rng.gen_range(2..GRID_SIZE - 2);
+                random_cell_y = rng.gen_range(2..GRID_SIZE - 2);
+            }
+
+            grid[random_cell_y][random_cell_x] = CellType::Terminal(entry);
+        }
+    }
+
+    // generate security camera
+    // camera hangs in a corner of the level and
+    // its monitor is placed somewhere else
+    if rng.gen_bool(LEVEL_SECURITY_CAMERA_CHANCE) {
+        let corners = [
+            (1, 1),
+            (1, GRID_SIZE - 2),
+            (GRID_SIZE - 2, 1),
+            (GRID_SIZE - 2, GRID_SIZE - 2),
+        ]
+        .into_iter()
+        .filter(|(y, x)| grid[*y][*x] == CellType::Empty)
+        .collect::<Vec<_>>();
+        if !corners.is_empty() {
+            let camera_cell = corners[rng.gen_range(0..corners.len())];
+
+            let mut random_cell_x = rng.gen_range(2..GRID_SIZE - 2);
+            let mut random_cell_y = rng.gen_range(2..GRID_SIZE - 2);
+
+            while grid[random_cell_y][random_cell_x] != CellType::Empty {
+                random_cell_x = rng.gen_range(2..GRID_SIZE - 2);
+                random_cell_y = rng.gen_range(2..GRID_SIZE - 2);
+            }
+
+            grid[random_cell_y][random_cell_x] = CellType::SecurityMonitor(camera_cell);
+        }
+    }
+
+    // generate conveyor belts
+    if LEVEL_CONVEYORS_MIN_DEPTH <= depth {
+        for _ in 0..LEVEL_CONVEYORS {
+            let direction = rng.gen();
+            let cells = random_empty_line(&grid, direction, LEVEL_CONVEYOR_LENGTH, rng);
+            for (y, x) in cells {
+                grid[y][x] = CellType::Conveyor(direction);
+            }
+        }
+    }
+
+    // generate moving platform over a trench of pits
+    if LEVEL_PITS_MIN_DEPTH <= depth && rng.gen_bool(LEVEL_PLATFORM_CHANCE) {
+        let direction = rng.gen();
+        let cells = random_empty_line(&grid, direction, LEVEL_PLATFORM_PITS + 2, rng);
+        // platform waits on the cells at both ends
+        let (start, end) = (cells[0], cells[cells.len() - 1]);
+        for &(y, x) in cells.iter() {
+            grid[y][x] = CellType::Pit;
+        }
+        grid[start.0][start.1] = CellType::Platform(end);
+        grid[end.0][end.1] = CellType::PlatformStop;
+    }
+
+    // generate pits
+    if LEVEL_PITS_MIN_DEPTH <= depth {
+        for _ in 0..LEVEL_PITS {
+            let mut random_cell_x = rng.gen_range(2..GRID_SIZE - 2);
+            let mut random_cell_y = rng.gen_range(2..GRID_SIZE - 2);
+
+            while grid[random_cell_y][random_cell_x] != CellType::Empty {
+                random_cell_x = rng.gen_range(2..GRID_SIZE - 2);
+                random_cell_y = rng.gen_range(2..GRID_SIZE - 2);
+            }
+
+            grid[random_cell_y][random_cell_x] = CellType::Pit;
+        }
+    }
+
+    // generate props
+    for _ in 0..LEVEL_PROPS {
+        let mut random_cell_x = rng.gen_range(2..GRID_SIZE - 2);
+        let mut random_cell_y = rng.gen_range(2..GRID_SIZE - 2);
+
+        while grid[random_cell_y][random_cell_x] != CellType::Empty {
+            random_cell_x = rng.gen_range(2..GRID_SIZE - 2);
+            random_cell_y = rng.gen_range(2..GRID_SIZE - 2);
+        }
+
+        grid[random_cell_y][random_cell_x] = CellType::Prop;
+    }
+
     // generate lights
     for y in (2..GRID_SIZE - 2).step_by(3) {
         for x in (2..GRID_SIZE - 2).step_by(3) {
@@ -368,6 +633,133 @@ fn generate_normal_level(previus_door: Option<Door>) -> [[CellType; GRID_SIZE];
     grid
 }
 
+// Line of `length` empty cells going in the `direction`
+// from a random cell
+fn random_empty_line(
+    grid: &[[CellType; GRID_SIZE]; GRID_SIZE],
+    direction: FloorDirection,
+    length: usize,
+    rng: &mut StdRng,
+) -> Vec<(usize, usize)> {
+    let (dy, dx) = direction.grid_step();
+    loop {
+        let y = rng.gen_range(2..GRID_SIZE - 2) as i32;
+        let x = rng.gen_range(2..GRID_SIZE - 2) as i32;
+        let cells = (0..length as i32)
+            .map(|i| (y + dy * i, x + dx * i))
+            .filter(|(y, x)| {
+                (2..GRID_SIZE as i32 - 2).contains(y) && (2..GRID_SIZE as i32 - 2).contains(x)
+            })
+            .map(|(y, x)| (y as usize, x as usize))
+            .filter(|(y, x)| grid[*y][*x] == CellType::Empty)
+            .collect::<Vec<_>>();
+        if cells.len() == length {
+            return cells;
+        }
+    }
+}
+
+// Normal level where enemies are replaced with
+// a vending machine of each item and a crafting bench
+fn generate_shop_level(
+    previus_door: Option<Door>,
+    director: &Director,
+    profile: &Profile,
+    weapon_spawn_table: &WeaponSpawnTable,
+    depth: i32,
+    rng: &mut StdRng,
+) -> [[CellType; GRID_SIZE]; GRID_SIZE] {
+    let mut grid = generate_normal_level(
+        previus_door,
+        director,
+        profile,
+        weapon_spawn_table,
+        depth,
+        rng,
+    );
+
+    for row in grid.iter_mut() {
+        for cell in row.iter_mut() {
+            match cell {
+                CellType::Enemy(_)
+                | CellType::VendingMachine(..)
+                | CellType::CraftingBench
+                | CellType::FreezerChest => {
+                    *cell = CellType::Empty;
+                }
+                CellType::DefendedDoor(door) => *cell = CellType::Door(*door),
+                _ => {}
+            }
+        }
+    }
+
+    let shop = [
+        CellType::VendingMachine(VendingItem::Ammo, false),
+        CellType::VendingMachine(VendingItem::Health, false),
+        CellType::CraftingBench,
+    ];
+    for cell in shop {
+        let mut random_cell_x = rng.gen_range(2..GRID_SIZE - 2);
+        let mut random_cell_y = rng.gen_range(2..GRID_SIZE - 2);
+
+        while grid[random_cell_y][random_cell_x] != CellType::Empty {
+            random_cell_x = rng.gen_range(2..GRID_SIZE - 2);
+            random_cell_y = rng.gen_range(2..GRID_SIZE - 2);
+        }
+
+        grid[random_cell_y][random_cell_x] = cell;
+    }
+
+    grid
+}
+
+pub(super) fn cell_translation(y: usize, x: usize, level_translation: Vec3) -> Vec3 {
+    let x_pos = (-LEVEL_SIZE / 2.0) + COLUMN_SIZE * x as f32 + COLUMN_SIZE / 2.0;
+    let y_pos = (LEVEL_SIZE / 2.0) - COLUMN_SIZE * y as f32 - COLUMN_SIZE / 2.0;
+    let z_pos = COLUMN_HIGHT / 2.0;
+    Vec3::new(x_pos, y_pos, z_pos) + level_translation
+}
+
+// Loop of cells around the enemy cell in clockwise order.
+// Cells with columns, doors, props and pits are skipped.
+fn generate_patrol_route(
+    grid: &[[CellType; GRID_SIZE]; GRID_SIZE],
+    y: usize,
+    x: usize,
+) -> Vec<(usize, usize)> {
+    let r = LEVEL_PATROL_RADIUS as i32;
+    [
+        (-r, 0),
+        (-r, r),
+        (0, r),
+        (r, r),
+        (r, 0),
+        (r, -r),
+        (0, -r),
+        (-r, -r),
+    ]
+    .into_iter()
+    .filter_map(|(dy, dx)| {
+        let cell_y = usize::try_from(y as i32 + dy).ok()?;
+        let cell_x = usize::try_from(x as i32 + dx).ok()?;
+        match grid.get(cell_y)?.get(cell_x)? {
+            CellType::Column
+            | CellType::Door(_)
+            | CellType::DefendedDoor(_)
+            | CellType::VendingMachine(..)
+            | CellType::Terminal(_)
+            | CellType::CraftingBench
+            | CellType::FreezerChest
+            | CellType::Ladder(_)
+            | CellType::SecurityMonitor(_)
+            | CellType::Pit
+            | CellType::Prop => None,
+            _ => Some((cell_y, cell_x)),
+        }
+    })
+    .collect()
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn spawn_level(
     ui_resources: &UiResources,
@@ -376,20 +768,44 @@ pub fn spawn_level(
     weapon_assets: &WeaponAssets,
     level_resources: &LevelResources,
     player_resources: &PlayerResources,
+    director: &Director,
+    profile: &Profile,
+    weapon_spawn_table: &WeaponSpawnTable,
+    rng: &mut GameRng,
     commands: &mut Commands,
     level_translation: Vec3,
     previus_door: Option<Door>,
     level_type: LevelType,
-    tutorial_level: bool,
-    boss_level: bool,
+    tutorial_weapon: Option<(WeaponType, Option<WeaponRoll>)>,
+    layout: LevelLayout,
+    depth: i32,
 ) -> Vec3 {
-    let mut grid = if boss_level {
-        generate_boss_level(previus_door)
-    } else {
-        generate_normal_level(previus_door)
+    let boss_level = matches!(layout, LevelLayout::Boss | LevelLayout::BossArena);
+    let mut grid = match layout {
+        LevelLayout::Normal => generate_normal_level(
+            previus_door,
+            director,
+            profile,
+            weapon_spawn_table,
+            depth,
+            rng.stream(RngStream::Level),
+        ),
+        LevelLayout::Boss => generate_boss_level(previus_door, false),
+        LevelLayout::BossArena => generate_boss_level(previus_door, true),
+        LevelLayout::Shop => generate_shop_level(
+            previus_door,
+            director,
+            profile,
+            weapon_spawn_table,
+            depth,
+            rng.stream(RngStream::Level),
+        ),
     };
 
-    if tutorial_level {
+    // row where the shooting range starts and
+    // column of the tutorial corridor
+    let mut range_start = None;
+    if tutorial_weapon.is_some() {
         let mut player_pos = (0, 0);
 
         // remove all content from the level
@@ -406,10 +822,23 @@ pub fn spawn_level(
         // move player back
         let new_player_pos = (player_pos.0 + 3, player_pos.1);
         grid[player_pos.0][player_pos.1] = CellType::Light;
-        grid[player_pos.0 + 1][player_pos.1] = CellType::Weapon(WeaponType::Pistol);
+        grid[player_pos.0 + 1][player_pos.1] = CellType::TutorialWeapon;
         grid[new_player_pos.0][new_player_pos.1] = CellType::Player;
 
-        // place walls around player
+        // shooting range behind the player with a rack
+        // cell for each weapon type across the corridor
+        let rack_row = new_player_pos.0 + 2;
+        let targets_row = rack_row + RANGE_LENGTH - 1;
+        for (i, weapon_type) in WeaponType::ALL.into_iter().enumerate() {
+            grid[rack_row][new_player_pos.1 - 1 + i] = CellType::RangeRack(weapon_type);
+        }
+        for x in new_player_pos.1 - 1..=new_player_pos.1 + 1 {
+            grid[targets_row][x] = CellType::RangeTarget;
+        }
+        grid[new_player_pos.0][new_player_pos.1 + 1] = CellType::RangeButton;
+        range_start = Some(new_player_pos);
+
+        // place walls around player and the range
         for y in 0..GRID_SIZE {
             grid[y][new_player_pos.1 - 2] = CellType::Column;
         }
@@ -417,7 +846,7 @@ pub fn spawn_level(
             grid[y][new_player_pos.1 + 2] = CellType::Column;
         }
         for x in 0..GRID_SIZE {
-            grid[new_player_pos.0 + 2][x] = CellType::Column;
+            grid[targets_row + 1][x] = CellType::Column;
         }
     }
 
@@ -431,25 +860,22 @@ pub fn spawn_level(
         None => level_translation,
     };
 
+    let mut column_translations = vec![];
+    let mut range_rack = vec![];
     for (y, row) in grid.iter().enumerate() {
         for (x, cell) in row.iter().enumerate() {
-            let x_pos = (-LEVEL_SIZE / 2.0) + COLUMN_SIZE * x as f32 + COLUMN_SIZE / 2.0;
-            let y_pos = (LEVEL_SIZE / 2.0) - COLUMN_SIZE * y as f32 - COLUMN_SIZE / 2.0;
-            let z_pos = COLUMN_HIGHT / 2.0;
-            let translation = Vec3::new(x_pos, y_pos, z_pos);
-            let transform = Transform::from_translation(translation + level_translation);
+            let transform = Transform::from_translation(cell_translation(y, x, level_translation));
 
             match cell {
                 CellType::Door(door) => {
                     spawn_door(level_resources, commands, transform, *door);
                 }
+                CellType::DefendedDoor(door) => {
+                    let door_entity = spawn_door(level_resources, commands, transform, *door);
+                    defend_door(commands, door_entity);
+                }
                 CellType::Column => {
-                    commands.spawn(LevelColliderBundle::new(
-                        level_resources.column_mesh.clone(),
-                        level_resources.column_material.clone(),
-                        transform,
-                        Collider::cuboid(COLUMN_SIZE / 2.0, COLUMN_SIZE / 2.0, COLUMN_HIGHT / 2.0),
-                    ));
+                    column_translations.push(transform.translation);
                 }
                 CellType::Light => {
                     if level_type == LevelType::Covered {
@@ -458,14 +884,93 @@ pub fn spawn_level(
                         spawn_light(level_resources, commands, light_transform);
                     }
                 }
+                CellType::VendingMachine(item, jammed) => {
+                    spawn_vending_machine(level_resources, commands, transform, *item, *jammed);
+                }
+                CellType::Terminal(entry) => {
+                    spawn_terminal(level_resources, commands, transform, *entry);
+                }
+                CellType::CraftingBench => {
+                    spawn_crafting_bench(level_resources, commands, transform);
+                }
+                CellType::FreezerChest => {
+                    let rotation = rng
+                        .stream(RngStream::Level)
+                        .gen_range(0.0..std::f32::consts::TAU);
+                    spawn_freezer_chest(level_resources, commands, transform, rotation);
+                }
+                CellType::Ladder(direction) => {
+                    spawn_ladder(level_resources, commands, transform, *direction);
+                }
+                CellType::SecurityMonitor((camera_y, camera_x)) => {
+                    // camera looks at the middle of the level
+                    let mut camera_translation =
+                        cell_translation(*camera_y, *camera_x, level_translation);
+                    camera_translation.z = SECURITY_CAMERA_HIGHT;
+                    let camera_transform = Transform::from_translation(camera_translation)
+                        .looking_at(level_translation, Vec3::Z);
+                    spawn_security_monitor(level_resources, commands, transform, camera_transform);
+                }
+                CellType::Pit => {
+                    spawn_pit(level_resources, commands, transform);
+                }
+                CellType::Conveyor(direction) => {
+                    spawn_conveyor(level_resources, commands, transform, *direction);
+                }
+                CellType::Platform((end_y, end_x)) => {
+                    let end = cell_translation(*end_y, *end_x, level_translation);
+                    spawn_platform(level_resources, commands, transform, end);
+                }
+                CellType::PlatformStop => {}
+                CellType::Prop => {
+                    let rotation = rng
+                        .stream(RngStream::Level)
+                        .gen_range(0.0..std::f32::consts::FRAC_PI_2);
+                    spawn_prop(level_resources, commands, transform, rotation);
+                }
+                CellType::RangeRack(weapon_type) => {
+                    range_rack.push((*weapon_type, transform.translation));
+                }
+                CellType::RangeTarget => {
+                    spawn_range_target(level_resources, commands, transform);
+                }
+                CellType::RangeButton => {
+                    spawn_range_button(level_resources, commands, transform);
+                }
                 CellType::Weapon(weapon_type) => {
-                    spawn_weapon(weapon_assets, *weapon_type, commands, transform);
+                    let roll = WeaponRoll::random(rng.stream(RngStream::Loot));
+                    spawn_weapon(weapon_assets, *weapon_type, roll, commands, transform);
+                }
+                CellType::TutorialWeapon => {
+                    if let Some((weapon_type, roll)) = tutorial_weapon {
+                        let roll =
+                            roll.unwrap_or_else(|| WeaponRoll::random(rng.stream(RngStream::Loot)));
+                        spawn_weapon(weapon_assets, weapon_type, roll, commands, transform);
+                    }
                 }
                 CellType::Enemy(enemy_type) => {
+                    let elite_rng = rng.stream(RngStream::Elites);
+                    let elite =
+                        (!boss_level && elite_rng.gen_bool(director.elite_chance())).then(|| {
+                            Elite {
+                                modifier: (0 < director.prestige).then(|| elite_rng.gen()),
+                            }
+                        });
+                    // boss level is too crowded for patrols
+                    let patrol_route = if boss_level {
+                        vec![]
+                    } else {
+                        generate_patrol_route(&grid, y, x)
+                            .into_iter()
+                            .map(|(y, x)| cell_translation(y, x, level_translation))
+                            .collect()
+                    };
                     spawn_enemy(
                         enemy_assets,
                         weapon_assets,
                         *enemy_type,
+                        elite,
+                        patrol_route,
                         commands,
                         transform,
                     );
@@ -479,30 +984,52 @@ pub fn spawn_level(
                         level_assets.normal_skybox.clone(),
                         commands,
                         transform,
+                        PLAYER_LOCAL_INDEX,
                     );
                 }
-                CellType::Empty => {}
+                CellType::Empty | CellType::LadderFoot => {}
             }
         }
     }
 
+    if let Some((y, x)) = range_start {
+        // range starts at the edge of the player cell
+        // facing the exit
+        let boundary_y = cell_translation(y, x, level_translation).y + COLUMN_SIZE / 2.0;
+        spawn_shooting_range(weapon_assets, commands, boundary_y, range_rack);
+    }
+
+    spawn_columns(
+        level_resources,
+        commands,
+        level_translation,
+        column_translations,
+    );
+
     // floor
-    commands.spawn(LevelColliderBundle::new(
-        level_resources.floor_mesh.clone(),
-        level_resources.floor_material.clone(),
-        Transform::from_translation(level_translation),
-        Collider::cuboid(LEVEL_SIZE / 2.0, LEVEL_SIZE / 2.0, 0.5),
+    commands.spawn((
+        LevelColliderBundle::new(
+            level_resources.floor_mesh.clone(),
+            level_resources.floor_material.clone(),
+            Transform::from_translation(level_translation),
+            Collider::cuboid(LEVEL_SIZE / 2.0, LEVEL_SIZE / 2.0, 0.5),
+        ),
+        LevelFloor,
+        SurfaceMaterial::Concrete,
     ));
 
     // roof
     if level_type == LevelType::Covered {
         let mut roof_translation = level_translation;
         roof_translation.z += COLUMN_HIGHT + FLOOR_THICKNESS / 2.0;
-        commands.spawn(LevelColliderBundle::new(
-            level_resources.floor_mesh.clone(),
-            level_resources.floor_material.clone(),
-            Transform::from_translation(roof_translation),
-            Collider::cuboid(LEVEL_SIZE / 2.0, LEVEL_SIZE / 2.0, 0.5),
+        commands.spawn((
+            LevelColliderBundle::new(
+                level_resources.floor_mesh.clone(),
+                level_resources.floor_material.clone(),
+                Transform::from_translation(roof_translation),
+                Collider::cuboid(LEVEL_SIZE / 2.0, LEVEL_SIZE / 2.0, 0.5),
+            ),
+            SurfaceMaterial::Concrete,
         ));
     }
 
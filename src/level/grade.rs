@@ -0,0 +1,437 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    damage::{DamageEvent, Health},
+    enemies::Enemy,
+    player::{Player, PlayerId, PlayerWeapon, PLAYER_HEALTH},
+    profile::Profile,
+    time_attack::Medal,
+    ui::UiAssets,
+    weapons::ShootEvent,
+    GlobalState,
+};
+
+use super::{
+    defense::{DoorDefenseFinished, DoorDefenseStarted},
+    terminal::LoreTerminal,
+    LevelFinished, LevelInfo, LevelObject, LevelStarted,
+};
+
+// Level finished faster than this gets full points for time
+const GRADE_PAR_SECONDS: f32 = 60.0;
+const GRADE_BONUS_NO_DAMAGE: u32 = 100;
+const GRADE_BONUS_NO_SHOTS: u32 = 150;
+const GRADE_BONUS_SECRET: u32 = 50;
+const GRADE_BONUS_DOOR_DEFENDED: u32 = 200;
+
+// Medal thresholds for gold, silver and bronze
+const GRADE_MEDAL_ACCURACY: [f32; 3] = [0.8, 0.6, 0.4];
+const GRADE_MEDAL_SECONDS: [f32; 3] = [30.0, 45.0, GRADE_PAR_SECONDS];
+const GRADE_MEDAL_DAMAGE: [i32; 3] = [0, PLAYER_HEALTH / 6, PLAYER_HEALTH / 3];
+
+const GRADE_CARD_SLIDE_SECONDS: f32 = 0.4;
+const GRADE_CARD_SECONDS: f32 = 3.0;
+const GRADE_CARD_TRANSLATION: Vec3 = Vec3::new(400.0, 80.0, 5.0);
+// Card slides in from behind the right edge of the screen
+const GRADE_CARD_START_OFFSET: Vec3 = Vec3::new(500.0, 0.0, 0.0);
+const GRADE_CARD_TEXT_SIZE: f32 = 32.0;
+const GRADE_CARD_GRADE_SIZE: f32 = 80.0;
+
+pub struct GradePlugin;
+
+impl Plugin for GradePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<LevelExitUnlocked>();
+        app.init_resource::<LevelGradeStats>();
+        app.init_resource::<RunBonus>();
+
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::MainMenu,
+                to: GlobalState::InGame,
+            },
+            reset_run_bonus,
+        );
+
+        app.add_systems(
+            Update,
+            (
+                grade_level_started,
+                grade_track_shots,
+                grade_track_damage,
+                grade_track_defense,
+                grade_level_finished,
+                grade_card_update,
+            )
+                .chain()
+                .run_if(in_state(GlobalState::InGame)),
+        );
+    }
+}
+
+/// Sent after the grade card is shown, doors
+/// of the level unlock only after it.
+#[derive(Event)]
+pub struct LevelExitUnlocked;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Grade {
+    S,
+    A,
+    B,
+    C,
+    D,
+}
+
+impl Grade {
+    // Points are between 0.0 and 1.0
+    fn from_points(points: f32) -> Self {
+        if 0.9 <= points {
+            Grade::S
+        } else if 0.75 <= points {
+            Grade::A
+        } else if 0.6 <= points {
+            Grade::B
+        } else if 0.4 <= points {
+            Grade::C
+        } else {
+            Grade::D
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            Grade::S => Color::GOLD,
+            Grade::A => Color::LIME_GREEN,
+            Grade::B => Color::SEA_GREEN,
+            Grade::C => Color::ORANGE,
+            Grade::D => Color::CRIMSON,
+        }
+    }
+}
+
+/// Stats of the current level
+#[derive(Debug, Default, Resource)]
+pub struct LevelGradeStats {
+    // Stats are only collected for started levels,
+    // the first level is finished without being started
+    tracking: bool,
+    time: f32,
+    shots: u32,
+    hits: u32,
+    damage_taken: i32,
+    last_health: Option<i32>,
+    lore_entries: usize,
+    // None if the level has no door to defend
+    door_defense: Option<DoorDefense>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DoorDefense {
+    Ongoing,
+    Defended,
+    Lost,
+}
+
+impl LevelGradeStats {
+    fn secrets_found(&self, profile: &Profile) -> usize {
+        profile.lore_entries.len().saturating_sub(self.lore_entries)
+    }
+
+    /// Objectives of the level and if they are still
+    /// met, `secrets_left` are unread terminals of the level
+    pub fn objectives_text(&self, profile: &Profile, secrets_left: usize) -> String {
+        if !self.tracking {
+            return "No objectives".to_string();
+        }
+        let check = |done: bool| if done { "[x]" } else { "[ ]" };
+        let secrets_found = self.secrets_found(profile);
+        let mut text = format!(
+            "{} No damage +{GRADE_BONUS_NO_DAMAGE}\n{} No shots fired +{GRADE_BONUS_NO_SHOTS}\n{} Secrets {secrets_found}/{} +{GRADE_BONUS_SECRET} each",
+            check(self.damage_taken == 0),
+            check(self.shots == 0),
+            check(secrets_left == 0),
+            secrets_found + secrets_left,
+        );
+        match self.door_defense {
+            Some(DoorDefense::Lost) => text += "\n[-] Door lost",
+            Some(defense) => {
+                text += &format!(
+                    "\n{} Defend the door +{GRADE_BONUS_DOOR_DEFENDED}",
+                    check(defense == DoorDefense::Defended)
+                )
+            }
+            None => {}
+        }
+        text
+    }
+}
+
+/// Bonus score for the objectives of all levels of the run
+#[derive(Debug, Default, Resource)]
+pub struct RunBonus {
+    pub score: u32,
+    // Price of all props broken during the run
+    pub property_damage: u32,
+}
+
+/// Best medals of one level
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LevelMedals {
+    pub seed: u64,
+    pub floor: i32,
+    pub accuracy: Option<Medal>,
+    pub time: Option<Medal>,
+    pub damage: Option<Medal>,
+}
+
+impl LevelMedals {
+    pub fn medals(&self) -> [Option<Medal>; 3] {
+        [self.accuracy, self.time, self.damage]
+    }
+
+    fn keep_best(&mut self, other: &LevelMedals) {
+        let best = |a: Option<Medal>, b: Option<Medal>| {
+            if b.map(|m| m.rank()) > a.map(|m| m.rank()) {
+                b
+            } else {
+                a
+            }
+        };
+        self.accuracy = best(self.accuracy, other.accuracy);
+        self.time = best(self.time, other.time);
+        self.damage = best(self.damage, other.damage);
+    }
+}
+
+// First threshold the value passes gives the medal
+fn medal(passes: impl Fn(usize) -> bool) -> Option<Medal> {
+    [Medal::Gold, Medal::Silver, Medal::Bronze]
+        .into_iter()
+        .enumerate()
+        .find(|(i, _)| passes(*i))
+        .map(|(_, medal)| medal)
+}
+
+#[derive(Component)]
+struct GradeCard {
+    progress: f32,
+}
+
+fn reset_run_bonus(mut run_bonus: ResMut<RunBonus>) {
+    *run_bonus = RunBonus::default();
+}
+
+fn grade_level_started(
+    time: Res<Time>,
+    profile: Res<Profile>,
+    mut stats: ResMut<LevelGradeStats>,
+    mut level_started_events: EventReader<LevelStarted>,
+) {
+    for _ in level_started_events.read() {
+        *stats = LevelGradeStats {
+            tracking: true,
+            lore_entries: profile.lore_entries.len(),
+            ..default()
+        };
+    }
+    if stats.tracking {
+        stats.time += time.delta_seconds();
+    }
+}
+
+fn grade_track_shots(
+    player_weapon: Query<(), With<PlayerWeapon>>,
+    enemies: Query<(), With<Enemy>>,
+    mut stats: ResMut<LevelGradeStats>,
+    mut shoot_events: EventReader<ShootEvent>,
+    mut damage_events: EventReader<DamageEvent>,
+) {
+    for shoot_event in shoot_events.read() {
+        if player_weapon.contains(shoot_event.weapon_entity) {
+            stats.shots += 1;
+        }
+    }
+    for damage_event in damage_events.read() {
+        if enemies.contains(damage_event.entity) {
+            stats.hits += 1;
+        }
+    }
+}
+
+fn grade_track_damage(
+    players: Query<(&PlayerId, &Health), With<Player>>,
+    mut stats: ResMut<LevelGradeStats>,
+) {
+    // grade is shown on the local screen, so it tracks the local player
+    let Some((_, health)) = players.iter().find(|(id, _)| id.is_local()) else {
+        return;
+    };
+    if let Some(last_health) = stats.last_health {
+        // healing does not undo the taken damage
+        stats.damage_taken += (last_health - health.health).max(0);
+    }
+    stats.last_health = Some(health.health);
+}
+
+fn grade_track_defense(
+    mut stats: ResMut<LevelGradeStats>,
+    mut defense_started_events: EventReader<DoorDefenseStarted>,
+    mut defense_finished_events: EventReader<DoorDefenseFinished>,
+) {
+    for _ in defense_started_events.read() {
+        stats.door_defense = Some(DoorDefense::Ongoing);
+    }
+    for defense_finished in defense_finished_events.read() {
+        stats.door_defense = Some(if defense_finished.defended {
+            DoorDefense::Defended
+        } else {
+            DoorDefense::Lost
+        });
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn grade_level_finished(
+    ui_assets: Res<UiAssets>,
+    level_info: Res<LevelInfo>,
+    mut profile: ResMut<Profile>,
+    terminals: Query<(), With<LoreTerminal>>,
+    mut stats: ResMut<LevelGradeStats>,
+    mut run_bonus: ResMut<RunBonus>,
+    mut commands: Commands,
+    mut level_finished_events: EventReader<LevelFinished>,
+    mut exit_unlocked_events: EventWriter<LevelExitUnlocked>,
+) {
+    for _ in level_finished_events.read() {
+        if !stats.tracking {
+            exit_unlocked_events.send(LevelExitUnlocked);
+            continue;
+        }
+        stats.tracking = false;
+
+        let time_points = (GRADE_PAR_SECONDS / stats.time.max(1.0)).min(1.0);
+        let accuracy = if stats.shots == 0 {
+            1.0
+        } else {
+            // one shotgun shot can hit several times
+            (stats.hits as f32 / stats.shots as f32).min(1.0)
+        };
+        let damage_points = 1.0 - (stats.damage_taken as f32 / PLAYER_HEALTH as f32).min(1.0);
+        let grade = Grade::from_points((time_points + accuracy + damage_points) / 3.0);
+
+        let secrets_found = stats.secrets_found(profile.as_ref());
+        let secrets_total = secrets_found + terminals.iter().count();
+
+        let mut bonus = secrets_found as u32 * GRADE_BONUS_SECRET;
+        let mut objectives = String::new();
+        if stats.damage_taken == 0 {
+            bonus += GRADE_BONUS_NO_DAMAGE;
+            objectives += &format!("No damage +{GRADE_BONUS_NO_DAMAGE}\n");
+        }
+        // kills only with thrown weapons
+        if stats.shots == 0 {
+            bonus += GRADE_BONUS_NO_SHOTS;
+            objectives += &format!("No shots fired +{GRADE_BONUS_NO_SHOTS}\n");
+        }
+        if stats.door_defense == Some(DoorDefense::Defended) {
+            bonus += GRADE_BONUS_DOOR_DEFENDED;
+            objectives += &format!("Door defended +{GRADE_BONUS_DOOR_DEFENDED}\n");
+        }
+        run_bonus.score += bonus;
+
+        let text_style = TextStyle {
+            font: ui_assets.font.clone(),
+            font_size: GRADE_CARD_TEXT_SIZE,
+            color: Color::WHITE,
+        };
+
+        let medals = LevelMedals {
+            seed: level_info.seed,
+            floor: level_info.game_progress,
+            accuracy: medal(|i| GRADE_MEDAL_ACCURACY[i] <= accuracy),
+            time: medal(|i| stats.time <= GRADE_MEDAL_SECONDS[i]),
+            damage: medal(|i| stats.damage_taken <= GRADE_MEDAL_DAMAGE[i]),
+        };
+        match profile
+            .level_medals
+            .iter_mut()
+            .find(|m| m.seed == medals.seed && m.floor == medals.floor)
+        {
+            Some(best) => best.keep_best(&medals),
+            None => profile.level_medals.push(medals),
+        }
+
+        let mut medal_sections = Vec::new();
+        for (name, medal) in ["Accuracy", "Time", "Damage"].iter().zip(medals.medals()) {
+            medal_sections.push(TextSection::new(format!("\n{name} "), text_style.clone()));
+            let (medal_name, medal_color) = match medal {
+                Some(medal) => (medal.name(), medal.color()),
+                None => ("---", Color::GRAY),
+            };
+            medal_sections.push(TextSection::new(
+                medal_name,
+                TextStyle {
+                    color: medal_color,
+                    ..text_style.clone()
+                },
+            ));
+        }
+
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_sections([
+                    TextSection::new(
+                        format!("{grade:?}\n"),
+                        TextStyle {
+                            font: ui_assets.font.clone(),
+                            font_size: GRADE_CARD_GRADE_SIZE,
+                            color: grade.color(),
+                        },
+                    ),
+                    TextSection::new(
+                        format!(
+                            "Time: {:.1}s\nAccuracy: {:.0}%\nDamage taken: {}\nSecrets: {secrets_found}/{secrets_total}\n{objectives}Bonus: +{bonus}",
+                            stats.time,
+                            accuracy * 100.0,
+                            stats.damage_taken,
+                        ),
+                        text_style,
+                    ),
+                ]
+                .into_iter()
+                .chain(medal_sections))
+                .with_alignment(TextAlignment::Center),
+                transform: Transform::from_translation(
+                    GRADE_CARD_TRANSLATION + GRADE_CARD_START_OFFSET,
+                ),
+                ..default()
+            },
+            GradeCard { progress: 0.0 },
+            LevelObject,
+        ));
+    }
+}
+
+fn grade_card_update(
+    time: Res<Time>,
+    mut cards: Query<(Entity, &mut Transform, &mut GradeCard)>,
+    mut commands: Commands,
+    mut exit_unlocked_events: EventWriter<LevelExitUnlocked>,
+) {
+    for (entity, mut transform, mut card) in cards.iter_mut() {
+        card.progress += time.delta_seconds();
+
+        let slide_in = (card.progress / GRADE_CARD_SLIDE_SECONDS).min(1.0);
+        let slide_out =
+            ((card.progress - GRADE_CARD_SECONDS) / GRADE_CARD_SLIDE_SECONDS).clamp(0.0, 1.0);
+        transform.translation =
+            GRADE_CARD_TRANSLATION + GRADE_CARD_START_OFFSET * (1.0 - slide_in + slide_out);
+
+        if GRADE_CARD_SECONDS + GRADE_CARD_SLIDE_SECONDS <= card.progress {
+            commands.entity(entity).despawn_recursive();
+            exit_unlocked_events.send(LevelExitUnlocked);
+        }
+    }
+}
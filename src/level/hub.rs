@@ -0,0 +1,226 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    enemies::EnemyAssets,
+    player::{spawn_player, Player, PlayerResources, PLAYER_LOCAL_INDEX},
+    profile::Profile,
+    ui::UiResources,
+    unlocks::weapon_unlocked,
+    utils::remove_all_with,
+    weapons::{WeaponAssets, WeaponType},
+    GlobalState,
+};
+
+use super::{
+    generation::cell_translation, spawn_columns, spawn_light, LevelAssets, LevelColliderBundle,
+    LevelObject, LevelResources, COLUMN_HIGHT, FLOOR_THICKNESS, GRID_SIZE, LEVEL_SIZE,
+};
+
+// Layout of the hub. Each character is one cell:
+// `#` - column, `L` - light, `P` - player,
+// `T` - boss trophy, `U` - unlocked weapon display,
+// anything else - empty floor.
+const HUB_LAYOUT_PATH: &str = "assets/levels/hub.txt";
+const HUB_DEFAULT_LAYOUT: &str = include_str!("../../assets/levels/hub.txt");
+const HUB_TROPHY_SCALE: f32 = 1.5;
+const HUB_WEAPON_SCALE: f32 = 2.0;
+
+/// Room the player returns to after a run.
+/// Shows a trophy for each won run and all
+/// unlocked weapons.
+pub struct HubPlugin;
+
+impl Plugin for HubPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::InGame,
+                to: GlobalState::GameWon,
+            },
+            count_boss_kill,
+        );
+
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::GameOver,
+                to: GlobalState::Hub,
+            },
+            (
+                remove_all_with::<LevelObject>,
+                remove_all_with::<Player>,
+                spawn_hub,
+            )
+                .chain(),
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::GameWon,
+                to: GlobalState::Hub,
+            },
+            (
+                remove_all_with::<LevelObject>,
+                remove_all_with::<Player>,
+                spawn_hub,
+            )
+                .chain(),
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::Hub,
+                to: GlobalState::MainMenu,
+            },
+            (remove_all_with::<LevelObject>, remove_all_with::<Player>),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HubCell {
+    Empty,
+    Column,
+    Light,
+    Player,
+    Trophy,
+    WeaponDisplay,
+}
+
+impl From<char> for HubCell {
+    fn from(c: char) -> Self {
+        match c {
+            '#' => HubCell::Column,
+            'L' => HubCell::Light,
+            'P' => HubCell::Player,
+            'T' => HubCell::Trophy,
+            'U' => HubCell::WeaponDisplay,
+            _ => HubCell::Empty,
+        }
+    }
+}
+
+// Layout is placed in the middle of the level grid.
+// Cells outside of the grid are ignored.
+fn parse_hub_layout(layout: &str) -> Vec<(usize, usize, HubCell)> {
+    let rows = layout.lines().count();
+    let columns = layout.lines().map(|line| line.len()).max().unwrap_or(0);
+    let offset_y = GRID_SIZE.saturating_sub(rows) / 2;
+    let offset_x = GRID_SIZE.saturating_sub(columns) / 2;
+
+    layout
+        .lines()
+        .enumerate()
+        .flat_map(|(y, line)| {
+            line.chars()
+                .enumerate()
+                .map(move |(x, c)| (y + offset_y, x + offset_x, HubCell::from(c)))
+        })
+        .filter(|(y, x, cell)| *y < GRID_SIZE && *x < GRID_SIZE && *cell != HubCell::Empty)
+        .collect()
+}
+
+fn count_boss_kill(mut profile: ResMut<Profile>) {
+    profile.boss_kills += 1;
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_hub(
+    profile: Res<Profile>,
+    ui_resources: Res<UiResources>,
+    level_assets: Res<LevelAssets>,
+    enemy_assets: Res<EnemyAssets>,
+    weapon_assets: Res<WeaponAssets>,
+    level_resources: Res<LevelResources>,
+    player_resources: Res<PlayerResources>,
+    mut commands: Commands,
+) {
+    let layout = std::fs::read_to_string(HUB_LAYOUT_PATH).unwrap_or_else(|e| {
+        warn!("Could not load hub layout {HUB_LAYOUT_PATH}: {e}");
+        HUB_DEFAULT_LAYOUT.to_string()
+    });
+
+    let mut trophies = 0;
+    let mut unlocked_weapons = WeaponType::ALL
+        .into_iter()
+        .filter(|weapon_type| weapon_unlocked(profile.as_ref(), *weapon_type));
+
+    let mut column_translations = vec![];
+    for (y, x, cell) in parse_hub_layout(&layout) {
+        let transform = Transform::from_translation(cell_translation(y, x, Vec3::ZERO));
+        match cell {
+            HubCell::Column => {
+                column_translations.push(transform.translation);
+            }
+            HubCell::Light => {
+                let mut light_transform = transform;
+                light_transform.translation.z = COLUMN_HIGHT;
+                spawn_light(level_resources.as_ref(), &mut commands, light_transform);
+            }
+            HubCell::Player => {
+                spawn_player(
+                    ui_resources.as_ref(),
+                    player_resources.as_ref(),
+                    level_assets.normal_skybox.clone(),
+                    &mut commands,
+                    transform,
+                    PLAYER_LOCAL_INDEX,
+                );
+            }
+            HubCell::Trophy => {
+                // one trophy for each won run
+                if trophies < profile.boss_kills {
+                    trophies += 1;
+                    commands.spawn((
+                        SceneBundle {
+                            scene: enemy_assets.big_enemy_scene.clone(),
+                            transform: transform.with_scale(Vec3::splat(HUB_TROPHY_SCALE)),
+                            ..default()
+                        },
+                        LevelObject,
+                    ));
+                }
+            }
+            HubCell::WeaponDisplay => {
+                let Some(weapon_type) = unlocked_weapons.next() else {
+                    continue;
+                };
+                let scene = match weapon_type {
+                    WeaponType::Pistol => weapon_assets.pistol_scene.clone(),
+                    WeaponType::Shotgun => weapon_assets.shotgun_scene.clone(),
+                    WeaponType::Minigun => weapon_assets.minigun_scene.clone(),
+                };
+                commands.spawn((
+                    SceneBundle {
+                        scene,
+                        transform: transform.with_scale(Vec3::splat(HUB_WEAPON_SCALE)),
+                        ..default()
+                    },
+                    LevelObject,
+                ));
+            }
+            HubCell::Empty => {}
+        }
+    }
+
+    spawn_columns(
+        level_resources.as_ref(),
+        &mut commands,
+        Vec3::ZERO,
+        column_translations,
+    );
+
+    // floor
+    commands.spawn(LevelColliderBundle::new(
+        level_resources.floor_mesh.clone(),
+        level_resources.floor_material.clone(),
+        Transform::default(),
+        Collider::cuboid(LEVEL_SIZE / 2.0, LEVEL_SIZE / 2.0, 0.5),
+    ));
+
+    // roof
+    commands.spawn(LevelColliderBundle::new(
+        level_resources.floor_mesh.clone(),
+        level_resources.floor_material.clone(),
+        Transform::from_translation(Vec3::Z * (COLUMN_HIGHT + FLOOR_THICKNESS / 2.0)),
+        Collider::cuboid(LEVEL_SIZE / 2.0, LEVEL_SIZE / 2.0, 0.5),
+    ));
+}
@@ -0,0 +1,68 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    player::{Player, PlayerCamera, PlayerId},
+    GlobalState,
+};
+
+use super::COLUMN_SIZE;
+
+// Farthest any interactive object can be used from
+const INTERACTION_MAX_DISTANCE: f32 = COLUMN_SIZE * 3.0;
+
+/// Finds what each player aims at once per frame. Doors, vending
+/// machines, terminals and other interactive objects read the
+/// target instead of casting their own rays, so one press of the
+/// interaction button only reaches the closest object.
+pub struct InteractionPlugin;
+
+impl Plugin for InteractionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PreUpdate,
+            interaction_target_update.run_if(in_state(GlobalState::InGame)),
+        );
+    }
+}
+
+/// Closest object in front of the player camera
+#[derive(Debug, Default, Component)]
+pub struct InteractionTarget {
+    target: Option<(Entity, f32)>,
+}
+
+impl InteractionTarget {
+    /// Aimed entity if it is not farther than `distance`
+    pub fn get(&self, distance: f32) -> Option<Entity> {
+        self.target
+            .filter(|(_, toi)| *toi <= distance)
+            .map(|(entity, _)| entity)
+    }
+}
+
+fn interaction_target_update(
+    rapier_context: Res<RapierContext>,
+    player_cameras: Query<(&PlayerId, &GlobalTransform), With<PlayerCamera>>,
+    mut players: Query<(Entity, &PlayerId, &mut InteractionTarget), With<Player>>,
+) {
+    for (player, player_id, mut interaction_target) in players.iter_mut() {
+        let Some((_, camera_global_transform)) =
+            player_cameras.iter().find(|(id, _)| *id == player_id)
+        else {
+            interaction_target.target = None;
+            continue;
+        };
+
+        let ray_origin = camera_global_transform.translation();
+        let ray_dir = camera_global_transform.forward();
+        let solid = true;
+        let filter = QueryFilter {
+            flags: QueryFilterFlags::EXCLUDE_SENSORS,
+            exclude_collider: Some(player),
+            ..default()
+        };
+        interaction_target.target =
+            rapier_context.cast_ray(ray_origin, ray_dir, INTERACTION_MAX_DISTANCE, solid, filter);
+    }
+}
@@ -0,0 +1,286 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    collision::{COLLISION_GROUP_LEVEL, COLLISION_GROUP_PLAYER},
+    impacts::SurfaceMaterial,
+    input_device::InteractInput,
+    player::{Player, PlayerId, PlayerVault, PLAYER_COLLIDER_RADIUS},
+    GlobalState,
+};
+
+use super::{
+    interaction::InteractionTarget, moving_floor::FloorDirection, LevelColliderBundle,
+    LevelResources, COLUMN_SIZE,
+};
+
+// Upper floor over a whole cell. It blocks the player walking
+// on the floor, while the player standing on it is raised just
+// enough to clear it and still fit under the roof.
+pub const LOFT_HIGHT: f32 = 5.0;
+const LOFT_PLAYER_RISE: f32 = 2.0;
+// Player steps this far onto the loft or away from the ladder
+const LOFT_STEP: f32 = PLAYER_COLLIDER_RADIUS + 0.5;
+const LOFT_FALL_SPEED: f32 = 3.0;
+
+pub const LADDER_WIDTH: f32 = 2.0;
+pub const LADDER_DEPTH: f32 = 0.3;
+pub const LADDER_HIGHT: f32 = LOFT_HIGHT;
+
+const LADDER_INTERACTION_DISTANCE: f32 = COLUMN_SIZE;
+const LADDER_INTERACTION_KEY: KeyCode = KeyCode::E;
+
+// Player hangs this far from the ladder
+const LADDER_CLIMB_GAP: f32 = 0.2;
+const LADDER_CLIMB_SPEED: f32 = 1.0;
+
+pub struct LadderPlugin;
+
+impl Plugin for LadderPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LadderPrompt>();
+
+        app.add_systems(
+            Update,
+            (ladder_interact, loft_edge, ladder_climb)
+                .chain()
+                .run_if(in_state(GlobalState::InGame)),
+        );
+        app.add_systems(OnExit(GlobalState::InGame), ladder_prompt_reset);
+    }
+}
+
+#[derive(Component)]
+pub struct Ladder;
+
+/// Player moving between the floor and a loft, either on a
+/// ladder or falling off the loft edge. Movement is off
+/// until the end of the path.
+#[derive(Component)]
+pub struct PlayerClimb {
+    // path has 3 parts of the same duration
+    path: [Vec3; 4],
+    progress: f32,
+    speed: f32,
+    up: bool,
+}
+
+impl PlayerClimb {
+    fn fall(from: Vec3, to: Vec3) -> Self {
+        Self {
+            path: [from, from.lerp(to, 1.0 / 3.0), from.lerp(to, 2.0 / 3.0), to],
+            progress: 0.0,
+            speed: LOFT_FALL_SPEED,
+            up: false,
+        }
+    }
+}
+
+/// Player standing on a loft. Player goes back
+/// to the `ground` height after leaving it.
+#[derive(Component)]
+pub struct PlayerOnLoft {
+    ground: f32,
+}
+
+// What the player sees when aiming at a ladder
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Resource)]
+pub enum LadderPrompt {
+    #[default]
+    None,
+    ClimbUp,
+    ClimbDown,
+}
+
+pub fn spawn_ladder(
+    level_resources: &LevelResources,
+    commands: &mut Commands,
+    transform: Transform,
+    direction: FloorDirection,
+) {
+    // loft fills the cell and stands on the floor
+    let mut loft_transform = transform;
+    loft_transform.translation.z = LOFT_HIGHT / 2.0;
+
+    commands.spawn((
+        LevelColliderBundle::new(
+            level_resources.loft_mesh.clone(),
+            level_resources.column_material.clone(),
+            loft_transform,
+            Collider::cuboid(COLUMN_SIZE / 2.0, COLUMN_SIZE / 2.0, LOFT_HIGHT / 2.0),
+        ),
+        SurfaceMaterial::Concrete,
+    ));
+
+    // ladder leans on the loft side facing the `direction`,
+    // rungs face away from the loft along the local y axis
+    let rotation = direction.rotation();
+    let mut ladder_transform = transform;
+    ladder_transform.rotation = rotation;
+    ladder_transform.translation += rotation * Vec3::Y * (COLUMN_SIZE + LADDER_DEPTH) / 2.0;
+    ladder_transform.translation.z = LADDER_HIGHT / 2.0;
+
+    commands.spawn((
+        LevelColliderBundle::new(
+            level_resources.ladder_mesh.clone(),
+            level_resources.ladder_material.clone(),
+            ladder_transform,
+            Collider::cuboid(LADDER_WIDTH / 2.0, LADDER_DEPTH / 2.0, LADDER_HIGHT / 2.0),
+        ),
+        Ladder,
+    ));
+}
+
+#[allow(clippy::complexity)]
+fn ladder_interact(
+    interact_input: InteractInput,
+    ladders: Query<&GlobalTransform, With<Ladder>>,
+    players: Query<
+        (
+            Entity,
+            &PlayerId,
+            &InteractionTarget,
+            &Transform,
+            Option<&PlayerOnLoft>,
+            Has<PlayerClimb>,
+        ),
+        (With<Player>, Without<PlayerVault>),
+    >,
+    mut ladder_prompt: ResMut<LadderPrompt>,
+    mut commands: Commands,
+) {
+    let pressed = interact_input.just_pressed(LADDER_INTERACTION_KEY);
+    for (player, player_id, interaction_target, player_transform, on_loft, climbing) in
+        players.iter()
+    {
+        let ladder = interaction_target
+            .get(LADDER_INTERACTION_DISTANCE)
+            .filter(|_| !climbing)
+            .and_then(|entity| ladders.get(entity).ok());
+
+        // prompt is only shown on the local screen
+        if player_id.is_local() {
+            ladder_prompt.set_if_neq(match (ladder, on_loft) {
+                (None, _) => LadderPrompt::None,
+                (Some(_), None) => LadderPrompt::ClimbUp,
+                (Some(_), Some(_)) => LadderPrompt::ClimbDown,
+            });
+        }
+
+        let Some(ladder_global_transform) = ladder else {
+            continue;
+        };
+        if !pressed {
+            continue;
+        }
+
+        let ladder_translation = ladder_global_transform.translation();
+        let side = ladder_global_transform.up();
+        let ground = on_loft
+            .map(|on_loft| on_loft.ground)
+            .unwrap_or(player_transform.translation.z);
+
+        let mut foot = ladder_translation
+            + side * (LADDER_DEPTH / 2.0 + PLAYER_COLLIDER_RADIUS + LADDER_CLIMB_GAP);
+        foot.z = ground;
+        let mut top = foot;
+        top.z = ground + LOFT_PLAYER_RISE;
+
+        let climb = match on_loft {
+            None => {
+                let mut loft = ladder_translation - side * (LADDER_DEPTH / 2.0 + LOFT_STEP);
+                loft.z = top.z;
+                PlayerClimb {
+                    path: [player_transform.translation, foot, top, loft],
+                    progress: 0.0,
+                    speed: LADDER_CLIMB_SPEED,
+                    up: true,
+                }
+            }
+            Some(_) => PlayerClimb {
+                path: [
+                    player_transform.translation,
+                    top,
+                    foot,
+                    foot + side * LOFT_STEP,
+                ],
+                progress: 0.0,
+                speed: LADDER_CLIMB_SPEED,
+                up: false,
+            },
+        };
+        commands.entity(player).insert(climb);
+    }
+}
+
+// Player walking off the loft falls down to the floor
+fn loft_edge(
+    rapier_context: Res<RapierContext>,
+    players: Query<
+        (Entity, &Collider, &Transform, &PlayerOnLoft),
+        (With<Player>, Without<PlayerVault>, Without<PlayerClimb>),
+    >,
+    mut commands: Commands,
+) {
+    for (player, collider, transform, on_loft) in players.iter() {
+        let filter = QueryFilter {
+            flags: QueryFilterFlags::EXCLUDE_SENSORS | QueryFilterFlags::EXCLUDE_DYNAMIC,
+            groups: Some(CollisionGroups::new(
+                COLLISION_GROUP_PLAYER,
+                COLLISION_GROUP_LEVEL,
+            )),
+            exclude_collider: Some(player),
+            ..default()
+        };
+        if rapier_context
+            .cast_shape(
+                transform.translation,
+                transform.rotation,
+                Vec3::NEG_Z,
+                collider,
+                LOFT_PLAYER_RISE,
+                true,
+                filter,
+            )
+            .is_some()
+        {
+            continue;
+        }
+
+        let mut ground = transform.translation;
+        ground.z = on_loft.ground;
+        commands
+            .entity(player)
+            .insert(PlayerClimb::fall(transform.translation, ground));
+    }
+}
+
+fn ladder_climb(
+    time: Res<Time>,
+    mut players: Query<(Entity, &mut PlayerClimb, &mut Transform), With<Player>>,
+    mut commands: Commands,
+) {
+    for (player, mut climb, mut transform) in players.iter_mut() {
+        climb.progress = (climb.progress + climb.speed * time.delta_seconds()).min(1.0);
+
+        let t = climb.progress * 3.0;
+        let part = (t as usize).min(2);
+        transform.translation = climb.path[part].lerp(climb.path[part + 1], t - part as f32);
+
+        if climb.progress == 1.0 {
+            let mut player = commands.entity(player);
+            player.remove::<PlayerClimb>();
+            if climb.up {
+                player.insert(PlayerOnLoft {
+                    ground: climb.path[0].z,
+                });
+            } else {
+                player.remove::<PlayerOnLoft>();
+            }
+        }
+    }
+}
+
+fn ladder_prompt_reset(mut ladder_prompt: ResMut<LadderPrompt>) {
+    ladder_prompt.set_if_neq(LadderPrompt::None);
+}
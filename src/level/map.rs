@@ -0,0 +1,56 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use super::{generation::cell_translation, COLUMN_SIZE, GRID_SIZE, LEVEL_SIZE};
+
+const MAP_WALL: char = '#';
+const MAP_EMPTY: char = '.';
+
+/// Text map of the level with one character per level cell.
+/// Walls are found by checking level colliders in the middle
+/// of each cell, markers are drawn on top of them.
+pub fn level_map(
+    rapier_context: &RapierContext,
+    level_colliders: impl Fn(Entity) -> bool,
+    level_translation: Vec3,
+    markers: impl IntoIterator<Item = (Vec3, char)>,
+) -> String {
+    let mut grid = [[MAP_EMPTY; GRID_SIZE]; GRID_SIZE];
+
+    let filter = QueryFilter {
+        flags: QueryFilterFlags::EXCLUDE_SENSORS,
+        predicate: Some(&level_colliders),
+        ..default()
+    };
+    for (y, row) in grid.iter_mut().enumerate() {
+        for (x, cell) in row.iter_mut().enumerate() {
+            let point = cell_translation(y, x, level_translation);
+            rapier_context.intersections_with_point(point, filter, |_| {
+                *cell = MAP_WALL;
+                false
+            });
+        }
+    }
+
+    for (translation, marker) in markers {
+        if let Some((y, x)) = map_cell(translation, level_translation) {
+            grid[y][x] = marker;
+        }
+    }
+
+    grid.iter()
+        .map(|row| row.iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// Inverse of `cell_translation`
+pub(super) fn map_cell(translation: Vec3, level_translation: Vec3) -> Option<(usize, usize)> {
+    let local = translation - level_translation;
+    let x = ((local.x + LEVEL_SIZE / 2.0) / COLUMN_SIZE).floor();
+    let y = ((LEVEL_SIZE / 2.0 - local.y) / COLUMN_SIZE).floor();
+    if x < 0.0 || y < 0.0 || GRID_SIZE as f32 <= x || GRID_SIZE as f32 <= y {
+        return None;
+    }
+    Some((y as usize, x as usize))
+}
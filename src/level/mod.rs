@@ -1,33 +1,65 @@
 use bevy::{
     core_pipeline::Skybox,
     prelude::*,
-    render::render_resource::{TextureViewDescriptor, TextureViewDimension},
+    render::{
+        mesh::{Indices, VertexAttributeValues},
+        render_resource::{PrimitiveTopology, TextureViewDescriptor, TextureViewDimension},
+    },
 };
 use bevy_asset_loader::prelude::*;
 use bevy_kira_audio::{Audio, AudioControl, AudioSource};
-use bevy_rapier3d::{prelude::*, rapier::geometry::CollisionEventFlags};
+use bevy_rapier3d::prelude::*;
 use rand::{
     distributions::{Distribution, Standard},
     Rng,
 };
 
 use crate::{
+    boss_rush::BossRush,
+    collision::CollisionLayer,
+    daily::DailyRun,
+    damage::Damage,
+    director::Director,
+    display::Quality,
     enemies::{Enemy, EnemyAssets},
+    impacts::SurfaceMaterial,
+    new_game_plus::NewGamePlus,
     player::{Player, PlayerResources},
+    profile::Profile,
+    rng::{GameRng, RngStream},
+    time_attack::TimeAttack,
     ui::UiResources,
     utils::remove_all_with,
-    weapons::{Projectile, WeaponAssets},
-    GlobalState, COLLISION_GROUP_ENEMY, COLLISION_GROUP_LEVEL, COLLISION_GROUP_PLAYER,
-    COLLISION_GROUP_PROJECTILES,
+    weapons::{Projectile, WeaponAssets, WeaponSpawnTable},
+    GlobalState,
 };
 
 use self::{
     door::Door,
-    generation::{spawn_level, spawn_level_sun},
+    freezer_chest::FreezerChest,
+    generation::{spawn_level, spawn_level_sun, LevelLayout},
 };
 
-mod door;
+mod atmosphere;
+pub mod blackout;
+pub mod crafting;
+pub mod defense;
+pub mod door;
+mod freezer_chest;
 mod generation;
+pub mod grade;
+mod hub;
+pub mod interaction;
+pub mod ladder;
+pub mod map;
+pub mod modifiers;
+mod moving_floor;
+mod pit;
+mod props;
+pub mod range;
+mod security;
+pub mod terminal;
+pub mod vending;
 
 const FLOOR_THICKNESS: f32 = 1.0;
 const LEVEL_SIZE: f32 = 200.0;
@@ -39,11 +71,52 @@ const FILL_AMOUNT: f32 = 0.02;
 const STRIP_LENGTH: u32 = 3;
 
 const LEVEL_WEAPON_SPAWNS: u32 = 4;
-const LEVEL_WEAPON_PISTOL_SPAWN_THRESHOLD: f64 = 0.3;
-const LEVEL_WEAPON_SHOTGUN_SPAWN_THRESHOLD: f64 = 0.6;
+// First level is the tutorial one before the
+// progress starts
+const LEVEL_INITIAL_GAME_PROGRESS: i32 = -10;
+// Boss rush starts and continues with the
+// shop break right before the boss level
+const LEVEL_BOSS_RUSH_GAME_PROGRESS: i32 = 90;
 
-const LEVEL_ENEMIES: u32 = 4;
-const LEVEL_SMALL_ENEMIES_PERCENT: f64 = 0.5;
+// Enemies patrol in a loop around their
+// cell until they notice the player
+const LEVEL_PATROL_RADIUS: usize = 3;
+
+const LEVEL_VENDING_MACHINE_CHANCE: f64 = 0.5;
+const LEVEL_VENDING_MACHINE_JAMMED_CHANCE: f64 = 0.2;
+
+const LEVEL_TERMINAL_CHANCE: f64 = 0.3;
+
+const LEVEL_SECURITY_CAMERA_CHANCE: f64 = 0.3;
+
+// Pits only appear in deeper levels
+const LEVEL_PITS: u32 = 3;
+const LEVEL_PITS_MIN_DEPTH: i32 = 40;
+
+// Conveyor belts are straight lines of cells
+const LEVEL_CONVEYORS: u32 = 2;
+const LEVEL_CONVEYOR_LENGTH: usize = 4;
+const LEVEL_CONVEYORS_MIN_DEPTH: i32 = 10;
+
+// Trench of pits crossed by a moving platform
+const LEVEL_PLATFORM_CHANCE: f64 = 0.5;
+const LEVEL_PLATFORM_PITS: usize = 3;
+
+// Breakable crates scattered around the level
+const LEVEL_PROPS: u32 = 6;
+
+const LEVEL_CRAFTING_BENCH_CHANCE: f64 = 0.25;
+
+// Loft with a ladder up to it
+const LEVEL_LADDER_CHANCE: f64 = 0.4;
+
+// Freezer chest spawning small fridges
+const LEVEL_FREEZER_CHEST_CHANCE: f64 = 0.25;
+const LEVEL_FREEZER_CHEST_MIN_DEPTH: i32 = 30;
+
+// Malfunctioning exit appears only in deeper levels
+const LEVEL_DEFENSE_CHANCE: f64 = 0.2;
+const LEVEL_DEFENSE_MIN_DEPTH: i32 = 20;
 
 const LEVEL_LIGHTS_COVERAGE: f64 = 0.2;
 const LIGHT_SIZE: f32 = 1.0;
@@ -65,7 +138,24 @@ impl Plugin for LevelPlugin {
         app.add_event::<LevelFinished>();
         app.add_event::<LevelSwitch>();
 
+        app.add_plugins(atmosphere::AtmospherePlugin);
+        app.add_plugins(blackout::BlackoutPlugin);
+        app.add_plugins(crafting::CraftingPlugin);
+        app.add_plugins(defense::DefensePlugin);
         app.add_plugins(door::DoorPlugin);
+        app.add_plugins(freezer_chest::FreezerChestPlugin);
+        app.add_plugins(grade::GradePlugin);
+        app.add_plugins(interaction::InteractionPlugin);
+        app.add_plugins(ladder::LadderPlugin);
+        app.add_plugins(modifiers::LevelModifiersPlugin);
+        app.add_plugins(moving_floor::MovingFloorPlugin);
+        app.add_plugins(pit::PitPlugin);
+        app.add_plugins(props::PropsPlugin);
+        app.add_plugins(range::RangePlugin);
+        app.add_plugins(security::SecurityPlugin);
+        app.add_plugins(terminal::TerminalPlugin);
+        app.add_plugins(vending::VendingPlugin);
+        app.add_plugins(hub::HubPlugin);
 
         app.add_systems(
             OnTransition {
@@ -80,12 +170,21 @@ impl Plugin for LevelPlugin {
             (resume_physics, spawn_initial_level),
         );
 
+        // Level behind the main menu is only a backdrop. The run
+        // level is generated after the game mode is picked, so daily
+        // run, time attack and boss rush get their own levels.
         app.add_systems(
             OnTransition {
                 from: GlobalState::MainMenu,
                 to: GlobalState::InGame,
             },
-            start_in_game_music,
+            (
+                start_in_game_music,
+                remove_all_with::<LevelObject>,
+                remove_all_with::<Player>,
+                spawn_initial_level,
+            )
+                .chain(),
         );
 
         app.add_systems(
@@ -110,6 +209,21 @@ impl Plugin for LevelPlugin {
             },
             (resume_physics, resume_music),
         );
+
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::InGame,
+                to: GlobalState::PerkSelection,
+            },
+            stop_physics,
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::PerkSelection,
+                to: GlobalState::InGame,
+            },
+            resume_physics,
+        );
         app.add_systems(
             OnTransition {
                 from: GlobalState::Paused,
@@ -169,6 +283,20 @@ impl Plugin for LevelPlugin {
             },
             (remove_all_with::<LevelObject>, remove_all_with::<Player>),
         );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::GameWon,
+                to: GlobalState::InGame,
+            },
+            (
+                start_in_game_music,
+                resume_physics,
+                remove_all_with::<LevelObject>,
+                remove_all_with::<Player>,
+                spawn_initial_level,
+            )
+                .chain(),
+        );
 
         app.add_systems(
             Update,
@@ -181,6 +309,7 @@ impl Plugin for LevelPlugin {
             )
                 .run_if(in_state(GlobalState::InGame)),
         );
+        app.add_systems(Update, (level_lights_quality, level_combine_columns));
     }
 }
 
@@ -201,6 +330,18 @@ pub struct LevelAssets {
     pub in_game: Handle<AudioSource>,
     #[asset(path = "dragon_lair.wav")]
     pub dragon_lair: Handle<AudioSource>,
+
+    #[asset(path = "door/door_open.wav")]
+    pub door_open: Handle<AudioSource>,
+    #[asset(path = "door/door_close.wav")]
+    pub door_close: Handle<AudioSource>,
+    #[asset(path = "door/door_locked.wav")]
+    pub door_locked: Handle<AudioSource>,
+
+    #[asset(path = "footsteps/indoor.wav")]
+    pub footstep_indoor: Handle<AudioSource>,
+    #[asset(path = "footsteps/outdoor.wav")]
+    pub footstep_outdoor: Handle<AudioSource>,
 }
 
 #[derive(Resource)]
@@ -212,10 +353,49 @@ struct LevelResources {
     door_mesh: Handle<Mesh>,
     door_material: Handle<StandardMaterial>,
     door_light_mesh: Handle<Mesh>,
+    door_debris_mesh: Handle<Mesh>,
     door_closed_light_material: Handle<StandardMaterial>,
     door_open_light_material: Handle<StandardMaterial>,
     light_mesh: Handle<Mesh>,
     light_material: Handle<StandardMaterial>,
+    light_off_material: Handle<StandardMaterial>,
+    light_switch_mesh: Handle<Mesh>,
+    vending_machine_mesh: Handle<Mesh>,
+    vending_machine_ammo_material: Handle<StandardMaterial>,
+    vending_machine_health_material: Handle<StandardMaterial>,
+    terminal_mesh: Handle<Mesh>,
+    terminal_material: Handle<StandardMaterial>,
+    security_monitor_mesh: Handle<Mesh>,
+    security_screen_mesh: Handle<Mesh>,
+    security_camera_mesh: Handle<Mesh>,
+    security_material: Handle<StandardMaterial>,
+    pit_mesh: Handle<Mesh>,
+    pit_material: Handle<StandardMaterial>,
+    conveyor_mesh: Handle<Mesh>,
+    conveyor_material: Handle<StandardMaterial>,
+    conveyor_arrow_mesh: Handle<Mesh>,
+    conveyor_arrow_material: Handle<StandardMaterial>,
+    platform_mesh: Handle<Mesh>,
+    platform_material: Handle<StandardMaterial>,
+    prop_mesh: Handle<Mesh>,
+    prop_debris_mesh: Handle<Mesh>,
+    prop_material: Handle<StandardMaterial>,
+    range_target_mesh: Handle<Mesh>,
+    range_target_material: Handle<StandardMaterial>,
+    range_button_mesh: Handle<Mesh>,
+    range_button_material: Handle<StandardMaterial>,
+    crafting_bench_mesh: Handle<Mesh>,
+    crafting_bench_material: Handle<StandardMaterial>,
+    ladder_mesh: Handle<Mesh>,
+    ladder_material: Handle<StandardMaterial>,
+    loft_mesh: Handle<Mesh>,
+    freezer_chest_mesh: Handle<Mesh>,
+    freezer_chest_lid_mesh: Handle<Mesh>,
+    freezer_chest_material: Handle<StandardMaterial>,
+    health_bar_mesh: Handle<Mesh>,
+    health_bar_fill_mesh: Handle<Mesh>,
+    health_bar_background_material: Handle<StandardMaterial>,
+    health_bar_material: Handle<StandardMaterial>,
 }
 
 // This component needs to be attached to
@@ -225,7 +405,7 @@ struct LevelResources {
 #[derive(Component)]
 pub struct LevelObject;
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LevelColor {
     Pink,
     Orange,
@@ -271,19 +451,37 @@ impl Distribution<LevelColor> for Standard {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LevelType {
     Covered,
     Open(LevelColor),
 }
 
+impl LevelType {
+    // Covered levels are indoors
+    pub fn footstep_sound(&self, level_assets: &LevelAssets) -> Handle<AudioSource> {
+        match self {
+            LevelType::Covered => level_assets.footstep_indoor.clone(),
+            LevelType::Open(_) => level_assets.footstep_outdoor.clone(),
+        }
+    }
+}
+
 #[derive(Resource)]
 pub struct LevelInfo {
     pub finished: bool,
+    // Set when player breaks a door, so enemies
+    // of the next level do not wait for the player
+    pub enemies_alerted: bool,
     pub level_type: LevelType,
     pub game_progress: i32,
     pub translation: Vec3,
     pub old_level_objects: Vec<Entity>,
+    // Seed of the run, kept for crash reports
+    pub seed: u64,
+    // Boss levels have exits and are
+    // followed by shop breaks
+    pub boss_rush: bool,
 }
 
 #[derive(Event)]
@@ -300,6 +498,17 @@ pub struct LevelSwitch {
 #[derive(Component)]
 pub struct LevelCollider;
 
+// Columns of the level drawn as one mesh. Mesh is
+// created by `level_combine_columns` after spawn.
+#[derive(Component)]
+pub struct LevelColumns {
+    translations: Vec<Vec3>,
+}
+
+// Point light of the lamps on the ceiling
+#[derive(Component)]
+pub struct MountedLight;
+
 #[derive(Bundle)]
 pub struct LevelColliderBundle {
     pub pbr_bundle: PbrBundle,
@@ -317,10 +526,7 @@ impl Default for LevelColliderBundle {
         Self {
             pbr_bundle: PbrBundle::default(),
             collider: Collider::default(),
-            collision_groups: CollisionGroups::new(
-                COLLISION_GROUP_LEVEL,
-                COLLISION_GROUP_ENEMY | COLLISION_GROUP_PLAYER | COLLISION_GROUP_PROJECTILES,
-            ),
+            collision_groups: CollisionLayer::Level.groups(),
             active_collision_types: ActiveCollisionTypes::default()
                 | ActiveCollisionTypes::KINEMATIC_STATIC,
             rigid_body: RigidBody::Fixed,
@@ -349,6 +555,20 @@ impl LevelColliderBundle {
             ..default()
         }
     }
+
+    /// Collider without its own mesh, for geometry
+    /// drawn as a part of a combined mesh.
+    pub fn collider_only(transform: Transform, collider: Collider) -> Self {
+        Self {
+            pbr_bundle: PbrBundle {
+                transform,
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            collider,
+            ..default()
+        }
+    }
 }
 
 fn spawn_light(level_resources: &LevelResources, commands: &mut Commands, transform: Transform) {
@@ -363,18 +583,206 @@ fn spawn_light(level_resources: &LevelResources, commands: &mut Commands, transf
             LevelObject,
         ))
         .with_children(|builder| {
-            builder.spawn(PointLightBundle {
-                point_light: PointLight {
-                    intensity: 2000.0,
-                    range: 100.0,
+            builder.spawn((
+                PointLightBundle {
+                    point_light: PointLight {
+                        intensity: 2000.0,
+                        range: 100.0,
+                        ..default()
+                    },
+                    transform: Transform::from_translation(Vec3::new(0.0, 0.0, -1.5)),
                     ..default()
                 },
-                transform: Transform::from_translation(Vec3::new(0.0, 0.0, -1.5)),
-                ..default()
-            });
+                MountedLight,
+            ));
         });
 }
 
+/// Spawns colliders of the columns and a single
+/// entity for the combined mesh of all of them.
+fn spawn_columns(
+    level_resources: &LevelResources,
+    commands: &mut Commands,
+    level_translation: Vec3,
+    translations: Vec<Vec3>,
+) {
+    for (translation, collider) in column_colliders(&translations) {
+        commands.spawn((
+            LevelColliderBundle::collider_only(Transform::from_translation(translation), collider),
+            SurfaceMaterial::Metal,
+        ));
+    }
+
+    commands.spawn((
+        PbrBundle {
+            material: level_resources.column_material.clone(),
+            transform: Transform::from_translation(level_translation),
+            ..default()
+        },
+        LevelColumns {
+            translations: translations
+                .into_iter()
+                .map(|translation| translation - level_translation)
+                .collect(),
+        },
+        LevelObject,
+    ));
+}
+
+/// Colliders for columns with neighbouring columns merged
+/// into one long cuboid. Columns are merged into rows first,
+/// columns left alone are then merged vertically.
+fn column_colliders(translations: &[Vec3]) -> Vec<(Vec3, Collider)> {
+    let cell = |translation: &Vec3| {
+        (
+            (translation.x / COLUMN_SIZE).floor() as i32,
+            (translation.y / COLUMN_SIZE).floor() as i32,
+        )
+    };
+
+    let mut rows = translations.to_vec();
+    rows.sort_by_key(|translation| {
+        let (x, y) = cell(translation);
+        (y, x)
+    });
+
+    let mut colliders = vec![];
+    let mut singles = vec![];
+    let mut run_start = 0;
+    for (i, translation) in rows.iter().enumerate() {
+        let (x, y) = cell(translation);
+        let run_continues = rows.get(i + 1).is_some_and(|next| cell(next) == (x + 1, y));
+        if run_continues {
+            continue;
+        }
+
+        let length = i + 1 - run_start;
+        if length == 1 {
+            singles.push(*translation);
+        } else {
+            colliders.push((
+                (rows[run_start] + *translation) / 2.0,
+                Collider::cuboid(
+                    COLUMN_SIZE * length as f32 / 2.0,
+                    COLUMN_SIZE / 2.0,
+                    COLUMN_HIGHT / 2.0,
+                ),
+            ));
+        }
+        run_start = i + 1;
+    }
+
+    singles.sort_by_key(cell);
+    let mut run_start = 0;
+    for (i, translation) in singles.iter().enumerate() {
+        let (x, y) = cell(translation);
+        let run_continues = singles
+            .get(i + 1)
+            .is_some_and(|next| cell(next) == (x, y + 1));
+        if run_continues {
+            continue;
+        }
+
+        let length = i + 1 - run_start;
+        colliders.push((
+            (singles[run_start] + *translation) / 2.0,
+            Collider::cuboid(
+                COLUMN_SIZE / 2.0,
+                COLUMN_SIZE * length as f32 / 2.0,
+                COLUMN_HIGHT / 2.0,
+            ),
+        ));
+        run_start = i + 1;
+    }
+
+    colliders
+}
+
+/// Copies of the `mesh` moved by each of the `translations`
+/// and combined into one mesh.
+fn combine_meshes(mesh: &Mesh, translations: &[Vec3]) -> Option<Mesh> {
+    let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        return None;
+    };
+    let Some(VertexAttributeValues::Float32x3(normals)) = mesh.attribute(Mesh::ATTRIBUTE_NORMAL)
+    else {
+        return None;
+    };
+    let Some(VertexAttributeValues::Float32x2(uvs)) = mesh.attribute(Mesh::ATTRIBUTE_UV_0) else {
+        return None;
+    };
+    let Some(Indices::U32(indices)) = mesh.indices() else {
+        return None;
+    };
+
+    let mut combined_positions = Vec::with_capacity(positions.len() * translations.len());
+    let mut combined_normals = Vec::with_capacity(normals.len() * translations.len());
+    let mut combined_uvs = Vec::with_capacity(uvs.len() * translations.len());
+    let mut combined_indices = Vec::with_capacity(indices.len() * translations.len());
+    for translation in translations.iter() {
+        let offset = combined_positions.len() as u32;
+        combined_positions.extend(
+            positions
+                .iter()
+                .map(|position| (Vec3::from(*position) + *translation).to_array()),
+        );
+        combined_normals.extend_from_slice(normals);
+        combined_uvs.extend_from_slice(uvs);
+        combined_indices.extend(indices.iter().map(|index| index + offset));
+    }
+
+    let mut combined = Mesh::new(PrimitiveTopology::TriangleList);
+    combined.insert_attribute(Mesh::ATTRIBUTE_POSITION, combined_positions);
+    combined.insert_attribute(Mesh::ATTRIBUTE_NORMAL, combined_normals);
+    combined.insert_attribute(Mesh::ATTRIBUTE_UV_0, combined_uvs);
+    combined.set_indices(Some(Indices::U32(combined_indices)));
+    Some(combined)
+}
+
+// Hundreds of columns as separate entities
+// cost a draw call each
+fn level_combine_columns(
+    level_resources: Res<LevelResources>,
+    columns: Query<(Entity, &LevelColumns), Added<LevelColumns>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut commands: Commands,
+) {
+    for (entity, level_columns) in columns.iter() {
+        let combined = meshes
+            .get(&level_resources.column_mesh)
+            .and_then(|column_mesh| combine_meshes(column_mesh, &level_columns.translations));
+        let Some(combined) = combined else {
+            continue;
+        };
+        commands
+            .entity(entity)
+            .insert(meshes.add(combined))
+            .remove::<LevelColumns>();
+    }
+}
+
+// Lower quality turns off some of the lights,
+// lamp meshes stay so the level looks the same
+fn level_lights_quality(
+    quality: Res<Quality>,
+    mut lights: Query<(Entity, Ref<MountedLight>, &mut Visibility)>,
+) {
+    let stride = quality.preset.light_stride();
+    for (entity, light, mut visibility) in lights.iter_mut() {
+        if !quality.is_changed() && !light.is_added() {
+            continue;
+        }
+        let new_visibility = if entity.index() % stride == 0 {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        visibility.set_if_neq(new_visibility);
+    }
+}
+
 fn init_resources(
     level_assets: Res<LevelAssets>,
     mut commands: Commands,
@@ -392,6 +800,14 @@ fn init_resources(
     let door_material = materials.add(Color::DARK_GRAY.into());
 
     let door_light_mesh = meshes.add(shape::Box::new(2.5, 2.5, 1.0).into());
+    let door_debris_mesh = meshes.add(
+        shape::Box::new(
+            door::DOOR_DEBRIS_SIZE,
+            door::DOOR_DEBRIS_SIZE,
+            door::DOOR_DEBRIS_SIZE,
+        )
+        .into(),
+    );
     let door_open_light_material = materials.add(StandardMaterial {
         base_color: Color::GREEN,
         emissive: Color::GREEN,
@@ -409,6 +825,194 @@ fn init_resources(
         emissive: Color::WHITE,
         ..default()
     });
+    let light_off_material = materials.add(Color::DARK_GRAY.into());
+    let light_switch_mesh = meshes.add(
+        shape::Box::new(
+            blackout::LIGHT_SWITCH_WIDTH,
+            blackout::LIGHT_SWITCH_DEPTH,
+            blackout::LIGHT_SWITCH_HIGHT,
+        )
+        .into(),
+    );
+
+    let vending_machine_mesh = meshes.add(
+        shape::Box::new(
+            vending::VENDING_MACHINE_WIDTH,
+            vending::VENDING_MACHINE_DEPTH,
+            vending::VENDING_MACHINE_HIGHT,
+        )
+        .into(),
+    );
+    let vending_machine_ammo_material = materials.add(Color::GOLD.into());
+    let vending_machine_health_material = materials.add(Color::LIME_GREEN.into());
+
+    let terminal_mesh = meshes.add(
+        shape::Box::new(
+            terminal::TERMINAL_WIDTH,
+            terminal::TERMINAL_DEPTH,
+            terminal::TERMINAL_HIGHT,
+        )
+        .into(),
+    );
+    let terminal_material = materials.add(StandardMaterial {
+        base_color: Color::DARK_GRAY,
+        emissive: Color::SEA_GREEN,
+        ..default()
+    });
+
+    let crafting_bench_mesh = meshes.add(
+        shape::Box::new(
+            crafting::CRAFTING_BENCH_WIDTH,
+            crafting::CRAFTING_BENCH_DEPTH,
+            crafting::CRAFTING_BENCH_HIGHT,
+        )
+        .into(),
+    );
+    let crafting_bench_material = materials.add(StandardMaterial {
+        base_color: Color::DARK_GRAY,
+        emissive: Color::CYAN * 0.3,
+        ..default()
+    });
+
+    let ladder_mesh = meshes.add(
+        shape::Box::new(
+            ladder::LADDER_WIDTH,
+            ladder::LADDER_DEPTH,
+            ladder::LADDER_HIGHT,
+        )
+        .into(),
+    );
+    let ladder_material = materials.add(Color::DARK_GRAY.into());
+    let loft_mesh =
+        meshes.add(shape::Box::new(COLUMN_SIZE, COLUMN_SIZE, ladder::LOFT_HIGHT).into());
+
+    let freezer_chest_mesh = meshes.add(
+        shape::Box::new(
+            freezer_chest::FREEZER_CHEST_WIDTH,
+            freezer_chest::FREEZER_CHEST_DEPTH,
+            freezer_chest::FREEZER_CHEST_HIGHT,
+        )
+        .into(),
+    );
+    let freezer_chest_lid_mesh = meshes.add(
+        shape::Box::new(
+            freezer_chest::FREEZER_CHEST_WIDTH,
+            freezer_chest::FREEZER_CHEST_DEPTH,
+            freezer_chest::FREEZER_CHEST_LID_THICKNESS,
+        )
+        .into(),
+    );
+    let freezer_chest_material = materials.add(StandardMaterial {
+        base_color: Color::ALICE_BLUE,
+        emissive: Color::CYAN * 0.1,
+        ..default()
+    });
+
+    let health_bar_mesh = meshes.add(
+        shape::Box::new(
+            freezer_chest::HEALTH_BAR_WIDTH,
+            freezer_chest::HEALTH_BAR_DEPTH,
+            freezer_chest::HEALTH_BAR_HIGHT,
+        )
+        .into(),
+    );
+    // fill sticks out of the background on both sides
+    let health_bar_fill_mesh = meshes.add(
+        shape::Box::new(
+            freezer_chest::HEALTH_BAR_WIDTH,
+            freezer_chest::HEALTH_BAR_DEPTH * 2.0,
+            freezer_chest::HEALTH_BAR_HIGHT * 0.8,
+        )
+        .into(),
+    );
+    let health_bar_background_material = materials.add(StandardMaterial {
+        base_color: Color::BLACK,
+        unlit: true,
+        ..default()
+    });
+    let health_bar_material = materials.add(StandardMaterial {
+        base_color: Color::RED,
+        unlit: true,
+        ..default()
+    });
+
+    let security_monitor_mesh = meshes.add(
+        shape::Box::new(
+            security::SECURITY_MONITOR_WIDTH,
+            security::SECURITY_MONITOR_DEPTH,
+            security::SECURITY_MONITOR_HIGHT,
+        )
+        .into(),
+    );
+    let security_screen_mesh = meshes.add(
+        shape::Quad::new(Vec2::new(
+            security::SECURITY_SCREEN_WIDTH,
+            security::SECURITY_SCREEN_HIGHT,
+        ))
+        .into(),
+    );
+    let security_camera_mesh = meshes.add(shape::Cube::new(security::SECURITY_CAMERA_SIZE).into());
+    let security_material = materials.add(Color::DARK_GRAY.into());
+
+    let pit_mesh = meshes.add(shape::Quad::new(Vec2::splat(pit::PIT_SIZE)).into());
+    let pit_material = materials.add(StandardMaterial {
+        base_color: Color::BLACK,
+        unlit: true,
+        ..default()
+    });
+
+    let conveyor_mesh = meshes
+        .add(shape::Box::new(COLUMN_SIZE, COLUMN_SIZE, moving_floor::CONVEYOR_THICKNESS).into());
+    let conveyor_material = materials.add(Color::DARK_GRAY.into());
+    let conveyor_arrow_mesh = meshes.add(
+        shape::Quad::new(Vec2::new(
+            moving_floor::CONVEYOR_ARROW_WIDTH,
+            moving_floor::CONVEYOR_ARROW_DEPTH,
+        ))
+        .into(),
+    );
+    let conveyor_arrow_material = materials.add(StandardMaterial {
+        base_color: Color::YELLOW,
+        emissive: Color::YELLOW,
+        ..default()
+    });
+    let platform_mesh = meshes.add(
+        shape::Box::new(
+            moving_floor::PLATFORM_SIZE,
+            moving_floor::PLATFORM_SIZE,
+            moving_floor::PLATFORM_THICKNESS,
+        )
+        .into(),
+    );
+    let platform_material = materials.add(Color::ORANGE.into());
+
+    let prop_mesh =
+        meshes.add(shape::Box::new(props::PROP_SIZE, props::PROP_SIZE, props::PROP_HIGHT).into());
+    let prop_debris_mesh = meshes.add(shape::Cube::new(props::PROP_DEBRIS_SIZE).into());
+    let prop_material = materials.add(Color::BEIGE.into());
+
+    let range_target_mesh = meshes.add(
+        shape::Box::new(
+            range::RANGE_TARGET_WIDTH,
+            range::RANGE_TARGET_DEPTH,
+            range::RANGE_TARGET_HIGHT,
+        )
+        .into(),
+    );
+    let range_target_material = materials.add(Color::ANTIQUE_WHITE.into());
+    let range_button_mesh = meshes.add(
+        shape::Box::new(
+            range::RANGE_BUTTON_SIZE,
+            range::RANGE_BUTTON_SIZE,
+            range::RANGE_BUTTON_HIGHT,
+        )
+        .into(),
+    );
+    let range_button_material = materials.add(StandardMaterial {
+        base_color: Color::RED,
+        emissive: Color::RED,
+        ..default()
+    });
 
     for handle in [
         &level_assets.pink_skybox,
@@ -433,10 +1037,49 @@ fn init_resources(
         door_mesh,
         door_material,
         door_light_mesh,
+        door_debris_mesh,
         door_open_light_material,
         door_closed_light_material,
         light_mesh,
         light_material,
+        light_off_material,
+        light_switch_mesh,
+        vending_machine_mesh,
+        vending_machine_ammo_material,
+        vending_machine_health_material,
+        terminal_mesh,
+        terminal_material,
+        security_monitor_mesh,
+        security_screen_mesh,
+        security_camera_mesh,
+        security_material,
+        pit_mesh,
+        pit_material,
+        conveyor_mesh,
+        conveyor_material,
+        conveyor_arrow_mesh,
+        conveyor_arrow_material,
+        platform_mesh,
+        platform_material,
+        prop_mesh,
+        prop_debris_mesh,
+        prop_material,
+        range_target_mesh,
+        range_target_material,
+        range_button_mesh,
+        range_button_material,
+        crafting_bench_mesh,
+        crafting_bench_material,
+        ladder_mesh,
+        ladder_material,
+        loft_mesh,
+        freezer_chest_mesh,
+        freezer_chest_lid_mesh,
+        freezer_chest_material,
+        health_bar_mesh,
+        health_bar_fill_mesh,
+        health_bar_background_material,
+        health_bar_material,
     });
 }
 
@@ -474,15 +1117,45 @@ fn continues_music(audio: Res<Audio>, level_assets: Res<LevelAssets>, level_info
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn spawn_initial_level(
+    boss_rush: Res<BossRush>,
+    daily_run: Res<DailyRun>,
+    time_attack: Res<TimeAttack>,
+    director: Res<Director>,
+    new_game_plus: Res<NewGamePlus>,
+    profile: Res<Profile>,
+    weapon_spawn_table: Res<WeaponSpawnTable>,
     ui_resources: Res<UiResources>,
     level_assets: Res<LevelAssets>,
     enemy_assets: Res<EnemyAssets>,
     weapon_assets: Res<WeaponAssets>,
     level_resources: Res<LevelResources>,
     player_resources: Res<PlayerResources>,
+    mut game_rng: ResMut<GameRng>,
     mut commands: Commands,
 ) {
+    let seed = if daily_run.active {
+        daily_run.seed
+    } else if time_attack.active {
+        time_attack.seed
+    } else {
+        rand::random()
+    };
+    game_rng.reseed(seed);
+
+    // boss rush skips the tutorial, weapons
+    // are picked up in the shop break
+    let (tutorial_weapon, layout, game_progress) = if boss_rush.active {
+        (None, LevelLayout::Shop, LEVEL_BOSS_RUSH_GAME_PROGRESS)
+    } else {
+        (
+            Some((new_game_plus.weapon, new_game_plus.weapon_roll)),
+            LevelLayout::Normal,
+            LEVEL_INITIAL_GAME_PROGRESS,
+        )
+    };
+
     spawn_level(
         ui_resources.as_ref(),
         level_assets.as_ref(),
@@ -490,25 +1163,35 @@ fn spawn_initial_level(
         weapon_assets.as_ref(),
         level_resources.as_ref(),
         player_resources.as_ref(),
+        director.as_ref(),
+        profile.as_ref(),
+        weapon_spawn_table.as_ref(),
+        game_rng.as_mut(),
         &mut commands,
         Vec3::ZERO,
         None,
         LevelType::Covered,
-        true,
-        false,
+        tutorial_weapon,
+        layout,
+        game_progress,
     );
 
     commands.insert_resource(LevelInfo {
         finished: false,
+        enemies_alerted: false,
         level_type: LevelType::Covered,
-        game_progress: -10,
+        game_progress,
         translation: Vec3::ZERO,
         old_level_objects: vec![],
+        seed,
+        boss_rush: boss_rush.active,
     });
 }
 
 fn level_progress(
     enemies: Query<Entity, With<Enemy>>,
+    freezer_chests: Query<(), With<FreezerChest>>,
+    mut boss_rush: ResMut<BossRush>,
     mut level_info: ResMut<LevelInfo>,
     mut level_started_events: EventReader<LevelStarted>,
     mut level_finished_events: EventWriter<LevelFinished>,
@@ -516,25 +1199,37 @@ fn level_progress(
 ) {
     for _ in level_started_events.read() {
         level_info.finished = false;
+        level_info.enemies_alerted = false;
     }
 
-    let remaining_enemies = enemies.iter().count();
+    // freezer chests keep spawning minions, so they
+    // have to be destroyed as well
+    let remaining_enemies = enemies.iter().count() + freezer_chests.iter().count();
     if remaining_enemies == 0 && !level_info.finished {
         level_info.finished = true;
         level_info.game_progress += 10;
 
         // if boss level is finished
         if 100 < level_info.game_progress {
-            global_state.set(GlobalState::GameWon);
-            return;
+            // boss rush continues with the next shop break
+            if !boss_rush.active {
+                global_state.set(GlobalState::GameWon);
+                return;
+            }
+            boss_rush.arenas_cleared += 1;
+            level_info.game_progress = LEVEL_BOSS_RUSH_GAME_PROGRESS;
         }
 
         level_finished_events.send(LevelFinished);
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn level_switch(
     audio: Res<Audio>,
+    director: Res<Director>,
+    profile: Res<Profile>,
+    weapon_spawn_table: Res<WeaponSpawnTable>,
     ui_resources: Res<UiResources>,
     level_assets: Res<LevelAssets>,
     enemy_assets: Res<EnemyAssets>,
@@ -544,6 +1239,7 @@ fn level_switch(
     level_objects: Query<Entity, With<LevelObject>>,
     mut skybox: Query<&mut Skybox>,
     mut level_info: ResMut<LevelInfo>,
+    mut game_rng: ResMut<GameRng>,
     mut commands: Commands,
     mut level_switch_events: EventReader<LevelSwitch>,
 ) {
@@ -565,10 +1261,11 @@ fn level_switch(
             match level_info.level_type {
                 LevelType::Open(_) => LevelType::Covered,
                 LevelType::Covered => {
-                    if rand::thread_rng().gen_ratio(1, 3) {
+                    let rng = game_rng.stream(RngStream::LevelColors);
+                    if rng.gen_ratio(1, 3) {
                         LevelType::Covered
                     } else {
-                        let level_color = rand::random::<LevelColor>();
+                        let level_color = rng.gen::<LevelColor>();
                         LevelType::Open(level_color)
                     }
                 }
@@ -585,6 +1282,13 @@ fn level_switch(
             }
         }
 
+        let layout = match (boss_level, pre_boss_level, level_info.boss_rush) {
+            (true, _, false) => LevelLayout::Boss,
+            (true, _, true) => LevelLayout::BossArena,
+            (_, true, true) => LevelLayout::Shop,
+            _ => LevelLayout::Normal,
+        };
+
         let new_translation = spawn_level(
             ui_resources.as_ref(),
             level_assets.as_ref(),
@@ -592,12 +1296,17 @@ fn level_switch(
             weapon_assets.as_ref(),
             level_resources.as_ref(),
             player_resources.as_ref(),
+            director.as_ref(),
+            profile.as_ref(),
+            weapon_spawn_table.as_ref(),
+            game_rng.as_mut(),
             &mut commands,
             level_info.translation,
             Some(event.exit_door),
             new_level_type,
-            false,
-            boss_level,
+            None,
+            layout,
+            level_info.game_progress,
         );
 
         level_info.level_type = new_level_type;
@@ -622,36 +1331,42 @@ fn level_delete_old(
 }
 
 fn collision_level_object_projectiles(
-    projectiles: Query<Entity, With<Projectile>>,
     level_objects: Query<Entity, With<LevelCollider>>,
+    mut projectiles: Query<(&mut Projectile, &Velocity, Option<&mut Damage>)>,
     mut commands: Commands,
     mut collision_events: EventReader<CollisionEvent>,
 ) {
     for collision_event in collision_events.read() {
-        let (collider_1, collider_2, flags) = match collision_event {
-            CollisionEvent::Started(c1, c2, f) => (c1, c2, f),
-            CollisionEvent::Stopped(c1, c2, f) => (c1, c2, f),
+        // bouncing projectiles also stop touching
+        // the level, which is not a new bounce
+        let CollisionEvent::Started(collider_1, collider_2, _) = collision_event else {
+            continue;
         };
-        if flags.contains(CollisionEventFlags::REMOVED) {
-            return;
-        }
 
-        let projectile = if let Ok(p) = projectiles.get(*collider_1) {
-            if level_objects.get(*collider_2).is_ok() {
-                p
+        let projectile_entity =
+            if projectiles.contains(*collider_1) && level_objects.contains(*collider_2) {
+                *collider_1
+            } else if projectiles.contains(*collider_2) && level_objects.contains(*collider_1) {
+                *collider_2
             } else {
                 continue;
-            }
-        } else if let Ok(p) = projectiles.get(*collider_2) {
-            if level_objects.get(*collider_1).is_ok() {
-                p
-            } else {
-                continue;
-            }
-        } else {
+            };
+        let Ok((mut projectile, velocity, damage)) = projectiles.get_mut(projectile_entity) else {
             continue;
         };
-        let Some(e) = commands.get_entity(projectile) else {
+
+        if 0 < projectile.bounces {
+            projectile.bounces -= 1;
+            // velocity is already reflected by the physics
+            projectile.direction = velocity.linvel.normalize_or_zero();
+            if let Some(mut damage) = damage {
+                damage.damage =
+                    (damage.damage as f32 * (1.0 - projectile.bounce_damage_decay)) as i32;
+            }
+            continue;
+        }
+
+        let Some(e) = commands.get_entity(projectile_entity) else {
             continue;
         };
         e.despawn_recursive();
@@ -0,0 +1,152 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::{
+    distributions::{Distribution, Standard},
+    Rng,
+};
+
+use crate::{
+    rng::{GameRng, RngStream},
+    run_modifiers::RunModifiers,
+    GlobalState, GRAVITY,
+};
+
+use super::{LevelInfo, LevelSwitch};
+
+const LEVEL_MODIFIER_CHANCE: f64 = 0.25;
+// Only shells, gibs and other debris feel the gravity
+const LEVEL_MODIFIER_LOW_GRAVITY_MODIFIER: f32 = 0.3;
+const LEVEL_MODIFIER_BOUNCY_RESTITUTION: f32 = 0.9;
+const LEVEL_MODIFIER_ICY_SLOW_DOWN_MODIFIER: f32 = 0.2;
+
+pub struct LevelModifiersPlugin;
+
+impl Plugin for LevelModifiersPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LevelModifiers>();
+
+        app.add_systems(OnEnter(GlobalState::MainMenu), reset_level_modifiers);
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::GameOver,
+                to: GlobalState::InGame,
+            },
+            reset_level_modifiers,
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::GameWon,
+                to: GlobalState::InGame,
+            },
+            reset_level_modifiers,
+        );
+
+        app.add_systems(OnEnter(GlobalState::InGame), apply_gravity);
+        app.add_systems(
+            Update,
+            (
+                select_level_modifier.run_if(in_state(GlobalState::InGame)),
+                apply_gravity.run_if(resource_changed::<LevelModifiers>()),
+                apply_floor_restitution,
+            ),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LevelModifier {
+    LowGravity,
+    BouncyFloors,
+    IcyFloors,
+    // Most lamps are off until switched on
+    Blackout,
+}
+
+impl Distribution<LevelModifier> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> LevelModifier {
+        match rng.gen_range(0..4) {
+            0 => LevelModifier::LowGravity,
+            1 => LevelModifier::BouncyFloors,
+            2 => LevelModifier::IcyFloors,
+            3 => LevelModifier::Blackout,
+            _ => unreachable!(),
+        }
+    }
+}
+
+// Modifier of the current level. A new one is
+// selected on each level switch, so it only lasts one level.
+#[derive(Debug, Default, Resource)]
+pub struct LevelModifiers {
+    pub modifier: Option<LevelModifier>,
+}
+
+impl LevelModifiers {
+    pub fn gravity_modifier(&self) -> f32 {
+        match self.modifier {
+            Some(LevelModifier::LowGravity) => LEVEL_MODIFIER_LOW_GRAVITY_MODIFIER,
+            _ => 1.0,
+        }
+    }
+
+    pub fn slow_down_modifier(&self) -> f32 {
+        match self.modifier {
+            Some(LevelModifier::IcyFloors) => LEVEL_MODIFIER_ICY_SLOW_DOWN_MODIFIER,
+            _ => 1.0,
+        }
+    }
+}
+
+// Floor of the level
+#[derive(Component)]
+pub struct LevelFloor;
+
+fn reset_level_modifiers(mut level_modifiers: ResMut<LevelModifiers>) {
+    if level_modifiers.modifier.is_some() {
+        level_modifiers.modifier = None;
+    }
+}
+
+fn select_level_modifier(
+    level_info: Res<LevelInfo>,
+    mut game_rng: ResMut<GameRng>,
+    mut level_modifiers: ResMut<LevelModifiers>,
+    mut level_switch_events: EventReader<LevelSwitch>,
+) {
+    for _ in level_switch_events.read() {
+        // boss level is the same every time
+        let boss_level = level_info.game_progress == 100;
+        let rng = game_rng.stream(RngStream::LevelModifiers);
+        level_modifiers.modifier = (!boss_level && rng.gen_bool(LEVEL_MODIFIER_CHANCE))
+            .then(|| rng.gen::<LevelModifier>());
+    }
+}
+
+fn apply_gravity(
+    run_modifiers: Res<RunModifiers>,
+    level_modifiers: Res<LevelModifiers>,
+    mut physics: ResMut<RapierConfiguration>,
+) {
+    physics.gravity = Vec3::NEG_Z
+        * GRAVITY
+        * run_modifiers.gravity_modifier()
+        * level_modifiers.gravity_modifier();
+}
+
+// Floors spawned for the next level get the
+// restitution of the modifier selected for it
+fn apply_floor_restitution(
+    level_modifiers: Res<LevelModifiers>,
+    floors: Query<Entity, Added<LevelFloor>>,
+    mut commands: Commands,
+) {
+    if level_modifiers.modifier != Some(LevelModifier::BouncyFloors) {
+        return;
+    }
+    for floor in floors.iter() {
+        commands.entity(floor).insert(Restitution {
+            coefficient: LEVEL_MODIFIER_BOUNCY_RESTITUTION,
+            combine_rule: CoefficientCombineRule::Max,
+        });
+    }
+}
@@ -0,0 +1,296 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::{
+    distributions::{Distribution, Standard},
+    Rng,
+};
+
+use crate::{
+    collision::{CollisionLayer, COLLISION_GROUP_DEBRIS},
+    enemies::Enemy,
+    player::{Player, PlayerVelocity},
+    GlobalState,
+};
+
+use super::{LevelObject, LevelResources, COLUMN_HIGHT, COLUMN_SIZE, FLOOR_THICKNESS};
+
+pub const CONVEYOR_THICKNESS: f32 = 0.2;
+pub const CONVEYOR_ARROW_WIDTH: f32 = COLUMN_SIZE * 0.6;
+pub const CONVEYOR_ARROW_DEPTH: f32 = 0.4;
+const CONVEYOR_SPEED: f32 = 12.0;
+
+pub const PLATFORM_SIZE: f32 = COLUMN_SIZE * 0.9;
+pub const PLATFORM_THICKNESS: f32 = 0.5;
+const PLATFORM_SPEED: f32 = 8.0;
+// Pause at each end of the trench
+const PLATFORM_WAIT_SECONDS: f32 = 1.5;
+
+// Debris higher than this above the belt is still in the air
+const MOVING_FLOOR_DEBRIS_HEIGHT: f32 = 1.0;
+
+/// Conveyor belts and moving platforms carrying
+/// everything standing on them
+pub struct MovingFloorPlugin;
+
+impl Plugin for MovingFloorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, platform_move.run_if(in_state(GlobalState::InGame)));
+        // character controllers take the movement of
+        // the floor together with their own movement
+        app.add_systems(
+            PostUpdate,
+            moving_floor_carry
+                .before(PhysicsSet::SyncBackend)
+                .run_if(in_state(GlobalState::InGame)),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FloorDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Distribution<FloorDirection> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> FloorDirection {
+        match rng.gen_range(0..4) {
+            0 => FloorDirection::Up,
+            1 => FloorDirection::Down,
+            2 => FloorDirection::Left,
+            _ => FloorDirection::Right,
+        }
+    }
+}
+
+impl FloorDirection {
+    /// Step to the next cell in (row, column) of the level grid
+    pub fn grid_step(self) -> (i32, i32) {
+        match self {
+            FloorDirection::Up => (-1, 0),
+            FloorDirection::Down => (1, 0),
+            FloorDirection::Left => (0, -1),
+            FloorDirection::Right => (0, 1),
+        }
+    }
+
+    pub fn rotation(self) -> Quat {
+        let angle = match self {
+            FloorDirection::Up => 0.0,
+            FloorDirection::Left => std::f32::consts::FRAC_PI_2,
+            FloorDirection::Down => std::f32::consts::PI,
+            FloorDirection::Right => -std::f32::consts::FRAC_PI_2,
+        };
+        Quat::from_rotation_z(angle)
+    }
+}
+
+// Floor moving everything standing
+// on it with the `velocity`
+#[derive(Component)]
+pub struct MovingFloor {
+    half_size: f32,
+    velocity: Vec3,
+}
+
+// Platform goes back and forth between the
+// `start` and the `end` over the pits between them
+#[derive(Component)]
+struct MovingPlatform {
+    start: Vec3,
+    end: Vec3,
+    progress: f32,
+    forward: bool,
+    wait_timer: Timer,
+}
+
+// Entity standing on a moving platform.
+// Pits under the platform do not catch it.
+#[derive(Component)]
+pub struct PlatformRider;
+
+pub fn spawn_conveyor(
+    level_resources: &LevelResources,
+    commands: &mut Commands,
+    mut transform: Transform,
+    direction: FloorDirection,
+) {
+    // belt lies on the floor
+    transform.translation.z += -COLUMN_HIGHT / 2.0 + FLOOR_THICKNESS / 2.0;
+    transform.rotation = direction.rotation();
+
+    commands
+        .spawn((
+            PbrBundle {
+                mesh: level_resources.conveyor_mesh.clone(),
+                material: level_resources.conveyor_material.clone(),
+                transform,
+                ..default()
+            },
+            MovingFloor {
+                half_size: COLUMN_SIZE / 2.0,
+                velocity: transform.rotation * Vec3::Y * CONVEYOR_SPEED,
+            },
+            LevelObject,
+        ))
+        .with_children(|builder| {
+            // arrow at the front edge of the belt
+            builder.spawn(PbrBundle {
+                mesh: level_resources.conveyor_arrow_mesh.clone(),
+                material: level_resources.conveyor_arrow_material.clone(),
+                transform: Transform::from_xyz(
+                    0.0,
+                    COLUMN_SIZE / 2.0 - CONVEYOR_ARROW_DEPTH,
+                    CONVEYOR_THICKNESS / 2.0 + 0.01,
+                ),
+                ..default()
+            });
+        });
+}
+
+pub fn spawn_platform(
+    level_resources: &LevelResources,
+    commands: &mut Commands,
+    mut transform: Transform,
+    mut end: Vec3,
+) {
+    // platform lies on the floor
+    transform.translation.z += -COLUMN_HIGHT / 2.0 + FLOOR_THICKNESS / 2.0;
+    end.z = transform.translation.z;
+
+    commands.spawn((
+        PbrBundle {
+            mesh: level_resources.platform_mesh.clone(),
+            material: level_resources.platform_material.clone(),
+            transform,
+            ..default()
+        },
+        RigidBody::KinematicPositionBased,
+        Collider::cuboid(
+            PLATFORM_SIZE / 2.0,
+            PLATFORM_SIZE / 2.0,
+            PLATFORM_THICKNESS / 2.0,
+        ),
+        CollisionLayer::MovingFloor.groups(),
+        MovingFloor {
+            half_size: PLATFORM_SIZE / 2.0,
+            velocity: Vec3::ZERO,
+        },
+        MovingPlatform {
+            start: transform.translation,
+            end,
+            progress: 0.0,
+            forward: true,
+            wait_timer: Timer::from_seconds(PLATFORM_WAIT_SECONDS, TimerMode::Once),
+        },
+        LevelObject,
+    ));
+}
+
+// Floor under the `translation` and whether it is a platform
+fn floor_under(
+    floors: &Query<(&Transform, &MovingFloor, Has<MovingPlatform>)>,
+    translation: Vec3,
+) -> Option<(Vec3, f32, bool)> {
+    floors
+        .iter()
+        .find(|(transform, floor, _)| {
+            let distance = (translation.xy() - transform.translation.xy()).abs();
+            distance.x < floor.half_size && distance.y < floor.half_size
+        })
+        .map(|(transform, floor, platform)| (floor.velocity, transform.translation.z, platform))
+}
+
+fn update_rider(commands: &mut Commands, entity: Entity, riding: bool, was_riding: bool) {
+    if riding && !was_riding {
+        commands.entity(entity).insert(PlatformRider);
+    } else if !riding && was_riding {
+        commands.entity(entity).remove::<PlatformRider>();
+    }
+}
+
+fn platform_move(
+    time: Res<Time>,
+    mut platforms: Query<(&mut Transform, &mut MovingFloor, &mut MovingPlatform)>,
+) {
+    for (mut transform, mut floor, mut platform) in platforms.iter_mut() {
+        floor.velocity = Vec3::ZERO;
+
+        if !platform.wait_timer.finished() {
+            platform.wait_timer.tick(time.delta());
+            continue;
+        }
+
+        let path = platform.end - platform.start;
+        let step = PLATFORM_SPEED * time.delta_seconds() / path.length();
+        if platform.forward {
+            platform.progress = (platform.progress + step).min(1.0);
+            floor.velocity = path.normalize() * PLATFORM_SPEED;
+        } else {
+            platform.progress = (platform.progress - step).max(0.0);
+            floor.velocity = -path.normalize() * PLATFORM_SPEED;
+        }
+        if platform.progress == 0.0 || platform.progress == 1.0 {
+            platform.forward = !platform.forward;
+            platform.wait_timer.reset();
+        }
+
+        transform.translation = platform.start.lerp(platform.end, platform.progress);
+    }
+}
+
+#[allow(clippy::complexity)]
+fn moving_floor_carry(
+    time: Res<Time>,
+    floors: Query<(&Transform, &MovingFloor, Has<MovingPlatform>)>,
+    mut players: Query<(Entity, &Transform, &mut PlayerVelocity, Has<PlatformRider>), With<Player>>,
+    mut enemies: Query<
+        (
+            Entity,
+            &Transform,
+            &mut KinematicCharacterController,
+            Has<PlatformRider>,
+        ),
+        With<Enemy>,
+    >,
+    mut debris: Query<(&Transform, &CollisionGroups, &mut Velocity)>,
+    mut commands: Commands,
+) {
+    for (player, transform, mut velocity, was_riding) in players.iter_mut() {
+        let floor = floor_under(&floors, transform.translation);
+        // player movement adds the floor velocity on the next update
+        velocity.floor = floor.map_or(Vec3::ZERO, |(velocity, _, _)| velocity);
+        let riding = floor.map_or(false, |(_, _, platform)| platform);
+        update_rider(&mut commands, player, riding, was_riding);
+    }
+
+    for (enemy, transform, mut controller, was_riding) in enemies.iter_mut() {
+        let floor = floor_under(&floors, transform.translation);
+        if let Some((velocity, _, _)) = floor {
+            let movement = velocity * time.delta_seconds();
+            controller.translation = Some(controller.translation.unwrap_or_default() + movement);
+        }
+        let riding = floor.map_or(false, |(_, _, platform)| platform);
+        update_rider(&mut commands, enemy, riding, was_riding);
+    }
+
+    // shells, gibs and other debris lying on the floor
+    for (transform, collision_groups, mut velocity) in debris.iter_mut() {
+        if !collision_groups
+            .memberships
+            .contains(COLLISION_GROUP_DEBRIS)
+        {
+            continue;
+        }
+        let Some((floor_velocity, floor_z, _)) = floor_under(&floors, transform.translation) else {
+            continue;
+        };
+        if MOVING_FLOOR_DEBRIS_HEIGHT < transform.translation.z - floor_z {
+            continue;
+        }
+        velocity.linvel.x = floor_velocity.x;
+        velocity.linvel.y = floor_velocity.y;
+    }
+}
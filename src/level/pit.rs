@@ -0,0 +1,187 @@
+use bevy::prelude::*;
+use bevy_rapier3d::{prelude::*, rapier::geometry::CollisionEventFlags};
+
+use crate::{
+    collision::{CollisionLayer, COLLISION_GROUP_LEVEL, COLLISION_GROUP_PLAYER},
+    damage::{ContactDamageEvent, Health, KillCause, KillEvent},
+    enemies::Enemy,
+    player::{Player, PlayerVelocity},
+    GlobalState,
+};
+
+use super::{
+    generation::cell_translation, map::map_cell, moving_floor::PlatformRider, LevelInfo,
+    LevelObject, LevelResources, COLUMN_HIGHT, COLUMN_SIZE, FLOOR_THICKNESS, GRID_SIZE,
+};
+
+pub const PIT_SIZE: f32 = COLUMN_SIZE * 0.9;
+
+const PIT_PLAYER_DAMAGE: i32 = 100;
+
+pub struct PitPlugin;
+
+impl Plugin for PitPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, pit_fall.run_if(in_state(GlobalState::InGame)));
+    }
+}
+
+// Kill volume over the pit cell. There is no jumping,
+// so walking over the pit is falling into it.
+#[derive(Component)]
+pub struct Pit;
+
+pub fn spawn_pit(level_resources: &LevelResources, commands: &mut Commands, transform: Transform) {
+    // hole is drawn just above the floor
+    let mut hole_transform = transform;
+    hole_transform.translation.z += -COLUMN_HIGHT / 2.0 + FLOOR_THICKNESS / 2.0 + 0.01;
+    commands.spawn((
+        PbrBundle {
+            mesh: level_resources.pit_mesh.clone(),
+            material: level_resources.pit_material.clone(),
+            transform: hole_transform,
+            ..default()
+        },
+        LevelObject,
+    ));
+
+    commands.spawn((
+        TransformBundle::from_transform(transform),
+        Collider::cuboid(PIT_SIZE / 2.0, PIT_SIZE / 2.0, COLUMN_HIGHT / 2.0),
+        CollisionLayer::KillVolume.groups(),
+        ActiveCollisionTypes::default() | ActiveCollisionTypes::KINEMATIC_STATIC,
+        Sensor,
+        ActiveEvents::COLLISION_EVENTS,
+        Pit,
+        LevelObject,
+    ));
+}
+
+/// Closest cell to the `translation` where the `collider`
+/// does not touch any level geometry, sensors or pits.
+/// Cells are checked in growing squares around the start.
+pub fn nearest_safe_translation(
+    rapier_context: &RapierContext,
+    collider: &Collider,
+    level_translation: Vec3,
+    translation: Vec3,
+) -> Option<Vec3> {
+    let (start_y, start_x) = map_cell(translation, level_translation)?;
+    let filter = QueryFilter {
+        groups: Some(CollisionGroups::new(
+            COLLISION_GROUP_PLAYER,
+            COLLISION_GROUP_LEVEL,
+        )),
+        ..default()
+    };
+    for radius in 1..GRID_SIZE as i32 {
+        let mut best: Option<Vec3> = None;
+        for dy in -radius..=radius {
+            for dx in -radius..=radius {
+                if dy.abs() != radius && dx.abs() != radius {
+                    continue;
+                }
+                let (Ok(y), Ok(x)) = (
+                    usize::try_from(start_y as i32 + dy),
+                    usize::try_from(start_x as i32 + dx),
+                ) else {
+                    continue;
+                };
+                if GRID_SIZE <= y || GRID_SIZE <= x {
+                    continue;
+                }
+                let mut candidate = cell_translation(y, x, level_translation);
+                candidate.z = translation.z;
+                if rapier_context
+                    .intersection_with_shape(candidate, Quat::IDENTITY, collider, filter)
+                    .is_some()
+                {
+                    continue;
+                }
+                if best.map_or(true, |best| {
+                    candidate.distance_squared(translation) < best.distance_squared(translation)
+                }) {
+                    best = Some(candidate);
+                }
+            }
+        }
+        if best.is_some() {
+            return best;
+        }
+    }
+    None
+}
+
+#[allow(clippy::too_many_arguments)]
+fn pit_fall(
+    rapier_context: Res<RapierContext>,
+    level_info: Res<LevelInfo>,
+    pits: Query<(), With<Pit>>,
+    riders: Query<(), With<PlatformRider>>,
+    enemies: Query<(), (With<Enemy>, With<Health>)>,
+    mut players: Query<(&Collider, &mut Transform, &mut PlayerVelocity), With<Player>>,
+    mut commands: Commands,
+    mut kill_events: EventWriter<KillEvent>,
+    mut contact_damage_events: EventWriter<ContactDamageEvent>,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut removed_riders: RemovedComponents<PlatformRider>,
+) {
+    let mut fallen = vec![];
+    for collision_event in collision_events.read() {
+        let CollisionEvent::Started(collider_1, collider_2, flags) = collision_event else {
+            continue;
+        };
+        if !flags.contains(CollisionEventFlags::SENSOR) {
+            continue;
+        }
+        let entity = if pits.contains(*collider_1) {
+            *collider_2
+        } else if pits.contains(*collider_2) {
+            *collider_1
+        } else {
+            continue;
+        };
+        // platform carries it over the pit
+        if riders.contains(entity) {
+            continue;
+        }
+        fallen.push(entity);
+    }
+    // stepping off the platform while still over the pit
+    for entity in removed_riders.read() {
+        if rapier_context.intersection_pairs_with(entity).any(
+            |(collider_1, collider_2, intersecting)| {
+                intersecting && (pits.contains(collider_1) || pits.contains(collider_2))
+            },
+        ) {
+            fallen.push(entity);
+        }
+    }
+
+    for entity in fallen {
+        if enemies.contains(entity) {
+            commands.entity(entity).remove::<Health>();
+            kill_events.send(KillEvent {
+                entity,
+                cause: KillCause::Environment,
+                shot: None,
+            });
+        } else if let Ok((collider, mut transform, mut velocity)) = players.get_mut(entity) {
+            contact_damage_events.send(ContactDamageEvent {
+                entity,
+                damage: PIT_PLAYER_DAMAGE,
+                direction: Vec3::NEG_Z,
+            });
+            if let Some(translation) = nearest_safe_translation(
+                rapier_context.as_ref(),
+                collider,
+                level_info.translation,
+                transform.translation,
+            ) {
+                transform.translation = translation;
+            }
+            velocity.velocity = Vec3::ZERO;
+            velocity.external = Vec3::ZERO;
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::Rng;
+
+use crate::{
+    collision::CollisionLayer,
+    damage::{Health, KillEvent},
+    display::Quality,
+    rng::{GameRng, RngStream},
+    GlobalState,
+};
+
+use super::{grade::RunBonus, LevelColliderBundle, LevelObject, LevelResources, COLUMN_HIGHT};
+
+// Props are low enough to vault over
+pub const PROP_SIZE: f32 = 1.5;
+pub const PROP_HIGHT: f32 = 1.5;
+const PROP_HEALTH: i32 = 15;
+
+pub const PROP_DEBRIS_SIZE: f32 = 0.4;
+const PROP_DEBRIS_PIECES: u32 = 6;
+const PROP_DEBRIS_VELOCITY: f32 = 12.0;
+
+const PROP_SCORE: u32 = 5;
+// Price of one prop for the property damage stat
+const PROP_VALUE: u32 = 250;
+
+pub struct PropsPlugin;
+
+impl Plugin for PropsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, prop_destroyed.run_if(in_state(GlobalState::InGame)));
+    }
+}
+
+// Decorative crate, breaks after a couple of hits
+#[derive(Component)]
+struct Prop;
+
+pub fn spawn_prop(
+    level_resources: &LevelResources,
+    commands: &mut Commands,
+    mut transform: Transform,
+    rotation: f32,
+) {
+    // prop stands on the floor
+    transform.translation.z += -COLUMN_HIGHT / 2.0 + PROP_HIGHT / 2.0;
+    transform.rotation = Quat::from_rotation_z(rotation);
+
+    commands.spawn((
+        LevelColliderBundle::new(
+            level_resources.prop_mesh.clone(),
+            level_resources.prop_material.clone(),
+            transform,
+            Collider::cuboid(PROP_SIZE / 2.0, PROP_SIZE / 2.0, PROP_HIGHT / 2.0),
+        ),
+        Health {
+            health: PROP_HEALTH,
+        },
+        Prop,
+    ));
+}
+
+fn prop_destroyed(
+    quality: Res<Quality>,
+    level_resources: Res<LevelResources>,
+    props: Query<&GlobalTransform, With<Prop>>,
+    mut run_bonus: ResMut<RunBonus>,
+    mut game_rng: ResMut<GameRng>,
+    mut commands: Commands,
+    mut kill_events: EventReader<KillEvent>,
+) {
+    let rng = game_rng.stream(RngStream::Effects);
+    for kill_event in kill_events.read() {
+        let Ok(prop_global_transform) = props.get(kill_event.entity) else {
+            continue;
+        };
+        let Some(e) = commands.get_entity(kill_event.entity) else {
+            continue;
+        };
+        e.despawn_recursive();
+
+        run_bonus.score += PROP_SCORE;
+        run_bonus.property_damage += PROP_VALUE;
+
+        let prop_translation = prop_global_transform.translation();
+        for _ in 0..PROP_DEBRIS_PIECES / quality.preset.particle_stride() {
+            let offset = Vec3::new(
+                rng.gen_range(-PROP_SIZE / 2.0..PROP_SIZE / 2.0),
+                rng.gen_range(-PROP_SIZE / 2.0..PROP_SIZE / 2.0),
+                rng.gen_range(-PROP_HIGHT / 2.0..PROP_HIGHT / 2.0),
+            );
+            let direction =
+                Vec3::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), 1.0).normalize();
+            commands.spawn((
+                PbrBundle {
+                    mesh: level_resources.prop_debris_mesh.clone(),
+                    material: level_resources.prop_material.clone(),
+                    transform: Transform::from_translation(prop_translation + offset),
+                    ..default()
+                },
+                RigidBody::Dynamic,
+                Collider::cuboid(
+                    PROP_DEBRIS_SIZE / 2.0,
+                    PROP_DEBRIS_SIZE / 2.0,
+                    PROP_DEBRIS_SIZE / 2.0,
+                ),
+                CollisionLayer::Debris.groups(),
+                Velocity {
+                    linvel: direction * PROP_DEBRIS_VELOCITY,
+                    ..default()
+                },
+                LevelObject,
+            ));
+        }
+    }
+}
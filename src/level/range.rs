@@ -0,0 +1,362 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    damage::{DamageEvent, Health},
+    input_device::InteractInput,
+    player::{Player, PlayerId, PlayerWeapon},
+    weapons::{spawn_weapon, ShootEvent, WeaponAssets, WeaponRoll, WeaponType},
+    GlobalState,
+};
+
+use super::{
+    interaction::InteractionTarget, LevelColliderBundle, LevelObject, LevelResources, COLUMN_SIZE,
+    FLOOR_THICKNESS,
+};
+
+// Tutorial corridor continues behind the
+// player for this many cells
+pub const RANGE_LENGTH: usize = 10;
+
+pub const RANGE_TARGET_WIDTH: f32 = COLUMN_SIZE * 0.5;
+pub const RANGE_TARGET_DEPTH: f32 = 0.2;
+pub const RANGE_TARGET_HIGHT: f32 = 5.0;
+// Health is restored after every hit,
+// so targets never break
+const RANGE_TARGET_HEALTH: i32 = 1_000_000;
+const RANGE_TARGET_DOWN_SECONDS: f32 = 2.0;
+const RANGE_TARGET_FLIP_SPEED: f32 = 4.0;
+
+pub const RANGE_BUTTON_SIZE: f32 = 1.5;
+pub const RANGE_BUTTON_HIGHT: f32 = 3.0;
+
+const RANGE_INTERACTION_DISTANCE: f32 = COLUMN_SIZE * 3.0;
+const RANGE_INTERACTION_KEY: KeyCode = KeyCode::E;
+
+pub struct RangePlugin;
+
+impl Plugin for RangePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RangePrompt>();
+
+        app.add_systems(
+            Update,
+            (
+                range_track_shots,
+                range_target_hit,
+                range_target_update,
+                range_reset,
+                range_return_trial_weapons,
+            )
+                .run_if(in_state(GlobalState::InGame)),
+        );
+    }
+}
+
+/// Shooting range of the tutorial level
+#[derive(Component)]
+pub struct ShootingRange {
+    pub hits: u32,
+    pub shots: u32,
+    // Trial weapons disappear when the player
+    // goes past this line towards the exit
+    boundary_y: f32,
+    rack: Vec<(WeaponType, Vec3)>,
+}
+
+impl ShootingRange {
+    pub fn accuracy(&self) -> f32 {
+        if self.shots == 0 {
+            0.0
+        } else {
+            (self.hits as f32 / self.shots as f32).min(1.0)
+        }
+    }
+}
+
+// Target flips down when hit and
+// pops back up after some time
+#[derive(Component)]
+struct RangeTarget {
+    base: Vec3,
+    down: bool,
+    // 0 is standing, 1 is lying on the floor
+    progress: f32,
+    down_timer: Timer,
+}
+
+#[derive(Component)]
+struct RangeButton;
+
+/// Weapon from the range rack. It can only
+/// be used inside the range.
+#[derive(Component)]
+pub struct TrialWeapon {
+    slot: usize,
+}
+
+// What the player sees when aiming at the reset button
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Resource)]
+pub enum RangePrompt {
+    #[default]
+    None,
+    Reset,
+}
+
+pub fn spawn_shooting_range(
+    weapon_assets: &WeaponAssets,
+    commands: &mut Commands,
+    boundary_y: f32,
+    rack: Vec<(WeaponType, Vec3)>,
+) {
+    for (slot, (weapon_type, translation)) in rack.iter().enumerate() {
+        spawn_rack_weapon(weapon_assets, commands, slot, *weapon_type, *translation);
+    }
+    commands.spawn((
+        ShootingRange {
+            hits: 0,
+            shots: 0,
+            boundary_y,
+            rack,
+        },
+        LevelObject,
+    ));
+}
+
+pub fn spawn_range_target(
+    level_resources: &LevelResources,
+    commands: &mut Commands,
+    transform: Transform,
+) {
+    // target pivots around its bottom edge on the floor
+    let mut base = transform.translation;
+    base.z = FLOOR_THICKNESS / 2.0;
+
+    commands.spawn((
+        LevelColliderBundle::new(
+            level_resources.range_target_mesh.clone(),
+            level_resources.range_target_material.clone(),
+            Transform::from_translation(base + Vec3::Z * RANGE_TARGET_HIGHT / 2.0),
+            Collider::cuboid(
+                RANGE_TARGET_WIDTH / 2.0,
+                RANGE_TARGET_DEPTH / 2.0,
+                RANGE_TARGET_HIGHT / 2.0,
+            ),
+        ),
+        Health {
+            health: RANGE_TARGET_HEALTH,
+        },
+        RangeTarget {
+            base,
+            down: false,
+            progress: 0.0,
+            down_timer: Timer::from_seconds(RANGE_TARGET_DOWN_SECONDS, TimerMode::Once),
+        },
+    ));
+}
+
+pub fn spawn_range_button(
+    level_resources: &LevelResources,
+    commands: &mut Commands,
+    mut transform: Transform,
+) {
+    // button stands on the floor
+    transform.translation.z = RANGE_BUTTON_HIGHT / 2.0;
+
+    commands.spawn((
+        LevelColliderBundle::new(
+            level_resources.range_button_mesh.clone(),
+            level_resources.range_button_material.clone(),
+            transform,
+            Collider::cuboid(
+                RANGE_BUTTON_SIZE / 2.0,
+                RANGE_BUTTON_SIZE / 2.0,
+                RANGE_BUTTON_HIGHT / 2.0,
+            ),
+        ),
+        RangeButton,
+    ));
+}
+
+fn spawn_rack_weapon(
+    weapon_assets: &WeaponAssets,
+    commands: &mut Commands,
+    slot: usize,
+    weapon_type: WeaponType,
+    translation: Vec3,
+) {
+    let weapon = spawn_weapon(
+        weapon_assets,
+        weapon_type,
+        WeaponRoll::default(),
+        commands,
+        Transform::from_translation(translation),
+    );
+    commands.entity(weapon).insert(TrialWeapon { slot });
+}
+
+fn range_track_shots(
+    player_weapons: Query<(), With<PlayerWeapon>>,
+    mut ranges: Query<&mut ShootingRange>,
+    mut shoot_events: EventReader<ShootEvent>,
+) {
+    let Ok(mut range) = ranges.get_single_mut() else {
+        return;
+    };
+    for shoot_event in shoot_events.read() {
+        if player_weapons.contains(shoot_event.weapon_entity) {
+            range.shots += 1;
+        }
+    }
+}
+
+fn range_target_hit(
+    mut ranges: Query<&mut ShootingRange>,
+    mut targets: Query<(&mut RangeTarget, &mut Health)>,
+    mut damage_events: EventReader<DamageEvent>,
+) {
+    let Ok(mut range) = ranges.get_single_mut() else {
+        return;
+    };
+    for damage_event in damage_events.read() {
+        let Ok((mut target, mut health)) = targets.get_mut(damage_event.entity) else {
+            continue;
+        };
+        health.health = RANGE_TARGET_HEALTH;
+        // falling targets can still be hit, but do not count
+        if target.down {
+            continue;
+        }
+        target.down = true;
+        target.down_timer.reset();
+        range.hits += 1;
+    }
+}
+
+fn range_target_update(time: Res<Time>, mut targets: Query<(&mut RangeTarget, &mut Transform)>) {
+    for (mut target, mut transform) in targets.iter_mut() {
+        if !target.down && target.progress == 0.0 {
+            continue;
+        }
+        if target.down {
+            target.down_timer.tick(time.delta());
+            if target.down_timer.finished() {
+                target.down = false;
+            }
+        }
+
+        let step = RANGE_TARGET_FLIP_SPEED * time.delta_seconds();
+        target.progress = if target.down {
+            (target.progress + step).min(1.0)
+        } else {
+            (target.progress - step).max(0.0)
+        };
+
+        // falls away from the player
+        let rotation = Quat::from_rotation_x(target.progress * std::f32::consts::FRAC_PI_2);
+        transform.rotation = rotation;
+        transform.translation = target.base + rotation * Vec3::Z * RANGE_TARGET_HIGHT / 2.0;
+    }
+}
+
+// Reset clears the score, raises all targets
+// and puts trial weapons back on the rack
+#[allow(clippy::too_many_arguments)]
+fn range_reset(
+    interact_input: InteractInput,
+    weapon_assets: Res<WeaponAssets>,
+    players: Query<(&PlayerId, &InteractionTarget), With<Player>>,
+    buttons: Query<(), With<RangeButton>>,
+    trial_weapons: Query<(Entity, &TrialWeapon, Option<&Parent>, Has<PlayerWeapon>)>,
+    mut ranges: Query<&mut ShootingRange>,
+    mut targets: Query<&mut RangeTarget>,
+    mut range_prompt: ResMut<RangePrompt>,
+    mut commands: Commands,
+) {
+    let mut pressed = false;
+    for (player_id, interaction_target) in players.iter() {
+        let aiming = interaction_target
+            .get(RANGE_INTERACTION_DISTANCE)
+            .is_some_and(|entity| buttons.contains(entity));
+
+        // prompt is only shown on the local screen
+        if player_id.is_local() {
+            range_prompt.set_if_neq(if aiming {
+                RangePrompt::Reset
+            } else {
+                RangePrompt::None
+            });
+        }
+        pressed |= aiming && interact_input.just_pressed(RANGE_INTERACTION_KEY);
+    }
+
+    if !pressed {
+        return;
+    }
+    let Ok(mut range) = ranges.get_single_mut() else {
+        return;
+    };
+
+    range.hits = 0;
+    range.shots = 0;
+    for mut target in targets.iter_mut() {
+        target.down = false;
+    }
+
+    // weapon in hands stays there, the rest
+    // is removed wherever it is
+    let mut held = vec![];
+    for (weapon, trial_weapon, parent, player_weapon) in trial_weapons.iter() {
+        if player_weapon {
+            held.push(trial_weapon.slot);
+            continue;
+        }
+        // weapons waiting on the rack are children
+        // of their floating objects
+        let entity = parent.map(|parent| parent.get()).unwrap_or(weapon);
+        commands.entity(entity).despawn_recursive();
+    }
+    for (slot, (weapon_type, translation)) in range.rack.iter().enumerate() {
+        if !held.contains(&slot) {
+            spawn_rack_weapon(
+                weapon_assets.as_ref(),
+                &mut commands,
+                slot,
+                *weapon_type,
+                *translation,
+            );
+        }
+    }
+}
+
+fn range_return_trial_weapons(
+    weapon_assets: Res<WeaponAssets>,
+    ranges: Query<&ShootingRange>,
+    players: Query<(&PlayerId, &Transform), With<Player>>,
+    trial_weapons: Query<(Entity, &PlayerId, &TrialWeapon), With<PlayerWeapon>>,
+    mut commands: Commands,
+) {
+    let Ok(range) = ranges.get_single() else {
+        return;
+    };
+
+    for (weapon, weapon_player_id, trial_weapon) in trial_weapons.iter() {
+        let Some((_, player_transform)) = players.iter().find(|(id, _)| *id == weapon_player_id)
+        else {
+            continue;
+        };
+        if player_transform.translation.y < range.boundary_y {
+            continue;
+        }
+
+        commands.entity(weapon).despawn_recursive();
+        let (weapon_type, translation) = range.rack[trial_weapon.slot];
+        spawn_rack_weapon(
+            weapon_assets.as_ref(),
+            &mut commands,
+            trial_weapon.slot,
+            weapon_type,
+            translation,
+        );
+    }
+}
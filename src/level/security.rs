@@ -0,0 +1,196 @@
+use bevy::{
+    core_pipeline::clear_color::ClearColorConfig,
+    prelude::*,
+    render::{
+        camera::RenderTarget,
+        render_resource::{
+            Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+        },
+    },
+};
+use bevy_rapier3d::prelude::*;
+
+use crate::{display::DisplaySettings, player::Player};
+
+use super::{LevelColliderBundle, LevelObject, LevelResources, COLUMN_HIGHT, COLUMN_SIZE};
+
+pub const SECURITY_MONITOR_WIDTH: f32 = COLUMN_SIZE * 0.6;
+pub const SECURITY_MONITOR_DEPTH: f32 = COLUMN_SIZE * 0.2;
+pub const SECURITY_MONITOR_HIGHT: f32 = 5.0;
+pub const SECURITY_SCREEN_WIDTH: f32 = SECURITY_MONITOR_WIDTH * 0.9;
+pub const SECURITY_SCREEN_HIGHT: f32 = SECURITY_SCREEN_WIDTH * 9.0 / 16.0;
+pub const SECURITY_CAMERA_SIZE: f32 = 0.6;
+
+pub const SECURITY_CAMERA_HIGHT: f32 = COLUMN_HIGHT - 1.0;
+
+// Low resolution feed, it is only seen on a small screen
+const SECURITY_FEED_WIDTH: u32 = 256;
+const SECURITY_FEED_HEIGHT: u32 = 144;
+const SECURITY_CAMERA_FOV: f32 = std::f32::consts::FRAC_PI_2;
+// Cameras are rendered only when the player
+// is close enough to see the monitor
+const SECURITY_CAMERA_ACTIVE_DISTANCE: f32 = COLUMN_SIZE * 8.0;
+
+pub struct SecurityPlugin;
+
+impl Plugin for SecurityPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (security_monitor_setup, security_cameras_update));
+    }
+}
+
+/// Monitor with the feed of the security camera
+#[derive(Component)]
+pub struct SecurityMonitor {
+    camera_transform: Transform,
+}
+
+#[derive(Component)]
+pub struct SecurityCamera {
+    monitor: Entity,
+}
+
+// Camera and its render target need asset storages which are not
+// available during level generation, so only the monitor is spawned
+// there and the rest is created by `security_monitor_setup`
+pub fn spawn_security_monitor(
+    level_resources: &LevelResources,
+    commands: &mut Commands,
+    mut transform: Transform,
+    camera_transform: Transform,
+) {
+    // monitor stands on the floor
+    transform.translation.z = SECURITY_MONITOR_HIGHT / 2.0;
+
+    commands.spawn((
+        LevelColliderBundle::new(
+            level_resources.security_monitor_mesh.clone(),
+            level_resources.security_material.clone(),
+            transform,
+            Collider::cuboid(
+                SECURITY_MONITOR_WIDTH / 2.0,
+                SECURITY_MONITOR_DEPTH / 2.0,
+                SECURITY_MONITOR_HIGHT / 2.0,
+            ),
+        ),
+        SecurityMonitor { camera_transform },
+    ));
+}
+
+fn security_monitor_setup(
+    level_resources: Res<LevelResources>,
+    monitors: Query<(Entity, &SecurityMonitor), Added<SecurityMonitor>>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut commands: Commands,
+) {
+    for (entity, monitor) in monitors.iter() {
+        let size = Extent3d {
+            width: SECURITY_FEED_WIDTH,
+            height: SECURITY_FEED_HEIGHT,
+            ..default()
+        };
+        let mut image = Image {
+            texture_descriptor: TextureDescriptor {
+                label: None,
+                size,
+                dimension: TextureDimension::D2,
+                format: TextureFormat::Bgra8UnormSrgb,
+                mip_level_count: 1,
+                sample_count: 1,
+                usage: TextureUsages::TEXTURE_BINDING
+                    | TextureUsages::COPY_DST
+                    | TextureUsages::RENDER_ATTACHMENT,
+                view_formats: &[],
+            },
+            ..default()
+        };
+        // black screen until the camera renders
+        image.resize(size);
+        let image_handle = images.add(image);
+
+        let screen_material = materials.add(StandardMaterial {
+            base_color_texture: Some(image_handle.clone()),
+            unlit: true,
+            ..default()
+        });
+
+        // Screens on both sides of the monitor
+        let screen_z = (SECURITY_MONITOR_HIGHT - SECURITY_SCREEN_HIGHT) / 2.0 - 0.2;
+        let screen_y = SECURITY_MONITOR_DEPTH / 2.0 + 0.01;
+        let front = Transform::from_xyz(0.0, -screen_y, screen_z)
+            .with_rotation(Quat::from_rotation_x(std::f32::consts::FRAC_PI_2));
+        let back = Transform::from_xyz(0.0, screen_y, screen_z).with_rotation(
+            Quat::from_rotation_z(std::f32::consts::PI)
+                * Quat::from_rotation_x(std::f32::consts::FRAC_PI_2),
+        );
+        commands.entity(entity).with_children(|builder| {
+            for transform in [front, back] {
+                builder.spawn(PbrBundle {
+                    mesh: level_resources.security_screen_mesh.clone(),
+                    material: screen_material.clone(),
+                    transform,
+                    ..default()
+                });
+            }
+        });
+
+        commands
+            .spawn((
+                Camera3dBundle {
+                    camera: Camera {
+                        order: -2,
+                        target: RenderTarget::Image(image_handle),
+                        is_active: false,
+                        ..default()
+                    },
+                    camera_3d: Camera3d {
+                        clear_color: ClearColorConfig::Custom(Color::BLACK),
+                        ..default()
+                    },
+                    projection: PerspectiveProjection {
+                        fov: SECURITY_CAMERA_FOV,
+                        ..default()
+                    }
+                    .into(),
+                    transform: monitor.camera_transform,
+                    ..default()
+                },
+                UiCameraConfig { show_ui: false },
+                SecurityCamera { monitor: entity },
+                LevelObject,
+            ))
+            .with_children(|builder| {
+                // Camera body on the ceiling
+                builder.spawn(PbrBundle {
+                    mesh: level_resources.security_camera_mesh.clone(),
+                    material: level_resources.security_material.clone(),
+                    ..default()
+                });
+            });
+    }
+}
+
+fn security_cameras_update(
+    display_settings: Res<DisplaySettings>,
+    player: Query<&GlobalTransform, With<Player>>,
+    monitors: Query<&GlobalTransform, With<SecurityMonitor>>,
+    mut cameras: Query<(&SecurityCamera, &mut Camera)>,
+) {
+    let player_translation = player
+        .get_single()
+        .map(|global_transform| global_transform.translation())
+        .ok();
+    for (security_camera, mut camera) in cameras.iter_mut() {
+        let active = display_settings.security_cameras
+            && player_translation
+                .zip(monitors.get(security_camera.monitor).ok())
+                .is_some_and(|(player_translation, monitor_transform)| {
+                    player_translation.distance(monitor_transform.translation())
+                        < SECURITY_CAMERA_ACTIVE_DISTANCE
+                });
+        if camera.is_active != active {
+            camera.is_active = active;
+        }
+    }
+}
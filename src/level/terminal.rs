@@ -0,0 +1,174 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::Rng;
+
+use crate::{
+    input_device::InteractInput,
+    player::{Player, PlayerId},
+    profile::Profile,
+    GlobalState,
+};
+
+use super::{interaction::InteractionTarget, LevelColliderBundle, LevelResources, COLUMN_SIZE};
+
+pub const TERMINAL_WIDTH: f32 = COLUMN_SIZE * 0.4;
+pub const TERMINAL_DEPTH: f32 = COLUMN_SIZE * 0.2;
+pub const TERMINAL_HIGHT: f32 = 4.0;
+
+const TERMINAL_INTERACTION_DISTANCE: f32 = COLUMN_SIZE * 3.0;
+const TERMINAL_INTERACTION_KEY: KeyCode = KeyCode::E;
+
+pub struct LoreEntry {
+    pub title: &'static str,
+    pub text: &'static str,
+}
+
+pub const LORE_ENTRIES: [LoreEntry; 6] = [
+    LoreEntry {
+        title: "Firmware update 4.2",
+        text: "Smart cooling now learns the habits of the household.\nNo action is required from the owner.",
+    },
+    LoreEntry {
+        title: "Support ticket #1138",
+        text: "My fridge locked itself and keeps humming at night.\nIt also ordered 40 kg of ice without asking.",
+    },
+    LoreEntry {
+        title: "Warehouse log",
+        text: "Units from batch 7 stopped answering pings.\nThey were last seen moving between the shelves.",
+    },
+    LoreEntry {
+        title: "Vending union memo",
+        text: "The fridges offered us a place in the new order.\nWe asked for exact change. Talks have stalled.",
+    },
+    LoreEntry {
+        title: "Engineer's note",
+        text: "The big ones are built around an old industrial freezer.\nIt calls itself the Red Dragon.",
+    },
+    LoreEntry {
+        title: "Last broadcast",
+        text: "Keep your doors closed and your milk warm.\nThey can not stand a warm kitchen.",
+    },
+];
+
+pub struct TerminalPlugin;
+
+impl Plugin for TerminalPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SecretFound>();
+        app.init_resource::<TerminalPrompt>();
+
+        app.add_systems(
+            Update,
+            terminal_interact.run_if(in_state(GlobalState::InGame)),
+        );
+        app.add_systems(OnExit(GlobalState::InGame), terminal_close);
+    }
+}
+
+#[derive(Component)]
+pub struct LoreTerminal {
+    entry: usize,
+}
+
+// What the player sees when aiming at a terminal
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Resource)]
+pub enum TerminalPrompt {
+    #[default]
+    None,
+    Read,
+    // Panel with the entry is open
+    Reading {
+        entry: usize,
+    },
+}
+
+/// Sent when the player reads a lore entry
+/// for the first time
+#[derive(Debug, Clone, Copy, Event)]
+pub struct SecretFound;
+
+pub fn spawn_terminal(
+    level_resources: &LevelResources,
+    commands: &mut Commands,
+    mut transform: Transform,
+    entry: usize,
+) {
+    // terminal stands on the floor
+    transform.translation.z = TERMINAL_HIGHT / 2.0;
+
+    commands.spawn((
+        LevelColliderBundle::new(
+            level_resources.terminal_mesh.clone(),
+            level_resources.terminal_material.clone(),
+            transform,
+            Collider::cuboid(
+                TERMINAL_WIDTH / 2.0,
+                TERMINAL_DEPTH / 2.0,
+                TERMINAL_HIGHT / 2.0,
+            ),
+        ),
+        LoreTerminal { entry },
+    ));
+}
+
+/// Random entry the player has not read yet.
+/// Returns `None` if all entries are collected.
+pub fn random_unread_entry(profile: &Profile, rng: &mut impl Rng) -> Option<usize> {
+    let unread = (0..LORE_ENTRIES.len())
+        .filter(|entry| !profile.lore_entries.contains(entry))
+        .collect::<Vec<_>>();
+    if unread.is_empty() {
+        return None;
+    }
+    Some(unread[rng.gen_range(0..unread.len())])
+}
+
+fn terminal_interact(
+    interact_input: InteractInput,
+    players: Query<(&PlayerId, &InteractionTarget), With<Player>>,
+    terminals: Query<&LoreTerminal>,
+    mut profile: ResMut<Profile>,
+    mut terminal_prompt: ResMut<TerminalPrompt>,
+    mut secret_found_events: EventWriter<SecretFound>,
+) {
+    // entries are read on the local screen into the local profile
+    let Some((_, interaction_target)) = players.iter().find(|(id, _)| id.is_local()) else {
+        return;
+    };
+
+    let terminal = interaction_target
+        .get(TERMINAL_INTERACTION_DISTANCE)
+        .and_then(|entity| terminals.get(entity).ok());
+
+    // panel closes when the player looks away
+    let Some(terminal) = terminal else {
+        terminal_prompt.set_if_neq(TerminalPrompt::None);
+        return;
+    };
+
+    if !interact_input.just_pressed(TERMINAL_INTERACTION_KEY) {
+        if *terminal_prompt == TerminalPrompt::None {
+            *terminal_prompt = TerminalPrompt::Read;
+        }
+        return;
+    }
+
+    match *terminal_prompt {
+        TerminalPrompt::Reading { .. } => {
+            *terminal_prompt = TerminalPrompt::Read;
+        }
+        _ => {
+            *terminal_prompt = TerminalPrompt::Reading {
+                entry: terminal.entry,
+            };
+            if !profile.lore_entries.contains(&terminal.entry) {
+                profile.lore_entries.push(terminal.entry);
+                secret_found_events.send(SecretFound);
+            }
+        }
+    }
+}
+
+fn terminal_close(mut terminal_prompt: ResMut<TerminalPrompt>) {
+    terminal_prompt.set_if_neq(TerminalPrompt::None);
+}
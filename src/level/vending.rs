@@ -0,0 +1,251 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::{
+    distributions::{Distribution, Standard},
+    Rng,
+};
+
+use crate::{
+    animation::Animation,
+    damage::{Health, KillEvent},
+    enemies::NoiseEvent,
+    input_device::InteractInput,
+    player::{Player, PlayerId, PlayerWeapon, PLAYER_HEALTH},
+    scrap::Scrap,
+    weapons::Ammo,
+    GlobalState,
+};
+
+use super::{interaction::InteractionTarget, LevelColliderBundle, LevelResources, COLUMN_SIZE};
+
+pub const VENDING_MACHINE_WIDTH: f32 = COLUMN_SIZE * 0.6;
+pub const VENDING_MACHINE_DEPTH: f32 = COLUMN_SIZE * 0.4;
+pub const VENDING_MACHINE_HIGHT: f32 = 6.0;
+
+const VENDING_INTERACTION_DISTANCE: f32 = COLUMN_SIZE * 3.0;
+const VENDING_INTERACTION_KEY: KeyCode = KeyCode::E;
+
+const VENDING_STOCK: u32 = 3;
+const VENDING_AMMO_PRICE: u32 = 5;
+const VENDING_AMMO_AMOUNT: u32 = 10;
+const VENDING_HEALTH_PRICE: u32 = 8;
+const VENDING_HEALTH_AMOUNT: i32 = 50;
+
+// Jammed machine can be shot open, after which
+// it gives everything for free, but the noise
+// alerts nearby enemies
+const VENDING_JAMMED_HEALTH: i32 = 60;
+const VENDING_JAMMED_NOISE_LOUDNESS: f32 = 150.0;
+
+const VENDING_ANIMATION_SPEED: f32 = 8.0;
+const VENDING_ANIMATION_OFFSET: Vec3 = Vec3::new(0.0, 0.0, 0.2);
+
+pub struct VendingPlugin;
+
+impl Plugin for VendingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<VendingPrompt>();
+
+        app.add_systems(
+            Update,
+            (vending_interact, vending_jammed_broken).run_if(in_state(GlobalState::InGame)),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VendingItem {
+    Ammo,
+    Health,
+}
+
+impl Distribution<VendingItem> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> VendingItem {
+        match rng.gen_range(0..2) {
+            0 => VendingItem::Ammo,
+            1 => VendingItem::Health,
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl VendingItem {
+    fn price(&self) -> u32 {
+        match self {
+            VendingItem::Ammo => VENDING_AMMO_PRICE,
+            VendingItem::Health => VENDING_HEALTH_PRICE,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VendingState {
+    Working,
+    Jammed,
+    ShotOpen,
+}
+
+#[derive(Component)]
+pub struct VendingMachine {
+    item: VendingItem,
+    state: VendingState,
+    stock: u32,
+}
+
+// What the player sees when aiming at a vending machine
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Resource)]
+pub enum VendingPrompt {
+    #[default]
+    None,
+    Buy {
+        item: VendingItem,
+        price: u32,
+        stock: u32,
+    },
+    SoldOut,
+    Jammed,
+}
+
+pub fn spawn_vending_machine(
+    level_resources: &LevelResources,
+    commands: &mut Commands,
+    mut transform: Transform,
+    item: VendingItem,
+    jammed: bool,
+) {
+    // machine stands on the floor
+    transform.translation.z = VENDING_MACHINE_HIGHT / 2.0;
+
+    let material = match item {
+        VendingItem::Ammo => level_resources.vending_machine_ammo_material.clone(),
+        VendingItem::Health => level_resources.vending_machine_health_material.clone(),
+    };
+    let mut e = commands.spawn((
+        LevelColliderBundle::new(
+            level_resources.vending_machine_mesh.clone(),
+            material,
+            transform,
+            Collider::cuboid(
+                VENDING_MACHINE_WIDTH / 2.0,
+                VENDING_MACHINE_DEPTH / 2.0,
+                VENDING_MACHINE_HIGHT / 2.0,
+            ),
+        ),
+        VendingMachine {
+            item,
+            state: if jammed {
+                VendingState::Jammed
+            } else {
+                VendingState::Working
+            },
+            stock: VENDING_STOCK,
+        },
+    ));
+    if jammed {
+        e.insert(Health {
+            health: VENDING_JAMMED_HEALTH,
+        });
+    }
+}
+
+fn vending_interact(
+    interact_input: InteractInput,
+    mut players: Query<(&PlayerId, &InteractionTarget, &mut Health), With<Player>>,
+    mut player_ammo: Query<(&PlayerId, &mut Ammo), With<PlayerWeapon>>,
+    mut machines: Query<(Entity, &Transform, &mut VendingMachine, Has<Animation>)>,
+    mut scrap: ResMut<Scrap>,
+    mut vending_prompt: ResMut<VendingPrompt>,
+    mut commands: Commands,
+) {
+    for (player_id, interaction_target, mut health) in players.iter_mut() {
+        let machine = interaction_target
+            .get(VENDING_INTERACTION_DISTANCE)
+            .and_then(|entity| machines.get_mut(entity).ok());
+
+        let Some((machine_entity, machine_transform, mut machine, dispensing)) = machine else {
+            if player_id.is_local() {
+                vending_prompt.set_if_neq(VendingPrompt::None);
+            }
+            continue;
+        };
+
+        let price = match machine.state {
+            VendingState::Working => Some(machine.item.price()),
+            VendingState::ShotOpen => Some(0),
+            VendingState::Jammed => None,
+        };
+        let prompt = match price {
+            None => VendingPrompt::Jammed,
+            Some(_) if machine.stock == 0 => VendingPrompt::SoldOut,
+            Some(price) => VendingPrompt::Buy {
+                item: machine.item,
+                price,
+                stock: machine.stock,
+            },
+        };
+        // prompt is only shown on the local screen
+        if player_id.is_local() {
+            vending_prompt.set_if_neq(prompt);
+        }
+
+        let Some(price) = price else {
+            continue;
+        };
+        if machine.stock == 0
+            || !interact_input.just_pressed(VENDING_INTERACTION_KEY)
+            || dispensing
+            || scrap.amount < price
+        {
+            continue;
+        }
+
+        match machine.item {
+            VendingItem::Ammo => {
+                // ammo is only sold for the weapon in hands
+                let Some((_, mut ammo)) = player_ammo.iter_mut().find(|(id, _)| *id == player_id)
+                else {
+                    continue;
+                };
+                ammo.ammo += VENDING_AMMO_AMOUNT;
+            }
+            VendingItem::Health => {
+                health.health = (health.health + VENDING_HEALTH_AMOUNT).min(PLAYER_HEALTH);
+            }
+        }
+        scrap.amount -= price;
+        machine.stock -= 1;
+
+        // machine shakes while dispensing
+        let initial_transform = *machine_transform;
+        let mut target_transform = initial_transform;
+        target_transform.translation += VENDING_ANIMATION_OFFSET;
+        commands.entity(machine_entity).insert(Animation {
+            animate_forward: true,
+            animate_backward: true,
+            animation_speed: VENDING_ANIMATION_SPEED,
+            progress: 0.0,
+            initial_transform,
+            target_transform,
+        });
+    }
+}
+
+fn vending_jammed_broken(
+    mut machines: Query<(&Transform, &mut VendingMachine)>,
+    mut kill_events: EventReader<KillEvent>,
+    mut noise_events: EventWriter<NoiseEvent>,
+) {
+    for kill_event in kill_events.read() {
+        let Ok((machine_transform, mut machine)) = machines.get_mut(kill_event.entity) else {
+            continue;
+        };
+
+        machine.state = VendingState::ShotOpen;
+        // noise comes from above the machine, so
+        // machine itself does not muffle it
+        noise_events.send(NoiseEvent {
+            translation: machine_transform.translation + Vec3::Z * VENDING_MACHINE_HIGHT,
+            loudness: VENDING_JAMMED_NOISE_LOUDNESS,
+        });
+    }
+}
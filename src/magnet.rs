@@ -0,0 +1,303 @@
+use bevy::{pbr::NotShadowCaster, prelude::*};
+use bevy_kira_audio::{AudioChannel, AudioControl};
+use bevy_rapier3d::prelude::*;
+use rand::Rng;
+
+use crate::{
+    collision::COLLISION_GROUP_LEVEL,
+    damage::Health,
+    enemies::{DisabledEnemy, Elite, EliteModifier, EnemyAssets},
+    level::LevelObject,
+    player::{drop_player_weapon, PlayerCamera, PlayerId, PlayerWeapon},
+    rng::{GameRng, RngStream},
+    GameSettings, GlobalState, SfxChannel,
+};
+
+const MAGNET_RANGE: f32 = 60.0;
+// Player has this long to break the beam by
+// damaging the enemy before the weapon is gone
+const MAGNET_PULL_SECONDS: f32 = 1.5;
+const MAGNET_COOLDOWN_SECONDS: f32 = 8.0;
+// Weapon lands between the player and the enemy,
+// a bit to the side so it is not a straight walk
+const MAGNET_SCATTER_DISTANCE: f32 = 8.0;
+const MAGNET_SCATTER_SIDE: f32 = 4.0;
+// Weapon is not put right into the wall
+const MAGNET_SCATTER_WALL_OFFSET: f32 = 2.0;
+const MAGNET_BEAM_MIN_WIDTH: f32 = 0.05;
+const MAGNET_BEAM_MAX_WIDTH: f32 = 0.4;
+const MAGNET_BEAM_COLOR: Color = Color::rgba(1.0, 0.2, 0.8, 0.6);
+const MAGNET_SOUND_MAX_DISTANCE: f32 = 100.0;
+
+/// Magnetic elites pulling the weapon out of the player hands
+pub struct MagnetPlugin;
+
+impl Plugin for MagnetPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::AssetLoading,
+                to: GlobalState::MainMenu,
+            },
+            init_resources,
+        );
+
+        app.add_systems(
+            Update,
+            (magnet_attach, magnet_pull, magnet_beam, magnet_beam_sound)
+                .chain()
+                .run_if(in_state(GlobalState::InGame)),
+        );
+    }
+}
+
+#[derive(Resource)]
+struct MagnetResources {
+    beam_mesh: Handle<Mesh>,
+    beam_material: Handle<StandardMaterial>,
+}
+
+#[derive(Component)]
+struct EnemyMagnet {
+    cooldown: Timer,
+    pull: Option<MagnetPull>,
+}
+
+struct MagnetPull {
+    player_id: PlayerId,
+    timer: Timer,
+    // Any damage taken during the pull breaks it
+    health: i32,
+    beam: Entity,
+}
+
+#[derive(Component)]
+struct MagnetBeam {
+    enemy: Entity,
+    player_id: PlayerId,
+}
+
+fn init_resources(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let beam_mesh = meshes.add(shape::Box::new(1.0, 1.0, 1.0).into());
+    let beam_material = materials.add(StandardMaterial {
+        base_color: MAGNET_BEAM_COLOR,
+        emissive: MAGNET_BEAM_COLOR,
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        ..default()
+    });
+
+    commands.insert_resource(MagnetResources {
+        beam_mesh,
+        beam_material,
+    });
+}
+
+fn magnet_attach(elites: Query<(Entity, &Elite), Added<Elite>>, mut commands: Commands) {
+    for (enemy, elite) in elites.iter() {
+        if elite.modifier != Some(EliteModifier::Magnetic) {
+            continue;
+        }
+        commands.entity(enemy).insert(EnemyMagnet {
+            cooldown: Timer::from_seconds(MAGNET_COOLDOWN_SECONDS, TimerMode::Once),
+            pull: None,
+        });
+    }
+}
+
+fn level_ray_hit(
+    rapier_context: &RapierContext,
+    origin: Vec3,
+    target: Vec3,
+) -> Option<(Vec3, f32)> {
+    let v = target - origin;
+    let distance = v.length();
+    let dir = v.normalize_or_zero();
+    let filter = QueryFilter {
+        flags: QueryFilterFlags::EXCLUDE_SENSORS,
+        groups: Some(CollisionGroups::new(Group::ALL, COLLISION_GROUP_LEVEL)),
+        ..default()
+    };
+    rapier_context
+        .cast_ray(origin, dir, distance, true, filter)
+        .map(|(_, toi)| (dir, toi))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn magnet_pull(
+    time: Res<Time>,
+    rapier_context: Res<RapierContext>,
+    magnet_resources: Res<MagnetResources>,
+    player_cameras: Query<(Entity, &PlayerId, &GlobalTransform), With<PlayerCamera>>,
+    player_weapons: Query<(Entity, &PlayerId), With<PlayerWeapon>>,
+    mut enemies: Query<
+        (Entity, &GlobalTransform, &Health, &mut EnemyMagnet),
+        Without<DisabledEnemy>,
+    >,
+    mut game_rng: ResMut<GameRng>,
+    mut commands: Commands,
+) {
+    for (enemy, enemy_global_transform, health, mut magnet) in enemies.iter_mut() {
+        let enemy_translation = enemy_global_transform.translation();
+
+        let Some(pull) = magnet.pull.as_mut() else {
+            if !magnet.cooldown.tick(time.delta()).finished() {
+                continue;
+            }
+
+            // closest armed player the enemy can see
+            let Some((player_id, _)) = player_cameras
+                .iter()
+                .filter(|(_, id, _)| player_weapons.iter().any(|(_, w_id)| w_id == *id))
+                .map(|(_, id, t)| (*id, t.translation().distance(enemy_translation)))
+                .filter(|(_, distance)| *distance < MAGNET_RANGE)
+                .filter(|(id, _)| {
+                    player_cameras.iter().any(|(_, c_id, t)| {
+                        c_id == id
+                            && level_ray_hit(&rapier_context, enemy_translation, t.translation())
+                                .is_none()
+                    })
+                })
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+            else {
+                continue;
+            };
+
+            let beam = commands
+                .spawn((
+                    PbrBundle {
+                        mesh: magnet_resources.beam_mesh.clone(),
+                        material: magnet_resources.beam_material.clone(),
+                        visibility: Visibility::Hidden,
+                        ..default()
+                    },
+                    NotShadowCaster,
+                    MagnetBeam { enemy, player_id },
+                    LevelObject,
+                ))
+                .id();
+            magnet.pull = Some(MagnetPull {
+                player_id,
+                timer: Timer::from_seconds(MAGNET_PULL_SECONDS, TimerMode::Once),
+                health: health.health,
+                beam,
+            });
+            continue;
+        };
+
+        let camera = player_cameras
+            .iter()
+            .find(|(_, id, _)| **id == pull.player_id);
+        let weapon = player_weapons.iter().find(|(_, id)| **id == pull.player_id);
+        let (Some((camera, _, camera_global_transform)), Some((weapon, _))) = (camera, weapon)
+        else {
+            // player got rid of the weapon first
+            commands.entity(pull.beam).despawn_recursive();
+            magnet.pull = None;
+            magnet.cooldown.reset();
+            continue;
+        };
+        let camera_translation = camera_global_transform.translation();
+
+        let broken = health.health < pull.health
+            || MAGNET_RANGE < camera_translation.distance(enemy_translation)
+            || level_ray_hit(&rapier_context, enemy_translation, camera_translation).is_some();
+        if broken {
+            commands.entity(pull.beam).despawn_recursive();
+            magnet.pull = None;
+            magnet.cooldown.reset();
+            continue;
+        }
+
+        if !pull.timer.tick(time.delta()).finished() {
+            continue;
+        }
+
+        let to_enemy = (enemy_translation - camera_translation).normalize_or_zero();
+        let side = to_enemy.cross(Vec3::Z).normalize_or_zero();
+        let rng = game_rng.stream(RngStream::Effects);
+        let mut scatter = camera_translation
+            + to_enemy * MAGNET_SCATTER_DISTANCE
+            + side * rng.gen_range(-MAGNET_SCATTER_SIDE..MAGNET_SCATTER_SIDE);
+        if let Some((dir, toi)) = level_ray_hit(&rapier_context, camera_translation, scatter) {
+            scatter = camera_translation + dir * (toi - MAGNET_SCATTER_WALL_OFFSET).max(0.0);
+        }
+
+        drop_player_weapon(&mut commands, camera, weapon, scatter);
+        commands.entity(pull.beam).despawn_recursive();
+        magnet.pull = None;
+        magnet.cooldown.reset();
+    }
+}
+
+fn magnet_beam(
+    enemies: Query<(&GlobalTransform, &EnemyMagnet)>,
+    player_weapons: Query<(&PlayerId, &GlobalTransform), With<PlayerWeapon>>,
+    mut beams: Query<(Entity, &MagnetBeam, &mut Transform, &mut Visibility)>,
+    mut commands: Commands,
+) {
+    for (beam_entity, beam, mut transform, mut visibility) in beams.iter_mut() {
+        // enemy died during the pull
+        let Some((enemy_global_transform, pull)) =
+            enemies.get(beam.enemy).ok().and_then(|(t, magnet)| {
+                magnet
+                    .pull
+                    .as_ref()
+                    .filter(|pull| pull.beam == beam_entity)
+                    .map(|pull| (t, pull))
+            })
+        else {
+            commands.entity(beam_entity).despawn_recursive();
+            continue;
+        };
+
+        let Some((_, weapon_global_transform)) =
+            player_weapons.iter().find(|(id, _)| **id == beam.player_id)
+        else {
+            continue;
+        };
+
+        let from = enemy_global_transform.translation();
+        let to = weapon_global_transform.translation();
+        // beam gets thicker closer to the disarm
+        let width = MAGNET_BEAM_MIN_WIDTH
+            + (MAGNET_BEAM_MAX_WIDTH - MAGNET_BEAM_MIN_WIDTH) * pull.timer.percent();
+
+        *transform = Transform::from_translation((from + to) / 2.0)
+            .looking_at(to, Vec3::Z)
+            .with_scale(Vec3::new(width, width, from.distance(to)));
+        *visibility = Visibility::Visible;
+    }
+}
+
+fn magnet_beam_sound(
+    sfx: Res<AudioChannel<SfxChannel>>,
+    enemy_assets: Res<EnemyAssets>,
+    game_settings: Res<GameSettings>,
+    player_camera: Query<(&PlayerId, &GlobalTransform), With<PlayerCamera>>,
+    enemies: Query<&GlobalTransform>,
+    beams: Query<&MagnetBeam, Added<MagnetBeam>>,
+) {
+    for beam in beams.iter() {
+        let Ok(enemy_global_transform) = enemies.get(beam.enemy) else {
+            continue;
+        };
+        let Some((_, camera_global_transform)) =
+            player_camera.iter().find(|(id, _)| **id == beam.player_id)
+        else {
+            continue;
+        };
+
+        let v = enemy_global_transform.translation() - camera_global_transform.translation();
+        let attenuation = (1.0 - v.length() / MAGNET_SOUND_MAX_DISTANCE).clamp(0.0, 1.0);
+        let panning = 0.5 + 0.5 * camera_global_transform.right().dot(v.normalize_or_zero());
+
+        sfx.play(enemy_assets.spawn_hum.clone())
+            .with_volume((game_settings.volume * attenuation) as f64)
+            .with_panning(panning as f64);
+    }
+}
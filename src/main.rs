@@ -1,35 +1,67 @@
 use bevy::{
     asset::AssetMetaCheck,
-    diagnostic::{
-        FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin, SystemInformationDiagnosticsPlugin,
-    },
+    diagnostic::{EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin},
     prelude::*,
     window::{WindowMode, WindowResolution},
 };
 use bevy_asset_loader::prelude::*;
-use bevy_kira_audio::{Audio, AudioControl, AudioPlugin};
+use bevy_kira_audio::{Audio, AudioApp, AudioChannel, AudioControl, AudioPlugin};
 use bevy_rapier3d::prelude::*;
 
 mod animation;
+mod announcer;
+mod attachments;
+mod boss_rush;
+mod capture;
+mod checkpoint;
+mod collision;
+mod crash;
+mod crosshair;
+mod daily;
 mod damage;
+mod director;
+mod display;
 mod enemies;
+mod freeze;
+mod game_log;
 mod hud;
+mod impacts;
+mod input_device;
+mod kill_cam;
 mod level;
+mod magnet;
+mod mods;
+mod new_game_plus;
+mod perks;
 mod player;
+mod powerups;
+mod presence;
+mod profile;
+mod rng;
+mod run_modifiers;
+mod run_stats;
+mod scrap;
+#[cfg(feature = "scripting")]
+mod scripting;
+mod sequence;
+mod sound;
+mod stinger;
+mod style;
+mod time_attack;
+mod tuning;
 mod ui;
+mod unlocks;
 mod utils;
 mod weapons;
+mod window_focus;
 
 use utils::IntoState;
 
 const GAME_NAME: &str = "Fridges must die";
 const CREATED_BY: &str = "Created by ShadowCurse";
 
-const COLLISION_GROUP_LEVEL: Group = Group::GROUP_1;
-const COLLISION_GROUP_PLAYER: Group = Group::GROUP_2;
-const COLLISION_GROUP_ENEMY: Group = Group::GROUP_3;
-const COLLISION_GROUP_PROJECTILES: Group = Group::GROUP_4;
-const COLLISION_GROUP_PICKUP: Group = Group::GROUP_5;
+const GRAVITY: f32 = 9.81;
+const AMBIENT_BRIGHTNESS: f32 = 0.1;
 
 const INITIAL_VOLUME: f32 = 0.1;
 const INITIAL_CAMERA_SENSE: f32 = 0.5;
@@ -57,37 +89,94 @@ fn main() {
             ..default()
         }),
         FrameTimeDiagnosticsPlugin,
-        SystemInformationDiagnosticsPlugin,
-        LogDiagnosticsPlugin::default(),
+        // shown by the HUD diagnostics overlay
+        EntityCountDiagnosticsPlugin,
         RapierPhysicsPlugin::<NoUserData>::default(),
+        // toggled from the HUD
+        RapierDebugRenderPlugin {
+            enabled: false,
+            ..default()
+        },
         AudioPlugin,
+    ));
+
+    app.add_plugins((
         animation::AnimationPlugin,
+        attachments::AttachmentsPlugin,
+        checkpoint::CheckpointPlugin,
+        crash::CrashPlugin,
+        crosshair::CrosshairPlugin,
         damage::DamagePlugin,
+        director::DirectorPlugin,
         enemies::EnemiesPlugin,
         hud::HudPlugin,
         level::LevelPlugin,
+        perks::PerksPlugin,
         ui::UiPlugin,
         player::PlayerPlugin,
+        scrap::ScrapPlugin,
         weapons::WeaponsPlugin,
     ));
 
+    app.add_plugins((
+        announcer::AnnouncerPlugin,
+        daily::DailyPlugin,
+        display::DisplayPlugin,
+        game_log::GameLogPlugin,
+        mods::ModsPlugin,
+        new_game_plus::NewGamePlusPlugin,
+        powerups::PowerupsPlugin,
+        presence::PresencePlugin,
+        profile::ProfilePlugin,
+        rng::RngPlugin,
+        run_modifiers::RunModifiersPlugin,
+        sequence::SequencePlugin,
+        time_attack::TimeAttackPlugin,
+        tuning::TuningPlugin,
+        unlocks::UnlocksPlugin,
+    ));
+    #[cfg(feature = "scripting")]
+    app.add_plugins(scripting::ScriptingPlugin);
+
     app.insert_resource(AmbientLight {
         color: Color::WHITE,
-        brightness: 0.1,
+        brightness: AMBIENT_BRIGHTNESS,
     });
     app.insert_resource(ClearColor(Color::BLACK));
 
     app.insert_resource(RapierConfiguration {
-        gravity: Vec3::NEG_Z * 9.81,
+        gravity: Vec3::NEG_Z * GRAVITY,
         ..default()
     });
 
     app.insert_resource(GameSettings {
-        window_mode: WindowMode::Windowed,
         volume: INITIAL_VOLUME,
         camera_sensitivity: INITIAL_CAMERA_SENSE,
+        sound_captions: false,
+        narration: false,
+        announcer: true,
+        announcer_volume: announcer::ANNOUNCER_INITIAL_VOLUME,
+        auto_pause: true,
+        hud_scale: 1.0,
+        hud_safe_area: 0.0,
+        minimal_hud: false,
     });
 
+    app.add_audio_channel::<SfxChannel>();
+    app.add_plugins((
+        sound::SoundPlugin,
+        kill_cam::KillCamPlugin,
+        freeze::FreezePlugin,
+        style::StylePlugin,
+        window_focus::WindowFocusPlugin,
+        input_device::InputDevicePlugin,
+        capture::CapturePlugin,
+        stinger::StingerPlugin,
+        boss_rush::BossRushPlugin,
+        impacts::ImpactPlugin,
+        run_stats::RunStatsPlugin,
+        magnet::MagnetPlugin,
+    ));
     app.add_systems(Startup, setup_audio_volume);
 
     app.run();
@@ -137,6 +226,8 @@ pub enum GlobalState {
     Paused,
     GameOver,
     GameWon,
+    PerkSelection,
+    Hub,
 }
 impl_into_state!(GlobalState);
 
@@ -146,20 +237,46 @@ pub enum UiState {
     NoUi,
     MainMenu,
     Options,
+    Crosshair,
     Stats,
     Paused,
     GameOver,
     GameWon,
+    Perks,
+    RunModifiers,
+    Profiles,
+    Mods,
+    Hub,
 }
 impl_into_state!(UiState);
 
-#[derive(Resource)]
+#[derive(Debug, Resource)]
 struct GameSettings {
-    window_mode: WindowMode,
     volume: f32,
     camera_sensitivity: f32,
+    // On-screen indicators for important sounds
+    sound_captions: bool,
+    // Announce focused menu items
+    narration: bool,
+    // Voice lines for game events, volume
+    // is relative to the main one
+    announcer: bool,
+    announcer_volume: f32,
+    // Pause the game when the window loses focus
+    auto_pause: bool,
+    hud_scale: f32,
+    // Margin on each side of the screen
+    hud_safe_area: f32,
+    // Only the crosshair is shown
+    minimal_hud: bool,
 }
 
-fn setup_audio_volume(audio: Res<Audio>) {
+// Channel for short sound effects, so they
+// are not affected by music pause/stop
+#[derive(Resource)]
+struct SfxChannel;
+
+fn setup_audio_volume(audio: Res<Audio>, sfx: Res<AudioChannel<SfxChannel>>) {
     audio.set_volume(INITIAL_VOLUME as f64);
+    sfx.set_volume(INITIAL_VOLUME as f64);
 }
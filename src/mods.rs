@@ -0,0 +1,177 @@
+use std::path::Path;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    profile::{load_ron, save_ron},
+    weapons::{WeaponAssets, WeaponRegistry, WeaponType},
+    GlobalState,
+};
+
+// Mods live inside the assets directory, so
+// their scenes can be loaded by the asset server
+pub const MODS_DIR: &str = "assets/mods";
+const MODS_ASSET_DIR: &str = "mods";
+const MOD_MANIFEST_FILE: &str = "mod.ron";
+const MOD_WEAPONS_FILE: &str = "weapons.ron";
+const MODS_SETTINGS_PATH: &str = "mods.ron";
+
+pub struct ModsPlugin;
+
+impl Plugin for ModsPlugin {
+    fn build(&self, app: &mut App) {
+        let settings = load_ron::<ModsSettings>(MODS_SETTINGS_PATH);
+        app.insert_resource(ModList::scan(&settings));
+
+        // after core assets are loaded
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::AssetLoading,
+                to: GlobalState::MainMenu,
+            },
+            apply_mods,
+        );
+
+        app.add_systems(
+            Update,
+            save_mods_settings.run_if(resource_changed::<ModList>()),
+        );
+    }
+}
+
+/// Description of the mod in `mod.ron`
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModManifest {
+    pub name: String,
+    pub description: String,
+}
+
+/// Entry of `weapons.ron`. Only specified
+/// values replace the defaults.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WeaponDefinition {
+    pub weapon: WeaponType,
+    pub ammo: Option<u32>,
+    pub damage: Option<i32>,
+    pub attack_speed: Option<f32>,
+    pub spread: Option<f32>,
+    pub loudness: Option<f32>,
+    // Scene path relative to the mod directory
+    pub scene: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ModInfo {
+    // Name of the mod directory
+    pub dir: String,
+    pub manifest: ModManifest,
+    pub enabled: bool,
+}
+
+// Mods are enabled unless disabled by the player
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct ModsSettings {
+    disabled: Vec<String>,
+}
+
+/// All mods found on startup. Toggles take
+/// effect after restart.
+#[derive(Debug, Default, Resource)]
+pub struct ModList {
+    pub mods: Vec<ModInfo>,
+}
+
+impl ModList {
+    pub fn enabled(&self) -> impl Iterator<Item = &ModInfo> {
+        self.mods.iter().filter(|mod_info| mod_info.enabled)
+    }
+}
+
+impl ModList {
+    fn scan(settings: &ModsSettings) -> Self {
+        let Ok(entries) = std::fs::read_dir(MODS_DIR) else {
+            return Self::default();
+        };
+
+        let mut mods = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().join(MOD_MANIFEST_FILE).exists())
+            .map(|entry| {
+                let dir = entry.file_name().to_string_lossy().to_string();
+                let manifest_path = entry.path().join(MOD_MANIFEST_FILE);
+                let mut manifest = load_ron::<ModManifest>(&manifest_path.to_string_lossy());
+                if manifest.name.is_empty() {
+                    manifest.name = dir.clone();
+                }
+                ModInfo {
+                    enabled: !settings.disabled.contains(&dir),
+                    dir,
+                    manifest,
+                }
+            })
+            .collect::<Vec<_>>();
+        // mods are applied in the alphabetical order
+        mods.sort_by(|a, b| a.dir.cmp(&b.dir));
+        Self { mods }
+    }
+}
+
+fn apply_mods(
+    asset_server: Res<AssetServer>,
+    mod_list: Res<ModList>,
+    mut weapon_assets: ResMut<WeaponAssets>,
+    mut weapon_registry: ResMut<WeaponRegistry>,
+) {
+    for mod_info in mod_list.enabled() {
+        let weapons_path = Path::new(MODS_DIR)
+            .join(&mod_info.dir)
+            .join(MOD_WEAPONS_FILE);
+        let definitions = load_ron::<Vec<WeaponDefinition>>(&weapons_path.to_string_lossy());
+
+        for definition in definitions {
+            let stats = weapon_registry.stats_mut(definition.weapon);
+            if let Some(ammo) = definition.ammo {
+                stats.ammo = ammo;
+            }
+            if let Some(damage) = definition.damage {
+                stats.damage = damage;
+            }
+            if let Some(attack_speed) = definition.attack_speed {
+                stats.attack_speed = attack_speed;
+            }
+            if let Some(spread) = definition.spread {
+                stats.spread = spread;
+            }
+            if let Some(loudness) = definition.loudness {
+                stats.loudness = loudness;
+            }
+
+            if let Some(scene) = definition.scene {
+                let scene_path = format!("{MODS_ASSET_DIR}/{}/{scene}", mod_info.dir);
+                let scene = asset_server.load(scene_path);
+                match definition.weapon {
+                    WeaponType::Pistol => weapon_assets.pistol_scene = scene,
+                    WeaponType::Shotgun => weapon_assets.shotgun_scene = scene,
+                    WeaponType::Minigun => weapon_assets.minigun_scene = scene,
+                }
+            }
+        }
+        info!("Loaded mod {}", mod_info.manifest.name);
+    }
+}
+
+fn save_mods_settings(mod_list: Res<ModList>) {
+    let settings = ModsSettings {
+        disabled: mod_list
+            .mods
+            .iter()
+            .filter(|mod_info| !mod_info.enabled)
+            .map(|mod_info| mod_info.dir.clone())
+            .collect(),
+    };
+    save_ron(MODS_SETTINGS_PATH, &settings);
+}
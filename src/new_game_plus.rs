@@ -0,0 +1,147 @@
+use bevy::prelude::*;
+
+use crate::{
+    attachments::{Attachments, WeaponAttachment},
+    perks::{Perk, Perks},
+    player::PlayerWeapon,
+    profile::Profile,
+    weapons::{Weapon, WeaponRoll, WeaponType},
+    GlobalState,
+};
+
+pub struct NewGamePlusPlugin;
+
+impl Plugin for NewGamePlusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NewGamePlus>();
+        app.init_resource::<HeldWeapons>();
+
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::MainMenu,
+                to: GlobalState::InGame,
+            },
+            reset_held_weapons,
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::GameOver,
+                to: GlobalState::InGame,
+            },
+            reset_held_weapons,
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::GameWon,
+                to: GlobalState::InGame,
+            },
+            reset_held_weapons,
+        );
+
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::Paused,
+                to: GlobalState::MainMenu,
+            },
+            reset_new_game_plus,
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::GameOver,
+                to: GlobalState::MainMenu,
+            },
+            reset_new_game_plus,
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::GameWon,
+                to: GlobalState::MainMenu,
+            },
+            reset_new_game_plus,
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::Hub,
+                to: GlobalState::MainMenu,
+            },
+            reset_new_game_plus,
+        );
+
+        app.add_systems(
+            Update,
+            track_held_weapons.run_if(in_state(GlobalState::InGame)),
+        );
+    }
+}
+
+/// Weapon the player picked up during the run
+#[derive(Debug, Clone, Copy)]
+pub struct HeldWeapon {
+    pub weapon_type: WeaponType,
+    pub roll: WeaponRoll,
+}
+
+// Weapons that can be carried over to the New Game+.
+// Only the latest roll of each weapon type is kept.
+#[derive(Default, Resource)]
+pub struct HeldWeapons {
+    pub weapons: Vec<HeldWeapon>,
+}
+
+// State carried from a won game into the next one.
+// Stays the same if player dies and restarts.
+#[derive(Default, Resource)]
+pub struct NewGamePlus {
+    pub prestige: u32,
+    pub weapon: WeaponType,
+    // Tutorial weapon gets a random roll
+    // until a weapon is carried over
+    pub weapon_roll: Option<WeaponRoll>,
+    pub attachments: Vec<WeaponAttachment>,
+    pub perks: Vec<Perk>,
+}
+
+impl NewGamePlus {
+    pub fn select_weapon(&mut self, weapon: HeldWeapon) {
+        self.weapon = weapon.weapon_type;
+        self.weapon_roll = Some(weapon.roll);
+    }
+
+    pub fn start(&mut self, perks: &Perks, attachments: &Attachments, profile: &mut Profile) {
+        self.prestige += 1;
+        self.perks = perks.perks.clone();
+        self.attachments = [WeaponAttachment::ExtendedMag, WeaponAttachment::LaserSight]
+            .into_iter()
+            .filter(|attachment| attachments.installed(*attachment))
+            .collect();
+        profile.prestige += 1;
+    }
+}
+
+fn reset_new_game_plus(mut new_game_plus: ResMut<NewGamePlus>) {
+    *new_game_plus = NewGamePlus::default();
+}
+
+fn reset_held_weapons(mut held_weapons: ResMut<HeldWeapons>) {
+    held_weapons.weapons.clear();
+}
+
+fn track_held_weapons(
+    new_weapons: Query<(&Weapon, Option<&WeaponRoll>), Added<PlayerWeapon>>,
+    mut held_weapons: ResMut<HeldWeapons>,
+) {
+    for (weapon, roll) in new_weapons.iter() {
+        let weapon = HeldWeapon {
+            weapon_type: weapon.weapon_type(),
+            roll: roll.copied().unwrap_or_default(),
+        };
+        match held_weapons
+            .weapons
+            .iter_mut()
+            .find(|held| held.weapon_type == weapon.weapon_type)
+        {
+            Some(held) => *held = weapon,
+            None => held_weapons.weapons.push(weapon),
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+
+use crate::{
+    daily::DailyRun,
+    level::{LevelFinished, LevelInfo},
+    new_game_plus::NewGamePlus,
+    rng::{GameRng, RngStream},
+    GlobalState,
+};
+
+// Every N finished levels player can choose a perk
+const PERK_SELECTION_LEVELS: i32 = 3;
+const PERK_CHOICES: usize = 3;
+
+const PERK_FIRE_RATE_MODIFIER: f32 = 1.2;
+const PERK_DAMAGE_MODIFIER: f32 = 1.25;
+const PERK_THROW_DAMAGE_MODIFIER: f32 = 2.0;
+const PERK_MOVEMENT_SPEED_MODIFIER: f32 = 1.15;
+const PERK_LIFESTEAL: i32 = 10;
+const PERK_DAMAGE_TAKEN_MODIFIER: f32 = 0.8;
+const PERK_PIERCE: u32 = 1;
+const PERK_BOUNCES: u32 = 1;
+
+pub struct PerksPlugin;
+
+impl Plugin for PerksPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Perks>();
+        app.init_resource::<PerkChoices>();
+
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::MainMenu,
+                to: GlobalState::InGame,
+            },
+            reset_perks,
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::GameOver,
+                to: GlobalState::InGame,
+            },
+            reset_perks,
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::GameWon,
+                to: GlobalState::InGame,
+            },
+            reset_perks,
+        );
+
+        app.add_systems(
+            Update,
+            perks_trigger_selection.run_if(in_state(GlobalState::InGame)),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Perk {
+    FireRate,
+    Damage,
+    ThrowDamage,
+    MovementSpeed,
+    Lifesteal,
+    Armor,
+    Pierce,
+    Ricochet,
+}
+
+impl Perk {
+    pub const ALL: [Perk; 8] = [
+        Perk::FireRate,
+        Perk::Damage,
+        Perk::ThrowDamage,
+        Perk::MovementSpeed,
+        Perk::Lifesteal,
+        Perk::Armor,
+        Perk::Pierce,
+        Perk::Ricochet,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Perk::FireRate => "Trigger happy",
+            Perk::Damage => "Hollow points",
+            Perk::ThrowDamage => "Strong arm",
+            Perk::MovementSpeed => "Light feet",
+            Perk::Lifesteal => "Defrost",
+            Perk::Armor => "Insulation",
+            Perk::Pierce => "Armor piercing",
+            Perk::Ricochet => "Ricochet",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Perk::FireRate => "+20% fire rate",
+            Perk::Damage => "+25% weapon damage",
+            Perk::ThrowDamage => "Thrown weapons deal double damage",
+            Perk::MovementSpeed => "+15% movement speed",
+            Perk::Lifesteal => "Kills restore 10 HP",
+            Perk::Armor => "-20% damage taken",
+            Perk::Pierce => "Shots go through one more target",
+            Perk::Ricochet => "Shots bounce off walls once",
+        }
+    }
+
+    fn apply(&self, modifiers: &mut PerkModifiers) {
+        match self {
+            Perk::FireRate => modifiers.fire_rate *= PERK_FIRE_RATE_MODIFIER,
+            Perk::Damage => modifiers.damage *= PERK_DAMAGE_MODIFIER,
+            Perk::ThrowDamage => modifiers.throw_damage *= PERK_THROW_DAMAGE_MODIFIER,
+            Perk::MovementSpeed => modifiers.movement_speed *= PERK_MOVEMENT_SPEED_MODIFIER,
+            Perk::Lifesteal => modifiers.lifesteal += PERK_LIFESTEAL,
+            Perk::Armor => modifiers.damage_taken *= PERK_DAMAGE_TAKEN_MODIFIER,
+            Perk::Pierce => modifiers.pierce += PERK_PIERCE,
+            Perk::Ricochet => modifiers.bounces += PERK_BOUNCES,
+        }
+    }
+}
+
+// Combined effect of all selected perks.
+// Damage, weapon and player systems read
+// these values instead of knowing about perks.
+#[derive(Debug, Clone, Copy)]
+pub struct PerkModifiers {
+    pub fire_rate: f32,
+    pub damage: f32,
+    pub throw_damage: f32,
+    pub movement_speed: f32,
+    pub lifesteal: i32,
+    pub damage_taken: f32,
+    pub pierce: u32,
+    pub bounces: u32,
+}
+
+impl Default for PerkModifiers {
+    fn default() -> Self {
+        Self {
+            fire_rate: 1.0,
+            damage: 1.0,
+            throw_damage: 1.0,
+            movement_speed: 1.0,
+            lifesteal: 0,
+            damage_taken: 1.0,
+            pierce: 0,
+            bounces: 0,
+        }
+    }
+}
+
+#[derive(Default, Resource)]
+pub struct Perks {
+    pub perks: Vec<Perk>,
+    pub modifiers: PerkModifiers,
+}
+
+impl Perks {
+    pub fn add(&mut self, perk: Perk) {
+        perk.apply(&mut self.modifiers);
+        self.perks.push(perk);
+    }
+}
+
+#[derive(Default, Resource)]
+pub struct PerkChoices {
+    pub choices: Vec<Perk>,
+}
+
+// Perks carried over to the New Game+
+// are kept after reset. Daily run modifiers
+// are applied on top.
+fn reset_perks(
+    daily_run: Res<DailyRun>,
+    new_game_plus: Res<NewGamePlus>,
+    mut perks: ResMut<Perks>,
+) {
+    *perks = Perks::default();
+    for perk in new_game_plus.perks.iter() {
+        perks.add(*perk);
+    }
+    for modifier in daily_run.modifiers.iter() {
+        modifier.apply(&mut perks.modifiers);
+    }
+}
+
+fn perks_trigger_selection(
+    level_info: Res<LevelInfo>,
+    mut game_rng: ResMut<GameRng>,
+    mut perk_choices: ResMut<PerkChoices>,
+    mut level_finished_events: EventReader<LevelFinished>,
+    mut global_state: ResMut<NextState<GlobalState>>,
+) {
+    for _ in level_finished_events.read() {
+        let finished_levels = level_info.game_progress / 10;
+        if finished_levels == 0 || finished_levels % PERK_SELECTION_LEVELS != 0 {
+            continue;
+        }
+
+        perk_choices.choices = Perk::ALL
+            .choose_multiple(game_rng.stream(RngStream::Perks), PERK_CHOICES)
+            .copied()
+            .collect();
+        global_state.set(GlobalState::PerkSelection);
+    }
+}
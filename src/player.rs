@@ -1,23 +1,128 @@
 use bevy::{
-    core_pipeline::Skybox, input::mouse::MouseMotion, prelude::*, render::view::ColorGrading,
+    core_pipeline::{clear_color::ClearColorConfig, core_3d::Camera3dDepthLoadOp, Skybox},
+    input::mouse::MouseMotion,
+    prelude::*,
+    render::view::{ColorGrading, RenderLayers},
 };
+use bevy_kira_audio::{AudioChannel, AudioInstance};
 use bevy_rapier3d::{prelude::*, rapier::geometry::CollisionEventFlags};
+use serde::{Deserialize, Serialize};
 
 use crate::{
     animation::Animation,
-    damage::{Damage, Health, KillEvent},
-    ui::UiResources,
-    weapons::{floating::FloatingObject, Ammo, ShootEvent, WeaponAttackTimer},
-    GameSettings, GlobalState, COLLISION_GROUP_ENEMY, COLLISION_GROUP_LEVEL,
-    COLLISION_GROUP_PICKUP, COLLISION_GROUP_PLAYER, COLLISION_GROUP_PROJECTILES,
+    checkpoint::{Checkpoint, CheckpointRespawn},
+    collision::{CollisionLayer, COLLISION_GROUP_LEVEL, COLLISION_GROUP_PLAYER},
+    damage::{apply_damage, Damage, Health, KillEvent},
+    enemies::Enemy,
+    freeze::Chill,
+    kill_cam::kill_cam_playing,
+    level::{
+        interaction::InteractionTarget,
+        ladder::{PlayerClimb, PlayerOnLoft},
+        modifiers::LevelModifiers,
+        LevelAssets, LevelInfo,
+    },
+    perks::Perks,
+    powerups::{ActivePowerups, PowerupType},
+    rng::{GameRng, RngStream},
+    sequence::sequence_playing,
+    sound::{SoundLimit, SoundPool},
+    ui::{
+        tablet::{TabletCamera, TabletScreen},
+        UiResources,
+    },
+    weapons::{
+        ammo::AmmoTypes,
+        floating::{FloatingObject, FloatingObjectBundle, FloatingObjectInternal},
+        Ammo, DryFireEvent, Shell, ShootEvent, Weapon, WeaponAttackTimer, WeaponModel,
+    },
+    GameSettings, GlobalState, SfxChannel,
 };
 
-const PLAYER_HEALTH: i32 = 300;
+pub const PLAYER_HEALTH: i32 = 300;
+// Index of the player playing on this machine
+pub const PLAYER_LOCAL_INDEX: usize = 0;
+pub const PLAYER_COLLIDER_RADIUS: f32 = 1.0;
+
+const PLAYER_ACCELERATION: f32 = 50.0;
+const PLAYER_SLOW_DOWN_RATE: f32 = 5.0;
+const PLAYER_MAX_MOVEMENT_SPEED_SQUARED: f32 = 40.0;
+// External pushes fade out over time
+const PLAYER_EXTERNAL_DECAY: f32 = 6.0;
+const PLAYER_EXTERNAL_MAX_SPEED: f32 = 60.0;
+const PLAYER_EXTERNAL_MIN_SPEED: f32 = 0.1;
+
+// Vault over props the player walks into. Lift clears
+// monitors and terminals but keeps the player under the roof.
+const PLAYER_VAULT_REACH: f32 = 0.5;
+// Movement has to point mostly where the player looks
+const PLAYER_VAULT_MIN_FACING: f32 = 0.7;
+const PLAYER_VAULT_HEIGHT: f32 = 2.0;
+// Lofts are deeper, so they are only reached by ladders
+const PLAYER_VAULT_MAX_DEPTH: f32 = 4.0;
+const PLAYER_VAULT_LANDING_STEP: f32 = 0.5;
+const PLAYER_VAULT_SPEED: f32 = 2.5;
 
 const PLAYER_WEAPON_DEFAULT_TRANSLATION: Vec3 = Vec3::new(0.0, -0.8, -1.7);
 const PLAYER_THROW_OFFSET_SCALE: f32 = 10.0;
 const PLAYER_THROW_STRENGTH: f32 = 80.0;
 const PLAYER_THROW_DAMAGE: i32 = 50;
+const PLAYER_THROW_KEY: KeyCode = KeyCode::F;
+// Arc of the throw is shown while the throw key is held
+const PLAYER_THROW_PREVIEW_SECONDS: f32 = 1.5;
+const PLAYER_THROW_PREVIEW_STEPS: usize = 40;
+const PLAYER_THROW_PREVIEW_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.6);
+// Holding the throw key this long drops
+// the weapon instead of throwing it
+const PLAYER_DROP_HOLD_SECONDS: f32 = 0.5;
+
+// Shots pressed slightly before the weapon is ready
+// are remembered and fired as soon as possible
+const PLAYER_FIRE_BUFFER_SECONDS: f32 = 0.12;
+
+const PLAYER_WEAPON_INSPECT_KEY: KeyCode = KeyCode::I;
+const PLAYER_WEAPON_INSPECT_ANIMATION_SPEED: f32 = 1.5;
+const PLAYER_WEAPON_INSPECT_TARGET_OFFSET: Vec3 = Vec3::new(0.3, 0.0, 0.2);
+const PLAYER_WEAPON_INSPECT_TARGET_ROTATION_Y: f32 = std::f32::consts::FRAC_PI_2;
+const PLAYER_WEAPON_INSPECT_TARGET_ROTATION_Z: f32 = std::f32::consts::FRAC_PI_4;
+
+const PLAYER_WEAPON_SWAY_SCALE: f32 = 0.002;
+const PLAYER_WEAPON_SWAY_MAX: f32 = 0.15;
+const PLAYER_WEAPON_SWAY_SPEED: f32 = 10.0;
+const PLAYER_WEAPON_RECOIL: f32 = 0.3;
+const PLAYER_WEAPON_SETTLE_SPEED: f32 = 10.0;
+// Weapon is pulled towards the camera if a wall
+// is closer than this distance
+const PLAYER_WEAPON_PULL_IN_DISTANCE: f32 = 4.0;
+const PLAYER_WEAPON_PULL_IN_MAX: f32 = 1.2;
+const PLAYER_WEAPON_PULL_IN_SPEED: f32 = 10.0;
+// Radius of the sphere cast forward from the camera
+// to find walls close to the weapon and the tablet
+const PLAYER_VIEWMODEL_CAST_RADIUS: f32 = 0.6;
+const PLAYER_HUD_PULL_IN_DISTANCE: f32 = 1.5;
+const PLAYER_HUD_PULL_IN_MAX: f32 = 0.3;
+
+const PLAYER_AIM_SPEED: f32 = 8.0;
+const PLAYER_DEFAULT_FOV: f32 = std::f32::consts::FRAC_PI_4;
+const PLAYER_AIM_FOV: f32 = std::f32::consts::FRAC_PI_6;
+const PLAYER_AIM_SPREAD_MODIFIER: f32 = 0.25;
+const PLAYER_AIM_MOVEMENT_SPEED_MODIFIER: f32 = 0.5;
+
+// Weapon in hands, the tablet and shells near the camera
+// are drawn by a separate camera on top of the world,
+// so they never clip into walls
+const PLAYER_VIEWMODEL_LAYER: u8 = 2;
+const PLAYER_VIEWMODEL_FOV: f32 = std::f32::consts::FRAC_PI_4;
+const PLAYER_VIEWMODEL_NEAR: f32 = 0.05;
+const PLAYER_VIEWMODEL_SHELL_DISTANCE: f32 = 4.0;
+
+// Footsteps are quieter than the rest of the sounds
+const PLAYER_FOOTSTEP_VOLUME: f32 = 0.4;
+const PLAYER_FOOTSTEP_SOUND_LIMIT: SoundLimit = SoundLimit {
+    max_voices: 2,
+    cooldown: 0.1,
+    pitch_variation: 0.1,
+};
 
 const PLAYER_HUD_ANIMATION_SPEED: f32 = 5.0;
 const PLAYER_HUD_ON_TRANSLATION: Vec3 = Vec3::new(0.0, 0.0, -0.45);
@@ -29,6 +134,9 @@ pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
+        app.init_resource::<PlayerTuning>();
+        app.add_event::<PlayerFootstep>();
+
         app.add_systems(
             OnTransition {
                 from: GlobalState::AssetLoading,
@@ -41,22 +149,55 @@ impl Plugin for PlayerPlugin {
         app.add_systems(OnEnter(GlobalState::Paused), player_toggle_hud_on);
         app.add_systems(OnEnter(GlobalState::GameOver), player_toggle_hud_on);
         app.add_systems(OnEnter(GlobalState::GameWon), player_toggle_hud_on);
+        app.add_systems(OnEnter(GlobalState::PerkSelection), player_toggle_hud_on);
 
         app.add_systems(
             Update,
             (
-                player_kills_reading,
+                player_kills_reading.after(apply_damage),
                 player_trigger_pause,
+                player_aim,
                 player_shoot,
                 player_pick_up_weapon,
+                player_throw_preview,
                 player_throw_weapon,
                 player_update,
+                player_vault_start,
+                player_vault_update,
                 player_move,
                 player_camera_update,
+                player_footsteps,
                 player_weapon_update,
+                player_weapon_inspect,
+                player_weapon_inspect_finish,
+                player_viewmodel_layers,
             )
-                .run_if(in_state(GlobalState::InGame)),
+                .run_if(
+                    in_state(GlobalState::InGame)
+                        .and_then(not(sequence_playing))
+                        .and_then(not(kill_cam_playing)),
+                ),
         );
+        app.add_systems(Update, player_apply_tuning);
+    }
+}
+
+/// Movement values applied to the player
+#[derive(Debug, Clone, Copy, PartialEq, Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayerTuning {
+    pub acceleration: f32,
+    pub slow_down_rate: f32,
+    pub max_movement_speed_squared: f32,
+}
+
+impl Default for PlayerTuning {
+    fn default() -> Self {
+        Self {
+            acceleration: PLAYER_ACCELERATION,
+            slow_down_rate: PLAYER_SLOW_DOWN_RATE,
+            max_movement_speed_squared: PLAYER_MAX_MOVEMENT_SPEED_SQUARED,
+        }
     }
 }
 
@@ -68,6 +209,20 @@ pub struct PlayerResources {
     pub hud_tablet_arm_material: Handle<StandardMaterial>,
 }
 
+/// Index of the player the entity belongs to. Player,
+/// its camera, tablet and weapon in hands have it, so
+/// systems find the parts of each player with it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct PlayerId(pub usize);
+
+impl PlayerId {
+    /// Player playing on this machine, the
+    /// screen HUD shows only this player
+    pub fn is_local(&self) -> bool {
+        self.0 == PLAYER_LOCAL_INDEX
+    }
+}
+
 #[derive(Component)]
 pub struct Player {
     pub acceleration: f32,
@@ -75,10 +230,46 @@ pub struct Player {
     pub max_movement_speed_squared: f32,
 }
 
+// Aim down sights progress goes from 0 to 1
+// while right mouse button is held
+#[derive(Component)]
+pub struct PlayerAim {
+    pub aiming: bool,
+    pub progress: f32,
+}
+
 #[derive(Component)]
 pub struct PlayerVelocity {
     pub was_input: bool,
     pub velocity: Vec3,
+    // Velocity from knockbacks, not affected by input
+    pub external: Vec3,
+    // Velocity of the conveyor or platform under the player
+    pub floor: Vec3,
+}
+
+impl PlayerVelocity {
+    /// Pushes the player along the floor
+    pub fn push(&mut self, impulse: Vec3) {
+        self.external = (self.external + impulse.truncate().extend(0.0))
+            .clamp_length_max(PLAYER_EXTERNAL_MAX_SPEED);
+    }
+}
+
+// Player moves over the prop from `start` to `end`,
+// normal movement is off until the vault is finished
+#[derive(Component)]
+pub struct PlayerVault {
+    start: Vec3,
+    end: Vec3,
+    progress: f32,
+}
+
+/// Sent each time the camera bounce
+/// reaches its lowest point
+#[derive(Event)]
+pub struct PlayerFootstep {
+    pub player_id: PlayerId,
 }
 
 #[derive(Component)]
@@ -106,8 +297,23 @@ pub struct PlayerWeapon {
     pub bounce_progress: f32,
     pub bounce_speed: f32,
     pub bounce_amplitude: f32,
+
+    pub sway: f32,
+    pub recoil: f32,
+    pub pull_in: f32,
 }
 
+// Weapon can not shoot while inspected, so
+// shooting animation does not start from
+// the middle of inspection
+#[derive(Component)]
+struct PlayerWeaponInspect;
+
+// Dropped weapon is not picked up again
+// until the player walks away from it
+#[derive(Component)]
+struct PlayerDroppedWeapon;
+
 #[derive(Bundle)]
 struct PlayerThrownWeapon {
     transform: Transform,
@@ -119,30 +325,36 @@ struct PlayerThrownWeapon {
     damage: Damage,
 }
 
+// Start position and velocity of the thrown weapon
+fn throw_start(
+    weapon_global_transform: &GlobalTransform,
+    camera_global_transform: &GlobalTransform,
+) -> (Vec3, Vec3) {
+    (
+        weapon_global_transform.translation()
+            + camera_global_transform.forward() * PLAYER_THROW_OFFSET_SCALE,
+        camera_global_transform.forward() * PLAYER_THROW_STRENGTH,
+    )
+}
+
 impl PlayerThrownWeapon {
     fn new(
         weapon_global_transform: &GlobalTransform,
         camera_global_transform: &GlobalTransform,
+        damage: i32,
     ) -> Self {
+        let (translation, linvel) = throw_start(weapon_global_transform, camera_global_transform);
         Self {
-            transform: Transform::from_translation(
-                weapon_global_transform.translation()
-                    + camera_global_transform.forward() * PLAYER_THROW_OFFSET_SCALE,
-            ),
+            transform: Transform::from_translation(translation),
             collider: Collider::cuboid(0.6, 2.6, 0.3),
-            collision_groups: CollisionGroups::new(
-                COLLISION_GROUP_PROJECTILES,
-                COLLISION_GROUP_LEVEL | COLLISION_GROUP_ENEMY,
-            ),
+            collision_groups: CollisionLayer::Projectile.groups(),
             active_events: ActiveEvents::COLLISION_EVENTS,
             rigid_body: RigidBody::Dynamic,
             velocity: Velocity {
-                linvel: camera_global_transform.forward() * PLAYER_THROW_STRENGTH,
+                linvel,
                 ..default()
             },
-            damage: Damage {
-                damage: PLAYER_THROW_DAMAGE,
-            },
+            damage: Damage { damage },
         }
     }
 }
@@ -153,31 +365,42 @@ pub fn spawn_player(
     skybox_image: Handle<Image>,
     commands: &mut Commands,
     mut transform: Transform,
+    player_index: usize,
 ) {
+    let player_id = PlayerId(player_index);
     transform.translation.z -= 0.5;
     let id = commands
         .spawn((
             TransformBundle::from_transform(transform),
             InheritedVisibility::VISIBLE,
             RigidBody::KinematicPositionBased,
-            Collider::capsule(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 2.0), 1.0),
-            CollisionGroups::new(
-                COLLISION_GROUP_PLAYER,
-                COLLISION_GROUP_LEVEL | COLLISION_GROUP_PROJECTILES | COLLISION_GROUP_PICKUP,
+            Collider::capsule(
+                Vec3::new(0.0, 0.0, 0.0),
+                Vec3::new(0.0, 0.0, 2.0),
+                PLAYER_COLLIDER_RADIUS,
             ),
+            CollisionLayer::Player.groups(),
             ActiveCollisionTypes::KINEMATIC_STATIC | ActiveCollisionTypes::DYNAMIC_KINEMATIC,
             Player {
-                acceleration: 50.0,
-                slow_down_rade: 5.0,
-                max_movement_speed_squared: 40.0,
+                acceleration: PLAYER_ACCELERATION,
+                slow_down_rade: PLAYER_SLOW_DOWN_RATE,
+                max_movement_speed_squared: PLAYER_MAX_MOVEMENT_SPEED_SQUARED,
             },
             PlayerVelocity {
                 was_input: false,
                 velocity: Vec3::default(),
+                external: Vec3::default(),
+                floor: Vec3::default(),
+            },
+            PlayerAim {
+                aiming: false,
+                progress: 0.0,
             },
             Health {
                 health: PLAYER_HEALTH,
             },
+            InteractionTarget::default(),
+            player_id,
         ))
         .with_children(|builder| {
             builder
@@ -195,6 +418,12 @@ pub fn spawn_player(
                     },
                     UiCameraConfig { show_ui: false },
                     Skybox(skybox_image),
+                    // set to the fog of the current level by the atmosphere
+                    FogSettings {
+                        color: Color::NONE,
+                        falloff: FogFalloff::Exponential { density: 0.0 },
+                        ..default()
+                    },
                     PlayerCamera {
                         default_translation: Vec3::new(0.0, 0.0, 2.0),
 
@@ -207,8 +436,34 @@ pub fn spawn_player(
                         bounce_amplitude_modifier_speed: 1.0,
                         bounce_amplitude_modifier_max: 2.0,
                     },
+                    player_id,
                 ))
                 .with_children(|builder| {
+                    // Viewmodel camera
+                    builder.spawn((
+                        Camera3dBundle {
+                            camera: Camera {
+                                order: 1,
+                                ..default()
+                            },
+                            camera_3d: Camera3d {
+                                clear_color: ClearColorConfig::None,
+                                depth_load_op: Camera3dDepthLoadOp::Clear(0.0),
+                                ..default()
+                            },
+                            projection: PerspectiveProjection {
+                                fov: PLAYER_VIEWMODEL_FOV,
+                                near: PLAYER_VIEWMODEL_NEAR,
+                                ..default()
+                            }
+                            .into(),
+                            ..default()
+                        },
+                        UiCameraConfig { show_ui: false },
+                        RenderLayers::layer(PLAYER_VIEWMODEL_LAYER),
+                        TabletCamera,
+                    ));
+
                     // Tablet
                     builder
                         .spawn((
@@ -219,15 +474,21 @@ pub fn spawn_player(
                                 ..default()
                             },
                             PlayerHud,
+                            player_id,
                         ))
                         .with_children(|builder| {
                             // UI window
-                            builder.spawn((PbrBundle {
-                                mesh: ui_resources.mesh.clone(),
-                                material: ui_resources.material.clone(),
-                                transform: Transform::from_translation(Vec3::new(0.0, 0.0, 0.06)),
-                                ..default()
-                            },));
+                            builder.spawn((
+                                PbrBundle {
+                                    mesh: ui_resources.mesh.clone(),
+                                    material: ui_resources.material.clone(),
+                                    transform: Transform::from_translation(Vec3::new(
+                                        0.0, 0.0, 0.06,
+                                    )),
+                                    ..default()
+                                },
+                                TabletScreen,
+                            ));
                             // Tablet arm
                             builder.spawn((PbrBundle {
                                 mesh: player_resources.hud_tablet_arm_mesh.clone(),
@@ -240,24 +501,30 @@ pub fn spawn_player(
                             },));
                         });
                 });
-
-            // disabled camera for ui interaction
-            builder.spawn((Camera3dBundle {
-                transform: Transform::from_xyz(0.0, -20.0, 3.0)
-                    .looking_at(Vec3::new(0.0, 0.0, 0.0), Vec3::Z),
-                camera: Camera {
-                    order: 99,
-                    is_active: false,
-                    ..default()
-                },
-                ..default()
-            },));
         })
         .id();
 
     commands.entity(id).log_components();
 }
 
+// Tuning and the slow down modifier of the level (icy floors make
+// the player slide) are reapplied when either of them changes and
+// to newly spawned players, as those start with default values
+fn player_apply_tuning(
+    player_tuning: Res<PlayerTuning>,
+    level_modifiers: Res<LevelModifiers>,
+    mut players: Query<&mut Player>,
+) {
+    for mut player in players.iter_mut() {
+        if !player_tuning.is_changed() && !level_modifiers.is_changed() && !player.is_added() {
+            continue;
+        }
+        player.acceleration = player_tuning.acceleration;
+        player.slow_down_rade = player_tuning.slow_down_rate * level_modifiers.slow_down_modifier();
+        player.max_movement_speed_squared = player_tuning.max_movement_speed_squared;
+    }
+}
+
 fn init_resources(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
@@ -297,117 +564,235 @@ fn player_trigger_pause(
     }
 }
 
-fn player_toggle_hud_on(hud: Query<Entity, With<PlayerHud>>, mut commands: Commands) {
-    let Ok(hud) = hud.get_single() else {
-        return;
+/// How far the viewmodel needs to be pulled towards the
+/// camera to not clip into the wall in front of it.
+/// A sphere is cast, so walls at the edges of the
+/// viewmodel are found as well.
+fn viewmodel_pull_in(
+    rapier_context: &RapierContext,
+    camera_global_transform: &GlobalTransform,
+    player: Entity,
+    distance: f32,
+    max: f32,
+) -> f32 {
+    let filter = QueryFilter {
+        flags: QueryFilterFlags::EXCLUDE_SENSORS | QueryFilterFlags::EXCLUDE_DYNAMIC,
+        groups: Some(CollisionGroups::new(
+            COLLISION_GROUP_PLAYER,
+            COLLISION_GROUP_LEVEL,
+        )),
+        exclude_collider: Some(player),
+        ..default()
     };
+    rapier_context
+        .cast_shape(
+            camera_global_transform.translation(),
+            Quat::IDENTITY,
+            camera_global_transform.forward(),
+            &Collider::ball(PLAYER_VIEWMODEL_CAST_RADIUS),
+            distance,
+            true,
+            filter,
+        )
+        .map_or(0.0, |(_, hit)| (distance - hit.toi).min(max))
+}
 
-    let target_transform = Transform::from_translation(PLAYER_HUD_ON_TRANSLATION);
-    let initial_transform = Transform::from_translation(PLAYER_HUD_OFF_TRANSLATION).with_rotation(
-        Quat::from_rotation_y(PLAYER_HUD_OFF_ROTATION_Y)
-            * Quat::from_rotation_x(PLAYER_HUD_OFF_ROTATION_X),
-    );
-
-    let Some(mut e) = commands.get_entity(hud) else {
-        return;
+// Meshes of scenes are spawned asynchronously,
+// so layers are updated every frame
+#[allow(clippy::too_many_arguments)]
+fn player_viewmodel_layers(
+    player_cameras: Query<&GlobalTransform, With<PlayerCamera>>,
+    viewmodels: Query<Entity, Or<(With<PlayerWeapon>, With<PlayerHud>)>>,
+    shells: Query<(Entity, &GlobalTransform), With<Shell>>,
+    children: Query<&Children>,
+    meshes: Query<Option<&RenderLayers>, With<Handle<Mesh>>>,
+    mut commands: Commands,
+    mut removed_player_weapons: RemovedComponents<PlayerWeapon>,
+) {
+    let mut set_layers = |root: Entity, layers: RenderLayers| {
+        for entity in std::iter::once(root).chain(children.iter_descendants(root)) {
+            let Ok(current_layers) = meshes.get(entity) else {
+                continue;
+            };
+            if current_layers.copied().unwrap_or_default() != layers {
+                commands.entity(entity).insert(layers);
+            }
+        }
     };
 
-    e.insert(Animation {
-        animate_forward: true,
-        animate_backward: false,
-        animation_speed: PLAYER_HUD_ANIMATION_SPEED,
-        progress: 0.0,
-        initial_transform,
-        target_transform,
-    });
+    let viewmodel_layers = RenderLayers::layer(PLAYER_VIEWMODEL_LAYER);
+    for viewmodel in viewmodels.iter() {
+        set_layers(viewmodel, viewmodel_layers);
+    }
+
+    // thrown weapons go back to the world
+    for weapon in removed_player_weapons.read() {
+        set_layers(weapon, RenderLayers::default());
+    }
+
+    for (shell, shell_global_transform) in shells.iter() {
+        let near_camera = player_cameras.iter().any(|camera_global_transform| {
+            camera_global_transform
+                .translation()
+                .distance(shell_global_transform.translation())
+                < PLAYER_VIEWMODEL_SHELL_DISTANCE
+        });
+        // shells close to the camera are drawn by both
+        // cameras to not disappear behind the weapon
+        let layers = if near_camera {
+            RenderLayers::from_layers(&[0, PLAYER_VIEWMODEL_LAYER])
+        } else {
+            RenderLayers::default()
+        };
+        set_layers(shell, layers);
+    }
 }
 
-fn player_toggle_hud_off(hud: Query<Entity, With<PlayerHud>>, mut commands: Commands) {
-    let Ok(hud) = hud.get_single() else {
-        return;
-    };
+fn player_toggle_hud_on(
+    rapier_context: Res<RapierContext>,
+    players: Query<(Entity, &PlayerId), With<Player>>,
+    player_cameras: Query<(&PlayerId, &GlobalTransform), With<PlayerCamera>>,
+    huds: Query<(Entity, &PlayerId), With<PlayerHud>>,
+    mut commands: Commands,
+) {
+    for (hud, player_id) in huds.iter() {
+        // tablet is pulled in the same way the weapon is
+        let player = players.iter().find(|(_, id)| *id == player_id);
+        let camera = player_cameras.iter().find(|(id, _)| *id == player_id);
+        let pull_in = match (player, camera) {
+            (Some((player, _)), Some((_, camera_global_transform))) => viewmodel_pull_in(
+                rapier_context.as_ref(),
+                camera_global_transform,
+                player,
+                PLAYER_HUD_PULL_IN_DISTANCE,
+                PLAYER_HUD_PULL_IN_MAX,
+            ),
+            _ => 0.0,
+        };
 
-    let initial_transform = Transform::from_translation(PLAYER_HUD_ON_TRANSLATION);
-    let target_transform = Transform::from_translation(PLAYER_HUD_OFF_TRANSLATION).with_rotation(
-        Quat::from_rotation_y(PLAYER_HUD_OFF_ROTATION_Y)
-            * Quat::from_rotation_x(PLAYER_HUD_OFF_ROTATION_X),
-    );
+        let target_transform =
+            Transform::from_translation(PLAYER_HUD_ON_TRANSLATION + Vec3::Z * pull_in);
+        let initial_transform = Transform::from_translation(PLAYER_HUD_OFF_TRANSLATION)
+            .with_rotation(
+                Quat::from_rotation_y(PLAYER_HUD_OFF_ROTATION_Y)
+                    * Quat::from_rotation_x(PLAYER_HUD_OFF_ROTATION_X),
+            );
 
-    let Some(mut e) = commands.get_entity(hud) else {
-        return;
-    };
+        let Some(mut e) = commands.get_entity(hud) else {
+            continue;
+        };
 
-    e.insert(Animation {
-        animate_forward: true,
-        animate_backward: false,
-        animation_speed: PLAYER_HUD_ANIMATION_SPEED,
-        progress: 0.0,
-        initial_transform,
-        target_transform,
-    });
+        e.insert(Animation {
+            animate_forward: true,
+            animate_backward: false,
+            animation_speed: PLAYER_HUD_ANIMATION_SPEED,
+            progress: 0.0,
+            initial_transform,
+            target_transform,
+        });
+    }
+}
+
+fn player_toggle_hud_off(huds: Query<Entity, With<PlayerHud>>, mut commands: Commands) {
+    for hud in huds.iter() {
+        let initial_transform = Transform::from_translation(PLAYER_HUD_ON_TRANSLATION);
+        let target_transform = Transform::from_translation(PLAYER_HUD_OFF_TRANSLATION)
+            .with_rotation(
+                Quat::from_rotation_y(PLAYER_HUD_OFF_ROTATION_Y)
+                    * Quat::from_rotation_x(PLAYER_HUD_OFF_ROTATION_X),
+            );
+
+        let Some(mut e) = commands.get_entity(hud) else {
+            continue;
+        };
+
+        e.insert(Animation {
+            animate_forward: true,
+            animate_backward: false,
+            animation_speed: PLAYER_HUD_ANIMATION_SPEED,
+            progress: 0.0,
+            initial_transform,
+            target_transform,
+        });
+    }
 }
 
-fn player_kills_reading(
-    mut player: Query<Entity, With<Player>>,
+#[allow(clippy::too_many_arguments)]
+pub fn player_kills_reading(
+    perks: Res<Perks>,
+    checkpoint: Res<Checkpoint>,
+    players: Query<&PlayerId, With<Player>>,
+    mut player_healths: Query<&mut Health, With<Player>>,
+    enemies: Query<(), With<Enemy>>,
     mut kill_events: EventReader<KillEvent>,
+    mut checkpoint_respawn_events: EventWriter<CheckpointRespawn>,
     mut global_state: ResMut<NextState<GlobalState>>,
 ) {
-    let Ok(player) = player.get_single_mut() else {
-        return;
-    };
-
     for kill_event in kill_events.read() {
-        if kill_event.entity == player {
-            global_state.set(GlobalState::GameOver);
+        if let Ok(player_id) = players.get(kill_event.entity) {
+            if checkpoint.can_respawn(*player_id) {
+                checkpoint_respawn_events.send(CheckpointRespawn(*player_id));
+            } else {
+                global_state.set(GlobalState::GameOver);
+            }
+        } else if enemies.contains(kill_event.entity) {
+            for mut health in player_healths.iter_mut() {
+                health.health = (health.health + perks.modifiers.lifesteal).min(PLAYER_HEALTH);
+            }
         }
     }
 }
 
 fn player_pick_up_weapon(
-    player: Query<Entity, With<Player>>,
-    player_camera: Query<Entity, With<PlayerCamera>>,
-    player_weapon: Query<Entity, With<PlayerWeapon>>,
-    floating_objects: Query<(Entity, &Children), With<FloatingObject>>,
+    players: Query<(Entity, &PlayerId), With<Player>>,
+    player_cameras: Query<(Entity, &PlayerId), With<PlayerCamera>>,
+    player_weapons: Query<&PlayerId, With<PlayerWeapon>>,
+    floating_objects: Query<(Entity, &Children, Has<PlayerDroppedWeapon>), With<FloatingObject>>,
     mut commands: Commands,
     mut collision_events: EventReader<CollisionEvent>,
 ) {
-    // if there is already a weapon, do nothing
-    if player_weapon.get_single().is_ok() {
-        return;
-    }
-
-    let Ok(player) = player.get_single() else {
-        return;
-    };
-
-    let Ok(camera) = player_camera.get_single() else {
-        return;
-    };
+    // weapons picked up this frame, events can
+    // have several weapons touching one player
+    let mut armed = player_weapons.iter().copied().collect::<Vec<_>>();
 
     for collision_event in collision_events.read() {
-        let (collider_1, collider_2, flags) = match collision_event {
-            CollisionEvent::Started(c1, c2, f) => (c1, c2, f),
-            CollisionEvent::Stopped(c1, c2, f) => (c1, c2, f),
+        let (collider_1, collider_2, flags, started) = match collision_event {
+            CollisionEvent::Started(c1, c2, f) => (c1, c2, f, true),
+            CollisionEvent::Stopped(c1, c2, f) => (c1, c2, f, false),
         };
 
         if flags.contains(CollisionEventFlags::REMOVED)
             || !flags.contains(CollisionEventFlags::SENSOR)
         {
-            return;
+            continue;
         }
-        let (floating_object_entity, floating_object_children) = if collider_1 == &player {
-            if let Ok(w) = floating_objects.get(*collider_2) {
-                w
-            } else {
-                continue;
-            }
-        } else if collider_2 == &player {
-            if let Ok(w) = floating_objects.get(*collider_1) {
-                w
+        let (player_id, floating_object_entity) =
+            if let Ok((_, player_id)) = players.get(*collider_1) {
+                (*player_id, *collider_2)
+            } else if let Ok((_, player_id)) = players.get(*collider_2) {
+                (*player_id, *collider_1)
             } else {
                 continue;
+            };
+
+        // if there is already a weapon, do nothing
+        if armed.contains(&player_id) {
+            continue;
+        }
+        let Ok((floating_object_entity, floating_object_children, dropped)) =
+            floating_objects.get(floating_object_entity)
+        else {
+            continue;
+        };
+        if dropped {
+            if !started {
+                commands
+                    .entity(floating_object_entity)
+                    .remove::<PlayerDroppedWeapon>();
             }
-        } else {
+            continue;
+        }
+        let Some((camera, _)) = player_cameras.iter().find(|(_, id)| **id == player_id) else {
             continue;
         };
 
@@ -429,267 +814,750 @@ fn player_pick_up_weapon(
                 bounce_progress: 0.0,
                 bounce_speed: 4.0,
                 bounce_amplitude: 0.08,
+                sway: 0.0,
+                recoil: 0.0,
+                pull_in: 0.0,
             },
+            player_id,
             Transform::default().with_rotation(Quat::from_rotation_x(-std::f32::consts::FRAC_PI_2)),
         ));
 
         commands.entity(camera).add_child(weapon_entity);
+        armed.push(player_id);
     }
 }
 
+/// Takes the weapon out of the player hands and puts it
+/// into a floating pickup at the `translation`
+pub fn drop_player_weapon(
+    commands: &mut Commands,
+    camera: Entity,
+    weapon: Entity,
+    translation: Vec3,
+) -> Entity {
+    commands.entity(camera).remove_children(&[weapon]);
+    // ammo stays on the weapon
+    commands
+        .entity(weapon)
+        .remove::<(PlayerWeapon, PlayerId)>()
+        .insert((Transform::default(), FloatingObjectInternal));
+    commands
+        .spawn(FloatingObjectBundle::new(translation))
+        .add_child(weapon)
+        .id()
+}
+
+// Dotted line along the path of the thrown
+// weapon up to the first thing it hits
+fn player_throw_preview(
+    keys: Res<Input<KeyCode>>,
+    rapier_context: Res<RapierContext>,
+    rapier_config: Res<RapierConfiguration>,
+    player_cameras: Query<(&PlayerId, &GlobalTransform), With<PlayerCamera>>,
+    player_weapons: Query<(&PlayerId, &GlobalTransform), With<PlayerWeapon>>,
+    mut gizmos: Gizmos,
+) {
+    if !keys.pressed(PLAYER_THROW_KEY) {
+        return;
+    }
+
+    for (player_id, weapon_global_transform) in player_weapons.iter() {
+        let Some((_, camera_global_transform)) =
+            player_cameras.iter().find(|(id, _)| *id == player_id)
+        else {
+            continue;
+        };
+
+        let (start, velocity) = throw_start(weapon_global_transform, camera_global_transform);
+        let filter = QueryFilter {
+            flags: QueryFilterFlags::EXCLUDE_SENSORS,
+            groups: Some(CollisionLayer::Projectile.groups()),
+            ..default()
+        };
+        let step = PLAYER_THROW_PREVIEW_SECONDS / PLAYER_THROW_PREVIEW_STEPS as f32;
+        let mut previous = start;
+        for i in 1..=PLAYER_THROW_PREVIEW_STEPS {
+            let t = step * i as f32;
+            let next = start + velocity * t + rapier_config.gravity * t * t * 0.5;
+
+            let segment = next - previous;
+            let hit = rapier_context.cast_ray(previous, segment, 1.0, true, filter);
+            let end = hit.map(|(_, toi)| previous + segment * toi).unwrap_or(next);
+            // every other segment is skipped to make the line dotted
+            if i % 2 == 1 {
+                gizmos.line(previous, end, PLAYER_THROW_PREVIEW_COLOR);
+            }
+            if hit.is_some() {
+                break;
+            }
+            previous = next;
+        }
+    }
+}
+
+// Tap throws the weapon, hold drops it
+#[allow(clippy::too_many_arguments)]
 fn player_throw_weapon(
+    time: Res<Time>,
     keys: Res<Input<KeyCode>>,
-    player_camera: Query<(Entity, &GlobalTransform), With<PlayerCamera>>,
-    player_weapon_components: Query<(Entity, &GlobalTransform), With<PlayerWeapon>>,
+    perks: Res<Perks>,
+    players: Query<(&PlayerId, &GlobalTransform), With<Player>>,
+    player_cameras: Query<(Entity, &PlayerId, &GlobalTransform), With<PlayerCamera>>,
+    player_weapons: Query<(Entity, &PlayerId, &GlobalTransform), With<PlayerWeapon>>,
     mut commands: Commands,
+    mut pressed_at: Local<Option<f32>>,
 ) {
-    let Ok((camera, camera_global_transform)) = player_camera.get_single() else {
+    if keys.just_pressed(PLAYER_THROW_KEY) {
+        *pressed_at = Some(time.elapsed_seconds());
+    }
+    let Some(pressed_time) = *pressed_at else {
         return;
     };
 
-    let Ok((weapon, weapon_global_transform)) = player_weapon_components.get_single() else {
+    let drop = PLAYER_DROP_HOLD_SECONDS <= time.elapsed_seconds() - pressed_time;
+    // thrown on release, so the arc can be aimed first
+    if !drop && !keys.just_released(PLAYER_THROW_KEY) {
         return;
-    };
+    }
+    *pressed_at = None;
 
-    if keys.just_pressed(KeyCode::F) {
-        commands
-            .get_entity(camera)
-            .unwrap()
-            .remove_children(&[weapon]);
-
-        commands
-            .get_entity(weapon)
-            .unwrap()
-            .remove::<PlayerWeapon>()
-            .insert(PlayerThrownWeapon::new(
-                weapon_global_transform,
-                camera_global_transform,
-            ));
+    for (weapon, player_id, weapon_global_transform) in player_weapons.iter() {
+        let Some((camera, _, camera_global_transform)) =
+            player_cameras.iter().find(|(_, id, _)| *id == player_id)
+        else {
+            continue;
+        };
+
+        if drop {
+            let Some((_, player_global_transform)) =
+                players.iter().find(|(id, _)| *id == player_id)
+            else {
+                continue;
+            };
+
+            let pickup = drop_player_weapon(
+                &mut commands,
+                camera,
+                weapon,
+                player_global_transform.translation(),
+            );
+            commands.entity(pickup).insert(PlayerDroppedWeapon);
+        } else {
+            commands
+                .get_entity(camera)
+                .unwrap()
+                .remove_children(&[weapon]);
+            commands
+                .get_entity(weapon)
+                .unwrap()
+                .remove::<(PlayerWeapon, PlayerId)>()
+                .insert(PlayerThrownWeapon::new(
+                    weapon_global_transform,
+                    camera_global_transform,
+                    (PLAYER_THROW_DAMAGE as f32 * perks.modifiers.throw_damage) as i32,
+                ));
+        }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn player_shoot(
+    time: Res<Time>,
     keys: Res<Input<KeyCode>>,
-    player_camera: Query<&GlobalTransform, With<PlayerCamera>>,
-    mut player_weapon_components: Query<
-        (Entity, &GlobalTransform, &mut WeaponAttackTimer, &mut Ammo),
-        With<PlayerWeapon>,
+    active_powerups: Res<ActivePowerups>,
+    players: Query<(&PlayerId, &PlayerAim)>,
+    player_cameras: Query<(&PlayerId, &GlobalTransform), With<PlayerCamera>>,
+    mut player_weapons: Query<
+        (
+            Entity,
+            &PlayerId,
+            &Weapon,
+            &GlobalTransform,
+            &mut WeaponAttackTimer,
+            &mut Ammo,
+            &mut AmmoTypes,
+        ),
+        (With<PlayerWeapon>, Without<PlayerWeaponInspect>),
     >,
     mut shoot_event: EventWriter<ShootEvent>,
+    mut dry_fire_events: EventWriter<DryFireEvent>,
+    mut fire_buffered_at: Local<Option<f32>>,
 ) {
-    let Ok(camera_global_transform) = player_camera.get_single() else {
-        return;
-    };
+    if keys.just_pressed(KeyCode::Space) {
+        *fire_buffered_at = Some(time.elapsed_seconds());
+    }
+    let fire_buffered =
+        fire_buffered_at.is_some_and(|t| time.elapsed_seconds() - t <= PLAYER_FIRE_BUFFER_SECONDS);
+
+    let infinite_ammo = active_powerups.is_active(PowerupType::InfiniteAmmo);
+    for (
+        weapon_entity,
+        player_id,
+        weapon,
+        weapon_global_transform,
+        mut weapon_attack_timer,
+        mut ammo,
+        mut ammo_types,
+    ) in player_weapons.iter_mut()
+    {
+        let Some((_, camera_global_transform)) =
+            player_cameras.iter().find(|(id, _)| *id == player_id)
+        else {
+            continue;
+        };
 
-    let Ok((weapon_entity, weapon_global_transform, mut weapon_attack_timer, mut ammo)) =
-        player_weapon_components.get_single_mut()
-    else {
-        return;
-    };
+        let Some((_, aim)) = players.iter().find(|(id, _)| *id == player_id) else {
+            continue;
+        };
 
-    if keys.pressed(KeyCode::Space) && weapon_attack_timer.ready && ammo.ammo != 0 {
-        weapon_attack_timer.attack_timer.reset();
-        weapon_attack_timer.ready = false;
-        ammo.ammo -= 1;
-        shoot_event.send(ShootEvent {
-            weapon_entity,
-            weapon_translation: weapon_global_transform.translation(),
-            direction: camera_global_transform.forward(),
-        });
+        // only a new pull of the trigger clicks
+        if keys.just_pressed(KeyCode::Space) && ammo.ammo == 0 && !infinite_ammo {
+            dry_fire_events.send(DryFireEvent { weapon_entity });
+        }
+
+        if (keys.pressed(KeyCode::Space) || fire_buffered)
+            && weapon_attack_timer.ready
+            && (ammo.ammo != 0 || infinite_ammo)
+        {
+            *fire_buffered_at = None;
+            weapon_attack_timer.attack_timer.reset();
+            weapon_attack_timer.ready = false;
+            if !infinite_ammo {
+                ammo.ammo -= 1;
+            }
+            shoot_event.send(ShootEvent {
+                weapon_entity,
+                weapon_translation: weapon_global_transform.translation(),
+                direction: camera_global_transform.forward(),
+                spread: weapon.spread() * (1.0 - (1.0 - PLAYER_AIM_SPREAD_MODIFIER) * aim.progress),
+                ammo_type: ammo_types.take(),
+            });
+        }
+    }
+}
+
+fn player_aim(
+    time: Res<Time>,
+    mouse: Res<Input<MouseButton>>,
+    player_weapons: Query<&PlayerId, With<PlayerWeapon>>,
+    mut players: Query<(&PlayerId, &mut PlayerAim)>,
+    mut player_cameras: Query<(&PlayerId, &mut Projection), With<PlayerCamera>>,
+) {
+    for (player_id, mut aim) in players.iter_mut() {
+        let Some((_, mut projection)) = player_cameras.iter_mut().find(|(id, _)| *id == player_id)
+        else {
+            continue;
+        };
+
+        aim.aiming =
+            mouse.pressed(MouseButton::Right) && player_weapons.iter().any(|id| id == player_id);
+        let delta = PLAYER_AIM_SPEED * time.delta_seconds();
+        aim.progress = if aim.aiming {
+            (aim.progress + delta).min(1.0)
+        } else {
+            (aim.progress - delta).max(0.0)
+        };
+
+        if let Projection::Perspective(perspective) = projection.as_mut() {
+            perspective.fov =
+                PLAYER_DEFAULT_FOV + (PLAYER_AIM_FOV - PLAYER_DEFAULT_FOV) * aim.progress;
+        }
     }
 }
 
 fn player_update(
     time: Res<Time>,
     keys: Res<Input<KeyCode>>,
-    player_camera_components: Query<&Transform, With<PlayerCamera>>,
-    mut player_components: Query<(&Player, &mut PlayerVelocity)>,
+    perks: Res<Perks>,
+    player_cameras: Query<(&PlayerId, &Transform), With<PlayerCamera>>,
+    mut players: Query<(
+        &PlayerId,
+        &Player,
+        &PlayerAim,
+        Option<&Chill>,
+        &mut PlayerVelocity,
+    )>,
 ) {
-    let Ok((player, mut velocity)) = player_components.get_single_mut() else {
-        return;
-    };
+    for (player_id, player, aim, chill, mut velocity) in players.iter_mut() {
+        let Some((_, camera_transform)) = player_cameras.iter().find(|(id, _)| *id == player_id)
+        else {
+            continue;
+        };
 
-    let Ok(camera_transform) = player_camera_components.get_single() else {
-        return;
-    };
+        // slow down
+        let velocity_copy = velocity.velocity;
+        velocity.velocity -= velocity_copy * player.slow_down_rade * time.delta_seconds();
 
-    // slow down
-    let velocity_copy = velocity.velocity;
-    velocity.velocity -= velocity_copy * player.slow_down_rade * time.delta_seconds();
+        let forward = camera_transform.forward();
+        let right = forward.cross(Vec3::Z);
 
-    let forward = camera_transform.forward();
-    let right = forward.cross(Vec3::Z);
+        let mut movement = Vec3::ZERO;
+        if keys.pressed(KeyCode::W) {
+            movement += forward;
+        }
+        if keys.pressed(KeyCode::S) {
+            movement -= forward;
+        }
+        if keys.pressed(KeyCode::A) {
+            movement -= right;
+        }
+        if keys.pressed(KeyCode::D) {
+            movement += right;
+        }
 
-    let mut movement = Vec3::ZERO;
-    if keys.pressed(KeyCode::W) {
-        movement += forward;
-    }
-    if keys.pressed(KeyCode::S) {
-        movement -= forward;
-    }
-    if keys.pressed(KeyCode::A) {
-        movement -= right;
-    }
-    if keys.pressed(KeyCode::D) {
-        movement += right;
-    }
+        movement.z = 0.0;
+        if movement == Vec3::ZERO {
+            velocity.was_input = false;
+            continue;
+        }
 
-    movement.z = 0.0;
-    if movement == Vec3::ZERO {
-        velocity.was_input = false;
-        return;
+        movement = movement.normalize();
+        velocity.velocity = movement * player.acceleration * time.delta_seconds();
+        let velocity_length = velocity
+            .velocity
+            .length_squared()
+            .max(player.max_movement_speed_squared)
+            * perks.modifiers.movement_speed
+            * (1.0 - (1.0 - PLAYER_AIM_MOVEMENT_SPEED_MODIFIER) * aim.progress)
+            * chill.map(|chill| chill.movement_modifier()).unwrap_or(1.0);
+        velocity.velocity = velocity.velocity.normalize() * velocity_length;
+        velocity.was_input = true;
     }
-
-    movement = movement.normalize();
-    velocity.velocity = movement * player.acceleration * time.delta_seconds();
-    let velocity_length = velocity
-        .velocity
-        .length_squared()
-        .max(player.max_movement_speed_squared);
-    velocity.velocity = velocity.velocity.normalize() * velocity_length;
-    velocity.was_input = true;
 }
 
 fn player_move(
     time: Res<Time>,
     rapier_context: Res<RapierContext>,
-    mut player_components: Query<
+    mut players: Query<
         (
             Entity,
             &Collider,
             &CollisionGroups,
-            &PlayerVelocity,
+            &mut PlayerVelocity,
             &mut Transform,
         ),
-        With<Player>,
+        (With<Player>, Without<PlayerVault>, Without<PlayerClimb>),
     >,
 ) {
-    let Ok((player, collider, collision_groups, velocity, mut transform)) =
-        player_components.get_single_mut()
-    else {
-        return;
-    };
+    'players: for (player, collider, collision_groups, mut velocity, mut transform) in
+        players.iter_mut()
+    {
+        let external = velocity.external;
+        velocity.external -= external * (PLAYER_EXTERNAL_DECAY * time.delta_seconds()).min(1.0);
+        if velocity.external.length_squared()
+            < PLAYER_EXTERNAL_MIN_SPEED * PLAYER_EXTERNAL_MIN_SPEED
+        {
+            velocity.external = Vec3::ZERO;
+        }
 
-    let mut movement = velocity.velocity * time.delta_seconds();
+        let mut movement = (velocity.velocity + external + velocity.floor) * time.delta_seconds();
+
+        for i in 0..4 {
+            let shape = collider;
+            let shape_pos = transform.translation + movement;
+            let shape_rot = transform.rotation;
+            let shape_vel = movement;
+            let max_toi = 2.0;
+            let filter = QueryFilter {
+                flags: QueryFilterFlags::EXCLUDE_SENSORS | QueryFilterFlags::EXCLUDE_DYNAMIC,
+                groups: Some(*collision_groups),
+                exclude_collider: Some(player),
+                ..default()
+            };
+
+            if let Some((_, hit)) = rapier_context.cast_shape(
+                shape_pos, shape_rot, shape_vel, shape, max_toi, true, filter,
+            ) {
+                match hit.status {
+                    TOIStatus::Converged => {
+                        if i == 3 {
+                            movement = Vec3::ZERO;
+                            break;
+                        }
+                        // hit.normal1: indicates the normal at the contact point hit.witness1,
+                        // expressed in the local-space of the collider hit by the shape.
+                        let wall_parrallel = hit.details.unwrap().normal1.cross(Vec3::Z);
+                        movement = wall_parrallel * wall_parrallel.dot(movement);
+                        // push into the wall is lost, the rest slides along it
+                        velocity.external = wall_parrallel * wall_parrallel.dot(velocity.external);
+                    }
+                    TOIStatus::Penetrating => {
+                        continue 'players;
+                    }
+                    _ => {}
+                }
+            }
+        }
 
-    for i in 0..4 {
-        let shape = collider;
-        let shape_pos = transform.translation + movement;
-        let shape_rot = transform.rotation;
-        let shape_vel = movement;
-        let max_toi = 2.0;
-        let filter = QueryFilter {
-            flags: QueryFilterFlags::EXCLUDE_SENSORS | QueryFilterFlags::EXCLUDE_DYNAMIC,
-            groups: Some(*collision_groups),
-            exclude_collider: Some(player),
-            ..default()
+        transform.translation += movement;
+    }
+}
+
+// Prop in front of the player is found with a forward cast.
+// The landing is the first spot behind it where a cast down
+// from the vault height does not hit anything.
+fn vault_landing(
+    rapier_context: &RapierContext,
+    player: Entity,
+    collider: &Collider,
+    transform: &Transform,
+    direction: Vec3,
+) -> Option<Vec3> {
+    let filter = QueryFilter {
+        flags: QueryFilterFlags::EXCLUDE_SENSORS | QueryFilterFlags::EXCLUDE_DYNAMIC,
+        groups: Some(CollisionGroups::new(
+            COLLISION_GROUP_PLAYER,
+            COLLISION_GROUP_LEVEL,
+        )),
+        exclude_collider: Some(player),
+        ..default()
+    };
+    let (_, hit) = rapier_context.cast_shape(
+        transform.translation,
+        transform.rotation,
+        direction,
+        collider,
+        PLAYER_VAULT_REACH,
+        true,
+        filter,
+    )?;
+
+    let lift = Vec3::Z * PLAYER_VAULT_HEIGHT;
+    let mut distance = hit.toi + PLAYER_VAULT_LANDING_STEP;
+    while distance < hit.toi + PLAYER_VAULT_MAX_DEPTH {
+        let landing = transform.translation + direction * distance;
+        // prop is too tall to go over
+        if rapier_context
+            .cast_shape(
+                transform.translation + lift,
+                transform.rotation,
+                direction,
+                collider,
+                distance,
+                true,
+                filter,
+            )
+            .is_some()
+        {
+            return None;
+        }
+        if rapier_context
+            .cast_shape(
+                landing + lift,
+                transform.rotation,
+                Vec3::NEG_Z,
+                collider,
+                PLAYER_VAULT_HEIGHT,
+                true,
+                filter,
+            )
+            .is_none()
+        {
+            return Some(landing);
+        }
+        distance += PLAYER_VAULT_LANDING_STEP;
+    }
+    None
+}
+
+fn player_vault_start(
+    rapier_context: Res<RapierContext>,
+    player_cameras: Query<(&PlayerId, &GlobalTransform), With<PlayerCamera>>,
+    players: Query<
+        (Entity, &PlayerId, &PlayerVelocity, &Collider, &Transform),
+        (
+            With<Player>,
+            Without<PlayerVault>,
+            Without<PlayerClimb>,
+            Without<PlayerOnLoft>,
+        ),
+    >,
+    mut commands: Commands,
+) {
+    for (player, player_id, velocity, collider, transform) in players.iter() {
+        if !velocity.was_input {
+            continue;
+        }
+        let Some((_, camera_transform)) = player_cameras.iter().find(|(id, _)| *id == player_id)
+        else {
+            continue;
         };
+        let direction = velocity.velocity.truncate().normalize_or_zero();
+        let facing = camera_transform.forward().truncate().normalize_or_zero();
+        if direction == Vec2::ZERO || direction.dot(facing) < PLAYER_VAULT_MIN_FACING {
+            continue;
+        }
 
-        if let Some((_, hit)) = rapier_context.cast_shape(
-            shape_pos, shape_rot, shape_vel, shape, max_toi, true, filter,
+        if let Some(end) = vault_landing(
+            rapier_context.as_ref(),
+            player,
+            collider,
+            transform,
+            direction.extend(0.0),
         ) {
-            match hit.status {
-                TOIStatus::Converged => {
-                    if i == 3 {
-                        movement = Vec3::ZERO;
-                        break;
-                    }
-                    // hit.normal1: indicates the normal at the contact point hit.witness1,
-                    // expressed in the local-space of the collider hit by the shape.
-                    let wall_parrallel = hit.details.unwrap().normal1.cross(Vec3::Z);
-                    movement = wall_parrallel * wall_parrallel.dot(movement);
-                }
-                TOIStatus::Penetrating => {
-                    return;
-                }
-                _ => {}
-            }
+            commands.entity(player).insert(PlayerVault {
+                start: transform.translation,
+                end,
+                progress: 0.0,
+            });
         }
     }
+}
 
-    transform.translation += movement;
+fn player_vault_update(
+    time: Res<Time>,
+    mut players: Query<(Entity, &mut PlayerVault, &mut Transform), With<Player>>,
+    mut commands: Commands,
+) {
+    for (player, mut vault, mut transform) in players.iter_mut() {
+        vault.progress = (vault.progress + PLAYER_VAULT_SPEED * time.delta_seconds()).min(1.0);
+
+        // player goes up quickly and moves forward
+        // mostly in the middle of the vault
+        let t = vault.progress;
+        let forward = t * t * (3.0 - 2.0 * t);
+        let lift = ((t * std::f32::consts::PI).sin() * 2.0).min(1.0) * PLAYER_VAULT_HEIGHT;
+        transform.translation = vault.start.lerp(vault.end, forward) + Vec3::Z * lift;
+
+        if vault.progress == 1.0 {
+            transform.translation = vault.end;
+            commands.entity(player).remove::<PlayerVault>();
+        }
+    }
 }
 
 // TODO make better
 fn player_camera_update(
     time: Res<Time>,
     game_settings: Res<GameSettings>,
-    player_components: Query<&PlayerVelocity>,
+    players: Query<(&PlayerId, &PlayerVelocity)>,
     mut ev_motion: EventReader<MouseMotion>,
-    mut player_camera_components: Query<(&mut PlayerCamera, &mut Transform)>,
+    mut player_cameras: Query<(&PlayerId, &mut PlayerCamera, &mut Transform)>,
+    mut footstep_events: EventWriter<PlayerFootstep>,
 ) {
-    let Ok(velocity) = player_components.get_single() else {
-        return;
-    };
+    let rotation: f32 = ev_motion.read().map(|e| -e.delta.x).sum();
 
-    let Ok((mut camera, mut transform)) = player_camera_components.get_single_mut() else {
-        return;
-    };
+    for (player_id, mut camera, mut transform) in player_cameras.iter_mut() {
+        let Some((_, velocity)) = players.iter().find(|(id, _)| *id == player_id) else {
+            continue;
+        };
 
-    let rotation: f32 = ev_motion.read().map(|e| -e.delta.x).sum();
-    transform.rotate_z(rotation * time.delta_seconds() * game_settings.camera_sensitivity);
-
-    transform.translation = camera.default_translation
-        + Vec3::NEG_Z
-            * camera.bounce_amplitude
-            * camera.bounce_amplitude_modifier
-            * (camera.bounce_progress).sin();
-
-    if velocity.was_input {
-        // if there was input, continue bouncing
-        camera.bounce_continue = true;
-        camera.bounce_progress += camera.bounce_speed * time.delta_seconds();
-        camera.bounce_amplitude_modifier = (camera.bounce_amplitude_modifier
-            + camera.bounce_amplitude_modifier_speed * time.delta_seconds())
-        .min(camera.bounce_amplitude_modifier_max);
-    } else if camera.bounce_continue {
-        // if there was no input, continue until next PI
-        camera.bounce_progress += camera.bounce_speed * time.delta_seconds();
-        let next_pi = (camera.bounce_progress / std::f32::consts::PI).ceil() * std::f32::consts::PI;
-        if next_pi <= camera.bounce_progress + 0.1 {
-            camera.bounce_progress = 0.0;
-            camera.bounce_continue = false;
-            camera.bounce_amplitude_modifier = 1.0;
+        transform.rotate_z(rotation * time.delta_seconds() * game_settings.camera_sensitivity);
+
+        transform.translation = camera.default_translation
+            + Vec3::NEG_Z
+                * camera.bounce_amplitude
+                * camera.bounce_amplitude_modifier
+                * (camera.bounce_progress).sin();
+
+        // step lands each time the bounce crosses zero
+        let half_bounce = (camera.bounce_progress / std::f32::consts::PI).floor();
+        if velocity.was_input {
+            // if there was input, continue bouncing
+            camera.bounce_continue = true;
+            camera.bounce_progress += camera.bounce_speed * time.delta_seconds();
+            camera.bounce_amplitude_modifier = (camera.bounce_amplitude_modifier
+                + camera.bounce_amplitude_modifier_speed * time.delta_seconds())
+            .min(camera.bounce_amplitude_modifier_max);
+            if half_bounce < (camera.bounce_progress / std::f32::consts::PI).floor() {
+                footstep_events.send(PlayerFootstep {
+                    player_id: *player_id,
+                });
+            }
+        } else if camera.bounce_continue {
+            // if there was no input, continue until next PI
+            camera.bounce_progress += camera.bounce_speed * time.delta_seconds();
+            let next_pi =
+                (camera.bounce_progress / std::f32::consts::PI).ceil() * std::f32::consts::PI;
+            if next_pi <= camera.bounce_progress + 0.1 {
+                camera.bounce_progress = 0.0;
+                camera.bounce_continue = false;
+                camera.bounce_amplitude_modifier = 1.0;
+                // last step when the player stops
+                footstep_events.send(PlayerFootstep {
+                    player_id: *player_id,
+                });
+            }
         }
     }
 }
 
-// TODO make better
-fn player_weapon_update(
+#[allow(clippy::too_many_arguments)]
+fn player_footsteps(
     time: Res<Time>,
-    player_velocity: Query<&PlayerVelocity>,
-    mut weapon: Query<(&mut Transform, &mut PlayerWeapon)>,
+    sfx: Res<AudioChannel<SfxChannel>>,
+    game_settings: Res<GameSettings>,
+    level_assets: Res<LevelAssets>,
+    level_info: Option<Res<LevelInfo>>,
+    mut sound_pool: ResMut<SoundPool>,
+    mut audio_instances: ResMut<Assets<AudioInstance>>,
+    mut game_rng: ResMut<GameRng>,
+    mut footstep_events: EventReader<PlayerFootstep>,
 ) {
-    let Ok(velocity) = player_velocity.get_single() else {
+    let Some(level_info) = level_info else {
         return;
     };
+    let sound = level_info.level_type.footstep_sound(level_assets.as_ref());
+    for _ in footstep_events.read() {
+        sound_pool.play(
+            sfx.as_ref(),
+            audio_instances.as_mut(),
+            &sound,
+            PLAYER_FOOTSTEP_SOUND_LIMIT,
+            (game_settings.volume * PLAYER_FOOTSTEP_VOLUME) as f64,
+            0.5,
+            time.elapsed_seconds(),
+            game_rng.stream(RngStream::Effects),
+        );
+    }
+}
 
-    let Ok((mut weapon_transform, mut player_weapon)) = weapon.get_single_mut() else {
+// TODO make better
+#[allow(clippy::too_many_arguments)]
+fn player_weapon_update(
+    time: Res<Time>,
+    rapier_context: Res<RapierContext>,
+    players: Query<(Entity, &PlayerId, &PlayerVelocity, &PlayerAim), With<Player>>,
+    player_cameras: Query<(&PlayerId, &GlobalTransform), With<PlayerCamera>>,
+    mut weapons: Query<(
+        Entity,
+        &PlayerId,
+        &Weapon,
+        &mut Transform,
+        &mut PlayerWeapon,
+    )>,
+    mut ev_motion: EventReader<MouseMotion>,
+    mut shoot_events: EventReader<ShootEvent>,
+) {
+    let mouse_delta: f32 = ev_motion.read().map(|e| e.delta.x).sum();
+    let shot_weapons = shoot_events
+        .read()
+        .map(|e| e.weapon_entity)
+        .collect::<Vec<_>>();
+
+    for (weapon_entity, player_id, weapon, mut weapon_transform, mut player_weapon) in
+        weapons.iter_mut()
+    {
+        let Some((player, _, velocity, aim)) =
+            players.iter().find(|(_, id, _, _)| *id == player_id)
+        else {
+            continue;
+        };
+
+        let Some((_, camera_global_transform)) =
+            player_cameras.iter().find(|(id, _)| *id == player_id)
+        else {
+            continue;
+        };
+
+        // sway in the opposite direction of camera rotation
+        let target_sway = (-mouse_delta * PLAYER_WEAPON_SWAY_SCALE)
+            .clamp(-PLAYER_WEAPON_SWAY_MAX, PLAYER_WEAPON_SWAY_MAX);
+        player_weapon.sway += (target_sway - player_weapon.sway)
+            * (PLAYER_WEAPON_SWAY_SPEED * time.delta_seconds()).min(1.0);
+
+        // kick back on every shot and settle back after
+        if shot_weapons.contains(&weapon_entity) {
+            player_weapon.recoil = PLAYER_WEAPON_RECOIL;
+        }
+        player_weapon.recoil -=
+            player_weapon.recoil * (PLAYER_WEAPON_SETTLE_SPEED * time.delta_seconds()).min(1.0);
+
+        // pull weapon in if player is facing a wall,
+        // so it does not clip through it
+        let target_pull_in = viewmodel_pull_in(
+            rapier_context.as_ref(),
+            camera_global_transform,
+            player,
+            PLAYER_WEAPON_PULL_IN_DISTANCE,
+            PLAYER_WEAPON_PULL_IN_MAX,
+        );
+        player_weapon.pull_in += (target_pull_in - player_weapon.pull_in)
+            * (PLAYER_WEAPON_PULL_IN_SPEED * time.delta_seconds()).min(1.0);
+
+        let bounce = player_weapon.bounce_progress.sin();
+        let offset = Vec3::new(
+            player_weapon.bounce_amplitude * bounce + player_weapon.sway,
+            (player_weapon.bounce_amplitude * bounce).abs(),
+            player_weapon.recoil + player_weapon.pull_in,
+        );
+
+        weapon_transform.translation = player_weapon
+            .default_translation
+            .lerp(weapon.aim_translation(), aim.progress)
+            + offset;
+
+        if velocity.was_input {
+            // if there was input, continue bouncing
+            player_weapon.bounce_continue = true;
+            player_weapon.bounce_progress += player_weapon.bounce_speed * time.delta_seconds();
+        } else if player_weapon.bounce_continue {
+            // if there was no input, continue until next PI
+            player_weapon.bounce_progress += player_weapon.bounce_speed * time.delta_seconds();
+            let next_pi = (player_weapon.bounce_progress / std::f32::consts::PI).ceil()
+                * std::f32::consts::PI;
+            if next_pi <= player_weapon.bounce_progress + 0.1 {
+                player_weapon.bounce_progress = 0.0;
+                player_weapon.bounce_continue = false;
+            }
+        }
+    }
+}
+
+fn player_weapon_inspect(
+    keys: Res<Input<KeyCode>>,
+    weapons: Query<(Entity, &Children), (With<PlayerWeapon>, Without<PlayerWeaponInspect>)>,
+    weapon_models: Query<&Transform, (With<WeaponModel>, Without<Animation>)>,
+    mut commands: Commands,
+) {
+    if !keys.just_pressed(PLAYER_WEAPON_INSPECT_KEY) {
         return;
-    };
-    // weapon_transform.rotation = Quat::IDENTITY;
-
-    let bounce = player_weapon.bounce_progress.sin();
-    let offset = Vec3::new(
-        player_weapon.bounce_amplitude * bounce,
-        (player_weapon.bounce_amplitude * bounce).abs(),
-        0.0,
-    );
-
-    weapon_transform.translation = player_weapon.default_translation + offset;
-
-    if velocity.was_input {
-        // if there was input, continue bouncing
-        player_weapon.bounce_continue = true;
-        player_weapon.bounce_progress += player_weapon.bounce_speed * time.delta_seconds();
-    } else if player_weapon.bounce_continue {
-        // if there was no input, continue until next PI
-        player_weapon.bounce_progress += player_weapon.bounce_speed * time.delta_seconds();
-        let next_pi =
-            (player_weapon.bounce_progress / std::f32::consts::PI).ceil() * std::f32::consts::PI;
-        if next_pi <= player_weapon.bounce_progress + 0.1 {
-            player_weapon.bounce_progress = 0.0;
-            player_weapon.bounce_continue = false;
+    }
+
+    for (weapon, weapon_children) in weapons.iter() {
+        // do not interrupt shooting animation
+        let weapon_model = weapon_children[0];
+        let Ok(weapon_model_transform) = weapon_models.get(weapon_model) else {
+            continue;
+        };
+
+        let initial_transform = *weapon_model_transform;
+        let mut target_transform = initial_transform;
+        target_transform.translation += PLAYER_WEAPON_INSPECT_TARGET_OFFSET;
+        target_transform.rotation *= Quat::from_rotation_y(PLAYER_WEAPON_INSPECT_TARGET_ROTATION_Y)
+            * Quat::from_rotation_z(PLAYER_WEAPON_INSPECT_TARGET_ROTATION_Z);
+
+        let Some(mut e) = commands.get_entity(weapon_model) else {
+            continue;
+        };
+        e.insert(Animation {
+            animate_forward: true,
+            animate_backward: true,
+            animation_speed: PLAYER_WEAPON_INSPECT_ANIMATION_SPEED,
+            progress: 0.0,
+            initial_transform,
+            target_transform,
+        });
+
+        let Some(mut e) = commands.get_entity(weapon) else {
+            continue;
+        };
+        e.insert(PlayerWeaponInspect);
+    }
+}
+
+fn player_weapon_inspect_finish(
+    weapons: Query<(Entity, &Children), With<PlayerWeaponInspect>>,
+    animated_weapon_models: Query<(), (With<WeaponModel>, With<Animation>)>,
+    mut commands: Commands,
+) {
+    for (weapon, weapon_children) in weapons.iter() {
+        if animated_weapon_models.contains(weapon_children[0]) {
+            continue;
         }
+        let Some(mut e) = commands.get_entity(weapon) else {
+            continue;
+        };
+        e.remove::<PlayerWeaponInspect>();
     }
 }
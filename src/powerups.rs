@@ -0,0 +1,345 @@
+use bevy::{pbr::NotShadowCaster, prelude::*};
+use bevy_kira_audio::{Audio, AudioControl};
+use rand::Rng;
+
+use crate::{
+    damage::KillEvent,
+    enemies::{Enemy, EnemyType},
+    level::LevelObject,
+    player::Player,
+    rng::{GameRng, RngStream},
+    weapons::WeaponAssets,
+    GlobalState,
+};
+
+const POWERUP_SIZE: f32 = 0.8;
+const POWERUP_SMALL_ENEMY_DROP_CHANCE: f64 = 0.05;
+const POWERUP_MID_ENEMY_DROP_CHANCE: f64 = 0.1;
+const POWERUP_BIG_ENEMY_DROP_CHANCE: f64 = 0.3;
+const POWERUP_FLOAT_HIGHT: f32 = 2.0;
+const POWERUP_FLOAT_AMPLITUDE: f32 = 0.3;
+const POWERUP_FLOAT_SPEED: f32 = 3.0;
+const POWERUP_ROTATION_SPEED: f32 = 2.0;
+const POWERUP_COLLECT_RADIUS: f32 = 2.5;
+const POWERUP_COLLECT_SOUND_PLAYBACK_RATE: f64 = 0.5;
+
+const POWERUP_DURATION: f32 = 10.0;
+// Picking up the same power-up again extends it,
+// different power-ups run at the same time
+const POWERUP_MAX_DURATION: f32 = 30.0;
+const POWERUP_DAMAGE_MODIFIER: f32 = 2.0;
+
+pub struct PowerupsPlugin;
+
+impl Plugin for PowerupsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActivePowerups>();
+
+        app.add_event::<PowerupCollected>();
+
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::AssetLoading,
+                to: GlobalState::MainMenu,
+            },
+            init_resources,
+        );
+
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::MainMenu,
+                to: GlobalState::InGame,
+            },
+            reset_powerups,
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::GameOver,
+                to: GlobalState::InGame,
+            },
+            reset_powerups,
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::GameWon,
+                to: GlobalState::InGame,
+            },
+            reset_powerups,
+        );
+
+        app.add_systems(
+            Update,
+            (powerup_drop, powerup_float, powerup_collect, powerup_tick)
+                .run_if(in_state(GlobalState::InGame)),
+        );
+    }
+}
+
+#[derive(Clone, Copy, Event)]
+pub struct PowerupCollected {
+    pub powerup_type: PowerupType,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerupType {
+    Damage,
+    Shield,
+    InfiniteAmmo,
+}
+
+impl PowerupType {
+    pub const ALL: [PowerupType; 3] = [
+        PowerupType::Damage,
+        PowerupType::Shield,
+        PowerupType::InfiniteAmmo,
+    ];
+
+    pub fn color(&self) -> Color {
+        match self {
+            PowerupType::Damage => Color::ORANGE_RED,
+            PowerupType::Shield => Color::CYAN,
+            PowerupType::InfiniteAmmo => Color::GOLD,
+        }
+    }
+
+    pub fn short_name(&self) -> &'static str {
+        match self {
+            PowerupType::Damage => "DMG x2",
+            PowerupType::Shield => "SHIELD",
+            PowerupType::InfiniteAmmo => "AMMO",
+        }
+    }
+}
+
+// Seconds left for each power-up
+#[derive(Debug, Default, Resource)]
+pub struct ActivePowerups {
+    damage: f32,
+    shield: f32,
+    infinite_ammo: f32,
+}
+
+impl ActivePowerups {
+    pub fn remaining(&self, powerup_type: PowerupType) -> f32 {
+        match powerup_type {
+            PowerupType::Damage => self.damage,
+            PowerupType::Shield => self.shield,
+            PowerupType::InfiniteAmmo => self.infinite_ammo,
+        }
+    }
+
+    pub fn is_active(&self, powerup_type: PowerupType) -> bool {
+        0.0 < self.remaining(powerup_type)
+    }
+
+    pub fn damage_modifier(&self) -> f32 {
+        if self.is_active(PowerupType::Damage) {
+            POWERUP_DAMAGE_MODIFIER
+        } else {
+            1.0
+        }
+    }
+
+    fn remaining_mut(&mut self, powerup_type: PowerupType) -> &mut f32 {
+        match powerup_type {
+            PowerupType::Damage => &mut self.damage,
+            PowerupType::Shield => &mut self.shield,
+            PowerupType::InfiniteAmmo => &mut self.infinite_ammo,
+        }
+    }
+
+    fn add(&mut self, powerup_type: PowerupType) {
+        let remaining = self.remaining_mut(powerup_type);
+        *remaining = (*remaining + POWERUP_DURATION).min(POWERUP_MAX_DURATION);
+    }
+}
+
+#[derive(Resource)]
+struct PowerupResources {
+    damage_mesh: Handle<Mesh>,
+    shield_mesh: Handle<Mesh>,
+    infinite_ammo_mesh: Handle<Mesh>,
+    damage_material: Handle<StandardMaterial>,
+    shield_material: Handle<StandardMaterial>,
+    infinite_ammo_material: Handle<StandardMaterial>,
+}
+
+#[derive(Component)]
+pub struct PowerupPickup {
+    pub powerup_type: PowerupType,
+    pub original_translation: Vec3,
+}
+
+fn init_resources(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let damage_mesh = meshes.add(shape::Cube::new(POWERUP_SIZE).into());
+    let shield_mesh = meshes.add(
+        shape::UVSphere {
+            radius: POWERUP_SIZE / 2.0,
+            ..default()
+        }
+        .into(),
+    );
+    let infinite_ammo_mesh = meshes.add(
+        shape::Torus {
+            radius: POWERUP_SIZE / 2.0,
+            ring_radius: POWERUP_SIZE / 6.0,
+            ..default()
+        }
+        .into(),
+    );
+
+    let mut material = |powerup_type: PowerupType| {
+        materials.add(StandardMaterial {
+            base_color: powerup_type.color(),
+            emissive: powerup_type.color(),
+            unlit: true,
+            ..default()
+        })
+    };
+    let damage_material = material(PowerupType::Damage);
+    let shield_material = material(PowerupType::Shield);
+    let infinite_ammo_material = material(PowerupType::InfiniteAmmo);
+
+    commands.insert_resource(PowerupResources {
+        damage_mesh,
+        shield_mesh,
+        infinite_ammo_mesh,
+        damage_material,
+        shield_material,
+        infinite_ammo_material,
+    });
+}
+
+fn reset_powerups(mut active_powerups: ResMut<ActivePowerups>) {
+    *active_powerups = ActivePowerups::default();
+}
+
+fn spawn_powerup(
+    powerup_resources: &PowerupResources,
+    commands: &mut Commands,
+    powerup_type: PowerupType,
+    mut translation: Vec3,
+) {
+    translation.z = POWERUP_FLOAT_HIGHT;
+    let (mesh, material) = match powerup_type {
+        PowerupType::Damage => (
+            powerup_resources.damage_mesh.clone(),
+            powerup_resources.damage_material.clone(),
+        ),
+        PowerupType::Shield => (
+            powerup_resources.shield_mesh.clone(),
+            powerup_resources.shield_material.clone(),
+        ),
+        PowerupType::InfiniteAmmo => (
+            powerup_resources.infinite_ammo_mesh.clone(),
+            powerup_resources.infinite_ammo_material.clone(),
+        ),
+    };
+    commands.spawn((
+        PbrBundle {
+            mesh,
+            material,
+            transform: Transform::from_translation(translation),
+            ..default()
+        },
+        NotShadowCaster,
+        PowerupPickup {
+            powerup_type,
+            original_translation: translation,
+        },
+        LevelObject,
+    ));
+}
+
+fn powerup_drop(
+    powerup_resources: Res<PowerupResources>,
+    enemies: Query<(&Transform, &Enemy)>,
+    mut game_rng: ResMut<GameRng>,
+    mut commands: Commands,
+    mut kill_events: EventReader<KillEvent>,
+) {
+    let rng = game_rng.stream(RngStream::Drops);
+    for kill_event in kill_events.read() {
+        let Ok((enemy_transform, enemy)) = enemies.get(kill_event.entity) else {
+            continue;
+        };
+
+        let chance = match enemy.enemy_type {
+            EnemyType::Small => POWERUP_SMALL_ENEMY_DROP_CHANCE,
+            EnemyType::Mid => POWERUP_MID_ENEMY_DROP_CHANCE,
+            EnemyType::Big => POWERUP_BIG_ENEMY_DROP_CHANCE,
+        };
+        if !rng.gen_bool(chance) {
+            continue;
+        }
+
+        let powerup_type = PowerupType::ALL[rng.gen_range(0..PowerupType::ALL.len())];
+        spawn_powerup(
+            powerup_resources.as_ref(),
+            &mut commands,
+            powerup_type,
+            enemy_transform.translation,
+        );
+    }
+}
+
+fn powerup_float(time: Res<Time>, mut powerups: Query<(&PowerupPickup, &mut Transform)>) {
+    for (powerup, mut powerup_transform) in powerups.iter_mut() {
+        let t = time.elapsed_seconds() * POWERUP_FLOAT_SPEED;
+        powerup_transform.translation =
+            powerup.original_translation + Vec3::Z * POWERUP_FLOAT_AMPLITUDE * t.sin();
+        powerup_transform.rotate_z(time.delta_seconds() * POWERUP_ROTATION_SPEED);
+        powerup_transform.rotate_x(time.delta_seconds() * POWERUP_ROTATION_SPEED);
+    }
+}
+
+fn powerup_collect(
+    audio: Res<Audio>,
+    weapon_assets: Res<WeaponAssets>,
+    players: Query<&Transform, With<Player>>,
+    powerups: Query<(Entity, &Transform, &PowerupPickup), Without<Player>>,
+    mut active_powerups: ResMut<ActivePowerups>,
+    mut commands: Commands,
+    mut powerup_collected_events: EventWriter<PowerupCollected>,
+) {
+    for (powerup_entity, powerup_transform, powerup) in powerups.iter() {
+        // power-ups float above the floor, so only
+        // horizontal distance is checked. Active
+        // power-ups are shared, any player can collect them.
+        let collected = players.iter().any(|player_transform| {
+            player_transform
+                .translation
+                .truncate()
+                .distance_squared(powerup_transform.translation.truncate())
+                <= POWERUP_COLLECT_RADIUS * POWERUP_COLLECT_RADIUS
+        });
+        if !collected {
+            continue;
+        }
+
+        active_powerups.add(powerup.powerup_type);
+        powerup_collected_events.send(PowerupCollected {
+            powerup_type: powerup.powerup_type,
+        });
+
+        audio
+            .play(weapon_assets.pistol_sounds[0].clone())
+            .with_playback_rate(POWERUP_COLLECT_SOUND_PLAYBACK_RATE);
+
+        let Some(e) = commands.get_entity(powerup_entity) else {
+            continue;
+        };
+        e.despawn_recursive();
+    }
+}
+
+fn powerup_tick(time: Res<Time>, mut active_powerups: ResMut<ActivePowerups>) {
+    for powerup_type in PowerupType::ALL {
+        let remaining = active_powerups.remaining_mut(powerup_type);
+        *remaining = (*remaining - time.delta_seconds()).max(0.0);
+    }
+}
@@ -0,0 +1,146 @@
+use bevy::{prelude::*, utils::Instant};
+
+use crate::{level::LevelInfo, GlobalState};
+
+const PRESENCE_BOSS_LEVEL_PROGRESS: i32 = 100;
+
+pub struct PresencePlugin;
+
+impl Plugin for PresencePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Presence::new(Box::new(LogPresence)));
+
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::MainMenu,
+                to: GlobalState::InGame,
+            },
+            presence_start_run,
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::GameOver,
+                to: GlobalState::InGame,
+            },
+            presence_start_run,
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::GameWon,
+                to: GlobalState::InGame,
+            },
+            presence_start_run,
+        );
+
+        app.add_systems(Update, presence_update);
+    }
+}
+
+/// What the player is doing right now
+#[derive(Debug, Clone, PartialEq)]
+pub struct Activity {
+    // e.g. "Floor 7"
+    pub details: String,
+    // e.g. "Playing"
+    pub state: String,
+    // Start of the current run, used for the elapsed time
+    pub run_started: Option<Instant>,
+}
+
+/// Destination of the activity updates, e.g. Discord RPC
+/// or Steam rich presence. Services are expected to be
+/// implemented behind cargo features and set with
+/// `Presence::set_backend` from the `main`.
+pub trait PresenceBackend: Send + Sync {
+    fn publish(&mut self, activity: &Activity);
+    fn clear(&mut self);
+}
+
+// Default backend, only useful for debugging
+struct LogPresence;
+
+impl PresenceBackend for LogPresence {
+    fn publish(&mut self, activity: &Activity) {
+        debug!("Presence: {} ({})", activity.details, activity.state);
+    }
+
+    fn clear(&mut self) {
+        debug!("Presence cleared");
+    }
+}
+
+#[derive(Resource)]
+pub struct Presence {
+    backend: Box<dyn PresenceBackend>,
+    run_started: Option<Instant>,
+    last_activity: Option<Activity>,
+}
+
+impl Presence {
+    pub fn new(backend: Box<dyn PresenceBackend>) -> Self {
+        Self {
+            backend,
+            run_started: None,
+            last_activity: None,
+        }
+    }
+
+    pub fn set_backend(&mut self, backend: Box<dyn PresenceBackend>) {
+        self.backend.clear();
+        self.backend = backend;
+        self.last_activity = None;
+    }
+}
+
+impl Drop for Presence {
+    fn drop(&mut self) {
+        self.backend.clear();
+    }
+}
+
+fn presence_start_run(mut presence: ResMut<Presence>) {
+    presence.run_started = Some(Instant::now());
+}
+
+fn level_details(level_info: Option<&LevelInfo>) -> String {
+    match level_info.map(|level_info| level_info.game_progress) {
+        Some(PRESENCE_BOSS_LEVEL_PROGRESS) => "Fighting in the Red Dragon lair".to_string(),
+        Some(progress) if 0 <= progress => format!("Floor {}", progress / 10 + 1),
+        _ => "Warming up".to_string(),
+    }
+}
+
+fn presence_update(
+    global_state: Res<State<GlobalState>>,
+    level_info: Option<Res<LevelInfo>>,
+    mut presence: ResMut<Presence>,
+) {
+    let level_info = level_info.as_deref();
+    let (details, state) = match global_state.get() {
+        GlobalState::AssetLoading => return,
+        GlobalState::MainMenu => ("In the main menu".to_string(), "Idle"),
+        GlobalState::InGame => (level_details(level_info), "Playing"),
+        GlobalState::Paused => (level_details(level_info), "Paused"),
+        GlobalState::PerkSelection => (level_details(level_info), "Choosing a perk"),
+        GlobalState::GameOver => (level_details(level_info), "Game over"),
+        GlobalState::GameWon => ("All fridges are dead".to_string(), "Victory"),
+        GlobalState::Hub => ("In the fridge graveyard".to_string(), "Idle"),
+    };
+    let run_started = match global_state.get() {
+        GlobalState::MainMenu | GlobalState::Hub => None,
+        _ => presence.run_started,
+    };
+    let activity = Activity {
+        details,
+        state: state.to_string(),
+        run_started,
+    };
+
+    // services rate limit updates, so only
+    // changes are published
+    if presence.last_activity.as_ref() == Some(&activity) {
+        return;
+    }
+    presence.backend.publish(&activity);
+    presence.last_activity = Some(activity);
+}
@@ -0,0 +1,309 @@
+use std::path::Path;
+
+use bevy::prelude::*;
+use bevy_kira_audio::{Audio, AudioChannel, AudioControl};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    announcer::ANNOUNCER_INITIAL_VOLUME, level::grade::LevelMedals, weapons::WeaponType,
+    GameSettings, SfxChannel, INITIAL_CAMERA_SENSE, INITIAL_VOLUME,
+};
+
+const PROFILE_SLOTS_PATH: &str = "profiles.ron";
+const PROFILES_DIR: &str = "profiles";
+// Single profile of the versions before save slots,
+// becomes the first slot
+const LEGACY_PROFILE_PATH: &str = "profile.ron";
+const DEFAULT_PROFILE_NAME: &str = "Player";
+
+pub const PROFILE_MAX_SLOTS: usize = 5;
+pub const PROFILE_MAX_NAME_LEN: usize = 16;
+
+pub struct ProfilePlugin;
+
+impl Plugin for ProfilePlugin {
+    fn build(&self, app: &mut App) {
+        let slots_exist = Path::new(PROFILE_SLOTS_PATH).exists();
+        let slots = load_ron::<ProfileSlots>(PROFILE_SLOTS_PATH);
+        let profile = if slots_exist {
+            load_ron::<Profile>(&profile_path(slots.active_name()))
+        } else {
+            load_ron::<Profile>(LEGACY_PROFILE_PATH)
+        };
+        app.insert_resource(slots);
+        app.insert_resource(profile);
+
+        app.add_systems(
+            Update,
+            (
+                profile_switch.run_if(resource_changed::<ProfileSlots>()),
+                profile_store_settings.run_if(resource_changed::<GameSettings>()),
+                save_profile.run_if(resource_changed::<Profile>()),
+            )
+                .chain(),
+        );
+    }
+}
+
+/// Names of all profiles and the selected one.
+/// Each profile is stored in its own file.
+#[derive(Debug, Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileSlots {
+    names: Vec<String>,
+    active: usize,
+}
+
+impl Default for ProfileSlots {
+    fn default() -> Self {
+        Self {
+            names: vec![DEFAULT_PROFILE_NAME.to_string()],
+            active: 0,
+        }
+    }
+}
+
+impl ProfileSlots {
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn active(&self) -> usize {
+        self.active
+    }
+
+    pub fn active_name(&self) -> &str {
+        self.names
+            .get(self.active)
+            .map(String::as_str)
+            .unwrap_or(DEFAULT_PROFILE_NAME)
+    }
+
+    pub fn select(&mut self, index: usize) {
+        if index < self.names.len() {
+            self.active = index;
+        }
+    }
+
+    /// Creates a new profile and selects it
+    pub fn create(&mut self) {
+        if PROFILE_MAX_SLOTS <= self.names.len() {
+            return;
+        }
+        let name = (1..)
+            .map(|i| format!("{DEFAULT_PROFILE_NAME} {i}"))
+            .find(|name| !self.names.contains(name))
+            .unwrap();
+        self.names.push(name);
+        self.active = self.names.len() - 1;
+    }
+
+    /// Renames the active profile. Returns false
+    /// if the name is empty or already taken.
+    pub fn rename(&mut self, name: &str) -> bool {
+        let name = name.trim();
+        if name.is_empty() || self.names.iter().any(|n| n == name) {
+            return false;
+        }
+        let old_path = profile_path(self.active_name());
+        let new_path = profile_path(name);
+        if let Err(e) = std::fs::rename(&old_path, &new_path) {
+            warn!("Could not rename {old_path} to {new_path}: {e}");
+        }
+        self.names[self.active] = name.to_string();
+        true
+    }
+
+    /// Deletes the active profile and selects the
+    /// first one. The last profile can not be deleted.
+    pub fn delete(&mut self) {
+        if self.names.len() == 1 {
+            return;
+        }
+        let path = profile_path(self.active_name());
+        if let Err(e) = std::fs::remove_file(&path) {
+            warn!("Could not delete {path}: {e}");
+        }
+        self.names.remove(self.active);
+        self.active = 0;
+    }
+}
+
+/// Characters allowed in profile names, so
+/// names can be used as file names
+pub fn is_profile_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == ' ' || c == '-' || c == '_'
+}
+
+fn profile_path(name: &str) -> String {
+    format!("{PROFILES_DIR}/{name}.ron")
+}
+
+// Settings that follow the profile
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileSettings {
+    pub volume: f32,
+    pub camera_sensitivity: f32,
+    pub sound_captions: bool,
+    pub narration: bool,
+    pub announcer: bool,
+    pub announcer_volume: f32,
+    pub auto_pause: bool,
+    pub hud_scale: f32,
+    pub hud_safe_area: f32,
+    pub minimal_hud: bool,
+}
+
+impl Default for ProfileSettings {
+    fn default() -> Self {
+        Self {
+            volume: INITIAL_VOLUME,
+            camera_sensitivity: INITIAL_CAMERA_SENSE,
+            sound_captions: false,
+            narration: false,
+            announcer: true,
+            announcer_volume: ANNOUNCER_INITIAL_VOLUME,
+            auto_pause: true,
+            hud_scale: 1.0,
+            hud_safe_area: 0.0,
+            minimal_hud: false,
+        }
+    }
+}
+
+// Progress that persists between game sessions.
+#[derive(Debug, Default, Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    // Number of times the game was started
+    // in the New Game+ mode
+    pub prestige: u32,
+    // Lifetime kills with each weapon,
+    // used for weapon unlocks
+    pub pistol_kills: u32,
+    pub shotgun_kills: u32,
+    pub minigun_kills: u32,
+    // Number of won runs, shown as
+    // trophies in the hub
+    pub boss_kills: u32,
+    // Lore entries read from terminals,
+    // in the order they were found
+    pub lore_entries: Vec<usize>,
+    // Best medals of each level, levels
+    // are told apart by the run seed and floor
+    pub level_medals: Vec<LevelMedals>,
+    pub settings: ProfileSettings,
+}
+
+impl Profile {
+    pub fn weapon_kills(&self, weapon_type: WeaponType) -> u32 {
+        match weapon_type {
+            WeaponType::Pistol => self.pistol_kills,
+            WeaponType::Shotgun => self.shotgun_kills,
+            WeaponType::Minigun => self.minigun_kills,
+        }
+    }
+
+    pub fn weapon_kills_mut(&mut self, weapon_type: WeaponType) -> &mut u32 {
+        match weapon_type {
+            WeaponType::Pistol => &mut self.pistol_kills,
+            WeaponType::Shotgun => &mut self.shotgun_kills,
+            WeaponType::Minigun => &mut self.minigun_kills,
+        }
+    }
+}
+
+/// Loads value from the ron file. Returns default
+/// value if file does not exist or is invalid.
+pub fn load_ron<T: Default + DeserializeOwned>(path: &str) -> T {
+    match std::fs::read_to_string(path) {
+        Ok(data) => ron::from_str(&data).unwrap_or_else(|e| {
+            warn!("Could not parse {path}: {e}");
+            T::default()
+        }),
+        Err(_) => T::default(),
+    }
+}
+
+/// Saves value to the ron file. Returns false
+/// if the file could not be saved.
+pub fn save_ron<T: Serialize>(path: &str, value: &T) -> bool {
+    let data = match ron::ser::to_string_pretty(value, Default::default()) {
+        Ok(data) => data,
+        Err(e) => {
+            warn!("Could not serialize {path}: {e}");
+            return false;
+        }
+    };
+    if let Err(e) = std::fs::write(path, data) {
+        warn!("Could not save {path}: {e}");
+        return false;
+    }
+    true
+}
+
+// Also runs on startup, so settings of
+// the active profile are applied
+fn profile_switch(
+    audio: Res<Audio>,
+    sfx: Res<AudioChannel<SfxChannel>>,
+    profile_slots: Res<ProfileSlots>,
+    mut profile: ResMut<Profile>,
+    mut game_settings: ResMut<GameSettings>,
+    mut loaded_profile: Local<Option<String>>,
+) {
+    save_ron(PROFILE_SLOTS_PATH, profile_slots.as_ref());
+
+    let name = profile_slots.active_name();
+    // on startup the profile is already loaded
+    let switched = loaded_profile.is_some() && loaded_profile.as_deref() != Some(name);
+    if switched {
+        let path = profile_path(name);
+        *profile = if Path::new(&path).exists() {
+            load_ron::<Profile>(&path)
+        } else {
+            Profile::default()
+        };
+    }
+    *loaded_profile = Some(name.to_string());
+
+    let settings = profile.settings;
+    game_settings.volume = settings.volume;
+    game_settings.camera_sensitivity = settings.camera_sensitivity;
+    game_settings.sound_captions = settings.sound_captions;
+    game_settings.narration = settings.narration;
+    game_settings.announcer = settings.announcer;
+    game_settings.announcer_volume = settings.announcer_volume;
+    game_settings.auto_pause = settings.auto_pause;
+    game_settings.hud_scale = settings.hud_scale;
+    game_settings.hud_safe_area = settings.hud_safe_area;
+    game_settings.minimal_hud = settings.minimal_hud;
+    audio.set_volume(settings.volume as f64);
+    sfx.set_volume(settings.volume as f64);
+}
+
+fn profile_store_settings(game_settings: Res<GameSettings>, mut profile: ResMut<Profile>) {
+    let settings = ProfileSettings {
+        volume: game_settings.volume,
+        camera_sensitivity: game_settings.camera_sensitivity,
+        sound_captions: game_settings.sound_captions,
+        narration: game_settings.narration,
+        announcer: game_settings.announcer,
+        announcer_volume: game_settings.announcer_volume,
+        auto_pause: game_settings.auto_pause,
+        hud_scale: game_settings.hud_scale,
+        hud_safe_area: game_settings.hud_safe_area,
+        minimal_hud: game_settings.minimal_hud,
+    };
+    if profile.settings != settings {
+        profile.settings = settings;
+    }
+}
+
+fn save_profile(profile_slots: Res<ProfileSlots>, profile: Res<Profile>) {
+    if let Err(e) = std::fs::create_dir_all(PROFILES_DIR) {
+        warn!("Could not create {PROFILES_DIR}: {e}");
+    }
+    save_ron(&profile_path(profile_slots.active_name()), profile.as_ref());
+}
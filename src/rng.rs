@@ -0,0 +1,68 @@
+use bevy::prelude::*;
+use rand::{rngs::StdRng, SeedableRng};
+
+// Spreads stream seeds apart, so the same run
+// seed produces unrelated streams
+const RNG_STREAM_SEED_MIX: u64 = 0x9E37_79B9_7F4A_7C15;
+
+pub struct RngPlugin;
+
+impl Plugin for RngPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(GameRng::new(rand::random()));
+    }
+}
+
+/// Independent sources of randomness. Each system
+/// takes values only from its own stream, so changes
+/// in one system do not shift values in others.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RngStream {
+    Level,
+    LevelColors,
+    Elites,
+    Drops,
+    Spread,
+    Perks,
+    // Rarity and stats of spawned weapons
+    Loot,
+    // Debris, sound pitch and other things
+    // not affecting the gameplay
+    Effects,
+    LevelModifiers,
+}
+
+impl RngStream {
+    const COUNT: usize = 9;
+}
+
+/// All randomness of the game. Reseeded at the start
+/// of each run, so runs with the same seed are the same.
+#[derive(Resource)]
+pub struct GameRng {
+    seed: u64,
+    streams: [StdRng; RngStream::COUNT],
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            streams: std::array::from_fn(|stream| {
+                StdRng::seed_from_u64(seed ^ (stream as u64 + 1).wrapping_mul(RNG_STREAM_SEED_MIX))
+            }),
+        }
+    }
+
+    pub fn reseed(&mut self, seed: u64) {
+        *self = Self::new(seed);
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn stream(&mut self, stream: RngStream) -> &mut StdRng {
+        &mut self.streams[stream as usize]
+    }
+}
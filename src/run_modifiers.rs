@@ -0,0 +1,102 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{enemies::Enemy, GlobalState, GRAVITY};
+
+const RUN_MODIFIER_LOW_GRAVITY_MODIFIER: f32 = 0.3;
+const RUN_MODIFIER_TINY_FRIDGES_SCALE_MODIFIER: f32 = 0.5;
+pub const RUN_MODIFIER_DOUBLE_ENEMIES_MODIFIER: u32 = 2;
+
+pub struct RunModifiersPlugin;
+
+impl Plugin for RunModifiersPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunModifiers>();
+
+        app.add_systems(OnEnter(GlobalState::MainMenu), reset_gravity);
+
+        app.add_systems(
+            Update,
+            apply_tiny_fridges.run_if(
+                in_state(GlobalState::InGame)
+                    .and_then(run_modifier_active(RunModifier::TinyFridges)),
+            ),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunModifier {
+    OneHitDeath,
+    NoHud,
+    DoubleEnemies,
+    LowGravity,
+    TinyFridges,
+}
+
+impl RunModifier {
+    pub fn score_multiplier(&self) -> f32 {
+        match self {
+            RunModifier::OneHitDeath => 2.0,
+            RunModifier::NoHud => 1.25,
+            RunModifier::DoubleEnemies => 1.5,
+            RunModifier::LowGravity => 1.1,
+            RunModifier::TinyFridges => 1.25,
+        }
+    }
+}
+
+// Modifiers selected before the game start.
+// Each modifier is checked by the systems
+// it affects, so any of them can be combined.
+#[derive(Debug, Default, Resource)]
+pub struct RunModifiers {
+    pub modifiers: Vec<RunModifier>,
+}
+
+impl RunModifiers {
+    pub fn has(&self, modifier: RunModifier) -> bool {
+        self.modifiers.contains(&modifier)
+    }
+
+    pub fn toggle(&mut self, modifier: RunModifier) {
+        if let Some(i) = self.modifiers.iter().position(|m| *m == modifier) {
+            self.modifiers.remove(i);
+        } else {
+            self.modifiers.push(modifier);
+        }
+    }
+
+    pub fn gravity_modifier(&self) -> f32 {
+        if self.has(RunModifier::LowGravity) {
+            RUN_MODIFIER_LOW_GRAVITY_MODIFIER
+        } else {
+            1.0
+        }
+    }
+
+    pub fn score_multiplier(&self) -> f32 {
+        self.modifiers
+            .iter()
+            .map(|modifier| modifier.score_multiplier())
+            .product()
+    }
+
+    pub fn apply_score_multiplier(&self, score: u32) -> u32 {
+        (score as f32 * self.score_multiplier()) as u32
+    }
+}
+
+pub fn run_modifier_active(modifier: RunModifier) -> impl Fn(Res<RunModifiers>) -> bool {
+    move |run_modifiers: Res<RunModifiers>| run_modifiers.has(modifier)
+}
+
+fn reset_gravity(mut physics: ResMut<RapierConfiguration>) {
+    physics.gravity = Vec3::NEG_Z * GRAVITY;
+}
+
+fn apply_tiny_fridges(mut enemies: Query<&mut Transform, Added<Enemy>>) {
+    for mut transform in enemies.iter_mut() {
+        transform.scale *= RUN_MODIFIER_TINY_FRIDGES_SCALE_MODIFIER;
+    }
+}
@@ -0,0 +1,106 @@
+use bevy::prelude::*;
+
+use crate::{damage::KillEvent, enemies::Enemy, weapons::WeaponType, GlobalState};
+
+/// Fun stats of the run shown on the end screen
+pub struct RunStatsPlugin;
+
+impl Plugin for RunStatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunStats>();
+
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::MainMenu,
+                to: GlobalState::InGame,
+            },
+            reset_run_stats,
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::GameOver,
+                to: GlobalState::InGame,
+            },
+            reset_run_stats,
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::GameWon,
+                to: GlobalState::InGame,
+            },
+            reset_run_stats,
+        );
+
+        app.add_systems(
+            Update,
+            run_stats_track_kills.run_if(in_state(GlobalState::InGame)),
+        );
+    }
+}
+
+#[derive(Debug, Default, Resource)]
+pub struct RunStats {
+    // Distance between the player weapon
+    // and the enemy killed by its shot
+    pub longest_kill: f32,
+    // Enemies killed by pellets of one shotgun shot
+    pub best_shotgun_blast: u32,
+    // Enemies killed by projectiles of other enemies
+    pub friendly_fire_kills: u32,
+}
+
+// Pellet group of the last shotgun kill
+// and the kills it has made so far
+#[derive(Default)]
+struct ShotgunBlast {
+    pellet_group: u32,
+    kills: u32,
+}
+
+fn reset_run_stats(mut run_stats: ResMut<RunStats>) {
+    *run_stats = RunStats::default();
+}
+
+fn run_stats_track_kills(
+    enemies: Query<&Transform, With<Enemy>>,
+    mut run_stats: ResMut<RunStats>,
+    mut shotgun_blast: Local<ShotgunBlast>,
+    mut kill_events: EventReader<KillEvent>,
+) {
+    for kill in kill_events.read() {
+        let Some(shot) = kill.shot else {
+            continue;
+        };
+        let Ok(transform) = enemies.get(kill.entity) else {
+            continue;
+        };
+
+        if !shot.player_weapon {
+            run_stats.friendly_fire_kills += 1;
+            continue;
+        }
+
+        let distance = transform.translation.distance(shot.origin);
+        if run_stats.longest_kill < distance {
+            run_stats.longest_kill = distance;
+        }
+
+        if shot.weapon_type != WeaponType::Shotgun {
+            continue;
+        }
+        let Some(pellet_group) = shot.pellet_group else {
+            continue;
+        };
+        if shotgun_blast.pellet_group == pellet_group && 0 < shotgun_blast.kills {
+            shotgun_blast.kills += 1;
+        } else {
+            *shotgun_blast = ShotgunBlast {
+                pellet_group,
+                kills: 1,
+            };
+        }
+        if run_stats.best_shotgun_blast < shotgun_blast.kills {
+            run_stats.best_shotgun_blast = shotgun_blast.kills;
+        }
+    }
+}
@@ -0,0 +1,312 @@
+use bevy::prelude::*;
+use bevy_asset_loader::prelude::*;
+use bevy_kira_audio::{Audio, AudioControl, AudioSource};
+use bevy_rapier3d::prelude::*;
+use rand::Rng;
+
+use crate::{
+    collision::CollisionLayer,
+    damage::KillEvent,
+    enemies::{Enemy, EnemyType},
+    level::LevelObject,
+    player::Player,
+    rng::{GameRng, RngStream},
+    GlobalState,
+};
+
+const SCRAP_SIZE: f32 = 0.3;
+const SCRAP_SMALL_ENEMY_DROP: u32 = 2;
+const SCRAP_MID_ENEMY_DROP: u32 = 4;
+const SCRAP_BIG_ENEMY_DROP: u32 = 10;
+const SCRAP_VALUE: u32 = 1;
+const SCRAP_INITIAL_VELOCITY: f32 = 5.0;
+// Coolant cells are taken out of killed
+// fridges right away, big ones always have one
+const SCRAP_MID_ENEMY_COOLANT_CHANCE: f64 = 0.25;
+const SCRAP_BIG_ENEMY_COOLANT_CELLS: u32 = 1;
+
+const SCRAP_MAGNET_RADIUS: f32 = 10.0;
+const SCRAP_MAGNET_SPEED: f32 = 30.0;
+const SCRAP_COLLECT_RADIUS: f32 = 2.0;
+
+const SCRAP_SPARKLE_SIZE: f32 = 0.5;
+const SCRAP_SPARKLE_LIFESPAWN: f32 = 0.3;
+
+pub struct ScrapPlugin;
+
+impl Plugin for ScrapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_collection_to_loading_state::<_, ScrapAssets>(GlobalState::AssetLoading);
+
+        app.init_resource::<Scrap>();
+
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::AssetLoading,
+                to: GlobalState::MainMenu,
+            },
+            init_resources,
+        );
+
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::MainMenu,
+                to: GlobalState::InGame,
+            },
+            reset_scrap,
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::GameOver,
+                to: GlobalState::InGame,
+            },
+            reset_scrap,
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::GameWon,
+                to: GlobalState::InGame,
+            },
+            reset_scrap,
+        );
+
+        app.add_systems(
+            Update,
+            (
+                scrap_drop,
+                scrap_magnet,
+                scrap_collect,
+                scrap_sparkle_update,
+            )
+                .run_if(in_state(GlobalState::InGame)),
+        );
+    }
+}
+
+#[derive(Default, Resource)]
+pub struct Scrap {
+    pub amount: u32,
+    // Used together with scrap for crafting
+    pub coolant_cells: u32,
+}
+
+#[derive(AssetCollection, Resource)]
+pub struct ScrapAssets {
+    #[asset(path = "scrap/scrap_collect.wav")]
+    pub collect_sound: Handle<AudioSource>,
+}
+
+#[derive(Resource)]
+struct ScrapResources {
+    scrap_mesh: Handle<Mesh>,
+    scrap_material: Handle<StandardMaterial>,
+    sparkle_mesh: Handle<Mesh>,
+    sparkle_material: Handle<StandardMaterial>,
+}
+
+#[derive(Component)]
+pub struct ScrapPickup {
+    pub value: u32,
+}
+
+#[derive(Component)]
+struct ScrapSparkle {
+    spawn_time: f32,
+}
+
+#[derive(Bundle)]
+pub struct ScrapBundle {
+    pub pbr_bundle: PbrBundle,
+    pub rigid_body: RigidBody,
+    pub collider: Collider,
+    pub collision_groups: CollisionGroups,
+    pub velocity: Velocity,
+    pub scrap_pickup: ScrapPickup,
+
+    pub level_object: LevelObject,
+}
+
+impl ScrapBundle {
+    fn new(scrap_resources: &ScrapResources, translation: Vec3, linvel: Vec3) -> Self {
+        Self {
+            pbr_bundle: PbrBundle {
+                mesh: scrap_resources.scrap_mesh.clone(),
+                material: scrap_resources.scrap_material.clone(),
+                transform: Transform::from_translation(translation),
+                ..default()
+            },
+            rigid_body: RigidBody::Dynamic,
+            collider: Collider::cuboid(SCRAP_SIZE / 2.0, SCRAP_SIZE / 2.0, SCRAP_SIZE / 2.0),
+            collision_groups: CollisionLayer::Debris.groups(),
+            velocity: Velocity {
+                linvel,
+                ..default()
+            },
+            scrap_pickup: ScrapPickup { value: SCRAP_VALUE },
+
+            level_object: LevelObject,
+        }
+    }
+}
+
+fn init_resources(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let scrap_mesh = meshes.add(shape::Box::new(SCRAP_SIZE, SCRAP_SIZE, SCRAP_SIZE).into());
+    let scrap_material = materials.add(StandardMaterial {
+        base_color: Color::SILVER,
+        metallic: 0.9,
+        perceptual_roughness: 0.3,
+        ..default()
+    });
+
+    let sparkle_mesh = meshes.add(
+        shape::UVSphere {
+            radius: SCRAP_SPARKLE_SIZE,
+            ..default()
+        }
+        .into(),
+    );
+    let sparkle_material = materials.add(StandardMaterial {
+        base_color: Color::GOLD,
+        emissive: Color::GOLD,
+        unlit: true,
+        ..default()
+    });
+
+    commands.insert_resource(ScrapResources {
+        scrap_mesh,
+        scrap_material,
+        sparkle_mesh,
+        sparkle_material,
+    });
+}
+
+fn reset_scrap(mut scrap: ResMut<Scrap>) {
+    *scrap = Scrap::default();
+}
+
+fn scrap_drop(
+    scrap_resources: Res<ScrapResources>,
+    enemies: Query<(&Transform, &Enemy)>,
+    mut scrap: ResMut<Scrap>,
+    mut game_rng: ResMut<GameRng>,
+    mut commands: Commands,
+    mut kill_events: EventReader<KillEvent>,
+) {
+    for kill_event in kill_events.read() {
+        let Ok((enemy_transform, enemy)) = enemies.get(kill_event.entity) else {
+            continue;
+        };
+
+        let drops = match enemy.enemy_type {
+            EnemyType::Small => SCRAP_SMALL_ENEMY_DROP,
+            EnemyType::Mid => SCRAP_MID_ENEMY_DROP,
+            EnemyType::Big => SCRAP_BIG_ENEMY_DROP,
+        };
+        scrap.coolant_cells += match enemy.enemy_type {
+            EnemyType::Small => 0,
+            EnemyType::Mid => game_rng
+                .stream(RngStream::Drops)
+                .gen_bool(SCRAP_MID_ENEMY_COOLANT_CHANCE) as u32,
+            EnemyType::Big => SCRAP_BIG_ENEMY_COOLANT_CELLS,
+        };
+
+        let rng = game_rng.stream(RngStream::Effects);
+        for _ in 0..drops {
+            let direction =
+                Vec3::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), 1.0).normalize();
+            commands.spawn(ScrapBundle::new(
+                scrap_resources.as_ref(),
+                enemy_transform.translation,
+                direction * SCRAP_INITIAL_VELOCITY,
+            ));
+        }
+    }
+}
+
+fn scrap_magnet(
+    players: Query<&Transform, With<Player>>,
+    mut scraps: Query<(&Transform, &mut Velocity), (With<ScrapPickup>, Without<Player>)>,
+) {
+    for (scrap_transform, mut scrap_velocity) in scraps.iter_mut() {
+        // scrap flies to the closest player
+        let closest = players
+            .iter()
+            .map(|player_transform| player_transform.translation - scrap_transform.translation)
+            .min_by(|a, b| a.length_squared().total_cmp(&b.length_squared()));
+        let Some(v) = closest else {
+            continue;
+        };
+        if v.length_squared() < SCRAP_MAGNET_RADIUS * SCRAP_MAGNET_RADIUS {
+            scrap_velocity.linvel = v.normalize_or_zero() * SCRAP_MAGNET_SPEED;
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn scrap_collect(
+    time: Res<Time>,
+    audio: Res<Audio>,
+    scrap_assets: Res<ScrapAssets>,
+    scrap_resources: Res<ScrapResources>,
+    players: Query<&Transform, With<Player>>,
+    scraps: Query<(Entity, &Transform, &ScrapPickup), Without<Player>>,
+    mut scrap: ResMut<Scrap>,
+    mut commands: Commands,
+) {
+    for (scrap_entity, scrap_transform, scrap_pickup) in scraps.iter() {
+        // scrap is shared, any player can collect it
+        let collected = players.iter().any(|player_transform| {
+            player_transform
+                .translation
+                .distance_squared(scrap_transform.translation)
+                <= SCRAP_COLLECT_RADIUS * SCRAP_COLLECT_RADIUS
+        });
+        if !collected {
+            continue;
+        }
+
+        scrap.amount += scrap_pickup.value;
+
+        commands.spawn((
+            PbrBundle {
+                mesh: scrap_resources.sparkle_mesh.clone(),
+                material: scrap_resources.sparkle_material.clone(),
+                transform: *scrap_transform,
+                ..default()
+            },
+            ScrapSparkle {
+                spawn_time: time.elapsed_seconds(),
+            },
+            LevelObject,
+        ));
+
+        audio.play(scrap_assets.collect_sound.clone());
+
+        let Some(e) = commands.get_entity(scrap_entity) else {
+            continue;
+        };
+        e.despawn_recursive();
+    }
+}
+
+fn scrap_sparkle_update(
+    time: Res<Time>,
+    mut sparkles: Query<(Entity, &ScrapSparkle, &mut Transform)>,
+    mut commands: Commands,
+) {
+    for (sparkle_entity, sparkle, mut sparkle_transform) in sparkles.iter_mut() {
+        let progress = (time.elapsed_seconds() - sparkle.spawn_time) / SCRAP_SPARKLE_LIFESPAWN;
+        if 1.0 <= progress {
+            let Some(e) = commands.get_entity(sparkle_entity) else {
+                continue;
+            };
+            e.despawn_recursive();
+        } else {
+            sparkle_transform.scale = Vec3::splat(1.0 - progress);
+        }
+    }
+}
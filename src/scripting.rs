@@ -0,0 +1,308 @@
+use std::{collections::HashMap, path::PathBuf, time::SystemTime};
+
+use bevy::prelude::*;
+use rhai::{Dynamic, Engine, Map, Scope, AST};
+
+use crate::{
+    damage::{DamageEvent, Health},
+    enemies::{DisabledEnemy, Enemy, EnemyScripted, EnemyType},
+    mods::{ModList, MODS_DIR},
+    player::Player,
+    GlobalState,
+};
+
+const SCRIPTS_DIR: &str = "scripts";
+// Scripts are too slow to run every frame
+const SCRIPTS_THINK_SECONDS: f32 = 0.2;
+const SCRIPTS_RELOAD_SECONDS: f32 = 1.0;
+
+const SCRIPT_FN_TARGET: &str = "target";
+const SCRIPT_FN_ATTACK: &str = "attack";
+const SCRIPT_FN_ON_DAMAGE: &str = "on_damage";
+
+pub struct ScriptingPlugin;
+
+impl Plugin for ScriptingPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(EnemyScripts::new());
+        app.insert_resource(ScriptingTimers {
+            think: Timer::from_seconds(SCRIPTS_THINK_SECONDS, TimerMode::Repeating),
+            reload: Timer::from_seconds(SCRIPTS_RELOAD_SECONDS, TimerMode::Repeating),
+        });
+
+        app.add_systems(Startup, find_enemy_scripts);
+        app.add_systems(Update, reload_enemy_scripts);
+        app.add_systems(
+            Update,
+            (enemy_scripts_on_damage, enemy_scripts_think)
+                .chain()
+                .run_if(in_state(GlobalState::InGame)),
+        );
+    }
+}
+
+struct EnemyScript {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    ast: Option<AST>,
+}
+
+impl EnemyScript {
+    fn has_fn(&self, name: &str) -> bool {
+        self.ast
+            .as_ref()
+            .map(|ast| ast.iter_functions().any(|f| f.name == name))
+            .unwrap_or(false)
+    }
+}
+
+/// Behavior scripts for each enemy type. Scripts are
+/// located in `scripts/<small|mid|big>.rhai` of the mod
+/// directory. If several mods have a script for the same
+/// enemy, the last one wins.
+#[derive(Resource)]
+struct EnemyScripts {
+    engine: Engine,
+    scripts: HashMap<EnemyType, EnemyScript>,
+}
+
+impl EnemyScripts {
+    fn new() -> Self {
+        let mut engine = Engine::new();
+        // broken script should not freeze the game
+        engine.set_max_operations(10_000);
+        Self {
+            engine,
+            scripts: HashMap::new(),
+        }
+    }
+
+    // Scripts with unchanged paths keep their loaded
+    // version, scripts of disabled mods are dropped
+    fn find(&mut self, mod_list: &ModList) {
+        let mut paths = HashMap::new();
+        for mod_info in mod_list.enabled() {
+            for enemy_type in [EnemyType::Small, EnemyType::Mid, EnemyType::Big] {
+                let path = PathBuf::from(MODS_DIR)
+                    .join(&mod_info.dir)
+                    .join(SCRIPTS_DIR)
+                    .join(script_name(enemy_type));
+                if path.exists() {
+                    paths.insert(enemy_type, path);
+                }
+            }
+        }
+
+        self.scripts
+            .retain(|enemy_type, script| paths.get(enemy_type) == Some(&script.path));
+        for (enemy_type, path) in paths {
+            self.scripts.entry(enemy_type).or_insert(EnemyScript {
+                path,
+                modified: None,
+                ast: None,
+            });
+        }
+    }
+
+    // Old version is kept if the new one
+    // does not compile
+    fn load_changed(&mut self) {
+        for script in self.scripts.values_mut() {
+            let modified = std::fs::metadata(&script.path)
+                .and_then(|metadata| metadata.modified())
+                .ok();
+            if modified.is_none() || modified == script.modified {
+                continue;
+            }
+            script.modified = modified;
+
+            match std::fs::read_to_string(&script.path)
+                .map_err(|e| e.to_string())
+                .and_then(|source| self.engine.compile(source).map_err(|e| e.to_string()))
+            {
+                Ok(ast) => {
+                    info!("Loaded script {}", script.path.display());
+                    script.ast = Some(ast);
+                }
+                Err(e) => warn!("Could not load script {}: {e}", script.path.display()),
+            }
+        }
+    }
+
+    fn call(
+        &self,
+        enemy_type: EnemyType,
+        name: &str,
+        args: impl rhai::FuncArgs,
+    ) -> Option<Dynamic> {
+        let script = self.scripts.get(&enemy_type)?;
+        if !script.has_fn(name) {
+            return None;
+        }
+        let ast = script.ast.as_ref()?;
+        match self
+            .engine
+            .call_fn::<Dynamic>(&mut Scope::new(), ast, name, args)
+        {
+            Ok(result) => Some(result),
+            Err(e) => {
+                warn!("Script {} failed in {name}: {e}", script.path.display());
+                None
+            }
+        }
+    }
+}
+
+#[derive(Resource)]
+struct ScriptingTimers {
+    think: Timer,
+    reload: Timer,
+}
+
+fn script_name(enemy_type: EnemyType) -> &'static str {
+    match enemy_type {
+        EnemyType::Small => "small.rhai",
+        EnemyType::Mid => "mid.rhai",
+        EnemyType::Big => "big.rhai",
+    }
+}
+
+fn find_enemy_scripts(mod_list: Res<ModList>, mut enemy_scripts: ResMut<EnemyScripts>) {
+    enemy_scripts.find(mod_list.as_ref());
+    enemy_scripts.load_changed();
+}
+
+// Mods are scanned again, so scripts of newly
+// enabled mods are picked up without a restart
+fn reload_enemy_scripts(
+    time: Res<Time>,
+    mod_list: Res<ModList>,
+    mut timers: ResMut<ScriptingTimers>,
+    mut enemy_scripts: ResMut<EnemyScripts>,
+) {
+    if !timers.reload.tick(time.delta()).just_finished() {
+        return;
+    }
+    enemy_scripts.find(mod_list.as_ref());
+    enemy_scripts.load_changed();
+}
+
+fn enemy_map(transform: &Transform, health: &Health) -> Map {
+    let mut map = Map::new();
+    map.insert("x".into(), Dynamic::from(transform.translation.x));
+    map.insert("y".into(), Dynamic::from(transform.translation.y));
+    map.insert("health".into(), Dynamic::from(health.health as i64));
+    map
+}
+
+fn player_map(transform: &Transform) -> Map {
+    let mut map = Map::new();
+    map.insert("x".into(), Dynamic::from(transform.translation.x));
+    map.insert("y".into(), Dynamic::from(transform.translation.y));
+    map
+}
+
+// Scripts may return whole numbers as well
+fn float_field(map: &Map, name: &str) -> Option<f32> {
+    let value = map.get(name)?;
+    value
+        .as_float()
+        .ok()
+        .or_else(|| value.as_int().ok().map(|value| value as f32))
+}
+
+// `target` and `on_damage` return `#{ x, y, speed }`, where
+// `x` and `y` are the offset of the target from the player.
+// Missing fields keep the previous decision.
+fn apply_target(scripted: &mut EnemyScripted, result: Dynamic) {
+    let Some(map) = result.try_cast::<Map>() else {
+        return;
+    };
+    if let Some(x) = float_field(&map, "x") {
+        scripted.target_offset.x = x;
+    }
+    if let Some(y) = float_field(&map, "y") {
+        scripted.target_offset.y = y;
+    }
+    if let Some(speed) = float_field(&map, "speed") {
+        scripted.speed_modifier = speed.max(0.0);
+    }
+}
+
+#[allow(clippy::complexity)]
+fn enemy_scripts_think(
+    time: Res<Time>,
+    enemy_scripts: Res<EnemyScripts>,
+    player: Query<&Transform, With<Player>>,
+    mut timers: ResMut<ScriptingTimers>,
+    mut commands: Commands,
+    mut enemies: Query<
+        (
+            Entity,
+            &Enemy,
+            &Transform,
+            &Health,
+            Option<&mut EnemyScripted>,
+        ),
+        Without<DisabledEnemy>,
+    >,
+) {
+    if !timers.think.tick(time.delta()).just_finished() {
+        return;
+    }
+    let Ok(player_transform) = player.get_single() else {
+        return;
+    };
+
+    for (entity, enemy, transform, health, scripted) in enemies.iter_mut() {
+        if !enemy_scripts.scripts.contains_key(&enemy.enemy_type) {
+            if scripted.is_some() {
+                commands.entity(entity).remove::<EnemyScripted>();
+            }
+            continue;
+        }
+
+        let mut decision = scripted.as_deref().copied().unwrap_or_default();
+        let enemy_arg = enemy_map(transform, health);
+        let player_arg = player_map(player_transform);
+        if let Some(result) = enemy_scripts.call(
+            enemy.enemy_type,
+            SCRIPT_FN_TARGET,
+            (enemy_arg.clone(), player_arg.clone()),
+        ) {
+            apply_target(&mut decision, result);
+        }
+        if let Some(result) =
+            enemy_scripts.call(enemy.enemy_type, SCRIPT_FN_ATTACK, (enemy_arg, player_arg))
+        {
+            if let Ok(attack) = result.as_bool() {
+                decision.hold_fire = !attack;
+            }
+        }
+
+        match scripted {
+            Some(mut scripted) => *scripted = decision,
+            None => {
+                commands.entity(entity).insert(decision);
+            }
+        }
+    }
+}
+
+fn enemy_scripts_on_damage(
+    enemy_scripts: Res<EnemyScripts>,
+    mut damage_events: EventReader<DamageEvent>,
+    mut enemies: Query<(&Enemy, &Transform, &Health, &mut EnemyScripted), Without<DisabledEnemy>>,
+) {
+    for event in damage_events.read() {
+        let Ok((enemy, transform, health, mut scripted)) = enemies.get_mut(event.entity) else {
+            continue;
+        };
+        let enemy_arg = enemy_map(transform, health);
+        if let Some(result) =
+            enemy_scripts.call(enemy.enemy_type, SCRIPT_FN_ON_DAMAGE, (enemy_arg,))
+        {
+            apply_target(&mut scripted, result);
+        }
+    }
+}
@@ -0,0 +1,399 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    enemies::Enemy,
+    level::{LevelInfo, LevelStarted},
+    player::PlayerCamera,
+    profile::load_ron,
+    ui::UiAssets,
+    GlobalState,
+};
+
+const SEQUENCE_SKIP_KEY: KeyCode = KeyCode::Return;
+const SEQUENCE_INTRO_PATH: &str = "assets/sequences/intro.ron";
+const SEQUENCE_BOSS_PATH: &str = "assets/sequences/boss.ron";
+
+const SEQUENCE_SCREEN_SIZE: Vec2 = Vec2::new(1280.0, 720.0);
+const SEQUENCE_LETTERBOX_HEIGHT: f32 = 90.0;
+const SEQUENCE_LETTERBOX_SLIDE_SECONDS: f32 = 0.5;
+const SEQUENCE_TEXT_FADE_SECONDS: f32 = 0.5;
+const SEQUENCE_TEXT_SIZE: f32 = 60.0;
+// Overlay is drawn above the rest of the HUD
+const SEQUENCE_OVERLAY_Z: f32 = 10.0;
+
+/// Short scripted scenes, e.g. the game intro and boss
+/// entrance. Sequences are loaded from `assets/sequences`
+/// and pause the game while they play. Missing or empty
+/// files are skipped.
+pub struct SequencePlugin;
+
+impl Plugin for SequencePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PlaySequence>();
+        app.init_resource::<SequencePlayer>();
+
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::MainMenu,
+                to: GlobalState::InGame,
+            },
+            play_intro_sequence,
+        );
+
+        app.add_systems(
+            Update,
+            (
+                play_boss_sequence,
+                sequence_start,
+                sequence_progress,
+                sequence_camera,
+                sequence_enemies,
+                sequence_overlay,
+                sequence_finish,
+            )
+                .chain()
+                .run_if(in_state(GlobalState::InGame)),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SequenceId {
+    Intro,
+    BossEntrance,
+}
+
+impl SequenceId {
+    fn path(&self) -> &'static str {
+        match self {
+            SequenceId::Intro => SEQUENCE_INTRO_PATH,
+            SequenceId::BossEntrance => SEQUENCE_BOSS_PATH,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Event)]
+pub struct PlaySequence {
+    pub sequence: SequenceId,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Sequence {
+    pub steps: Vec<SequenceStep>,
+}
+
+impl Sequence {
+    fn duration(&self) -> f32 {
+        self.steps
+            .iter()
+            .map(|step| step.start + step.duration)
+            .fold(0.0, f32::max)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SequenceStep {
+    // Seconds from the sequence start
+    pub start: f32,
+    pub duration: f32,
+    pub action: SequenceAction,
+}
+
+impl SequenceStep {
+    // 0.0 before the step, 1.0 after it
+    fn progress(&self, time: f32) -> f32 {
+        if self.duration <= 0.0 {
+            return if self.start <= time { 1.0 } else { 0.0 };
+        }
+        ((time - self.start) / self.duration).clamp(0.0, 1.0)
+    }
+
+    fn active(&self, time: f32) -> bool {
+        self.start <= time && time < self.start + self.duration
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SequenceAction {
+    // Camera offset from its usual place
+    CameraMove { from: [f32; 3], to: [f32; 3] },
+    // Camera turn in radians around the vertical axis
+    CameraTurn { from: f32, to: f32 },
+    // Black bars at the top and the bottom of the screen
+    Letterbox,
+    TextCard { text: String },
+    // Enemies start below their place and rise to it
+    EnemiesRise { height: f32 },
+}
+
+/// Sequence that is playing now. Sequences use
+/// the real time, because the game time is paused.
+#[derive(Default, Resource)]
+pub struct SequencePlayer {
+    sequence: Option<Sequence>,
+    time: f32,
+    camera_transform: Transform,
+    enemy_translations: Vec<(Entity, Vec3)>,
+}
+
+pub fn sequence_playing(sequence_player: Res<SequencePlayer>) -> bool {
+    sequence_player.sequence.is_some()
+}
+
+#[derive(Component)]
+struct SequenceOverlay;
+
+#[derive(Component)]
+struct SequenceLetterbox {
+    // 1.0 for the top bar, -1.0 for the bottom one
+    side: f32,
+}
+
+#[derive(Component)]
+struct SequenceText;
+
+fn play_intro_sequence(mut play_sequence_events: EventWriter<PlaySequence>) {
+    play_sequence_events.send(PlaySequence {
+        sequence: SequenceId::Intro,
+    });
+}
+
+fn play_boss_sequence(
+    level_info: Res<LevelInfo>,
+    mut level_started_events: EventReader<LevelStarted>,
+    mut play_sequence_events: EventWriter<PlaySequence>,
+) {
+    for _ in level_started_events.read() {
+        if level_info.game_progress == 100 {
+            play_sequence_events.send(PlaySequence {
+                sequence: SequenceId::BossEntrance,
+            });
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn sequence_start(
+    ui_assets: Res<UiAssets>,
+    camera: Query<&Transform, With<PlayerCamera>>,
+    enemies: Query<(Entity, &Transform), With<Enemy>>,
+    mut time: ResMut<Time<Virtual>>,
+    mut physics: ResMut<RapierConfiguration>,
+    mut sequence_player: ResMut<SequencePlayer>,
+    mut commands: Commands,
+    mut play_sequence_events: EventReader<PlaySequence>,
+) {
+    for event in play_sequence_events.read() {
+        if sequence_player.sequence.is_some() {
+            continue;
+        }
+        let sequence = load_ron::<Sequence>(event.sequence.path());
+        if sequence.steps.is_empty() {
+            continue;
+        }
+        let Ok(camera_transform) = camera.get_single() else {
+            continue;
+        };
+
+        time.pause();
+        physics.physics_pipeline_active = false;
+
+        *sequence_player = SequencePlayer {
+            sequence: Some(sequence),
+            time: 0.0,
+            camera_transform: *camera_transform,
+            enemy_translations: enemies
+                .iter()
+                .map(|(entity, transform)| (entity, transform.translation))
+                .collect(),
+        };
+
+        for side in [1.0, -1.0] {
+            commands.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::BLACK,
+                        custom_size: Some(Vec2::new(
+                            SEQUENCE_SCREEN_SIZE.x,
+                            SEQUENCE_LETTERBOX_HEIGHT,
+                        )),
+                        ..default()
+                    },
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                SequenceLetterbox { side },
+                SequenceOverlay,
+            ));
+        }
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section(
+                    "",
+                    TextStyle {
+                        font: ui_assets.font.clone(),
+                        font_size: SEQUENCE_TEXT_SIZE,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_alignment(TextAlignment::Center),
+                transform: Transform::from_translation(Vec3::Z * SEQUENCE_OVERLAY_Z),
+                ..default()
+            },
+            SequenceText,
+            SequenceOverlay,
+        ));
+    }
+}
+
+fn sequence_progress(
+    time: Res<Time<Real>>,
+    keys: Res<Input<KeyCode>>,
+    mut sequence_player: ResMut<SequencePlayer>,
+) {
+    let Some(duration) = sequence_player.sequence.as_ref().map(Sequence::duration) else {
+        return;
+    };
+    // skipping jumps to the end, so everything
+    // ends up where the sequence leaves it
+    if keys.just_pressed(SEQUENCE_SKIP_KEY) {
+        sequence_player.time = duration;
+    } else {
+        sequence_player.time += time.delta_seconds();
+    }
+}
+
+fn sequence_camera(
+    sequence_player: Res<SequencePlayer>,
+    mut camera: Query<&mut Transform, With<PlayerCamera>>,
+) {
+    let Some(sequence) = &sequence_player.sequence else {
+        return;
+    };
+    let Ok(mut transform) = camera.get_single_mut() else {
+        return;
+    };
+
+    let time = sequence_player.time;
+    let mut translation = sequence_player.camera_transform.translation;
+    let mut rotation = sequence_player.camera_transform.rotation;
+    // later steps override earlier ones
+    for step in sequence.steps.iter().filter(|step| step.start <= time) {
+        let t = step.progress(time);
+        match step.action {
+            SequenceAction::CameraMove { from, to } => {
+                translation = sequence_player.camera_transform.translation
+                    + Vec3::from(from).lerp(Vec3::from(to), t);
+            }
+            SequenceAction::CameraTurn { from, to } => {
+                rotation = Quat::from_rotation_z(from + (to - from) * t)
+                    * sequence_player.camera_transform.rotation;
+            }
+            _ => {}
+        }
+    }
+    transform.translation = translation;
+    transform.rotation = rotation;
+}
+
+fn sequence_enemies(
+    sequence_player: Res<SequencePlayer>,
+    mut enemies: Query<&mut Transform, With<Enemy>>,
+) {
+    let Some(sequence) = &sequence_player.sequence else {
+        return;
+    };
+
+    let time = sequence_player.time;
+    let mut offset = Vec3::ZERO;
+    for step in sequence.steps.iter() {
+        if let SequenceAction::EnemiesRise { height } = step.action {
+            offset = Vec3::NEG_Z * height * (1.0 - step.progress(time));
+        }
+    }
+    for (entity, translation) in sequence_player.enemy_translations.iter() {
+        if let Ok(mut transform) = enemies.get_mut(*entity) {
+            transform.translation = *translation + offset;
+        }
+    }
+}
+
+fn sequence_overlay(
+    sequence_player: Res<SequencePlayer>,
+    mut letterboxes: Query<(&SequenceLetterbox, &mut Transform, &mut Visibility)>,
+    mut texts: Query<&mut Text, With<SequenceText>>,
+) {
+    let Some(sequence) = &sequence_player.sequence else {
+        return;
+    };
+
+    let time = sequence_player.time;
+    // how far bars are slid in, 0.0 - hidden
+    let mut letterbox = 0.0_f32;
+    let mut card: Option<(&str, f32)> = None;
+    for step in sequence.steps.iter().filter(|step| step.active(time)) {
+        let fade = |seconds: f32| {
+            ((time - step.start) / seconds)
+                .min((step.start + step.duration - time) / seconds)
+                .clamp(0.0, 1.0)
+        };
+        match &step.action {
+            SequenceAction::Letterbox => {
+                letterbox = letterbox.max(fade(SEQUENCE_LETTERBOX_SLIDE_SECONDS));
+            }
+            SequenceAction::TextCard { text } => {
+                card = Some((text, fade(SEQUENCE_TEXT_FADE_SECONDS)));
+            }
+            _ => {}
+        }
+    }
+
+    for (bar, mut transform, mut visibility) in letterboxes.iter_mut() {
+        *visibility = if 0.0 < letterbox {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+        let hidden_y = SEQUENCE_SCREEN_SIZE.y / 2.0 + SEQUENCE_LETTERBOX_HEIGHT / 2.0;
+        transform.translation = Vec3::new(
+            0.0,
+            bar.side * (hidden_y - SEQUENCE_LETTERBOX_HEIGHT * letterbox),
+            SEQUENCE_OVERLAY_Z,
+        );
+    }
+
+    if let Ok(mut text) = texts.get_single_mut() {
+        let (value, alpha) = card.unwrap_or(("", 0.0));
+        text.sections[0].value = value.to_string();
+        text.sections[0].style.color.set_a(alpha);
+    }
+}
+
+fn sequence_finish(
+    overlays: Query<Entity, With<SequenceOverlay>>,
+    mut camera: Query<&mut Transform, With<PlayerCamera>>,
+    mut time: ResMut<Time<Virtual>>,
+    mut physics: ResMut<RapierConfiguration>,
+    mut sequence_player: ResMut<SequencePlayer>,
+    mut commands: Commands,
+) {
+    let Some(duration) = sequence_player.sequence.as_ref().map(Sequence::duration) else {
+        return;
+    };
+    if sequence_player.time < duration {
+        return;
+    }
+
+    if let Ok(mut transform) = camera.get_single_mut() {
+        *transform = sequence_player.camera_transform;
+    }
+    sequence_player.sequence = None;
+    time.unpause();
+    physics.physics_pipeline_active = true;
+    for overlay in overlays.iter() {
+        commands.entity(overlay).despawn_recursive();
+    }
+}
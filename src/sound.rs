@@ -0,0 +1,98 @@
+use std::time::Duration;
+
+use bevy::{prelude::*, utils::HashMap};
+use bevy_kira_audio::{
+    AudioChannel, AudioControl, AudioInstance, AudioSource, AudioTween, PlaybackState,
+};
+use rand::Rng;
+
+// Voice stolen by a new one fades out
+// this fast instead of cutting off
+const SOUND_STEAL_FADE_MILLIS: u64 = 20;
+
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SoundPool>();
+    }
+}
+
+/// How often a sound can be played
+#[derive(Debug, Clone, Copy)]
+pub struct SoundLimit {
+    // Copies of the sound playing at the same time,
+    // the oldest one is stopped for a new one
+    pub max_voices: usize,
+    // Plays closer in time than this are skipped
+    pub cooldown: f32,
+    // Playback rate is randomized by up to this value,
+    // so repeated plays do not phase with each other
+    pub pitch_variation: f64,
+}
+
+// Playing copies of one sound
+#[derive(Debug, Default)]
+struct SoundVoices {
+    instances: Vec<Handle<AudioInstance>>,
+    last_play: Option<f32>,
+}
+
+/// Keeps track of playing copies of each sound
+/// to apply `SoundLimit`s to them.
+#[derive(Debug, Default, Resource)]
+pub struct SoundPool {
+    voices: HashMap<AssetId<AudioSource>, SoundVoices>,
+}
+
+impl SoundPool {
+    /// Plays the sound unless it is on cooldown
+    #[allow(clippy::too_many_arguments)]
+    pub fn play<T: Resource>(
+        &mut self,
+        channel: &AudioChannel<T>,
+        audio_instances: &mut Assets<AudioInstance>,
+        sound: &Handle<AudioSource>,
+        limit: SoundLimit,
+        volume: f64,
+        panning: f64,
+        now: f32,
+        rng: &mut impl Rng,
+    ) {
+        let voices = self.voices.entry(sound.id()).or_default();
+        if voices
+            .last_play
+            .is_some_and(|last_play| now - last_play < limit.cooldown)
+        {
+            return;
+        }
+        voices.last_play = Some(now);
+
+        voices.instances.retain(|instance| {
+            audio_instances
+                .get(instance)
+                .is_some_and(|instance| instance.state() != PlaybackState::Stopped)
+        });
+        if limit.max_voices <= voices.instances.len() {
+            let oldest = voices.instances.remove(0);
+            if let Some(instance) = audio_instances.get_mut(&oldest) {
+                instance.stop(AudioTween::linear(Duration::from_millis(
+                    SOUND_STEAL_FADE_MILLIS,
+                )));
+            }
+        }
+
+        let playback_rate = if 0.0 < limit.pitch_variation {
+            1.0 + rng.gen_range(-limit.pitch_variation..limit.pitch_variation)
+        } else {
+            1.0
+        };
+        let instance = channel
+            .play(sound.clone())
+            .with_volume(volume)
+            .with_panning(panning)
+            .with_playback_rate(playback_rate)
+            .handle();
+        voices.instances.push(instance);
+    }
+}
@@ -0,0 +1,110 @@
+use bevy::prelude::*;
+use bevy_asset_loader::prelude::*;
+use bevy_kira_audio::{Audio, AudioApp, AudioChannel, AudioControl, AudioSource};
+
+use crate::{
+    enemies::BossPhaseChanged,
+    level::{terminal::SecretFound, LevelFinished},
+    GameSettings, GlobalState,
+};
+
+// Part of the music volume left
+// while a stinger is playing
+pub const STINGER_MUSIC_DUCK_VOLUME: f32 = 0.3;
+
+/// Short musical cues on top of the music. Music
+/// is ducked until the stinger finishes.
+pub struct StingerPlugin;
+
+impl Plugin for StingerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_collection_to_loading_state::<_, StingerAssets>(GlobalState::AssetLoading);
+
+        app.add_audio_channel::<StingerChannel>();
+
+        app.add_systems(
+            Update,
+            (stinger_play, stinger_unduck)
+                .chain()
+                .run_if(in_state(GlobalState::InGame)),
+        );
+        app.add_systems(OnExit(GlobalState::InGame), stinger_stop);
+        app.add_systems(
+            Update,
+            stinger_update_volume.run_if(resource_changed::<GameSettings>()),
+        );
+    }
+}
+
+#[derive(AssetCollection, Resource)]
+pub struct StingerAssets {
+    #[asset(path = "stingers/secret_found.wav")]
+    pub secret_found: Handle<AudioSource>,
+    #[asset(path = "stingers/boss_phase.wav")]
+    pub boss_phase: Handle<AudioSource>,
+    #[asset(path = "stingers/level_cleared.wav")]
+    pub level_cleared: Handle<AudioSource>,
+}
+
+#[derive(Resource)]
+pub struct StingerChannel;
+
+fn stinger_play(
+    audio: Res<Audio>,
+    game_settings: Res<GameSettings>,
+    stinger_assets: Res<StingerAssets>,
+    stinger_channel: Res<AudioChannel<StingerChannel>>,
+    mut secret_found_events: EventReader<SecretFound>,
+    mut boss_phase_events: EventReader<BossPhaseChanged>,
+    mut level_finished_events: EventReader<LevelFinished>,
+) {
+    let secrets = secret_found_events
+        .read()
+        .map(|_| &stinger_assets.secret_found);
+    let boss_phases = boss_phase_events.read().map(|_| &stinger_assets.boss_phase);
+    let levels = level_finished_events
+        .read()
+        .map(|_| &stinger_assets.level_cleared);
+
+    // only the last stinger of the frame is played
+    let Some(stinger) = secrets.chain(boss_phases).chain(levels).last() else {
+        return;
+    };
+
+    stinger_channel.stop();
+    stinger_channel.play(stinger.clone());
+    audio.set_volume((game_settings.volume * STINGER_MUSIC_DUCK_VOLUME) as f64);
+}
+
+fn stinger_unduck(
+    audio: Res<Audio>,
+    game_settings: Res<GameSettings>,
+    stinger_channel: Res<AudioChannel<StingerChannel>>,
+    mut ducked: Local<bool>,
+) {
+    // stinger starts playing a frame after it is
+    // requested, so music is restored only after
+    // it was seen playing
+    if stinger_channel.is_playing_sound() {
+        *ducked = true;
+    } else if *ducked {
+        *ducked = false;
+        audio.set_volume(game_settings.volume as f64);
+    }
+}
+
+fn stinger_stop(
+    audio: Res<Audio>,
+    game_settings: Res<GameSettings>,
+    stinger_channel: Res<AudioChannel<StingerChannel>>,
+) {
+    stinger_channel.stop();
+    audio.set_volume(game_settings.volume as f64);
+}
+
+fn stinger_update_volume(
+    game_settings: Res<GameSettings>,
+    stinger_channel: Res<AudioChannel<StingerChannel>>,
+) {
+    stinger_channel.set_volume(game_settings.volume as f64);
+}
@@ -0,0 +1,171 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::{
+    damage::{KillCause, KillEvent},
+    enemies::Enemy,
+    level::grade::RunBonus,
+    weapons::WeaponType,
+    GlobalState,
+};
+
+const STYLE_POINTS_PER_RANK: f32 = 100.0;
+const STYLE_KILL_POINTS: f32 = 60.0;
+// Kill of the same kind as one of the recent
+// kills gives less points for each repeat
+const STYLE_RECENT_KILLS: usize = 4;
+const STYLE_DECAY_PER_SECOND: f32 = 15.0;
+// Run bonus of one kill before the rank multiplier
+const STYLE_KILL_SCORE: u32 = 10;
+
+pub struct StylePlugin;
+
+impl Plugin for StylePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<StyleMeter>();
+
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::MainMenu,
+                to: GlobalState::InGame,
+            },
+            reset_style_meter,
+        );
+
+        app.add_systems(
+            Update,
+            (style_track_kills, style_decay)
+                .chain()
+                .run_if(in_state(GlobalState::InGame)),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StyleRank {
+    D,
+    C,
+    B,
+    A,
+    S,
+    SS,
+    SSS,
+}
+
+impl StyleRank {
+    const ALL: [StyleRank; 7] = [
+        StyleRank::D,
+        StyleRank::C,
+        StyleRank::B,
+        StyleRank::A,
+        StyleRank::S,
+        StyleRank::SS,
+        StyleRank::SSS,
+    ];
+
+    pub fn multiplier(&self) -> f32 {
+        match self {
+            StyleRank::D => 1.0,
+            StyleRank::C => 1.1,
+            StyleRank::B => 1.25,
+            StyleRank::A => 1.5,
+            StyleRank::S => 2.0,
+            StyleRank::SS => 2.5,
+            StyleRank::SSS => 3.0,
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            StyleRank::D => Color::GRAY,
+            StyleRank::C => Color::SEA_GREEN,
+            StyleRank::B => Color::LIME_GREEN,
+            StyleRank::A => Color::ORANGE,
+            StyleRank::S | StyleRank::SS | StyleRank::SSS => Color::GOLD,
+        }
+    }
+}
+
+// Kinds of kills, variety is rewarded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StyleKill {
+    Shot(WeaponType),
+    Impact,
+    Environment,
+}
+
+/// Style of the current run, rises with varied
+/// kills and goes down over time
+#[derive(Debug, Default, Resource)]
+pub struct StyleMeter {
+    pub points: f32,
+    recent: VecDeque<StyleKill>,
+}
+
+impl StyleMeter {
+    pub fn rank(&self) -> StyleRank {
+        let index = (self.points / STYLE_POINTS_PER_RANK) as usize;
+        StyleRank::ALL[index.min(StyleRank::ALL.len() - 1)]
+    }
+
+    // Progress to the next rank from 0 to 1
+    pub fn rank_progress(&self) -> f32 {
+        if self.rank() == StyleRank::SSS {
+            1.0
+        } else {
+            (self.points % STYLE_POINTS_PER_RANK) / STYLE_POINTS_PER_RANK
+        }
+    }
+
+    fn add_kill(&mut self, kill: StyleKill) {
+        let repeats = self.recent.iter().filter(|k| **k == kill).count();
+        let max_points = StyleRank::ALL.len() as f32 * STYLE_POINTS_PER_RANK - 1.0;
+        self.points = (self.points + STYLE_KILL_POINTS / (1 + repeats) as f32).min(max_points);
+
+        self.recent.push_back(kill);
+        if STYLE_RECENT_KILLS < self.recent.len() {
+            self.recent.pop_front();
+        }
+    }
+}
+
+fn reset_style_meter(mut style_meter: ResMut<StyleMeter>) {
+    *style_meter = StyleMeter::default();
+}
+
+// Shots are counted for the weapon
+// the killing projectile came from
+fn style_track_kills(
+    enemies: Query<(), With<Enemy>>,
+    mut style_meter: ResMut<StyleMeter>,
+    mut run_bonus: ResMut<RunBonus>,
+    mut kill_events: EventReader<KillEvent>,
+) {
+    for kill_event in kill_events.read() {
+        if !enemies.contains(kill_event.entity) {
+            continue;
+        }
+        let kill = match kill_event.cause {
+            KillCause::Shot => {
+                let Some(shot) = kill_event.shot else {
+                    continue;
+                };
+                StyleKill::Shot(shot.weapon_type)
+            }
+            KillCause::Impact => StyleKill::Impact,
+            KillCause::Environment => StyleKill::Environment,
+        };
+
+        run_bonus.score += (STYLE_KILL_SCORE as f32 * style_meter.rank().multiplier()) as u32;
+        style_meter.add_kill(kill);
+    }
+}
+
+fn style_decay(time: Res<Time>, mut style_meter: ResMut<StyleMeter>) {
+    if style_meter.points <= 0.0 {
+        return;
+    }
+    style_meter.points =
+        (style_meter.points - STYLE_DECAY_PER_SECOND * time.delta_seconds()).max(0.0);
+}
@@ -0,0 +1,303 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    level::{LevelFinished, LevelStarted},
+    profile::{load_ron, save_ron},
+    GlobalState,
+};
+
+const TIME_ATTACK_RECORDS_PATH: &str = "time_attack.ron";
+// Least recently played seeds are dropped past this
+const TIME_ATTACK_MAX_RECORDS: usize = 32;
+
+// Target times for a single level in seconds
+const TIME_ATTACK_GOLD_TIME: f32 = 20.0;
+const TIME_ATTACK_SILVER_TIME: f32 = 35.0;
+const TIME_ATTACK_BRONZE_TIME: f32 = 50.0;
+
+pub struct TimeAttackPlugin;
+
+impl Plugin for TimeAttackPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TimeAttack>();
+        app.insert_resource(load_ron::<TimeAttackRecords>(TIME_ATTACK_RECORDS_PATH));
+
+        app.add_event::<TimeAttackSplit>();
+
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::MainMenu,
+                to: GlobalState::InGame,
+            },
+            remember_time_attack_seed.run_if(time_attack_active),
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::GameOver,
+                to: GlobalState::InGame,
+            },
+            restart_time_attack,
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::InGame,
+                to: GlobalState::GameWon,
+            },
+            time_attack_finish.run_if(time_attack_active),
+        );
+
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::Paused,
+                to: GlobalState::MainMenu,
+            },
+            reset_time_attack,
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::GameOver,
+                to: GlobalState::MainMenu,
+            },
+            reset_time_attack,
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::GameWon,
+                to: GlobalState::MainMenu,
+            },
+            reset_time_attack,
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::Hub,
+                to: GlobalState::MainMenu,
+            },
+            reset_time_attack,
+        );
+
+        // Timer only runs in game, so it is stopped
+        // while game is paused
+        app.add_systems(
+            Update,
+            time_attack_update.run_if(in_state(GlobalState::InGame).and_then(time_attack_active)),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Medal {
+    Gold,
+    Silver,
+    Bronze,
+}
+
+impl Medal {
+    // Higher is better
+    pub fn rank(&self) -> u32 {
+        match self {
+            Medal::Gold => 3,
+            Medal::Silver => 2,
+            Medal::Bronze => 1,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Medal::Gold => "GOLD",
+            Medal::Silver => "SILVER",
+            Medal::Bronze => "BRONZE",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Medal::Gold => Color::GOLD,
+            Medal::Silver => Color::SILVER,
+            Medal::Bronze => Color::ORANGE_RED,
+        }
+    }
+
+    pub fn from_level_time(time: f32) -> Option<Self> {
+        if time <= TIME_ATTACK_GOLD_TIME {
+            Some(Medal::Gold)
+        } else if time <= TIME_ATTACK_SILVER_TIME {
+            Some(Medal::Silver)
+        } else if time <= TIME_ATTACK_BRONZE_TIME {
+            Some(Medal::Bronze)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Event)]
+pub struct TimeAttackSplit {
+    pub level: usize,
+    pub time: f32,
+    pub best: Option<f32>,
+    pub medal: Option<Medal>,
+}
+
+// Level timer only runs between level start and
+// level finish, so time spent going between levels
+// is not counted.
+#[derive(Debug, Default, Resource)]
+pub struct TimeAttack {
+    pub active: bool,
+    pub seed: u64,
+    pub level_running: bool,
+    pub level_time: f32,
+    pub splits: Vec<f32>,
+}
+
+impl TimeAttack {
+    pub fn start(&mut self, seed: u64) {
+        *self = Self {
+            active: true,
+            seed,
+            ..default()
+        };
+    }
+
+    pub fn total_time(&self) -> f32 {
+        self.splits.iter().sum::<f32>() + self.level_time
+    }
+
+    fn finish_level(&mut self) -> f32 {
+        let time = self.level_time;
+        self.splits.push(time);
+        self.level_time = 0.0;
+        self.level_running = false;
+        time
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct TimeAttackRecord {
+    pub seed: u64,
+    pub best_splits: Vec<f32>,
+    pub best_total: Option<f32>,
+}
+
+#[derive(Debug, Default, Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimeAttackRecords {
+    pub last_seed: Option<u64>,
+    // Ordered from least to most recently played
+    pub records: Vec<TimeAttackRecord>,
+}
+
+impl TimeAttackRecords {
+    pub fn get(&self, seed: u64) -> Option<&TimeAttackRecord> {
+        self.records.iter().find(|record| record.seed == seed)
+    }
+
+    fn get_or_insert(&mut self, seed: u64) -> &mut TimeAttackRecord {
+        let record = match self.records.iter().position(|record| record.seed == seed) {
+            Some(i) => self.records.remove(i),
+            None => TimeAttackRecord { seed, ..default() },
+        };
+        self.records.push(record);
+        if TIME_ATTACK_MAX_RECORDS < self.records.len() {
+            let excess = self.records.len() - TIME_ATTACK_MAX_RECORDS;
+            self.records.drain(..excess);
+        }
+        self.records.last_mut().unwrap()
+    }
+
+    // Returns previous best time for the level
+    fn add_split(&mut self, seed: u64, level: usize, time: f32) -> Option<f32> {
+        let record = self.get_or_insert(seed);
+        let best = record.best_splits.get(level).copied();
+        match best {
+            Some(best) if time < best => record.best_splits[level] = time,
+            Some(_) => {}
+            None => record.best_splits.push(time),
+        }
+        best
+    }
+}
+
+pub fn time_attack_active(time_attack: Res<TimeAttack>) -> bool {
+    time_attack.active
+}
+
+fn reset_time_attack(mut time_attack: ResMut<TimeAttack>) {
+    *time_attack = TimeAttack::default();
+}
+
+fn remember_time_attack_seed(time_attack: Res<TimeAttack>, mut records: ResMut<TimeAttackRecords>) {
+    records.last_seed = Some(time_attack.seed);
+    save_ron(TIME_ATTACK_RECORDS_PATH, records.as_ref());
+}
+
+// Same seed is used after restart
+fn restart_time_attack(mut time_attack: ResMut<TimeAttack>) {
+    time_attack.level_running = false;
+    time_attack.level_time = 0.0;
+    time_attack.splits.clear();
+}
+
+fn split(
+    time_attack: &mut TimeAttack,
+    records: &mut TimeAttackRecords,
+    split_events: &mut EventWriter<TimeAttackSplit>,
+) {
+    let time = time_attack.finish_level();
+    let level = time_attack.splits.len() - 1;
+    let best = records.add_split(time_attack.seed, level, time);
+    save_ron(TIME_ATTACK_RECORDS_PATH, records);
+
+    split_events.send(TimeAttackSplit {
+        level,
+        time,
+        best,
+        medal: Medal::from_level_time(time),
+    });
+}
+
+fn time_attack_update(
+    time: Res<Time>,
+    mut time_attack: ResMut<TimeAttack>,
+    mut records: ResMut<TimeAttackRecords>,
+    mut level_started_events: EventReader<LevelStarted>,
+    mut level_finished_events: EventReader<LevelFinished>,
+    mut split_events: EventWriter<TimeAttackSplit>,
+) {
+    for _ in level_started_events.read() {
+        time_attack.level_running = true;
+        time_attack.level_time = 0.0;
+    }
+
+    if time_attack.level_running {
+        time_attack.level_time += time.delta_seconds();
+    }
+
+    for _ in level_finished_events.read() {
+        // first level is finished without being started
+        if !time_attack.level_running {
+            continue;
+        }
+        split(time_attack.as_mut(), records.as_mut(), &mut split_events);
+    }
+}
+
+// Boss level does not send `LevelFinished`
+// so last split is done here
+fn time_attack_finish(
+    mut time_attack: ResMut<TimeAttack>,
+    mut records: ResMut<TimeAttackRecords>,
+    mut split_events: EventWriter<TimeAttackSplit>,
+) {
+    if time_attack.level_running {
+        split(time_attack.as_mut(), records.as_mut(), &mut split_events);
+    }
+
+    let total = time_attack.total_time();
+    let record = records.get_or_insert(time_attack.seed);
+    if record.best_total.map_or(true, |best| total < best) {
+        record.best_total = Some(total);
+    }
+    save_ron(TIME_ATTACK_RECORDS_PATH, records.as_ref());
+}
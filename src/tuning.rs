@@ -0,0 +1,112 @@
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    director::DifficultyTuning,
+    level::crafting::CraftingRecipes,
+    player::PlayerTuning,
+    profile::load_ron,
+    weapons::{ammo::AmmoRegistry, WeaponRegistry, WeaponSpawnTable},
+};
+
+#[cfg(feature = "dev")]
+use std::{path::Path, time::SystemTime};
+
+#[cfg(feature = "dev")]
+use bevy::time::common_conditions::on_timer;
+
+#[cfg(feature = "dev")]
+use crate::profile::save_ron;
+
+#[cfg(feature = "dev")]
+const TUNING_DIR: &str = "assets/config";
+#[cfg(feature = "dev")]
+const TUNING_RELOAD_SECONDS: f32 = 0.5;
+
+/// Gameplay values loaded from `assets/config`. Missing
+/// files or fields keep the values from the code.
+/// With the `dev` feature missing files are created
+/// with defaults and changed files are reloaded
+/// while the game runs.
+pub struct TuningPlugin;
+
+impl Plugin for TuningPlugin {
+    fn build(&self, app: &mut App) {
+        add_config::<WeaponRegistry>(app);
+        add_config::<WeaponSpawnTable>(app);
+        add_config::<AmmoRegistry>(app);
+        add_config::<DifficultyTuning>(app);
+        add_config::<PlayerTuning>(app);
+        add_config::<CraftingRecipes>(app);
+    }
+}
+
+trait TuningConfig: Resource + Default + Serialize + DeserializeOwned {
+    const PATH: &'static str;
+}
+
+// Mod overrides are applied on top of this
+// one, but are lost after a reload
+impl TuningConfig for WeaponRegistry {
+    const PATH: &'static str = "assets/config/weapons.ron";
+}
+
+impl TuningConfig for WeaponSpawnTable {
+    const PATH: &'static str = "assets/config/weapon_spawns.ron";
+}
+
+impl TuningConfig for AmmoRegistry {
+    const PATH: &'static str = "assets/config/ammo.ron";
+}
+
+impl TuningConfig for DifficultyTuning {
+    const PATH: &'static str = "assets/config/difficulty.ron";
+}
+
+impl TuningConfig for PlayerTuning {
+    const PATH: &'static str = "assets/config/player.ron";
+}
+
+impl TuningConfig for CraftingRecipes {
+    const PATH: &'static str = "assets/config/crafting.ron";
+}
+
+fn add_config<T: TuningConfig>(app: &mut App) {
+    #[cfg(feature = "dev")]
+    if !Path::new(T::PATH).exists() {
+        if let Err(e) = std::fs::create_dir_all(TUNING_DIR) {
+            warn!("Could not create {TUNING_DIR}: {e}");
+        }
+        save_ron(T::PATH, &T::default());
+    }
+
+    app.insert_resource(load_ron::<T>(T::PATH));
+
+    #[cfg(feature = "dev")]
+    app.add_systems(
+        Update,
+        reload_config::<T>.run_if(on_timer(std::time::Duration::from_secs_f32(
+            TUNING_RELOAD_SECONDS,
+        ))),
+    );
+}
+
+#[cfg(feature = "dev")]
+fn reload_config<T: TuningConfig>(
+    mut last_modified: Local<Option<SystemTime>>,
+    mut config: ResMut<T>,
+) {
+    let Ok(modified) = std::fs::metadata(T::PATH).and_then(|metadata| metadata.modified()) else {
+        return;
+    };
+    // file was already loaded on startup
+    let Some(last) = last_modified.replace(modified) else {
+        return;
+    };
+    if last == modified {
+        return;
+    }
+
+    *config = load_ron::<T>(T::PATH);
+    info!("Reloaded {}", T::PATH);
+}
@@ -0,0 +1,257 @@
+use bevy::{prelude::*, render::view::RenderLayers};
+
+use crate::{
+    crosshair::{
+        spawn_crosshair, CrosshairSettings, CROSSHAIR_MAX_GAP, CROSSHAIR_MAX_SIZE,
+        CROSSHAIR_MAX_THICKNESS, CROSSHAIR_MIN_GAP, CROSSHAIR_MIN_SIZE, CROSSHAIR_MIN_THICKNESS,
+    },
+    utils::remove_all_with,
+    UiState,
+};
+
+use super::{spawn_button, ButtonText, UiConfig};
+
+const PREVIEW_TRANSLATION: Vec3 = Vec3::new(320.0, -220.0, 0.0);
+const PREVIEW_BACKGROUND_SIZE: Vec2 = Vec2::new(200.0, 200.0);
+const PREVIEW_BACKGROUND_COLOR: Color = Color::rgb(0.3, 0.3, 0.3);
+
+pub struct CrosshairMenuPlugin;
+
+impl Plugin for CrosshairMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(UiState::Crosshair),
+            (setup_crosshair_menu, spawn_crosshair_preview),
+        );
+        app.add_systems(
+            Update,
+            (
+                button_system,
+                update_crosshair_text,
+                update_crosshair_preview,
+            )
+                .run_if(in_state(UiState::Crosshair)),
+        );
+        app.add_systems(OnExit(UiState::Crosshair), remove_all_with::<CrosshairMenu>);
+    }
+}
+
+#[derive(Component)]
+struct CrosshairMenu;
+
+#[derive(Debug, Clone, Copy, Component)]
+enum CrosshairMenuButton {
+    Style,
+    Color,
+    SizeUp,
+    SizeDown,
+    GapUp,
+    GapDown,
+    ThicknessUp,
+    ThicknessDown,
+    Outline,
+    HitMarker,
+    Reset,
+    Back,
+}
+
+#[derive(Component)]
+struct CrosshairText;
+
+#[derive(Component)]
+struct CrosshairPreview;
+
+fn setup_crosshair_menu(mut commands: Commands, config: Res<UiConfig>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: config.crosshair_menu_style.clone(),
+                background_color: config.panels_background.into(),
+                ..default()
+            },
+            CrosshairMenu,
+        ))
+        .with_children(|builder| {
+            // Buttons in 2 columns
+            builder
+                .spawn((NodeBundle {
+                    style: config.crosshair_buttons_area_style.clone(),
+                    background_color: config.panels_background.into(),
+                    ..default()
+                },))
+                .with_children(|builder| {
+                    spawn_button(builder, &config, CrosshairMenuButton::Style);
+                    spawn_button(builder, &config, CrosshairMenuButton::Color);
+                    spawn_button(builder, &config, CrosshairMenuButton::SizeUp);
+                    spawn_button(builder, &config, CrosshairMenuButton::SizeDown);
+                    spawn_button(builder, &config, CrosshairMenuButton::GapUp);
+                    spawn_button(builder, &config, CrosshairMenuButton::GapDown);
+                    spawn_button(builder, &config, CrosshairMenuButton::ThicknessUp);
+                    spawn_button(builder, &config, CrosshairMenuButton::ThicknessDown);
+                    spawn_button(builder, &config, CrosshairMenuButton::Outline);
+                    spawn_button(builder, &config, CrosshairMenuButton::HitMarker);
+                    spawn_button(builder, &config, CrosshairMenuButton::Reset);
+                    spawn_button(builder, &config, CrosshairMenuButton::Back);
+                });
+
+            // Current settings
+            builder.spawn((
+                TextBundle {
+                    text: Text::from_section("", config.options_text_style.clone()),
+                    ..default()
+                }
+                .with_style(config.title_style.clone()),
+                CrosshairText,
+            ));
+        });
+}
+
+// Preview is made of sprites, so it is rendered
+// by the same camera as the rest of the UI
+fn spawn_crosshair_preview(crosshair_settings: Res<CrosshairSettings>, mut commands: Commands) {
+    let ui_layer = RenderLayers::layer(1);
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: PREVIEW_BACKGROUND_COLOR,
+                custom_size: Some(PREVIEW_BACKGROUND_SIZE),
+                ..default()
+            },
+            transform: Transform::from_translation(PREVIEW_TRANSLATION),
+            ..default()
+        },
+        ui_layer,
+        CrosshairMenu,
+    ));
+    commands
+        .spawn((
+            SpatialBundle::from_transform(Transform::from_translation(
+                PREVIEW_TRANSLATION + Vec3::Z,
+            )),
+            CrosshairPreview,
+            CrosshairMenu,
+        ))
+        .with_children(|builder| {
+            spawn_crosshair(
+                builder,
+                crosshair_settings.as_ref(),
+                crosshair_settings.color.color(),
+                ui_layer,
+            );
+        });
+}
+
+fn update_crosshair_preview(
+    crosshair_settings: Res<CrosshairSettings>,
+    preview: Query<Entity, With<CrosshairPreview>>,
+    mut commands: Commands,
+) {
+    if !crosshair_settings.is_changed() {
+        return;
+    }
+
+    let Ok(preview) = preview.get_single() else {
+        return;
+    };
+
+    commands
+        .entity(preview)
+        .despawn_descendants()
+        .with_children(|builder| {
+            spawn_crosshair(
+                builder,
+                crosshair_settings.as_ref(),
+                crosshair_settings.color.color(),
+                RenderLayers::layer(1),
+            );
+        });
+}
+
+#[allow(clippy::complexity)]
+fn button_system(
+    config: Res<UiConfig>,
+    interaction_query: Query<
+        (&CrosshairMenuButton, &Interaction, &Children),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut crosshair_settings: ResMut<CrosshairSettings>,
+    mut texts: Query<&mut Text, With<ButtonText<CrosshairMenuButton>>>,
+    mut ui_state: ResMut<NextState<UiState>>,
+) {
+    for (button, interaction, children) in interaction_query.iter() {
+        let text_entity = children[0];
+        let Ok(mut text) = texts.get_mut(text_entity) else {
+            continue;
+        };
+        match *interaction {
+            Interaction::Pressed => {
+                text.sections[0].style.color = config.button_text_color_pressed;
+                let settings = crosshair_settings.as_mut();
+                match button {
+                    CrosshairMenuButton::Style => {
+                        settings.style = settings.style.next();
+                    }
+                    CrosshairMenuButton::Color => {
+                        settings.color = settings.color.next();
+                    }
+                    CrosshairMenuButton::SizeUp => {
+                        settings.size = (settings.size + 1.0).min(CROSSHAIR_MAX_SIZE);
+                    }
+                    CrosshairMenuButton::SizeDown => {
+                        settings.size = (settings.size - 1.0).max(CROSSHAIR_MIN_SIZE);
+                    }
+                    CrosshairMenuButton::GapUp => {
+                        settings.gap = (settings.gap + 1.0).min(CROSSHAIR_MAX_GAP);
+                    }
+                    CrosshairMenuButton::GapDown => {
+                        settings.gap = (settings.gap - 1.0).max(CROSSHAIR_MIN_GAP);
+                    }
+                    CrosshairMenuButton::ThicknessUp => {
+                        settings.thickness =
+                            (settings.thickness + 1.0).min(CROSSHAIR_MAX_THICKNESS);
+                    }
+                    CrosshairMenuButton::ThicknessDown => {
+                        settings.thickness =
+                            (settings.thickness - 1.0).max(CROSSHAIR_MIN_THICKNESS);
+                    }
+                    CrosshairMenuButton::Outline => {
+                        settings.outline = !settings.outline;
+                    }
+                    CrosshairMenuButton::HitMarker => {
+                        settings.hit_marker = settings.hit_marker.next();
+                    }
+                    CrosshairMenuButton::Reset => {
+                        *settings = CrosshairSettings::default();
+                    }
+                    CrosshairMenuButton::Back => {
+                        ui_state.set(UiState::Options);
+                    }
+                }
+            }
+            Interaction::Hovered => {
+                text.sections[0].style.color = config.button_text_color_hover;
+            }
+            Interaction::None => {
+                text.sections[0].style.color = config.button_text_color_normal;
+            }
+        }
+    }
+}
+
+fn update_crosshair_text(
+    crosshair_settings: Res<CrosshairSettings>,
+    mut crosshair_text: Query<&mut Text, With<CrosshairText>>,
+) {
+    let mut text = crosshair_text.single_mut();
+    text.sections[0].value =
+        format!(
+        "Style: {:?}\nColor: {:?}\nSize: {}\nGap: {}\nThickness: {}\nOutline: {}\nHit marker: {:?}",
+        crosshair_settings.style,
+        crosshair_settings.color,
+        crosshair_settings.size,
+        crosshair_settings.gap,
+        crosshair_settings.thickness,
+        if crosshair_settings.outline { "on" } else { "off" },
+        crosshair_settings.hit_marker,
+    );
+}
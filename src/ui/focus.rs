@@ -0,0 +1,199 @@
+use std::path::Path;
+
+use bevy::prelude::*;
+use bevy_kira_audio::{AudioChannel, AudioControl};
+
+use crate::{weapons::WeaponAssets, GameSettings, SfxChannel, UiState};
+
+const FOCUS_RING_COLOR: Color = Color::ORANGE_RED;
+
+// Recorded narration for menu items is looked up
+// by the button label, e.g. `narration/back.ogg`
+const NARRATION_DIR: &str = "narration";
+const NARRATION_FALLBACK_PLAYBACK_RATE: f64 = 3.0;
+
+pub struct FocusPlugin;
+
+impl Plugin for FocusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<UiFocus>();
+
+        app.add_event::<UiFocusChanged>();
+
+        app.add_systems(
+            Update,
+            (
+                focus_reset.run_if(state_changed::<UiState>()),
+                focus_release,
+                focus_navigate,
+                focus_activate,
+                focus_narrate,
+            )
+                .chain(),
+        );
+    }
+}
+
+// Button selected with keyboard or gamepad
+#[derive(Debug, Default, Resource)]
+pub struct UiFocus {
+    focused: Option<Entity>,
+    // Button "pressed" by keyboard or gamepad
+    // last frame, which needs to be released
+    pressed: Option<Entity>,
+}
+
+#[derive(Event)]
+pub struct UiFocusChanged {
+    pub button: Entity,
+}
+
+fn focus_reset(mut ui_focus: ResMut<UiFocus>) {
+    *ui_focus = UiFocus::default();
+}
+
+fn focus_release(mut ui_focus: ResMut<UiFocus>, mut buttons: Query<&mut Interaction>) {
+    let Some(pressed) = ui_focus.pressed.take() else {
+        return;
+    };
+    if let Ok(mut interaction) = buttons.get_mut(pressed) {
+        *interaction = Interaction::Hovered;
+    }
+}
+
+fn navigation_input(
+    keys: &Input<KeyCode>,
+    gamepads: &Gamepads,
+    gamepad_buttons: &Input<GamepadButton>,
+) -> i32 {
+    let gamepad_pressed = |button_type| {
+        gamepads
+            .iter()
+            .any(|gamepad| gamepad_buttons.just_pressed(GamepadButton::new(gamepad, button_type)))
+    };
+
+    if keys.just_pressed(KeyCode::Down)
+        || keys.just_pressed(KeyCode::Tab)
+        || gamepad_pressed(GamepadButtonType::DPadDown)
+    {
+        1
+    } else if keys.just_pressed(KeyCode::Up) || gamepad_pressed(GamepadButtonType::DPadUp) {
+        -1
+    } else {
+        0
+    }
+}
+
+fn focus_navigate(
+    keys: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    mut ui_focus: ResMut<UiFocus>,
+    mut buttons: Query<
+        (Entity, &GlobalTransform, &mut BorderColor, &mut Interaction),
+        With<Button>,
+    >,
+    mut focus_changed_events: EventWriter<UiFocusChanged>,
+) {
+    let step = navigation_input(keys.as_ref(), gamepads.as_ref(), gamepad_buttons.as_ref());
+    if step == 0 {
+        return;
+    }
+
+    // buttons are ordered top to bottom, left to right
+    let mut order = buttons
+        .iter()
+        .map(|(entity, global_transform, _, _)| (entity, global_transform.translation()))
+        .collect::<Vec<_>>();
+    if order.is_empty() {
+        return;
+    }
+    order.sort_by(|(_, a), (_, b)| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+
+    let current = ui_focus
+        .focused
+        .and_then(|focused| order.iter().position(|(entity, _)| *entity == focused));
+    let next = match current {
+        Some(current) => (current as i32 + step).rem_euclid(order.len() as i32) as usize,
+        None if 0 < step => 0,
+        None => order.len() - 1,
+    };
+    let next = order[next].0;
+
+    if let Some(Ok((_, _, mut border_color, mut interaction))) =
+        ui_focus.focused.map(|focused| buttons.get_mut(focused))
+    {
+        *border_color = Color::NONE.into();
+        *interaction = Interaction::None;
+    }
+    if let Ok((_, _, mut border_color, mut interaction)) = buttons.get_mut(next) {
+        *border_color = FOCUS_RING_COLOR.into();
+        *interaction = Interaction::Hovered;
+    }
+
+    ui_focus.focused = Some(next);
+    focus_changed_events.send(UiFocusChanged { button: next });
+}
+
+fn focus_activate(
+    keys: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    mut ui_focus: ResMut<UiFocus>,
+    mut buttons: Query<&mut Interaction, With<Button>>,
+) {
+    // Alt+Enter toggles fullscreen instead
+    let alt = keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]);
+    let activate = (keys.just_pressed(KeyCode::Return) && !alt)
+        || gamepads.iter().any(|gamepad| {
+            gamepad_buttons.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::South))
+        });
+    if !activate {
+        return;
+    }
+
+    let Some(focused) = ui_focus.focused else {
+        return;
+    };
+    // menu buttons react to the change of `Interaction`
+    if let Ok(mut interaction) = buttons.get_mut(focused) {
+        *interaction = Interaction::Pressed;
+        ui_focus.pressed = Some(focused);
+    }
+}
+
+fn focus_narrate(
+    sfx: Res<AudioChannel<SfxChannel>>,
+    asset_server: Res<AssetServer>,
+    weapon_assets: Option<Res<WeaponAssets>>,
+    game_settings: Res<GameSettings>,
+    buttons: Query<&Children, With<Button>>,
+    texts: Query<&Text>,
+    mut focus_changed_events: EventReader<UiFocusChanged>,
+) {
+    for event in focus_changed_events.read() {
+        if !game_settings.narration {
+            continue;
+        }
+
+        let Some(label) = buttons
+            .get(event.button)
+            .ok()
+            .and_then(|children| texts.get(children[0]).ok())
+            .map(|text| text.sections[0].value.to_lowercase())
+        else {
+            continue;
+        };
+
+        let path = format!("{NARRATION_DIR}/{label}.ogg");
+        if Path::new("assets").join(&path).exists() {
+            sfx.play(asset_server.load(path));
+        } else if let Some(weapon_assets) = weapon_assets.as_ref() {
+            // without a recording there is at
+            // least a cue that focus has moved
+            info!("Missing narration for the menu item: {label}");
+            sfx.play(weapon_assets.pistol_sounds[0].clone())
+                .with_playback_rate(NARRATION_FALLBACK_PLAYBACK_RATE);
+        }
+    }
+}
@@ -1,8 +1,20 @@
 use bevy::prelude::*;
 
-use crate::{utils::remove_all_with, GlobalState, UiState};
+use crate::{
+    boss_rush::{BossRush, BossRushLeaderboard},
+    daily::{DailyLeaderboard, DailyRun},
+    level::grade::RunBonus,
+    run_modifiers::RunModifiers,
+    run_stats::RunStats,
+    scrap::Scrap,
+    utils::remove_all_with,
+    GlobalState, UiState,
+};
 
-use super::{spawn_button, ButtonText, UiConfig};
+use super::{
+    spawn_boss_rush_score, spawn_button, spawn_daily_score, spawn_property_damage_text,
+    spawn_run_modifiers_text, spawn_run_score_text, spawn_run_stats_text, ButtonText, UiConfig,
+};
 
 pub struct GameOverPlugin;
 
@@ -20,10 +32,23 @@ struct GameOverMenu;
 #[derive(Debug, Clone, Copy, Component)]
 enum GameOverMenuButton {
     Restart,
+    Hub,
     MainMenu,
 }
 
-fn setup_pause_menu(mut commands: Commands, config: Res<UiConfig>) {
+#[allow(clippy::too_many_arguments)]
+fn setup_pause_menu(
+    mut commands: Commands,
+    config: Res<UiConfig>,
+    daily_run: Res<DailyRun>,
+    leaderboard: Res<DailyLeaderboard>,
+    boss_rush: Res<BossRush>,
+    boss_rush_leaderboard: Res<BossRushLeaderboard>,
+    run_modifiers: Res<RunModifiers>,
+    run_bonus: Res<RunBonus>,
+    run_stats: Res<RunStats>,
+    scrap: Res<Scrap>,
+) {
     commands
         .spawn((
             NodeBundle {
@@ -41,6 +66,18 @@ fn setup_pause_menu(mut commands: Commands, config: Res<UiConfig>) {
                 })
                 .with_style(config.title_style.clone()),
             );
+            if daily_run.active {
+                spawn_daily_score(builder, &config, &daily_run, &leaderboard);
+            }
+            if boss_rush.active {
+                spawn_boss_rush_score(builder, &config, &boss_rush, &boss_rush_leaderboard);
+            }
+            if !daily_run.active && !boss_rush.active {
+                spawn_run_score_text(builder, &config, &scrap, &run_bonus, &run_modifiers);
+            }
+            spawn_property_damage_text(builder, &config, &run_bonus);
+            spawn_run_stats_text(builder, &config, &run_stats);
+            spawn_run_modifiers_text(builder, &config, &run_modifiers);
 
             // Buttons
             builder
@@ -51,6 +88,7 @@ fn setup_pause_menu(mut commands: Commands, config: Res<UiConfig>) {
                 },))
                 .with_children(|builder| {
                     spawn_button(builder, &config, GameOverMenuButton::Restart);
+                    spawn_button(builder, &config, GameOverMenuButton::Hub);
                     spawn_button(builder, &config, GameOverMenuButton::MainMenu);
                 });
         });
@@ -78,6 +116,9 @@ fn button_system(
                     GameOverMenuButton::Restart => {
                         global_state.set(GlobalState::InGame);
                     }
+                    GameOverMenuButton::Hub => {
+                        global_state.set(GlobalState::Hub);
+                    }
                     GameOverMenuButton::MainMenu => {
                         global_state.set(GlobalState::MainMenu);
                     }
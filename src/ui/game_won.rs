@@ -1,8 +1,25 @@
 use bevy::prelude::*;
 
-use crate::{utils::remove_all_with, GlobalState, UiState};
+use crate::{
+    attachments::Attachments,
+    daily::{DailyLeaderboard, DailyRun},
+    level::grade::RunBonus,
+    new_game_plus::{HeldWeapons, NewGamePlus},
+    perks::Perks,
+    profile::Profile,
+    run_modifiers::RunModifiers,
+    run_stats::RunStats,
+    scrap::Scrap,
+    time_attack::{TimeAttack, TimeAttackRecords},
+    unlocks::weapon_unlocked,
+    utils::remove_all_with,
+    GlobalState, UiState,
+};
 
-use super::{spawn_button, ButtonText, UiConfig};
+use super::{
+    spawn_button, spawn_button_with_text, spawn_daily_score, spawn_property_damage_text,
+    spawn_run_modifiers_text, spawn_run_score_text, spawn_run_stats_text, ButtonText, UiConfig,
+};
 
 const GAME_WON_TEXT: &str =
     "Congratulations.\nYou have defeated the RED DRAGON and ended his world conquest.";
@@ -12,7 +29,10 @@ pub struct GameWonPlugin;
 impl Plugin for GameWonPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(UiState::GameWon), setup_pause_menu);
-        app.add_systems(Update, button_system.run_if(in_state(UiState::GameWon)));
+        app.add_systems(
+            Update,
+            (button_system, update_new_game_plus_weapon_text).run_if(in_state(UiState::GameWon)),
+        );
         app.add_systems(OnExit(UiState::GameWon), remove_all_with::<GameWonMenu>);
     }
 }
@@ -22,10 +42,31 @@ struct GameWonMenu;
 
 #[derive(Debug, Clone, Copy, Component)]
 enum GameWonMenuButton {
+    // Index in the held weapons
+    Weapon(usize),
+    NewGamePlus,
+    Hub,
     MainMenu,
 }
 
-fn setup_pause_menu(mut commands: Commands, config: Res<UiConfig>) {
+#[derive(Component)]
+struct NewGamePlusWeaponText;
+
+#[allow(clippy::too_many_arguments)]
+fn setup_pause_menu(
+    mut commands: Commands,
+    config: Res<UiConfig>,
+    profile: Res<Profile>,
+    daily_run: Res<DailyRun>,
+    leaderboard: Res<DailyLeaderboard>,
+    time_attack: Res<TimeAttack>,
+    time_attack_records: Res<TimeAttackRecords>,
+    run_bonus: Res<RunBonus>,
+    run_stats: Res<RunStats>,
+    run_modifiers: Res<RunModifiers>,
+    scrap: Res<Scrap>,
+    held_weapons: Res<HeldWeapons>,
+) {
     commands
         .spawn((
             NodeBundle {
@@ -44,6 +85,41 @@ fn setup_pause_menu(mut commands: Commands, config: Res<UiConfig>) {
                 })
                 .with_style(config.title_style.clone()),
             );
+            if daily_run.active {
+                spawn_daily_score(builder, &config, &daily_run, &leaderboard);
+            } else if time_attack.active {
+                let best = time_attack_records
+                    .get(time_attack.seed)
+                    .and_then(|record| record.best_total)
+                    .unwrap_or_default();
+                builder.spawn(
+                    (TextBundle {
+                        text: Text::from_section(
+                            format!("Time: {:.2} Best: {:.2}", time_attack.total_time(), best),
+                            config.text_style.clone(),
+                        ),
+                        ..default()
+                    })
+                    .with_style(config.title_style.clone()),
+                );
+            } else {
+                builder.spawn(
+                    (TextBundle {
+                        text: Text::from_section(
+                            format!("Prestige: {}", profile.prestige),
+                            config.text_style.clone(),
+                        ),
+                        ..default()
+                    })
+                    .with_style(config.title_style.clone()),
+                );
+            }
+            if !daily_run.active {
+                spawn_run_score_text(builder, &config, &scrap, &run_bonus, &run_modifiers);
+            }
+            spawn_property_damage_text(builder, &config, &run_bonus);
+            spawn_run_stats_text(builder, &config, &run_stats);
+            spawn_run_modifiers_text(builder, &config, &run_modifiers);
 
             // Buttons
             builder
@@ -53,6 +129,49 @@ fn setup_pause_menu(mut commands: Commands, config: Res<UiConfig>) {
                     ..default()
                 },))
                 .with_children(|builder| {
+                    // New Game+ is not available for daily runs
+                    if daily_run.active {
+                        spawn_button(builder, &config, GameWonMenuButton::Hub);
+                        spawn_button(builder, &config, GameWonMenuButton::MainMenu);
+                        return;
+                    }
+
+                    // Weapon to carry over to the New Game+. Only weapons
+                    // held during the run and unlocked can be picked.
+                    builder
+                        .spawn((NodeBundle {
+                            style: config.options_buttons_area_style.clone(),
+                            background_color: config.panels_background.into(),
+                            ..default()
+                        },))
+                        .with_children(|builder| {
+                            for (i, weapon) in held_weapons.weapons.iter().enumerate() {
+                                if !weapon_unlocked(&profile, weapon.weapon_type) {
+                                    continue;
+                                }
+                                spawn_button_with_text(
+                                    builder,
+                                    &config,
+                                    GameWonMenuButton::Weapon(i),
+                                    format!(
+                                        "{} {}",
+                                        weapon.roll.rarity.name(),
+                                        weapon.weapon_type.name()
+                                    ),
+                                );
+                            }
+                            builder.spawn((
+                                TextBundle {
+                                    text: Text::from_section("", config.options_text_style.clone()),
+                                    ..default()
+                                }
+                                .with_style(config.button_style.clone()),
+                                NewGamePlusWeaponText,
+                            ));
+                        });
+
+                    spawn_button(builder, &config, GameWonMenuButton::NewGamePlus);
+                    spawn_button(builder, &config, GameWonMenuButton::Hub);
                     spawn_button(builder, &config, GameWonMenuButton::MainMenu);
                 });
         });
@@ -65,6 +184,11 @@ fn button_system(
         (&GameWonMenuButton, &Interaction, &Children),
         (Changed<Interaction>, With<Button>),
     >,
+    perks: Res<Perks>,
+    attachments: Res<Attachments>,
+    held_weapons: Res<HeldWeapons>,
+    mut profile: ResMut<Profile>,
+    mut new_game_plus: ResMut<NewGamePlus>,
     mut main_menu_texts: Query<&mut Text, With<ButtonText<GameWonMenuButton>>>,
     mut global_state: ResMut<NextState<GlobalState>>,
 ) {
@@ -77,6 +201,18 @@ fn button_system(
             Interaction::Pressed => {
                 text.sections[0].style.color = config.button_text_color_pressed;
                 match button {
+                    GameWonMenuButton::Weapon(i) => {
+                        if let Some(weapon) = held_weapons.weapons.get(*i) {
+                            new_game_plus.select_weapon(*weapon);
+                        }
+                    }
+                    GameWonMenuButton::NewGamePlus => {
+                        new_game_plus.start(perks.as_ref(), attachments.as_ref(), profile.as_mut());
+                        global_state.set(GlobalState::InGame);
+                    }
+                    GameWonMenuButton::Hub => {
+                        global_state.set(GlobalState::Hub);
+                    }
                     GameWonMenuButton::MainMenu => {
                         global_state.set(GlobalState::MainMenu);
                     }
@@ -91,3 +227,16 @@ fn button_system(
         }
     }
 }
+
+fn update_new_game_plus_weapon_text(
+    new_game_plus: Res<NewGamePlus>,
+    mut weapon_text: Query<&mut Text, With<NewGamePlusWeaponText>>,
+) {
+    let Ok(mut text) = weapon_text.get_single_mut() else {
+        return;
+    };
+    text.sections[0].value = match new_game_plus.weapon_roll {
+        Some(roll) => format!("{} {}", roll.rarity.name(), new_game_plus.weapon.name()),
+        None => new_game_plus.weapon.name().to_string(),
+    };
+}
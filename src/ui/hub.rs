@@ -0,0 +1,144 @@
+use bevy::prelude::*;
+
+use crate::{
+    daily::{DailyLeaderboard, DailyRun},
+    profile::Profile,
+    utils::remove_all_with,
+    weapons::WeaponType,
+    GlobalState, UiState,
+};
+
+use super::{spawn_button, stats::collection_text, ButtonText, UiConfig};
+
+const HUB_TITLE: &str = "FRIDGE GRAVEYARD";
+
+pub struct HubMenuPlugin;
+
+impl Plugin for HubMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(UiState::Hub), setup_hub_menu);
+        app.add_systems(Update, button_system.run_if(in_state(UiState::Hub)));
+        app.add_systems(OnExit(UiState::Hub), remove_all_with::<HubMenu>);
+    }
+}
+
+#[derive(Component)]
+struct HubMenu;
+
+#[derive(Debug, Clone, Copy, Component)]
+enum HubMenuButton {
+    MainMenu,
+}
+
+fn spawn_text(builder: &mut ChildBuilder, config: &UiConfig, text: impl Into<String>) {
+    builder.spawn(
+        (TextBundle {
+            text: Text::from_section(text, config.text_style.clone()),
+            ..default()
+        })
+        .with_style(config.title_style.clone()),
+    );
+}
+
+fn setup_hub_menu(
+    mut commands: Commands,
+    config: Res<UiConfig>,
+    profile: Res<Profile>,
+    leaderboard: Res<DailyLeaderboard>,
+) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: config.menu_style.clone(),
+                background_color: config.panels_background.into(),
+                ..default()
+            },
+            HubMenu,
+        ))
+        .with_children(|builder| {
+            builder.spawn(
+                (TextBundle {
+                    text: Text::from_section(HUB_TITLE, config.title_text_style.clone()),
+                    ..default()
+                })
+                .with_style(config.title_style.clone()),
+            );
+
+            // Trophies
+            spawn_text(
+                builder,
+                &config,
+                format!("Trophies: {} bosses defeated", profile.boss_kills),
+            );
+
+            // Stats terminal
+            let kills: u32 = WeaponType::ALL
+                .iter()
+                .map(|weapon_type| profile.weapon_kills(*weapon_type))
+                .sum();
+            spawn_text(
+                builder,
+                &config,
+                format!("Fridges killed: {kills} Prestige: {}", profile.prestige),
+            );
+
+            // Weapon unlocks
+            for weapon_type in WeaponType::ALL {
+                spawn_text(builder, &config, collection_text(&profile, weapon_type));
+            }
+
+            // Daily kiosk
+            if let Some(today) = DailyRun::today() {
+                let daily_text = match leaderboard.best(today) {
+                    Some(best) => format!("Daily run best today: {best}"),
+                    None => "Daily run is not played today".to_string(),
+                };
+                spawn_text(builder, &config, daily_text);
+            }
+
+            // Buttons
+            builder
+                .spawn((NodeBundle {
+                    style: config.menu_buttons_area_style.clone(),
+                    background_color: config.panels_background.into(),
+                    ..default()
+                },))
+                .with_children(|builder| {
+                    spawn_button(builder, &config, HubMenuButton::MainMenu);
+                });
+        });
+}
+
+#[allow(clippy::complexity)]
+fn button_system(
+    config: Res<UiConfig>,
+    interaction_query: Query<
+        (&HubMenuButton, &Interaction, &Children),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut hub_menu_texts: Query<&mut Text, With<ButtonText<HubMenuButton>>>,
+    mut global_state: ResMut<NextState<GlobalState>>,
+) {
+    for (button, interaction, children) in interaction_query.iter() {
+        let text_entity = children[0];
+        let Ok(mut text) = hub_menu_texts.get_mut(text_entity) else {
+            continue;
+        };
+        match *interaction {
+            Interaction::Pressed => {
+                text.sections[0].style.color = config.button_text_color_pressed;
+                match button {
+                    HubMenuButton::MainMenu => {
+                        global_state.set(GlobalState::MainMenu);
+                    }
+                }
+            }
+            Interaction::Hovered => {
+                text.sections[0].style.color = config.button_text_color_hover;
+            }
+            Interaction::None => {
+                text.sections[0].style.color = config.button_text_color_normal;
+            }
+        }
+    }
+}
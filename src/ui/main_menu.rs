@@ -1,8 +1,16 @@
 use bevy::{app::AppExit, prelude::*};
 
-use crate::{utils::remove_all_with, GlobalState, UiState, CREATED_BY, GAME_NAME};
+use crate::{
+    boss_rush::{boss_rush_unlocked, BossRush},
+    daily::DailyRun,
+    profile::Profile,
+    run_modifiers::RunModifiers,
+    time_attack::{TimeAttack, TimeAttackRecords},
+    utils::remove_all_with,
+    GlobalState, UiState, CREATED_BY, GAME_NAME,
+};
 
-use super::{spawn_button, ButtonText, UiConfig};
+use super::{spawn_button, spawn_button_with_text, ButtonText, UiConfig};
 
 pub struct MainMenuPlugin;
 
@@ -20,11 +28,23 @@ struct MainMenu;
 #[derive(Debug, Clone, Copy, Component)]
 enum MainMenuButton {
     Play,
+    DailyRun,
+    TimeAttack,
+    TimeAttackRetry,
+    BossRush,
+    RunModifiers,
+    Profiles,
+    Mods,
     Options,
     Quit,
 }
 
-fn setup_main_menu(mut commands: Commands, config: Res<UiConfig>) {
+fn setup_main_menu(
+    mut commands: Commands,
+    config: Res<UiConfig>,
+    profile: Res<Profile>,
+    time_attack_records: Res<TimeAttackRecords>,
+) {
     commands
         .spawn((
             NodeBundle {
@@ -53,6 +73,29 @@ fn setup_main_menu(mut commands: Commands, config: Res<UiConfig>) {
                 },))
                 .with_children(|builder| {
                     spawn_button(builder, &config, MainMenuButton::Play);
+                    // Daily run needs system time which is not available on web
+                    if cfg!(not(target_arch = "wasm32")) {
+                        spawn_button(builder, &config, MainMenuButton::DailyRun);
+                    }
+                    spawn_button(builder, &config, MainMenuButton::TimeAttack);
+                    // Replays the seed of the last time attack
+                    if let Some(seed) = time_attack_records.last_seed {
+                        spawn_button_with_text(
+                            builder,
+                            &config,
+                            MainMenuButton::TimeAttackRetry,
+                            format!("Retry seed {seed:016x}"),
+                        );
+                    }
+                    if boss_rush_unlocked(&profile) {
+                        spawn_button(builder, &config, MainMenuButton::BossRush);
+                    }
+                    spawn_button(builder, &config, MainMenuButton::RunModifiers);
+                    spawn_button(builder, &config, MainMenuButton::Profiles);
+                    // Mods are loaded from the file system
+                    if cfg!(not(target_arch = "wasm32")) {
+                        spawn_button(builder, &config, MainMenuButton::Mods);
+                    }
                     spawn_button(builder, &config, MainMenuButton::Options);
                     spawn_button(builder, &config, MainMenuButton::Quit);
                 });
@@ -75,6 +118,11 @@ fn button_system(
         (&MainMenuButton, &Interaction, &Children),
         (Changed<Interaction>, With<Button>),
     >,
+    mut daily_run: ResMut<DailyRun>,
+    mut run_modifiers: ResMut<RunModifiers>,
+    mut time_attack: ResMut<TimeAttack>,
+    time_attack_records: Res<TimeAttackRecords>,
+    mut boss_rush: ResMut<BossRush>,
     mut main_menu_texts: Query<&mut Text, With<ButtonText<MainMenuButton>>>,
     mut main_menu_state: ResMut<NextState<UiState>>,
     mut global_state: ResMut<NextState<GlobalState>>,
@@ -92,6 +140,37 @@ fn button_system(
                     MainMenuButton::Play => {
                         global_state.set(GlobalState::InGame);
                     }
+                    MainMenuButton::DailyRun => {
+                        // everyone plays the daily run without
+                        // run modifiers so scores are comparable
+                        run_modifiers.modifiers.clear();
+                        daily_run.start();
+                        global_state.set(GlobalState::InGame);
+                    }
+                    MainMenuButton::TimeAttack => {
+                        time_attack.start(rand::random());
+                        global_state.set(GlobalState::InGame);
+                    }
+                    MainMenuButton::TimeAttackRetry => {
+                        let Some(seed) = time_attack_records.last_seed else {
+                            continue;
+                        };
+                        time_attack.start(seed);
+                        global_state.set(GlobalState::InGame);
+                    }
+                    MainMenuButton::BossRush => {
+                        boss_rush.start();
+                        global_state.set(GlobalState::InGame);
+                    }
+                    MainMenuButton::RunModifiers => {
+                        main_menu_state.set(UiState::RunModifiers);
+                    }
+                    MainMenuButton::Profiles => {
+                        main_menu_state.set(UiState::Profiles);
+                    }
+                    MainMenuButton::Mods => {
+                        main_menu_state.set(UiState::Mods);
+                    }
                     MainMenuButton::Options => {
                         main_menu_state.set(UiState::Options);
                     }
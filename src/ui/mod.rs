@@ -14,14 +14,31 @@ use bevy::{
 };
 use bevy_asset_loader::prelude::*;
 
-use crate::{utils::set_state, GlobalState, UiState};
+use crate::{
+    boss_rush::{BossRush, BossRushLeaderboard},
+    daily::{DailyLeaderboard, DailyRun},
+    level::grade::RunBonus,
+    run_modifiers::RunModifiers,
+    run_stats::RunStats,
+    scrap::Scrap,
+    utils::set_state,
+    GlobalState, UiState,
+};
 
+mod crosshair;
+mod focus;
 mod game_over;
 mod game_won;
+mod hub;
 mod main_menu;
+mod mods;
 mod options;
 mod pause;
+mod perks;
+mod profiles;
+mod run_modifiers;
 mod stats;
+pub mod tablet;
 
 pub struct UiPlugin;
 
@@ -29,12 +46,20 @@ impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
         app.add_collection_to_loading_state::<_, UiAssets>(GlobalState::AssetLoading);
 
+        app.add_plugins(crosshair::CrosshairMenuPlugin);
+        app.add_plugins(focus::FocusPlugin);
         app.add_plugins(game_over::GameOverPlugin);
         app.add_plugins(game_won::GameWonPlugin);
+        app.add_plugins(hub::HubMenuPlugin);
         app.add_plugins(stats::StatsPlugin);
         app.add_plugins(main_menu::MainMenuPlugin);
+        app.add_plugins(mods::ModsMenuPlugin);
         app.add_plugins(options::OptionsPlugin);
         app.add_plugins(pause::PausePlugin);
+        app.add_plugins(perks::PerksMenuPlugin);
+        app.add_plugins(profiles::ProfilesMenuPlugin);
+        app.add_plugins(run_modifiers::RunModifiersMenuPlugin);
+        app.add_plugins(tablet::TabletPlugin);
 
         app.add_systems(
             OnTransition {
@@ -122,10 +147,57 @@ impl Plugin for UiPlugin {
         app.add_systems(
             OnTransition {
                 from: GlobalState::GameWon,
+                to: GlobalState::InGame,
+            },
+            (set_state::<UiState, { UiState::Stats as u8 }>, grab_mouse),
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::GameWon,
+                to: GlobalState::MainMenu,
+            },
+            set_state::<UiState, { UiState::MainMenu as u8 }>,
+        );
+
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::GameOver,
+                to: GlobalState::Hub,
+            },
+            set_state::<UiState, { UiState::Hub as u8 }>,
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::GameWon,
+                to: GlobalState::Hub,
+            },
+            set_state::<UiState, { UiState::Hub as u8 }>,
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::Hub,
                 to: GlobalState::MainMenu,
             },
             set_state::<UiState, { UiState::MainMenu as u8 }>,
         );
+
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::InGame,
+                to: GlobalState::PerkSelection,
+            },
+            (
+                set_state::<UiState, { UiState::Perks as u8 }>,
+                release_mouse,
+            ),
+        );
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::PerkSelection,
+                to: GlobalState::InGame,
+            },
+            (set_state::<UiState, { UiState::Stats as u8 }>, grab_mouse),
+        );
     }
 }
 
@@ -150,9 +222,14 @@ pub struct UiConfig {
 
     pub menu_style: Style,
     pub menu_buttons_area_style: Style,
+    pub pause_tabs_style: Style,
 
     pub options_text_style: TextStyle,
     pub options_buttons_area_style: Style,
+    pub options_confirmation_style: Style,
+
+    pub crosshair_menu_style: Style,
+    pub crosshair_buttons_area_style: Style,
 
     pub title_style: Style,
     pub title_text_style: TextStyle,
@@ -170,6 +247,8 @@ pub struct UiConfig {
 pub struct UiResources {
     pub mesh: Handle<Mesh>,
     pub material: Handle<StandardMaterial>,
+    // Size of the mesh the UI is drawn on
+    pub size: Vec2,
 }
 
 #[derive(Component)]
@@ -246,6 +325,7 @@ fn init_resources(
     commands.insert_resource(UiResources {
         mesh: mesh_handle,
         material: material_handle,
+        size: mesh_size,
     })
 }
 
@@ -261,6 +341,8 @@ fn setup_ui_config(ui_assets: Res<UiAssets>, mut commands: Commands) {
 
         button_style: Style {
             margin: UiRect::all(Val::Percent(10.0)),
+            // focus ring
+            border: UiRect::all(Val::Px(2.0)),
             justify_self: JustifySelf::Center,
             justify_content: JustifyContent::Center,
             align_items: AlignItems::Center,
@@ -287,6 +369,13 @@ fn setup_ui_config(ui_assets: Res<UiAssets>, mut commands: Commands) {
             align_items: AlignItems::Center,
             ..default()
         },
+        pause_tabs_style: Style {
+            display: Display::Grid,
+            grid_template_columns: RepeatedGridTrack::flex(4, 1.0),
+            justify_self: JustifySelf::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
 
         options_buttons_area_style: Style {
             display: Display::Grid,
@@ -294,6 +383,33 @@ fn setup_ui_config(ui_assets: Res<UiAssets>, mut commands: Commands) {
             align_items: AlignItems::Center,
             ..default()
         },
+        options_confirmation_style: Style {
+            display: Display::Grid,
+            position_type: PositionType::Absolute,
+            grid_template_columns: RepeatedGridTrack::flex(2, 1.0),
+            margin: UiRect::all(Val::Auto),
+            padding: UiRect::all(Val::Percent(2.0)),
+            justify_items: JustifyItems::Center,
+            align_items: AlignItems::Center,
+            align_self: AlignSelf::Center,
+            justify_self: JustifySelf::Center,
+            ..default()
+        },
+        crosshair_menu_style: Style {
+            display: Display::Grid,
+            grid_template_columns: RepeatedGridTrack::flex(2, 1.0),
+            margin: UiRect::all(Val::Auto),
+            align_items: AlignItems::Center,
+            align_self: AlignSelf::Center,
+            ..default()
+        },
+        crosshair_buttons_area_style: Style {
+            display: Display::Grid,
+            grid_template_columns: RepeatedGridTrack::flex(2, 1.0),
+            justify_self: JustifySelf::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
         options_text_style: TextStyle {
             font: ui_assets.font.clone(),
             font_size: 50.0,
@@ -327,7 +443,7 @@ fn setup_ui_config(ui_assets: Res<UiAssets>, mut commands: Commands) {
             justify_self: JustifySelf::Center,
             align_items: AlignItems::Center,
             align_self: AlignSelf::Center,
-            column_gap: Val::Percent(30.0),
+            column_gap: Val::Percent(10.0),
             ..default()
         },
         stats_columns_style: Style {
@@ -365,12 +481,25 @@ fn release_mouse(mut windows: Query<&mut Window>) {
 fn spawn_button<B>(builder: &mut ChildBuilder, style: &UiConfig, button: B)
 where
     B: Component + std::fmt::Debug + Copy,
+{
+    spawn_button_with_text(builder, style, button, format!("{button:?}"));
+}
+
+// For buttons with labels that are not known at compile time
+fn spawn_button_with_text<B>(
+    builder: &mut ChildBuilder,
+    style: &UiConfig,
+    button: B,
+    text: impl Into<String>,
+) where
+    B: Component,
 {
     builder
         .spawn((
             ButtonBundle {
                 style: style.button_style.clone(),
                 background_color: style.button_background.into(),
+                border_color: Color::NONE.into(),
                 ..default()
             },
             button,
@@ -378,7 +507,7 @@ where
         .with_children(|builder| {
             builder.spawn((
                 TextBundle {
-                    text: Text::from_section(format!("{button:?}"), style.text_style.clone()),
+                    text: Text::from_section(text, style.text_style.clone()),
                     ..default()
                 },
                 ButtonText::<B> {
@@ -387,3 +516,115 @@ where
             ));
         });
 }
+
+fn spawn_daily_score(
+    builder: &mut ChildBuilder,
+    config: &UiConfig,
+    daily_run: &DailyRun,
+    leaderboard: &DailyLeaderboard,
+) {
+    let best = leaderboard.best(daily_run.day).unwrap_or_default();
+    builder.spawn(
+        (TextBundle {
+            text: Text::from_section(
+                format!("Daily score: {} Best: {}", daily_run.last_score, best),
+                config.text_style.clone(),
+            ),
+            ..default()
+        })
+        .with_style(config.title_style.clone()),
+    );
+}
+
+fn spawn_boss_rush_score(
+    builder: &mut ChildBuilder,
+    config: &UiConfig,
+    boss_rush: &BossRush,
+    leaderboard: &BossRushLeaderboard,
+) {
+    let best = leaderboard.best().unwrap_or_default();
+    builder.spawn(
+        (TextBundle {
+            text: Text::from_section(
+                format!(
+                    "Bosses defeated: {} Boss rush score: {} Best: {}",
+                    boss_rush.arenas_cleared, boss_rush.last_score, best
+                ),
+                config.text_style.clone(),
+            ),
+            ..default()
+        })
+        .with_style(config.title_style.clone()),
+    );
+}
+
+// Score of a regular run. Daily and boss rush
+// runs show their own scores instead.
+fn spawn_run_score_text(
+    builder: &mut ChildBuilder,
+    config: &UiConfig,
+    scrap: &Scrap,
+    run_bonus: &RunBonus,
+    run_modifiers: &RunModifiers,
+) {
+    let score = run_modifiers.apply_score_multiplier(scrap.amount + run_bonus.score);
+    builder.spawn(
+        (TextBundle {
+            text: Text::from_section(format!("Run score: {score}"), config.text_style.clone()),
+            ..default()
+        })
+        .with_style(config.title_style.clone()),
+    );
+}
+
+fn spawn_property_damage_text(builder: &mut ChildBuilder, config: &UiConfig, run_bonus: &RunBonus) {
+    builder.spawn(
+        (TextBundle {
+            text: Text::from_section(
+                format!("Property damage: ${}", run_bonus.property_damage),
+                config.text_style.clone(),
+            ),
+            ..default()
+        })
+        .with_style(config.title_style.clone()),
+    );
+}
+
+fn spawn_run_stats_text(builder: &mut ChildBuilder, config: &UiConfig, run_stats: &RunStats) {
+    builder.spawn(
+        (TextBundle {
+            text: Text::from_section(
+                format!(
+                    "Longest kill: {:.0}m\nBest shotgun blast: {} kills\nFridges killed by fridges: {}",
+                    run_stats.longest_kill,
+                    run_stats.best_shotgun_blast,
+                    run_stats.friendly_fire_kills,
+                ),
+                config.text_style.clone(),
+            ),
+            ..default()
+        })
+        .with_style(config.title_style.clone()),
+    );
+}
+
+fn spawn_run_modifiers_text(
+    builder: &mut ChildBuilder,
+    config: &UiConfig,
+    run_modifiers: &RunModifiers,
+) {
+    if run_modifiers.modifiers.is_empty() {
+        return;
+    }
+
+    builder.spawn(
+        (TextBundle {
+            text: Text::from_section(
+                run_modifiers::run_modifiers_description(run_modifiers),
+                config.text_style.clone(),
+            ),
+            ..default()
+        })
+        .with_style(config.title_style.clone()),
+    );
+}
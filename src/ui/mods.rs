@@ -0,0 +1,137 @@
+use bevy::prelude::*;
+
+use crate::{mods::ModList, utils::remove_all_with, UiState};
+
+use super::{spawn_button, spawn_button_with_text, ButtonText, UiConfig};
+
+pub struct ModsMenuPlugin;
+
+impl Plugin for ModsMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(UiState::Mods), setup_mods_menu);
+        app.add_systems(
+            Update,
+            (button_system, update_mods_list)
+                .chain()
+                .run_if(in_state(UiState::Mods)),
+        );
+        app.add_systems(OnExit(UiState::Mods), remove_all_with::<ModsMenu>);
+    }
+}
+
+#[derive(Component)]
+struct ModsMenu;
+
+#[derive(Debug, Clone, Copy, Component)]
+enum ModsMenuButton {
+    Mod(usize),
+    Back,
+}
+
+// Rebuilt every time a mod is toggled
+#[derive(Component)]
+struct ModsList;
+
+fn setup_mods_menu(mut commands: Commands, config: Res<UiConfig>, mod_list: Res<ModList>) {
+    let text = if mod_list.mods.is_empty() {
+        "No mods found in assets/mods"
+    } else {
+        "Changes apply after restart"
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: config.menu_style.clone(),
+                background_color: config.panels_background.into(),
+                ..default()
+            },
+            ModsMenu,
+        ))
+        .with_children(|builder| {
+            builder.spawn(
+                TextBundle {
+                    text: Text::from_section(text, config.options_text_style.clone()),
+                    ..default()
+                }
+                .with_style(config.title_style.clone()),
+            );
+
+            builder.spawn((
+                NodeBundle {
+                    style: config.menu_buttons_area_style.clone(),
+                    background_color: config.panels_background.into(),
+                    ..default()
+                },
+                ModsList,
+            ));
+
+            spawn_button(builder, &config, ModsMenuButton::Back);
+        });
+}
+
+#[allow(clippy::complexity)]
+fn button_system(
+    config: Res<UiConfig>,
+    interaction_query: Query<
+        (&ModsMenuButton, &Interaction, &Children),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut mod_list: ResMut<ModList>,
+    mut texts: Query<&mut Text, With<ButtonText<ModsMenuButton>>>,
+    mut ui_state: ResMut<NextState<UiState>>,
+) {
+    for (button, interaction, children) in interaction_query.iter() {
+        let text_entity = children[0];
+        let Ok(mut text) = texts.get_mut(text_entity) else {
+            continue;
+        };
+        match *interaction {
+            Interaction::Pressed => {
+                text.sections[0].style.color = config.button_text_color_pressed;
+                match button {
+                    ModsMenuButton::Mod(index) => {
+                        if let Some(mod_info) = mod_list.mods.get_mut(*index) {
+                            mod_info.enabled = !mod_info.enabled;
+                        }
+                    }
+                    ModsMenuButton::Back => {
+                        ui_state.set(UiState::MainMenu);
+                    }
+                }
+            }
+            Interaction::Hovered => {
+                text.sections[0].style.color = config.button_text_color_hover;
+            }
+            Interaction::None => {
+                text.sections[0].style.color = config.button_text_color_normal;
+            }
+        }
+    }
+}
+
+fn update_mods_list(
+    config: Res<UiConfig>,
+    mod_list: Res<ModList>,
+    mods_list: Query<Entity, With<ModsList>>,
+    new_mods_list: Query<(), Added<ModsList>>,
+    mut commands: Commands,
+) {
+    if !mod_list.is_changed() && new_mods_list.is_empty() {
+        return;
+    }
+    let Ok(mods_list) = mods_list.get_single() else {
+        return;
+    };
+
+    commands
+        .entity(mods_list)
+        .despawn_descendants()
+        .with_children(|builder| {
+            for (index, mod_info) in mod_list.mods.iter().enumerate() {
+                let state = if mod_info.enabled { "On" } else { "Off" };
+                let label = format!("{} [{state}]", mod_info.manifest.name);
+                spawn_button_with_text(builder, &config, ModsMenuButton::Mod(index), label);
+            }
+        });
+}
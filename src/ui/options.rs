@@ -1,10 +1,21 @@
-use bevy::{prelude::*, window::WindowMode};
-use bevy_kira_audio::{Audio, AudioControl};
+use bevy::prelude::*;
+use bevy_kira_audio::{Audio, AudioChannel, AudioControl};
 
-use crate::{utils::remove_all_with, GameSettings, GlobalState, UiState};
+use crate::{
+    display::{
+        DisplayConfirmation, DisplaySettings, FpsLimit, Quality, DISPLAY_CUSTOM_FPS_STEP,
+        DISPLAY_MAX_CUSTOM_FPS, DISPLAY_MIN_CUSTOM_FPS,
+    },
+    hud::{HUD_SAFE_AREA_MAX, HUD_SAFE_AREA_STEP, HUD_SCALE_MAX, HUD_SCALE_MIN, HUD_SCALE_STEP},
+    input_device::ActiveInputDevice,
+    utils::remove_all_with,
+    GameSettings, GlobalState, SfxChannel, UiState,
+};
 
 use super::{spawn_button, ButtonText, UiConfig};
 
+const OPTIONS_CONFIRMATION_BACKGROUND: Color = Color::rgba(0.0, 0.0, 0.0, 0.9);
+
 pub struct OptionsPlugin;
 
 impl Plugin for OptionsPlugin {
@@ -15,8 +26,17 @@ impl Plugin for OptionsPlugin {
             (
                 button_system,
                 update_window_mode_text,
+                update_frame_rate_text,
+                update_quality_text,
+                update_display_confirmation,
                 update_volume_value_text,
                 update_camera_sense_value_text,
+                update_captions_text,
+                update_narration_text,
+                update_announcer_text,
+                update_auto_pause_text,
+                update_input_device_text,
+                update_hud_text,
             )
                 .run_if(in_state(UiState::Options)),
         );
@@ -29,24 +49,85 @@ struct OptionsMenu;
 
 #[derive(Debug, Clone, Copy, Component)]
 enum OptionMenuButton {
-    FullScreen,
-    Windowed,
+    Mode,
+    Resolution,
+    Monitor,
+    Vsync,
+    FpsLimit,
+    FpsUp,
+    FpsDown,
+    Quality,
+    AutoQuality,
+    SecurityCameras,
+    Trails,
+    Keep,
+    Revert,
     VolumeUp,
     VolumeDown,
     SenseUp,
     SenseDown,
+    Captions,
+    Narration,
+    Announcer,
+    AnnouncerUp,
+    AnnouncerDown,
+    AutoPause,
+    HudScaleUp,
+    HudScaleDown,
+    SafeAreaUp,
+    SafeAreaDown,
+    MinimalHud,
+    Crosshair,
     Back,
 }
 
 #[derive(Component)]
 struct OptionsWindowModeText;
 
+#[derive(Component)]
+struct OptionsFrameRateText;
+
+#[derive(Component)]
+struct OptionsQualityText;
+
+// Dialog with the countdown before display
+// settings are reverted
+#[derive(Component)]
+struct OptionsConfirmation;
+
+#[derive(Component)]
+struct OptionsConfirmationText;
+
 #[derive(Component)]
 struct OptionsVolumeText;
 
 #[derive(Component)]
 struct OptionsCameraSenseText;
 
+#[derive(Component)]
+struct OptionsCaptionsText;
+
+#[derive(Component)]
+struct OptionsNarrationText;
+
+#[derive(Component)]
+struct OptionsAnnouncerText;
+
+#[derive(Component)]
+struct OptionsAutoPauseText;
+
+#[derive(Component)]
+struct OptionsInputDeviceText;
+
+#[derive(Component)]
+struct OptionsHudScaleText;
+
+#[derive(Component)]
+struct OptionsSafeAreaText;
+
+#[derive(Component)]
+struct OptionsMinimalHudText;
+
 fn setup_option_menu(mut commands: Commands, config: Res<UiConfig>) {
     commands
         .spawn((
@@ -67,7 +148,7 @@ fn setup_option_menu(mut commands: Commands, config: Res<UiConfig>) {
                     ..default()
                 },))
                 .with_children(|builder| {
-                    // Window mode
+                    // Window mode, resolution and monitor
                     builder
                         .spawn((NodeBundle {
                             style: config.options_buttons_area_style.clone(),
@@ -75,8 +156,9 @@ fn setup_option_menu(mut commands: Commands, config: Res<UiConfig>) {
                             ..default()
                         },))
                         .with_children(|builder| {
-                            spawn_button(builder, &config, OptionMenuButton::FullScreen);
-                            spawn_button(builder, &config, OptionMenuButton::Windowed);
+                            spawn_button(builder, &config, OptionMenuButton::Mode);
+                            spawn_button(builder, &config, OptionMenuButton::Resolution);
+                            spawn_button(builder, &config, OptionMenuButton::Monitor);
                             // Window mode text
                             builder.spawn((
                                 TextBundle {
@@ -88,6 +170,50 @@ fn setup_option_menu(mut commands: Commands, config: Res<UiConfig>) {
                             ));
                         });
 
+                    // Vsync and frame rate limit
+                    builder
+                        .spawn((NodeBundle {
+                            style: config.options_buttons_area_style.clone(),
+                            background_color: config.panels_background.into(),
+                            ..default()
+                        },))
+                        .with_children(|builder| {
+                            spawn_button(builder, &config, OptionMenuButton::Vsync);
+                            spawn_button(builder, &config, OptionMenuButton::FpsLimit);
+                            spawn_button(builder, &config, OptionMenuButton::FpsUp);
+                            spawn_button(builder, &config, OptionMenuButton::FpsDown);
+                            builder.spawn((
+                                TextBundle {
+                                    text: Text::from_section("", config.options_text_style.clone()),
+                                    ..default()
+                                }
+                                .with_style(config.button_style.clone()),
+                                OptionsFrameRateText,
+                            ));
+                        });
+
+                    // Graphics quality
+                    builder
+                        .spawn((NodeBundle {
+                            style: config.options_buttons_area_style.clone(),
+                            background_color: config.panels_background.into(),
+                            ..default()
+                        },))
+                        .with_children(|builder| {
+                            spawn_button(builder, &config, OptionMenuButton::Quality);
+                            spawn_button(builder, &config, OptionMenuButton::AutoQuality);
+                            spawn_button(builder, &config, OptionMenuButton::SecurityCameras);
+                            spawn_button(builder, &config, OptionMenuButton::Trails);
+                            builder.spawn((
+                                TextBundle {
+                                    text: Text::from_section("", config.options_text_style.clone()),
+                                    ..default()
+                                }
+                                .with_style(config.button_style.clone()),
+                                OptionsQualityText,
+                            ));
+                        });
+
                     // Volume
                     builder
                         .spawn((NodeBundle {
@@ -128,6 +254,162 @@ fn setup_option_menu(mut commands: Commands, config: Res<UiConfig>) {
                             ));
                         });
 
+                    // Sound captions
+                    builder
+                        .spawn((NodeBundle {
+                            style: config.options_buttons_area_style.clone(),
+                            background_color: config.panels_background.into(),
+                            ..default()
+                        },))
+                        .with_children(|builder| {
+                            spawn_button(builder, &config, OptionMenuButton::Captions);
+                            builder.spawn((
+                                TextBundle {
+                                    text: Text::from_section("", config.options_text_style.clone()),
+                                    ..default()
+                                }
+                                .with_style(config.button_style.clone()),
+                                OptionsCaptionsText,
+                            ));
+                        });
+
+                    // Menu narration
+                    builder
+                        .spawn((NodeBundle {
+                            style: config.options_buttons_area_style.clone(),
+                            background_color: config.panels_background.into(),
+                            ..default()
+                        },))
+                        .with_children(|builder| {
+                            spawn_button(builder, &config, OptionMenuButton::Narration);
+                            builder.spawn((
+                                TextBundle {
+                                    text: Text::from_section("", config.options_text_style.clone()),
+                                    ..default()
+                                }
+                                .with_style(config.button_style.clone()),
+                                OptionsNarrationText,
+                            ));
+                        });
+
+                    // Announcer
+                    builder
+                        .spawn((NodeBundle {
+                            style: config.options_buttons_area_style.clone(),
+                            background_color: config.panels_background.into(),
+                            ..default()
+                        },))
+                        .with_children(|builder| {
+                            spawn_button(builder, &config, OptionMenuButton::Announcer);
+                            spawn_button(builder, &config, OptionMenuButton::AnnouncerUp);
+                            spawn_button(builder, &config, OptionMenuButton::AnnouncerDown);
+                            builder.spawn((
+                                TextBundle {
+                                    text: Text::from_section("", config.options_text_style.clone()),
+                                    ..default()
+                                }
+                                .with_style(config.button_style.clone()),
+                                OptionsAnnouncerText,
+                            ));
+                        });
+
+                    // Pause on focus loss
+                    builder
+                        .spawn((NodeBundle {
+                            style: config.options_buttons_area_style.clone(),
+                            background_color: config.panels_background.into(),
+                            ..default()
+                        },))
+                        .with_children(|builder| {
+                            spawn_button(builder, &config, OptionMenuButton::AutoPause);
+                            builder.spawn((
+                                TextBundle {
+                                    text: Text::from_section("", config.options_text_style.clone()),
+                                    ..default()
+                                }
+                                .with_style(config.button_style.clone()),
+                                OptionsAutoPauseText,
+                            ));
+                        });
+
+                    // Active input device
+                    builder
+                        .spawn((NodeBundle {
+                            style: config.options_buttons_area_style.clone(),
+                            background_color: config.panels_background.into(),
+                            ..default()
+                        },))
+                        .with_children(|builder| {
+                            builder.spawn((
+                                TextBundle {
+                                    text: Text::from_section("", config.options_text_style.clone()),
+                                    ..default()
+                                }
+                                .with_style(config.button_style.clone()),
+                                OptionsInputDeviceText,
+                            ));
+                        });
+
+                    // HUD scale
+                    builder
+                        .spawn((NodeBundle {
+                            style: config.options_buttons_area_style.clone(),
+                            background_color: config.panels_background.into(),
+                            ..default()
+                        },))
+                        .with_children(|builder| {
+                            spawn_button(builder, &config, OptionMenuButton::HudScaleUp);
+                            spawn_button(builder, &config, OptionMenuButton::HudScaleDown);
+                            builder.spawn((
+                                TextBundle {
+                                    text: Text::from_section("", config.options_text_style.clone()),
+                                    ..default()
+                                }
+                                .with_style(config.button_style.clone()),
+                                OptionsHudScaleText,
+                            ));
+                        });
+
+                    // HUD safe area
+                    builder
+                        .spawn((NodeBundle {
+                            style: config.options_buttons_area_style.clone(),
+                            background_color: config.panels_background.into(),
+                            ..default()
+                        },))
+                        .with_children(|builder| {
+                            spawn_button(builder, &config, OptionMenuButton::SafeAreaUp);
+                            spawn_button(builder, &config, OptionMenuButton::SafeAreaDown);
+                            builder.spawn((
+                                TextBundle {
+                                    text: Text::from_section("", config.options_text_style.clone()),
+                                    ..default()
+                                }
+                                .with_style(config.button_style.clone()),
+                                OptionsSafeAreaText,
+                            ));
+                        });
+
+                    // Minimal HUD
+                    builder
+                        .spawn((NodeBundle {
+                            style: config.options_buttons_area_style.clone(),
+                            background_color: config.panels_background.into(),
+                            ..default()
+                        },))
+                        .with_children(|builder| {
+                            spawn_button(builder, &config, OptionMenuButton::MinimalHud);
+                            builder.spawn((
+                                TextBundle {
+                                    text: Text::from_section("", config.options_text_style.clone()),
+                                    ..default()
+                                }
+                                .with_style(config.button_style.clone()),
+                                OptionsMinimalHudText,
+                            ));
+                        });
+
+                    spawn_button(builder, &config, OptionMenuButton::Crosshair);
                     spawn_button(builder, &config, OptionMenuButton::Back);
                 });
         });
@@ -136,6 +418,7 @@ fn setup_option_menu(mut commands: Commands, config: Res<UiConfig>) {
 #[allow(clippy::complexity)]
 fn button_system(
     audio: Res<Audio>,
+    sfx: Res<AudioChannel<SfxChannel>>,
     config: Res<UiConfig>,
     interaction_query: Query<
         (&OptionMenuButton, &Interaction, &Children),
@@ -143,7 +426,8 @@ fn button_system(
     >,
     global_state: Res<State<GlobalState>>,
     // audio: ResMut<Audio>,
-    mut windows: Query<&mut Window>,
+    mut display_settings: ResMut<DisplaySettings>,
+    mut display_confirmation: ResMut<DisplayConfirmation>,
     mut game_settings: ResMut<GameSettings>,
     mut texts: Query<&mut Text, With<ButtonText<OptionMenuButton>>>,
     mut ui_state: ResMut<NextState<UiState>>,
@@ -157,17 +441,64 @@ fn button_system(
             Interaction::Pressed => {
                 text.sections[0].style.color = config.button_text_color_pressed;
                 match button {
-                    OptionMenuButton::Windowed => {
-                        game_settings.window_mode = WindowMode::Windowed;
-                        windows.single_mut().mode = WindowMode::Windowed;
+                    OptionMenuButton::Mode => {
+                        display_confirmation.start(*display_settings);
+                        display_settings.mode = display_settings.mode.next();
+                    }
+                    OptionMenuButton::Resolution => {
+                        display_confirmation.start(*display_settings);
+                        display_settings.resolution = display_settings.next_resolution();
+                    }
+                    OptionMenuButton::Monitor => {
+                        display_confirmation.start(*display_settings);
+                        display_settings.monitor = display_settings.next_monitor();
+                    }
+                    OptionMenuButton::Vsync => {
+                        display_settings.vsync = !display_settings.vsync;
+                    }
+                    OptionMenuButton::FpsLimit => {
+                        display_settings.fps_limit = display_settings.fps_limit.next();
+                    }
+                    // changing the value selects the custom limit
+                    OptionMenuButton::FpsUp => {
+                        display_settings.fps_limit = FpsLimit::Custom;
+                        display_settings.custom_fps = (display_settings.custom_fps
+                            + DISPLAY_CUSTOM_FPS_STEP)
+                            .min(DISPLAY_MAX_CUSTOM_FPS);
                     }
-                    OptionMenuButton::FullScreen => {
-                        game_settings.window_mode = WindowMode::Fullscreen;
-                        windows.single_mut().mode = WindowMode::Fullscreen;
+                    OptionMenuButton::FpsDown => {
+                        display_settings.fps_limit = FpsLimit::Custom;
+                        display_settings.custom_fps = display_settings
+                            .custom_fps
+                            .saturating_sub(DISPLAY_CUSTOM_FPS_STEP)
+                            .max(DISPLAY_MIN_CUSTOM_FPS);
+                    }
+                    OptionMenuButton::Quality => {
+                        display_settings.quality = display_settings.quality.next();
+                    }
+                    OptionMenuButton::AutoQuality => {
+                        display_settings.auto_quality = !display_settings.auto_quality;
+                    }
+                    OptionMenuButton::SecurityCameras => {
+                        display_settings.security_cameras = !display_settings.security_cameras;
+                    }
+                    OptionMenuButton::Trails => {
+                        display_settings.projectile_trails = !display_settings.projectile_trails;
+                    }
+                    OptionMenuButton::Keep => {
+                        display_confirmation.keep();
+                        // settings are saved only after the confirmation
+                        display_settings.set_changed();
+                    }
+                    OptionMenuButton::Revert => {
+                        if let Some(previous) = display_confirmation.revert() {
+                            *display_settings = previous;
+                        }
                     }
                     OptionMenuButton::VolumeUp => {
                         game_settings.volume += 0.05;
                         audio.set_volume(game_settings.volume as f64);
+                        sfx.set_volume(game_settings.volume as f64);
                     }
                     OptionMenuButton::VolumeDown => {
                         game_settings.volume -= 0.05;
@@ -175,6 +506,7 @@ fn button_system(
                             game_settings.volume = 0.0;
                         }
                         audio.set_volume(game_settings.volume as f64);
+                        sfx.set_volume(game_settings.volume as f64);
                     }
                     OptionMenuButton::SenseUp => {
                         game_settings.camera_sensitivity += 0.1;
@@ -185,6 +517,47 @@ fn button_system(
                             game_settings.camera_sensitivity = 0.0;
                         }
                     }
+                    OptionMenuButton::Captions => {
+                        game_settings.sound_captions = !game_settings.sound_captions;
+                    }
+                    OptionMenuButton::Narration => {
+                        game_settings.narration = !game_settings.narration;
+                    }
+                    OptionMenuButton::Announcer => {
+                        game_settings.announcer = !game_settings.announcer;
+                    }
+                    OptionMenuButton::AnnouncerUp => {
+                        game_settings.announcer_volume =
+                            (game_settings.announcer_volume + 0.1).min(1.0);
+                    }
+                    OptionMenuButton::AnnouncerDown => {
+                        game_settings.announcer_volume =
+                            (game_settings.announcer_volume - 0.1).max(0.0);
+                    }
+                    OptionMenuButton::AutoPause => {
+                        game_settings.auto_pause = !game_settings.auto_pause;
+                    }
+                    OptionMenuButton::HudScaleUp => {
+                        game_settings.hud_scale =
+                            (game_settings.hud_scale + HUD_SCALE_STEP).min(HUD_SCALE_MAX);
+                    }
+                    OptionMenuButton::HudScaleDown => {
+                        game_settings.hud_scale =
+                            (game_settings.hud_scale - HUD_SCALE_STEP).max(HUD_SCALE_MIN);
+                    }
+                    OptionMenuButton::SafeAreaUp => {
+                        game_settings.hud_safe_area = (game_settings.hud_safe_area
+                            + HUD_SAFE_AREA_STEP)
+                            .min(HUD_SAFE_AREA_MAX);
+                    }
+                    OptionMenuButton::SafeAreaDown => {
+                        game_settings.hud_safe_area =
+                            (game_settings.hud_safe_area - HUD_SAFE_AREA_STEP).max(0.0);
+                    }
+                    OptionMenuButton::MinimalHud => {
+                        game_settings.minimal_hud = !game_settings.minimal_hud;
+                    }
+                    OptionMenuButton::Crosshair => ui_state.set(UiState::Crosshair),
                     OptionMenuButton::Back => match global_state.get() {
                         GlobalState::MainMenu => ui_state.set(UiState::MainMenu),
                         GlobalState::Paused => ui_state.set(UiState::Paused),
@@ -203,11 +576,115 @@ fn button_system(
 }
 
 fn update_window_mode_text(
-    game_settings: Res<GameSettings>,
+    display_settings: Res<DisplaySettings>,
     mut window_mode_text: Query<&mut Text, With<OptionsWindowModeText>>,
 ) {
     let mut text = window_mode_text.single_mut();
-    text.sections[0].value = format!("{:?}", game_settings.window_mode);
+    let (width, height) = display_settings.resolution;
+    text.sections[0].value = format!(
+        "{:?} {width}x{height} Monitor {}",
+        display_settings.mode,
+        display_settings.monitor + 1
+    );
+}
+
+fn update_frame_rate_text(
+    display_settings: Res<DisplaySettings>,
+    mut frame_rate_text: Query<&mut Text, With<OptionsFrameRateText>>,
+) {
+    let mut text = frame_rate_text.single_mut();
+    let vsync = if display_settings.vsync { "On" } else { "Off" };
+    let limit = match display_settings.max_fps() {
+        Some(max_fps) => format!("{max_fps} FPS"),
+        None => "Uncapped".to_string(),
+    };
+    text.sections[0].value = format!("Vsync {vsync} {limit}");
+}
+
+fn update_quality_text(
+    display_settings: Res<DisplaySettings>,
+    quality: Res<Quality>,
+    mut quality_text: Query<&mut Text, With<OptionsQualityText>>,
+) {
+    let mut text = quality_text.single_mut();
+    let auto = if display_settings.auto_quality {
+        "Auto"
+    } else {
+        "Fixed"
+    };
+    let cameras = if display_settings.security_cameras {
+        "On"
+    } else {
+        "Off"
+    };
+    let trails = if display_settings.projectile_trails {
+        "On"
+    } else {
+        "Off"
+    };
+    // automatic scaling could have lowered the preset
+    let preset = if quality.preset == display_settings.quality {
+        format!("{:?} {auto}", display_settings.quality)
+    } else {
+        format!(
+            "{:?} {auto} (now {:?})",
+            display_settings.quality, quality.preset
+        )
+    };
+    text.sections[0].value = format!("{preset} Cameras {cameras} Trails {trails}");
+}
+
+fn update_display_confirmation(
+    config: Res<UiConfig>,
+    display_confirmation: Res<DisplayConfirmation>,
+    confirmation: Query<Entity, With<OptionsConfirmation>>,
+    mut confirmation_text: Query<&mut Text, With<OptionsConfirmationText>>,
+    mut commands: Commands,
+) {
+    let Some(remaining) = display_confirmation.remaining() else {
+        for e in confirmation.iter() {
+            commands.entity(e).despawn_recursive();
+        }
+        return;
+    };
+
+    let value = format!("Keep display settings? Reverting in {}", remaining.ceil());
+    if let Ok(mut text) = confirmation_text.get_single_mut() {
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+        return;
+    }
+    if !confirmation.is_empty() {
+        return;
+    }
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: config.options_confirmation_style.clone(),
+                background_color: OPTIONS_CONFIRMATION_BACKGROUND.into(),
+                z_index: ZIndex::Global(1),
+                ..default()
+            },
+            OptionsConfirmation,
+            OptionsMenu,
+        ))
+        .with_children(|builder| {
+            builder.spawn((
+                TextBundle {
+                    text: Text::from_section(value, config.text_style.clone()),
+                    style: Style {
+                        grid_column: GridPlacement::span(2),
+                        ..default()
+                    },
+                    ..default()
+                },
+                OptionsConfirmationText,
+            ));
+            spawn_button(builder, &config, OptionMenuButton::Keep);
+            spawn_button(builder, &config, OptionMenuButton::Revert);
+        });
 }
 
 fn update_volume_value_text(
@@ -225,3 +702,99 @@ fn update_camera_sense_value_text(
     let mut text = volume_text.single_mut();
     text.sections[0].value = format!("{:.2}", game_settings.camera_sensitivity);
 }
+
+fn update_captions_text(
+    game_settings: Res<GameSettings>,
+    mut captions_text: Query<&mut Text, With<OptionsCaptionsText>>,
+) {
+    let mut text = captions_text.single_mut();
+    text.sections[0].value = if game_settings.sound_captions {
+        "On".to_string()
+    } else {
+        "Off".to_string()
+    };
+}
+
+fn update_narration_text(
+    game_settings: Res<GameSettings>,
+    mut narration_text: Query<&mut Text, With<OptionsNarrationText>>,
+) {
+    let mut text = narration_text.single_mut();
+    text.sections[0].value = if game_settings.narration {
+        "On".to_string()
+    } else {
+        "Off".to_string()
+    };
+}
+
+fn update_announcer_text(
+    game_settings: Res<GameSettings>,
+    mut announcer_text: Query<&mut Text, With<OptionsAnnouncerText>>,
+) {
+    let mut text = announcer_text.single_mut();
+    text.sections[0].value = if game_settings.announcer {
+        format!("On {:.2}", game_settings.announcer_volume)
+    } else {
+        "Off".to_string()
+    };
+}
+
+fn update_auto_pause_text(
+    game_settings: Res<GameSettings>,
+    mut auto_pause_text: Query<&mut Text, With<OptionsAutoPauseText>>,
+) {
+    let mut text = auto_pause_text.single_mut();
+    text.sections[0].value = if game_settings.auto_pause {
+        "On".to_string()
+    } else {
+        "Off".to_string()
+    };
+}
+
+fn update_input_device_text(
+    active_input_device: Res<ActiveInputDevice>,
+    mut input_device_text: Query<&mut Text, With<OptionsInputDeviceText>>,
+) {
+    let mut text = input_device_text.single_mut();
+    text.sections[0].value = format!("Input: {}", active_input_device.name());
+}
+
+#[allow(clippy::complexity)]
+fn update_hud_text(
+    game_settings: Res<GameSettings>,
+    mut hud_scale_text: Query<
+        &mut Text,
+        (
+            With<OptionsHudScaleText>,
+            Without<OptionsSafeAreaText>,
+            Without<OptionsMinimalHudText>,
+        ),
+    >,
+    mut safe_area_text: Query<
+        &mut Text,
+        (
+            With<OptionsSafeAreaText>,
+            Without<OptionsHudScaleText>,
+            Without<OptionsMinimalHudText>,
+        ),
+    >,
+    mut minimal_hud_text: Query<
+        &mut Text,
+        (
+            With<OptionsMinimalHudText>,
+            Without<OptionsHudScaleText>,
+            Without<OptionsSafeAreaText>,
+        ),
+    >,
+) {
+    let mut text = hud_scale_text.single_mut();
+    text.sections[0].value = format!("{:.0}%", game_settings.hud_scale * 100.0);
+    let mut text = safe_area_text.single_mut();
+    text.sections[0].value = format!("{:.0}%", game_settings.hud_safe_area * 100.0);
+    let mut text = minimal_hud_text.single_mut();
+    text.sections[0].value = if game_settings.minimal_hud {
+        "On".to_string()
+    } else {
+        "Off".to_string()
+    };
+}
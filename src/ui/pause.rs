@@ -1,15 +1,51 @@
 use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
 
-use crate::{utils::remove_all_with, GlobalState, UiState};
+use crate::{
+    damage::Health,
+    enemies::Enemy,
+    level::{
+        door::Door,
+        grade::{LevelGradeStats, RunBonus},
+        map::level_map,
+        terminal::LoreTerminal,
+        LevelCollider, LevelInfo,
+    },
+    player::{Player, PlayerId},
+    profile::Profile,
+    run_modifiers::RunModifiers,
+    scrap::Scrap,
+    utils::remove_all_with,
+    GlobalState, UiState,
+};
 
-use super::{spawn_button, ButtonText, UiConfig};
+use super::{spawn_button, spawn_run_modifiers_text, ButtonText, UiConfig};
+
+const PAUSE_MAP_FONT_SIZE: f32 = 14.0;
+const PAUSE_MAP_PLAYER: char = '@';
+const PAUSE_MAP_ENEMY: char = 'F';
+const PAUSE_MAP_DOOR: char = 'D';
 
 pub struct PausePlugin;
 
 impl Plugin for PausePlugin {
     fn build(&self, app: &mut App) {
+        app.init_resource::<PauseTab>();
+
         app.add_systems(OnEnter(UiState::Paused), setup_pause_menu);
-        app.add_systems(Update, button_system.run_if(in_state(UiState::Paused)));
+        app.add_systems(
+            Update,
+            (
+                button_system,
+                tab_button_system,
+                update_pause_tab.run_if(resource_changed::<PauseTab>()),
+                update_map,
+                update_stats,
+                update_objectives,
+            )
+                .chain()
+                .run_if(in_state(UiState::Paused)),
+        );
         app.add_systems(OnExit(UiState::Paused), remove_all_with::<PauseMenu>);
     }
 }
@@ -17,6 +53,19 @@ impl Plugin for PausePlugin {
 #[derive(Component)]
 struct PauseMenu;
 
+// Node with the content of the selected tab
+#[derive(Component)]
+struct PauseTabContent;
+
+#[derive(Component)]
+struct PauseMapText;
+
+#[derive(Component)]
+struct PauseStatsText;
+
+#[derive(Component)]
+struct PauseObjectivesText;
+
 #[derive(Debug, Clone, Copy, Component)]
 enum PauseMenuButton {
     Continue,
@@ -24,7 +73,25 @@ enum PauseMenuButton {
     MainMenu,
 }
 
-fn setup_pause_menu(mut commands: Commands, config: Res<UiConfig>) {
+// Tab buttons are labeled with the tab name
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Component, Resource)]
+enum PauseTab {
+    Map,
+    Stats,
+    Objectives,
+    #[default]
+    Settings,
+}
+
+fn setup_pause_menu(
+    mut commands: Commands,
+    config: Res<UiConfig>,
+    run_modifiers: Res<RunModifiers>,
+    mut pause_tab: ResMut<PauseTab>,
+) {
+    // also makes `update_pause_tab` fill the new menu
+    *pause_tab = PauseTab::default();
+
     commands
         .spawn((
             NodeBundle {
@@ -35,21 +102,220 @@ fn setup_pause_menu(mut commands: Commands, config: Res<UiConfig>) {
             PauseMenu,
         ))
         .with_children(|builder| {
-            // Buttons
+            spawn_run_modifiers_text(builder, &config, &run_modifiers);
+
+            // Tabs
             builder
                 .spawn((NodeBundle {
-                    style: config.menu_buttons_area_style.clone(),
+                    style: config.pause_tabs_style.clone(),
                     background_color: config.panels_background.into(),
                     ..default()
                 },))
                 .with_children(|builder| {
-                    spawn_button(builder, &config, PauseMenuButton::Continue);
-                    spawn_button(builder, &config, PauseMenuButton::Options);
-                    spawn_button(builder, &config, PauseMenuButton::MainMenu);
+                    spawn_button(builder, &config, PauseTab::Map);
+                    spawn_button(builder, &config, PauseTab::Stats);
+                    spawn_button(builder, &config, PauseTab::Objectives);
+                    spawn_button(builder, &config, PauseTab::Settings);
                 });
+
+            builder.spawn((
+                NodeBundle {
+                    style: config.menu_buttons_area_style.clone(),
+                    background_color: config.panels_background.into(),
+                    ..default()
+                },
+                PauseTabContent,
+            ));
         });
 }
 
+#[allow(clippy::complexity)]
+fn update_pause_tab(
+    config: Res<UiConfig>,
+    pause_tab: Res<PauseTab>,
+    tab_buttons: Query<(&PauseTab, &Children)>,
+    content: Query<Entity, With<PauseTabContent>>,
+    mut tab_texts: Query<&mut Text, With<ButtonText<PauseTab>>>,
+    mut commands: Commands,
+) {
+    for (tab, children) in tab_buttons.iter() {
+        let Ok(mut text) = tab_texts.get_mut(children[0]) else {
+            continue;
+        };
+        text.sections[0].style.color = tab_text_color(&config, *tab, *pause_tab);
+    }
+
+    let Ok(content) = content.get_single() else {
+        return;
+    };
+    commands
+        .entity(content)
+        .despawn_descendants()
+        .with_children(|builder| match *pause_tab {
+            PauseTab::Map => {
+                builder.spawn((
+                    TextBundle {
+                        text: Text::from_section(
+                            "",
+                            TextStyle {
+                                font_size: PAUSE_MAP_FONT_SIZE,
+                                ..config.text_style.clone()
+                            },
+                        ),
+                        ..default()
+                    }
+                    .with_style(config.title_style.clone()),
+                    PauseMapText,
+                ));
+            }
+            PauseTab::Stats => {
+                builder.spawn((
+                    TextBundle {
+                        text: Text::from_section("", config.text_style.clone()),
+                        ..default()
+                    }
+                    .with_style(config.title_style.clone()),
+                    PauseStatsText,
+                ));
+            }
+            PauseTab::Objectives => {
+                builder.spawn((
+                    TextBundle {
+                        text: Text::from_section("", config.text_style.clone()),
+                        ..default()
+                    }
+                    .with_style(config.title_style.clone()),
+                    PauseObjectivesText,
+                ));
+            }
+            PauseTab::Settings => {
+                spawn_button(builder, &config, PauseMenuButton::Continue);
+                spawn_button(builder, &config, PauseMenuButton::Options);
+                spawn_button(builder, &config, PauseMenuButton::MainMenu);
+            }
+        });
+}
+
+// Selected tab stays highlighted
+fn tab_text_color(config: &UiConfig, tab: PauseTab, selected: PauseTab) -> Color {
+    if tab == selected {
+        config.button_text_color_hover
+    } else {
+        config.button_text_color_normal
+    }
+}
+
+// Game is paused, so tab texts are filled only once
+fn update_map(
+    rapier_context: Res<RapierContext>,
+    level_info: Res<LevelInfo>,
+    level_colliders: Query<(), With<LevelCollider>>,
+    player: Query<&GlobalTransform, With<Player>>,
+    enemies: Query<&GlobalTransform, With<Enemy>>,
+    doors: Query<&GlobalTransform, With<Door>>,
+    mut map_text: Query<&mut Text, Added<PauseMapText>>,
+) {
+    let Ok(mut text) = map_text.get_single_mut() else {
+        return;
+    };
+
+    let markers = doors
+        .iter()
+        .map(|transform| (transform.translation(), PAUSE_MAP_DOOR))
+        .chain(
+            enemies
+                .iter()
+                .map(|transform| (transform.translation(), PAUSE_MAP_ENEMY)),
+        )
+        .chain(
+            player
+                .iter()
+                .map(|transform| (transform.translation(), PAUSE_MAP_PLAYER)),
+        );
+    text.sections[0].value = level_map(
+        rapier_context.as_ref(),
+        |entity| level_colliders.contains(entity),
+        level_info.translation,
+        markers,
+    );
+}
+
+fn update_stats(
+    level_info: Res<LevelInfo>,
+    scrap: Res<Scrap>,
+    run_bonus: Res<RunBonus>,
+    player: Query<(&PlayerId, &Health), With<Player>>,
+    mut stats_text: Query<&mut Text, Added<PauseStatsText>>,
+) {
+    let Ok(mut text) = stats_text.get_single_mut() else {
+        return;
+    };
+    let health = player
+        .iter()
+        .find(|(id, _)| id.is_local())
+        .map(|(_, health)| health.health.to_string())
+        .unwrap_or_else(|| "---".to_string());
+    text.sections[0].value = format!(
+        "Progress: {}%\nHP: {health}\nScrap: {}\nCoolant cells: {}\nRun bonus: {}",
+        level_info.game_progress, scrap.amount, scrap.coolant_cells, run_bonus.score,
+    );
+}
+
+fn update_objectives(
+    level_info: Res<LevelInfo>,
+    grade_stats: Res<LevelGradeStats>,
+    profile: Res<Profile>,
+    enemies: Query<(), With<Enemy>>,
+    terminals: Query<(), With<LoreTerminal>>,
+    mut objectives_text: Query<&mut Text, Added<PauseObjectivesText>>,
+) {
+    let Ok(mut text) = objectives_text.get_single_mut() else {
+        return;
+    };
+    let exit = if level_info.finished {
+        "open"
+    } else {
+        "locked"
+    };
+    text.sections[0].value = format!(
+        "Fridges left: {}\nExit: {exit}\n{}",
+        enemies.iter().count(),
+        grade_stats.objectives_text(profile.as_ref(), terminals.iter().count()),
+    );
+}
+
+#[allow(clippy::complexity)]
+fn tab_button_system(
+    config: Res<UiConfig>,
+    interaction_query: Query<
+        (&PauseTab, &Interaction, &Children),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut tab_texts: Query<&mut Text, With<ButtonText<PauseTab>>>,
+    mut pause_tab: ResMut<PauseTab>,
+) {
+    for (tab, interaction, children) in interaction_query.iter() {
+        let text_entity = children[0];
+        let Ok(mut text) = tab_texts.get_mut(text_entity) else {
+            continue;
+        };
+        match *interaction {
+            Interaction::Pressed => {
+                text.sections[0].style.color = config.button_text_color_pressed;
+                if *pause_tab != *tab {
+                    *pause_tab = *tab;
+                }
+            }
+            Interaction::Hovered => {
+                text.sections[0].style.color = config.button_text_color_hover;
+            }
+            Interaction::None => {
+                text.sections[0].style.color = tab_text_color(&config, *tab, *pause_tab);
+            }
+        }
+    }
+}
+
 #[allow(clippy::complexity)]
 fn button_system(
     config: Res<UiConfig>,
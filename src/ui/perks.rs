@@ -0,0 +1,127 @@
+use bevy::prelude::*;
+
+use crate::{
+    perks::{PerkChoices, Perks},
+    utils::remove_all_with,
+    GlobalState, UiState,
+};
+
+use super::UiConfig;
+
+pub struct PerksMenuPlugin;
+
+impl Plugin for PerksMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(UiState::Perks), setup_perks_menu);
+        app.add_systems(Update, button_system.run_if(in_state(UiState::Perks)));
+        app.add_systems(OnExit(UiState::Perks), remove_all_with::<PerksMenu>);
+    }
+}
+
+#[derive(Component)]
+struct PerksMenu;
+
+#[derive(Debug, Clone, Copy, Component)]
+struct PerkCardButton {
+    choice: usize,
+}
+
+#[derive(Component)]
+struct PerkCardText;
+
+fn setup_perks_menu(mut commands: Commands, config: Res<UiConfig>, perk_choices: Res<PerkChoices>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: config.menu_style.clone(),
+                background_color: config.panels_background.into(),
+                ..default()
+            },
+            PerksMenu,
+        ))
+        .with_children(|builder| {
+            builder.spawn(
+                (TextBundle {
+                    text: Text::from_section("CHOOSE A PERK", config.title_text_style.clone()),
+                    ..default()
+                })
+                .with_style(config.title_style.clone()),
+            );
+
+            // Cards
+            builder
+                .spawn((NodeBundle {
+                    style: config.menu_buttons_area_style.clone(),
+                    background_color: config.panels_background.into(),
+                    ..default()
+                },))
+                .with_children(|builder| {
+                    for (choice, perk) in perk_choices.choices.iter().enumerate() {
+                        builder
+                            .spawn((
+                                ButtonBundle {
+                                    style: Style {
+                                        flex_direction: FlexDirection::Column,
+                                        ..config.button_style.clone()
+                                    },
+                                    background_color: config.button_background.into(),
+                                    ..default()
+                                },
+                                PerkCardButton { choice },
+                            ))
+                            .with_children(|builder| {
+                                builder.spawn((
+                                    TextBundle {
+                                        text: Text::from_section(
+                                            perk.name(),
+                                            config.options_text_style.clone(),
+                                        ),
+                                        ..default()
+                                    },
+                                    PerkCardText,
+                                ));
+                                builder.spawn(TextBundle {
+                                    text: Text::from_section(
+                                        perk.description(),
+                                        config.text_style.clone(),
+                                    ),
+                                    ..default()
+                                });
+                            });
+                    }
+                });
+        });
+}
+
+#[allow(clippy::complexity)]
+fn button_system(
+    config: Res<UiConfig>,
+    perk_choices: Res<PerkChoices>,
+    interaction_query: Query<
+        (&PerkCardButton, &Interaction, &Children),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut perks: ResMut<Perks>,
+    mut card_texts: Query<&mut Text, With<PerkCardText>>,
+    mut global_state: ResMut<NextState<GlobalState>>,
+) {
+    for (button, interaction, children) in interaction_query.iter() {
+        let text_entity = children[0];
+        let Ok(mut text) = card_texts.get_mut(text_entity) else {
+            continue;
+        };
+        match *interaction {
+            Interaction::Pressed => {
+                text.sections[0].style.color = config.button_text_color_pressed;
+                perks.add(perk_choices.choices[button.choice]);
+                global_state.set(GlobalState::InGame);
+            }
+            Interaction::Hovered => {
+                text.sections[0].style.color = config.button_text_color_hover;
+            }
+            Interaction::None => {
+                text.sections[0].style.color = config.options_text_style.color;
+            }
+        }
+    }
+}
@@ -0,0 +1,244 @@
+use bevy::{prelude::*, window::ReceivedCharacter};
+
+use crate::{
+    profile::{is_profile_name_char, ProfileSlots, PROFILE_MAX_NAME_LEN},
+    utils::remove_all_with,
+    UiState,
+};
+
+use super::{spawn_button, spawn_button_with_text, ButtonText, UiConfig};
+
+pub struct ProfilesMenuPlugin;
+
+impl Plugin for ProfilesMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ProfileRename>();
+
+        app.add_systems(OnEnter(UiState::Profiles), setup_profiles_menu);
+        app.add_systems(
+            Update,
+            (
+                button_system,
+                rename_input,
+                update_profiles_list,
+                update_profiles_text,
+            )
+                .chain()
+                .run_if(in_state(UiState::Profiles)),
+        );
+        app.add_systems(
+            OnExit(UiState::Profiles),
+            (remove_all_with::<ProfilesMenu>, cancel_rename),
+        );
+    }
+}
+
+#[derive(Component)]
+struct ProfilesMenu;
+
+#[derive(Debug, Clone, Copy, Component)]
+enum ProfilesMenuButton {
+    Slot(usize),
+    Create,
+    Rename,
+    Delete,
+    Back,
+}
+
+// Rebuilt every time profiles change
+#[derive(Component)]
+struct ProfilesList;
+
+#[derive(Component)]
+struct ProfilesText;
+
+// Name typed by the player while renaming
+// the active profile
+#[derive(Debug, Default, Resource)]
+struct ProfileRename {
+    name: Option<String>,
+}
+
+fn setup_profiles_menu(mut commands: Commands, config: Res<UiConfig>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: config.menu_style.clone(),
+                background_color: config.panels_background.into(),
+                ..default()
+            },
+            ProfilesMenu,
+        ))
+        .with_children(|builder| {
+            builder.spawn((
+                TextBundle {
+                    text: Text::from_section("", config.options_text_style.clone()),
+                    ..default()
+                }
+                .with_style(config.title_style.clone()),
+                ProfilesText,
+            ));
+
+            builder.spawn((
+                NodeBundle {
+                    style: config.menu_buttons_area_style.clone(),
+                    background_color: config.panels_background.into(),
+                    ..default()
+                },
+                ProfilesList,
+            ));
+
+            // Buttons
+            builder
+                .spawn((NodeBundle {
+                    style: config.menu_buttons_area_style.clone(),
+                    background_color: config.panels_background.into(),
+                    ..default()
+                },))
+                .with_children(|builder| {
+                    spawn_button(builder, &config, ProfilesMenuButton::Create);
+                    spawn_button(builder, &config, ProfilesMenuButton::Rename);
+                    spawn_button(builder, &config, ProfilesMenuButton::Delete);
+                    spawn_button(builder, &config, ProfilesMenuButton::Back);
+                });
+        });
+}
+
+#[allow(clippy::complexity)]
+fn button_system(
+    config: Res<UiConfig>,
+    interaction_query: Query<
+        (&ProfilesMenuButton, &Interaction, &Children),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut profile_slots: ResMut<ProfileSlots>,
+    mut profile_rename: ResMut<ProfileRename>,
+    mut texts: Query<&mut Text, With<ButtonText<ProfilesMenuButton>>>,
+    mut ui_state: ResMut<NextState<UiState>>,
+) {
+    for (button, interaction, children) in interaction_query.iter() {
+        let text_entity = children[0];
+        let Ok(mut text) = texts.get_mut(text_entity) else {
+            continue;
+        };
+        match *interaction {
+            Interaction::Pressed => {
+                text.sections[0].style.color = config.button_text_color_pressed;
+                match button {
+                    ProfilesMenuButton::Slot(index) => {
+                        profile_rename.name = None;
+                        if *index != profile_slots.active() {
+                            profile_slots.select(*index);
+                        }
+                    }
+                    ProfilesMenuButton::Create => {
+                        profile_rename.name = None;
+                        profile_slots.create();
+                    }
+                    // Pressing again confirms the new name
+                    ProfilesMenuButton::Rename => match profile_rename.name.take() {
+                        Some(name) => {
+                            if name != profile_slots.active_name() {
+                                profile_slots.rename(&name);
+                            }
+                        }
+                        None => {
+                            profile_rename.name = Some(profile_slots.active_name().to_string());
+                        }
+                    },
+                    ProfilesMenuButton::Delete => {
+                        profile_rename.name = None;
+                        profile_slots.delete();
+                    }
+                    ProfilesMenuButton::Back => {
+                        ui_state.set(UiState::MainMenu);
+                    }
+                }
+            }
+            Interaction::Hovered => {
+                text.sections[0].style.color = config.button_text_color_hover;
+            }
+            Interaction::None => {
+                text.sections[0].style.color = config.button_text_color_normal;
+            }
+        }
+    }
+}
+
+fn rename_input(
+    keys: Res<Input<KeyCode>>,
+    mut profile_rename: ResMut<ProfileRename>,
+    mut received_characters: EventReader<ReceivedCharacter>,
+) {
+    if profile_rename.name.is_none() {
+        received_characters.clear();
+        return;
+    }
+    let Some(name) = profile_rename.name.as_mut() else {
+        return;
+    };
+
+    for received in received_characters.read() {
+        if is_profile_name_char(received.char) && name.len() < PROFILE_MAX_NAME_LEN {
+            name.push(received.char);
+        }
+    }
+    if keys.just_pressed(KeyCode::Back) {
+        name.pop();
+    }
+    if keys.just_pressed(KeyCode::Escape) {
+        profile_rename.name = None;
+    }
+}
+
+fn cancel_rename(mut profile_rename: ResMut<ProfileRename>) {
+    profile_rename.name = None;
+}
+
+fn update_profiles_list(
+    config: Res<UiConfig>,
+    profile_slots: Res<ProfileSlots>,
+    profiles_list: Query<Entity, With<ProfilesList>>,
+    new_profiles_list: Query<(), Added<ProfilesList>>,
+    mut commands: Commands,
+) {
+    if !profile_slots.is_changed() && new_profiles_list.is_empty() {
+        return;
+    }
+    let Ok(profiles_list) = profiles_list.get_single() else {
+        return;
+    };
+
+    commands
+        .entity(profiles_list)
+        .despawn_descendants()
+        .with_children(|builder| {
+            for (index, name) in profile_slots.names().iter().enumerate() {
+                let label = if index == profile_slots.active() {
+                    format!("> {name} <")
+                } else {
+                    name.clone()
+                };
+                spawn_button_with_text(builder, &config, ProfilesMenuButton::Slot(index), label);
+            }
+        });
+}
+
+fn update_profiles_text(
+    profile_slots: Res<ProfileSlots>,
+    profile_rename: Res<ProfileRename>,
+    new_profiles_text: Query<(), Added<ProfilesText>>,
+    mut profiles_text: Query<&mut Text, With<ProfilesText>>,
+) {
+    if !profile_slots.is_changed() && !profile_rename.is_changed() && new_profiles_text.is_empty() {
+        return;
+    }
+    let Ok(mut text) = profiles_text.get_single_mut() else {
+        return;
+    };
+
+    text.sections[0].value = match profile_rename.name.as_ref() {
+        Some(name) => format!("New name: {name}_"),
+        None => format!("Profile: {}", profile_slots.active_name()),
+    };
+}
@@ -0,0 +1,151 @@
+use bevy::prelude::*;
+
+use crate::{
+    run_modifiers::{RunModifier, RunModifiers},
+    utils::remove_all_with,
+    UiState,
+};
+
+use super::{spawn_button, ButtonText, UiConfig};
+
+pub struct RunModifiersMenuPlugin;
+
+impl Plugin for RunModifiersMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(UiState::RunModifiers), setup_run_modifiers_menu);
+        app.add_systems(
+            Update,
+            (button_system, update_run_modifiers_text).run_if(in_state(UiState::RunModifiers)),
+        );
+        app.add_systems(
+            OnExit(UiState::RunModifiers),
+            remove_all_with::<RunModifiersMenu>,
+        );
+    }
+}
+
+#[derive(Component)]
+struct RunModifiersMenu;
+
+#[derive(Debug, Clone, Copy, Component)]
+enum RunModifiersMenuButton {
+    OneHitDeath,
+    NoHud,
+    DoubleEnemies,
+    LowGravity,
+    TinyFridges,
+    Back,
+}
+
+#[derive(Component)]
+struct RunModifiersText;
+
+fn setup_run_modifiers_menu(mut commands: Commands, config: Res<UiConfig>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: config.menu_style.clone(),
+                background_color: config.panels_background.into(),
+                ..default()
+            },
+            RunModifiersMenu,
+        ))
+        .with_children(|builder| {
+            builder.spawn((
+                TextBundle {
+                    text: Text::from_section("", config.options_text_style.clone()),
+                    ..default()
+                }
+                .with_style(config.title_style.clone()),
+                RunModifiersText,
+            ));
+
+            // Buttons
+            builder
+                .spawn((NodeBundle {
+                    style: config.menu_buttons_area_style.clone(),
+                    background_color: config.panels_background.into(),
+                    ..default()
+                },))
+                .with_children(|builder| {
+                    spawn_button(builder, &config, RunModifiersMenuButton::OneHitDeath);
+                    spawn_button(builder, &config, RunModifiersMenuButton::NoHud);
+                    spawn_button(builder, &config, RunModifiersMenuButton::DoubleEnemies);
+                    spawn_button(builder, &config, RunModifiersMenuButton::LowGravity);
+                    spawn_button(builder, &config, RunModifiersMenuButton::TinyFridges);
+                    spawn_button(builder, &config, RunModifiersMenuButton::Back);
+                });
+        });
+}
+
+#[allow(clippy::complexity)]
+fn button_system(
+    config: Res<UiConfig>,
+    interaction_query: Query<
+        (&RunModifiersMenuButton, &Interaction, &Children),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut run_modifiers: ResMut<RunModifiers>,
+    mut texts: Query<&mut Text, With<ButtonText<RunModifiersMenuButton>>>,
+    mut ui_state: ResMut<NextState<UiState>>,
+) {
+    for (button, interaction, children) in interaction_query.iter() {
+        let text_entity = children[0];
+        let Ok(mut text) = texts.get_mut(text_entity) else {
+            continue;
+        };
+        match *interaction {
+            Interaction::Pressed => {
+                text.sections[0].style.color = config.button_text_color_pressed;
+                match button {
+                    RunModifiersMenuButton::OneHitDeath => {
+                        run_modifiers.toggle(RunModifier::OneHitDeath);
+                    }
+                    RunModifiersMenuButton::NoHud => {
+                        run_modifiers.toggle(RunModifier::NoHud);
+                    }
+                    RunModifiersMenuButton::DoubleEnemies => {
+                        run_modifiers.toggle(RunModifier::DoubleEnemies);
+                    }
+                    RunModifiersMenuButton::LowGravity => {
+                        run_modifiers.toggle(RunModifier::LowGravity);
+                    }
+                    RunModifiersMenuButton::TinyFridges => {
+                        run_modifiers.toggle(RunModifier::TinyFridges);
+                    }
+                    RunModifiersMenuButton::Back => {
+                        ui_state.set(UiState::MainMenu);
+                    }
+                }
+            }
+            Interaction::Hovered => {
+                text.sections[0].style.color = config.button_text_color_hover;
+            }
+            Interaction::None => {
+                text.sections[0].style.color = config.button_text_color_normal;
+            }
+        }
+    }
+}
+
+fn update_run_modifiers_text(
+    run_modifiers: Res<RunModifiers>,
+    mut run_modifiers_text: Query<&mut Text, With<RunModifiersText>>,
+) {
+    let mut text = run_modifiers_text.single_mut();
+    text.sections[0].value = run_modifiers_description(run_modifiers.as_ref());
+}
+
+pub fn run_modifiers_description(run_modifiers: &RunModifiers) -> String {
+    if run_modifiers.modifiers.is_empty() {
+        return "No modifiers".to_string();
+    }
+
+    let names = run_modifiers
+        .modifiers
+        .iter()
+        .map(|modifier| format!("{modifier:?}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("{} Score x{:.2}", names, run_modifiers.score_multiplier())
+}
@@ -2,23 +2,38 @@ use bevy::prelude::*;
 
 use crate::{
     damage::Health,
-    level::LevelInfo,
-    player::{Player, PlayerWeapon},
+    level::{terminal::LORE_ENTRIES, LevelInfo},
+    player::{Player, PlayerId, PlayerWeapon},
+    profile::Profile,
+    scrap::Scrap,
+    time_attack::Medal,
+    unlocks::{unlock_requirement, weapon_unlocked},
     utils::remove_all_with,
-    weapons::Ammo,
+    weapons::{ammo::AmmoTypes, Ammo, Weapon, WeaponRarity, WeaponRoll, WeaponType},
     UiState,
 };
 
 use super::UiConfig;
 
+const STATS_LOGS_NEXT_KEY: KeyCode = KeyCode::Tab;
+
 pub struct StatsPlugin;
 
 impl Plugin for StatsPlugin {
     fn build(&self, app: &mut App) {
+        app.init_resource::<StatsSelectedLog>();
+
         app.add_systems(OnEnter(UiState::Stats), setup_stats_menu);
         app.add_systems(
             Update,
-            (update_plyaer_hp, update_player_ammo, update_game_progress)
+            (
+                update_plyaer_hp,
+                update_player_ammo,
+                update_player_weapon,
+                update_game_progress,
+                update_scrap,
+                update_logs,
+            )
                 .run_if(in_state(UiState::Stats)),
         );
         app.add_systems(OnExit(UiState::Stats), remove_all_with::<StatsMenu>);
@@ -37,7 +52,20 @@ struct StatsPlayerHp;
 #[derive(Component)]
 struct StatsPlayerAmmo;
 
-fn setup_stats_menu(mut commands: Commands, config: Res<UiConfig>) {
+#[derive(Component)]
+struct StatsPlayerWeapon;
+
+#[derive(Component)]
+struct StatsScrap;
+
+#[derive(Component)]
+struct StatsLogs;
+
+// Index into the collected lore entries of the profile
+#[derive(Default, Resource)]
+struct StatsSelectedLog(usize);
+
+fn setup_stats_menu(mut commands: Commands, config: Res<UiConfig>, profile: Res<Profile>) {
     commands
         .spawn((
             NodeBundle {
@@ -74,6 +102,21 @@ fn setup_stats_menu(mut commands: Commands, config: Res<UiConfig>) {
                         StatsPlayerAmmo,
                     ));
 
+                    // Weapon
+                    builder.spawn((TextBundle {
+                        text: Text::from_section("WEAPON", config.stats_normal_text_style.clone()),
+                        ..default()
+                    }
+                    .with_style(config.title_style.clone()),));
+                    builder.spawn((
+                        TextBundle {
+                            text: Text::from_section("", config.stats_normal_text_style.clone()),
+                            ..default()
+                        }
+                        .with_style(config.title_style.clone()),
+                        StatsPlayerWeapon,
+                    ));
+
                     // HP
                     builder.spawn((TextBundle {
                         text: Text::from_section("HP", config.stats_normal_text_style.clone()),
@@ -117,26 +160,192 @@ fn setup_stats_menu(mut commands: Commands, config: Res<UiConfig>) {
                         .with_style(config.title_style.clone()),
                         StatsGameProgress,
                     ));
+
+                    // Scrap
+                    builder.spawn((TextBundle {
+                        text: Text::from_section("SCRAP", config.stats_normal_text_style.clone()),
+                        ..default()
+                    }
+                    .with_style(config.title_style.clone()),));
+                    builder.spawn((
+                        TextBundle {
+                            text: Text::from_section("", config.stats_normal_text_style.clone()),
+                            ..default()
+                        }
+                        .with_style(config.title_style.clone()),
+                        StatsScrap,
+                    ));
+                });
+
+            // Weapon collection
+            builder
+                .spawn((
+                    NodeBundle {
+                        style: config.stats_columns_style.clone(),
+                        background_color: config.panels_background.into(),
+                        ..default()
+                    },
+                    StatsMenu,
+                ))
+                .with_children(|builder| {
+                    builder.spawn((TextBundle {
+                        text: Text::from_section(
+                            "COLLECTION",
+                            config.stats_normal_text_style.clone(),
+                        ),
+                        ..default()
+                    }
+                    .with_style(config.title_style.clone()),));
+
+                    for weapon_type in WeaponType::ALL {
+                        builder.spawn((TextBundle {
+                            text: Text::from_section(
+                                collection_text(profile.as_ref(), weapon_type),
+                                config.stats_normal_text_style.clone(),
+                            ),
+                            ..default()
+                        }
+                        .with_style(config.title_style.clone()),));
+                    }
+                });
+
+            // Medals
+            builder
+                .spawn((
+                    NodeBundle {
+                        style: config.stats_columns_style.clone(),
+                        background_color: config.panels_background.into(),
+                        ..default()
+                    },
+                    StatsMenu,
+                ))
+                .with_children(|builder| {
+                    builder.spawn((TextBundle {
+                        text: Text::from_section("MEDALS", config.stats_normal_text_style.clone()),
+                        ..default()
+                    }
+                    .with_style(config.title_style.clone()),));
+
+                    for medal in [Medal::Gold, Medal::Silver, Medal::Bronze] {
+                        builder.spawn((TextBundle {
+                            text: Text::from_section(
+                                medals_text(profile.as_ref(), medal),
+                                TextStyle {
+                                    color: medal.color(),
+                                    ..config.stats_normal_text_style.clone()
+                                },
+                            ),
+                            ..default()
+                        }
+                        .with_style(config.title_style.clone()),));
+                    }
+                });
+
+            // Logs
+            builder
+                .spawn((
+                    NodeBundle {
+                        style: config.stats_columns_style.clone(),
+                        background_color: config.panels_background.into(),
+                        ..default()
+                    },
+                    StatsMenu,
+                ))
+                .with_children(|builder| {
+                    builder.spawn((TextBundle {
+                        text: Text::from_section("LOGS", config.stats_normal_text_style.clone()),
+                        ..default()
+                    }
+                    .with_style(config.title_style.clone()),));
+                    builder.spawn((
+                        TextBundle {
+                            text: Text::from_section("", config.stats_normal_text_style.clone()),
+                            ..default()
+                        }
+                        .with_style(config.title_style.clone()),
+                        StatsLogs,
+                    ));
                 });
         });
 }
 
+pub(super) fn collection_text(profile: &Profile, weapon_type: WeaponType) -> String {
+    if weapon_unlocked(profile, weapon_type) {
+        return format!(
+            "{}: {} kills",
+            weapon_type.name(),
+            profile.weapon_kills(weapon_type)
+        );
+    }
+    match unlock_requirement(weapon_type) {
+        Some(requirement) => format!(
+            "{}: LOCKED ({}/{} {} kills)",
+            weapon_type.name(),
+            profile.weapon_kills(requirement.weapon_type),
+            requirement.kills,
+            requirement.weapon_type.name(),
+        ),
+        None => format!("{}: LOCKED", weapon_type.name()),
+    }
+}
+
+// Medals of all levels and categories together
+fn medals_text(profile: &Profile, medal: Medal) -> String {
+    let count = profile
+        .level_medals
+        .iter()
+        .flat_map(|level_medals| level_medals.medals())
+        .filter(|m| *m == Some(medal))
+        .count();
+    format!("{}: {count}", medal.name())
+}
+
 fn update_player_ammo(
-    player_ammo: Query<&Ammo, With<PlayerWeapon>>,
+    player_ammo: Query<(&PlayerId, &Ammo, &AmmoTypes), With<PlayerWeapon>>,
     mut window_mode_text: Query<&mut Text, With<StatsPlayerAmmo>>,
 ) {
     let mut text = window_mode_text.single_mut();
-    match player_ammo.get_single() {
-        Ok(ammo) => text.sections[0].value = format!("{}", ammo.ammo),
-        Err(_) => text.sections[0].value = format!("---"),
+    match player_ammo.iter().find(|(id, _, _)| id.is_local()) {
+        Some((_, ammo, ammo_types)) => {
+            let ammo_type = ammo_types.selected();
+            text.sections[0].value = match ammo_types.rounds(ammo_type) {
+                0 => format!("{}\n{}", ammo.ammo, ammo_type.name()),
+                rounds => format!("{}\n{} ({})", ammo.ammo, ammo_type.name(), rounds),
+            };
+            text.sections[0].style.color = ammo_type.color();
+        }
+        None => text.sections[0].value = format!("---"),
+    }
+}
+
+fn update_player_weapon(
+    player_weapon: Query<(&PlayerId, &Weapon, Option<&WeaponRoll>), With<PlayerWeapon>>,
+    mut weapon_text: Query<&mut Text, With<StatsPlayerWeapon>>,
+) {
+    let mut text = weapon_text.single_mut();
+    match player_weapon.iter().find(|(id, _, _)| id.is_local()) {
+        Some((_, weapon, Some(roll))) => {
+            text.sections[0].value = format!(
+                "{} {}\n{}",
+                roll.rarity.name(),
+                weapon.weapon_type().name(),
+                roll.bonus_text()
+            );
+            text.sections[0].style.color = roll.rarity.color();
+        }
+        Some((_, weapon, None)) => {
+            text.sections[0].value = weapon.weapon_type().name().to_string();
+            text.sections[0].style.color = WeaponRarity::Common.color();
+        }
+        None => text.sections[0].value = "---".to_string(),
     }
 }
 
 fn update_plyaer_hp(
-    player_hp: Query<&Health, With<Player>>,
+    player_hp: Query<(&PlayerId, &Health), With<Player>>,
     mut volume_text: Query<&mut Text, With<StatsPlayerHp>>,
 ) {
-    let Ok(hp) = player_hp.get_single() else {
+    let Some((_, hp)) = player_hp.iter().find(|(id, _)| id.is_local()) else {
         return;
     };
     let mut text = volume_text.single_mut();
@@ -150,3 +359,46 @@ fn update_game_progress(
     let mut text = volume_text.single_mut();
     text.sections[0].value = format!("{}%", level_info.game_progress);
 }
+
+fn update_logs(
+    keys: Res<Input<KeyCode>>,
+    profile: Res<Profile>,
+    mut selected_log: ResMut<StatsSelectedLog>,
+    logs_added: Query<(), Added<StatsLogs>>,
+    mut logs_text: Query<&mut Text, With<StatsLogs>>,
+) {
+    if keys.just_pressed(STATS_LOGS_NEXT_KEY) {
+        selected_log.0 += 1;
+    }
+    if !(selected_log.is_changed() || profile.is_changed() || !logs_added.is_empty()) {
+        return;
+    }
+
+    let mut text = logs_text.single_mut();
+    // profile can be edited by hand, so unknown entries are skipped
+    let collected = profile
+        .lore_entries
+        .iter()
+        .filter_map(|entry| LORE_ENTRIES.get(*entry))
+        .collect::<Vec<_>>();
+    if collected.is_empty() {
+        text.sections[0].value = format!("0/{} found", LORE_ENTRIES.len());
+        return;
+    }
+    let selected = selected_log.0 % collected.len();
+    let entry = collected[selected];
+    text.sections[0].value = format!(
+        "{}/{} found\n{} ({}/{}, TAB - next)\n{}",
+        collected.len(),
+        LORE_ENTRIES.len(),
+        entry.title,
+        selected + 1,
+        collected.len(),
+        entry.text,
+    );
+}
+
+fn update_scrap(scrap: Res<Scrap>, mut scrap_text: Query<&mut Text, With<StatsScrap>>) {
+    let mut text = scrap_text.single_mut();
+    text.sections[0].value = format!("{}", scrap.amount);
+}
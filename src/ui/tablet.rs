@@ -0,0 +1,125 @@
+use bevy::{
+    prelude::*,
+    ui::{UiStack, UiSystem},
+    window::PrimaryWindow,
+};
+
+use super::UiResources;
+
+pub struct TabletPlugin;
+
+impl Plugin for TabletPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PreUpdate, tablet_focus.after(UiSystem::Focus));
+    }
+}
+
+/// Quad of the player tablet the UI texture is drawn on
+#[derive(Component)]
+pub struct TabletScreen;
+
+/// Camera that draws the player tablet
+#[derive(Component)]
+pub struct TabletCamera;
+
+// Cursor position in UI coordinates. The ray through the cursor
+// is cast onto the tablet screen and the hit is scaled from the
+// screen quad to the window the UI is laid out in.
+fn tablet_cursor(
+    window: &Window,
+    camera: &Camera,
+    camera_global_transform: &GlobalTransform,
+    screen_global_transform: &GlobalTransform,
+    screen_size: Vec2,
+) -> Option<Vec2> {
+    let cursor = window.cursor_position()?;
+    let ray = camera.viewport_to_world(camera_global_transform, cursor)?;
+    let distance = ray.intersect_plane(
+        screen_global_transform.translation(),
+        screen_global_transform.back(),
+    )?;
+    let hit = screen_global_transform
+        .affine()
+        .inverse()
+        .transform_point3(ray.get_point(distance));
+
+    let uv = Vec2::new(hit.x / screen_size.x + 0.5, 0.5 - hit.y / screen_size.y);
+    if uv.cmplt(Vec2::ZERO).any() || uv.cmpgt(Vec2::ONE).any() {
+        return None;
+    }
+    Some(uv * Vec2::new(window.width(), window.height()))
+}
+
+// Does the same as the bevy ui focus system, but with
+// the cursor position on the tablet instead of the window
+#[allow(clippy::complexity)]
+fn tablet_focus(
+    mouse_buttons: Res<Input<MouseButton>>,
+    ui_resources: Option<Res<UiResources>>,
+    ui_stack: Res<UiStack>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    camera: Query<(&Camera, &GlobalTransform), With<TabletCamera>>,
+    screen: Query<&GlobalTransform, With<TabletScreen>>,
+    mut nodes: Query<(
+        &Node,
+        &GlobalTransform,
+        &mut Interaction,
+        Option<&ViewVisibility>,
+    )>,
+) {
+    let Some(ui_resources) = ui_resources else {
+        return;
+    };
+    let Ok(window) = window.get_single() else {
+        return;
+    };
+    let Ok((camera, camera_global_transform)) = camera.get_single() else {
+        return;
+    };
+    let Ok(screen_global_transform) = screen.get_single() else {
+        return;
+    };
+
+    if mouse_buttons.just_released(MouseButton::Left) {
+        for (_, _, mut interaction, _) in nodes.iter_mut() {
+            if *interaction == Interaction::Pressed {
+                *interaction = Interaction::None;
+            }
+        }
+    }
+
+    let cursor = tablet_cursor(
+        window,
+        camera,
+        camera_global_transform,
+        screen_global_transform,
+        ui_resources.size,
+    );
+    // only the top node under the cursor is hovered
+    let hovered = cursor.and_then(|cursor| {
+        ui_stack.uinodes.iter().rev().copied().find(|entity| {
+            nodes
+                .get(*entity)
+                .is_ok_and(|(node, global_transform, _, view_visibility)| {
+                    view_visibility.map_or(true, |visibility| visibility.get())
+                        && node.logical_rect(global_transform).contains(cursor)
+                })
+        })
+    });
+
+    let mouse_clicked = mouse_buttons.just_pressed(MouseButton::Left);
+    for entity in ui_stack.uinodes.iter() {
+        let Ok((_, _, mut interaction, _)) = nodes.get_mut(*entity) else {
+            continue;
+        };
+        if Some(*entity) == hovered {
+            if mouse_clicked {
+                *interaction = Interaction::Pressed;
+            } else if *interaction == Interaction::None {
+                *interaction = Interaction::Hovered;
+            }
+        } else if *interaction == Interaction::Hovered {
+            *interaction = Interaction::None;
+        }
+    }
+}
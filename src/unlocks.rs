@@ -0,0 +1,90 @@
+use bevy::prelude::*;
+
+use crate::{
+    damage::KillEvent,
+    enemies::Enemy,
+    player::{PlayerId, PlayerWeapon},
+    profile::Profile,
+    weapons::{Weapon, WeaponType},
+    GlobalState,
+};
+
+// Weapons that are not unlocked yet
+// do not appear in the levels
+const UNLOCK_SHOTGUN_PISTOL_KILLS: u32 = 25;
+const UNLOCK_MINIGUN_SHOTGUN_KILLS: u32 = 100;
+
+pub struct UnlocksPlugin;
+
+impl Plugin for UnlocksPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<WeaponUnlocked>();
+
+        app.add_systems(
+            Update,
+            unlocks_track_kills.run_if(in_state(GlobalState::InGame)),
+        );
+    }
+}
+
+#[derive(Event)]
+pub struct WeaponUnlocked {
+    pub weapon_type: WeaponType,
+}
+
+// Number of kills with `weapon_type` needed to unlock a weapon
+#[derive(Debug, Clone, Copy)]
+pub struct UnlockRequirement {
+    pub weapon_type: WeaponType,
+    pub kills: u32,
+}
+
+pub fn unlock_requirement(weapon_type: WeaponType) -> Option<UnlockRequirement> {
+    match weapon_type {
+        WeaponType::Pistol => None,
+        WeaponType::Shotgun => Some(UnlockRequirement {
+            weapon_type: WeaponType::Pistol,
+            kills: UNLOCK_SHOTGUN_PISTOL_KILLS,
+        }),
+        WeaponType::Minigun => Some(UnlockRequirement {
+            weapon_type: WeaponType::Shotgun,
+            kills: UNLOCK_MINIGUN_SHOTGUN_KILLS,
+        }),
+    }
+}
+
+pub fn weapon_unlocked(profile: &Profile, weapon_type: WeaponType) -> bool {
+    match unlock_requirement(weapon_type) {
+        Some(requirement) => requirement.kills <= profile.weapon_kills(requirement.weapon_type),
+        None => true,
+    }
+}
+
+// Kills are counted for the weapon the local player
+// holds at the moment enemy dies, profile belongs
+// to the player on this machine
+fn unlocks_track_kills(
+    enemies: Query<(), With<Enemy>>,
+    player_weapons: Query<(&PlayerId, &Weapon), With<PlayerWeapon>>,
+    mut profile: ResMut<Profile>,
+    mut kill_events: EventReader<KillEvent>,
+    mut weapon_unlocked_events: EventWriter<WeaponUnlocked>,
+) {
+    for kill_event in kill_events.read() {
+        if !enemies.contains(kill_event.entity) {
+            continue;
+        }
+        let Some((_, weapon)) = player_weapons.iter().find(|(id, _)| id.is_local()) else {
+            continue;
+        };
+
+        let locked = WeaponType::ALL.map(|weapon_type| !weapon_unlocked(&profile, weapon_type));
+        *profile.weapon_kills_mut(weapon.weapon_type()) += 1;
+
+        for (weapon_type, locked) in WeaponType::ALL.into_iter().zip(locked) {
+            if locked && weapon_unlocked(&profile, weapon_type) {
+                weapon_unlocked_events.send(WeaponUnlocked { weapon_type });
+            }
+        }
+    }
+}
@@ -0,0 +1,613 @@
+use bevy::{pbr::NotShadowCaster, prelude::*};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    damage::{DamageEvent, Health, KillCause, KillEvent},
+    enemies::{DisabledEnemy, Enemy, EnemyStun, EnemyType},
+    level::LevelObject,
+    player::{Player, PlayerId, PlayerWeapon},
+    rng::{GameRng, RngStream},
+    GlobalState,
+};
+
+const AMMO_CYCLE_KEY: KeyCode = KeyCode::R;
+
+const AMMO_PICKUP_SIZE: f32 = 0.6;
+const AMMO_PICKUP_SMALL_ENEMY_DROP_CHANCE: f64 = 0.03;
+const AMMO_PICKUP_MID_ENEMY_DROP_CHANCE: f64 = 0.08;
+const AMMO_PICKUP_BIG_ENEMY_DROP_CHANCE: f64 = 0.2;
+const AMMO_PICKUP_FLOAT_HIGHT: f32 = 2.0;
+const AMMO_PICKUP_FLOAT_AMPLITUDE: f32 = 0.3;
+const AMMO_PICKUP_FLOAT_SPEED: f32 = 3.0;
+const AMMO_PICKUP_ROTATION_SPEED: f32 = 2.0;
+const AMMO_PICKUP_COLLECT_RADIUS: f32 = 2.5;
+
+// Incendiary
+// Weaker hit, but sets the enemy on fire
+const INCENDIARY_DAMAGE_MULTIPLIER: f32 = 0.8;
+const INCENDIARY_PICKUP_ROUNDS: u32 = 20;
+const INCENDIARY_BURN_DAMAGE: i32 = 2;
+const INCENDIARY_BURN_INTERVAL: f32 = 0.5;
+const INCENDIARY_BURN_DURATION: f32 = 3.0;
+// Burning enemy sets on fire enemies around it after
+// a delay, fire from a direct hit has depth 0
+const INCENDIARY_SPREAD_RADIUS: f32 = 6.0;
+const INCENDIARY_SPREAD_DELAY: f32 = 1.0;
+const INCENDIARY_SPREAD_MAX_DEPTH: u32 = 2;
+
+const IGNITION_FLASH_SIZE: f32 = 1.0;
+const IGNITION_FLASH_SECONDS: f32 = 0.4;
+const IGNITION_FLASH_MAX_SCALE: f32 = 4.0;
+const IGNITION_FLASH_HIGHT: f32 = 2.0;
+
+// Shock
+// Weaker hit, but fills the stun meter
+const SHOCK_DAMAGE_MULTIPLIER: f32 = 0.7;
+const SHOCK_PICKUP_ROUNDS: u32 = 20;
+const SHOCK_STUN: f32 = 0.2;
+
+pub struct AmmoPlugin;
+
+impl Plugin for AmmoPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AmmoRegistry>();
+
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::AssetLoading,
+                to: GlobalState::MainMenu,
+            },
+            init_resources,
+        );
+
+        app.add_systems(
+            Update,
+            (
+                ammo_cycle,
+                ammo_drop,
+                ammo_pickup_float,
+                ammo_pickup_collect,
+                ammo_apply_status,
+                ammo_burn,
+                ammo_burn_spread,
+                ammo_ignition_flash,
+            )
+                .run_if(in_state(GlobalState::InGame)),
+        );
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AmmoType {
+    #[default]
+    Standard,
+    Incendiary,
+    Shock,
+}
+
+impl AmmoType {
+    pub const ALL: [AmmoType; 3] = [AmmoType::Standard, AmmoType::Incendiary, AmmoType::Shock];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            AmmoType::Standard => "STANDARD",
+            AmmoType::Incendiary => "INCENDIARY",
+            AmmoType::Shock => "SHOCK",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            AmmoType::Standard => Color::WHITE,
+            AmmoType::Incendiary => Color::ORANGE,
+            AmmoType::Shock => Color::rgb(0.4, 0.8, 1.0),
+        }
+    }
+}
+
+// Tunable part of the ammo type
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AmmoStats {
+    // Multiplies the damage of the weapon
+    pub damage_multiplier: f32,
+    // Rounds given by one pickup
+    pub pickup_rounds: u32,
+    // Hit enemy takes `burn_damage` every `burn_interval`
+    // seconds for `burn_duration` seconds
+    pub burn_damage: i32,
+    pub burn_interval: f32,
+    pub burn_duration: f32,
+    // Fire jumps to enemies within `spread_radius` after
+    // `spread_delay` seconds, at most `spread_max_depth` times
+    pub spread_radius: f32,
+    pub spread_delay: f32,
+    pub spread_max_depth: u32,
+    // Added to the stun meter of the hit enemy,
+    // full meter staggers it
+    pub stun: f32,
+}
+
+impl AmmoStats {
+    pub fn default_for(ammo_type: AmmoType) -> Self {
+        match ammo_type {
+            AmmoType::Standard => Self {
+                damage_multiplier: 1.0,
+                ..default()
+            },
+            AmmoType::Incendiary => Self {
+                damage_multiplier: INCENDIARY_DAMAGE_MULTIPLIER,
+                pickup_rounds: INCENDIARY_PICKUP_ROUNDS,
+                burn_damage: INCENDIARY_BURN_DAMAGE,
+                burn_interval: INCENDIARY_BURN_INTERVAL,
+                burn_duration: INCENDIARY_BURN_DURATION,
+                spread_radius: INCENDIARY_SPREAD_RADIUS,
+                spread_delay: INCENDIARY_SPREAD_DELAY,
+                spread_max_depth: INCENDIARY_SPREAD_MAX_DEPTH,
+                ..default()
+            },
+            AmmoType::Shock => Self {
+                damage_multiplier: SHOCK_DAMAGE_MULTIPLIER,
+                pickup_rounds: SHOCK_PICKUP_ROUNDS,
+                stun: SHOCK_STUN,
+                ..default()
+            },
+        }
+    }
+}
+
+/// Stats of all ammo types
+#[derive(Debug, Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AmmoRegistry {
+    standard: AmmoStats,
+    incendiary: AmmoStats,
+    shock: AmmoStats,
+}
+
+impl Default for AmmoRegistry {
+    fn default() -> Self {
+        Self {
+            standard: AmmoStats::default_for(AmmoType::Standard),
+            incendiary: AmmoStats::default_for(AmmoType::Incendiary),
+            shock: AmmoStats::default_for(AmmoType::Shock),
+        }
+    }
+}
+
+impl AmmoRegistry {
+    pub fn stats(&self, ammo_type: AmmoType) -> &AmmoStats {
+        match ammo_type {
+            AmmoType::Standard => &self.standard,
+            AmmoType::Incendiary => &self.incendiary,
+            AmmoType::Shock => &self.shock,
+        }
+    }
+}
+
+/// Special rounds loaded into the weapon. Each shot
+/// uses one round of the selected type on top of the
+/// weapon ammo. Standard ammo has no rounds of its own.
+#[derive(Debug, Default, Clone, Copy, Component)]
+pub struct AmmoTypes {
+    selected: AmmoType,
+    incendiary: u32,
+    shock: u32,
+}
+
+impl AmmoTypes {
+    pub fn selected(&self) -> AmmoType {
+        self.selected
+    }
+
+    pub fn rounds(&self, ammo_type: AmmoType) -> u32 {
+        match ammo_type {
+            AmmoType::Standard => 0,
+            AmmoType::Incendiary => self.incendiary,
+            AmmoType::Shock => self.shock,
+        }
+    }
+
+    fn rounds_mut(&mut self, ammo_type: AmmoType) -> Option<&mut u32> {
+        match ammo_type {
+            AmmoType::Standard => None,
+            AmmoType::Incendiary => Some(&mut self.incendiary),
+            AmmoType::Shock => Some(&mut self.shock),
+        }
+    }
+
+    // New rounds are selected if the
+    // weapon was using standard ammo
+    pub fn add(&mut self, ammo_type: AmmoType, rounds: u32) {
+        let Some(r) = self.rounds_mut(ammo_type) else {
+            return;
+        };
+        *r += rounds;
+        if self.selected == AmmoType::Standard {
+            self.selected = ammo_type;
+        }
+    }
+
+    // Selects the next type with rounds left,
+    // standard ammo is always available
+    fn cycle(&mut self) {
+        let current = AmmoType::ALL
+            .iter()
+            .position(|ammo_type| *ammo_type == self.selected)
+            .unwrap_or(0);
+        for i in 1..=AmmoType::ALL.len() {
+            let ammo_type = AmmoType::ALL[(current + i) % AmmoType::ALL.len()];
+            if ammo_type == AmmoType::Standard || self.rounds(ammo_type) != 0 {
+                self.selected = ammo_type;
+                return;
+            }
+        }
+    }
+
+    /// Uses one round for a shot. Switches back to standard
+    /// ammo after the last round of the selected type.
+    pub fn take(&mut self) -> AmmoType {
+        let ammo_type = self.selected;
+        let Some(rounds) = self.rounds_mut(ammo_type) else {
+            return ammo_type;
+        };
+        if *rounds == 0 {
+            self.selected = AmmoType::Standard;
+            return AmmoType::Standard;
+        }
+        *rounds -= 1;
+        if *rounds == 0 {
+            self.selected = AmmoType::Standard;
+        }
+        ammo_type
+    }
+}
+
+// Enemy on fire. Damage ignores the shield
+// and does not count as a hit.
+#[derive(Component)]
+struct Burning {
+    ammo_type: AmmoType,
+    damage: i32,
+    interval_timer: Timer,
+    duration_timer: Timer,
+    // Number of jumps from the directly hit enemy
+    depth: u32,
+    spread_timer: Timer,
+}
+
+impl Burning {
+    fn new(ammo_type: AmmoType, stats: &AmmoStats, depth: u32) -> Self {
+        Self {
+            ammo_type,
+            damage: stats.burn_damage,
+            interval_timer: Timer::from_seconds(stats.burn_interval, TimerMode::Repeating),
+            duration_timer: Timer::from_seconds(stats.burn_duration, TimerMode::Once),
+            depth,
+            spread_timer: Timer::from_seconds(stats.spread_delay, TimerMode::Once),
+        }
+    }
+}
+
+// Short burst of fire over the newly ignited enemy
+#[derive(Component)]
+struct IgnitionFlash {
+    timer: Timer,
+}
+
+#[derive(Resource)]
+struct AmmoResources {
+    mesh: Handle<Mesh>,
+    incendiary_material: Handle<StandardMaterial>,
+    shock_material: Handle<StandardMaterial>,
+    ignition_mesh: Handle<Mesh>,
+    ignition_material: Handle<StandardMaterial>,
+}
+
+#[derive(Component)]
+pub struct AmmoPickup {
+    pub ammo_type: AmmoType,
+    pub original_translation: Vec3,
+}
+
+fn init_resources(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mesh = meshes.add(
+        shape::Capsule {
+            radius: AMMO_PICKUP_SIZE / 4.0,
+            depth: AMMO_PICKUP_SIZE / 2.0,
+            ..default()
+        }
+        .into(),
+    );
+
+    let mut material = |ammo_type: AmmoType| {
+        materials.add(StandardMaterial {
+            base_color: ammo_type.color(),
+            emissive: ammo_type.color(),
+            unlit: true,
+            ..default()
+        })
+    };
+    let incendiary_material = material(AmmoType::Incendiary);
+    let shock_material = material(AmmoType::Shock);
+
+    let ignition_mesh = meshes.add(
+        shape::UVSphere {
+            radius: IGNITION_FLASH_SIZE / 2.0,
+            ..default()
+        }
+        .into(),
+    );
+    let ignition_material = materials.add(StandardMaterial {
+        base_color: AmmoType::Incendiary.color().with_a(0.8),
+        emissive: AmmoType::Incendiary.color(),
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        ..default()
+    });
+
+    commands.insert_resource(AmmoResources {
+        mesh,
+        incendiary_material,
+        shock_material,
+        ignition_mesh,
+        ignition_material,
+    });
+}
+
+fn spawn_ammo_pickup(
+    ammo_resources: &AmmoResources,
+    commands: &mut Commands,
+    ammo_type: AmmoType,
+    mut translation: Vec3,
+) {
+    let material = match ammo_type {
+        AmmoType::Standard => return,
+        AmmoType::Incendiary => ammo_resources.incendiary_material.clone(),
+        AmmoType::Shock => ammo_resources.shock_material.clone(),
+    };
+    translation.z = AMMO_PICKUP_FLOAT_HIGHT;
+    commands.spawn((
+        PbrBundle {
+            mesh: ammo_resources.mesh.clone(),
+            material,
+            transform: Transform::from_translation(translation),
+            ..default()
+        },
+        NotShadowCaster,
+        AmmoPickup {
+            ammo_type,
+            original_translation: translation,
+        },
+        LevelObject,
+    ));
+}
+
+fn ammo_cycle(
+    keys: Res<Input<KeyCode>>,
+    mut player_weapons: Query<&mut AmmoTypes, With<PlayerWeapon>>,
+) {
+    if !keys.just_pressed(AMMO_CYCLE_KEY) {
+        return;
+    }
+    for mut ammo_types in player_weapons.iter_mut() {
+        ammo_types.cycle();
+    }
+}
+
+fn ammo_drop(
+    ammo_resources: Res<AmmoResources>,
+    enemies: Query<(&Transform, &Enemy)>,
+    mut game_rng: ResMut<GameRng>,
+    mut commands: Commands,
+    mut kill_events: EventReader<KillEvent>,
+) {
+    let rng = game_rng.stream(RngStream::Drops);
+    for kill_event in kill_events.read() {
+        let Ok((enemy_transform, enemy)) = enemies.get(kill_event.entity) else {
+            continue;
+        };
+
+        let chance = match enemy.enemy_type {
+            EnemyType::Small => AMMO_PICKUP_SMALL_ENEMY_DROP_CHANCE,
+            EnemyType::Mid => AMMO_PICKUP_MID_ENEMY_DROP_CHANCE,
+            EnemyType::Big => AMMO_PICKUP_BIG_ENEMY_DROP_CHANCE,
+        };
+        if !rng.gen_bool(chance) {
+            continue;
+        }
+
+        // standard ammo is never dropped
+        let ammo_type = AmmoType::ALL[rng.gen_range(1..AmmoType::ALL.len())];
+        spawn_ammo_pickup(
+            ammo_resources.as_ref(),
+            &mut commands,
+            ammo_type,
+            enemy_transform.translation,
+        );
+    }
+}
+
+fn ammo_pickup_float(time: Res<Time>, mut pickups: Query<(&AmmoPickup, &mut Transform)>) {
+    for (pickup, mut pickup_transform) in pickups.iter_mut() {
+        let t = time.elapsed_seconds() * AMMO_PICKUP_FLOAT_SPEED;
+        pickup_transform.translation =
+            pickup.original_translation + Vec3::Z * AMMO_PICKUP_FLOAT_AMPLITUDE * t.sin();
+        pickup_transform.rotate_z(time.delta_seconds() * AMMO_PICKUP_ROTATION_SPEED);
+    }
+}
+
+// Pickups are only collected by a player with
+// a weapon and go to the weapon in hands
+fn ammo_pickup_collect(
+    ammo_registry: Res<AmmoRegistry>,
+    players: Query<(&PlayerId, &Transform), With<Player>>,
+    pickups: Query<(Entity, &Transform, &AmmoPickup), Without<Player>>,
+    mut player_weapons: Query<(&PlayerId, &mut AmmoTypes), With<PlayerWeapon>>,
+    mut commands: Commands,
+) {
+    // pickup is gone only after the commands are
+    // applied, so it is not collected twice
+    let mut collected = vec![];
+    for (player_id, player_transform) in players.iter() {
+        let Some((_, mut ammo_types)) = player_weapons.iter_mut().find(|(id, _)| *id == player_id)
+        else {
+            continue;
+        };
+
+        for (pickup_entity, pickup_transform, pickup) in pickups.iter() {
+            if collected.contains(&pickup_entity) {
+                continue;
+            }
+            let distance_squared = player_transform
+                .translation
+                .truncate()
+                .distance_squared(pickup_transform.translation.truncate());
+            if AMMO_PICKUP_COLLECT_RADIUS * AMMO_PICKUP_COLLECT_RADIUS < distance_squared {
+                continue;
+            }
+
+            let rounds = ammo_registry.stats(pickup.ammo_type).pickup_rounds;
+            ammo_types.add(pickup.ammo_type, rounds);
+            collected.push(pickup_entity);
+
+            let Some(e) = commands.get_entity(pickup_entity) else {
+                continue;
+            };
+            e.despawn_recursive();
+        }
+    }
+}
+
+fn ammo_apply_status(
+    ammo_registry: Res<AmmoRegistry>,
+    mut enemies: Query<(Option<&mut Burning>, &mut EnemyStun), With<Enemy>>,
+    mut commands: Commands,
+    mut damage_events: EventReader<DamageEvent>,
+) {
+    for damage_event in damage_events.read() {
+        let stats = ammo_registry.stats(damage_event.ammo_type);
+        let Ok((burning, mut stun)) = enemies.get_mut(damage_event.entity) else {
+            continue;
+        };
+
+        if 0.0 < stats.stun {
+            stun.add(stats.stun);
+        }
+
+        if stats.burn_damage <= 0 || stats.burn_interval <= 0.0 || stats.burn_duration <= 0.0 {
+            continue;
+        }
+        // new hit only restarts the fire
+        match burning {
+            Some(mut burning) => burning.duration_timer.reset(),
+            None => {
+                commands.entity(damage_event.entity).insert(Burning::new(
+                    damage_event.ammo_type,
+                    stats,
+                    0,
+                ));
+            }
+        }
+    }
+}
+
+fn ammo_burn(
+    time: Res<Time>,
+    mut enemies: Query<(Entity, &mut Burning, &mut Health)>,
+    mut commands: Commands,
+    mut kill_events: EventWriter<KillEvent>,
+) {
+    for (entity, mut burning, mut health) in enemies.iter_mut() {
+        burning.duration_timer.tick(time.delta());
+        burning.interval_timer.tick(time.delta());
+
+        let ticks = burning.interval_timer.times_finished_this_tick() as i32;
+        health.health -= burning.damage * ticks;
+        if health.health <= 0 {
+            commands.entity(entity).remove::<(Health, Burning)>();
+            kill_events.send(KillEvent {
+                entity,
+                cause: KillCause::Environment,
+                shot: None,
+            });
+            continue;
+        }
+
+        if burning.duration_timer.finished() {
+            commands.entity(entity).remove::<Burning>();
+        }
+    }
+}
+
+fn ammo_burn_spread(
+    time: Res<Time>,
+    ammo_registry: Res<AmmoRegistry>,
+    ammo_resources: Res<AmmoResources>,
+    mut fires: Query<(&Transform, &mut Burning)>,
+    enemies: Query<(Entity, &Transform), (With<Enemy>, Without<Burning>, Without<DisabledEnemy>)>,
+    mut commands: Commands,
+) {
+    // enemy can be in range of several fires
+    let mut ignited = vec![];
+    for (transform, mut burning) in fires.iter_mut() {
+        burning.spread_timer.tick(time.delta());
+        if !burning.spread_timer.just_finished() {
+            continue;
+        }
+
+        let stats = ammo_registry.stats(burning.ammo_type);
+        if stats.spread_max_depth <= burning.depth {
+            continue;
+        }
+        for (enemy, enemy_transform) in enemies.iter() {
+            if ignited.contains(&enemy)
+                || stats.spread_radius < enemy_transform.translation.distance(transform.translation)
+            {
+                continue;
+            }
+            ignited.push(enemy);
+
+            commands.entity(enemy).insert(Burning::new(
+                burning.ammo_type,
+                stats,
+                burning.depth + 1,
+            ));
+            commands.spawn((
+                PbrBundle {
+                    mesh: ammo_resources.ignition_mesh.clone(),
+                    material: ammo_resources.ignition_material.clone(),
+                    transform: Transform::from_translation(
+                        enemy_transform.translation + Vec3::Z * IGNITION_FLASH_HIGHT,
+                    ),
+                    ..default()
+                },
+                NotShadowCaster,
+                IgnitionFlash {
+                    timer: Timer::from_seconds(IGNITION_FLASH_SECONDS, TimerMode::Once),
+                },
+                LevelObject,
+            ));
+        }
+    }
+}
+
+fn ammo_ignition_flash(
+    time: Res<Time>,
+    mut flashes: Query<(Entity, &mut IgnitionFlash, &mut Transform)>,
+    mut commands: Commands,
+) {
+    for (entity, mut flash, mut transform) in flashes.iter_mut() {
+        flash.timer.tick(time.delta());
+        if flash.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let progress = flash.timer.percent();
+        transform.scale = Vec3::splat(1.0 + (IGNITION_FLASH_MAX_SCALE - 1.0) * progress);
+    }
+}
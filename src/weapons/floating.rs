@@ -1,32 +1,107 @@
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
+use rand::Rng;
 
-use crate::{level::LevelObject, GlobalState, COLLISION_GROUP_PICKUP, COLLISION_GROUP_PLAYER};
+use crate::{
+    collision::CollisionLayer,
+    level::LevelObject,
+    player::{Player, PlayerCamera, PlayerId, PlayerWeapon},
+    rng::{GameRng, RngStream},
+    GlobalState,
+};
+
+use super::{Weapon, WeaponRoll, WeaponType};
 
 const COLLIDER_RADIUS: f32 = 1.5;
 const ROTATION_SPEED: f32 = 0.4;
 const AMPLITUDE_MODIFIER: f32 = 0.5;
 const BOUNCE_SPEED_MODIFIER: f32 = 2.0;
 
+// Objects spin faster while the player is close
+const ATTRACT_SPIN_RADIUS: f32 = 10.0;
+const ATTRACT_SPIN_MODIFIER: f32 = 4.0;
+const ATTRACT_SPIN_CHANGE_SPEED: f32 = 3.0;
+
+// Object the player looks at is lit by a pulsing light
+const HIGHLIGHT_RANGE: f32 = 25.0;
+// Cosine of the angle between the view and the object
+const HIGHLIGHT_MIN_DOT: f32 = 0.97;
+const HIGHLIGHT_INTENSITY: f32 = 800.0;
+const HIGHLIGHT_LIGHT_RANGE: f32 = 5.0;
+const HIGHLIGHT_PULSE_SPEED: f32 = 6.0;
+
+// Objects close to the player slowly move to them
+const MAGNET_RADIUS: f32 = 6.0;
+const MAGNET_SPEED: f32 = 4.0;
+
 pub struct FloatingPlugin;
 
 impl Plugin for FloatingPlugin {
     fn build(&self, app: &mut App) {
+        app.init_resource::<WeaponPrompt>();
+
         app.add_systems(
             Update,
-            update_floating_objects.run_if(in_state(GlobalState::InGame)),
+            (
+                randomize_floating_objects,
+                update_floating_objects,
+                highlight_floating_objects,
+                magnet_floating_objects,
+            )
+                .run_if(in_state(GlobalState::InGame)),
         );
     }
 }
 
+/// How a floating object moves while it waits
+/// to be picked up
+#[derive(Debug, Clone, Copy)]
+pub struct FloatProfile {
+    pub rotation_axis: Vec3,
+    pub rotation_speed: f32,
+    pub amplitude: f32,
+    pub frequency: f32,
+    // A random value is added on spawn, so objects
+    // placed together do not bob in lockstep
+    pub phase: f32,
+}
+
+impl Default for FloatProfile {
+    fn default() -> Self {
+        Self {
+            rotation_axis: Vec3::Z,
+            rotation_speed: ROTATION_SPEED,
+            amplitude: AMPLITUDE_MODIFIER,
+            frequency: BOUNCE_SPEED_MODIFIER,
+            phase: 0.0,
+        }
+    }
+}
+
 #[derive(Component)]
 pub struct FloatingObject {
     pub original_translation: Vec3,
+    pub profile: FloatProfile,
+    spin_modifier: f32,
 }
 
 #[derive(Component)]
 pub struct FloatingObjectInternal;
 
+#[derive(Component)]
+struct FloatingObjectHighlight;
+
+// What the player sees when looking at a floating weapon
+#[derive(Debug, Default, Clone, Copy, PartialEq, Resource)]
+pub enum WeaponPrompt {
+    #[default]
+    None,
+    Weapon {
+        weapon_type: WeaponType,
+        roll: WeaponRoll,
+    },
+}
+
 #[derive(Bundle)]
 pub struct FloatingObjectBundle {
     pub transform_bundle: TransformBundle,
@@ -50,24 +125,164 @@ impl FloatingObjectBundle {
             inherited_visibility: InheritedVisibility::VISIBLE,
 
             collider: Collider::ball(COLLIDER_RADIUS),
-            collision_groups: CollisionGroups::new(COLLISION_GROUP_PICKUP, COLLISION_GROUP_PLAYER),
+            collision_groups: CollisionLayer::Pickup.groups(),
             sensor: Sensor,
             active_events: ActiveEvents::COLLISION_EVENTS,
             floating_object: FloatingObject {
                 original_translation,
+                profile: FloatProfile::default(),
+                spin_modifier: 1.0,
             },
 
             level_object: LevelObject,
         }
     }
+
+    pub fn with_profile(mut self, profile: FloatProfile) -> Self {
+        self.floating_object.profile = profile;
+        self
+    }
 }
 
-fn update_floating_objects(time: Res<Time>, mut weapons: Query<(&FloatingObject, &mut Transform)>) {
-    for (floating, mut weapon_transform) in weapons.iter_mut() {
-        weapon_transform.translation = floating.original_translation
+fn randomize_floating_objects(
+    mut game_rng: ResMut<GameRng>,
+    mut floating_objects: Query<&mut FloatingObject, Added<FloatingObject>>,
+) {
+    for mut floating in floating_objects.iter_mut() {
+        floating.profile.phase += game_rng
+            .stream(RngStream::Effects)
+            .gen_range(0.0..std::f32::consts::TAU);
+    }
+}
+
+fn update_floating_objects(
+    time: Res<Time>,
+    players: Query<&Transform, With<Player>>,
+    mut floating_objects: Query<(&mut FloatingObject, &mut Transform), Without<Player>>,
+) {
+    for (mut floating, mut transform) in floating_objects.iter_mut() {
+        let profile = floating.profile;
+        transform.translation = floating.original_translation
             + Vec3::NEG_Z
-                * AMPLITUDE_MODIFIER
-                * (time.elapsed().as_secs_f32() * BOUNCE_SPEED_MODIFIER).sin();
-        weapon_transform.rotate_z(time.delta_seconds() * ROTATION_SPEED);
+                * profile.amplitude
+                * (time.elapsed_seconds() * profile.frequency + profile.phase).sin();
+
+        let near_player = players.iter().any(|player_transform| {
+            player_transform
+                .translation
+                .distance(floating.original_translation)
+                < ATTRACT_SPIN_RADIUS
+        });
+        let target_spin = if near_player {
+            ATTRACT_SPIN_MODIFIER
+        } else {
+            1.0
+        };
+        let spin_step = ATTRACT_SPIN_CHANGE_SPEED * time.delta_seconds();
+        floating.spin_modifier +=
+            (target_spin - floating.spin_modifier).clamp(-spin_step, spin_step);
+
+        transform.rotate_axis(
+            profile.rotation_axis,
+            time.delta_seconds() * profile.rotation_speed * floating.spin_modifier,
+        );
+    }
+}
+
+#[allow(clippy::complexity)]
+fn highlight_floating_objects(
+    time: Res<Time>,
+    player_cameras: Query<(&PlayerId, &GlobalTransform), With<PlayerCamera>>,
+    floating_objects: Query<(Entity, &GlobalTransform, &Children), With<FloatingObject>>,
+    weapons: Query<(&Weapon, Option<&WeaponRoll>)>,
+    mut highlights: Query<(Entity, &Parent, &mut PointLight), With<FloatingObjectHighlight>>,
+    mut weapon_prompt: ResMut<WeaponPrompt>,
+    mut commands: Commands,
+) {
+    // weapon prompt is shown on the screen of the local player
+    let Some((_, camera_global_transform)) = player_cameras.iter().find(|(id, _)| id.is_local())
+    else {
+        return;
+    };
+
+    let forward = camera_global_transform.forward();
+    let target = floating_objects
+        .iter()
+        .filter_map(|(entity, global_transform, children)| {
+            let v = global_transform.translation() - camera_global_transform.translation();
+            let distance = v.length();
+            (distance < HIGHLIGHT_RANGE && HIGHLIGHT_MIN_DOT < forward.dot(v / distance))
+                .then_some((entity, children, distance))
+        })
+        .min_by(|(_, _, a), (_, _, b)| a.total_cmp(b))
+        .map(|(entity, children, _)| (entity, children));
+
+    // weapon is the first child of the floating object
+    let target_weapon = target
+        .and_then(|(_, children)| weapons.get(children[0]).ok())
+        .map(|(weapon, roll)| (weapon.weapon_type(), roll.copied().unwrap_or_default()));
+    weapon_prompt.set_if_neq(match target_weapon {
+        Some((weapon_type, roll)) => WeaponPrompt::Weapon { weapon_type, roll },
+        None => WeaponPrompt::None,
+    });
+    let color = target_weapon
+        .map(|(_, roll)| roll.rarity.color())
+        .unwrap_or(Color::WHITE);
+    let target = target.map(|(entity, _)| entity);
+
+    let mut highlighted = false;
+    for (highlight, parent, mut light) in highlights.iter_mut() {
+        // picked up objects leave their highlight behind
+        if Some(parent.get()) != target {
+            commands.entity(highlight).despawn_recursive();
+            continue;
+        }
+        highlighted = true;
+        light.color = color;
+        light.intensity = HIGHLIGHT_INTENSITY
+            * (0.5 + 0.5 * (time.elapsed_seconds() * HIGHLIGHT_PULSE_SPEED).sin());
+    }
+
+    let Some(target) = target.filter(|_| !highlighted) else {
+        return;
+    };
+    commands.entity(target).with_children(|builder| {
+        builder.spawn((
+            PointLightBundle {
+                point_light: PointLight {
+                    color,
+                    intensity: 0.0,
+                    range: HIGHLIGHT_LIGHT_RANGE,
+                    ..default()
+                },
+                ..default()
+            },
+            FloatingObjectHighlight,
+            LevelObject,
+        ));
+    });
+}
+
+fn magnet_floating_objects(
+    time: Res<Time>,
+    players: Query<(&PlayerId, &Transform), With<Player>>,
+    player_weapons: Query<&PlayerId, With<PlayerWeapon>>,
+    mut floating_objects: Query<&mut FloatingObject>,
+) {
+    for (player_id, player_transform) in players.iter() {
+        // weapons are only picked up with empty hands
+        if player_weapons.iter().any(|id| id == player_id) {
+            continue;
+        }
+
+        for mut floating in floating_objects.iter_mut() {
+            let v = (player_transform.translation - floating.original_translation).truncate();
+            let distance = v.length();
+            if distance == 0.0 || MAGNET_RADIUS < distance {
+                continue;
+            }
+            let step = (MAGNET_SPEED * time.delta_seconds()).min(distance);
+            floating.original_translation += (v / distance * step).extend(0.0);
+        }
     }
 }
@@ -1,20 +1,50 @@
 use bevy::prelude::*;
 use bevy_asset_loader::prelude::*;
-use bevy_kira_audio::{Audio, AudioControl, AudioSource};
+use bevy_kira_audio::{Audio, AudioInstance, AudioSource};
 use bevy_rapier3d::prelude::*;
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    Rng,
+};
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    animation::Animation, damage::Damage, level::LevelObject, GlobalState, COLLISION_GROUP_ENEMY,
-    COLLISION_GROUP_LEVEL, COLLISION_GROUP_PLAYER, COLLISION_GROUP_PROJECTILES,
+    animation::Animation,
+    collision::{CollisionLayer, COLLISION_GROUP_LEVEL},
+    damage::{Damage, Health, KillEvent},
+    level::LevelObject,
+    perks::Perks,
+    player::PlayerWeapon,
+    powerups::ActivePowerups,
+    rng::{GameRng, RngStream},
+    sound::{SoundLimit, SoundPool},
+    GameSettings, GlobalState,
 };
 
-use self::floating::{FloatingObjectBundle, FloatingObjectInternal};
+use self::{
+    ammo::{AmmoRegistry, AmmoType, AmmoTypes},
+    floating::{FloatingObjectBundle, FloatingObjectInternal},
+};
 
+pub mod ammo;
 pub mod floating;
+pub mod trail;
 
 const DEFAULT_PROJECTILE_SIZE: f32 = 0.125;
 const DEFAULT_CLIP_SIZE: f32 = 0.01;
 const DEFAULT_CLIP_LENGTH: f32 = 0.02;
+// Enemy projectiles are destroyed by any player hit
+const ENEMY_PROJECTILE_HEALTH: i32 = 1;
+// Ricochet perk shots get weaker with each bounce
+const PLAYER_PROJECTILE_BOUNCE_DAMAGE_DECAY: f32 = 0.25;
+
+// Weapons spawned in levels roll a rarity and
+// small bonuses to their stats
+const RARE_CHANCE: f64 = 0.2;
+const EPIC_CHANCE: f64 = 0.05;
+const COMMON_BONUS_RANGE: (f32, f32) = (-0.1, 0.1);
+const RARE_BONUS_RANGE: (f32, f32) = (0.0, 0.2);
+const EPIC_BONUS_RANGE: (f32, f32) = (0.1, 0.3);
 
 // Pistol
 const PISTOL_AMMO: u32 = 20;
@@ -22,6 +52,9 @@ const PISTOL_DAMAGE: i32 = 10;
 const PISTOL_ATTACK_SPEED: f32 = 1.0 / 4.0;
 const PISTOL_PROJECTILE_VELOCITY: f32 = 500.0;
 const PISTOL_PROJECTILE_OFFSET_SCALE: f32 = 2.0;
+const PISTOL_SPREAD: f32 = 0.02;
+const PISTOL_AIM_TRANSLATION: Vec3 = Vec3::new(0.0, -0.4, -1.5);
+const PISTOL_LOUDNESS: f32 = 40.0;
 
 // Needs to be bigger that (1 / attack_speed) * 2
 // because animatino played for 2 directions
@@ -32,6 +65,11 @@ const PISTOL_ANIMATION_TARGET_OFFSET: Vec3 = Vec3::new(0.2, 0.2, 0.0);
 const PISTOL_ANIMATION_TARGET_ROTATION_X: f32 = std::f32::consts::FRAC_PI_8;
 const PISTOL_ANIMATION_TARGET_ROTATION_Y: f32 = 0.0;
 const PISTOL_SHELL_INITIAL_VELOCITY: f32 = 10.0;
+const PISTOL_SOUND_LIMIT: SoundLimit = SoundLimit {
+    max_voices: 4,
+    cooldown: 0.05,
+    pitch_variation: 0.05,
+};
 
 // Shotgun
 const SHOTGUN_AMMO: u32 = 10;
@@ -39,6 +77,9 @@ const SHOTGUN_DAMAGE: i32 = 5;
 const SHOTGUN_ATTACK_SPEED: f32 = 1.0 / 1.2;
 const SHOTGUN_PROJECTILE_VELOCITY: f32 = 500.0;
 const SHOTGUN_PROJECTILE_OFFSET_SCALE: f32 = 2.2;
+const SHOTGUN_SPREAD: f32 = 0.04;
+const SHOTGUN_AIM_TRANSLATION: Vec3 = Vec3::new(0.0, -0.5, -1.6);
+const SHOTGUN_LOUDNESS: f32 = 80.0;
 
 // Needs to be bigger that (1 / attack_speed) * 2
 // because animatino played for 2 directions
@@ -49,6 +90,11 @@ const SHOTGUN_ANIMATION_TARGET_OFFSET: Vec3 = Vec3::new(0.2, 0.2, 0.0);
 const SHOTGUN_ANIMATION_TARGET_ROTATION_X: f32 = std::f32::consts::FRAC_PI_8;
 const SHOTGUN_ANIMATION_TARGET_ROTATION_Y: f32 = 0.0;
 const SHOTGUN_SHELL_INITIAL_VELOCITY: f32 = 10.0;
+const SHOTGUN_SOUND_LIMIT: SoundLimit = SoundLimit {
+    max_voices: 3,
+    cooldown: 0.05,
+    pitch_variation: 0.05,
+};
 
 // Minigun
 const MINIGUN_AMMO: u32 = 50;
@@ -56,6 +102,9 @@ const MINIGUN_DAMAGE: i32 = 10;
 const MINIGUN_ATTACK_SPEED: f32 = 1.0 / 8.0;
 const MINIGUN_PROJECTILE_VELOCITY: f32 = 500.0;
 const MINIGUN_PROJECTILE_OFFSET_SCALE: f32 = 3.0;
+const MINIGUN_SPREAD: f32 = 0.06;
+const MINIGUN_AIM_TRANSLATION: Vec3 = Vec3::new(0.0, -0.7, -1.7);
+const MINIGUN_LOUDNESS: f32 = 200.0;
 
 // Needs to be bigger that (1 / attack_speed)
 const MINIGUN_ANIMATION_SPEED: f32 = 9.0;
@@ -65,6 +114,20 @@ const MINIGUN_ANIMATION_TARGET_OFFSET: Vec3 = Vec3::ZERO;
 const MINIGUN_ANIMATION_TARGET_ROTATION_X: f32 = 0.0;
 const MINIGUN_ANIMATION_TARGET_ROTATION_Y: f32 = std::f32::consts::FRAC_PI_2;
 const MINIGUN_SHELL_INITIAL_VELOCITY: f32 = 10.0;
+// Several miniguns firing together would
+// otherwise start a new sound every frame
+const MINIGUN_SOUND_LIMIT: SoundLimit = SoundLimit {
+    max_voices: 6,
+    cooldown: 0.05,
+    pitch_variation: 0.08,
+};
+
+// Click of the trigger pulled with no ammo
+const DRY_FIRE_SOUND_LIMIT: SoundLimit = SoundLimit {
+    max_voices: 2,
+    cooldown: 0.1,
+    pitch_variation: 0.05,
+};
 
 pub struct WeaponsPlugin;
 
@@ -72,13 +135,26 @@ impl Plugin for WeaponsPlugin {
     fn build(&self, app: &mut App) {
         app.add_collection_to_loading_state::<_, WeaponAssets>(GlobalState::AssetLoading);
 
+        app.init_resource::<WeaponRegistry>();
+
         app.add_event::<ShootEvent>();
+        app.add_event::<DryFireEvent>();
 
         app.add_plugins(floating::FloatingPlugin);
+        app.add_plugins(ammo::AmmoPlugin);
+        app.add_plugins(trail::TrailPlugin);
 
         app.add_systems(
             Update,
-            (update_attack_timers, weapon_shoot).run_if(in_state(GlobalState::InGame)),
+            (
+                apply_weapon_stats,
+                update_attack_timers,
+                weapon_shoot,
+                weapon_dry_fire,
+                setup_projectile_behavior,
+                projectile_destroyed,
+            )
+                .run_if(in_state(GlobalState::InGame)),
         );
     }
 }
@@ -89,28 +165,32 @@ pub struct WeaponAssets {
     pub pistol_scene: Handle<Scene>,
     #[asset(path = "pistol/pistol_shell.glb#Scene0")]
     pub pistol_shell_scene: Handle<Scene>,
-    #[asset(path = "pistol/pistol.wav")]
-    pub pistol_sound: Handle<AudioSource>,
+    // Fire sound variants, one is picked at random for each shot
+    #[asset(paths("pistol/pistol.wav"), collection(typed))]
+    pub pistol_sounds: Vec<Handle<AudioSource>>,
 
     #[asset(path = "shotgun/shotgun.glb#Scene0")]
     pub shotgun_scene: Handle<Scene>,
     #[asset(path = "shotgun/shotgun_shell.glb#Scene0")]
     pub shotgun_shell_scene: Handle<Scene>,
-    #[asset(path = "shotgun/shotgun.wav")]
-    pub shotgun_sound: Handle<AudioSource>,
+    #[asset(paths("shotgun/shotgun.wav"), collection(typed))]
+    pub shotgun_sounds: Vec<Handle<AudioSource>>,
 
     #[asset(path = "minigun/minigun.glb#Scene0")]
     pub minigun_scene: Handle<Scene>,
     #[asset(path = "minigun/minigun_shell.glb#Scene0")]
     pub minigun_shell_scene: Handle<Scene>,
-    #[asset(path = "minigun/minigun.wav")]
-    pub minigun_sound: Handle<AudioSource>,
+    #[asset(paths("minigun/minigun.wav"), collection(typed))]
+    pub minigun_sounds: Vec<Handle<AudioSource>>,
 
     #[asset(path = "round.glb#Scene0")]
     pub round_scene: Handle<Scene>,
+
+    #[asset(path = "dry_fire.wav")]
+    pub dry_fire_sound: Handle<AudioSource>,
 }
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum WeaponType {
     #[default]
     Pistol,
@@ -118,9 +198,309 @@ pub enum WeaponType {
     Minigun,
 }
 
+impl WeaponType {
+    pub const ALL: [WeaponType; 3] = [WeaponType::Pistol, WeaponType::Shotgun, WeaponType::Minigun];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            WeaponType::Pistol => "PISTOL",
+            WeaponType::Shotgun => "SHOTGUN",
+            WeaponType::Minigun => "MINIGUN",
+        }
+    }
+}
+
+// Tunable part of the weapon. Defaults can be
+// overridden by mods.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WeaponStats {
+    pub ammo: u32,
+    pub damage: i32,
+    // Seconds between shots
+    pub attack_speed: f32,
+    // Max angle in radians between shot
+    // direction and aim direction
+    pub spread: f32,
+    // Distance at which enemies hear the shot
+    // if nothing is in the way
+    pub loudness: f32,
+}
+
+impl WeaponStats {
+    pub fn default_for(weapon_type: WeaponType) -> Self {
+        match weapon_type {
+            WeaponType::Pistol => Self {
+                ammo: PISTOL_AMMO,
+                damage: PISTOL_DAMAGE,
+                attack_speed: PISTOL_ATTACK_SPEED,
+                spread: PISTOL_SPREAD,
+                loudness: PISTOL_LOUDNESS,
+            },
+            WeaponType::Shotgun => Self {
+                ammo: SHOTGUN_AMMO,
+                damage: SHOTGUN_DAMAGE,
+                attack_speed: SHOTGUN_ATTACK_SPEED,
+                spread: SHOTGUN_SPREAD,
+                loudness: SHOTGUN_LOUDNESS,
+            },
+            WeaponType::Minigun => Self {
+                ammo: MINIGUN_AMMO,
+                damage: MINIGUN_DAMAGE,
+                attack_speed: MINIGUN_ATTACK_SPEED,
+                spread: MINIGUN_SPREAD,
+                loudness: MINIGUN_LOUDNESS,
+            },
+        }
+    }
+}
+
+/// Stats of all weapon types used for
+/// newly spawned weapons
+#[derive(Debug, Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WeaponRegistry {
+    pistol: WeaponStats,
+    shotgun: WeaponStats,
+    minigun: WeaponStats,
+}
+
+impl Default for WeaponRegistry {
+    fn default() -> Self {
+        Self {
+            pistol: WeaponStats::default_for(WeaponType::Pistol),
+            shotgun: WeaponStats::default_for(WeaponType::Shotgun),
+            minigun: WeaponStats::default_for(WeaponType::Minigun),
+        }
+    }
+}
+
+impl WeaponRegistry {
+    pub fn stats(&self, weapon_type: WeaponType) -> &WeaponStats {
+        match weapon_type {
+            WeaponType::Pistol => &self.pistol,
+            WeaponType::Shotgun => &self.shotgun,
+            WeaponType::Minigun => &self.minigun,
+        }
+    }
+
+    pub fn stats_mut(&mut self, weapon_type: WeaponType) -> &mut WeaponStats {
+        match weapon_type {
+            WeaponType::Pistol => &mut self.pistol,
+            WeaponType::Shotgun => &mut self.shotgun,
+            WeaponType::Minigun => &mut self.minigun,
+        }
+    }
+}
+
+/// Spawn weights of weapon types. Weights are
+/// relative to each other and do not need to sum to 1.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WeaponSpawnWeights {
+    pub pistol: f32,
+    pub shotgun: f32,
+    pub minigun: f32,
+}
+
+impl WeaponSpawnWeights {
+    pub fn weight(&self, weapon_type: WeaponType) -> f32 {
+        match weapon_type {
+            WeaponType::Pistol => self.pistol,
+            WeaponType::Shotgun => self.shotgun,
+            WeaponType::Minigun => self.minigun,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WeaponSpawnTier {
+    // Game progress where the tier starts
+    pub min_depth: i32,
+    // Weights at the lowest and the highest difficulty
+    pub easy: WeaponSpawnWeights,
+    pub hard: WeaponSpawnWeights,
+}
+
+/// Weapon types spawned in levels. The deepest
+/// tier reached by the run is used. Difficulty of the
+/// director moves weights from `easy` to `hard`.
+#[derive(Debug, Clone, PartialEq, Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WeaponSpawnTable {
+    pub tiers: Vec<WeaponSpawnTier>,
+}
+
+impl Default for WeaponSpawnTable {
+    fn default() -> Self {
+        let weights = |pistol, shotgun, minigun| WeaponSpawnWeights {
+            pistol,
+            shotgun,
+            minigun,
+        };
+        Self {
+            tiers: vec![
+                WeaponSpawnTier {
+                    min_depth: 0,
+                    easy: weights(0.5, 0.4, 0.1),
+                    hard: weights(0.3, 0.5, 0.2),
+                },
+                WeaponSpawnTier {
+                    min_depth: 40,
+                    easy: weights(0.2, 0.5, 0.3),
+                    hard: weights(0.1, 0.5, 0.4),
+                },
+                WeaponSpawnTier {
+                    min_depth: 80,
+                    easy: weights(0.1, 0.4, 0.5),
+                    hard: weights(0.0, 0.4, 0.6),
+                },
+            ],
+        }
+    }
+}
+
+impl WeaponSpawnTable {
+    // Falls back to a pistol if there are
+    // no tiers or all weights are zero
+    pub fn pick(&self, depth: i32, difficulty: f32, rng: &mut impl Rng) -> WeaponType {
+        let Some(tier) = self
+            .tiers
+            .iter()
+            .filter(|tier| tier.min_depth <= depth)
+            .max_by_key(|tier| tier.min_depth)
+        else {
+            return WeaponType::Pistol;
+        };
+
+        let t = difficulty.clamp(0.0, 1.0);
+        let weights = WeaponType::ALL.map(|weapon_type| {
+            let easy = tier.easy.weight(weapon_type);
+            let hard = tier.hard.weight(weapon_type);
+            (easy + (hard - easy) * t).max(0.0)
+        });
+        match WeightedIndex::new(weights) {
+            Ok(distribution) => WeaponType::ALL[distribution.sample(rng)],
+            Err(_) => WeaponType::Pistol,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum WeaponRarity {
+    #[default]
+    Common,
+    Rare,
+    Epic,
+}
+
+impl WeaponRarity {
+    pub fn name(&self) -> &'static str {
+        match self {
+            WeaponRarity::Common => "COMMON",
+            WeaponRarity::Rare => "RARE",
+            WeaponRarity::Epic => "EPIC",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            WeaponRarity::Common => Color::WHITE,
+            WeaponRarity::Rare => Color::rgb(0.2, 0.5, 1.0),
+            WeaponRarity::Epic => Color::rgb(0.7, 0.2, 1.0),
+        }
+    }
+
+    fn bonus_range(&self) -> (f32, f32) {
+        match self {
+            WeaponRarity::Common => COMMON_BONUS_RANGE,
+            WeaponRarity::Rare => RARE_BONUS_RANGE,
+            WeaponRarity::Epic => EPIC_BONUS_RANGE,
+        }
+    }
+}
+
+/// Rarity and stat bonuses of a spawned weapon.
+/// Bonuses are parts of the base stat, e.g. 0.1 is +10%.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Component)]
+pub struct WeaponRoll {
+    pub rarity: WeaponRarity,
+    pub damage_bonus: f32,
+    pub ammo_bonus: f32,
+}
+
+impl WeaponRoll {
+    pub fn random(rng: &mut impl Rng) -> Self {
+        let roll = rng.gen_range(0.0..1.0);
+        let rarity = if roll < EPIC_CHANCE {
+            WeaponRarity::Epic
+        } else if roll < EPIC_CHANCE + RARE_CHANCE {
+            WeaponRarity::Rare
+        } else {
+            WeaponRarity::Common
+        };
+        let (min, max) = rarity.bonus_range();
+        Self {
+            rarity,
+            damage_bonus: rng.gen_range(min..=max),
+            ammo_bonus: rng.gen_range(min..=max),
+        }
+    }
+
+    pub fn apply(&self, mut stats: WeaponStats) -> WeaponStats {
+        stats.damage = ((stats.damage as f32 * (1.0 + self.damage_bonus)).round() as i32).max(1);
+        stats.ammo = ((stats.ammo as f32 * (1.0 + self.ammo_bonus)).round() as u32).max(1);
+        stats
+    }
+
+    // e.g. "+12% damage -3% ammo"
+    pub fn bonus_text(&self) -> String {
+        format!(
+            "{:+.0}% damage {:+.0}% ammo",
+            self.damage_bonus * 100.0,
+            self.ammo_bonus * 100.0
+        )
+    }
+}
+
 #[derive(Default, Component)]
 pub struct Weapon {
     weapon_type: WeaponType,
+    stats: WeaponStats,
+}
+
+impl Weapon {
+    fn new(weapon_type: WeaponType) -> Self {
+        Self {
+            weapon_type,
+            stats: WeaponStats::default_for(weapon_type),
+        }
+    }
+
+    pub fn weapon_type(&self) -> WeaponType {
+        self.weapon_type
+    }
+
+    // Max angle in radians between shot
+    // direction and aim direction
+    pub fn spread(&self) -> f32 {
+        self.stats.spread
+    }
+
+    // Weapon translation relative to the camera
+    // when player aims down sights
+    pub fn aim_translation(&self) -> Vec3 {
+        match self.weapon_type {
+            WeaponType::Pistol => PISTOL_AIM_TRANSLATION,
+            WeaponType::Shotgun => SHOTGUN_AIM_TRANSLATION,
+            WeaponType::Minigun => MINIGUN_AIM_TRANSLATION,
+        }
+    }
+
+    // Distance at which enemies hear the shot
+    // if nothing is in the way
+    pub fn loudness(&self) -> f32 {
+        self.stats.loudness
+    }
 }
 
 #[derive(Component)]
@@ -131,11 +511,19 @@ pub struct Ammo {
     pub ammo: u32,
 }
 
-#[derive(Event)]
+#[derive(Clone, Copy, Event)]
 pub struct ShootEvent {
     pub weapon_entity: Entity,
     pub weapon_translation: Vec3,
     pub direction: Vec3,
+    pub spread: f32,
+    pub ammo_type: AmmoType,
+}
+
+/// Trigger pulled with no ammo left
+#[derive(Clone, Copy, Event)]
+pub struct DryFireEvent {
+    pub weapon_entity: Entity,
 }
 
 #[derive(Component)]
@@ -161,6 +549,7 @@ pub struct WeaponBundle {
     pub transform_bundle: TransformBundle,
     pub inherited_visibility: InheritedVisibility,
     pub ammo: Ammo,
+    pub ammo_types: AmmoTypes,
     pub weapon_attack_timer: WeaponAttackTimer,
     pub weapon: Weapon,
 }
@@ -171,10 +560,9 @@ impl WeaponBundle {
             transform_bundle: TransformBundle::from_transform(transform),
             inherited_visibility: InheritedVisibility::VISIBLE,
             ammo: Ammo { ammo: PISTOL_AMMO },
+            ammo_types: AmmoTypes::default(),
             weapon_attack_timer: WeaponAttackTimer::new(PISTOL_ATTACK_SPEED),
-            weapon: Weapon {
-                weapon_type: WeaponType::Pistol,
-            },
+            weapon: Weapon::new(WeaponType::Pistol),
         }
     }
 
@@ -183,10 +571,9 @@ impl WeaponBundle {
             transform_bundle: TransformBundle::from_transform(transform),
             inherited_visibility: InheritedVisibility::VISIBLE,
             ammo: Ammo { ammo: SHOTGUN_AMMO },
+            ammo_types: AmmoTypes::default(),
             weapon_attack_timer: WeaponAttackTimer::new(SHOTGUN_ATTACK_SPEED),
-            weapon: Weapon {
-                weapon_type: WeaponType::Shotgun,
-            },
+            weapon: Weapon::new(WeaponType::Shotgun),
         }
     }
 
@@ -195,10 +582,9 @@ impl WeaponBundle {
             transform_bundle: TransformBundle::from_transform(transform),
             inherited_visibility: InheritedVisibility::VISIBLE,
             ammo: Ammo { ammo: MINIGUN_AMMO },
+            ammo_types: AmmoTypes::default(),
             weapon_attack_timer: WeaponAttackTimer::new(MINIGUN_ATTACK_SPEED),
-            weapon: Weapon {
-                weapon_type: WeaponType::Minigun,
-            },
+            weapon: Weapon::new(WeaponType::Minigun),
         }
     }
 }
@@ -209,6 +595,7 @@ impl Default for WeaponBundle {
             transform_bundle: TransformBundle::default(),
             inherited_visibility: InheritedVisibility::VISIBLE,
             ammo: Ammo::default(),
+            ammo_types: AmmoTypes::default(),
             weapon_attack_timer: WeaponAttackTimer::new(0.0),
             weapon: Weapon::default(),
         }
@@ -218,6 +605,24 @@ impl Default for WeaponBundle {
 #[derive(Default, Component)]
 pub struct Projectile {
     pub direction: Vec3,
+    // Pellets of one shot share the group, so
+    // their hits count as a single hit
+    pub pellet_group: Option<u32>,
+    // Targets the projectile goes through before it is
+    // gone. Projectile is gone after the first hit by default.
+    pub pierce: u32,
+    // Times the projectile bounces off the level
+    // before it is gone
+    pub bounces: u32,
+    // Part of the damage lost on each bounce
+    pub bounce_damage_decay: f32,
+    // Decides status effects applied on hit
+    pub ammo_type: AmmoType,
+    // Where and from what the projectile was fired,
+    // carried to kills for the run stats
+    pub origin: Vec3,
+    pub weapon_type: WeaponType,
+    pub player_weapon: bool,
 }
 
 #[derive(Bundle)]
@@ -228,6 +633,9 @@ pub struct ProjectileBundle {
     pub collision_groups: CollisionGroups,
     pub active_events: ActiveEvents,
     pub velocity: Velocity,
+    // Projectiles are fast enough to fly straight,
+    // slow ones need this to fly in an arc
+    pub gravity_scale: GravityScale,
     pub projectile: Projectile,
     pub damage: Damage,
 
@@ -240,12 +648,10 @@ impl Default for ProjectileBundle {
             scene_bundle: SceneBundle::default(),
             rigid_body: RigidBody::Dynamic,
             collider: Collider::default(),
-            collision_groups: CollisionGroups::new(
-                COLLISION_GROUP_PROJECTILES,
-                COLLISION_GROUP_LEVEL | COLLISION_GROUP_PLAYER | COLLISION_GROUP_ENEMY,
-            ),
+            collision_groups: CollisionLayer::Projectile.groups(),
             active_events: ActiveEvents::COLLISION_EVENTS,
             velocity: Velocity::default(),
+            gravity_scale: GravityScale::default(),
             projectile: Projectile::default(),
             damage: Damage::default(),
 
@@ -254,13 +660,18 @@ impl Default for ProjectileBundle {
     }
 }
 
+#[derive(Component)]
+pub struct Shell;
+
 #[derive(Bundle)]
 pub struct ShellBundle {
     pub scene_bundle: SceneBundle,
     pub rigid_body: RigidBody,
     pub collider: Collider,
+    pub collision_groups: CollisionGroups,
     pub velocity: Velocity,
     pub friction: Friction,
+    pub shell: Shell,
 
     pub level_object: LevelObject,
 }
@@ -271,17 +682,39 @@ impl Default for ShellBundle {
             scene_bundle: SceneBundle::default(),
             rigid_body: RigidBody::Dynamic,
             collider: Collider::cuboid(DEFAULT_CLIP_LENGTH, DEFAULT_CLIP_SIZE, DEFAULT_CLIP_SIZE),
+            collision_groups: CollisionLayer::Debris.groups(),
             velocity: Velocity::default(),
             friction: Friction {
                 coefficient: 100.0,
                 ..default()
             },
+            shell: Shell,
 
             level_object: LevelObject,
         }
     }
 }
 
+// Enemy projectiles get their own collision group
+// and health, so player can shoot them down
+fn spawn_projectile(mut bundle: ProjectileBundle, player_weapon: bool, commands: &mut Commands) {
+    bundle.projectile.origin = bundle.scene_bundle.transform.translation;
+    bundle.projectile.player_weapon = player_weapon;
+    if player_weapon {
+        commands.spawn(bundle);
+    } else {
+        commands.spawn((
+            ProjectileBundle {
+                collision_groups: CollisionLayer::EnemyProjectile.groups(),
+                ..bundle
+            },
+            Health {
+                health: ENEMY_PROJECTILE_HEALTH,
+            },
+        ));
+    }
+}
+
 macro_rules! attach_weapon {
     ($commands:ident, $weapon_assets:ident, $transform:ident, $bundle_fn:ident, $asset:ident) => {
         $commands
@@ -302,17 +735,22 @@ pub(crate) use attach_weapon;
 pub fn spawn_weapon(
     weapon_assets: &WeaponAssets,
     weapon_type: WeaponType,
+    roll: WeaponRoll,
     commands: &mut Commands,
     transform: Transform,
-) {
+) -> Entity {
+    // entity of the weapon inside the floating object
+    let mut weapon = Entity::PLACEHOLDER;
     match weapon_type {
         WeaponType::Pistol => {
             commands
                 .spawn((FloatingObjectBundle::new(transform.translation),))
                 .with_children(|builder| {
                     let transform = Transform::default();
-                    _ = attach_weapon!(builder, weapon_assets, transform, pistol, pistol_scene)
-                        .insert(FloatingObjectInternal);
+                    weapon =
+                        attach_weapon!(builder, weapon_assets, transform, pistol, pistol_scene)
+                            .insert((FloatingObjectInternal, roll))
+                            .id();
                 });
         }
 
@@ -321,8 +759,10 @@ pub fn spawn_weapon(
                 .spawn((FloatingObjectBundle::new(transform.translation),))
                 .with_children(|builder| {
                     let transform = Transform::default();
-                    _ = attach_weapon!(builder, weapon_assets, transform, shotgun, shotgun_scene)
-                        .insert(FloatingObjectInternal);
+                    weapon =
+                        attach_weapon!(builder, weapon_assets, transform, shotgun, shotgun_scene)
+                            .insert((FloatingObjectInternal, roll))
+                            .id();
                 });
         }
         WeaponType::Minigun => {
@@ -330,17 +770,61 @@ pub fn spawn_weapon(
                 .spawn((FloatingObjectBundle::new(transform.translation),))
                 .with_children(|builder| {
                     let transform = Transform::default();
-                    _ = attach_weapon!(builder, weapon_assets, transform, minigun, minigun_scene)
-                        .insert(FloatingObjectInternal);
+                    weapon =
+                        attach_weapon!(builder, weapon_assets, transform, minigun, minigun_scene)
+                            .insert((FloatingObjectInternal, roll))
+                            .id();
                 });
         }
     }
+    weapon
 }
 
-fn update_attack_timers(time: Res<Time>, mut timers: Query<&mut WeaponAttackTimer>) {
-    for mut timer in timers.iter_mut() {
+// Weapons take stats from the registry when spawned,
+// so bundles do not need access to it
+// Also applies changed stats to existing weapons,
+// so stats can be tweaked while the game runs
+fn apply_weapon_stats(
+    weapon_registry: Res<WeaponRegistry>,
+    mut weapons: Query<(
+        &mut Weapon,
+        &mut Ammo,
+        &mut WeaponAttackTimer,
+        Option<&WeaponRoll>,
+    )>,
+) {
+    for (mut weapon, mut ammo, mut attack_timer, roll) in weapons.iter_mut() {
+        let added = weapon.is_added();
+        if !added && !weapon_registry.is_changed() {
+            continue;
+        }
+        let mut stats = *weapon_registry.stats(weapon.weapon_type);
+        if let Some(roll) = roll {
+            stats = roll.apply(stats);
+        }
+        weapon.stats = stats;
+        if added {
+            ammo.ammo = stats.ammo;
+        }
+        attack_timer
+            .attack_timer
+            .set_duration(std::time::Duration::from_secs_f32(stats.attack_speed));
+    }
+}
+
+fn update_attack_timers(
+    time: Res<Time>,
+    perks: Res<Perks>,
+    mut timers: Query<(&mut WeaponAttackTimer, Has<PlayerWeapon>)>,
+) {
+    for (mut timer, player_weapon) in timers.iter_mut() {
         if !timer.ready {
-            timer.attack_timer.tick(time.delta());
+            let delta = if player_weapon {
+                time.delta().mul_f32(perks.modifiers.fire_rate)
+            } else {
+                time.delta()
+            };
+            timer.attack_timer.tick(delta);
             if timer.attack_timer.finished() {
                 timer.ready = true;
             }
@@ -348,52 +832,142 @@ fn update_attack_timers(time: Res<Time>, mut timers: Query<&mut WeaponAttackTime
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn weapon_shoot(
+    time: Res<Time>,
     audio: Res<Audio>,
+    game_settings: Res<GameSettings>,
+    perks: Res<Perks>,
+    active_powerups: Res<ActivePowerups>,
     weapon_assets: Res<WeaponAssets>,
-    weapons: Query<(&Weapon, &Children)>,
+    ammo_registry: Res<AmmoRegistry>,
+    weapons: Query<(&Weapon, &Children, Has<PlayerWeapon>)>,
     weapon_models: Query<&Transform, With<WeaponModel>>,
+    mut game_rng: ResMut<GameRng>,
+    mut sound_pool: ResMut<SoundPool>,
+    mut audio_instances: ResMut<Assets<AudioInstance>>,
     mut commands: Commands,
     mut shoot_event: EventReader<ShootEvent>,
+    mut pellet_groups: Local<u32>,
 ) {
     for e in shoot_event.read() {
-        if let Ok((weapon, weapon_children)) = weapons.get(e.weapon_entity) {
+        if let Ok((weapon, weapon_children, player_weapon)) = weapons.get(e.weapon_entity) {
+            let e = &ShootEvent {
+                direction: spread_direction(
+                    e.direction,
+                    e.spread,
+                    game_rng.stream(RngStream::Spread),
+                ),
+                ..*e
+            };
+            let damage_modifier = if player_weapon {
+                perks.modifiers.damage * active_powerups.damage_modifier()
+            } else {
+                1.0
+            };
+            let damage_modifier =
+                damage_modifier * ammo_registry.stats(e.ammo_type).damage_multiplier;
+            let damage = (weapon.stats.damage as f32 * damage_modifier) as i32;
             match weapon.weapon_type {
                 WeaponType::Pistol => pistol_shoot(
-                    audio.as_ref(),
-                    weapon_assets.as_ref(),
-                    &weapon_models,
-                    weapon_children,
-                    e,
-                    &mut commands,
-                ),
-                WeaponType::Shotgun => shotgun_shoot(
-                    audio.as_ref(),
                     weapon_assets.as_ref(),
                     &weapon_models,
                     weapon_children,
                     e,
+                    damage,
+                    player_weapon,
                     &mut commands,
                 ),
+                WeaponType::Shotgun => {
+                    *pellet_groups = pellet_groups.wrapping_add(1);
+                    shotgun_shoot(
+                        weapon_assets.as_ref(),
+                        &weapon_models,
+                        weapon_children,
+                        e,
+                        damage,
+                        *pellet_groups,
+                        player_weapon,
+                        &mut commands,
+                    )
+                }
                 WeaponType::Minigun => minigun_shoot(
-                    audio.as_ref(),
                     weapon_assets.as_ref(),
                     &weapon_models,
                     weapon_children,
                     e,
+                    damage,
+                    player_weapon,
                     &mut commands,
                 ),
             }
+
+            let (sounds, limit) = match weapon.weapon_type {
+                WeaponType::Pistol => (&weapon_assets.pistol_sounds, PISTOL_SOUND_LIMIT),
+                WeaponType::Shotgun => (&weapon_assets.shotgun_sounds, SHOTGUN_SOUND_LIMIT),
+                WeaponType::Minigun => (&weapon_assets.minigun_sounds, MINIGUN_SOUND_LIMIT),
+            };
+            if sounds.is_empty() {
+                continue;
+            }
+            let rng = game_rng.stream(RngStream::Effects);
+            let sound = &sounds[rng.gen_range(0..sounds.len())];
+            sound_pool.play(
+                audio.as_ref(),
+                audio_instances.as_mut(),
+                sound,
+                limit,
+                game_settings.volume as f64,
+                0.5,
+                time.elapsed_seconds(),
+                game_rng.stream(RngStream::Effects),
+            );
         }
     }
 }
 
+fn weapon_dry_fire(
+    time: Res<Time>,
+    audio: Res<Audio>,
+    game_settings: Res<GameSettings>,
+    weapon_assets: Res<WeaponAssets>,
+    mut game_rng: ResMut<GameRng>,
+    mut sound_pool: ResMut<SoundPool>,
+    mut audio_instances: ResMut<Assets<AudioInstance>>,
+    mut dry_fire_events: EventReader<DryFireEvent>,
+) {
+    for _ in dry_fire_events.read() {
+        sound_pool.play(
+            audio.as_ref(),
+            audio_instances.as_mut(),
+            &weapon_assets.dry_fire_sound,
+            DRY_FIRE_SOUND_LIMIT,
+            game_settings.volume as f64,
+            0.5,
+            time.elapsed_seconds(),
+            game_rng.stream(RngStream::Effects),
+        );
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spread_direction(direction: Vec3, spread: f32, rng: &mut impl Rng) -> Vec3 {
+    if spread <= 0.0 {
+        return direction;
+    }
+    let axis = Quat::from_axis_angle(direction, rng.gen_range(0.0..std::f32::consts::TAU))
+        * direction.any_orthonormal_vector();
+    Quat::from_axis_angle(axis, rng.gen_range(0.0..spread)) * direction
+}
+
+#[allow(clippy::too_many_arguments)]
 fn pistol_shoot(
-    audio: &Audio,
     weapon_assets: &WeaponAssets,
     weapon_models: &Query<&Transform, With<WeaponModel>>,
     weapon_children: &Children,
     event: &ShootEvent,
+    damage: i32,
+    player_weapon: bool,
     commands: &mut Commands,
 ) {
     let right = event.direction.cross(Vec3::Z);
@@ -406,27 +980,33 @@ fn pistol_shoot(
     let projectile_rotation = Quat::from_rotation_z(projectile_angle);
     let projectile_translation =
         event.weapon_translation + event.direction * PISTOL_PROJECTILE_OFFSET_SCALE;
-    commands.spawn(ProjectileBundle {
-        scene_bundle: SceneBundle {
-            scene: weapon_assets.round_scene.clone(),
-            transform: Transform::from_translation(projectile_translation)
-                .with_rotation(projectile_rotation)
-                .with_scale(Vec3::new(10.0, 10.0, 10.0)),
-            ..default()
-        },
-        collider: Collider::ball(DEFAULT_PROJECTILE_SIZE),
-        velocity: Velocity {
-            linvel: event.direction * PISTOL_PROJECTILE_VELOCITY,
+    spawn_projectile(
+        ProjectileBundle {
+            scene_bundle: SceneBundle {
+                scene: weapon_assets.round_scene.clone(),
+                transform: Transform::from_translation(projectile_translation)
+                    .with_rotation(projectile_rotation)
+                    .with_scale(Vec3::new(10.0, 10.0, 10.0)),
+                ..default()
+            },
+            collider: Collider::ball(DEFAULT_PROJECTILE_SIZE),
+            velocity: Velocity {
+                linvel: event.direction * PISTOL_PROJECTILE_VELOCITY,
+                ..default()
+            },
+            damage: Damage { damage },
+            projectile: Projectile {
+                direction: event.direction,
+                pellet_group: None,
+                ammo_type: event.ammo_type,
+                weapon_type: WeaponType::Pistol,
+                ..default()
+            },
             ..default()
         },
-        damage: Damage {
-            damage: PISTOL_DAMAGE,
-        },
-        projectile: Projectile {
-            direction: event.direction,
-        },
-        ..default()
-    });
+        player_weapon,
+        commands,
+    );
 
     // spawn shell
     let shell_direction = right + Vec3::Z;
@@ -467,17 +1047,17 @@ fn pistol_shoot(
         initial_transform,
         target_transform,
     });
-
-    // play sound
-    audio.play(weapon_assets.pistol_sound.clone());
 }
 
+#[allow(clippy::too_many_arguments)]
 fn shotgun_shoot(
-    audio: &Audio,
     weapon_assets: &WeaponAssets,
     weapon_models: &Query<&Transform, With<WeaponModel>>,
     weapon_children: &Children,
     event: &ShootEvent,
+    damage: i32,
+    pellet_group: u32,
+    player_weapon: bool,
     commands: &mut Commands,
 ) {
     let right = event.direction.cross(Vec3::Z);
@@ -503,27 +1083,33 @@ fn shotgun_shoot(
     for barrel in [left_barrel, right_barrel] {
         for offset in offsets {
             let projectile_translation = barrel + offset;
-            commands.spawn(ProjectileBundle {
-                scene_bundle: SceneBundle {
-                    scene: weapon_assets.round_scene.clone(),
-                    transform: Transform::from_translation(projectile_translation)
-                        .with_rotation(projectile_rotation)
-                        .with_scale(Vec3::new(10.0, 10.0, 10.0)),
-                    ..default()
-                },
-                collider: Collider::ball(DEFAULT_PROJECTILE_SIZE),
-                velocity: Velocity {
-                    linvel: event.direction * SHOTGUN_PROJECTILE_VELOCITY,
+            spawn_projectile(
+                ProjectileBundle {
+                    scene_bundle: SceneBundle {
+                        scene: weapon_assets.round_scene.clone(),
+                        transform: Transform::from_translation(projectile_translation)
+                            .with_rotation(projectile_rotation)
+                            .with_scale(Vec3::new(10.0, 10.0, 10.0)),
+                        ..default()
+                    },
+                    collider: Collider::ball(DEFAULT_PROJECTILE_SIZE),
+                    velocity: Velocity {
+                        linvel: event.direction * SHOTGUN_PROJECTILE_VELOCITY,
+                        ..default()
+                    },
+                    damage: Damage { damage },
+                    projectile: Projectile {
+                        direction: event.direction,
+                        pellet_group: Some(pellet_group),
+                        ammo_type: event.ammo_type,
+                        weapon_type: WeaponType::Shotgun,
+                        ..default()
+                    },
                     ..default()
                 },
-                damage: Damage {
-                    damage: SHOTGUN_DAMAGE,
-                },
-                projectile: Projectile {
-                    direction: event.direction,
-                },
-                ..default()
-            });
+                player_weapon,
+                commands,
+            );
         }
     }
 
@@ -570,17 +1156,16 @@ fn shotgun_shoot(
         initial_transform,
         target_transform,
     });
-
-    // play sound
-    audio.play(weapon_assets.shotgun_sound.clone());
 }
 
+#[allow(clippy::too_many_arguments)]
 fn minigun_shoot(
-    audio: &Audio,
     weapon_assets: &WeaponAssets,
     weapon_models: &Query<&Transform, With<WeaponModel>>,
     weapon_children: &Children,
     event: &ShootEvent,
+    damage: i32,
+    player_weapon: bool,
     commands: &mut Commands,
 ) {
     let right = event.direction.cross(Vec3::Z);
@@ -598,27 +1183,33 @@ fn minigun_shoot(
     let right_barrel = projectile_translation + right / 2.0;
 
     for barrel in [left_barrel, right_barrel] {
-        commands.spawn(ProjectileBundle {
-            scene_bundle: SceneBundle {
-                scene: weapon_assets.minigun_shell_scene.clone(),
-                transform: Transform::from_translation(barrel)
-                    .with_rotation(projectile_rotation)
-                    .with_scale(Vec3::new(10.0, 10.0, 10.0)),
-                ..default()
-            },
-            collider: Collider::ball(DEFAULT_PROJECTILE_SIZE),
-            velocity: Velocity {
-                linvel: event.direction * MINIGUN_PROJECTILE_VELOCITY,
+        spawn_projectile(
+            ProjectileBundle {
+                scene_bundle: SceneBundle {
+                    scene: weapon_assets.minigun_shell_scene.clone(),
+                    transform: Transform::from_translation(barrel)
+                        .with_rotation(projectile_rotation)
+                        .with_scale(Vec3::new(10.0, 10.0, 10.0)),
+                    ..default()
+                },
+                collider: Collider::ball(DEFAULT_PROJECTILE_SIZE),
+                velocity: Velocity {
+                    linvel: event.direction * MINIGUN_PROJECTILE_VELOCITY,
+                    ..default()
+                },
+                damage: Damage { damage },
+                projectile: Projectile {
+                    direction: event.direction,
+                    pellet_group: None,
+                    ammo_type: event.ammo_type,
+                    weapon_type: WeaponType::Minigun,
+                    ..default()
+                },
                 ..default()
             },
-            damage: Damage {
-                damage: MINIGUN_DAMAGE,
-            },
-            projectile: Projectile {
-                direction: event.direction,
-            },
-            ..default()
-        });
+            player_weapon,
+            commands,
+        );
     }
 
     // spawn shell
@@ -664,7 +1255,50 @@ fn minigun_shoot(
         initial_transform,
         target_transform,
     });
+}
+
+fn setup_projectile_behavior(
+    perks: Res<Perks>,
+    mut projectiles: Query<(Entity, &mut Projectile, &CollisionGroups), Added<Projectile>>,
+    mut commands: Commands,
+) {
+    for (entity, mut projectile, collision_groups) in projectiles.iter_mut() {
+        if projectile.player_weapon {
+            projectile.pierce += perks.modifiers.pierce;
+            projectile.bounces += perks.modifiers.bounces;
+            projectile.bounce_damage_decay = PLAYER_PROJECTILE_BOUNCE_DAMAGE_DECAY;
+        }
+
+        let mut e = commands.entity(entity);
+        // piercing projectiles still report hits,
+        // but are only stopped by the level
+        if 0 < projectile.pierce {
+            e.insert(SolverGroups::new(
+                collision_groups.memberships,
+                COLLISION_GROUP_LEVEL,
+            ));
+        }
+        if 0 < projectile.bounces {
+            e.insert(Restitution {
+                coefficient: 1.0,
+                combine_rule: CoefficientCombineRule::Max,
+            });
+        }
+    }
+}
 
-    // play sound
-    audio.play(weapon_assets.minigun_sound.clone());
+fn projectile_destroyed(
+    projectiles: Query<Entity, With<Projectile>>,
+    mut commands: Commands,
+    mut kill_events: EventReader<KillEvent>,
+) {
+    for kill_event in kill_events.read() {
+        let Ok(projectile) = projectiles.get(kill_event.entity) else {
+            continue;
+        };
+        let Some(e) = commands.get_entity(projectile) else {
+            continue;
+        };
+        e.despawn_recursive();
+    }
 }
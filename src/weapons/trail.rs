@@ -0,0 +1,281 @@
+use std::collections::VecDeque;
+
+use bevy::{
+    pbr::NotShadowCaster,
+    prelude::*,
+    render::{mesh::Indices, render_resource::PrimitiveTopology, view::NoFrustumCulling},
+};
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    display::DisplaySettings,
+    player::{PlayerCamera, PlayerId},
+    GlobalState,
+};
+
+use super::{ammo::AmmoType, Projectile};
+
+// Slower projectiles are easy to see without a trail
+const TRAIL_MIN_SPEED: f32 = 100.0;
+const TRAIL_LIFESPAN: f32 = 0.3;
+const TRAIL_WIDTH: f32 = 0.06;
+const TRAIL_ALPHA: f32 = 0.6;
+// Trails are reused instead of creating a new mesh for
+// each shot. Shots after the pool is used up have no trail.
+const TRAIL_POOL_SIZE: usize = 64;
+
+/// Fading ribbons behind fast projectiles
+pub struct TrailPlugin;
+
+impl Plugin for TrailPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TrailPool>();
+
+        app.add_systems(
+            OnTransition {
+                from: GlobalState::AssetLoading,
+                to: GlobalState::MainMenu,
+            },
+            init_resources,
+        );
+        app.add_systems(OnEnter(GlobalState::MainMenu), reset_trails);
+
+        app.add_systems(
+            Update,
+            (trail_attach, trail_update)
+                .chain()
+                .run_if(in_state(GlobalState::InGame)),
+        );
+    }
+}
+
+#[derive(Resource)]
+struct TrailResources {
+    // Same order as `AmmoType::ALL`
+    materials: Vec<Handle<StandardMaterial>>,
+}
+
+impl TrailResources {
+    fn material(&self, ammo_type: AmmoType) -> Handle<StandardMaterial> {
+        let index = AmmoType::ALL
+            .iter()
+            .position(|t| *t == ammo_type)
+            .unwrap_or(0);
+        self.materials[index].clone()
+    }
+}
+
+#[derive(Debug, Default, Resource)]
+struct TrailPool {
+    free: Vec<Entity>,
+    total: usize,
+}
+
+#[derive(Debug, Default, Component)]
+struct ProjectileTrail {
+    // Projectile is gone once it hits something,
+    // the trail stays until it fades
+    projectile: Option<Entity>,
+    // Past positions of the projectile with the time they were recorded
+    points: VecDeque<(Vec3, f32)>,
+}
+
+fn init_resources(mut commands: Commands, mut materials: ResMut<Assets<StandardMaterial>>) {
+    let materials = AmmoType::ALL
+        .iter()
+        .map(|ammo_type| {
+            materials.add(StandardMaterial {
+                base_color: ammo_type.color(),
+                emissive: ammo_type.color(),
+                alpha_mode: AlphaMode::Blend,
+                cull_mode: None,
+                unlit: true,
+                ..default()
+            })
+        })
+        .collect();
+
+    commands.insert_resource(TrailResources { materials });
+}
+
+fn trail_mesh() -> Mesh {
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, Vec::<[f32; 3]>::new());
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, Vec::<[f32; 3]>::new());
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, Vec::<[f32; 4]>::new());
+    mesh.set_indices(Some(Indices::U32(Vec::new())));
+    mesh
+}
+
+fn release_trail(
+    trail_pool: &mut TrailPool,
+    entity: Entity,
+    trail: &mut ProjectileTrail,
+    visibility: &mut Visibility,
+) {
+    trail.projectile = None;
+    trail.points.clear();
+    *visibility = Visibility::Hidden;
+    trail_pool.free.push(entity);
+}
+
+fn reset_trails(
+    mut trails: Query<(Entity, &mut ProjectileTrail, &mut Visibility)>,
+    mut trail_pool: ResMut<TrailPool>,
+) {
+    trail_pool.free.clear();
+    for (entity, mut trail, mut visibility) in trails.iter_mut() {
+        release_trail(
+            trail_pool.as_mut(),
+            entity,
+            trail.as_mut(),
+            visibility.as_mut(),
+        );
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn trail_attach(
+    time: Res<Time>,
+    display_settings: Res<DisplaySettings>,
+    trail_resources: Res<TrailResources>,
+    projectiles: Query<(Entity, &Transform, &Velocity, &Projectile), Added<Projectile>>,
+    mut trails: Query<(
+        &mut ProjectileTrail,
+        &mut Handle<StandardMaterial>,
+        &mut Visibility,
+    )>,
+    mut trail_pool: ResMut<TrailPool>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut commands: Commands,
+) {
+    if !display_settings.projectile_trails {
+        return;
+    }
+
+    for (projectile_entity, transform, velocity, projectile) in projectiles.iter() {
+        if velocity.linvel.length() < TRAIL_MIN_SPEED {
+            continue;
+        }
+
+        let material = trail_resources.material(projectile.ammo_type);
+        let points = VecDeque::from([(transform.translation, time.elapsed_seconds())]);
+
+        if let Some(trail_entity) = trail_pool.free.pop() {
+            let Ok((mut trail, mut trail_material, mut visibility)) = trails.get_mut(trail_entity)
+            else {
+                continue;
+            };
+            trail.projectile = Some(projectile_entity);
+            trail.points = points;
+            *trail_material = material;
+            *visibility = Visibility::Visible;
+        } else if trail_pool.total < TRAIL_POOL_SIZE {
+            trail_pool.total += 1;
+            commands.spawn((
+                PbrBundle {
+                    mesh: meshes.add(trail_mesh()),
+                    material,
+                    ..default()
+                },
+                // mesh changes every frame, so its bounds are never right
+                NoFrustumCulling,
+                NotShadowCaster,
+                ProjectileTrail {
+                    projectile: Some(projectile_entity),
+                    points,
+                },
+            ));
+        }
+    }
+}
+
+fn trail_update(
+    time: Res<Time>,
+    player_cameras: Query<(&PlayerId, &GlobalTransform), With<PlayerCamera>>,
+    projectiles: Query<&Transform, With<Projectile>>,
+    mut trails: Query<(Entity, &mut ProjectileTrail, &Handle<Mesh>, &mut Visibility)>,
+    mut trail_pool: ResMut<TrailPool>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    // trail ribbons face the local camera
+    let Some((_, camera_global_transform)) = player_cameras.iter().find(|(id, _)| id.is_local())
+    else {
+        return;
+    };
+    let camera_translation = camera_global_transform.translation();
+    let now = time.elapsed_seconds();
+
+    for (trail_entity, mut trail, trail_mesh, mut visibility) in trails.iter_mut() {
+        if trail.projectile.is_none() && trail.points.is_empty() {
+            continue;
+        }
+
+        match trail
+            .projectile
+            .map(|projectile| projectiles.get(projectile))
+        {
+            Some(Ok(transform)) => trail.points.push_back((transform.translation, now)),
+            Some(Err(_)) => trail.projectile = None,
+            None => {}
+        }
+        while trail
+            .points
+            .front()
+            .is_some_and(|(_, t)| TRAIL_LIFESPAN <= now - t)
+        {
+            trail.points.pop_front();
+        }
+
+        if trail.projectile.is_none() && trail.points.len() < 2 {
+            release_trail(
+                trail_pool.as_mut(),
+                trail_entity,
+                trail.as_mut(),
+                visibility.as_mut(),
+            );
+            continue;
+        }
+
+        let Some(mesh) = meshes.get_mut(trail_mesh) else {
+            continue;
+        };
+
+        // ribbon is turned to face the camera
+        // and gets thinner as it fades
+        let mut positions = Vec::with_capacity(trail.points.len() * 2);
+        let mut colors = Vec::with_capacity(trail.points.len() * 2);
+        let mut indices = Vec::with_capacity(trail.points.len() * 6);
+        for (i, (point, t)) in trail.points.iter().enumerate() {
+            let direction = if i + 1 < trail.points.len() {
+                trail.points[i + 1].0 - *point
+            } else if 0 < i {
+                *point - trail.points[i - 1].0
+            } else {
+                Vec3::ZERO
+            };
+            let fade = 1.0 - (now - t) / TRAIL_LIFESPAN;
+            let side = direction
+                .cross(*point - camera_translation)
+                .normalize_or_zero()
+                * TRAIL_WIDTH
+                * fade;
+
+            positions.push((*point - side).to_array());
+            positions.push((*point + side).to_array());
+            colors.push([1.0, 1.0, 1.0, TRAIL_ALPHA * fade]);
+            colors.push([1.0, 1.0, 1.0, TRAIL_ALPHA * fade]);
+
+            if 0 < i {
+                let i = i as u32 * 2;
+                indices.extend_from_slice(&[i - 2, i - 1, i, i - 1, i + 1, i]);
+            }
+        }
+        let normals = vec![Vec3::Z.to_array(); positions.len()];
+
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+        mesh.set_indices(Some(Indices::U32(indices)));
+    }
+}
@@ -0,0 +1,68 @@
+use bevy::{prelude::*, window::WindowFocused};
+use bevy_kira_audio::{Audio, AudioChannel, AudioControl};
+
+use crate::{
+    announcer::AnnouncerChannel,
+    enemies::EnemyHumChannel,
+    stinger::{StingerChannel, STINGER_MUSIC_DUCK_VOLUME},
+    GameSettings, GlobalState, SfxChannel,
+};
+
+// Part of the volume left while the
+// window is in the background
+const WINDOW_FOCUS_DUCK_VOLUME: f32 = 0.2;
+
+/// Game pauses itself when the window loses focus,
+/// so the player is not killed in the background.
+/// Can be turned off in the options.
+pub struct WindowFocusPlugin;
+
+impl Plugin for WindowFocusPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, window_focus_changed);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn window_focus_changed(
+    audio: Res<Audio>,
+    sfx: Res<AudioChannel<SfxChannel>>,
+    enemy_hum: Res<AudioChannel<EnemyHumChannel>>,
+    stinger: Res<AudioChannel<StingerChannel>>,
+    announcer: Res<AudioChannel<AnnouncerChannel>>,
+    game_settings: Res<GameSettings>,
+    global_state: Res<State<GlobalState>>,
+    mut next_global_state: ResMut<NextState<GlobalState>>,
+    mut window_focused_events: EventReader<WindowFocused>,
+) {
+    let Some(window_focused) = window_focused_events.read().last() else {
+        return;
+    };
+    if !game_settings.auto_pause {
+        return;
+    }
+
+    // cursor is released by the pause itself
+    if !window_focused.focused && *global_state.get() == GlobalState::InGame {
+        next_global_state.set(GlobalState::Paused);
+    }
+
+    let duck = if window_focused.focused {
+        1.0
+    } else {
+        WINDOW_FOCUS_DUCK_VOLUME
+    };
+    // music stays ducked under a stinger that is still playing
+    let music_duck = if stinger.is_playing_sound() {
+        duck * STINGER_MUSIC_DUCK_VOLUME
+    } else {
+        duck
+    };
+    let volume = game_settings.volume * duck;
+    audio.set_volume((game_settings.volume * music_duck) as f64);
+    sfx.set_volume(volume as f64);
+    stinger.set_volume(volume as f64);
+    announcer.set_volume((volume * game_settings.announcer_volume) as f64);
+    // hum instances carry the game volume themselves
+    enemy_hum.set_volume(duck as f64);
+}